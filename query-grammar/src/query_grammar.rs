@@ -10,26 +10,92 @@ parser! {
     where [I: Stream<Item = char>] {
         (
             letter(),
-            many(satisfy(|c: char| c.is_alphanumeric() || c == '_')),
+            many(satisfy(|c: char| c.is_alphanumeric() || c == '_' || c == '.')),
         ).skip(char(':')).map(|(s1, s2): (char, String)| format!("{}{}", s1, s2))
     }
 }
 
+/// The characters that must be escaped with a `\` within a word.
+const SPECIAL_CHARS: [char; 10] = [':', '{', '}', '"', '[', ']', '(', ')', '^', '\\'];
+
+parser! {
+    /// Parses a character of a word, either escaped with a `\` or not.
+    ///
+    /// Returns the character and whether it was escaped.
+    fn word_char[I](is_first: bool)(I) -> (char, bool)
+    where [I: Stream<Item = char>] {
+        let is_first = *is_first;
+        char('\\').with(any()).map(|c| (c, true))
+            .or(satisfy(move |c: char| {
+                let is_separator = c.is_whitespace()
+                    || SPECIAL_CHARS.contains(&c)
+                    || (is_first && ['-', '`'].contains(&c));
+                !is_separator
+            }).map(|c| (c, false)))
+    }
+}
+
+parser! {
+    /// Parses a word, made of its characters and whether each was escaped.
+    fn word_chars[I]()(I) -> Vec<(char, bool)>
+    where [I: Stream<Item = char>] {
+        (word_char(true), many(word_char(false)))
+        .map(|(first, mut others): ((char, bool), Vec<(char, bool)>)| {
+            others.insert(0, first);
+            others
+        })
+        .and_then(|chars: Vec<(char, bool)>| {
+            let word: String = chars.iter().map(|&(c, _)| c).collect();
+            match word.as_str() {
+                "OR" => Err(StreamErrorFor::<I>::unexpected_static_message("OR")),
+                "AND" => Err(StreamErrorFor::<I>::unexpected_static_message("AND")),
+                "NOT" => Err(StreamErrorFor::<I>::unexpected_static_message("NOT")),
+                _ => Ok(chars)
+            }
+        })
+    }
+}
+
 parser! {
     fn word[I]()(I) -> String
     where [I: Stream<Item = char>] {
-        (
-            satisfy(|c: char| !c.is_whitespace() && !['-', '`', ':', '{', '}', '"', '[', ']', '(',')'].contains(&c) ),
-            many(satisfy(|c: char| !c.is_whitespace() && ![':', '{', '}', '"', '[', ']', '(',')'].contains(&c)))
-        )
-        .map(|(s1, s2): (char, String)| format!("{}{}", s1, s2))
-        .and_then(|s: String|
-           match s.as_str() {
-             "OR" => Err(StreamErrorFor::<I>::unexpected_static_message("OR")),
-             "AND" => Err(StreamErrorFor::<I>::unexpected_static_message("AND")),
-             "NOT" => Err(StreamErrorFor::<I>::unexpected_static_message("NOT")),
-             _ => Ok(s)
-           })
+        word_chars().map(|chars: Vec<(char, bool)>| chars.into_iter().map(|(c, _)| c).collect())
+    }
+}
+
+parser! {
+    /// Parses a quoted phrase, in which `"` and `\` must be escaped with a `\`.
+    fn quoted[I]()(I) -> String
+    where [I: Stream<Item = char>] {
+        char('"')
+            .with(many1(char('\\').with(any()).or(satisfy(|c| c != '"' && c != '\\'))))
+            .skip(char('"'))
+    }
+}
+
+parser! {
+    /// Parses the boost of a leaf, e.g. `^2.5`
+    fn boost[I]()(I) -> f32
+    where [I: Stream<Item = char>] {
+        char('^').with((many1(digit()), optional((char('.'), many1(digit())))))
+            .and_then(|(integer, decimals): (String, Option<(char, String)>)| {
+                let boost = match decimals {
+                    Some((_, decimals)) => format!("{}.{}", integer, decimals),
+                    None => integer,
+                };
+                boost.parse::<f32>()
+                    .map_err(|_| StreamErrorFor::<I>::unexpected_static_message("boost"))
+            })
+    }
+}
+
+parser! {
+    /// Parses the slop of a phrase, e.g. `~2`
+    fn slop[I]()(I) -> u32
+    where [I: Stream<Item = char>] {
+        char('~').with(many1(digit()))
+            .and_then(|digits: String| digits.parse::<u32>()
+                .map_err(|_| StreamErrorFor::<I>::unexpected_static_message("slop")))
     }
 }
 
@@ -38,19 +104,32 @@ parser! {
     where [I: Stream<Item = char>]
     {
         let term_val = || {
-            let phrase = char('"').with(many1(satisfy(|c| c != '"'))).skip(char('"'));
-            phrase.or(word())
+            let phrase = (quoted(), optional(slop()))
+                .map(|(phrase, slop): (String, Option<u32>)| (phrase, slop.unwrap_or(0), false));
+            phrase.or(word_chars().map(|mut chars: Vec<(char, bool)>| {
+                // A trailing unescaped `*` turns the word into a prefix, e.g. `foo*`
+                let prefix = chars.len() > 1 && chars.last() == Some(&('*', false));
+                if prefix {
+                    chars.pop();
+                }
+                let word: String = chars.into_iter().map(|(c, _)| c).collect();
+                (word, 0, prefix)
+            }))
         };
-        let term_val_with_field = negative_number().or(term_val());
+        let term_val_with_field = negative_number().map(|number| (number, 0, false)).or(term_val());
         let term_query =
             (field(), term_val_with_field)
-            .map(|(field_name, phrase)| UserInputLiteral {
+            .map(|(field_name, (phrase, slop, prefix))| UserInputLiteral {
                 field_name: Some(field_name),
                 phrase,
+                slop,
+                prefix,
             });
-        let term_default_field = term_val().map(|phrase| UserInputLiteral {
+        let term_default_field = term_val().map(|(phrase, slop, prefix)| UserInputLiteral {
             field_name: None,
             phrase,
+            slop,
+            prefix,
         });
         attempt(term_query)
             .or(term_default_field)
@@ -86,10 +165,14 @@ parser! {
     /// Supports ranges like:
     /// [5 TO 10], {5 TO 10}, [* TO 10], [10 TO *], {10 TO *], >5, <=10
     /// [a TO *], [a TO c], [abc TO bcd}
+    /// ["1985-04-12T23:20:50.52Z" TO *]
     fn range[I]()(I) -> UserInputLeaf
     where [I: Stream<Item = char>] {
         let range_term_val = || {
-            word().or(negative_number()).or(char('*').with(value("*".to_string())))
+            word()
+                .or(negative_number())
+                .or(quoted())
+                .or(char('*').with(value("*".to_string())))
         };
 
         // check for unbounded range in the form of <5, <=10, >5, >=5
@@ -158,13 +241,19 @@ fn must(expr: UserInputAST) -> UserInputAST {
 parser! {
     fn leaf[I]()(I) -> UserInputAST
     where [I: Stream<Item = char>] {
+        let unboosted_leaf =
             char('-').with(leaf()).map(negate)
         .or(char('+').with(leaf()).map(must))
         .or(char('(').with(ast()).skip(char(')')))
         .or(char('*').map(|_| UserInputAST::from(UserInputLeaf::All)))
         .or(attempt(string("NOT").skip(spaces1()).with(leaf()).map(negate)))
         .or(attempt(range().map(UserInputAST::from)))
-        .or(literal().map(UserInputAST::from))
+        .or(literal().map(UserInputAST::from));
+        (unboosted_leaf, optional(boost()))
+            .map(|(leaf, boost_opt): (UserInputAST, Option<f32>)| match boost_opt {
+                Some(boost) => leaf.boost(boost),
+                None => leaf,
+            })
     }
 }
 
@@ -272,6 +361,31 @@ mod test {
         test_parse_query_to_ast_helper("NOT a", "-(\"a\")");
     }
 
+    #[test]
+    fn test_parse_query_to_ast_boost() {
+        test_parse_query_to_ast_helper("a^2", "\"a\"^2");
+        test_parse_query_to_ast_helper("title:foo^2.5 bar", "(title:\"foo\"^2.5 \"bar\")");
+        test_parse_query_to_ast_helper("\"a b\"~1^3", "\"a b\"~1^3");
+        test_parse_query_to_ast_helper("foo*^2", "\"foo\"*^2");
+        test_parse_query_to_ast_helper("(a OR b)^2", "(?(\"a\") ?(\"b\"))^2");
+        test_parse_query_to_ast_helper("+a^2", "+(\"a\"^2)");
+        test_parse_query_to_ast_helper("title:[a TO b]^2", "title:[\"a\" TO \"b\"]^2");
+        test_is_parse_err("a^");
+        test_is_parse_err("a^b");
+    }
+
+    #[test]
+    fn test_parse_query_to_ast_escaping() {
+        test_parse_query_to_ast_helper(r#"a\:b"#, "\"a:b\"");
+        test_parse_query_to_ast_helper(r#"title:a\(b\)"#, "title:\"a(b)\"");
+        test_parse_query_to_ast_helper(r#"\-a"#, "\"-a\"");
+        test_parse_query_to_ast_helper(r#"a\^2"#, "\"a^2\"");
+        test_parse_query_to_ast_helper(r#"a\\b"#, r#""a\b""#);
+        test_parse_query_to_ast_helper(r#"foo\*"#, "\"foo*\"");
+        test_parse_query_to_ast_helper(r#""a \"b\" c""#, r#""a "b" c""#);
+        test_parse_query_to_ast_helper(r#"title:[a\:b TO c]"#, "title:[\"a:b\" TO \"c\"]");
+    }
+
     #[test]
     fn test_parse_query_to_ast_binary_op() {
         test_parse_query_to_ast_helper("a AND b", "(+(\"a\") +(\"b\"))");
@@ -337,6 +451,17 @@ mod test {
         let res4 = range().parse("weight:[71.2 TO *}").unwrap().0;
         assert_eq!(res3, expected_weight);
         assert_eq!(res4, expected_weight);
+
+        let expected_date = UserInputLeaf::Range {
+            field: Some("date".to_string()),
+            lower: UserInputBound::Inclusive("2019-01-01T00:00:00Z".to_string()),
+            upper: UserInputBound::Exclusive("2019-02-01".to_string()),
+        };
+        let res5 = range()
+            .parse("date:[\"2019-01-01T00:00:00Z\" TO 2019-02-01}")
+            .unwrap()
+            .0;
+        assert_eq!(res5, expected_date);
     }
 
     #[test]
@@ -363,11 +488,19 @@ mod test {
         test_parse_query_to_ast_helper("(+a +b)", "(+(\"a\") +(\"b\"))");
         test_parse_query_to_ast_helper("abc:toto", "abc:\"toto\"");
         test_parse_query_to_ast_helper("abc:1.1", "abc:\"1.1\"");
+        test_parse_query_to_ast_helper("attrs.color:red", "attrs.color:\"red\"");
         test_parse_query_to_ast_helper("+abc:toto", "+(abc:\"toto\")");
         test_parse_query_to_ast_helper("(+abc:toto -titi)", "(+(abc:\"toto\") -(\"titi\"))");
         test_parse_query_to_ast_helper("-abc:toto", "-(abc:\"toto\")");
         test_parse_query_to_ast_helper("abc:a b", "(abc:\"a\" \"b\")");
         test_parse_query_to_ast_helper("abc:\"a b\"", "abc:\"a b\"");
+        test_parse_query_to_ast_helper("abc:\"a b\"~2", "abc:\"a b\"~2");
+        test_parse_query_to_ast_helper("\"a b\"~0", "\"a b\"");
+        test_parse_query_to_ast_helper("\"a b\"~1 c", "(\"a b\"~1 \"c\")");
+        test_parse_query_to_ast_helper("a~1", "\"a~1\"");
+        test_parse_query_to_ast_helper("abc*", "\"abc\"*");
+        test_parse_query_to_ast_helper("title:abc* d", "(title:\"abc\"* \"d\")");
+        test_parse_query_to_ast_helper("a*b", "\"a*b\"");
         test_parse_query_to_ast_helper("foo:[1 TO 5]", "foo:[\"1\" TO \"5\"]");
         test_parse_query_to_ast_helper("[1 TO 5]", "[\"1\" TO \"5\"]");
         test_parse_query_to_ast_helper("foo:{a TO z}", "foo:{\"a\" TO \"z\"}");
@@ -40,14 +40,25 @@ impl Debug for UserInputLeaf {
 pub struct UserInputLiteral {
     pub field_name: Option<String>,
     pub phrase: String,
+    /// Slop of the phrase, e.g. `2` for `"a b"~2`
+    pub slop: u32,
+    /// Whether the phrase is a prefix, e.g. `true` for `abc*`
+    pub prefix: bool,
 }
 
 impl fmt::Debug for UserInputLiteral {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match self.field_name {
-            Some(ref field_name) => write!(formatter, "{}:\"{}\"", field_name, self.phrase),
-            None => write!(formatter, "\"{}\"", self.phrase),
+            Some(ref field_name) => write!(formatter, "{}:\"{}\"", field_name, self.phrase)?,
+            None => write!(formatter, "\"{}\"", self.phrase)?,
         }
+        if self.slop > 0 {
+            write!(formatter, "~{}", self.slop)?;
+        }
+        if self.prefix {
+            write!(formatter, "*")?;
+        }
+        Ok(())
     }
 }
 
@@ -88,6 +99,8 @@ pub enum UserInputAST {
     Clause(Vec<UserInputAST>),
     Unary(Occur, Box<UserInputAST>),
     Leaf(Box<UserInputLeaf>),
+    /// Boosted AST, e.g. `title:foo^2`
+    Boost(Box<UserInputAST>, f32),
 }
 
 impl UserInputAST {
@@ -95,6 +108,10 @@ impl UserInputAST {
         UserInputAST::Unary(occur, Box::new(self))
     }
 
+    pub fn boost(self, boost: f32) -> UserInputAST {
+        UserInputAST::Boost(Box::new(self), boost)
+    }
+
     fn compose(occur: Occur, asts: Vec<UserInputAST>) -> UserInputAST {
         assert_ne!(occur, Occur::MustNot);
         assert!(!asts.is_empty());
@@ -154,6 +171,9 @@ impl fmt::Debug for UserInputAST {
                 write!(formatter, "{}({:?})", occur, subquery)
             }
             UserInputAST::Leaf(ref subquery) => write!(formatter, "{:?}", subquery),
+            UserInputAST::Boost(ref subquery, boost) => {
+                write!(formatter, "{:?}^{}", subquery, boost)
+            }
         }
     }
 }
@@ -0,0 +1,409 @@
+/*!
+Export of fast fields as [Apache Arrow](https://arrow.apache.org/) columns.
+
+This module requires the `arrow` feature.
+
+Fast fields are already stored column-wise, which makes it cheap to convert
+them into Arrow arrays. Analytical engines consuming Arrow `RecordBatch`es
+can then scan tantivy segments without requiring a custom reader.
+
+Columns only contain the documents that are not deleted,
+in the order of their `DocId`.
+
+The Arrow type of a column depends on the type of the field:
+
+| Field                          | Arrow type                           |
+|--------------------------------|--------------------------------------|
+| `u64`                          | `UInt64`                             |
+| `i64`                          | `Int64`                              |
+| `f64`                          | `Float64`                            |
+| date                           | `Timestamp(Second, None)`            |
+| multi-valued numeric or date   | `LargeList` of the above             |
+| facet, or text declared `FAST` | `LargeList<UInt64>` of term ordinals |
+| bytes                          | `LargeBinary`                        |
+| multi-valued bytes             | `LargeList<LargeBinary>`             |
+
+Term ordinals are segment local. The terms themselves can be exported
+via [`term_dictionary_to_arrow`](./fn.term_dictionary_to_arrow.html),
+the term of ordinal `ord` being at the position `ord` of the resulting array.
+!*/
+
+use crate::fastfield::{
+    FastFieldNotAvailableError, FastFieldReader, FastValue, MultiValueIntFastFieldReader,
+};
+use crate::schema::{Cardinality, Facet, Field, FieldType};
+use crate::termdict::TermDictionary;
+use crate::DocId;
+use crate::Result;
+use crate::SegmentReader;
+use crate::TantivyError;
+use arrow_array::builder::{LargeBinaryBuilder, LargeListBuilder, PrimitiveBuilder};
+use arrow_array::types::{
+    ArrowPrimitiveType, Float64Type, Int64Type, TimestampSecondType, UInt64Type,
+};
+use arrow_array::{PrimitiveArray, RecordBatchOptions, StringArray};
+use arrow_schema::{Field as ArrowField, Schema as ArrowSchema};
+use std::str;
+use std::sync::Arc;
+
+pub use arrow_array::{ArrayRef, RecordBatch};
+
+fn single_value_column<T>(
+    fast_field_reader: &FastFieldReader<T::Native>,
+    docs: &[DocId],
+) -> ArrayRef
+where
+    T: ArrowPrimitiveType,
+    T::Native: FastValue,
+{
+    let mut vals = vec![T::Native::default(); docs.len()];
+    if docs
+        .iter()
+        .enumerate()
+        .all(|(ord, &doc)| ord as DocId == doc)
+    {
+        fast_field_reader.get_range(0, &mut vals[..]);
+    } else {
+        fast_field_reader.get_batch(docs, &mut vals[..]);
+    }
+    Arc::new(PrimitiveArray::<T>::from_iter_values(vals))
+}
+
+fn multi_value_column<T>(
+    multi_value_reader: &MultiValueIntFastFieldReader<T::Native>,
+    docs: &[DocId],
+) -> ArrayRef
+where
+    T: ArrowPrimitiveType,
+    T::Native: FastValue,
+{
+    let mut list_builder = LargeListBuilder::new(PrimitiveBuilder::<T>::new());
+    let mut vals = Vec::new();
+    for &doc in docs {
+        multi_value_reader.get_vals(doc, &mut vals);
+        list_builder.values().append_slice(&vals[..]);
+        list_builder.append(true);
+    }
+    Arc::new(list_builder.finish())
+}
+
+fn numeric_column<T>(
+    segment_reader: &SegmentReader,
+    field: Field,
+    cardinality: Cardinality,
+    docs: &[DocId],
+    single_value_reader: impl Fn(Field) -> Option<FastFieldReader<T::Native>>,
+    multi_value_reader: impl Fn(Field) -> Option<MultiValueIntFastFieldReader<T::Native>>,
+) -> Result<ArrayRef>
+where
+    T: ArrowPrimitiveType,
+    T::Native: FastValue,
+{
+    let column_opt = match cardinality {
+        Cardinality::SingleValue => single_value_reader(field)
+            .map(|fast_field_reader| single_value_column::<T>(&fast_field_reader, docs)),
+        Cardinality::MultiValues => multi_value_reader(field)
+            .map(|multi_value_reader| multi_value_column::<T>(&multi_value_reader, docs)),
+    };
+    column_opt.ok_or_else(|| not_available(segment_reader, field))
+}
+
+fn not_available(segment_reader: &SegmentReader, field: Field) -> TantivyError {
+    let field_entry = segment_reader.schema().get_field_entry(field);
+    TantivyError::from(FastFieldNotAvailableError::new(field_entry))
+}
+
+fn invalid_term() -> TantivyError {
+    TantivyError::SystemError("Term is not valid utf-8.".to_string())
+}
+
+fn alive_docs(segment_reader: &SegmentReader) -> Vec<DocId> {
+    segment_reader.doc_ids_alive().collect()
+}
+
+fn fast_field_column(
+    segment_reader: &SegmentReader,
+    field: Field,
+    docs: &[DocId],
+) -> Result<ArrayRef> {
+    let fast_fields = segment_reader.fast_fields();
+    let field_entry = segment_reader.schema().get_field_entry(field);
+    let cardinality_opt = match field_entry.field_type() {
+        FieldType::U64(options)
+        | FieldType::I64(options)
+        | FieldType::F64(options)
+        | FieldType::Date(options) => options.get_fastfield_cardinality(),
+        _ => None,
+    };
+    match (field_entry.field_type(), cardinality_opt) {
+        (FieldType::U64(_), Some(cardinality)) => numeric_column::<UInt64Type>(
+            segment_reader,
+            field,
+            cardinality,
+            docs,
+            |field| fast_fields.u64(field),
+            |field| fast_fields.u64s(field),
+        ),
+        (FieldType::I64(_), Some(cardinality)) => numeric_column::<Int64Type>(
+            segment_reader,
+            field,
+            cardinality,
+            docs,
+            |field| fast_fields.i64(field),
+            |field| fast_fields.i64s(field),
+        ),
+        (FieldType::F64(_), Some(cardinality)) => numeric_column::<Float64Type>(
+            segment_reader,
+            field,
+            cardinality,
+            docs,
+            |field| fast_fields.f64(field),
+            |field| fast_fields.f64s(field),
+        ),
+        (FieldType::Date(_), Some(cardinality)) => numeric_column::<TimestampSecondType>(
+            segment_reader,
+            field,
+            cardinality,
+            docs,
+            |field| fast_fields.i64(field),
+            |field| fast_fields.i64s(field),
+        ),
+        (FieldType::HierarchicalFacet, _) | (FieldType::Str(_), _) => fast_fields
+            .u64s(field)
+            .map(|term_ords_reader| multi_value_column::<UInt64Type>(&term_ords_reader, docs))
+            .ok_or_else(|| not_available(segment_reader, field)),
+        (FieldType::Bytes(options), _)
+            if options.get_fastfield_cardinality() == Some(Cardinality::MultiValues) =>
+        {
+            let multi_bytes_reader = fast_fields
+                .multi_bytes(field)
+                .ok_or_else(|| not_available(segment_reader, field))?;
+            let mut list_builder = LargeListBuilder::new(LargeBinaryBuilder::new());
+            let mut vals = Vec::new();
+            for &doc in docs {
+                multi_bytes_reader.get_vals(doc, &mut vals);
+                for val in &vals {
+                    list_builder.values().append_value(val);
+                }
+                list_builder.append(true);
+            }
+            Ok(Arc::new(list_builder.finish()))
+        }
+        (FieldType::Bytes(_), _) => {
+            let bytes_reader = fast_fields
+                .bytes(field)
+                .ok_or_else(|| not_available(segment_reader, field))?;
+            let mut bytes_builder = LargeBinaryBuilder::new();
+            for &doc in docs {
+                bytes_builder.append_value(bytes_reader.get_bytes(doc));
+            }
+            Ok(Arc::new(bytes_builder.finish()))
+        }
+        _ => Err(not_available(segment_reader, field)),
+    }
+}
+
+/// Converts the fast field `field` of a segment into an Arrow array.
+///
+/// The array has one row per document that is not deleted.
+///
+/// Returns a `FastFieldNotAvailable` error if the field is not a fast field.
+pub fn fast_field_to_arrow(segment_reader: &SegmentReader, field: Field) -> Result<ArrayRef> {
+    fast_field_column(segment_reader, field, &alive_docs(segment_reader)[..])
+}
+
+/// Converts the given fast fields of a segment into an Arrow `RecordBatch`.
+///
+/// Columns are named after the fields, and have one row per document
+/// that is not deleted.
+///
+/// Returns a `FastFieldNotAvailable` error if one of the fields is not a fast field.
+pub fn segment_to_record_batch(
+    segment_reader: &SegmentReader,
+    fields: &[Field],
+) -> Result<RecordBatch> {
+    let docs = alive_docs(segment_reader);
+    let schema = segment_reader.schema();
+    let mut arrow_fields = Vec::with_capacity(fields.len());
+    let mut columns = Vec::with_capacity(fields.len());
+    for &field in fields {
+        let column = fast_field_column(segment_reader, field, &docs[..])?;
+        let field_name = schema.get_field_name(field);
+        arrow_fields.push(ArrowField::new(
+            field_name,
+            column.data_type().clone(),
+            false,
+        ));
+        columns.push(column);
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(docs.len()));
+    RecordBatch::try_new_with_options(Arc::new(ArrowSchema::new(arrow_fields)), columns, &options)
+        .map_err(|err| TantivyError::SystemError(err.to_string()))
+}
+
+/// Exports the terms of a facet field, or of a text field declared as fast,
+/// into an Arrow `Utf8` array.
+///
+/// The term of ordinal `ord` is at position `ord` of the array, which makes it
+/// possible to resolve the term ordinals exported by `fast_field_to_arrow`.
+/// Facets are exported in their usual `/parent/child` form.
+pub fn term_dictionary_to_arrow(segment_reader: &SegmentReader, field: Field) -> Result<ArrayRef> {
+    let field_type = segment_reader.schema().get_field_entry(field).field_type();
+    let mut terms: Vec<String> = Vec::new();
+    match *field_type {
+        FieldType::HierarchicalFacet => {
+            let facet_reader = segment_reader
+                .facet_reader(field)
+                .ok_or_else(|| not_available(segment_reader, field))?;
+            let mut stream = facet_reader.facet_dict().stream();
+            while stream.advance() {
+                let facet =
+                    Facet::from_encoded(stream.key().to_vec()).map_err(|_| invalid_term())?;
+                terms.push(facet.to_string());
+            }
+        }
+        _ => {
+            let str_reader = segment_reader
+                .str_fast_field_reader(field)
+                .ok_or_else(|| not_available(segment_reader, field))?;
+            let term_dict: &TermDictionary = str_reader.term_dict();
+            let mut stream = term_dict.stream();
+            while stream.advance() {
+                let term = str::from_utf8(stream.key()).map_err(|_| invalid_term())?;
+                terms.push(term.to_string());
+            }
+        }
+    }
+    Ok(Arc::new(StringArray::from(terms)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fast_field_to_arrow, segment_to_record_batch, term_dictionary_to_arrow};
+    use crate::schema::{Cardinality, Facet, IntOptions, Schema, FAST, STRING};
+    use crate::{DateTime, Index, Term};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, Int64Type, TimestampSecondType, UInt64Type};
+    use arrow_array::Array;
+
+    #[test]
+    fn test_fast_fields_to_record_batch() {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", FAST);
+        let score_field = schema_builder.add_f64_field("score", FAST);
+        let date_field = schema_builder.add_date_field("date", FAST);
+        let tags_field = schema_builder.add_i64_field(
+            "tags",
+            IntOptions::default()
+                .set_fast(Cardinality::MultiValues)
+                .set_indexed(),
+        );
+        let bytes_field = schema_builder.add_bytes_field("bytes", FAST);
+        let city_field = schema_builder.add_text_field("city", STRING | FAST);
+        let category_field = schema_builder.add_facet_field("category");
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let date: DateTime = "2017-07-14T02:40:00+00:00".parse().unwrap();
+        index_writer.add_document(doc!(
+            id_field => 1u64,
+            score_field => 0.5f64,
+            date_field => date,
+            tags_field => -1i64,
+            tags_field => 3i64,
+            bytes_field => vec![1u8, 2u8],
+            city_field => "Tokyo",
+            category_field => Facet::from("/a/b"),
+        ));
+        index_writer.add_document(doc!(
+            id_field => 2u64,
+            score_field => 1.5f64,
+            date_field => date,
+            tags_field => 2i64,
+            bytes_field => vec![],
+            city_field => "Paris",
+            category_field => Facet::from("/a/c"),
+        ));
+        index_writer.add_document(doc!(
+            id_field => 3u64,
+            tags_field => 7i64,
+            city_field => "Lyon",
+        ));
+        index_writer.commit().unwrap();
+        index_writer.delete_term(Term::from_field_i64(tags_field, 2i64));
+        index_writer.commit().unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.num_docs(), 2);
+
+        let record_batch = segment_to_record_batch(
+            segment_reader,
+            &[
+                id_field,
+                score_field,
+                date_field,
+                tags_field,
+                bytes_field,
+                city_field,
+                category_field,
+            ],
+        )
+        .unwrap();
+        assert_eq!(record_batch.num_rows(), 2);
+        assert_eq!(record_batch.num_columns(), 7);
+        assert_eq!(record_batch.schema().field(0).name(), "id");
+
+        let ids = record_batch.column(0).as_primitive::<UInt64Type>();
+        assert_eq!(ids.values().to_vec(), vec![1u64, 3u64]);
+        let scores = record_batch.column(1).as_primitive::<Float64Type>();
+        assert_eq!(scores.values().to_vec(), vec![0.5f64, 0f64]);
+        let dates = record_batch.column(2).as_primitive::<TimestampSecondType>();
+        assert_eq!(dates.value(0), 1_500_000_000i64);
+        let tags = record_batch.column(3).as_list::<i64>();
+        assert_eq!(
+            tags.value(0).as_primitive::<Int64Type>().values().to_vec(),
+            vec![-1i64, 3i64]
+        );
+        assert_eq!(
+            tags.value(1).as_primitive::<Int64Type>().values().to_vec(),
+            vec![7i64]
+        );
+        let bytes = record_batch.column(4).as_binary::<i64>();
+        assert_eq!(bytes.value(0), &[1u8, 2u8]);
+        assert!(bytes.value(1).is_empty());
+
+        let city_ords = record_batch.column(5).as_list::<i64>();
+        let city_terms = term_dictionary_to_arrow(segment_reader, city_field).unwrap();
+        let city_terms = city_terms.as_string::<i32>();
+        let cities: Vec<&str> = (0..city_ords.len())
+            .map(|row| {
+                let ords = city_ords.value(row);
+                let ord = ords.as_primitive::<UInt64Type>().value(0);
+                city_terms.value(ord as usize)
+            })
+            .collect();
+        assert_eq!(cities, vec!["Tokyo", "Lyon"]);
+
+        let category_terms = term_dictionary_to_arrow(segment_reader, category_field).unwrap();
+        let category_terms = category_terms.as_string::<i32>();
+        assert!((0..category_terms.len()).any(|ord| category_terms.value(ord) == "/a/b"));
+        let category_ords = record_batch.column(6).as_list::<i64>();
+        assert_eq!(category_ords.value(1).len(), 0);
+    }
+
+    #[test]
+    fn test_not_a_fast_field() {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(title_field => "hello"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert!(fast_field_to_arrow(segment_reader, title_field).is_err());
+        assert!(term_dictionary_to_arrow(segment_reader, title_field).is_err());
+        let record_batch = segment_to_record_batch(segment_reader, &[]).unwrap();
+        assert_eq!(record_batch.num_rows(), 1);
+    }
+}
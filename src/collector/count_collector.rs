@@ -70,6 +70,10 @@ impl Collector for Count {
     fn merge_fruits(&self, segment_counts: Vec<usize>) -> Result<usize> {
         Ok(segment_counts.into_iter().sum())
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some("Count".to_string())
+    }
 }
 
 #[derive(Default)]
@@ -0,0 +1,241 @@
+use crate::chrono::{TimeZone, Utc};
+use crate::collector::{fast_field_u64_reader, Collector, SegmentCollector};
+use crate::fastfield::{DateTruncation, DateTruncator, FastFieldReader, FastValue};
+use crate::schema::Field;
+use crate::DateTime;
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::SegmentLocalId;
+use crate::SegmentReader;
+use std::collections::{BTreeMap, HashMap};
+
+/// Collector counting the documents per calendar interval (hour, day,
+/// week, month...) of the values of a date fast field.
+///
+/// The intervals follow the calendar of a given timezone: with a day
+/// interval in UTC+2, a bucket starts at midnight in UTC+2, and a month
+/// bucket covers the whole month, whatever its number of days.
+///
+/// The field has to be a single valued date (or `i64` timestamp) fast field.
+///
+/// The fruit lists the start of the buckets containing at least one
+/// document, in chronological order, along with their document count.
+///
+/// ```rust
+/// use tantivy::chrono::{FixedOffset, TimeZone, Utc};
+/// use tantivy::collector::DateHistogramCollector;
+/// use tantivy::fastfield::DateTruncation;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, Result};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let timestamp = schema_builder.add_date_field("timestamp", FAST);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         for &(day, hour) in &[(17, 8), (17, 23), (18, 10)] {
+///             let date = Utc.with_ymd_and_hms(2019, 10, day, hour, 0, 0).unwrap();
+///             index_writer.add_document(doc!(timestamp => date));
+///         }
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     // 2019-10-17 23:00 UTC is already the 18th in UTC+2.
+///     let utc_plus_two = FixedOffset::east_opt(2 * 3_600).unwrap();
+///     let collector = DateHistogramCollector::new(timestamp, DateTruncation::Day, utc_plus_two);
+///     let histogram = searcher.search(&AllQuery, &collector)?;
+///     let day = |day: u32| {
+///         utc_plus_two
+///             .with_ymd_and_hms(2019, 10, day, 0, 0, 0)
+///             .unwrap()
+///             .with_timezone(&Utc)
+///     };
+///     assert_eq!(histogram, vec![(day(17), 1), (day(18), 2)]);
+///     Ok(())
+/// }
+/// ```
+pub struct DateHistogramCollector<Tz: TimeZone = Utc> {
+    field: Field,
+    truncator: DateTruncator<Tz>,
+}
+
+impl DateHistogramCollector<Utc> {
+    /// Creates a collector counting the documents per calendar
+    /// interval of `field`, in UTC.
+    pub fn utc(field: Field, truncation: DateTruncation) -> DateHistogramCollector<Utc> {
+        DateHistogramCollector::new(field, truncation, Utc)
+    }
+}
+
+impl<Tz: TimeZone> DateHistogramCollector<Tz> {
+    /// Creates a collector counting the documents per calendar
+    /// interval of `field`, in the given timezone.
+    pub fn new(
+        field: Field,
+        truncation: DateTruncation,
+        timezone: Tz,
+    ) -> DateHistogramCollector<Tz> {
+        DateHistogramCollector {
+            field,
+            truncator: DateTruncator::new(truncation, timezone),
+        }
+    }
+}
+
+impl<Tz> Collector for DateHistogramCollector<Tz>
+where
+    Tz: TimeZone + Send + Sync + 'static,
+{
+    type Fruit = Vec<(DateTime, u64)>;
+
+    type Child = DateHistogramSegmentCollector<Tz>;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<DateHistogramSegmentCollector<Tz>> {
+        let ff_reader = fast_field_u64_reader::<i64>(reader, self.field)?;
+        Ok(DateHistogramSegmentCollector {
+            ff_reader,
+            truncator: self.truncator.clone(),
+            counts: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_histograms: Vec<Vec<(DateTime, u64)>>,
+    ) -> Result<Vec<(DateTime, u64)>> {
+        let mut counts: BTreeMap<DateTime, u64> = BTreeMap::new();
+        for (bucket_start, count) in segment_histograms.into_iter().flatten() {
+            *counts.entry(bucket_start).or_insert(0) += count;
+        }
+        Ok(counts.into_iter().collect())
+    }
+}
+
+/// Segment Collector associated to the `DateHistogramCollector`.
+pub struct DateHistogramSegmentCollector<Tz: TimeZone> {
+    ff_reader: FastFieldReader<u64>,
+    truncator: DateTruncator<Tz>,
+    counts: HashMap<i64, u64>,
+}
+
+impl<Tz> SegmentCollector for DateHistogramSegmentCollector<Tz>
+where
+    Tz: TimeZone + Send + Sync + 'static,
+{
+    type Fruit = Vec<(DateTime, u64)>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let timestamp = i64::from_u64(self.ff_reader.get(doc));
+        let bucket_start = self.truncator.truncate(timestamp);
+        *self.counts.entry(bucket_start).or_insert(0) += 1;
+    }
+
+    fn harvest(self) -> Vec<(DateTime, u64)> {
+        let mut counts: Vec<(i64, u64)> = self.counts.into_iter().collect();
+        counts.sort_unstable();
+        counts
+            .into_iter()
+            .filter_map(|(bucket_start, count)| {
+                // Buckets out of the range of dates supported by chrono are dropped.
+                let bucket_start = Utc.timestamp_opt(bucket_start, 0).single()?;
+                Some((bucket_start, count))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DateHistogramCollector;
+    use crate::chrono::{FixedOffset, TimeZone, Utc};
+    use crate::fastfield::DateTruncation;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, STORED};
+    use crate::{DateTime, Index, TantivyError};
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32) -> DateTime {
+        Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_date_histogram_collector() {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_date_field("timestamp", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        // One document every 7 hours, from 2019-12-20 to 2020-02-07.
+        let start = utc(2019, 12, 20, 0);
+        for i in 0..170 {
+            let date = Utc
+                .timestamp_opt(start.timestamp() + i * 7 * 3_600, 0)
+                .unwrap();
+            index_writer.add_document(doc!(timestamp => date));
+            if i == 100 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let collector = DateHistogramCollector::utc(timestamp, DateTruncation::Month);
+        let histogram = searcher.search(&AllQuery, &collector).unwrap();
+        // 12 days of december, 31 days of january, and the first week of february.
+        assert_eq!(
+            histogram,
+            vec![
+                (utc(2019, 12, 1, 0), 42),
+                (utc(2020, 1, 1, 0), 106),
+                (utc(2020, 2, 1, 0), 22)
+            ]
+        );
+
+        let collector = DateHistogramCollector::utc(timestamp, DateTruncation::Week);
+        let histogram = searcher.search(&AllQuery, &collector).unwrap();
+        assert_eq!(histogram.len(), 8);
+        // 2019-12-20 is a friday.
+        assert_eq!(histogram[0], (utc(2019, 12, 16, 0), 11));
+        assert_eq!(histogram.iter().map(|&(_, count)| count).sum::<u64>(), 170);
+
+        let utc_minus_five = FixedOffset::west_opt(5 * 3_600).unwrap();
+        let collector =
+            DateHistogramCollector::new(timestamp, DateTruncation::Month, utc_minus_five);
+        let histogram = searcher.search(&AllQuery, &collector).unwrap();
+        assert_eq!(
+            histogram,
+            vec![
+                (utc(2019, 12, 1, 5), 42),
+                (utc(2020, 1, 1, 5), 107),
+                (utc(2020, 2, 1, 5), 21)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_date_histogram_collector_not_fast() {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_date_field("timestamp", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(timestamp => utc(2019, 12, 1, 0)));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let collector = DateHistogramCollector::utc(timestamp, DateTruncation::Day);
+        assert!(matches!(
+            searcher.search(&AllQuery, &collector),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::collector::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{fast_field_u64_reader, Collector, SegmentCollector};
+use crate::fastfield::{FastFieldReader, FastValue};
+use crate::schema::Field;
+use crate::Result;
+use crate::{DocAddress, DocId, Score, SegmentReader};
+use std::marker::PhantomData;
+
+/// Order in which the documents are ranked by a fast field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+    /// The documents with the smallest values come first.
+    Asc,
+    /// The documents with the largest values come first.
+    Desc,
+}
+
+impl Order {
+    /// Maps the `u64` representation of a fast field value to the feature
+    /// of the top collector, which keeps the largest features.
+    ///
+    /// The mapping is its own inverse.
+    fn to_feature(self, val: u64) -> u64 {
+        match self {
+            Order::Asc => !val,
+            Order::Desc => val,
+        }
+    }
+}
+
+/// The top collector ranking the documents by a fast field.
+///
+/// It is created by [`TopDocs::order_by_fast_field`](./struct.TopDocs.html#method.order_by_fast_field).
+pub struct FastFieldTopCollector<TFastValue> {
+    field: Field,
+    order: Order,
+    collector: TopCollector<u64>,
+    _marker: PhantomData<fn() -> TFastValue>,
+}
+
+impl<TFastValue: FastValue> FastFieldTopCollector<TFastValue> {
+    pub(crate) fn new(
+        field: Field,
+        order: Order,
+        limit: usize,
+    ) -> FastFieldTopCollector<TFastValue> {
+        FastFieldTopCollector {
+            field,
+            order,
+            collector: TopCollector::with_limit(limit),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Only collects the documents ranked after a previously returned document,
+    /// given its fast field value and address.
+    ///
+    /// Documents with the same value are ranked by their address.
+    pub fn search_after(
+        self,
+        value: TFastValue,
+        doc_address: DocAddress,
+    ) -> FastFieldTopCollector<TFastValue> {
+        let feature = self.order.to_feature(value.to_u64());
+        FastFieldTopCollector {
+            collector: self.collector.search_after(feature, doc_address),
+            ..self
+        }
+    }
+}
+
+impl<TFastValue> Collector for FastFieldTopCollector<TFastValue>
+where
+    TFastValue: FastValue + 'static,
+{
+    type Fruit = Vec<(TFastValue, DocAddress)>;
+
+    type Child = FastFieldTopSegmentCollector<TFastValue>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: u32,
+        segment_reader: &SegmentReader,
+    ) -> Result<Self::Child> {
+        let ff_reader = fast_field_u64_reader::<TFastValue>(segment_reader, self.field)?;
+        let segment_collector = self
+            .collector
+            .for_segment(segment_local_id, segment_reader)?;
+        Ok(FastFieldTopSegmentCollector {
+            ff_reader,
+            order: self.order,
+            segment_collector,
+            _marker: PhantomData,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> Result<Self::Fruit> {
+        let order = self.order;
+        let segment_features: Vec<Vec<(u64, DocAddress)>> = segment_fruits
+            .into_iter()
+            .map(|segment_fruit| {
+                segment_fruit
+                    .into_iter()
+                    .map(|(val, doc_address)| (order.to_feature(val.to_u64()), doc_address))
+                    .collect()
+            })
+            .collect();
+        let features = self.collector.merge_fruits(segment_features)?;
+        Ok(features
+            .into_iter()
+            .map(|(feature, doc_address)| {
+                (TFastValue::from_u64(order.to_feature(feature)), doc_address)
+            })
+            .collect())
+    }
+}
+
+/// Segment Collector associated to the top collector ranking documents by a fast field.
+pub struct FastFieldTopSegmentCollector<TFastValue> {
+    ff_reader: FastFieldReader<u64>,
+    order: Order,
+    segment_collector: TopSegmentCollector<u64>,
+    _marker: PhantomData<fn() -> TFastValue>,
+}
+
+impl<TFastValue> SegmentCollector for FastFieldTopSegmentCollector<TFastValue>
+where
+    TFastValue: FastValue + 'static,
+{
+    type Fruit = Vec<(TFastValue, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let feature = self.order.to_feature(self.ff_reader.get(doc));
+        self.segment_collector.collect(doc, feature);
+    }
+
+    fn harvest(self) -> Vec<(TFastValue, DocAddress)> {
+        let order = self.order;
+        self.segment_collector
+            .harvest()
+            .into_iter()
+            .map(|(feature, doc_address)| {
+                (TFastValue::from_u64(order.to_feature(feature)), doc_address)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,106 @@
+use crate::collector::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{fast_field_u64_reader, Collector, SegmentCollector};
+use crate::common;
+use crate::fastfield::FastFieldReader;
+use crate::schema::{Field, GeoPoint};
+use crate::Result;
+use crate::{DocAddress, DocId, Score, SegmentReader};
+
+/// Maps a distance to the feature of the top collector, which keeps
+/// the largest features.
+///
+/// The mapping is its own inverse.
+fn to_feature(distance: u64) -> u64 {
+    !distance
+}
+
+/// The top collector ranking the documents by their distance to an origin,
+/// the closest documents first.
+///
+/// It is created by [`TopDocs::order_by_distance`](./struct.TopDocs.html#method.order_by_distance).
+pub struct GeoDistanceTopCollector {
+    field: Field,
+    origin: GeoPoint,
+    collector: TopCollector<u64>,
+}
+
+impl GeoDistanceTopCollector {
+    pub(crate) fn new(field: Field, origin: GeoPoint, limit: usize) -> GeoDistanceTopCollector {
+        GeoDistanceTopCollector {
+            field,
+            origin,
+            collector: TopCollector::with_limit(limit),
+        }
+    }
+}
+
+impl Collector for GeoDistanceTopCollector {
+    type Fruit = Vec<(f64, DocAddress)>;
+
+    type Child = GeoDistanceTopSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: u32,
+        segment_reader: &SegmentReader,
+    ) -> Result<Self::Child> {
+        let ff_reader = fast_field_u64_reader::<GeoPoint>(segment_reader, self.field)?;
+        let segment_collector = self
+            .collector
+            .for_segment(segment_local_id, segment_reader)?;
+        Ok(GeoDistanceTopSegmentCollector {
+            ff_reader,
+            origin: self.origin,
+            segment_collector,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> Result<Self::Fruit> {
+        let segment_features: Vec<Vec<(u64, DocAddress)>> = segment_fruits
+            .into_iter()
+            .map(|segment_fruit| {
+                segment_fruit
+                    .into_iter()
+                    .map(|(distance, doc_address)| {
+                        (to_feature(common::f64_to_u64(distance)), doc_address)
+                    })
+                    .collect()
+            })
+            .collect();
+        let features = self.collector.merge_fruits(segment_features)?;
+        Ok(features
+            .into_iter()
+            .map(|(feature, doc_address)| (common::u64_to_f64(to_feature(feature)), doc_address))
+            .collect())
+    }
+}
+
+/// Segment Collector associated to the top collector ranking documents by distance.
+pub struct GeoDistanceTopSegmentCollector {
+    ff_reader: FastFieldReader<u64>,
+    origin: GeoPoint,
+    segment_collector: TopSegmentCollector<u64>,
+}
+
+impl SegmentCollector for GeoDistanceTopSegmentCollector {
+    type Fruit = Vec<(f64, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let geo_point = GeoPoint::from_u64(self.ff_reader.get(doc));
+        let distance = self.origin.distance(&geo_point);
+        self.segment_collector
+            .collect(doc, to_feature(common::f64_to_u64(distance)));
+    }
+
+    fn harvest(self) -> Vec<(f64, DocAddress)> {
+        self.segment_collector
+            .harvest()
+            .into_iter()
+            .map(|(feature, doc_address)| (common::u64_to_f64(to_feature(feature)), doc_address))
+            .collect()
+    }
+}
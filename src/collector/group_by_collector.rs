@@ -0,0 +1,277 @@
+use crate::collector::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{fast_field_u64_reader, Collector, SegmentCollector};
+use crate::fastfield::{FastFieldReader, FastValue};
+use crate::schema::Field;
+use crate::DocAddress;
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::SegmentLocalId;
+use crate::SegmentReader;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/// Collector grouping the matching documents by the value of a fast field,
+/// and keeping the best groups with their best documents.
+///
+/// This makes it possible to collapse the results, e.g. to only show the
+/// best hit for each `product_id`.
+///
+/// Groups are ranked by the score of their best document. The fruit lists
+/// the `num_groups` best groups, by decreasing score, each with the value
+/// of the field and its `docs_per_group` best documents.
+///
+/// The field has to be a single valued `u64`, `i64`, `f64` or date fast field,
+/// of type `TFastValue`. Date fields are handled as `i64` timestamps.
+///
+/// All of the groups of a segment are kept until the end of the collection,
+/// so the memory usage grows with the number of distinct values among
+/// the matching documents.
+///
+/// ```rust
+/// use tantivy::collector::GroupByCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, Index, Result, Score};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let product_id = schema_builder.add_u64_field("product_id", FAST);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(title => "blue shirt", product_id => 1u64));
+///         index_writer.add_document(doc!(title => "shirt shirt", product_id => 1u64));
+///         index_writer.add_document(doc!(title => "striped shirt, long sleeves", product_id => 2u64));
+///         index_writer.add_document(doc!(title => "pants", product_id => 3u64));
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///     let query = QueryParser::for_index(&index, vec![title]).parse_query("shirt")?;
+///
+///     // The best document of each of the 10 best products.
+///     let collector = GroupByCollector::<u64>::for_field(product_id, 10, 1);
+///     let groups: Vec<(u64, Vec<(Score, DocAddress)>)> = searcher.search(&query, &collector)?;
+///     assert_eq!(groups.len(), 2);
+///     assert_eq!(groups[0].0, 1);
+///     assert_eq!(groups[0].1[0].1, DocAddress(0, 1));
+///     assert_eq!(groups[1].0, 2);
+///     Ok(())
+/// }
+/// ```
+pub struct GroupByCollector<TFastValue> {
+    field: Field,
+    num_groups: usize,
+    doc_collector: TopCollector<Score>,
+    _marker: PhantomData<fn() -> TFastValue>,
+}
+
+impl<TFastValue: FastValue> GroupByCollector<TFastValue> {
+    /// Creates a collector keeping the `num_groups` best groups of documents
+    /// sharing the same value for `field`, and the `docs_per_group` best
+    /// documents of each group.
+    ///
+    /// # Panics
+    /// The method panics if `num_groups` or `docs_per_group` is 0.
+    pub fn for_field(
+        field: Field,
+        num_groups: usize,
+        docs_per_group: usize,
+    ) -> GroupByCollector<TFastValue> {
+        assert!(
+            num_groups > 0,
+            "The number of groups must be strictly greater than 0."
+        );
+        GroupByCollector {
+            field,
+            num_groups,
+            doc_collector: TopCollector::with_limit(docs_per_group),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Compares groups by the score of their best document, and then by
+/// the address of this document, the best group first.
+fn compare_groups(left: &[(Score, DocAddress)], right: &[(Score, DocAddress)]) -> Ordering {
+    let (left_score, left_doc) = left[0];
+    let (right_score, right_doc) = right[0];
+    right_score
+        .partial_cmp(&left_score)
+        .unwrap_or(Ordering::Equal)
+        .then(left_doc.cmp(&right_doc))
+}
+
+impl<TFastValue> Collector for GroupByCollector<TFastValue>
+where
+    TFastValue: FastValue + 'static,
+{
+    type Fruit = Vec<(TFastValue, Vec<(Score, DocAddress)>)>;
+
+    type Child = GroupBySegmentCollector<TFastValue>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<GroupBySegmentCollector<TFastValue>> {
+        let ff_reader = fast_field_u64_reader::<TFastValue>(reader, self.field)?;
+        Ok(GroupBySegmentCollector {
+            ff_reader,
+            segment_local_id,
+            docs_per_group: self.doc_collector.limit(),
+            groups: HashMap::new(),
+            _marker: PhantomData,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_groups: Vec<Vec<(TFastValue, Vec<(Score, DocAddress)>)>>,
+    ) -> Result<Vec<(TFastValue, Vec<(Score, DocAddress)>)>> {
+        let mut groups_docs: HashMap<u64, Vec<Vec<(Score, DocAddress)>>> = HashMap::new();
+        for (value, docs) in segment_groups.into_iter().flatten() {
+            groups_docs.entry(value.to_u64()).or_default().push(docs);
+        }
+        let mut groups = Vec::with_capacity(groups_docs.len());
+        for (value, docs) in groups_docs {
+            let docs = self.doc_collector.merge_fruits(docs)?;
+            groups.push((TFastValue::from_u64(value), docs));
+        }
+        groups.sort_by(|(_, left_docs), (_, right_docs)| compare_groups(left_docs, right_docs));
+        groups.truncate(self.num_groups);
+        Ok(groups)
+    }
+}
+
+/// Segment Collector associated to the `GroupByCollector`.
+pub struct GroupBySegmentCollector<TFastValue> {
+    ff_reader: FastFieldReader<u64>,
+    segment_local_id: SegmentLocalId,
+    docs_per_group: usize,
+    groups: HashMap<u64, TopSegmentCollector<Score>>,
+    _marker: PhantomData<fn() -> TFastValue>,
+}
+
+impl<TFastValue> SegmentCollector for GroupBySegmentCollector<TFastValue>
+where
+    TFastValue: FastValue + 'static,
+{
+    type Fruit = Vec<(TFastValue, Vec<(Score, DocAddress)>)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let value = self.ff_reader.get(doc);
+        let (segment_local_id, docs_per_group) = (self.segment_local_id, self.docs_per_group);
+        self.groups
+            .entry(value)
+            .or_insert_with(|| TopSegmentCollector::new(segment_local_id, docs_per_group))
+            .collect(doc, score);
+    }
+
+    fn harvest(self) -> Vec<(TFastValue, Vec<(Score, DocAddress)>)> {
+        // Every group of the segment is returned: a group that is not among the
+        // best groups of this segment may still be among the best groups overall,
+        // and its documents from this segment among its best documents.
+        self.groups
+            .into_iter()
+            .map(|(value, doc_collector)| (TFastValue::from_u64(value), doc_collector.harvest()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GroupByCollector;
+    use crate::collector::TopDocs;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, FAST, TEXT};
+    use crate::{DocAddress, Index, Score, TantivyError};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_group_by_collector() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let group = schema_builder.add_i64_field("group", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..200i64 {
+            let filler = vec!["filler"; (i % 13) as usize].join(" ");
+            index_writer.add_document(doc!(
+                text => format!("beer {}", filler),
+                group => (i * 7) % 23 - 11,
+            ));
+            if i % 60 == 0 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query = QueryParser::for_index(&index, vec![text])
+            .parse_query("beer")
+            .unwrap();
+
+        // Computes the expected groups from all of the documents.
+        let group_values: Vec<i64> = (0..200i64).map(|i| (i * 7) % 23 - 11).collect();
+        let all_docs = searcher.search(&query, &TopDocs::with_limit(200)).unwrap();
+        let mut expected_groups: BTreeMap<i64, Vec<(Score, DocAddress)>> = BTreeMap::new();
+        for &(score, doc_address) in &all_docs {
+            let segment_reader = searcher.segment_reader(doc_address.segment_ord());
+            let value = segment_reader
+                .fast_fields()
+                .i64(group)
+                .unwrap()
+                .get(doc_address.doc());
+            expected_groups
+                .entry(value)
+                .or_default()
+                .push((score, doc_address));
+        }
+        assert_eq!(expected_groups.len(), 23);
+        assert!(group_values
+            .iter()
+            .all(|value| expected_groups.contains_key(value)));
+
+        let groups = searcher
+            .search(&query, &GroupByCollector::<i64>::for_field(group, 5, 3))
+            .unwrap();
+        assert_eq!(groups.len(), 5);
+        for window in groups.windows(2) {
+            assert!(window[0].1[0].0 >= window[1].1[0].0);
+        }
+        for (value, docs) in &groups {
+            // `TopDocs` ranks the documents the same way.
+            assert_eq!(docs, &expected_groups[value][..3].to_vec());
+        }
+        let mut best_scores: Vec<Score> = expected_groups.values().map(|docs| docs[0].0).collect();
+        best_scores.sort_by(|left, right| right.partial_cmp(left).unwrap());
+        let group_scores: Vec<Score> = groups.iter().map(|(_, docs)| docs[0].0).collect();
+        assert_eq!(group_scores, best_scores[..5].to_vec());
+    }
+
+    #[test]
+    fn test_group_by_collector_wrong_type() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let group = schema_builder.add_u64_field("group", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text => "beer", group => 1u64));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query = QueryParser::for_index(&index, vec![text])
+            .parse_query("beer")
+            .unwrap();
+        assert!(matches!(
+            searcher.search(&query, &GroupByCollector::<f64>::for_field(group, 5, 3)),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+use crate::collector::{Collector, NumericFastFieldReader, SegmentCollector};
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::SegmentLocalId;
+use crate::SegmentReader;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Collector counting the documents per bucket of a fixed interval
+/// of the values of a numeric fast field.
+///
+/// A document whose value is `val` belongs to the bucket whose key is
+/// `offset + interval * ((val - offset) / interval).floor()`.
+///
+/// The field has to be a single valued `u64`, `i64`, `f64` or date fast field.
+/// Values are converted to `f64`, and dates are bucketed by their timestamp,
+/// in seconds.
+///
+/// The fruit lists the key of the buckets containing at least one
+/// document, in increasing order, along with their document count.
+///
+/// ```rust
+/// use tantivy::collector::HistogramCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, Result};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let price = schema_builder.add_u64_field("price", FAST);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         for &doc_price in &[2u64, 8, 13, 42, 47] {
+///             index_writer.add_document(doc!(price => doc_price));
+///         }
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     let histogram = searcher.search(&AllQuery, &HistogramCollector::new(price, 10.0))?;
+///     assert_eq!(histogram, vec![(0.0, 2), (10.0, 1), (40.0, 2)]);
+///     Ok(())
+/// }
+/// ```
+pub struct HistogramCollector {
+    field: Field,
+    interval: f64,
+    offset: f64,
+}
+
+impl HistogramCollector {
+    /// Creates a collector bucketing the values of `field` by `interval`.
+    ///
+    /// # Panics
+    /// The method panics if `interval` is not a strictly positive, finite number.
+    pub fn new(field: Field, interval: f64) -> HistogramCollector {
+        assert!(
+            interval > 0.0 && interval.is_finite(),
+            "The interval of a histogram must be strictly positive and finite."
+        );
+        HistogramCollector {
+            field,
+            interval,
+            offset: 0.0,
+        }
+    }
+
+    /// Shifts the bounds of the buckets by `offset`.
+    ///
+    /// For instance, with an interval of `10` and an offset of `5`,
+    /// the buckets start at `..., -5, 5, 15, ...`.
+    pub fn set_offset(&mut self, offset: f64) {
+        self.offset = offset;
+    }
+}
+
+impl Collector for HistogramCollector {
+    type Fruit = Vec<(f64, u64)>;
+
+    type Child = HistogramSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<HistogramSegmentCollector> {
+        let ff_reader = NumericFastFieldReader::open(reader, self.field)?;
+        Ok(HistogramSegmentCollector {
+            ff_reader,
+            interval: self.interval,
+            offset: self.offset,
+            counts: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_histograms: Vec<Vec<(f64, u64)>>) -> Result<Vec<(f64, u64)>> {
+        let mut buckets: Vec<(f64, u64)> = segment_histograms.into_iter().flatten().collect();
+        buckets.sort_by(|left, right| left.0.partial_cmp(&right.0).unwrap_or(Ordering::Equal));
+        let mut histogram: Vec<(f64, u64)> = Vec::with_capacity(buckets.len());
+        for (key, count) in buckets {
+            match histogram.last_mut() {
+                // Keys are computed the same way in every segment,
+                // so that the keys of a same bucket are exactly equal.
+                Some(last_bucket) if last_bucket.0 == key => last_bucket.1 += count,
+                _ => histogram.push((key, count)),
+            }
+        }
+        Ok(histogram)
+    }
+}
+
+/// Segment Collector associated to the `HistogramCollector`.
+pub struct HistogramSegmentCollector {
+    ff_reader: NumericFastFieldReader,
+    interval: f64,
+    offset: f64,
+    counts: HashMap<i64, u64>,
+}
+
+impl SegmentCollector for HistogramSegmentCollector {
+    type Fruit = Vec<(f64, u64)>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self.ff_reader.get(doc);
+        if val.is_nan() {
+            return;
+        }
+        let bucket = ((val - self.offset) / self.interval).floor() as i64;
+        *self.counts.entry(bucket).or_insert(0) += 1;
+    }
+
+    fn harvest(self) -> Vec<(f64, u64)> {
+        let (offset, interval) = (self.offset, self.interval);
+        let mut counts: Vec<(i64, u64)> = self.counts.into_iter().collect();
+        counts.sort_unstable();
+        counts
+            .into_iter()
+            .map(|(bucket, count)| (offset + bucket as f64 * interval, count))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HistogramCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING, TEXT};
+    use crate::{Index, TantivyError, Term};
+
+    #[test]
+    fn test_histogram_collector() {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING);
+        let temperature = schema_builder.add_i64_field("temperature", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..40i64 {
+            index_writer.add_document(doc!(
+                category => if i % 2 == 0 { "even" } else { "odd" },
+                temperature => i - 12,
+            ));
+            if i == 25 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let histogram_collector = HistogramCollector::new(temperature, 10.0);
+        let histogram = searcher.search(&AllQuery, &histogram_collector).unwrap();
+        assert_eq!(
+            histogram,
+            vec![(-20.0, 2), (-10.0, 10), (0.0, 10), (10.0, 10), (20.0, 8)]
+        );
+
+        let mut histogram_collector = HistogramCollector::new(temperature, 15.0);
+        histogram_collector.set_offset(3.0);
+        let query = TermQuery::new(
+            Term::from_field_text(category, "odd"),
+            IndexRecordOption::Basic,
+        );
+        let histogram = searcher.search(&query, &histogram_collector).unwrap();
+        assert_eq!(histogram, vec![(-12.0, 7), (3.0, 8), (18.0, 5)]);
+    }
+
+    #[test]
+    fn test_histogram_collector_f64() {
+        let mut schema_builder = Schema::builder();
+        let ratio = schema_builder.add_f64_field("ratio", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for &val in &[0.05, 0.1, 0.12, 0.2499, 0.25, 0.99, -0.01] {
+            index_writer.add_document(doc!(ratio => val));
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let histogram = searcher
+            .search(&AllQuery, &HistogramCollector::new(ratio, 0.25))
+            .unwrap();
+        assert_eq!(histogram, vec![(-0.25, 1), (0.0, 4), (0.25, 1), (0.75, 1)]);
+    }
+
+    #[test]
+    fn test_histogram_collector_not_numeric() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(title => "hello"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert!(matches!(
+            searcher.search(&AllQuery, &HistogramCollector::new(title, 1.0)),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_histogram_collector_zero_interval() {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_u64_field("price", FAST);
+        HistogramCollector::new(price, 0.0);
+    }
+}
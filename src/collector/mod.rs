@@ -9,6 +9,11 @@ Your fruit could for instance be :
 - [the count of matching documents](./struct.Count.html)
 - [the top 10 documents, by relevancy or by a fast field](./struct.TopDocs.html)
 - [facet counts](./struct.FacetCollector.html)
+- [document counts per range of a numeric fast field](./struct.RangeFacetCollector.html)
+- [a histogram of a numeric fast field](./struct.HistogramCollector.html)
+- [a histogram of a date fast field, by calendar interval](./struct.DateHistogramCollector.html)
+- [the count, min, max, sum, mean and variance of a numeric fast field](./struct.StatsCollector.html)
+- [the best documents, grouped by the value of a fast field](./struct.GroupByCollector.html)
 
 At one point in your code, you will trigger the actual search operation by calling
 [the `search(...)` method of your `Searcher` object](../struct.Searcher.html#method.search).
@@ -84,24 +89,36 @@ See the `custom_collector` example.
 
 */
 
+use crate::common;
+use crate::fastfield::{FastFieldReader, FastValue};
+use crate::query::Weight;
+use crate::schema::{Cardinality, Field, FieldType};
 use crate::DocId;
 use crate::Result;
 use crate::Score;
 use crate::SegmentLocalId;
 use crate::SegmentReader;
+use crate::TantivyError;
 use downcast_rs::impl_downcast;
+use std::mem;
 
 mod count_collector;
 pub use self::count_collector::Count;
 
 mod multi_collector;
-pub use self::multi_collector::MultiCollector;
+pub use self::multi_collector::{FruitHandle, MultiCollector, MultiFruit};
 
 mod top_collector;
 
 mod top_score_collector;
 pub use self::top_score_collector::TopDocs;
 
+mod fast_field_top_collector;
+pub use self::fast_field_top_collector::{FastFieldTopCollector, Order};
+
+mod geo_distance_top_collector;
+pub use self::geo_distance_top_collector::GeoDistanceTopCollector;
+
 mod custom_score_top_collector;
 pub use self::custom_score_top_collector::{CustomScorer, CustomSegmentScorer};
 
@@ -111,12 +128,163 @@ pub use self::tweak_score_top_collector::{ScoreSegmentTweaker, ScoreTweaker};
 mod facet_collector;
 pub use self::facet_collector::FacetCollector;
 
+mod range_facet_collector;
+pub use self::range_facet_collector::{RangeFacetCollector, RangeFacetCounts};
+
+mod histogram_collector;
+pub use self::histogram_collector::HistogramCollector;
+
+mod date_histogram_collector;
+pub use self::date_histogram_collector::DateHistogramCollector;
+
+mod stats_collector;
+pub use self::stats_collector::{Stats, StatsCollector};
+
+mod group_by_collector;
+pub use self::group_by_collector::GroupByCollector;
+
+/// Returns the reader of `field`, a single valued fast field of type `TFastValue`.
+///
+/// The values are mapped to `u64` with the monotonic mapping of `TFastValue`,
+/// so that they can be compared without being converted back.
+pub(crate) fn fast_field_u64_reader<TFastValue: FastValue>(
+    segment_reader: &SegmentReader,
+    field: Field,
+) -> Result<FastFieldReader<u64>> {
+    let schema = segment_reader.schema();
+    let field_entry = schema
+        .fields()
+        .get(field.0 as usize)
+        .ok_or_else(|| TantivyError::SchemaError(format!("Field {:?} does not exist", field)))?;
+    if TFastValue::fast_field_cardinality(field_entry.field_type())
+        != Some(Cardinality::SingleValue)
+    {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not a single valued fast field of the requested type",
+            field_entry.name()
+        )));
+    }
+    segment_reader
+        .fast_fields()
+        .u64_lenient(field)
+        .ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field {:?} has no fast field reader",
+                field_entry.name()
+            ))
+        })
+}
+
+/// Reader of a single valued numeric fast field, whatever its type,
+/// returning its values as `f64`.
+pub(crate) struct NumericFastFieldReader {
+    ff_reader: FastFieldReader<u64>,
+    to_f64: fn(u64) -> f64,
+}
+
+impl NumericFastFieldReader {
+    /// Returns an error if `field` is not a single valued `u64`, `i64`, `f64`
+    /// or date fast field.
+    pub fn open(segment_reader: &SegmentReader, field: Field) -> Result<NumericFastFieldReader> {
+        let field_entry = segment_reader.schema().get_field_entry(field);
+        let (options, to_f64): (_, fn(u64) -> f64) = match *field_entry.field_type() {
+            FieldType::U64(ref options) => (options, |val| val as f64),
+            FieldType::I64(ref options) | FieldType::Date(ref options) => {
+                (options, |val| common::u64_to_i64(val) as f64)
+            }
+            FieldType::F64(ref options) => (options, common::u64_to_f64),
+            _ => {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is not a numeric field",
+                    field_entry.name()
+                )));
+            }
+        };
+        if options.get_fastfield_cardinality() != Some(Cardinality::SingleValue) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a single valued fast field",
+                field_entry.name()
+            )));
+        }
+        let ff_reader = segment_reader
+            .fast_fields()
+            .u64_lenient(field)
+            .ok_or_else(|| {
+                TantivyError::SchemaError(format!(
+                    "Field {:?} has no fast field reader",
+                    field_entry.name()
+                ))
+            })?;
+        Ok(NumericFastFieldReader { ff_reader, to_f64 })
+    }
+
+    #[inline(always)]
+    pub fn get(&self, doc: DocId) -> f64 {
+        (self.to_f64)(self.ff_reader.get(doc))
+    }
+}
+
 /// `Fruit` is the type for the result of our collection.
 /// e.g. `usize` for the `Count` collector.
 pub trait Fruit: Send + downcast_rs::Downcast {}
 
 impl<T> Fruit for T where T: Send + downcast_rs::Downcast {}
 
+/// Approximate size of a `Fruit` in memory.
+///
+/// It bounds the memory used by the results cached by `Searcher::search_cached`.
+pub trait FruitSize {
+    /// Returns the approximate number of bytes taken by the fruit.
+    fn num_bytes(&self) -> usize;
+}
+
+macro_rules! impl_fruit_size_for_copy_types {
+    ($($fruit_type:ty),*) => {
+        $(
+            impl FruitSize for $fruit_type {
+                fn num_bytes(&self) -> usize {
+                    mem::size_of::<$fruit_type>()
+                }
+            }
+        )*
+    };
+}
+
+impl_fruit_size_for_copy_types!(usize, u64, i64, f64);
+
+/// The elements are only accounted for by their inline size.
+impl<T> FruitSize for Vec<T> {
+    fn num_bytes(&self) -> usize {
+        mem::size_of::<Vec<T>>() + self.capacity() * mem::size_of::<T>()
+    }
+}
+
+impl<T: FruitSize> FruitSize for Option<T> {
+    fn num_bytes(&self) -> usize {
+        mem::size_of::<Option<T>>() + self.as_ref().map_or(0, T::num_bytes)
+    }
+}
+
+impl<Left: FruitSize, Right: FruitSize> FruitSize for (Left, Right) {
+    fn num_bytes(&self) -> usize {
+        self.0.num_bytes() + self.1.num_bytes()
+    }
+}
+
+impl<One: FruitSize, Two: FruitSize, Three: FruitSize> FruitSize for (One, Two, Three) {
+    fn num_bytes(&self) -> usize {
+        self.0.num_bytes() + self.1.num_bytes() + self.2.num_bytes()
+    }
+}
+
+impl<One: FruitSize, Two: FruitSize, Three: FruitSize, Four: FruitSize> FruitSize
+    for (One, Two, Three, Four)
+{
+    fn num_bytes(&self) -> usize {
+        self.0.num_bytes() + self.1.num_bytes() + self.2.num_bytes() + self.3.num_bytes()
+    }
+}
+
 /// Collectors are in charge of collecting and retaining relevant
 /// information from the document found and scored by the query.
 ///
@@ -155,6 +323,43 @@ pub trait Collector: Sync {
     /// Combines the fruit associated to the collection of each segments
     /// into one fruit.
     fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> Result<Self::Fruit>;
+
+    /// Returns a key identifying the parameters of the collector, used to cache
+    /// its results (see `Searcher::search_cached`).
+    ///
+    /// Two collectors of the same type with the same key must return the same
+    /// fruit for the same documents. Collectors that cannot be identified this
+    /// way, for instance because they wrap a closure, return `None` (the default)
+    /// and are never cached.
+    fn cache_key(&self) -> Option<String> {
+        None
+    }
+
+    /// Collects the documents of the segment matching the `weight`.
+    ///
+    /// The default implementation pushes all of the alive documents matching the
+    /// weight to the `SegmentCollector` returned by `for_segment`. Collectors
+    /// that are only interested in some of the documents can override it
+    /// to skip the others, e.g. with `Weight::for_each_pruning`.
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_local_id: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> Result<Self::Fruit> {
+        let mut scorer = weight.scorer(segment)?;
+        let mut segment_collector = self.for_segment(segment_local_id, segment)?;
+        if let Some(delete_bitset) = segment.delete_bitset() {
+            scorer.for_each(&mut |doc, score| {
+                if delete_bitset.is_alive(doc) {
+                    segment_collector.collect(doc, score);
+                }
+            });
+        } else {
+            scorer.for_each(&mut |doc, score| segment_collector.collect(doc, score));
+        }
+        Ok(segment_collector.harvest())
+    }
 }
 
 /// The `SegmentCollector` is the trait in charge of defining the
@@ -210,6 +415,14 @@ where
             self.1.merge_fruits(right_fruits)?,
         ))
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "({}, {})",
+            self.0.cache_key()?,
+            self.1.cache_key()?
+        ))
+    }
 }
 
 impl<Left, Right> SegmentCollector for (Left, Right)
@@ -266,6 +479,15 @@ where
             self.2.merge_fruits(three_fruits)?,
         ))
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "({}, {}, {})",
+            self.0.cache_key()?,
+            self.1.cache_key()?,
+            self.2.cache_key()?
+        ))
+    }
 }
 
 impl<One, Two, Three> SegmentCollector for (One, Two, Three)
@@ -332,6 +554,16 @@ where
             self.3.merge_fruits(four_fruits)?,
         ))
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "({}, {}, {}, {})",
+            self.0.cache_key()?,
+            self.1.cache_key()?,
+            self.2.cache_key()?,
+            self.3.cache_key()?
+        ))
+    }
 }
 
 impl<One, Two, Three, Four> SegmentCollector for (One, Two, Three, Four)
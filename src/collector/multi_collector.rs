@@ -10,6 +10,8 @@ use crate::TantivyError;
 use std::marker::PhantomData;
 use std::ops::Deref;
 
+/// Fruits of the collectors of a `MultiCollector`, extracted with their
+/// [`FruitHandle`](./struct.FruitHandle.html).
 pub struct MultiFruit {
     sub_fruits: Vec<Option<Box<dyn Fruit>>>,
 }
@@ -81,14 +83,50 @@ impl<TSegmentCollector: SegmentCollector> BoxableSegmentCollector
     }
 }
 
+/// Typed handle to the fruit of a collector registered in a `MultiCollector`.
+///
+/// Handles are returned by [`MultiCollector::add_collector`](./struct.MultiCollector.html#method.add_collector)
+/// and are `Copy`, so that the same `MultiCollector` can be used for several searches.
 pub struct FruitHandle<TFruit: Fruit> {
     pos: usize,
-    _phantom: PhantomData<TFruit>,
+    _phantom: PhantomData<fn() -> TFruit>,
 }
 
+impl<TFruit: Fruit> Clone for FruitHandle<TFruit> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<TFruit: Fruit> Copy for FruitHandle<TFruit> {}
+
 impl<TFruit: Fruit> FruitHandle<TFruit> {
+    /// Returns a reference to the fruit of the collector.
+    ///
+    /// # Panics
+    /// The method panics if the fruit was already extracted, or if the `MultiFruit`
+    /// was not produced by the `MultiCollector` this handle was obtained from.
+    pub fn get<'a>(&self, fruits: &'a MultiFruit) -> &'a TFruit {
+        fruits
+            .sub_fruits
+            .get(self.pos)
+            .and_then(Option::as_ref)
+            .expect("The fruit was already extracted from this MultiFruit.")
+            .downcast_ref::<TFruit>()
+            .expect("Failed to downcast collector fruit.")
+    }
+
+    /// Takes the fruit of the collector out of the `MultiFruit`.
+    ///
+    /// # Panics
+    /// The method panics if the fruit was already extracted, or if the `MultiFruit`
+    /// was not produced by the `MultiCollector` this handle was obtained from.
     pub fn extract(self, fruits: &mut MultiFruit) -> TFruit {
-        let boxed_fruit = fruits.sub_fruits[self.pos].take().expect("");
+        let boxed_fruit = fruits
+            .sub_fruits
+            .get_mut(self.pos)
+            .and_then(Option::take)
+            .expect("The fruit was already extracted from this MultiFruit.");
         *boxed_fruit
             .downcast::<TFruit>()
             .map_err(|_| ())
@@ -100,6 +138,11 @@ impl<TFruit: Fruit> FruitHandle<TFruit> {
 /// It should only be used for use cases where the Collector types is unknown
 /// at compile time.
 ///
+/// Any number of collectors of any type can be registered, and the query is
+/// only run once. Each call to `add_collector` returns a typed
+/// [`FruitHandle`](./struct.FruitHandle.html), used to get the fruit of the
+/// collector back from the resulting `MultiFruit`.
+///
 /// If the type of the collectors is known, you can just group yours collectors
 /// in a tuple. See the
 /// [Combining several collectors section of the collector documentation](./index.html#combining-several-collectors).
@@ -256,10 +299,10 @@ impl SegmentCollector for MultiCollectorChild {
 mod tests {
 
     use super::*;
-    use crate::collector::{Count, TopDocs};
+    use crate::collector::{Count, FacetCollector, TopDocs};
     use crate::query::TermQuery;
     use crate::schema::IndexRecordOption;
-    use crate::schema::{Schema, TEXT};
+    use crate::schema::{Facet, Schema, TEXT};
     use crate::Index;
     use crate::Term;
 
@@ -293,4 +336,65 @@ mod tests {
         assert_eq!(count_handler.extract(&mut multifruits), 5);
         assert_eq!(topdocs_handler.extract(&mut multifruits).len(), 2);
     }
+
+    #[test]
+    fn test_multi_collector_reuse_handles() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let category = schema_builder.add_facet_field("category");
+        let schema = schema_builder.build();
+
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text=>"abc", category=>Facet::from("/cat/a")));
+            index_writer.add_document(doc!(text=>"abc def", category=>Facet::from("/cat/b")));
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text=>"def", category=>Facet::from("/cat/a")));
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.reader().unwrap().searcher();
+
+        let mut facet_collector = FacetCollector::for_field(category);
+        facet_collector.add_facet("/cat");
+        let mut collectors = MultiCollector::new();
+        let topdocs_handle = collectors.add_collector(TopDocs::with_limit(10));
+        let facet_handle = collectors.add_collector(facet_collector);
+        let count_handle = collectors.add_collector(Count);
+
+        for &(word, expected_count) in &[("abc", 2), ("def", 2)] {
+            let query = TermQuery::new(Term::from_field_text(text, word), IndexRecordOption::Basic);
+            let mut multifruit = searcher.search(&query, &collectors).unwrap();
+            assert_eq!(*count_handle.get(&multifruit), expected_count);
+            assert_eq!(count_handle.extract(&mut multifruit), expected_count);
+            let facet_counts = facet_handle.extract(&mut multifruit);
+            let facets: Vec<(String, u64)> = facet_counts
+                .get("/cat")
+                .map(|(facet, count)| (facet.to_string(), count))
+                .collect();
+            assert_eq!(
+                facets,
+                vec![("/cat/a".to_string(), 1), ("/cat/b".to_string(), 1)]
+            );
+            assert_eq!(
+                topdocs_handle.extract(&mut multifruit).len(),
+                expected_count
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The fruit was already extracted from this MultiFruit.")]
+    fn test_multi_collector_extract_twice() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader().unwrap().searcher();
+        let mut collectors = MultiCollector::new();
+        let count_handle = collectors.add_collector(Count);
+        let query = TermQuery::new(Term::from_field_text(text, "abc"), IndexRecordOption::Basic);
+        let mut multifruit = searcher.search(&query, &collectors).unwrap();
+        count_handle.extract(&mut multifruit);
+        count_handle.extract(&mut multifruit);
+    }
 }
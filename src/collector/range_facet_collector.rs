@@ -0,0 +1,251 @@
+use crate::collector::{fast_field_u64_reader, Collector, SegmentCollector};
+use crate::fastfield::{FastFieldReader, FastValue};
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::SegmentLocalId;
+use crate::SegmentReader;
+use std::collections::Bound;
+use std::ops::RangeBounds;
+
+fn map_bound<TFrom, TTo>(bound: &Bound<TFrom>, transform: impl Fn(&TFrom) -> TTo) -> Bound<TTo> {
+    match *bound {
+        Bound::Included(ref from_val) => Bound::Included(transform(from_val)),
+        Bound::Excluded(ref from_val) => Bound::Excluded(transform(from_val)),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Collector counting the documents whose value for a numeric fast field
+/// falls into each of a list of ranges.
+///
+/// The field has to be a single valued `u64`, `i64` or `f64` fast field,
+/// of type `TFastValue`. Date fields are handled as `i64` timestamps.
+///
+/// Ranges may overlap, or leave gaps: a document is counted once in each of the
+/// ranges containing its value.
+///
+/// ```rust
+/// use tantivy::collector::RangeFacetCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, Index, Result};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let price = schema_builder.add_u64_field("price", FAST);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(title => "Pen", price => 2u64));
+///         index_writer.add_document(doc!(title => "Notebook", price => 10u64));
+///         index_writer.add_document(doc!(title => "Backpack", price => 45u64));
+///         index_writer.add_document(doc!(title => "Desk", price => 250u64));
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     let mut range_facet_collector = RangeFacetCollector::<u64>::for_field(price);
+///     range_facet_collector.add_range(0..10);
+///     range_facet_collector.add_range(10..100);
+///     range_facet_collector.add_range(100..);
+///     let range_facet_counts = searcher.search(&AllQuery, &range_facet_collector)?;
+///
+///     // The counts are returned in the order the ranges were added.
+///     let counts: Vec<u64> = range_facet_counts.iter().map(|(_, count)| count).collect();
+///     assert_eq!(counts, vec![1, 2, 1]);
+///     Ok(())
+/// }
+/// ```
+pub struct RangeFacetCollector<TFastValue> {
+    field: Field,
+    ranges: Vec<(Bound<TFastValue>, Bound<TFastValue>)>,
+    u64_ranges: Vec<(Bound<u64>, Bound<u64>)>,
+}
+
+impl<TFastValue: FastValue> RangeFacetCollector<TFastValue> {
+    /// Creates a collector counting the documents for the ranges of
+    /// values of the given field.
+    ///
+    /// Ranges are then added with [`add_range`](#method.add_range).
+    pub fn for_field(field: Field) -> RangeFacetCollector<TFastValue> {
+        RangeFacetCollector {
+            field,
+            ranges: Vec::new(),
+            u64_ranges: Vec::new(),
+        }
+    }
+
+    /// Adds a range of values, e.g. `10..100` or `100..`.
+    pub fn add_range<TRange: RangeBounds<TFastValue>>(&mut self, range: TRange) {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        self.u64_ranges.push((
+            map_bound(&range.0, FastValue::to_u64),
+            map_bound(&range.1, FastValue::to_u64),
+        ));
+        self.ranges.push(range);
+    }
+}
+
+impl<TFastValue> Collector for RangeFacetCollector<TFastValue>
+where
+    TFastValue: FastValue + 'static,
+{
+    type Fruit = RangeFacetCounts<TFastValue>;
+
+    type Child = RangeFacetSegmentCollector<TFastValue>;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<RangeFacetSegmentCollector<TFastValue>> {
+        let ff_reader = fast_field_u64_reader::<TFastValue>(reader, self.field)?;
+        Ok(RangeFacetSegmentCollector {
+            ff_reader,
+            u64_ranges: self.u64_ranges.clone(),
+            counts: RangeFacetCounts {
+                ranges: self.ranges.clone(),
+                counts: vec![0; self.ranges.len()],
+            },
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_counts: Vec<RangeFacetCounts<TFastValue>>,
+    ) -> Result<RangeFacetCounts<TFastValue>> {
+        let mut counts = vec![0; self.ranges.len()];
+        for segment_count in segment_counts {
+            for (count, segment_count) in counts.iter_mut().zip(segment_count.counts) {
+                *count += segment_count;
+            }
+        }
+        Ok(RangeFacetCounts {
+            ranges: self.ranges.clone(),
+            counts,
+        })
+    }
+}
+
+/// Segment Collector associated to the `RangeFacetCollector`.
+pub struct RangeFacetSegmentCollector<TFastValue> {
+    ff_reader: FastFieldReader<u64>,
+    u64_ranges: Vec<(Bound<u64>, Bound<u64>)>,
+    counts: RangeFacetCounts<TFastValue>,
+}
+
+impl<TFastValue> SegmentCollector for RangeFacetSegmentCollector<TFastValue>
+where
+    TFastValue: FastValue + 'static,
+{
+    type Fruit = RangeFacetCounts<TFastValue>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self.ff_reader.get(doc);
+        for (range, count) in self.u64_ranges.iter().zip(self.counts.counts.iter_mut()) {
+            if range.contains(&val) {
+                *count += 1;
+            }
+        }
+    }
+
+    fn harvest(self) -> RangeFacetCounts<TFastValue> {
+        self.counts
+    }
+}
+
+/// Document counts of the ranges of a `RangeFacetCollector`.
+pub struct RangeFacetCounts<TFastValue> {
+    ranges: Vec<(Bound<TFastValue>, Bound<TFastValue>)>,
+    counts: Vec<u64>,
+}
+
+impl<TFastValue> RangeFacetCounts<TFastValue> {
+    /// Returns the number of documents in the `range_ord`-th range added
+    /// to the collector.
+    ///
+    /// # Panics
+    /// The method panics if less than `range_ord + 1` ranges were added.
+    pub fn get(&self, range_ord: usize) -> u64 {
+        self.counts[range_ord]
+    }
+
+    /// Iterates over the ranges and their document counts, in the
+    /// order the ranges were added to the collector.
+    pub fn iter(&self) -> impl Iterator<Item = (&(Bound<TFastValue>, Bound<TFastValue>), u64)> {
+        self.ranges.iter().zip(self.counts.iter().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeFacetCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{Index, TantivyError, Term};
+    use std::collections::Bound;
+
+    #[test]
+    fn test_range_facet_collector() {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING);
+        let temperature = schema_builder.add_f64_field("temperature", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..100 {
+            index_writer.add_document(doc!(
+                category => if i % 2 == 0 { "even" } else { "odd" },
+                temperature => f64::from(i) / 2.0 - 10.0,
+            ));
+            if i == 60 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let mut range_facet_collector = RangeFacetCollector::for_field(temperature);
+        range_facet_collector.add_range(..0.0);
+        range_facet_collector.add_range(0.0..=10.0);
+        range_facet_collector.add_range(-5.5..5.5);
+        range_facet_collector.add_range(30.0..);
+        let range_facet_counts = searcher.search(&AllQuery, &range_facet_collector).unwrap();
+        let counts: Vec<u64> = range_facet_counts.iter().map(|(_, count)| count).collect();
+        assert_eq!(counts, vec![20, 21, 22, 20]);
+        assert_eq!(range_facet_counts.get(1), 21);
+        let (range, _) = range_facet_counts.iter().nth(1).unwrap();
+        assert_eq!(range, &(Bound::Included(0.0), Bound::Included(10.0)));
+
+        let query = TermQuery::new(
+            Term::from_field_text(category, "even"),
+            IndexRecordOption::Basic,
+        );
+        let range_facet_counts = searcher.search(&query, &range_facet_collector).unwrap();
+        assert_eq!(range_facet_counts.get(1), 11);
+    }
+
+    #[test]
+    fn test_range_facet_collector_wrong_type() {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_u64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(price => 3u64));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let mut range_facet_collector = RangeFacetCollector::for_field(price);
+        range_facet_collector.add_range(0i64..10i64);
+        assert!(matches!(
+            searcher.search(&AllQuery, &range_facet_collector),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::collector::{Collector, FruitSize, NumericFastFieldReader, SegmentCollector};
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::SegmentLocalId;
+use crate::SegmentReader;
+use std::mem;
+
+/// Statistics over the values of a numeric fast field, computed by the
+/// [`StatsCollector`](./struct.StatsCollector.html).
+///
+/// The variance is computed with Welford's algorithm, and the statistics
+/// of the segments are merged with the parallel variant of the algorithm,
+/// which is more accurate than summing the squares of the values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    count: u64,
+    min: f64,
+    max: f64,
+    sum: f64,
+    mean: f64,
+    // Sum of the squared differences to the mean.
+    m2: f64,
+}
+
+impl Default for Stats {
+    fn default() -> Stats {
+        Stats {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+}
+
+impl FruitSize for Stats {
+    fn num_bytes(&self) -> usize {
+        mem::size_of::<Stats>()
+    }
+}
+
+impl Stats {
+    /// Returns the number of values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the smallest value, or `None` if there are no values.
+    pub fn min(&self) -> Option<f64> {
+        self.if_not_empty(self.min)
+    }
+
+    /// Returns the largest value, or `None` if there are no values.
+    pub fn max(&self) -> Option<f64> {
+        self.if_not_empty(self.max)
+    }
+
+    /// Returns the sum of the values.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Returns the mean of the values, or `None` if there are no values.
+    pub fn mean(&self) -> Option<f64> {
+        self.if_not_empty(self.mean)
+    }
+
+    /// Returns the population variance of the values,
+    /// or `None` if there are no values.
+    pub fn variance(&self) -> Option<f64> {
+        self.if_not_empty(self.m2 / self.count as f64)
+    }
+
+    /// Returns the population standard deviation of the values,
+    /// or `None` if there are no values.
+    pub fn std_deviation(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    fn if_not_empty(&self, val: f64) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(val)
+        }
+    }
+
+    fn add(&mut self, val: f64) {
+        self.count += 1;
+        self.min = self.min.min(val);
+        self.max = self.max.max(val);
+        self.sum += val;
+        let delta = val - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (val - self.mean);
+    }
+
+    fn merge(&mut self, other: &Stats) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 +=
+            other.m2 + delta * delta * (self.count as f64 * other.count as f64 / count as f64);
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+    }
+}
+
+/// Collector computing the count, min, max, sum, mean and variance of the
+/// values of a numeric fast field, in a single pass.
+///
+/// The field has to be a single valued `u64`, `i64`, `f64` or date fast field.
+/// Values are converted to `f64`, and `NaN` values are ignored.
+///
+/// Like any collector, it can be combined with other collectors in a tuple,
+/// or with the [`MultiCollector`](./struct.MultiCollector.html).
+///
+/// ```rust
+/// use tantivy::collector::{Count, StatsCollector};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, Index, Result};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let price = schema_builder.add_f64_field("price", FAST);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(title => "Red pen", price => 2.0));
+///         index_writer.add_document(doc!(title => "Blue pen", price => 4.0));
+///         index_writer.add_document(doc!(title => "Notebook", price => 9.0));
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///     let query = QueryParser::for_index(&index, vec![title]).parse_query("pen")?;
+///
+///     let (count, stats) = searcher.search(&query, &(Count, StatsCollector::for_field(price)))?;
+///     assert_eq!(count, 2);
+///     assert_eq!(stats.min(), Some(2.0));
+///     assert_eq!(stats.max(), Some(4.0));
+///     assert_eq!(stats.mean(), Some(3.0));
+///     assert_eq!(stats.variance(), Some(1.0));
+///     Ok(())
+/// }
+/// ```
+pub struct StatsCollector {
+    field: Field,
+}
+
+impl StatsCollector {
+    /// Creates a collector computing statistics over the values of `field`.
+    pub fn for_field(field: Field) -> StatsCollector {
+        StatsCollector { field }
+    }
+}
+
+impl Collector for StatsCollector {
+    type Fruit = Stats;
+
+    type Child = StatsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<StatsSegmentCollector> {
+        let ff_reader = NumericFastFieldReader::open(reader, self.field)?;
+        Ok(StatsSegmentCollector {
+            ff_reader,
+            stats: Stats::default(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_stats: Vec<Stats>) -> Result<Stats> {
+        let mut stats = Stats::default();
+        for segment_stat in &segment_stats {
+            stats.merge(segment_stat);
+        }
+        Ok(stats)
+    }
+}
+
+/// Segment Collector associated to the `StatsCollector`.
+pub struct StatsSegmentCollector {
+    ff_reader: NumericFastFieldReader,
+    stats: Stats,
+}
+
+impl SegmentCollector for StatsSegmentCollector {
+    type Fruit = Stats;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self.ff_reader.get(doc);
+        if !val.is_nan() {
+            self.stats.add(val);
+        }
+    }
+
+    fn harvest(self) -> Stats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stats, StatsCollector};
+    use crate::collector::{Count, MultiCollector};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::tests::assert_nearly_equals;
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_stats_collector() {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING);
+        let size = schema_builder.add_i64_field("size", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let sizes: Vec<i64> = (0..300).map(|i| (i * 37) % 101 - 50).collect();
+        for (i, &val) in sizes.iter().enumerate() {
+            index_writer.add_document(doc!(
+                category => if val > 0 { "positive" } else { "other" },
+                size => val,
+            ));
+            if i % 70 == 0 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert!(searcher.segment_readers().len() > 1);
+
+        let stats = searcher
+            .search(&AllQuery, &StatsCollector::for_field(size))
+            .unwrap();
+        let count = sizes.len() as f64;
+        let mean = sizes.iter().sum::<i64>() as f64 / count;
+        let variance = sizes
+            .iter()
+            .map(|&val| (val as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        assert_eq!(stats.count(), 300);
+        assert_eq!(stats.min(), Some(-50.0));
+        assert_eq!(stats.max(), Some(50.0));
+        assert_eq!(stats.sum(), sizes.iter().sum::<i64>() as f64);
+        assert_nearly_equals(stats.mean().unwrap() as f32, mean as f32);
+        assert_nearly_equals(stats.variance().unwrap() as f32, variance as f32);
+        assert_nearly_equals(
+            stats.std_deviation().unwrap() as f32,
+            variance.sqrt() as f32,
+        );
+
+        let mut multi_collector = MultiCollector::new();
+        let count_handle = multi_collector.add_collector(Count);
+        let stats_handle = multi_collector.add_collector(StatsCollector::for_field(size));
+        let query = TermQuery::new(
+            Term::from_field_text(category, "positive"),
+            IndexRecordOption::Basic,
+        );
+        let mut multi_fruit = searcher.search(&query, &multi_collector).unwrap();
+        let count = count_handle.extract(&mut multi_fruit);
+        let stats = stats_handle.extract(&mut multi_fruit);
+        assert_eq!(stats.count(), count as u64);
+        assert_eq!(stats.min(), Some(1.0));
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let stats = Stats::default();
+        assert_eq!(stats.count(), 0);
+        assert_eq!(stats.sum(), 0.0);
+        assert_eq!(stats.min(), None);
+        assert_eq!(stats.mean(), None);
+        assert_eq!(stats.variance(), None);
+    }
+}
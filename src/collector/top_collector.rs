@@ -3,7 +3,6 @@ use crate::DocId;
 use crate::Result;
 use crate::SegmentLocalId;
 use crate::SegmentReader;
-use serde::export::PhantomData;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 
@@ -12,42 +11,45 @@ use std::collections::BinaryHeap;
 /// It has a custom implementation of `PartialOrd` that reverses the order. This is because the
 /// default Rust heap is a max heap, whereas a min heap is needed.
 ///
+/// Documents with the same feature are ordered by `doc`, the smallest first,
+/// so that the ranking of the documents is deterministic.
+///
 /// WARNING: equality is not what you would expect here.
-/// Two elements are equal if their feature is equal, and regardless of whether `doc`
-/// is equal. This should be perfectly fine for this usage, but let's make sure this
-/// struct is never public.
+/// Two elements are equal if their feature and `doc` are equal, or if their features
+/// cannot be compared. This should be perfectly fine for this usage, but let's make
+/// sure this struct is never public.
 struct ComparableDoc<T, D> {
     feature: T,
     doc: D,
 }
 
-impl<T: PartialOrd, D> PartialOrd for ComparableDoc<T, D> {
+impl<T: PartialOrd, D: PartialOrd> PartialOrd for ComparableDoc<T, D> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<T: PartialOrd, D> Ord for ComparableDoc<T, D> {
+impl<T: PartialOrd, D: PartialOrd> Ord for ComparableDoc<T, D> {
     #[inline]
     fn cmp(&self, other: &Self) -> Ordering {
-        other
-            .feature
-            .partial_cmp(&self.feature)
-            .unwrap_or_else(|| Ordering::Equal)
+        match other.feature.partial_cmp(&self.feature) {
+            Some(Ordering::Equal) => self.doc.partial_cmp(&other.doc).unwrap_or(Ordering::Equal),
+            ordering => ordering.unwrap_or(Ordering::Equal),
+        }
     }
 }
 
-impl<T: PartialOrd, D> PartialEq for ComparableDoc<T, D> {
+impl<T: PartialOrd, D: PartialOrd> PartialEq for ComparableDoc<T, D> {
     fn eq(&self, other: &Self) -> bool {
         self.cmp(other) == Ordering::Equal
     }
 }
 
-impl<T: PartialOrd, D> Eq for ComparableDoc<T, D> {}
+impl<T: PartialOrd, D: PartialOrd> Eq for ComparableDoc<T, D> {}
 
 pub(crate) struct TopCollector<T> {
     limit: usize,
-    _marker: PhantomData<T>,
+    after: Option<(T, DocAddress)>,
 }
 
 impl<T> TopCollector<T>
@@ -62,16 +64,30 @@ where
         if limit < 1 {
             panic!("Limit must be strictly greater than 0.");
         }
-        TopCollector {
-            limit,
-            _marker: PhantomData,
-        }
+        TopCollector { limit, after: None }
     }
 
     pub fn limit(&self) -> usize {
         self.limit
     }
 
+    /// Returns the feature and the address of the anchor set by `search_after`.
+    pub fn after(&self) -> Option<&(T, DocAddress)> {
+        self.after.as_ref()
+    }
+
+    /// Only collects the documents ranked after the document at `doc_address`,
+    /// whose feature is `feature`.
+    ///
+    /// A document is ranked after the anchor if its feature is lower, or if its
+    /// feature is equal and its address is greater.
+    pub fn search_after(self, feature: T, doc_address: DocAddress) -> TopCollector<T> {
+        TopCollector {
+            limit: self.limit,
+            after: Some((feature, doc_address)),
+        }
+    }
+
     pub fn merge_fruits(
         &self,
         children: Vec<Vec<(T, DocAddress)>>,
@@ -82,11 +98,12 @@ where
         let mut top_collector = BinaryHeap::new();
         for child_fruit in children {
             for (feature, doc) in child_fruit {
+                let comparable_doc = ComparableDoc { feature, doc };
                 if top_collector.len() < self.limit {
-                    top_collector.push(ComparableDoc { feature, doc });
+                    top_collector.push(comparable_doc);
                 } else if let Some(mut head) = top_collector.peek_mut() {
-                    if head.feature < feature {
-                        *head = ComparableDoc { feature, doc };
+                    if comparable_doc < *head {
+                        *head = comparable_doc;
                     }
                 }
             }
@@ -98,12 +115,14 @@ where
             .collect())
     }
 
-    pub(crate) fn for_segment<F: PartialOrd>(
+    pub(crate) fn for_segment(
         &self,
         segment_id: SegmentLocalId,
         _: &SegmentReader,
-    ) -> Result<TopSegmentCollector<F>> {
-        Ok(TopSegmentCollector::new(segment_id, self.limit))
+    ) -> Result<TopSegmentCollector<T>> {
+        let mut segment_collector = TopSegmentCollector::new(segment_id, self.limit);
+        segment_collector.after = self.after.clone();
+        Ok(segment_collector)
     }
 }
 
@@ -117,14 +136,31 @@ pub(crate) struct TopSegmentCollector<T> {
     limit: usize,
     heap: BinaryHeap<ComparableDoc<T, DocId>>,
     segment_id: u32,
+    after: Option<(T, DocAddress)>,
 }
 
 impl<T: PartialOrd> TopSegmentCollector<T> {
-    fn new(segment_id: SegmentLocalId, limit: usize) -> TopSegmentCollector<T> {
+    pub(crate) fn new(segment_id: SegmentLocalId, limit: usize) -> TopSegmentCollector<T> {
         TopSegmentCollector {
             limit,
             heap: BinaryHeap::with_capacity(limit),
             segment_id,
+            after: None,
+        }
+    }
+
+    /// Returns true iff the document is ranked after the anchor of the collector, if any.
+    #[inline(always)]
+    fn is_after_anchor(&self, doc: DocId, feature: &T) -> bool {
+        match self.after {
+            Some((ref after_feature, after_doc_address)) => {
+                match after_feature.partial_cmp(feature) {
+                    Some(Ordering::Greater) => true,
+                    Some(Ordering::Equal) => DocAddress(self.segment_id, doc) > after_doc_address,
+                    _ => false,
+                }
+            }
+            None => true,
         }
     }
 }
@@ -151,12 +187,27 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
         self.heap.len() >= self.limit
     }
 
+    /// Returns the lowest feature of the collector, once it has reached its capacity.
+    ///
+    /// Until then, any document is collected. Afterwards, only the documents with
+    /// a feature greater than this threshold are.
+    pub(crate) fn threshold(&self) -> Option<T> {
+        if self.at_capacity() {
+            self.heap.peek().map(|head| head.feature.clone())
+        } else {
+            None
+        }
+    }
+
     /// Collects a document scored by the given feature
     ///
     /// It collects documents until it has reached the max capacity. Once it reaches capacity, it
     /// will compare the lowest scoring item with the given one and keep whichever is greater.
     #[inline(always)]
     pub fn collect(&mut self, doc: DocId, feature: T) {
+        if !self.is_after_anchor(doc, &feature) {
+            return;
+        }
         if self.at_capacity() {
             // It's ok to unwrap as long as a limit of 0 is forbidden.
             if let Some(limit_feature) = self.heap.peek().map(|head| head.feature.clone()) {
@@ -177,7 +228,7 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::TopSegmentCollector;
+    use super::{TopCollector, TopSegmentCollector};
     use crate::DocAddress;
 
     #[test]
@@ -196,6 +247,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_top_collector_ties() {
+        let mut top_collector = TopSegmentCollector::new(1, 2);
+        top_collector.collect(1, 0.5);
+        top_collector.collect(3, 0.8);
+        top_collector.collect(5, 0.5);
+        assert_eq!(
+            top_collector.harvest(),
+            vec![(0.8, DocAddress(1, 3)), (0.5, DocAddress(1, 1))]
+        );
+        let collector = TopCollector::with_limit(2);
+        let merged = collector
+            .merge_fruits(vec![
+                vec![(0.5, DocAddress(1, 1))],
+                vec![(0.5, DocAddress(0, 4)), (0.2, DocAddress(0, 2))],
+            ])
+            .unwrap();
+        assert_eq!(
+            merged,
+            vec![(0.5, DocAddress(0, 4)), (0.5, DocAddress(1, 1))]
+        );
+    }
+
+    #[test]
+    fn test_top_collector_search_after() {
+        let mut top_collector = TopSegmentCollector::new(1, 4);
+        top_collector.after = Some((0.5, DocAddress(1, 3)));
+        top_collector.collect(1, 0.5);
+        top_collector.collect(2, 0.8);
+        top_collector.collect(3, 0.5);
+        top_collector.collect(5, 0.5);
+        top_collector.collect(7, 0.2);
+        assert_eq!(
+            top_collector.harvest(),
+            vec![(0.5, DocAddress(1, 5)), (0.2, DocAddress(1, 7))]
+        );
+    }
+
     #[test]
     fn test_top_collector_at_capacity() {
         let mut top_collector = TopSegmentCollector::new(0, 4);
@@ -1,12 +1,16 @@
 use super::Collector;
 use crate::collector::custom_score_top_collector::CustomScoreTopCollector;
+use crate::collector::fast_field_top_collector::FastFieldTopCollector;
+use crate::collector::geo_distance_top_collector::GeoDistanceTopCollector;
 use crate::collector::top_collector::TopCollector;
 use crate::collector::top_collector::TopSegmentCollector;
 use crate::collector::tweak_score_top_collector::TweakedScoreTopCollector;
 use crate::collector::{
-    CustomScorer, CustomSegmentScorer, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
+    CustomScorer, CustomSegmentScorer, Order, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
 };
-use crate::schema::Field;
+use crate::fastfield::FastValue;
+use crate::query::Weight;
+use crate::schema::{Field, GeoPoint};
 use crate::DocAddress;
 use crate::DocId;
 use crate::Result;
@@ -81,6 +85,49 @@ impl TopDocs {
         TopDocs(TopCollector::with_limit(limit))
     }
 
+    /// Only collects the documents ranked after a previously returned document,
+    /// given its score and address.
+    ///
+    /// This makes it possible to fetch the pages of a result list one after the
+    /// other, without collecting the documents of all of the previous pages.
+    /// Documents with the same score are ranked by their address.
+    ///
+    /// The anchor only applies to the ranking by score: it is not carried over by
+    /// [`order_by_fast_field`](#method.order_by_fast_field),
+    /// [`tweak_score`](#method.tweak_score) or [`custom_score`](#method.custom_score).
+    ///
+    /// ```rust
+    /// # use tantivy::schema::{Schema, TEXT};
+    /// # use tantivy::{doc, Index, DocAddress, Score};
+    /// # use tantivy::query::QueryParser;
+    /// use tantivy::collector::TopDocs;
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// #   let mut schema_builder = Schema::builder();
+    /// #   let title = schema_builder.add_text_field("title", TEXT);
+    /// #   let index = Index::create_in_ram(schema_builder.build());
+    /// #   let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+    /// #   index_writer.add_document(doc!(title => "The Diary of Muadib"));
+    /// #   index_writer.add_document(doc!(title => "A Dairy Cow"));
+    /// #   index_writer.add_document(doc!(title => "The Diary of a Young Girl"));
+    /// #   index_writer.add_document(doc!(title => "Diary of a Wimpy Kid"));
+    /// #   index_writer.commit()?;
+    /// #   let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+    /// #   let searcher = index.reader()?.searcher();
+    /// let first_page: Vec<(Score, DocAddress)> = searcher.search(&query, &TopDocs::with_limit(2))?;
+    /// let (last_score, last_doc_address) = first_page[1];
+    /// let second_page: Vec<(Score, DocAddress)> = searcher.search(
+    ///     &query,
+    ///     &TopDocs::with_limit(2).search_after(last_score, last_doc_address),
+    /// )?;
+    /// assert_eq!(second_page.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_after(self, score: Score, doc_address: DocAddress) -> TopDocs {
+        TopDocs(self.0.search_after(score, doc_address))
+    }
+
     /// Set top-K to rank documents by a given fast field.
     ///
     /// ```rust
@@ -167,6 +214,99 @@ impl TopDocs {
         })
     }
 
+    /// Set top-K to rank documents by a given single valued fast field,
+    /// in the given order.
+    ///
+    /// `TFastValue` is the type of the field: `u64`, `i64` or `f64`.
+    /// Date fields are ranked by their timestamp, as `i64`.
+    ///
+    /// ```rust
+    /// # use tantivy::schema::{Schema, FAST, TEXT};
+    /// # use tantivy::{doc, Index, DocAddress};
+    /// # use tantivy::query::QueryParser;
+    /// use tantivy::collector::{Order, TopDocs};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// #   let mut schema_builder = Schema::builder();
+    /// #   let title = schema_builder.add_text_field("title", TEXT);
+    /// let price = schema_builder.add_f64_field("price", FAST);
+    /// #   let index = Index::create_in_ram(schema_builder.build());
+    /// #   let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+    /// #   index_writer.add_document(doc!(title => "The Diary of Muadib", price => 12.5f64));
+    /// #   index_writer.add_document(doc!(title => "A Dairy Cow", price => 3.0f64));
+    /// #   index_writer.add_document(doc!(title => "The Diary of a Young Girl", price => 7.25f64));
+    /// #   index_writer.commit()?;
+    /// #   let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+    /// #   let searcher = index.reader()?.searcher();
+    /// // The cheapest documents come first.
+    /// let top_docs_by_price = TopDocs::with_limit(10).order_by_fast_field::<f64>(price, Order::Asc);
+    /// let resulting_docs: Vec<(f64, DocAddress)> = searcher.search(&query, &top_docs_by_price)?;
+    /// assert_eq!(resulting_docs, vec![(7.25, DocAddress(0, 2)), (12.5, DocAddress(0, 0))]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The search returns an error if the field is not a single valued
+    /// fast field of type `TFastValue`.
+    ///
+    /// # See also
+    /// [FastFieldTopCollector::search_after(...)](./struct.FastFieldTopCollector.html#method.search_after).
+    pub fn order_by_fast_field<TFastValue>(
+        self,
+        field: Field,
+        order: Order,
+    ) -> FastFieldTopCollector<TFastValue>
+    where
+        TFastValue: FastValue + 'static,
+    {
+        FastFieldTopCollector::new(field, order, self.0.limit())
+    }
+
+    /// Set top-K to rank documents by their distance to `origin`,
+    /// the closest documents first.
+    ///
+    /// `field` must be a single valued geo point fast field.
+    /// The documents are returned with their distance to `origin`, in meters.
+    ///
+    /// ```rust
+    /// # use tantivy::schema::{Schema, FAST, TEXT};
+    /// # use tantivy::{doc, Index, DocAddress};
+    /// # use tantivy::query::QueryParser;
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::schema::GeoPoint;
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// #   let mut schema_builder = Schema::builder();
+    /// #   let name = schema_builder.add_text_field("name", TEXT);
+    /// let location = schema_builder.add_geo_point_field("location", FAST);
+    /// #   let index = Index::create_in_ram(schema_builder.build());
+    /// #   let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+    /// #   index_writer.add_document(doc!(name => "Gare du Nord", location => GeoPoint::new(48.8809, 2.3553)));
+    /// #   index_writer.add_document(doc!(name => "Gare de Lyon", location => GeoPoint::new(48.8443, 2.3744)));
+    /// #   index_writer.add_document(doc!(name => "Gare Saint-Lazare", location => GeoPoint::new(48.8763, 2.3254)));
+    /// #   index_writer.commit()?;
+    /// #   let query = QueryParser::for_index(&index, vec![name]).parse_query("gare")?;
+    /// #   let searcher = index.reader()?.searcher();
+    /// let notre_dame = GeoPoint::new(48.8530, 2.3499);
+    /// let closest_stations = TopDocs::with_limit(2).order_by_distance(location, notre_dame);
+    /// let resulting_docs: Vec<(f64, DocAddress)> = searcher.search(&query, &closest_stations)?;
+    /// assert_eq!(resulting_docs[0].1, DocAddress(0, 1));
+    /// assert_eq!(resulting_docs[1].1, DocAddress(0, 0));
+    /// assert!(resulting_docs[0].0 < resulting_docs[1].0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The search returns an error if the field is not a single valued
+    /// geo point fast field.
+    pub fn order_by_distance(self, field: Field, origin: GeoPoint) -> GeoDistanceTopCollector {
+        GeoDistanceTopCollector::new(field, origin, self.0.limit())
+    }
+
     /// Ranks the documents using a custom score.
     ///
     /// This method offers a convenient way to tweak or replace
@@ -407,6 +547,33 @@ impl Collector for TopDocs {
     fn merge_fruits(&self, child_fruits: Vec<Vec<(Score, DocAddress)>>) -> Result<Self::Fruit> {
         self.0.merge_fruits(child_fruits)
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!("TopDocs({}, {:?})", self.0.limit(), self.0.after()))
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_local_id: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<Self::Fruit> {
+        // Once the collector is full, only the documents whose score exceeds
+        // the lowest score of the collector are of interest: the weight can skip the others.
+        let mut top_collector = self.0.for_segment(segment_local_id, reader)?;
+        let delete_bitset = reader.delete_bitset();
+        weight.for_each_pruning(Score::NEG_INFINITY, reader, &mut |doc, score| {
+            let is_alive = match delete_bitset {
+                Some(delete_bitset) => delete_bitset.is_alive(doc),
+                None => true,
+            };
+            if is_alive {
+                top_collector.collect(doc, score);
+            }
+            top_collector.threshold().unwrap_or(Score::NEG_INFINITY)
+        })?;
+        Ok(top_collector.harvest())
+    }
 }
 
 /// Segment Collector associated to `TopDocs`.
@@ -427,13 +594,15 @@ impl SegmentCollector for TopScoreSegmentCollector {
 #[cfg(test)]
 mod tests {
     use super::TopDocs;
-    use crate::collector::Collector;
+    use crate::collector::{Collector, Order};
     use crate::query::{Query, QueryParser};
-    use crate::schema::{Field, Schema, FAST, STORED, TEXT};
+    use crate::schema::{Field, GeoPoint, Schema, FAST, STORED, TEXT};
     use crate::DocAddress;
     use crate::Index;
     use crate::IndexWriter;
     use crate::Score;
+    use crate::TantivyError;
+    use chrono::{TimeZone, Utc};
 
     fn make_index() -> Index {
         let mut schema_builder = Schema::builder();
@@ -577,6 +746,227 @@ mod tests {
         assert!(top_collector.for_segment(0, segment).is_ok());
     }
 
+    #[test]
+    fn test_top_field_collector_order() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let temperature = schema_builder.add_i64_field("temperature", FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let schema = schema_builder.build();
+        let (index, query) = index("beer", title, schema, |index_writer| {
+            index_writer.add_document(doc!(
+                title => "bottle of beer",
+                temperature => -3i64,
+                price => 2.5f64,
+            ));
+            index_writer.add_document(doc!(
+                title => "growler of beer",
+                temperature => 4i64,
+                price => 12f64,
+            ));
+            index_writer.add_document(doc!(
+                title => "pint of beer",
+                temperature => -12i64,
+                price => 6.75f64,
+            ));
+        });
+        let searcher = index.reader().unwrap().searcher();
+
+        let top_collector =
+            TopDocs::with_limit(2).order_by_fast_field::<i64>(temperature, Order::Asc);
+        let top_docs: Vec<(i64, DocAddress)> = searcher.search(&query, &top_collector).unwrap();
+        assert_eq!(
+            top_docs,
+            vec![(-12, DocAddress(0, 2)), (-3, DocAddress(0, 0))]
+        );
+
+        let top_collector =
+            TopDocs::with_limit(2).order_by_fast_field::<i64>(temperature, Order::Desc);
+        let top_docs: Vec<(i64, DocAddress)> = searcher.search(&query, &top_collector).unwrap();
+        assert_eq!(
+            top_docs,
+            vec![(4, DocAddress(0, 1)), (-3, DocAddress(0, 0))]
+        );
+
+        let top_collector = TopDocs::with_limit(4).order_by_fast_field::<f64>(price, Order::Asc);
+        let top_docs: Vec<(f64, DocAddress)> = searcher.search(&query, &top_collector).unwrap();
+        assert_eq!(
+            top_docs,
+            vec![
+                (2.5, DocAddress(0, 0)),
+                (6.75, DocAddress(0, 2)),
+                (12.0, DocAddress(0, 1))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_field_collector_date() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let timestamp = schema_builder.add_date_field("timestamp", FAST);
+        let schema = schema_builder.build();
+        let (index, query) = index("beer", title, schema, |index_writer| {
+            for (day, beer) in [(12, "stout"), (3, "lager"), (25, "porter")].iter() {
+                index_writer.add_document(doc!(
+                    title => format!("{} beer", beer),
+                    timestamp => Utc.with_ymd_and_hms(2019, 12, *day, 12, 0, 0).unwrap(),
+                ));
+            }
+        });
+        let searcher = index.reader().unwrap().searcher();
+        let top_collector =
+            TopDocs::with_limit(2).order_by_fast_field::<i64>(timestamp, Order::Desc);
+        let top_docs: Vec<(i64, DocAddress)> = searcher.search(&query, &top_collector).unwrap();
+        assert_eq!(
+            top_docs,
+            vec![
+                (
+                    Utc.with_ymd_and_hms(2019, 12, 25, 12, 0, 0)
+                        .unwrap()
+                        .timestamp(),
+                    DocAddress(0, 2)
+                ),
+                (
+                    Utc.with_ymd_and_hms(2019, 12, 12, 12, 0, 0)
+                        .unwrap()
+                        .timestamp(),
+                    DocAddress(0, 0)
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_distance_collector() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let location = schema_builder.add_geo_point_field("location", FAST);
+        let schema = schema_builder.build();
+        let (index, query) = index("beer", title, schema, |index_writer| {
+            for (city, lat, lon) in [
+                ("brussels", 50.8503, 4.3517),
+                ("munich", 48.1351, 11.5820),
+                ("dublin", 53.3498, -6.2603),
+                ("prague", 50.0755, 14.4378),
+            ]
+            .iter()
+            {
+                index_writer.add_document(doc!(
+                    title => format!("{} beer", city),
+                    location => GeoPoint::new(*lat, *lon),
+                ));
+            }
+        });
+        let searcher = index.reader().unwrap().searcher();
+        let origin = GeoPoint::new(48.8566, 2.3522);
+        let top_collector = TopDocs::with_limit(3).order_by_distance(location, origin);
+        let top_docs: Vec<(f64, DocAddress)> = searcher.search(&query, &top_collector).unwrap();
+        let doc_addresses: Vec<DocAddress> = top_docs.iter().map(|(_, addr)| *addr).collect();
+        assert_eq!(
+            doc_addresses,
+            vec![DocAddress(0, 0), DocAddress(0, 1), DocAddress(0, 2)]
+        );
+        assert!(
+            (top_docs[0].0 - 264_000f64).abs() < 1_000f64,
+            "{}",
+            top_docs[0].0
+        );
+        let top_collector = TopDocs::with_limit(3).order_by_distance(title, origin);
+        assert!(matches!(
+            searcher.search(&query, &top_collector),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+
+    #[test]
+    fn test_top_field_collector_errors() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let size = schema_builder.add_u64_field(SIZE, FAST);
+        let stored_size = schema_builder.add_u64_field("stored_size", STORED);
+        let schema = schema_builder.build();
+        let (index, query) = index("beer", title, schema, |index_writer| {
+            index_writer.add_document(doc!(
+                title => "bottle of beer",
+                size => 12u64,
+                stored_size => 12u64,
+            ));
+        });
+        let searcher = index.reader().unwrap().searcher();
+        for top_collector in &[
+            TopDocs::with_limit(4).order_by_fast_field::<u64>(stored_size, Order::Desc),
+            TopDocs::with_limit(4).order_by_fast_field::<u64>(Field(3), Order::Desc),
+        ] {
+            assert!(matches!(
+                searcher.search(&query, top_collector),
+                Err(TantivyError::SchemaError(_))
+            ));
+        }
+        let top_collector = TopDocs::with_limit(4).order_by_fast_field::<i64>(size, Order::Desc);
+        assert!(searcher.search(&query, &top_collector).is_err());
+    }
+
+    #[test]
+    fn test_top_collector_search_after() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let size = schema_builder.add_u64_field(SIZE, FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..20u64 {
+            let text = if i % 3 == 0 { "beer" } else { "beer beer" };
+            index_writer.add_document(doc!(title => text, size => i % 4));
+            if i == 11 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query = QueryParser::for_index(&index, vec![title])
+            .parse_query("beer")
+            .unwrap();
+
+        let all_docs = searcher.search(&query, &TopDocs::with_limit(100)).unwrap();
+        assert_eq!(all_docs.len(), 20);
+        let mut pages = Vec::new();
+        let mut top_docs = TopDocs::with_limit(3);
+        loop {
+            let page = searcher.search(&query, &top_docs).unwrap();
+            if let Some(&(score, doc_address)) = page.last() {
+                top_docs = TopDocs::with_limit(3).search_after(score, doc_address);
+                pages.extend(page);
+            } else {
+                break;
+            }
+        }
+        assert_eq!(pages, all_docs);
+
+        for &order in &[Order::Asc, Order::Desc] {
+            let all_docs = searcher
+                .search(
+                    &query,
+                    &TopDocs::with_limit(100).order_by_fast_field::<u64>(size, order),
+                )
+                .unwrap();
+            assert_eq!(all_docs.len(), 20);
+            let mut pages: Vec<(u64, DocAddress)> = Vec::new();
+            let mut top_docs = TopDocs::with_limit(3).order_by_fast_field::<u64>(size, order);
+            loop {
+                let page = searcher.search(&query, &top_docs).unwrap();
+                if let Some(&(value, doc_address)) = page.last() {
+                    top_docs = TopDocs::with_limit(3)
+                        .order_by_fast_field(size, order)
+                        .search_after(value, doc_address);
+                    pages.extend(page);
+                } else {
+                    break;
+                }
+            }
+            assert_eq!(pages, all_docs);
+        }
+    }
+
     fn index(
         query: &str,
         query_field: Field,
@@ -104,6 +104,34 @@ where
         let val_shifted = (val_unshifted_unmasked >> bit_shift) as u64;
         val_shifted & mask
     }
+
+    /// Fills `output` with the values going from `start` to `start + output.len()`.
+    ///
+    /// This is equivalent to calling `get` for each of the values,
+    /// but the address of the values is computed incrementally.
+    pub fn get_range(&self, start: u64, output: &mut [u64]) {
+        if self.num_bits == 0 {
+            for out in output.iter_mut() {
+                *out = 0u64;
+            }
+            return;
+        }
+        let data: &[u8] = &self.data;
+        let num_bits = self.num_bits;
+        let mask = self.mask;
+        let mut addr_in_bits = start * num_bits;
+        debug_assert!(
+            (addr_in_bits + output.len() as u64 * num_bits) / 8 + 7 <= data.len() as u64,
+            "The fast field field should have been padded with 7 bytes."
+        );
+        for out in output.iter_mut() {
+            let addr = (addr_in_bits >> 3) as usize;
+            let bit_shift = addr_in_bits & 7;
+            let val_unshifted_unmasked: u64 = LittleEndian::read_u64(&data[addr..]);
+            *out = (val_unshifted_unmasked >> bit_shift) & mask;
+            addr_in_bits += num_bits;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -131,6 +159,10 @@ mod test {
         for (i, val) in vals.iter().enumerate() {
             assert_eq!(bitunpacker.get(i as u64), *val);
         }
+        let start = len / 3;
+        let mut output = vec![1u64; len - start];
+        bitunpacker.get_range(start as u64, &mut output);
+        assert_eq!(&output[..], &vals[start..]);
     }
 
     #[test]
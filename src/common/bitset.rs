@@ -1,4 +1,5 @@
 use std::fmt;
+use std::mem;
 use std::u64;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -157,6 +158,11 @@ impl BitSet {
         self.len
     }
 
+    /// Returns the number of bytes taken by the `BitSet`.
+    pub(crate) fn num_bytes(&self) -> usize {
+        mem::size_of::<BitSet>() + self.tinysets.len() * mem::size_of::<TinySet>()
+    }
+
     /// Inserts an element in the `BitSet`
     pub fn insert(&mut self, el: u32) {
         // we do not check saturated els.
@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+struct LruCacheEntry<V> {
+    value: V,
+    num_bytes: usize,
+    generation: u64,
+}
+
+/// Cache bounded by the overall size of its entries, evicting the least
+/// recently used entries first.
+///
+/// Each access gives the entry a new generation. The keys are also indexed
+/// by the generation of their last access, so that the least recently used
+/// entry is found in logarithmic time.
+pub(crate) struct LruCache<K, V> {
+    max_num_bytes: usize,
+    num_bytes: usize,
+    entries: HashMap<K, LruCacheEntry<V>>,
+    keys_by_generation: BTreeMap<u64, K>,
+    generation: u64,
+}
+
+impl<K: Clone + Eq + Hash, V> LruCache<K, V> {
+    /// Creates a cache whose entries take at most `max_num_bytes` overall.
+    pub fn with_max_num_bytes(max_num_bytes: usize) -> LruCache<K, V> {
+        LruCache {
+            max_num_bytes,
+            num_bytes: 0,
+            entries: HashMap::new(),
+            keys_by_generation: BTreeMap::new(),
+            generation: 0,
+        }
+    }
+
+    fn next_generation(&mut self) -> u64 {
+        self.generation += 1;
+        self.generation
+    }
+
+    /// Returns the value associated with `key`, if any, and marks
+    /// it as the most recently used one.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let generation = self.next_generation();
+        let entry = self.entries.get_mut(key)?;
+        let key = self
+            .keys_by_generation
+            .remove(&entry.generation)
+            .expect("The entry should be indexed by its generation");
+        entry.generation = generation;
+        self.keys_by_generation.insert(generation, key);
+        Some(&entry.value)
+    }
+
+    /// Inserts a value taking approximately `num_bytes`, evicting the least
+    /// recently used entries until it fits within the cache.
+    ///
+    /// Values larger than the cache itself are not inserted.
+    pub fn insert(&mut self, key: K, value: V, num_bytes: usize) {
+        self.remove(&key);
+        if num_bytes > self.max_num_bytes {
+            return;
+        }
+        while self.num_bytes + num_bytes > self.max_num_bytes {
+            let least_recently_used_key = match self.keys_by_generation.values().next() {
+                Some(key) => key.clone(),
+                None => break,
+            };
+            self.remove(&least_recently_used_key);
+        }
+        let generation = self.next_generation();
+        self.num_bytes += num_bytes;
+        self.keys_by_generation.insert(generation, key.clone());
+        self.entries.insert(
+            key,
+            LruCacheEntry {
+                value,
+                num_bytes,
+                generation,
+            },
+        );
+    }
+
+    /// Removes the entry associated with `key`, if any.
+    pub fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.keys_by_generation.remove(&entry.generation);
+            self.num_bytes -= entry.num_bytes;
+        }
+    }
+
+    /// Only keeps the entries whose key satisfies the predicate.
+    pub fn retain<P: Fn(&K) -> bool>(&mut self, predicate: P) {
+        let removed_keys: Vec<K> = self
+            .entries
+            .keys()
+            .filter(|key| !predicate(key))
+            .cloned()
+            .collect();
+        for key in &removed_keys {
+            self.remove(key);
+        }
+    }
+
+    /// Returns the number of entries.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the overall size of the entries.
+    #[cfg(test)]
+    pub fn num_bytes(&self) -> usize {
+        self.num_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LruCache;
+
+    #[test]
+    fn test_lru_cache() {
+        let mut cache = LruCache::with_max_num_bytes(10);
+        cache.insert("a", 1, 4);
+        cache.insert("b", 2, 4);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.num_bytes(), 8);
+        // "b" is the least recently used entry.
+        cache.insert("c", 3, 2);
+        assert_eq!(cache.len(), 3);
+        cache.insert("d", 4, 3);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.num_bytes(), 9);
+        // Replacing an entry updates its size.
+        cache.insert("a", 5, 1);
+        assert_eq!(cache.get(&"a"), Some(&5));
+        assert_eq!(cache.num_bytes(), 6);
+        cache.retain(|&key| key != "c");
+        assert_eq!(cache.get(&"c"), None);
+        assert_eq!(cache.num_bytes(), 4);
+    }
+
+    #[test]
+    fn test_lru_cache_too_large_value() {
+        let mut cache = LruCache::with_max_num_bytes(10);
+        cache.insert("a", 1, 4);
+        cache.insert("b", 2, 11);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        let mut empty_cache = LruCache::with_max_num_bytes(0);
+        empty_cache.insert("a", 1, 1);
+        assert_eq!(empty_cache.len(), 0);
+    }
+}
@@ -9,28 +9,80 @@ use scoped_pool::{Pool, ThreadConfig};
 /// First dependency hell. It is not a good idea to expose the
 /// API of a dependency, knowing it might conflict with a different version
 /// used by the client. Second, we may stop using rayon in the future.
-pub enum Executor {
-    SingleThread,
-    ThreadPool(Pool),
+///
+/// A multithread executor can be shared by several indexes and readers
+/// (see `IndexReaderBuilder::search_executor`), or dedicated to one of them.
+pub struct Executor {
+    // `None` if the tasks are performed in the caller thread.
+    pool: Option<Pool>,
+    max_parallelism: Option<usize>,
 }
 
 impl Executor {
     /// Creates an Executor that performs all task in the caller thread.
     pub fn single_thread() -> Executor {
-        Executor::SingleThread
+        Executor {
+            pool: None,
+            max_parallelism: None,
+        }
     }
 
-    // Creates an Executor that dispatches the tasks in a thread pool.
+    /// Creates an Executor that dispatches the tasks in a thread pool.
     pub fn multi_thread(num_threads: usize, prefix: &'static str) -> Executor {
         let thread_config = ThreadConfig::new().prefix(prefix);
         let pool = Pool::with_thread_config(num_threads, thread_config);
-        Executor::ThreadPool(pool)
+        Executor {
+            pool: Some(pool),
+            max_parallelism: None,
+        }
+    }
+
+    /// Limits the number of tasks of a single call to `map` (e.g. the number
+    /// of segments of a single query) that run concurrently.
+    ///
+    /// By default, all of the tasks are dispatched at once, and are only
+    /// limited by the number of threads of the pool. Limiting their parallelism
+    /// makes it possible to run several queries concurrently on a shared pool,
+    /// without one of them starving the others.
+    ///
+    /// This has no effect on a single thread executor.
+    ///
+    /// # Panics
+    ///
+    /// If `max_parallelism` is 0.
+    pub fn with_max_parallelism(mut self, max_parallelism: usize) -> Executor {
+        assert!(max_parallelism > 0, "The parallelism should be at least 1.");
+        self.max_parallelism = Some(max_parallelism);
+        self
+    }
+
+    /// Returns the number of threads of the pool, or 1 if the executor
+    /// performs the tasks in the caller thread.
+    pub fn num_threads(&self) -> usize {
+        self.pool.as_ref().map(|pool| pool.workers()).unwrap_or(1)
+    }
+
+    /// Returns the maximum number of tasks of a single call
+    /// to `map` that run concurrently, if any.
+    pub fn max_parallelism(&self) -> Option<usize> {
+        self.max_parallelism
+    }
+
+    /// Runs a task in the thread pool, without waiting for its completion.
+    ///
+    /// A single thread executor runs the task in the caller thread
+    /// before returning.
+    pub fn spawn<F: FnOnce() + Send + 'static>(&self, task: F) {
+        match self.pool {
+            Some(ref pool) => pool.spawn(task),
+            None => task(),
+        }
     }
 
-    // Perform a map in the thread pool.
-    //
-    // Regardless of the executor (`SingleThread` or `ThreadPool`), panics in the task
-    // will propagate to the caller.
+    /// Perform a map in the thread pool.
+    ///
+    /// Regardless of the executor (single thread or thread pool), panics in the task
+    /// will propagate to the caller.
     pub fn map<
         A: Send,
         R: Send,
@@ -41,43 +93,51 @@ impl Executor {
         f: F,
         args: AIterator,
     ) -> Result<Vec<R>> {
-        match self {
-            Executor::SingleThread => args.map(f).collect::<Result<_>>(),
-            Executor::ThreadPool(pool) => {
-                let args_with_indices: Vec<(usize, A)> = args.enumerate().collect();
-                let num_fruits = args_with_indices.len();
-                let fruit_receiver = {
-                    let (fruit_sender, fruit_receiver) = channel::unbounded();
-                    pool.scoped(|scope| {
-                        for arg_with_idx in args_with_indices {
-                            scope.execute(|| {
-                                let (idx, arg) = arg_with_idx;
-                                let fruit = f(arg);
-                                if let Err(err) = fruit_sender.send((idx, fruit)) {
-                                    error!("Failed to send search task. It probably means all search threads have panicked. {:?}", err);
-                                }
-                            });
+        let pool = match self.pool {
+            Some(ref pool) => pool,
+            None => return args.map(f).collect::<Result<_>>(),
+        };
+        let args_with_indices: Vec<(usize, A)> = args.enumerate().collect();
+        let num_fruits = args_with_indices.len();
+        // Each task processes its arguments sequentially.
+        let num_tasks = self.max_parallelism.unwrap_or(num_fruits).min(num_fruits);
+        let mut tasks: Vec<Vec<(usize, A)>> = (0..num_tasks).map(|_| Vec::new()).collect();
+        for arg_with_idx in args_with_indices {
+            tasks[arg_with_idx.0 % num_tasks].push(arg_with_idx);
+        }
+        let fruit_receiver = {
+            let (fruit_sender, fruit_receiver) = channel::unbounded();
+            pool.scoped(|scope| {
+                for task in tasks {
+                    let fruit_sender = fruit_sender.clone();
+                    let f = &f;
+                    scope.execute(move || {
+                        for (idx, arg) in task {
+                            let fruit = f(arg);
+                            if let Err(err) = fruit_sender.send((idx, fruit)) {
+                                error!("Failed to send search task. It probably means all search threads have panicked. {:?}", err);
+                            }
                         }
                     });
-                    fruit_receiver
-                    // This ends the scope of fruit_sender.
-                    // This is important as it makes it possible for the fruit_receiver iteration to
-                    // terminate.
-                };
-                // This is lame, but safe.
-                let mut results_with_position = Vec::with_capacity(num_fruits);
-                for (pos, fruit_res) in fruit_receiver {
-                    let fruit = fruit_res?;
-                    results_with_position.push((pos, fruit));
                 }
-                results_with_position.sort_by_key(|(pos, _)| *pos);
-                assert_eq!(results_with_position.len(), num_fruits);
-                Ok(results_with_position
-                    .into_iter()
-                    .map(|(_, fruit)| fruit)
-                    .collect::<Vec<_>>())
-            }
+            });
+            fruit_receiver
+            // This ends the scope of fruit_sender.
+            // This is important as it makes it possible for the fruit_receiver iteration to
+            // terminate.
+        };
+        // This is lame, but safe.
+        let mut results_with_position = Vec::with_capacity(num_fruits);
+        for (pos, fruit_res) in fruit_receiver {
+            let fruit = fruit_res?;
+            results_with_position.push((pos, fruit));
         }
+        results_with_position.sort_by_key(|(pos, _)| *pos);
+        assert_eq!(results_with_position.len(), num_fruits);
+        Ok(results_with_position
+            .into_iter()
+            .map(|(_, fruit)| fruit)
+            .collect::<Vec<_>>())
     }
 }
 
@@ -85,6 +145,7 @@ impl Executor {
 mod tests {
 
     use super::Executor;
+    use crossbeam::channel;
 
     #[test]
     #[should_panic(expected = "panic should propagate")]
@@ -123,6 +184,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_spawn() {
+        for executor in &[
+            Executor::single_thread(),
+            Executor::multi_thread(2, "search-test"),
+        ] {
+            let (sender, receiver) = channel::unbounded();
+            for i in 0..3 {
+                let sender = sender.clone();
+                executor.spawn(move || sender.send(i).unwrap());
+            }
+            let mut received: Vec<usize> = receiver.iter().take(3).collect();
+            received.sort();
+            assert_eq!(received, vec![0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_map_multithread_max_parallelism() {
+        let executor = Executor::multi_thread(3, "search-test").with_max_parallelism(2);
+        assert_eq!(executor.num_threads(), 3);
+        assert_eq!(executor.max_parallelism(), Some(2));
+        let result: Vec<usize> = executor.map(|i| Ok(i * 2), 0..10).unwrap();
+        assert_eq!(result, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_map_multithread() {
         let result: Vec<usize> = Executor::multi_thread(3, "search-test")
@@ -0,0 +1,115 @@
+use crate::common::LruCache;
+use crate::core::SegmentId;
+use crate::query::BitSetFilter;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct FilterCacheKey {
+    segment_id: SegmentId,
+    // Cache key of the filter query.
+    description: String,
+}
+
+/// Cache of the documents matching the filter queries, per segment.
+///
+/// Filters are identified by the segment and the cache key of the query.
+/// Unlike the `QueryCache`, it survives the reloads of the reader, segments being
+/// immutable: only the filters of the segments that are no longer searched are dropped.
+///
+/// The cache is bounded by the size of the filters and of their descriptions.
+/// When it is full, the least recently used filters are evicted.
+pub(crate) struct FilterCache {
+    entries: Mutex<LruCache<FilterCacheKey, BitSetFilter>>,
+}
+
+impl FilterCache {
+    /// Creates a cache whose segment filters take at most approximately `num_bytes`.
+    pub fn with_max_num_bytes(num_bytes: usize) -> FilterCache {
+        FilterCache {
+            entries: Mutex::new(LruCache::with_max_num_bytes(num_bytes)),
+        }
+    }
+
+    /// Returns the filter cached for the given segment and description, if any.
+    pub fn get(&self, segment_id: SegmentId, description: &str) -> Option<BitSetFilter> {
+        let key = FilterCacheKey {
+            segment_id,
+            description: description.to_string(),
+        };
+        let mut entries = self.entries.lock().expect("Filter cache lock poisoned");
+        entries.get(&key).cloned()
+    }
+
+    /// Caches the filter of the given segment and description.
+    pub fn insert(&self, segment_id: SegmentId, description: String, filter: BitSetFilter) {
+        let num_bytes = filter.num_bytes() + description.len();
+        let key = FilterCacheKey {
+            segment_id,
+            description,
+        };
+        self.entries
+            .lock()
+            .expect("Filter cache lock poisoned")
+            .insert(key, filter, num_bytes);
+    }
+
+    /// Drops the filters of the segments that are not in `segment_ids`.
+    pub fn retain_segments(&self, segment_ids: &[SegmentId]) {
+        let segment_ids: HashSet<SegmentId> = segment_ids.iter().cloned().collect();
+        self.entries
+            .lock()
+            .expect("Filter cache lock poisoned")
+            .retain(|key| segment_ids.contains(&key.segment_id));
+    }
+
+    /// Returns the number of cached segment filters.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("Filter cache lock poisoned")
+            .len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilterCache;
+    use crate::core::SegmentId;
+    use crate::query::{BitSetFilter, SegmentDocFilter, VecDocSet};
+
+    fn filter(docs: Vec<u32>) -> BitSetFilter {
+        BitSetFilter::for_docset(&mut VecDocSet::from(docs), 10)
+    }
+
+    #[test]
+    fn test_filter_cache() {
+        // Room for two filters, with a description of one byte.
+        let cache = FilterCache::with_max_num_bytes(2 * (filter(vec![]).num_bytes() + 1));
+        let (first_segment, second_segment) =
+            (SegmentId::generate_random(), SegmentId::generate_random());
+        cache.insert(first_segment, "a".to_string(), filter(vec![1, 3]));
+        cache.insert(second_segment, "a".to_string(), filter(vec![2]));
+        assert!(cache.get(first_segment, "a").unwrap().accept(3));
+        assert!(cache.get(first_segment, "b").is_none());
+        // The filter of the second segment is the least recently used one.
+        cache.insert(first_segment, "b".to_string(), filter(vec![]));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(second_segment, "a").is_none());
+        assert!(cache.get(first_segment, "b").unwrap().is_empty());
+        cache.retain_segments(&[second_segment]);
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn test_filter_cache_empty() {
+        let cache = FilterCache::with_max_num_bytes(0);
+        cache.insert(
+            SegmentId::generate_random(),
+            "a".to_string(),
+            filter(vec![1]),
+        );
+        assert_eq!(cache.len(), 0);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::query::Query;
+use crate::schema::{Field, Term};
+use crate::Searcher;
+use std::collections::{BTreeSet, HashMap};
+
+/// Corpus-level statistics of a field.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FieldStatistics {
+    /// Number of documents, including deleted ones.
+    pub num_docs: u64,
+    /// Number of tokens of the field, over all of the documents.
+    pub total_num_tokens: u64,
+}
+
+/// Corpus-level statistics used for scoring, overriding the statistics of a searcher.
+///
+/// In a distributed deployment, each shard only knows the statistics of its
+/// own documents, so that the BM25 scores of two shards are not comparable.
+/// The statistics of the whole corpus are typically gathered from all of the
+/// shards with [`.add_searcher(...)`](#method.add_searcher) before running the query,
+/// and passed to each shard with `Searcher::with_global_statistics`.
+///
+/// Only scoring is affected: `Searcher::num_docs` and `Searcher::doc_freq` keep
+/// returning the statistics of the searcher. Terms and fields missing from the
+/// global statistics fall back to the statistics of the searcher.
+#[derive(Clone, Debug, Default)]
+pub struct GlobalStatistics {
+    field_statistics: HashMap<Field, FieldStatistics>,
+    doc_freqs: HashMap<Term, u64>,
+}
+
+impl GlobalStatistics {
+    /// Creates empty statistics.
+    pub fn new() -> GlobalStatistics {
+        GlobalStatistics::default()
+    }
+
+    /// Adds the statistics of a searcher (typically the searcher of a shard)
+    /// for the terms of the query, and for the fields of these terms.
+    pub fn add_searcher(&mut self, searcher: &Searcher, query: &dyn Query) {
+        let mut terms = BTreeSet::new();
+        query.query_terms(&mut terms);
+        let fields: BTreeSet<Field> = terms.iter().map(Term::field).collect();
+        for field in fields {
+            let field_statistics = searcher.local_field_statistics(field);
+            let global_field_statistics = self.field_statistics.entry(field).or_default();
+            global_field_statistics.num_docs += field_statistics.num_docs;
+            global_field_statistics.total_num_tokens += field_statistics.total_num_tokens;
+        }
+        for term in terms {
+            let doc_freq = searcher.doc_freq(&term);
+            *self.doc_freqs.entry(term).or_insert(0) += doc_freq;
+        }
+    }
+
+    /// Sets the statistics of a field.
+    pub fn set_field_statistics(&mut self, field: Field, field_statistics: FieldStatistics) {
+        self.field_statistics.insert(field, field_statistics);
+    }
+
+    /// Sets the number of documents containing a term.
+    pub fn set_doc_freq(&mut self, term: Term, doc_freq: u64) {
+        self.doc_freqs.insert(term, doc_freq);
+    }
+
+    /// Returns the statistics of a field, if known.
+    pub fn field_statistics(&self, field: Field) -> Option<FieldStatistics> {
+        self.field_statistics.get(&field).cloned()
+    }
+
+    /// Returns the number of documents containing a term, if known.
+    pub fn doc_freq(&self, term: &Term) -> Option<u64> {
+        self.doc_freqs.get(term).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlobalStatistics;
+    use crate::collector::TopDocs;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::Index;
+    use crate::Searcher;
+    use crate::Term;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_global_statistics() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let create_index = |texts: &[&str]| {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            for text in texts {
+                index_writer.add_document(doc!(text_field => *text));
+            }
+            index_writer.commit().unwrap();
+            index.reader().unwrap().searcher()
+        };
+        let shard_1 = create_index(&["a", "b"]);
+        let shard_2 = create_index(&["a b", "c c", "b c a"]);
+        let whole = create_index(&["a", "b", "a b", "c c", "b c a"]);
+
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let top_score =
+            |searcher: &Searcher| searcher.search(&query, &TopDocs::with_limit(1)).unwrap()[0].0;
+        let expected_score = top_score(&whole);
+        assert!((top_score(&shard_1) - expected_score).abs() > 1e-3);
+
+        let mut global_statistics = GlobalStatistics::new();
+        global_statistics.add_searcher(&shard_1, &query);
+        global_statistics.add_searcher(&shard_2, &query);
+        assert_eq!(
+            global_statistics.doc_freq(&Term::from_field_text(text_field, "a")),
+            Some(3)
+        );
+        let global_statistics = Arc::new(global_statistics);
+        let shard_1 = shard_1.with_global_statistics(global_statistics.clone());
+        assert!(shard_1.global_statistics().is_some());
+        assert!((top_score(&shard_1) - expected_score).abs() < 1e-5);
+        // Only scoring is affected.
+        assert_eq!(shard_1.doc_freq(&Term::from_field_text(text_field, "a")), 1);
+    }
+}
@@ -1,11 +1,18 @@
 use super::segment::create_segment;
 use super::segment::Segment;
+use crate::collector::Order;
 use crate::core::Executor;
 use crate::core::IndexMeta;
+use crate::core::IndexSettings;
 use crate::core::SegmentId;
 use crate::core::SegmentMeta;
 use crate::core::SegmentMetaInventory;
+use crate::core::{validate_index, IntegrityProblem};
 use crate::core::META_FILEPATH;
+use crate::core::{
+    list_meta_generations, meta_generation_filepath, PinnedMetaGeneration, PinnedMetaGenerations,
+    DEFAULT_NUM_META_GENERATIONS_KEPT,
+};
 use crate::directory::ManagedDirectory;
 #[cfg(feature = "mmap")]
 use crate::directory::MmapDirectory;
@@ -15,8 +22,10 @@ use crate::error::DataCorruption;
 use crate::error::TantivyError;
 use crate::indexer::index_writer::HEAP_SIZE_MIN;
 use crate::indexer::segment_updater::save_new_metas;
+use crate::query::{BM25Similarity, Similarity};
 use crate::reader::IndexReader;
 use crate::reader::IndexReaderBuilder;
+use crate::schema::Cardinality;
 use crate::schema::Field;
 use crate::schema::FieldType;
 use crate::schema::Schema;
@@ -24,27 +33,130 @@ use crate::tokenizer::BoxedTokenizer;
 use crate::tokenizer::TokenizerManager;
 use crate::IndexWriter;
 use crate::Result;
+use crate::Searcher;
+use crate::SegmentReader;
 use num_cpus;
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::path::Path;
 #[cfg(feature = "mmap")]
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 
-fn load_metas(directory: &dyn Directory, inventory: &SegmentMetaInventory) -> Result<IndexMeta> {
-    let meta_data = directory.atomic_read(&META_FILEPATH)?;
+fn load_metas_from_file(
+    directory: &dyn Directory,
+    meta_filepath: &Path,
+    inventory: &SegmentMetaInventory,
+) -> Result<IndexMeta> {
+    let meta_data = directory.atomic_read(meta_filepath)?;
     let meta_string = String::from_utf8_lossy(&meta_data);
     IndexMeta::deserialize(&meta_string, &inventory)
         .map_err(|e| {
             DataCorruption::new(
-                META_FILEPATH.to_path_buf(),
+                meta_filepath.to_path_buf(),
                 format!("Meta file cannot be deserialized. {:?}.", e),
             )
         })
         .map_err(From::from)
 }
 
+/// Loads the index metas from `meta.json`.
+///
+/// If `meta.json` cannot be read, falls back to the last readable
+/// generation of the meta file.
+fn load_metas(directory: &ManagedDirectory, inventory: &SegmentMetaInventory) -> Result<IndexMeta> {
+    match load_metas_from_file(directory, &META_FILEPATH, inventory) {
+        Ok(metas) => Ok(metas),
+        Err(err) => {
+            for generation in list_meta_generations(directory).into_iter().rev() {
+                let generation_filepath = meta_generation_filepath(generation);
+                if let Ok(metas) = load_metas_from_file(directory, &generation_filepath, inventory)
+                {
+                    warn!(
+                        "Failed to load {:?} ({:?}). Falling back to {:?}.",
+                        *META_FILEPATH, err, generation_filepath
+                    );
+                    return Ok(metas);
+                }
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Checks that the boost field exists and is a single-valued `f64` fast field.
+fn check_boost_field(schema: &Schema, boost_field_name: &str) -> Result<()> {
+    let field = schema.get_field(boost_field_name).ok_or_else(|| {
+        TantivyError::InvalidArgument(format!(
+            "Boost field {:?} does not exist in the schema.",
+            boost_field_name
+        ))
+    })?;
+    match *schema.get_field_entry(field).field_type() {
+        FieldType::F64(ref options)
+            if options.get_fastfield_cardinality() == Some(Cardinality::SingleValue) =>
+        {
+            Ok(())
+        }
+        _ => Err(TantivyError::InvalidArgument(format!(
+            "Boost field {:?} should be a single-valued f64 fast field.",
+            boost_field_name
+        ))),
+    }
+}
+
+/// Checks that the sort field exists and is a single-valued numeric fast field.
+fn check_sort_by_field(schema: &Schema, sort_by_field_name: &str) -> Result<()> {
+    let field = schema.get_field(sort_by_field_name).ok_or_else(|| {
+        TantivyError::InvalidArgument(format!(
+            "Sort field {:?} does not exist in the schema.",
+            sort_by_field_name
+        ))
+    })?;
+    match *schema.get_field_entry(field).field_type() {
+        FieldType::U64(ref options)
+        | FieldType::I64(ref options)
+        | FieldType::F64(ref options)
+        | FieldType::Date(ref options)
+            if options.get_fastfield_cardinality() == Some(Cardinality::SingleValue) =>
+        {
+            Ok(())
+        }
+        _ => Err(TantivyError::InvalidArgument(format!(
+            "Sort field {:?} should be a single-valued u64, i64, f64 or date fast field.",
+            sort_by_field_name
+        ))),
+    }
+}
+
+fn check_similarity_field(schema: &Schema, field_name: &str) -> Result<()> {
+    if schema.get_field(field_name).is_none() {
+        return Err(TantivyError::InvalidArgument(format!(
+            "Similarity field {:?} does not exist in the schema.",
+            field_name
+        )));
+    }
+    Ok(())
+}
+
+fn check_primary_key_field(schema: &Schema, primary_key_field_name: &str) -> Result<()> {
+    let field = schema.get_field(primary_key_field_name).ok_or_else(|| {
+        TantivyError::InvalidArgument(format!(
+            "Primary key field {:?} does not exist in the schema.",
+            primary_key_field_name
+        ))
+    })?;
+    if schema.get_field_entry(field).is_indexed() {
+        Ok(())
+    } else {
+        Err(TantivyError::InvalidArgument(format!(
+            "Primary key field {:?} should be indexed.",
+            primary_key_field_name
+        )))
+    }
+}
+
 /// Search Index
 #[derive(Clone)]
 pub struct Index {
@@ -53,6 +165,11 @@ pub struct Index {
     executor: Arc<Executor>,
     tokenizers: TokenizerManager,
     inventory: SegmentMetaInventory,
+    num_meta_generations_kept: usize,
+    allow_missing_tokenizers: bool,
+    pinned_meta_generations: PinnedMetaGenerations,
+    settings: IndexSettings,
+    similarities: Arc<RwLock<HashMap<Field, Arc<dyn Similarity>>>>,
 }
 
 impl Index {
@@ -71,6 +188,10 @@ impl Index {
         self.executor.as_ref()
     }
 
+    pub(crate) fn search_executor_arc(&self) -> Arc<Executor> {
+        self.executor.clone()
+    }
+
     /// Replace the default single thread search executor pool
     /// by a thread pool with a given number of threads.
     pub fn set_multithread_executor(&mut self, num_threads: usize) {
@@ -84,6 +205,72 @@ impl Index {
         self.set_multithread_executor(default_num_threads);
     }
 
+    /// Returns the number of generations of the meta file that are protected
+    /// from garbage collection.
+    pub fn num_meta_generations_kept(&self) -> usize {
+        self.num_meta_generations_kept
+    }
+
+    /// Sets the number of generations of the meta file that are protected
+    /// from garbage collection. The segment files they reference are kept as well.
+    ///
+    /// Every commit saves the `meta.json` file as a new generation `meta.json.<generation>`.
+    /// If `meta.json` is corrupted, opening the index falls back to the last
+    /// valid generation.
+    ///
+    /// Keeping more than one generation makes it possible to recover from a corrupted
+    /// last generation, at the cost of keeping the files of the older segments around.
+    ///
+    /// This setting needs to be set before creating the `IndexWriter`.
+    ///
+    /// # Panics
+    /// If `num_generations` is 0.
+    pub fn set_num_meta_generations_kept(&mut self, num_generations: usize) {
+        assert!(
+            num_generations > 0,
+            "At least one generation of the meta file needs to be kept."
+        );
+        self.num_meta_generations_kept = num_generations;
+    }
+
+    /// Allows creating an `IndexWriter` even though some indexed text fields
+    /// use a tokenizer that is not registered in the `TokenizerManager`.
+    ///
+    /// By default, creating the `IndexWriter` fails with a `SchemaError` in this case.
+    /// In lenient mode, no token is indexed for these fields.
+    ///
+    /// This setting needs to be set before creating the `IndexWriter`.
+    pub fn set_allow_missing_tokenizers(&mut self, allow_missing_tokenizers: bool) {
+        self.allow_missing_tokenizers = allow_missing_tokenizers;
+    }
+
+    /// Returns a `SchemaError` listing the tokenizers used by the indexed
+    /// text fields that are not registered in the `TokenizerManager`.
+    fn check_tokenizers(&self) -> Result<()> {
+        let missing_tokenizers: Vec<String> = self
+            .schema
+            .fields()
+            .iter()
+            .filter_map(|field_entry| match field_entry.field_type() {
+                FieldType::Str(text_options) | FieldType::Json(text_options) => text_options
+                    .get_indexing_options()
+                    .map(|text_indexing_options| text_indexing_options.tokenizer())
+                    .filter(|tokenizer_name| self.tokenizers.get(tokenizer_name).is_none())
+                    .map(|tokenizer_name| {
+                        format!("{:?} (field {:?})", tokenizer_name, field_entry.name())
+                    }),
+                _ => None,
+            })
+            .collect();
+        if missing_tokenizers.is_empty() {
+            return Ok(());
+        }
+        Err(TantivyError::SchemaError(format!(
+            "The following tokenizers are not registered: {}.",
+            missing_tokenizers.join(", ")
+        )))
+    }
+
     /// Creates a new index using the `RAMDirectory`.
     ///
     /// The index will be allocated in anonymous memory.
@@ -139,16 +326,75 @@ impl Index {
 
     /// Creates a new index given an implementation of the trait `Directory`
     pub fn create<Dir: Directory>(dir: Dir, schema: Schema) -> Result<Index> {
+        Index::create_with_settings(dir, schema, IndexSettings::default())
+    }
+
+    /// Creates a new index given an implementation of the trait `Directory`
+    /// and some `IndexSettings`.
+    ///
+    /// The settings are persisted in the meta file, and are used
+    /// for the whole lifetime of the index.
+    ///
+    /// Returns an `InvalidArgument` error if the doc store compressor
+    /// requires a feature that is not enabled, if the boost field
+    /// is not a single-valued `f64` fast field of the schema, if
+    /// the primary key field is not an indexed field of the schema,
+    /// if the postings skip interval is lower than `2`, if the sort field
+    /// is not a single-valued numeric fast field of the schema, if
+    /// the definition of a tokenizer is invalid, or if a similarity
+    /// refers to a field missing from the schema or has invalid parameters.
+    pub fn create_with_settings<Dir: Directory>(
+        dir: Dir,
+        schema: Schema,
+        settings: IndexSettings,
+    ) -> Result<Index> {
+        settings
+            .docstore_compression
+            .check_available()
+            .map_err(|err| TantivyError::InvalidArgument(err.to_string()))?;
+        if let Some(boost_field_name) = settings.boost_field.as_ref() {
+            check_boost_field(&schema, boost_field_name)?;
+        }
+        if let Some(primary_key_field_name) = settings.primary_key_field.as_ref() {
+            check_primary_key_field(&schema, primary_key_field_name)?;
+        }
+        if let Some(sort_by_field) = settings.sort_by_field.as_ref() {
+            check_sort_by_field(&schema, &sort_by_field.field)?;
+        }
+        if settings.postings_skip_interval() < 2 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The postings skip interval must be at least 2, got {}.",
+                settings.postings_skip_interval()
+            )));
+        }
+        for analyzer_def in settings.tokenizers.values() {
+            analyzer_def.build()?;
+        }
+        for (field_name, similarity_def) in &settings.similarities {
+            check_similarity_field(&schema, field_name)?;
+            similarity_def.build()?;
+        }
+        if let Some(doc_token_limit) = settings.doc_token_limit {
+            if doc_token_limit.max_num_tokens == 0 {
+                return Err(TantivyError::InvalidArgument(
+                    "The maximum number of tokens per document must be at least 1.".to_string(),
+                ));
+            }
+        }
         let directory = ManagedDirectory::wrap(dir)?;
-        Index::from_directory(directory, schema)
+        Index::from_directory(directory, schema, settings)
     }
 
     /// Create a new index from a directory.
     ///
     /// This will overwrite existing meta.json
-    fn from_directory(mut directory: ManagedDirectory, schema: Schema) -> Result<Index> {
-        save_new_metas(schema.clone(), directory.borrow_mut())?;
-        let metas = IndexMeta::with_schema(schema);
+    fn from_directory(
+        mut directory: ManagedDirectory,
+        schema: Schema,
+        settings: IndexSettings,
+    ) -> Result<Index> {
+        save_new_metas(schema.clone(), settings.clone(), directory.borrow_mut())?;
+        let metas = IndexMeta::with_schema_and_settings(schema, settings);
         Index::create_from_metas(directory, &metas, SegmentMetaInventory::default())
     }
 
@@ -159,15 +405,66 @@ impl Index {
         inventory: SegmentMetaInventory,
     ) -> Result<Index> {
         let schema = metas.schema.clone();
+        let tokenizers = TokenizerManager::default();
+        for (tokenizer_name, analyzer_def) in &metas.index_settings.tokenizers {
+            tokenizers.register(tokenizer_name, analyzer_def.build()?);
+        }
+        let mut similarities = HashMap::new();
+        for (field_name, similarity_def) in &metas.index_settings.similarities {
+            if let Some(field) = schema.get_field(field_name) {
+                similarities.insert(field, similarity_def.build()?);
+            }
+        }
         Ok(Index {
             directory,
             schema,
-            tokenizers: TokenizerManager::default(),
+            tokenizers,
             executor: Arc::new(Executor::single_thread()),
             inventory,
+            num_meta_generations_kept: DEFAULT_NUM_META_GENERATIONS_KEPT,
+            allow_missing_tokenizers: false,
+            pinned_meta_generations: PinnedMetaGenerations::default(),
+            settings: metas.index_settings.clone(),
+            similarities: Arc::new(RwLock::new(similarities)),
         })
     }
 
+    /// Accessor to the index settings.
+    pub fn settings(&self) -> &IndexSettings {
+        &self.settings
+    }
+
+    /// Returns the field holding the static boost of the documents,
+    /// as defined in the index settings.
+    pub(crate) fn boost_field(&self) -> Option<Field> {
+        self.settings
+            .boost_field
+            .as_ref()
+            .and_then(|boost_field_name| self.schema.get_field(boost_field_name))
+    }
+
+    /// Returns the field whose terms uniquely identify documents,
+    /// as defined in the index settings.
+    pub(crate) fn primary_key_field(&self) -> Option<Field> {
+        self.settings
+            .primary_key_field
+            .as_ref()
+            .and_then(|primary_key_field_name| self.schema.get_field(primary_key_field_name))
+    }
+
+    /// Returns the field by which the documents of the segments are sorted,
+    /// and their order, as defined in the index settings.
+    pub(crate) fn sort_by_field(&self) -> Option<(Field, Order)> {
+        self.settings
+            .sort_by_field
+            .as_ref()
+            .and_then(|sort_by_field| {
+                self.schema
+                    .get_field(&sort_by_field.field)
+                    .map(|field| (field, sort_by_field.order))
+            })
+    }
+
     /// Accessor for the tokenizer manager.
     pub fn tokenizers(&self) -> &TokenizerManager {
         &self.tokenizers
@@ -179,7 +476,7 @@ impl Index {
         let field_type = field_entry.field_type();
         let tokenizer_manager: &TokenizerManager = self.tokenizers();
         let tokenizer_name_opt: Option<BoxedTokenizer> = match field_type {
-            FieldType::Str(text_options) => text_options
+            FieldType::Str(text_options) | FieldType::Json(text_options) => text_options
                 .get_indexing_options()
                 .map(|text_indexing_options| text_indexing_options.tokenizer().to_string())
                 .and_then(|tokenizer_name| tokenizer_manager.get(&tokenizer_name)),
@@ -194,6 +491,33 @@ impl Index {
         }
     }
 
+    /// Sets the similarity used to score the documents matching
+    /// the terms of `field`.
+    ///
+    /// The similarity is shared by all of the clones of the `Index`,
+    /// and applies to the queries created afterwards.
+    /// It overrides the similarity of the field defined in the `IndexSettings`,
+    /// but it is not persisted: the built-in similarities should rather be set
+    /// in the `IndexSettings` when the index is created.
+    /// By default, fields are scored with the [`BM25Similarity`](./query/struct.BM25Similarity.html).
+    pub fn set_similarity<TSimilarity: Similarity>(&self, field: Field, similarity: TSimilarity) {
+        self.similarities
+            .write()
+            .expect("Acquiring the lock should never fail")
+            .insert(field, Arc::new(similarity));
+    }
+
+    /// Returns the similarity used to score the documents matching
+    /// the terms of `field`.
+    pub fn similarity_for_field(&self, field: Field) -> Arc<dyn Similarity> {
+        self.similarities
+            .read()
+            .expect("Acquiring the lock should never fail")
+            .get(&field)
+            .cloned()
+            .unwrap_or_else(|| Arc::new(BM25Similarity::default()))
+    }
+
     /// Create a default `IndexReader` for the given index.
     ///
     /// See [`Index.reader_builder()`](#method.reader_builder).
@@ -248,6 +572,69 @@ impl Index {
         load_metas(self.directory(), &self.inventory)
     }
 
+    /// Returns the generations of the meta file available in the directory,
+    /// in increasing order.
+    ///
+    /// Every commit creates a new generation. The last one is therefore
+    /// the generation of the last commit.
+    ///
+    /// See [`IndexReader::searcher_at_generation`](./struct.IndexReader.html#method.searcher_at_generation).
+    pub fn list_meta_generations(&self) -> Vec<u64> {
+        list_meta_generations(self.directory())
+    }
+
+    /// Pins a generation of the meta file, protecting it and its segments
+    /// from garbage collection.
+    pub(crate) fn pin_meta_generation(&self, generation: u64) -> Result<PinnedMetaGeneration> {
+        // The generation is pinned before being read, so that it cannot be
+        // garbage collected in between.
+        let pinned_generation = self.pinned_meta_generations.pin(generation);
+        let metas = load_metas_from_file(
+            self.directory(),
+            &meta_generation_filepath(generation),
+            &self.inventory,
+        )?;
+        Ok(PinnedMetaGeneration::new(pinned_generation, metas.segments))
+    }
+
+    /// Returns a searcher over the index exactly as it was after a given commit,
+    /// identified by its generation of the meta file.
+    ///
+    /// The retained commits are listed by [`.list_meta_generations()`](#method.list_meta_generations).
+    /// This is the same as
+    /// [`IndexReader::searcher_at_generation`](./struct.IndexReader.html#method.searcher_at_generation),
+    /// without having to create a reader: the searcher uses the search executor of
+    /// the index, and no warmer nor query cache.
+    pub fn searcher_for_commit(&self, commit_id: u64) -> Result<Searcher> {
+        self.pinned_searcher(commit_id, self.search_executor_arc())
+    }
+
+    /// Returns a searcher pinned to a generation of the meta file.
+    pub(crate) fn pinned_searcher(
+        &self,
+        generation: u64,
+        executor: Arc<Executor>,
+    ) -> Result<Searcher> {
+        let pinned_generation = self.pin_meta_generation(generation)?;
+        let segment_readers: Vec<SegmentReader> = pinned_generation
+            .segment_metas()
+            .iter()
+            .map(|segment_meta| SegmentReader::open(&self.segment(segment_meta.clone())))
+            .collect::<Result<_>>()?;
+        Ok(Searcher::pinned(
+            self.schema(),
+            self.clone(),
+            segment_readers,
+            executor,
+            pinned_generation,
+        ))
+    }
+
+    /// Returns the generations of the meta file currently pinned by a `Searcher`.
+    pub(crate) fn pinned_meta_generations(&self) -> Vec<u64> {
+        self.pinned_meta_generations.list()
+    }
+
     /// Open a new index writer. Attempts to acquire a lockfile.
     ///
     /// The lockfile should be deleted on drop, but it is possible
@@ -265,6 +652,8 @@ impl Index {
     ///
     /// # Errors
     /// If the lockfile already exists, returns `Error::DirectoryLockBusy` or an `Error::IOError`.
+    /// If an indexed text field uses a tokenizer that is not registered, returns
+    /// an `Error::SchemaError`, unless `set_allow_missing_tokenizers` was called.
     ///
     /// # Panics
     /// If the heap size per thread is too small, panics.
@@ -273,6 +662,9 @@ impl Index {
         num_threads: usize,
         overall_heap_size_in_bytes: usize,
     ) -> Result<IndexWriter> {
+        if !self.allow_missing_tokenizers {
+            self.check_tokenizers()?;
+        }
         let directory_lock = self
             .directory
             .acquire_lock(&INDEX_WRITER_LOCK)
@@ -305,6 +697,8 @@ impl Index {
     ///
     /// # Errors
     /// If the lockfile already exists, returns `Error::FileAlreadyExists`.
+    /// If an indexed text field uses a tokenizer that is not registered, returns
+    /// an `Error::SchemaError`, unless `set_allow_missing_tokenizers` was called.
     /// # Panics
     /// If the heap size per thread is too small, panics.
     pub fn writer(&self, overall_heap_size_in_bytes: usize) -> Result<IndexWriter> {
@@ -374,6 +768,26 @@ impl Index {
     pub fn validate_checksum(&self) -> Result<HashSet<PathBuf>> {
         self.directory.list_damaged().map_err(Into::into)
     }
+
+    /// Checks the integrity of the last commit, and returns the list of
+    /// problems found. An empty list means the index is valid.
+    ///
+    /// For each segment, this method checks that
+    /// - its files exist and match their checksum,
+    /// - its meta information is consistent with its delete bitset,
+    /// - its postings lists are sorted, and contain as many documents
+    ///   as their term's document frequency,
+    /// - its fast field values are within the bounds of their column,
+    /// - all of the blocks of its doc store can be decompressed.
+    ///
+    /// The content of a segment with a missing or damaged file is not checked.
+    ///
+    /// This walks through all of the data of the index, and is
+    /// therefore expensive. It is typically useful before trusting
+    /// an index restored from a backup.
+    pub fn validate(&self) -> Result<Vec<IntegrityProblem>> {
+        validate_index(self)
+    }
 }
 
 impl fmt::Debug for Index {
@@ -384,13 +798,25 @@ impl fmt::Debug for Index {
 
 #[cfg(test)]
 mod tests {
-    use crate::directory::RAMDirectory;
+    use crate::collector::Count;
+    use crate::core::{list_meta_generations, meta_generation_filepath, META_FILEPATH};
+    use crate::directory::{Directory, RAMDirectory};
+    use crate::query::{AllQuery, QueryParser, TermQuery};
     use crate::schema::Field;
-    use crate::schema::{Schema, INDEXED, TEXT};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STORED, STRING, TEXT};
+    use crate::schema::{TextFieldIndexing, TextOptions};
+    use crate::store::Compressor;
+    use crate::tokenizer::{Language, TextAnalyzerDef, TokenFilterDef, TokenizerDef};
+    use crate::Executor;
     use crate::Index;
     use crate::IndexReader;
+    use crate::IndexSettings;
     use crate::IndexWriter;
     use crate::ReloadPolicy;
+    use crate::TantivyError;
+    use crate::Term;
+    use futures::Future;
+    use std::sync::{Arc, Mutex};
     use std::thread;
     use std::time::Duration;
 
@@ -432,6 +858,217 @@ mod tests {
         assert!(Index::open_or_create(directory, throw_away_schema()).is_ok());
     }
 
+    #[test]
+    fn test_fallback_to_previous_meta_generation() {
+        let mut directory = RAMDirectory::create();
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let mut index = Index::create(directory.clone(), schema).unwrap();
+        index.set_num_meta_generations_kept(3);
+        let mut writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0u64..5u64 {
+            writer.add_document(doc!(field => i));
+            writer.commit().unwrap();
+        }
+        writer.wait_merging_threads().unwrap();
+        let generations = list_meta_generations(index.directory());
+        assert_eq!(generations.len(), 3);
+        let last_generation = *generations.last().unwrap();
+        assert!(!directory.exists(&meta_generation_filepath(0)));
+
+        directory
+            .atomic_write(&META_FILEPATH, b"{\"segments\": [")
+            .unwrap();
+        let index = Index::open(directory.clone()).unwrap();
+        let reader = index.reader().unwrap();
+        assert_eq!(reader.searcher().num_docs(), 5);
+
+        // Truncate the last generation as well.
+        directory
+            .atomic_write(&meta_generation_filepath(last_generation), b"")
+            .unwrap();
+        let index = Index::open(directory).unwrap();
+        let reader = index.reader().unwrap();
+        assert_eq!(reader.searcher().num_docs(), 4);
+    }
+
+    #[test]
+    fn test_index_settings_are_persisted() {
+        let directory = RAMDirectory::create();
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let settings = IndexSettings {
+            docstore_compression: Compressor::None,
+            ..IndexSettings::default()
+        };
+        let index =
+            Index::create_with_settings(directory.clone(), schema_builder.build(), settings)
+                .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field=>"uncompressed"));
+        index_writer.commit().unwrap();
+
+        let index = Index::open(directory).unwrap();
+        assert_eq!(index.settings().docstore_compression, Compressor::None);
+        assert_eq!(
+            index.load_metas().unwrap().index_settings,
+            *index.settings()
+        );
+        let searcher = index.reader().unwrap().searcher();
+        let doc = searcher
+            .segment_reader(0)
+            .get_store_reader()
+            .get(0)
+            .unwrap();
+        assert_eq!(
+            doc.get_first(text_field).unwrap().text(),
+            Some("uncompressed")
+        );
+    }
+
+    #[test]
+    fn test_index_settings_unavailable_compressor() {
+        let settings = IndexSettings {
+            docstore_compression: Compressor::Zstd { level: 3 },
+            ..IndexSettings::default()
+        };
+        let res =
+            Index::create_with_settings(RAMDirectory::create(), throw_away_schema(), settings);
+        assert_eq!(res.is_ok(), cfg!(feature = "zstd"));
+    }
+
+    #[test]
+    fn test_index_settings_invalid_boost_field() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_f64_field("not_fast", INDEXED);
+        schema_builder.add_f64_field("boost", FAST);
+        let schema = schema_builder.build();
+        let create_with_boost_field = |boost_field: &str| {
+            let settings = IndexSettings {
+                boost_field: Some(boost_field.to_string()),
+                ..IndexSettings::default()
+            };
+            Index::create_with_settings(RAMDirectory::create(), schema.clone(), settings)
+        };
+        assert!(create_with_boost_field("missing").is_err());
+        assert!(create_with_boost_field("not_fast").is_err());
+        assert!(create_with_boost_field("boost").is_ok());
+    }
+
+    #[test]
+    fn test_index_settings_invalid_primary_key_field() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("stored", STORED);
+        schema_builder.add_text_field("id", STRING);
+        let schema = schema_builder.build();
+        let create_with_primary_key_field = |primary_key_field: &str| {
+            let settings = IndexSettings {
+                primary_key_field: Some(primary_key_field.to_string()),
+                ..IndexSettings::default()
+            };
+            Index::create_with_settings(RAMDirectory::create(), schema.clone(), settings)
+        };
+        assert!(create_with_primary_key_field("missing").is_err());
+        assert!(create_with_primary_key_field("stored").is_err());
+        assert!(create_with_primary_key_field("id").is_ok());
+    }
+
+    #[test]
+    fn test_index_settings_invalid_postings_skip_interval() {
+        let create_with_skip_interval = |skip_interval: u32| {
+            let settings = IndexSettings {
+                postings_skip_interval: Some(skip_interval),
+                ..IndexSettings::default()
+            };
+            Index::create_with_settings(RAMDirectory::create(), throw_away_schema(), settings)
+        };
+        assert!(create_with_skip_interval(1).is_err());
+        assert!(create_with_skip_interval(2).is_ok());
+    }
+
+    #[test]
+    fn test_index_settings_tokenizers_are_persisted() {
+        let directory = RAMDirectory::create();
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("fr_stem")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let fr_stem = TextAnalyzerDef::new(TokenizerDef::Simple)
+            .filter(TokenFilterDef::LowerCaser)
+            .filter(TokenFilterDef::Stemmer {
+                language: Language::French,
+            });
+        let mut settings = IndexSettings::default();
+        settings.tokenizers.insert("fr_stem".to_string(), fr_stem);
+        {
+            let index =
+                Index::create_with_settings(directory.clone(), schema_builder.build(), settings)
+                    .unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text_field => "Les chevaux"));
+            index_writer.commit().unwrap();
+        }
+        // The tokenizer does not have to be registered again.
+        let index = Index::open(directory).unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("cheval").unwrap();
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 1);
+
+        let mut settings = IndexSettings::default();
+        settings.tokenizers.insert(
+            "ngram".to_string(),
+            TextAnalyzerDef::new(TokenizerDef::Ngram {
+                min_gram: 0,
+                max_gram: 2,
+                prefix_only: false,
+            }),
+        );
+        assert!(
+            Index::create_with_settings(RAMDirectory::create(), throw_away_schema(), settings)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_writer_fails_on_missing_tokenizer() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("missing")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let mut index = Index::create_in_ram(schema_builder.build());
+        match index.writer_with_num_threads(1, 3_000_000) {
+            Err(TantivyError::SchemaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "The following tokenizers are not registered: \"missing\" (field \"text\")."
+                );
+            }
+            _ => panic!("Expected a SchemaError"),
+        }
+        index.set_allow_missing_tokenizers(true);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "hello"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&term_query, &Count).unwrap(), 0);
+    }
+
     #[test]
     fn create_should_wipeoff_existing() {
         let directory = RAMDirectory::create();
@@ -474,6 +1111,105 @@ mod tests {
         test_index_on_commit_reload_policy_aux(field, &mut writer, &reader);
     }
 
+    #[test]
+    fn test_index_reader_warmers() {
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let index = Index::create_in_ram(schema);
+        let warmed_num_docs = Arc::new(Mutex::new(Vec::new()));
+        let warmed_num_docs_clone = warmed_num_docs.clone();
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .num_searchers(2)
+            .warmer(move |searcher| {
+                warmed_num_docs_clone
+                    .lock()
+                    .unwrap()
+                    .push(searcher.num_docs())
+            })
+            .try_into()
+            .unwrap();
+        assert_eq!(&*warmed_num_docs.lock().unwrap(), &[0]);
+        let mut writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        writer.add_document(doc!(field=>1u64));
+        writer.commit().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert_eq!(&*warmed_num_docs.lock().unwrap(), &[0, 1]);
+    }
+
+    #[test]
+    fn test_index_reader_search_executor() {
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let index = Index::create_in_ram(schema);
+        let mut writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0u64..4u64 {
+            writer.add_document(doc!(field=>i));
+            writer.commit().unwrap();
+        }
+        let executor = Arc::new(Executor::multi_thread(2, "search-test").with_max_parallelism(1));
+        let reader = index
+            .reader_builder()
+            .search_executor(executor)
+            .try_into()
+            .unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(index.search_executor().num_threads(), 1);
+        assert_eq!(searcher.search_executor().num_threads(), 2);
+        assert_eq!(searcher.search_executor().max_parallelism(), Some(1));
+        assert_eq!(searcher.segment_readers().len(), 4);
+        assert_eq!(searcher.search(&AllQuery, &Count).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_searcher_at_generation() {
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let index = Index::create_in_ram(schema);
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let mut writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        writer.add_document(doc!(field=>1u64));
+        writer.add_document(doc!(field=>2u64));
+        writer.commit().unwrap();
+        let generation = *index.list_meta_generations().last().unwrap();
+        let pinned_searcher = reader.searcher_at_generation(generation).unwrap();
+        assert_eq!(pinned_searcher.pinned_generation(), Some(generation));
+        assert_eq!(reader.searcher().pinned_generation(), None);
+        writer.delete_term(Term::from_field_u64(field, 1u64));
+        writer.add_document(doc!(field=>3u64));
+        writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        writer.merge(&segment_ids).unwrap().wait().unwrap();
+        writer.garbage_collect_files().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(reader.searcher().num_docs(), 2);
+        assert_eq!(reader.searcher().segment_readers().len(), 1);
+        // The pinned searcher still sees the state of the first commit.
+        assert_eq!(pinned_searcher.num_docs(), 2);
+        let term_query =
+            TermQuery::new(Term::from_field_u64(field, 1u64), IndexRecordOption::Basic);
+        assert_eq!(pinned_searcher.search(&term_query, &Count).unwrap(), 1);
+        // ... and the generation can be pinned again.
+        let other_pinned_searcher = reader.searcher_at_generation(generation).unwrap();
+        assert_eq!(other_pinned_searcher.num_docs(), 2);
+        let commit_searcher = index.searcher_for_commit(generation).unwrap();
+        assert_eq!(commit_searcher.pinned_generation(), Some(generation));
+        assert_eq!(commit_searcher.search(&term_query, &Count).unwrap(), 1);
+        drop(pinned_searcher);
+        drop(other_pinned_searcher);
+        drop(commit_searcher);
+        writer.garbage_collect_files().unwrap();
+        assert!(!index.list_meta_generations().contains(&generation));
+        assert!(reader.searcher_at_generation(generation).is_err());
+        assert!(index.searcher_for_commit(generation).is_err());
+    }
+
     #[cfg(feature = "mmap")]
     mod mmap_specific {
 
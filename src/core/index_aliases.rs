@@ -0,0 +1,167 @@
+use crate::directory::error::OpenReadError;
+use crate::directory::Directory;
+#[cfg(feature = "mmap")]
+use crate::directory::MmapDirectory;
+use crate::error::DataCorruption;
+use crate::reader::AliasedIndexReader;
+use crate::Index;
+use crate::IndexReaderBuilder;
+use crate::Result;
+use crate::TantivyError;
+use std::fmt;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// Opens the index registered under a given target name.
+type IndexOpener = Arc<dyn Fn(&str) -> Result<Index> + Sync + Send>;
+
+/// Registry of aliases, each pointing to an index.
+///
+/// An alias (e.g. `products_current`) is a small pointer file, holding the
+/// name of the target index (e.g. `products_2019_10_17`). Pointing an alias
+/// to another index is atomic, so that a rebuilt index can be swapped in
+/// without the application having to juggle with directory paths.
+///
+/// Readers following an alias are created with [`.reader(...)`](#method.reader).
+///
+/// ```rust,no_run
+/// use tantivy::IndexAliases;
+/// # fn main() -> tantivy::Result<()> {
+/// let mut aliases = IndexAliases::open_in_dir("/data/indexes")?;
+/// let reader = aliases.reader("products_current")?;
+/// // `/data/indexes/products_2019_10_17` is a freshly built index.
+/// aliases.set_alias("products_current", "products_2019_10_17")?;
+/// // The reader now searches `products_2019_10_17`.
+/// reader.reload()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct IndexAliases {
+    directory: Box<dyn Directory>,
+    index_opener: IndexOpener,
+}
+
+impl Clone for IndexAliases {
+    fn clone(&self) -> IndexAliases {
+        IndexAliases {
+            directory: self.directory.box_clone(),
+            index_opener: self.index_opener.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for IndexAliases {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IndexAliases({:?})", self.directory)
+    }
+}
+
+fn alias_filepath(alias: &str) -> Result<PathBuf> {
+    let is_valid = !alias.is_empty()
+        && alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !is_valid {
+        return Err(TantivyError::InvalidArgument(format!(
+            "Invalid alias {:?}. Aliases may only contain ASCII letters, digits, '_' and '-'.",
+            alias
+        )));
+    }
+    Ok(PathBuf::from(format!("{}.alias", alias)))
+}
+
+impl IndexAliases {
+    /// Creates a registry storing its aliases in `directory`, and opening
+    /// the target indexes with `index_opener`.
+    pub fn new<D, F>(directory: D, index_opener: F) -> IndexAliases
+    where
+        D: Directory,
+        F: Fn(&str) -> Result<Index> + Sync + Send + 'static,
+    {
+        IndexAliases {
+            directory: Box::new(directory),
+            index_opener: Arc::new(index_opener),
+        }
+    }
+
+    /// Opens the registry of a directory holding the target indexes as subdirectories.
+    ///
+    /// The aliases are stored in this directory too, and the name
+    /// of a target index is the name of its subdirectory.
+    #[cfg(feature = "mmap")]
+    pub fn open_in_dir<P: AsRef<Path>>(directory_path: P) -> Result<IndexAliases> {
+        let root = directory_path.as_ref().to_path_buf();
+        let mmap_directory = MmapDirectory::open(&root)?;
+        Ok(IndexAliases::new(mmap_directory, move |target: &str| {
+            Index::open_in_dir(root.join(target))
+        }))
+    }
+
+    /// Atomically points `alias` to the index `target`.
+    ///
+    /// The alias is created if it does not exist yet.
+    pub fn set_alias(&mut self, alias: &str, target: &str) -> Result<()> {
+        let alias_filepath = alias_filepath(alias)?;
+        self.directory
+            .atomic_write(&alias_filepath, target.as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the name of the index `alias` points to, or `None`
+    /// if the alias does not exist.
+    pub fn resolve(&self, alias: &str) -> Result<Option<String>> {
+        let alias_filepath = alias_filepath(alias)?;
+        let target_data = match self.directory.atomic_read(&alias_filepath) {
+            Ok(target_data) => target_data,
+            Err(OpenReadError::FileDoesNotExist(_)) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let target = String::from_utf8(target_data).map_err(|_| {
+            DataCorruption::new(
+                alias_filepath.clone(),
+                "The alias file is not valid UTF-8.".to_string(),
+            )
+        })?;
+        Ok(Some(target))
+    }
+
+    /// Opens the index `alias` currently points to.
+    pub fn open_index(&self, alias: &str) -> Result<Index> {
+        let target = self.resolve_existing(alias)?;
+        self.open_target(&target)
+    }
+
+    /// Creates a reader following `alias`, with the default settings.
+    ///
+    /// See [`.reader_with_builder(...)`](#method.reader_with_builder).
+    pub fn reader(&self, alias: &str) -> Result<AliasedIndexReader> {
+        self.reader_with_builder(alias, |index: &Index| index.reader_builder())
+    }
+
+    /// Creates a reader following `alias`.
+    ///
+    /// `reader_builder` creates the builder of the reader of the index the alias
+    /// points to, and is called again whenever the alias is pointed to another index.
+    pub fn reader_with_builder<F>(
+        &self,
+        alias: &str,
+        reader_builder: F,
+    ) -> Result<AliasedIndexReader>
+    where
+        F: Fn(&Index) -> IndexReaderBuilder + Sync + Send + 'static,
+    {
+        AliasedIndexReader::new(self.clone(), alias.to_string(), Box::new(reader_builder))
+    }
+
+    pub(crate) fn resolve_existing(&self, alias: &str) -> Result<String> {
+        self.resolve(alias)?.ok_or_else(|| {
+            TantivyError::InvalidArgument(format!("The alias {:?} does not exist.", alias))
+        })
+    }
+
+    pub(crate) fn open_target(&self, target: &str) -> Result<Index> {
+        (self.index_opener)(target)
+    }
+}
@@ -1,11 +1,16 @@
 use super::SegmentComponent;
+use crate::collector::Order;
 use crate::core::SegmentId;
+use crate::postings::DEFAULT_POSTINGS_SKIP_INTERVAL;
+use crate::query::SimilarityDef;
 use crate::schema::Schema;
+use crate::store::Compressor;
+use crate::tokenizer::TextAnalyzerDef;
 use crate::Opstamp;
 use census::{Inventory, TrackedObject};
 use serde;
 use serde_json;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::path::PathBuf;
 
@@ -180,16 +185,145 @@ impl InnerSegmentMeta {
     }
 }
 
+/// Action taken when a document exceeds its `DocTokenLimit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenLimitPolicy {
+    /// The tokens beyond the limit are not indexed.
+    #[default]
+    Truncate,
+    /// The document is not indexed, and the opstamp of its operation is
+    /// reported by `IndexWriter::take_rejected_opstamps`.
+    /// The other documents are indexed as usual.
+    Error,
+}
+
+/// Limit on the number of tokens indexed for a single document,
+/// over all of its text and json fields.
+///
+/// It prevents a pathological document, e.g. containing a large base64 blob,
+/// from filling the memory of the indexing thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocTokenLimit {
+    /// Maximum number of tokens of a document. It must be at least `1`.
+    pub max_num_tokens: usize,
+    /// Action taken when a document has more tokens.
+    #[serde(default)]
+    pub policy: TokenLimitPolicy,
+}
+
+/// Field by which the documents of the segments are sorted.
+///
+/// The field must be a single-valued `u64`, `i64`, `f64` or `date` fast field.
+/// Documents without a value for the field are sorted as if their value was `0`.
+///
+/// The documents of a block added with `IndexWriter::add_document_block` must all
+/// have the same value, so that they are kept together, including when segments
+/// are merged. Adding a block with different values returns an error.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexSortByField {
+    /// Name of the field.
+    pub field: String,
+    /// Order of the documents.
+    pub order: Order,
+}
+
+/// Settings of an `Index`, fixed when the index is created.
+///
+/// They are persisted in the `meta.json` file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexSettings {
+    /// Compressor used for the blocks of the doc store.
+    #[serde(default)]
+    pub docstore_compression: Compressor,
+    /// Name of a single-valued `f64` fast field holding a static boost
+    /// for each document (pagerank, popularity, ...).
+    ///
+    /// When set, the BM25 score computed by term and phrase queries
+    /// is multiplied by the boost of the document.
+    /// Documents without a value for the field get a boost of `1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost_field: Option<String>,
+    /// Name of an indexed field whose terms uniquely identify documents.
+    ///
+    /// When set, each segment stores the `DocId` associated to each term
+    /// of the field, so that `Searcher::doc_by_key` does not need to
+    /// go through the postings of the term.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_key_field: Option<String>,
+    /// Number of entries of a level of the postings skip data covered by each
+    /// entry of the level above (`8` by default, see `DEFAULT_POSTINGS_SKIP_INTERVAL`).
+    ///
+    /// The level `0` has one entry per block of 128 documents. A smaller interval
+    /// makes seeking in very long posting lists (as done by conjunctive queries)
+    /// read fewer skip entries, at the cost of a larger skip data.
+    /// It must be at least `2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub postings_skip_interval: Option<u32>,
+    /// Definitions of the tokenizers of the index, by name.
+    ///
+    /// They are registered in the `TokenizerManager` of the index whenever it is
+    /// opened, so that the text fields can refer to them in their `TextFieldIndexing`.
+    /// A definition overrides the default tokenizer with the same name, if any.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tokenizers: BTreeMap<String, TextAnalyzerDef>,
+    /// Limit on the number of tokens indexed for each document, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_token_limit: Option<DocTokenLimit>,
+    /// Field by which the documents are sorted within each segment, if any.
+    ///
+    /// Documents are otherwise stored in the order they were added.
+    /// A sorted index compresses better, e.g. log data sorted by timestamp,
+    /// and the top documents for the order of the field come first in each segment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_by_field: Option<IndexSortByField>,
+    /// Similarities used to score the documents matching the terms of a field,
+    /// by field name.
+    ///
+    /// The fields without a similarity are scored with the `BM25Similarity`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub similarities: BTreeMap<String, SimilarityDef>,
+}
+
+impl IndexSettings {
+    /// Returns the postings skip interval, or its default value if it is not set.
+    pub fn postings_skip_interval(&self) -> usize {
+        self.postings_skip_interval
+            .map(|skip_interval| skip_interval as usize)
+            .unwrap_or(DEFAULT_POSTINGS_SKIP_INTERVAL)
+    }
+
+    /// Sorts the documents of the segments by the given field.
+    ///
+    /// See `IndexSortByField`.
+    pub fn sort_by_field(mut self, field_name: &str, order: Order) -> IndexSettings {
+        self.sort_by_field = Some(IndexSortByField {
+            field: field_name.to_string(),
+            order,
+        });
+        self
+    }
+
+    /// Scores the documents matching the terms of the given field with `similarity`.
+    pub fn similarity(mut self, field_name: &str, similarity: SimilarityDef) -> IndexSettings {
+        self.similarities.insert(field_name.to_string(), similarity);
+        self
+    }
+}
+
 /// Meta information about the `Index`.
 ///
 /// This object is serialized on disk in the `meta.json` file.
 /// It keeps information about
+/// * the index settings,
 /// * the searchable segments,
 /// * the index `docstamp`
 /// * the schema
 ///
 #[derive(Clone, Serialize)]
 pub struct IndexMeta {
+    /// `IndexSettings` the index was created with.
+    pub index_settings: IndexSettings,
     /// List of `SegmentMeta` informations associated to each finalized segment of the index.
     pub segments: Vec<SegmentMeta>,
     /// Index `Schema`
@@ -207,6 +341,8 @@ pub struct IndexMeta {
 
 #[derive(Deserialize)]
 struct UntrackedIndexMeta {
+    #[serde(default)]
+    pub index_settings: IndexSettings,
     pub segments: Vec<InnerSegmentMeta>,
     pub schema: Schema,
     pub opstamp: Opstamp,
@@ -217,6 +353,7 @@ struct UntrackedIndexMeta {
 impl UntrackedIndexMeta {
     pub fn track(self, inventory: &SegmentMetaInventory) -> IndexMeta {
         IndexMeta {
+            index_settings: self.index_settings,
             segments: self
                 .segments
                 .into_iter()
@@ -236,7 +373,14 @@ impl IndexMeta {
     /// This new index does not contains any segments.
     /// Opstamp will the value `0u64`.
     pub fn with_schema(schema: Schema) -> IndexMeta {
+        IndexMeta::with_schema_and_settings(schema, IndexSettings::default())
+    }
+
+    /// Create an `IndexMeta` object representing a brand new `Index`
+    /// with the given schema and settings.
+    pub fn with_schema_and_settings(schema: Schema, index_settings: IndexSettings) -> IndexMeta {
         IndexMeta {
+            index_settings,
             segments: vec![],
             schema,
             opstamp: 0u64,
@@ -267,8 +411,10 @@ impl fmt::Debug for IndexMeta {
 #[cfg(test)]
 mod tests {
 
-    use super::IndexMeta;
+    use super::{IndexMeta, IndexSettings, SegmentMetaInventory};
+    use crate::collector::Order;
     use crate::schema::{Schema, TEXT};
+    use crate::store::Compressor;
     use serde_json;
 
     #[test]
@@ -279,12 +425,45 @@ mod tests {
             schema_builder.build()
         };
         let index_metas = IndexMeta {
+            index_settings: IndexSettings {
+                docstore_compression: Compressor::Snappy,
+                ..IndexSettings::default()
+            },
             segments: Vec::new(),
             schema,
             opstamp: 0u64,
             payload: None,
         };
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
-        assert_eq!(json, r#"{"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"opstamp":0}"#);
+        assert_eq!(
+            json,
+            r#"{"index_settings":{"docstore_compression":"snappy"},"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"opstamp":0}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_metas_settings() {
+        let inventory = SegmentMetaInventory::default();
+        let json = r#"{"index_settings":{"docstore_compression":{"zstd":{"level":7}}},"segments":[],"schema":[],"opstamp":3}"#;
+        let metas = IndexMeta::deserialize(json, &inventory).unwrap();
+        assert_eq!(
+            metas.index_settings.docstore_compression,
+            Compressor::Zstd { level: 7 }
+        );
+        // metas written before index settings existed use the default settings.
+        let json = r#"{"segments":[],"schema":[],"opstamp":3}"#;
+        let metas = IndexMeta::deserialize(json, &inventory).unwrap();
+        assert_eq!(metas.index_settings, IndexSettings::default());
+    }
+
+    #[test]
+    fn test_deserialize_metas_sort_by_field() {
+        let inventory = SegmentMetaInventory::default();
+        let json = r#"{"index_settings":{"sort_by_field":{"field":"timestamp","order":"desc"}},"segments":[],"schema":[],"opstamp":3}"#;
+        let metas = IndexMeta::deserialize(json, &inventory).unwrap();
+        assert_eq!(
+            metas.index_settings,
+            IndexSettings::default().sort_by_field("timestamp", Order::Desc)
+        );
     }
 }
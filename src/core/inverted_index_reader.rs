@@ -1,6 +1,6 @@
 use crate::common::BinarySerializable;
 use crate::directory::ReadOnlySource;
-use crate::positions::PositionReader;
+use crate::positions::{PositionLayout, PositionReader};
 use crate::postings::TermInfo;
 use crate::postings::{BlockSegmentPostings, SegmentPostings};
 use crate::schema::FieldType;
@@ -28,6 +28,8 @@ pub struct InvertedIndexReader {
     positions_source: ReadOnlySource,
     positions_idx_source: ReadOnlySource,
     record_option: IndexRecordOption,
+    position_layout: PositionLayout,
+    max_doc_freq: Option<u32>,
     total_num_tokens: u64,
 }
 
@@ -39,6 +41,8 @@ impl InvertedIndexReader {
         positions_source: ReadOnlySource,
         positions_idx_source: ReadOnlySource,
         record_option: IndexRecordOption,
+        position_layout: PositionLayout,
+        max_doc_freq: Option<u32>,
     ) -> InvertedIndexReader {
         let total_num_tokens_data = postings_source.slice(0, 8);
         let mut total_num_tokens_cursor = total_num_tokens_data.as_slice();
@@ -49,6 +53,8 @@ impl InvertedIndexReader {
             positions_source,
             positions_idx_source,
             record_option,
+            position_layout,
+            max_doc_freq,
             total_num_tokens,
         }
     }
@@ -65,6 +71,8 @@ impl InvertedIndexReader {
             positions_source: ReadOnlySource::empty(),
             positions_idx_source: ReadOnlySource::empty(),
             record_option,
+            position_layout: PositionLayout::for_field_type(field_type),
+            max_doc_freq: field_type.max_doc_freq(),
             total_num_tokens: 0u64,
         }
     }
@@ -74,6 +82,18 @@ impl InvertedIndexReader {
         self.termdict.get(term.value_bytes())
     }
 
+    /// Returns true iff the postings of the term were not indexed, because
+    /// it appears in too many documents.
+    ///
+    /// Pruned terms do not match any document, but their `doc_freq` is
+    /// still available.
+    /// See [`TextFieldIndexing::set_max_doc_freq`](../schema/struct.TextFieldIndexing.html#method.set_max_doc_freq).
+    pub fn is_pruned(&self, term_info: &TermInfo) -> bool {
+        self.max_doc_freq
+            .map(|max_doc_freq| term_info.doc_freq > max_doc_freq)
+            .unwrap_or(false)
+    }
+
     /// Return the term dictionary datastructure.
     pub fn terms(&self) -> &TermDictionary {
         &self.termdict
@@ -94,6 +114,10 @@ impl InvertedIndexReader {
         term_info: &TermInfo,
         block_postings: &mut BlockSegmentPostings,
     ) {
+        if self.is_pruned(term_info) {
+            block_postings.reset(0u32, OwnedRead::new(&[][..]));
+            return;
+        }
         let offset = term_info.postings_offset as usize;
         let end_source = self.postings_source.len();
         let postings_slice = self.postings_source.slice(offset, end_source);
@@ -123,6 +147,9 @@ impl InvertedIndexReader {
         term_info: &TermInfo,
         requested_option: IndexRecordOption,
     ) -> BlockSegmentPostings {
+        if self.is_pruned(term_info) {
+            return BlockSegmentPostings::empty();
+        }
         let offset = term_info.postings_offset as usize;
         let postings_data = self.postings_source.slice_from(offset);
         BlockSegmentPostings::from_data(
@@ -143,18 +170,21 @@ impl InvertedIndexReader {
         option: IndexRecordOption,
     ) -> SegmentPostings {
         let block_postings = self.read_block_postings_from_terminfo(term_info, option);
-        let position_stream = {
-            if option.has_positions() {
-                let position_reader = self.positions_source.clone();
-                let skip_reader = self.positions_idx_source.clone();
-                let position_reader =
-                    PositionReader::new(position_reader, skip_reader, term_info.positions_idx);
-                Some(position_reader)
-            } else {
-                None
-            }
+        let position_stream = if option.has_positions() {
+            Some(self.read_positions_from_terminfo(term_info))
+        } else {
+            None
         };
-        SegmentPostings::from_block_postings(block_postings, position_stream)
+        SegmentPostings::from_block_postings(block_postings, position_stream, self.position_layout)
+    }
+
+    /// Returns a reader over the positions of the term, as they are stored.
+    pub(crate) fn read_positions_from_terminfo(&self, term_info: &TermInfo) -> PositionReader {
+        PositionReader::new(
+            self.positions_source.clone(),
+            self.positions_idx_source.clone(),
+            term_info.positions_idx,
+        )
     }
 
     /// Returns the total number of tokens recorded for all documents
@@ -193,4 +223,46 @@ impl InvertedIndexReader {
             .map(|term_info| term_info.doc_freq)
             .unwrap_or(0u32)
     }
+
+    /// Returns the overall number of occurrences of the term (including in
+    /// deleted documents), or `None` if the field does not record term frequencies.
+    ///
+    /// The postings of the term are read to compute it.
+    pub fn total_term_freq(&self, term: &Term) -> Option<u64> {
+        if !self.record_option.has_freq() {
+            return None;
+        }
+        let mut total_term_freq = 0u64;
+        if let Some(mut block_postings) =
+            self.read_block_postings(term, IndexRecordOption::WithFreqs)
+        {
+            while block_postings.advance() {
+                total_term_freq += block_postings
+                    .freqs()
+                    .iter()
+                    .map(|&term_freq| u64::from(term_freq))
+                    .sum::<u64>();
+            }
+        }
+        Some(total_term_freq)
+    }
+
+    /// Returns the number of distinct terms of the field.
+    pub fn num_terms(&self) -> usize {
+        self.termdict.num_terms()
+    }
+}
+
+/// Aggregate statistics of the terms of a field.
+///
+/// See `Searcher::field_term_statistics` and `SegmentReader::field_term_statistics`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FieldTermStatistics {
+    /// Number of documents, including deleted ones.
+    pub num_docs: u64,
+    /// Number of tokens of the field, over all of the documents
+    /// (including deleted ones).
+    pub total_num_tokens: u64,
+    /// Number of distinct terms of the field.
+    pub num_terms: u64,
 }
@@ -0,0 +1,115 @@
+use crate::core::SegmentMeta;
+use crate::directory::{Directory, ManagedDirectory};
+use census::{Inventory, TrackedObject};
+use std::path::{Path, PathBuf};
+
+/// Default number of generations of the meta file that are
+/// protected from garbage collection.
+pub(crate) const DEFAULT_NUM_META_GENERATIONS_KEPT: usize = 1;
+
+const META_GENERATION_PREFIX: &str = "meta.json.";
+
+/// Returns the path of the file storing a given generation of the meta file.
+pub(crate) fn meta_generation_filepath(generation: u64) -> PathBuf {
+    PathBuf::from(format!("{}{}", META_GENERATION_PREFIX, generation))
+}
+
+fn parse_meta_generation(path: &Path) -> Option<u64> {
+    let filename = path.to_str()?;
+    if !filename.starts_with(META_GENERATION_PREFIX) {
+        return None;
+    }
+    filename[META_GENERATION_PREFIX.len()..].parse::<u64>().ok()
+}
+
+/// Lists the generations of the meta file available in the directory,
+/// in increasing order.
+pub(crate) fn list_meta_generations(directory: &ManagedDirectory) -> Vec<u64> {
+    let mut generations: Vec<u64> = directory
+        .list_managed_files()
+        .iter()
+        .filter_map(|path| parse_meta_generation(path))
+        .filter(|&generation| directory.exists(&meta_generation_filepath(generation)))
+        .collect();
+    generations.sort();
+    generations
+}
+
+/// Returns the last `num_generations_kept` generations of the meta file.
+pub(crate) fn kept_meta_generations(
+    directory: &ManagedDirectory,
+    num_generations_kept: usize,
+) -> Vec<u64> {
+    let generations = list_meta_generations(directory);
+    let num_skipped = generations.len().saturating_sub(num_generations_kept);
+    generations[num_skipped..].to_vec()
+}
+
+/// Keeps track of the generations of the meta file that are pinned
+/// by a `Searcher`.
+///
+/// Pinned generations are protected from garbage collection.
+#[derive(Clone, Default)]
+pub(crate) struct PinnedMetaGenerations(Inventory<u64>);
+
+impl PinnedMetaGenerations {
+    pub fn pin(&self, generation: u64) -> TrackedObject<u64> {
+        self.0.track(generation)
+    }
+
+    /// Lists the generations pinned at the time of the call.
+    pub fn list(&self) -> Vec<u64> {
+        self.0
+            .list()
+            .into_iter()
+            .map(|generation| *generation)
+            .collect()
+    }
+}
+
+/// A generation of the meta file pinned by a `Searcher`.
+///
+/// As long as it lives, neither the generation of the meta file nor
+/// the files of its segments are garbage collected.
+#[derive(Clone)]
+pub(crate) struct PinnedMetaGeneration {
+    generation: TrackedObject<u64>,
+    segment_metas: Vec<SegmentMeta>,
+}
+
+impl PinnedMetaGeneration {
+    pub fn new(
+        generation: TrackedObject<u64>,
+        segment_metas: Vec<SegmentMeta>,
+    ) -> PinnedMetaGeneration {
+        PinnedMetaGeneration {
+            generation,
+            segment_metas,
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        *self.generation
+    }
+
+    pub fn segment_metas(&self) -> &[SegmentMeta] {
+        &self.segment_metas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{meta_generation_filepath, parse_meta_generation};
+    use std::path::Path;
+
+    #[test]
+    fn test_parse_meta_generation() {
+        assert_eq!(
+            parse_meta_generation(&meta_generation_filepath(17)),
+            Some(17)
+        );
+        assert_eq!(parse_meta_generation(Path::new("meta.json")), None);
+        assert_eq!(parse_meta_generation(Path::new("meta.json.a")), None);
+        assert_eq!(parse_meta_generation(Path::new("17")), None);
+    }
+}
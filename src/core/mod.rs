@@ -1,29 +1,55 @@
 mod executor;
+mod filter_cache;
+mod global_statistics;
 pub mod index;
+mod index_aliases;
 mod index_meta;
 mod inverted_index_reader;
+mod meta_generation;
+mod multi_searcher;
+mod query_cache;
+mod search_profile;
 pub mod searcher;
 mod segment;
 mod segment_component;
 mod segment_id;
 mod segment_reader;
+mod validation;
 
 pub use self::executor::Executor;
+pub(crate) use self::filter_cache::FilterCache;
+pub use self::global_statistics::{FieldStatistics, GlobalStatistics};
 pub use self::index::Index;
-pub use self::index_meta::{IndexMeta, SegmentMeta, SegmentMetaInventory};
-pub use self::inverted_index_reader::InvertedIndexReader;
-pub use self::searcher::Searcher;
+pub use self::index_aliases::IndexAliases;
+pub use self::index_meta::{
+    DocTokenLimit, IndexMeta, IndexSettings, IndexSortByField, SegmentMeta, SegmentMetaInventory,
+    TokenLimitPolicy,
+};
+pub use self::inverted_index_reader::{FieldTermStatistics, InvertedIndexReader};
+pub(crate) use self::meta_generation::{
+    kept_meta_generations, list_meta_generations, meta_generation_filepath, PinnedMetaGeneration,
+    PinnedMetaGenerations, DEFAULT_NUM_META_GENERATIONS_KEPT,
+};
+pub use self::multi_searcher::MultiSearcher;
+pub(crate) use self::query_cache::QueryCache;
+pub use self::search_profile::{QueryProfile, SearchProfile, SegmentProfile};
+pub use self::searcher::{PartialSearchResult, Searcher, SegmentCompletion};
 pub use self::segment::Segment;
 pub use self::segment::SerializableSegment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
 pub use self::segment_reader::SegmentReader;
+pub(crate) use self::validation::validate_index;
+pub use self::validation::IntegrityProblem;
 
 use once_cell::sync::Lazy;
 use std::path::Path;
 
 /// The meta file contains all the information about the list of segments and the schema
 /// of the index.
+///
+/// Every version of the meta file is also saved as `meta.json.<generation>`.
+/// If `meta.json` gets corrupted, tantivy falls back to the last valid generation.
 pub static META_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new("meta.json"));
 
 /// The managed file contains a list of files that were created by the tantivy
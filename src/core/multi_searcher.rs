@@ -0,0 +1,283 @@
+use crate::collector::Collector;
+use crate::core::Executor;
+use crate::core::SegmentReader;
+use crate::query::Query;
+use crate::query::Weight;
+use crate::schema::Document;
+use crate::schema::{Schema, Term};
+use crate::DocAddress;
+use crate::LeasedItem;
+use crate::Result;
+use crate::Searcher;
+use crate::TantivyError;
+use std::fmt;
+
+/// Searches several indexes (typically the shards of a larger index) at once.
+///
+/// The segments of all of the indexes are searched as if they belonged to a single
+/// index, so that collectors merge their results (top docs, facet counts, ...)
+/// exactly as they merge the results of the segments of a single index.
+///
+/// The `DocAddress`es returned by the collectors are shard-aware: their segment
+/// ordinal is global to the `MultiSearcher`. They can be passed directly to
+/// [`.doc(...)`](#method.doc), or mapped back to a shard and the address of the
+/// document in this shard with [`.shard_address(...)`](#method.shard_address).
+///
+/// All of the indexes must share the same schema.
+///
+/// Each shard computes the statistics used for scoring (e.g. the BM25 idf)
+/// on its own documents only.
+pub struct MultiSearcher {
+    schema: Schema,
+    searchers: Vec<LeasedItem<Searcher>>,
+    // Global ordinal of the first segment of each shard.
+    segment_offsets: Vec<u32>,
+}
+
+impl MultiSearcher {
+    /// Creates a `MultiSearcher` over the searchers of several indexes.
+    ///
+    /// The ordinal of a shard is the position of its searcher in `searchers`.
+    ///
+    /// Returns an error if `searchers` is empty or if
+    /// the indexes do not all share the same schema.
+    pub fn new(searchers: Vec<LeasedItem<Searcher>>) -> Result<MultiSearcher> {
+        let schema = searchers
+            .first()
+            .map(|searcher| searcher.schema().clone())
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(
+                    "A MultiSearcher requires at least one searcher.".to_string(),
+                )
+            })?;
+        if searchers
+            .iter()
+            .any(|searcher| *searcher.schema() != schema)
+        {
+            return Err(TantivyError::InvalidArgument(
+                "The indexes of a MultiSearcher must share the same schema.".to_string(),
+            ));
+        }
+        let mut segment_offsets = Vec::with_capacity(searchers.len());
+        let mut num_segments = 0u32;
+        for searcher in &searchers {
+            segment_offsets.push(num_segments);
+            num_segments += searcher.segment_readers().len() as u32;
+        }
+        Ok(MultiSearcher {
+            schema,
+            searchers,
+            segment_offsets,
+        })
+    }
+
+    /// Access the schema shared by the indexes.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns the number of shards.
+    pub fn num_shards(&self) -> usize {
+        self.searchers.len()
+    }
+
+    /// Returns the searcher of the given shard.
+    pub fn searcher(&self, shard_ord: usize) -> &Searcher {
+        &self.searchers[shard_ord]
+    }
+
+    /// Returns the overall number of documents in the indexes.
+    pub fn num_docs(&self) -> u64 {
+        self.searchers
+            .iter()
+            .map(|searcher| searcher.num_docs())
+            .sum()
+    }
+
+    /// Return the overall number of documents containing
+    /// the given term.
+    pub fn doc_freq(&self, term: &Term) -> u64 {
+        self.searchers
+            .iter()
+            .map(|searcher| searcher.doc_freq(term))
+            .sum()
+    }
+
+    /// Maps a `DocAddress` returned by a search on the `MultiSearcher`
+    /// to the ordinal of the shard holding the document, and to the
+    /// address of the document in this shard.
+    pub fn shard_address(&self, doc_address: DocAddress) -> (usize, DocAddress) {
+        let DocAddress(segment_ord, doc_id) = doc_address;
+        // Shards without any segment share the offset of the next shard,
+        // hence the search for the last shard starting before the segment.
+        let shard_ord = self
+            .segment_offsets
+            .iter()
+            .rposition(|&segment_offset| segment_offset <= segment_ord)
+            .expect("The first shard starts at segment 0.");
+        let local_segment_ord = segment_ord - self.segment_offsets[shard_ord];
+        (shard_ord, DocAddress(local_segment_ord, doc_id))
+    }
+
+    /// Fetches a document given a `DocAddress` returned by a search
+    /// on the `MultiSearcher`.
+    pub fn doc(&self, doc_address: DocAddress) -> Result<Document> {
+        let (shard_ord, local_doc_address) = self.shard_address(doc_address);
+        self.searchers[shard_ord].doc(local_doc_address)
+    }
+
+    /// Runs a query on all of the shards.
+    ///
+    /// The search executor of the searcher of the first shard is used.
+    /// See [`Searcher::search`](./struct.Searcher.html#method.search).
+    pub fn search<C: Collector>(&self, query: &dyn Query, collector: &C) -> Result<C::Fruit> {
+        let executor = self.searchers[0].search_executor();
+        self.search_with_executor(query, collector, executor)
+    }
+
+    /// Same as [`search(...)`](#method.search), but using the given executor
+    /// to search the segments of all of the shards.
+    pub fn search_with_executor<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        executor: &Executor,
+    ) -> Result<C::Fruit> {
+        let scoring_enabled = collector.requires_scoring();
+        let weights: Vec<Box<dyn Weight>> = self
+            .searchers
+            .iter()
+            .map(|searcher| query.weight(searcher, scoring_enabled))
+            .collect::<Result<_>>()?;
+        let segments: Vec<(u32, &dyn Weight, &SegmentReader)> = self
+            .searchers
+            .iter()
+            .zip(weights.iter())
+            .zip(self.segment_offsets.iter())
+            .flat_map(|((searcher, weight), &segment_offset)| {
+                searcher.segment_readers().iter().enumerate().map(
+                    move |(segment_ord, segment_reader)| {
+                        (
+                            segment_offset + segment_ord as u32,
+                            weight.as_ref(),
+                            segment_reader,
+                        )
+                    },
+                )
+            })
+            .collect();
+        let fruits = executor.map(
+            |(segment_ord, weight, segment_reader)| {
+                collector.collect_segment(weight, segment_ord, segment_reader)
+            },
+            segments.into_iter(),
+        )?;
+        collector.merge_fruits(fruits)
+    }
+}
+
+impl fmt::Debug for MultiSearcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.searchers.iter().map(|searcher| &**searcher))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiSearcher;
+    use crate::collector::{Count, FacetCollector, TopDocs};
+    use crate::query::TermQuery;
+    use crate::schema::{Facet, IndexRecordOption, Schema, STORED, TEXT};
+    use crate::DocAddress;
+    use crate::Index;
+    use crate::Term;
+
+    #[test]
+    fn test_multi_searcher() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let facet_field = schema_builder.add_facet_field("facet");
+        let schema = schema_builder.build();
+        let create_shard = |texts: &[&[&str]]| {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            for segment_texts in texts {
+                for text in segment_texts.iter() {
+                    index_writer.add_document(doc!(
+                        text_field => *text,
+                        facet_field => Facet::from(&format!("/lang/{}", text.len())),
+                    ));
+                }
+                index_writer.commit().unwrap();
+            }
+            index.reader().unwrap()
+        };
+        let readers = [
+            create_shard(&[&["a b", "a"], &["c"]]),
+            create_shard(&[]),
+            create_shard(&[&["a a a", "b"]]),
+        ];
+        let multi_searcher =
+            MultiSearcher::new(readers.iter().map(|reader| reader.searcher()).collect()).unwrap();
+        assert_eq!(multi_searcher.num_shards(), 3);
+        assert_eq!(multi_searcher.num_docs(), 5);
+        let term_a = Term::from_field_text(text_field, "a");
+        assert_eq!(multi_searcher.doc_freq(&term_a), 3);
+
+        let query = TermQuery::new(term_a, IndexRecordOption::WithFreqs);
+        assert_eq!(multi_searcher.search(&query, &Count).unwrap(), 3);
+        let top_docs = multi_searcher
+            .search(&query, &TopDocs::with_limit(3))
+            .unwrap();
+        let texts: Vec<String> = top_docs
+            .iter()
+            .map(|&(_, doc_address)| {
+                let doc = multi_searcher.doc(doc_address).unwrap();
+                doc.get_first(text_field)
+                    .unwrap()
+                    .text()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(texts[0], "a a a");
+        assert_eq!(
+            multi_searcher.shard_address(top_docs[0].1),
+            (2, DocAddress(0, 0))
+        );
+
+        let mut facet_collector = FacetCollector::for_field(facet_field);
+        facet_collector.add_facet("/lang");
+        let facet_counts = multi_searcher.search(&query, &facet_collector).unwrap();
+        let facets: Vec<(String, u64)> = facet_counts
+            .get("/lang")
+            .map(|(facet, count)| (facet.to_string(), count))
+            .collect();
+        assert_eq!(
+            facets,
+            vec![
+                ("/lang/1".to_string(), 1),
+                ("/lang/3".to_string(), 1),
+                ("/lang/5".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multi_searcher_different_schemas() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let other_index = Index::create_in_ram(schema_builder.build());
+        let searchers = vec![
+            index.reader().unwrap().searcher(),
+            other_index.reader().unwrap().searcher(),
+        ];
+        assert!(MultiSearcher::new(searchers).is_err());
+        assert!(MultiSearcher::new(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::common::LruCache;
+use std::any::{Any, TypeId};
+use std::sync::Mutex;
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct QueryCacheKey {
+    collector_type: TypeId,
+    // Cache keys of the query and of the collector.
+    description: String,
+}
+
+/// Cache of the results of the queries run on a given version of the index.
+///
+/// Results are identified by the type of the collector, and the cache keys
+/// of the query and of the collector.
+///
+/// The cache is bounded by the approximate size of the results and of their
+/// descriptions. When it is full, the least recently used results are evicted.
+pub(crate) struct QueryCache {
+    entries: Mutex<LruCache<QueryCacheKey, Box<dyn Any + Send>>>,
+}
+
+impl QueryCache {
+    /// Creates a cache whose results take at most approximately `num_bytes`.
+    pub fn with_max_num_bytes(num_bytes: usize) -> QueryCache {
+        QueryCache {
+            entries: Mutex::new(LruCache::with_max_num_bytes(num_bytes)),
+        }
+    }
+
+    fn key<C: 'static>(description: String) -> QueryCacheKey {
+        QueryCacheKey {
+            collector_type: TypeId::of::<C>(),
+            description,
+        }
+    }
+
+    /// Returns a copy of the result cached for the collector type `C`
+    /// and the given description, if any.
+    pub fn get<C: 'static, Fruit: Any + Clone>(&self, description: &str) -> Option<Fruit> {
+        let key = QueryCache::key::<C>(description.to_string());
+        let mut entries = self.entries.lock().expect("Query cache lock poisoned");
+        entries.get(&key)?.downcast_ref::<Fruit>().cloned()
+    }
+
+    /// Caches the result of the collector type `C` for the given description,
+    /// the result taking approximately `num_bytes`.
+    pub fn insert<C: 'static, Fruit: Any + Send>(
+        &self,
+        description: String,
+        fruit: Fruit,
+        num_bytes: usize,
+    ) {
+        let num_bytes = num_bytes + description.len();
+        let key = QueryCache::key::<C>(description);
+        self.entries
+            .lock()
+            .expect("Query cache lock poisoned")
+            .insert(key, Box::new(fruit), num_bytes);
+    }
+
+    /// Returns the number of cached results.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("Query cache lock poisoned")
+            .len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryCache;
+
+    #[test]
+    fn test_query_cache() {
+        let cache = QueryCache::with_max_num_bytes(20);
+        cache.insert::<u8, usize>("a".to_string(), 1, 9);
+        cache.insert::<u8, usize>("b".to_string(), 2, 9);
+        assert_eq!(cache.get::<u8, usize>("a"), Some(1));
+        // Same description, but a different collector type.
+        assert_eq!(cache.get::<u16, usize>("a"), None);
+        // "b" is the least recently used result.
+        cache.insert::<u8, usize>("c".to_string(), 3, 9);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get::<u8, usize>("b"), None);
+        assert_eq!(cache.get::<u8, usize>("a"), Some(1));
+        assert_eq!(cache.get::<u8, usize>("c"), Some(3));
+        // The result is larger than the cache.
+        cache.insert::<u8, usize>("d".to_string(), 4, 20);
+        assert_eq!(cache.get::<u8, usize>("d"), None);
+    }
+
+    #[test]
+    fn test_query_cache_empty() {
+        let cache = QueryCache::with_max_num_bytes(0);
+        cache.insert::<u8, usize>("a".to_string(), 1, 0);
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.get::<u8, usize>("a"), None);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::common::BitSet;
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, SkipResult};
+use crate::fastfield::DeleteBitSet;
+use crate::query::{EmptyScorer, Explanation, Query, Scorer, TermScorer, Weight};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Time spent searching a segment, as recorded by
+/// [`Searcher::search_with_profile`](./struct.Searcher.html#method.search_with_profile).
+#[derive(Clone, Debug, Default)]
+pub struct SegmentProfile {
+    /// Ordinal of the segment.
+    pub segment_ord: u32,
+    /// Time spent searching the segment with `Collector::collect_segment`,
+    /// i.e. creating its scorer, matching and collecting the documents.
+    pub search: Duration,
+}
+
+/// Time spent in a node of the tree of weights of a query, as recorded by
+/// [`Searcher::search_with_profile`](./struct.Searcher.html#method.search_with_profile).
+///
+/// The times of a node include the times of its children.
+///
+/// The term scorers of a subquery are not profiled, so that their parent can
+/// still use its specialized algorithms (e.g. the union of term scorers of a
+/// boolean query). Their matching time is then only included in the time of
+/// their parent.
+#[derive(Clone, Debug, Default)]
+pub struct QueryProfile {
+    /// `Debug` representation of the query.
+    pub query: String,
+    /// Time spent building the weight of the query (e.g. computing its idf).
+    pub weight: Duration,
+    /// Time spent creating the scorers of the query, over all of the segments.
+    pub scorer: Duration,
+    /// Time spent advancing the scorers and scoring the matching documents,
+    /// over all of the segments.
+    ///
+    /// Documents are only scored if the collector requires it.
+    pub matching: Duration,
+    /// Number of documents matched by the scorers, including deleted ones.
+    pub num_matching_docs: u64,
+    /// Profiles of the subqueries.
+    pub children: Vec<QueryProfile>,
+}
+
+/// Report of where the time of a search was spent, as recorded by
+/// [`Searcher::search_with_profile`](./struct.Searcher.html#method.search_with_profile).
+///
+/// Segments are searched concurrently when the search executor is multithreaded,
+/// so the times of the segments may add up to more than the overall time.
+///
+/// Each call to a scorer is timed, which adds an overhead to each matching
+/// document. Profiling is therefore opt-in.
+#[derive(Clone, Debug, Default)]
+pub struct SearchProfile {
+    /// Profile of the query and of its subqueries.
+    pub query: QueryProfile,
+    /// Profile of each segment, indexed by segment ordinal.
+    pub segments: Vec<SegmentProfile>,
+    /// Time spent collecting the matching documents, over all of the segments.
+    pub collection: Duration,
+    /// Time spent merging the results of the segments.
+    pub merge: Duration,
+    /// Time spent retrieving documents from the store.
+    ///
+    /// See [`Searcher::docs_with_profile`](./struct.Searcher.html#method.docs_with_profile).
+    pub doc_retrieval: Duration,
+    /// Number of documents retrieved from the store.
+    pub num_retrieved_docs: u64,
+    /// Overall time of the search and of the retrieval of the documents.
+    pub total: Duration,
+}
+
+fn duration_to_nanos(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + u64::from(duration.subsec_nanos())
+}
+
+/// Node of the tree of weights of a profiled query.
+///
+/// The timings are shared by the scorers of all of the segments.
+struct QueryNode {
+    query: String,
+    weight: Duration,
+    children: Vec<Arc<QueryNode>>,
+    scorer_nanos: AtomicU64,
+    matching_nanos: AtomicU64,
+    collection_nanos: AtomicU64,
+    num_matching_docs: AtomicU64,
+}
+
+impl QueryNode {
+    fn add_scorer(&self, duration: Duration) {
+        self.scorer_nanos
+            .fetch_add(duration_to_nanos(duration), Ordering::Relaxed);
+    }
+
+    fn add_matching(&self, duration: Duration, num_matching_docs: u64) {
+        self.matching_nanos
+            .fetch_add(duration_to_nanos(duration), Ordering::Relaxed);
+        self.num_matching_docs
+            .fetch_add(num_matching_docs, Ordering::Relaxed);
+    }
+
+    fn add_collection(&self, duration: Duration) {
+        self.collection_nanos
+            .fetch_add(duration_to_nanos(duration), Ordering::Relaxed);
+    }
+
+    fn collection(&self) -> Duration {
+        Duration::from_nanos(self.collection_nanos.load(Ordering::Relaxed))
+    }
+
+    fn profile(&self) -> QueryProfile {
+        QueryProfile {
+            query: self.query.clone(),
+            weight: self.weight,
+            scorer: Duration::from_nanos(self.scorer_nanos.load(Ordering::Relaxed)),
+            matching: Duration::from_nanos(self.matching_nanos.load(Ordering::Relaxed)),
+            num_matching_docs: self.num_matching_docs.load(Ordering::Relaxed),
+            children: self.children.iter().map(|child| child.profile()).collect(),
+        }
+    }
+}
+
+/// Records the tree of weights of a query, as they are built
+/// through `Searcher::weight`.
+#[derive(Default)]
+pub(crate) struct QueryProfiler {
+    // Children of each of the nodes being built.
+    building_children: Mutex<Vec<Vec<Arc<QueryNode>>>>,
+    root: Mutex<Option<Arc<QueryNode>>>,
+}
+
+impl QueryProfiler {
+    /// Builds the weight of `query` with `build_weight`, and wraps it
+    /// into a weight recording its timings.
+    pub fn weight<F>(&self, query: &dyn Query, build_weight: F) -> Result<Box<dyn Weight>>
+    where
+        F: FnOnce() -> Result<Box<dyn Weight>>,
+    {
+        self.building_children
+            .lock()
+            .expect("Acquiring the lock should never fail")
+            .push(Vec::new());
+        let start = Instant::now();
+        let weight_res = build_weight();
+        let weight_duration = start.elapsed();
+        let mut building_children = self
+            .building_children
+            .lock()
+            .expect("Acquiring the lock should never fail");
+        let children = building_children
+            .pop()
+            .expect("The children of the node should be on the stack");
+        let weight = weight_res?;
+        let node = Arc::new(QueryNode {
+            query: format!("{:?}", query),
+            weight: weight_duration,
+            children,
+            scorer_nanos: AtomicU64::default(),
+            matching_nanos: AtomicU64::default(),
+            collection_nanos: AtomicU64::default(),
+            num_matching_docs: AtomicU64::default(),
+        });
+        let is_root = match building_children.last_mut() {
+            Some(siblings) => {
+                siblings.push(node.clone());
+                false
+            }
+            None => {
+                *self
+                    .root
+                    .lock()
+                    .expect("Acquiring the lock should never fail") = Some(node.clone());
+                true
+            }
+        };
+        Ok(Box::new(ProfiledWeight {
+            weight,
+            node,
+            is_root,
+        }))
+    }
+
+    /// Returns the profile of the query whose weight was built last, and the
+    /// time spent collecting its documents.
+    pub fn profile(&self) -> (QueryProfile, Duration) {
+        self.root
+            .lock()
+            .expect("Acquiring the lock should never fail")
+            .as_ref()
+            .map(|root| (root.profile(), root.collection()))
+            .unwrap_or_default()
+    }
+}
+
+/// Calls `callback`, adding the time spent in it to `callback_duration`.
+fn timed_callback<T>(
+    callback: &mut dyn FnMut(DocId, Score) -> T,
+    callback_duration: &mut Duration,
+    num_docs: &mut u64,
+    doc: DocId,
+    score: Score,
+) -> T {
+    *num_docs += 1;
+    let start = Instant::now();
+    let res = callback(doc, score);
+    *callback_duration += start.elapsed();
+    res
+}
+
+struct ProfiledWeight {
+    weight: Box<dyn Weight>,
+    node: Arc<QueryNode>,
+    is_root: bool,
+}
+
+impl Weight for ProfiledWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let start = Instant::now();
+        let scorer = self.weight.scorer(reader)?;
+        self.node.add_scorer(start.elapsed());
+        if !self.is_root && (scorer.is::<TermScorer>() || scorer.is::<EmptyScorer>()) {
+            // The parent may downcast the scorer.
+            return Ok(scorer);
+        }
+        Ok(Box::new(ProfiledScorer {
+            scorer,
+            node: self.node.clone(),
+            matching: Duration::default(),
+            num_matching_docs: 0,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        self.weight.explain(reader, doc)
+    }
+
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> Result<()> {
+        let mut callback_duration = Duration::default();
+        let mut num_docs = 0u64;
+        let start = Instant::now();
+        self.weight
+            .for_each_pruning(threshold, reader, &mut |doc, score| {
+                timed_callback(callback, &mut callback_duration, &mut num_docs, doc, score)
+            })?;
+        let matching = start.elapsed().saturating_sub(callback_duration);
+        self.node.add_matching(matching, num_docs);
+        self.node.add_collection(callback_duration);
+        Ok(())
+    }
+
+    fn count(&self, reader: &SegmentReader) -> Result<u32> {
+        let start = Instant::now();
+        let count = self.weight.count(reader)?;
+        self.node.add_matching(start.elapsed(), u64::from(count));
+        Ok(count)
+    }
+}
+
+/// Scorer recording the time spent in the calls to the wrapped scorer.
+///
+/// The timings are added to the node when the scorer is dropped.
+struct ProfiledScorer {
+    scorer: Box<dyn Scorer>,
+    node: Arc<QueryNode>,
+    matching: Duration,
+    num_matching_docs: u64,
+}
+
+impl ProfiledScorer {
+    fn timed<T, F: FnOnce(&mut dyn Scorer) -> T>(&mut self, f: F) -> T {
+        let start = Instant::now();
+        let res = f(self.scorer.as_mut());
+        self.matching += start.elapsed();
+        res
+    }
+}
+
+impl Drop for ProfiledScorer {
+    fn drop(&mut self) {
+        self.node
+            .add_matching(self.matching, self.num_matching_docs);
+    }
+}
+
+impl DocSet for ProfiledScorer {
+    fn advance(&mut self) -> bool {
+        let has_doc = self.timed(|scorer| scorer.advance());
+        if has_doc {
+            self.num_matching_docs += 1;
+        }
+        has_doc
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        let skip_result = self.timed(|scorer| scorer.skip_next(target));
+        if skip_result != SkipResult::End {
+            self.num_matching_docs += 1;
+        }
+        skip_result
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId]) -> usize {
+        let num_docs = self.timed(|scorer| scorer.fill_buffer(buffer));
+        self.num_matching_docs += num_docs as u64;
+        num_docs
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+
+    fn append_to_bitset(&mut self, bitset: &mut BitSet) {
+        self.timed(|scorer| scorer.append_to_bitset(bitset));
+    }
+
+    fn count(&mut self, delete_bitset: &DeleteBitSet) -> u32 {
+        let count = self.timed(|scorer| scorer.count(delete_bitset));
+        self.num_matching_docs += u64::from(count);
+        count
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        let count = self.timed(|scorer| scorer.count_including_deleted());
+        self.num_matching_docs += u64::from(count);
+        count
+    }
+}
+
+impl Scorer for ProfiledScorer {
+    fn score(&mut self) -> Score {
+        self.timed(|scorer| scorer.score())
+    }
+
+    fn for_each(&mut self, callback: &mut dyn FnMut(DocId, Score)) {
+        let mut callback_duration = Duration::default();
+        let mut num_docs = 0u64;
+        let start = Instant::now();
+        self.scorer.for_each(&mut |doc, score| {
+            timed_callback(callback, &mut callback_duration, &mut num_docs, doc, score)
+        });
+        self.matching += start.elapsed().saturating_sub(callback_duration);
+        self.num_matching_docs += num_docs;
+        self.node.add_collection(callback_duration);
+    }
+}
@@ -1,41 +1,109 @@
 use crate::collector::Collector;
+use crate::collector::FruitSize;
 use crate::collector::SegmentCollector;
+use crate::core::search_profile::QueryProfiler;
 use crate::core::Executor;
+use crate::core::FilterCache;
 use crate::core::InvertedIndexReader;
+use crate::core::PinnedMetaGeneration;
+use crate::core::QueryCache;
 use crate::core::SegmentReader;
+use crate::core::{FieldStatistics, FieldTermStatistics, GlobalStatistics};
+use crate::core::{SearchProfile, SegmentProfile};
 use crate::query::Query;
 use crate::query::Scorer;
 use crate::query::Weight;
 use crate::schema::Document;
 use crate::schema::Schema;
 use crate::schema::{Field, Term};
-use crate::space_usage::SearcherSpaceUsage;
+use crate::space_usage::{SearcherMemoryUsage, SearcherSpaceUsage};
 use crate::store::StoreReader;
 use crate::termdict::TermMerger;
 use crate::DocAddress;
 use crate::Index;
 use crate::Result;
+use crate::TantivyError;
+use futures::sync::oneshot;
+use futures::Future;
 use std::fmt;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-fn collect_segment<C: Collector>(
+/// Number of documents scored between two checks of the deadline
+/// of a search with a timeout.
+const DEADLINE_CHECK_INTERVAL: u32 = 64;
+
+fn collect_segment_until<C: Collector>(
     collector: &C,
     weight: &dyn Weight,
     segment_ord: u32,
     segment_reader: &SegmentReader,
-) -> Result<C::Fruit> {
+    deadline: Instant,
+) -> Result<(C::Fruit, SegmentCompletion)> {
+    let mut segment_collector = collector.for_segment(segment_ord, segment_reader)?;
+    if Instant::now() >= deadline {
+        return Ok((segment_collector.harvest(), SegmentCompletion::NotStarted));
+    }
     let mut scorer = weight.scorer(segment_reader)?;
-    let mut segment_collector = collector.for_segment(segment_ord as u32, segment_reader)?;
-    if let Some(delete_bitset) = segment_reader.delete_bitset() {
-        scorer.for_each(&mut |doc, score| {
-            if delete_bitset.is_alive(doc) {
-                segment_collector.collect(doc, score);
+    let delete_bitset_opt = segment_reader.delete_bitset();
+    let mut num_docs_before_deadline_check = DEADLINE_CHECK_INTERVAL;
+    while scorer.advance() {
+        num_docs_before_deadline_check -= 1;
+        if num_docs_before_deadline_check == 0 {
+            if Instant::now() >= deadline {
+                return Ok((segment_collector.harvest(), SegmentCompletion::Interrupted));
             }
-        });
-    } else {
-        scorer.for_each(&mut |doc, score| segment_collector.collect(doc, score));
+            num_docs_before_deadline_check = DEADLINE_CHECK_INTERVAL;
+        }
+        let doc = scorer.doc();
+        let is_alive = match delete_bitset_opt {
+            Some(delete_bitset) => delete_bitset.is_alive(doc),
+            None => true,
+        };
+        if is_alive {
+            segment_collector.collect(doc, scorer.score());
+        }
     }
-    Ok(segment_collector.harvest())
+    Ok((segment_collector.harvest(), SegmentCompletion::Completed))
+}
+
+/// Tells how much of a segment was searched before the deadline
+/// of a search with a timeout.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SegmentCompletion {
+    /// All of the documents of the segment were searched.
+    Completed,
+    /// The search of the segment was interrupted: only some of
+    /// its documents were searched.
+    Interrupted,
+    /// The deadline was exceeded before the search of the segment started.
+    NotStarted,
+}
+
+/// Result of [`Searcher::search_with_timeout`](./struct.Searcher.html#method.search_with_timeout).
+#[derive(Debug)]
+pub struct PartialSearchResult<Fruit> {
+    /// Result of the collector, over the documents searched before the deadline.
+    pub fruit: Fruit,
+    /// True iff some of the documents could not be searched before the deadline.
+    pub timed_out: bool,
+    /// Completion of each segment, indexed by segment ordinal.
+    pub segment_completions: Vec<SegmentCompletion>,
+}
+
+fn collect_segments<C: Collector>(
+    collector: &C,
+    weight: &dyn Weight,
+    segment_readers: &[SegmentReader],
+) -> Result<C::Fruit> {
+    let fruits = segment_readers
+        .iter()
+        .enumerate()
+        .map(|(segment_ord, segment_reader)| {
+            collector.collect_segment(weight, segment_ord as u32, segment_reader)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    collector.merge_fruits(fruits)
 }
 
 /// Holds a list of `SegmentReader`s ready for search.
@@ -48,6 +116,12 @@ pub struct Searcher {
     index: Index,
     segment_readers: Vec<SegmentReader>,
     store_readers: Vec<StoreReader>,
+    executor: Arc<Executor>,
+    query_cache: Option<Arc<QueryCache>>,
+    filter_cache: Option<Arc<FilterCache>>,
+    pinned_generation: Option<PinnedMetaGeneration>,
+    global_statistics: Option<Arc<GlobalStatistics>>,
+    query_profiler: Option<Arc<QueryProfiler>>,
 }
 
 impl Searcher {
@@ -56,6 +130,7 @@ impl Searcher {
         schema: Schema,
         index: Index,
         segment_readers: Vec<SegmentReader>,
+        executor: Arc<Executor>,
     ) -> Searcher {
         let store_readers = segment_readers
             .iter()
@@ -66,14 +141,120 @@ impl Searcher {
             index,
             segment_readers,
             store_readers,
+            executor,
+            query_cache: None,
+            filter_cache: None,
+            pinned_generation: None,
+            global_statistics: None,
+            query_profiler: None,
+        }
+    }
+
+    /// Sets the cache of the results of `.search_cached(...)`.
+    pub(crate) fn with_query_cache(mut self, query_cache: Arc<QueryCache>) -> Searcher {
+        self.query_cache = Some(query_cache);
+        self
+    }
+
+    /// Sets the cache of the documents matching the filter queries.
+    pub(crate) fn with_filter_cache(mut self, filter_cache: Arc<FilterCache>) -> Searcher {
+        self.filter_cache = Some(filter_cache);
+        self
+    }
+
+    /// Returns the cache of the documents matching the filter queries, if any.
+    ///
+    /// See `IndexReaderBuilder::filter_cache_size_in_bytes`.
+    pub(crate) fn filter_cache(&self) -> Option<&Arc<FilterCache>> {
+        self.filter_cache.as_ref()
+    }
+
+    /// Creates a new `Searcher` pinned to a generation of the meta file.
+    pub(crate) fn pinned(
+        schema: Schema,
+        index: Index,
+        segment_readers: Vec<SegmentReader>,
+        executor: Arc<Executor>,
+        pinned_generation: PinnedMetaGeneration,
+    ) -> Searcher {
+        Searcher {
+            pinned_generation: Some(pinned_generation),
+            ..Searcher::new(schema, index, segment_readers, executor)
         }
     }
 
+    /// Returns the generation of the meta file the searcher is pinned to,
+    /// if it was obtained via `IndexReader::searcher_at_generation`.
+    pub fn pinned_generation(&self) -> Option<u64> {
+        self.pinned_generation
+            .as_ref()
+            .map(PinnedMetaGeneration::generation)
+    }
+
+    /// Returns a searcher over the same segments, scoring documents
+    /// with the given corpus-level statistics rather than its own.
+    ///
+    /// See [`GlobalStatistics`](./struct.GlobalStatistics.html).
+    /// The returned searcher does not share the query cache of this searcher,
+    /// but shares its filter cache.
+    pub fn with_global_statistics(&self, global_statistics: Arc<GlobalStatistics>) -> Searcher {
+        Searcher {
+            filter_cache: self.filter_cache.clone(),
+            pinned_generation: self.pinned_generation.clone(),
+            global_statistics: Some(global_statistics),
+            ..Searcher::new(
+                self.schema.clone(),
+                self.index.clone(),
+                self.segment_readers.clone(),
+                self.executor.clone(),
+            )
+        }
+    }
+
+    /// Returns the corpus-level statistics overriding the statistics
+    /// of the searcher for scoring, if any.
+    pub fn global_statistics(&self) -> Option<&GlobalStatistics> {
+        self.global_statistics.as_ref().map(Arc::as_ref)
+    }
+
+    /// Returns the statistics of a field, computed over the segments of the searcher.
+    pub(crate) fn local_field_statistics(&self, field: Field) -> FieldStatistics {
+        let mut field_statistics = FieldStatistics::default();
+        for segment_reader in &self.segment_readers {
+            let inverted_index = segment_reader.inverted_index(field);
+            field_statistics.total_num_tokens += inverted_index.total_num_tokens();
+            field_statistics.num_docs += u64::from(segment_reader.max_doc());
+        }
+        field_statistics
+    }
+
+    /// Returns the statistics of a field used for scoring.
+    pub(crate) fn scoring_field_statistics(&self, field: Field) -> FieldStatistics {
+        self.global_statistics()
+            .and_then(|global_statistics| global_statistics.field_statistics(field))
+            .unwrap_or_else(|| self.local_field_statistics(field))
+    }
+
+    /// Returns the number of documents containing a term used for scoring.
+    pub(crate) fn scoring_doc_freq(&self, term: &Term) -> u64 {
+        self.global_statistics()
+            .and_then(|global_statistics| global_statistics.doc_freq(term))
+            .unwrap_or_else(|| self.doc_freq(term))
+    }
+
     /// Returns the `Index` associated to the `Searcher`
     pub fn index(&self) -> &Index {
         &self.index
     }
 
+    /// Accessor to the search executor used by `.search(...)`.
+    ///
+    /// Unless another executor was set via `IndexReaderBuilder::search_executor`,
+    /// this is the search executor of the index.
+    pub fn search_executor(&self) -> &Executor {
+        self.executor.as_ref()
+    }
+
     /// Fetches a document from tantivy's store given a `DocAddress`.
     ///
     /// The searcher uses the segment ordinal to route the
@@ -84,6 +265,43 @@ impl Searcher {
         store_reader.get(doc_id)
     }
 
+    /// Fetches several documents from tantivy's store, in the order of
+    /// the given `DocAddress`es.
+    ///
+    /// The documents are read in the order of their address, so that
+    /// each store block is decompressed once, however many of the requested
+    /// documents it contains. This is much cheaper than calling `.doc(...)`
+    /// for every document of a page of results.
+    pub fn docs(&self, doc_addresses: &[DocAddress]) -> Result<Vec<Document>> {
+        let mut ords: Vec<usize> = (0..doc_addresses.len()).collect();
+        ords.sort_by_key(|&ord| doc_addresses[ord]);
+        let mut docs: Vec<Option<Document>> = vec![None; doc_addresses.len()];
+        for ord in ords {
+            docs[ord] = Some(self.doc(doc_addresses[ord])?);
+        }
+        Ok(docs.into_iter().flatten().collect())
+    }
+
+    /// Returns the address of the document identified by the given key.
+    ///
+    /// The key is a term of the primary key field defined in the
+    /// `IndexSettings`: each segment then directly associates its terms
+    /// to their `DocId`, so that no `TermQuery` needs to be run.
+    /// Terms of any other indexed field are also accepted, in which case
+    /// their postings are scanned.
+    ///
+    /// If several alive documents contain the key, one of them is returned.
+    pub fn doc_by_key(&self, key: &Term) -> Option<DocAddress> {
+        self.segment_readers
+            .iter()
+            .enumerate()
+            .find_map(|(segment_ord, segment_reader)| {
+                segment_reader
+                    .doc_by_key(key)
+                    .map(|doc| DocAddress(segment_ord as u32, doc))
+            })
+    }
+
     /// Access the schema associated to the index of this searcher.
     pub fn schema(&self) -> &Schema {
         &self.schema
@@ -108,6 +326,42 @@ impl Searcher {
             .sum::<u64>()
     }
 
+    /// Returns the overall number of occurrences of the term (including
+    /// in deleted documents), or `None` if the field does not record term frequencies.
+    ///
+    /// The postings of the term are read to compute it.
+    pub fn total_term_freq(&self, term: &Term) -> Option<u64> {
+        self.segment_readers
+            .iter()
+            .map(|segment_reader| {
+                segment_reader
+                    .inverted_index(term.field())
+                    .total_term_freq(term)
+            })
+            .sum()
+    }
+
+    /// Returns the aggregate statistics of the terms of a field over all of the segments.
+    ///
+    /// Counting the distinct terms requires to merge the term dictionaries of the segments.
+    ///
+    /// # Panics
+    /// If the field is not indexed.
+    pub fn field_term_statistics(&self, field: Field) -> FieldTermStatistics {
+        let mut field_term_statistics = FieldTermStatistics::default();
+        for segment_reader in &self.segment_readers {
+            let segment_statistics = segment_reader.field_term_statistics(field);
+            field_term_statistics.num_docs += segment_statistics.num_docs;
+            field_term_statistics.total_num_tokens += segment_statistics.total_num_tokens;
+        }
+        let field_searcher = self.field(field);
+        let mut terms = field_searcher.terms();
+        while terms.advance() {
+            field_term_statistics.num_terms += 1;
+        }
+        field_term_statistics
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.segment_readers
@@ -133,8 +387,40 @@ impl Searcher {
     ///  Finally, the Collector merges each of the child collectors into itself for result usability
     ///  by the caller.
     pub fn search<C: Collector>(&self, query: &dyn Query, collector: &C) -> Result<C::Fruit> {
-        let executor = self.index.search_executor();
-        self.search_with_executor(query, collector, executor)
+        self.search_with_executor(query, collector, self.search_executor())
+    }
+
+    /// Same as [`search(...)`](#method.search), but the result is cached.
+    ///
+    /// If the reader was built with a query cache (see
+    /// `IndexReaderBuilder::query_cache_size_in_bytes`), the results are shared by all
+    /// of the searchers of a same version of the index, and a new cache is started
+    /// every time the reader is reloaded. Without a query cache, this is
+    /// equivalent to `.search(...)`.
+    ///
+    /// A result is identified by the type of the collector, and the cache keys
+    /// of the query (see `Query::cache_key`) and of the collector (see
+    /// `Collector::cache_key`). If the query or the collector has no cache key,
+    /// the search is always run.
+    pub fn search_cached<C>(&self, query: &dyn Query, collector: &C) -> Result<C::Fruit>
+    where
+        C: Collector + 'static,
+        C::Fruit: Clone + FruitSize,
+    {
+        let query_cache = match self.query_cache {
+            Some(ref query_cache) => query_cache,
+            None => return self.search(query, collector),
+        };
+        let cache_key = match (query.cache_key(), collector.cache_key()) {
+            (Some(query_key), Some(collector_key)) => format!("{} {}", query_key, collector_key),
+            _ => return self.search(query, collector),
+        };
+        if let Some(fruit) = query_cache.get::<C, C::Fruit>(&cache_key) {
+            return Ok(fruit);
+        }
+        let fruit = self.search(query, collector)?;
+        query_cache.insert::<C, C::Fruit>(cache_key, fruit.clone(), fruit.num_bytes());
+        Ok(fruit)
     }
 
     /// Same as [`search(...)`](#method.search) but multithreaded.
@@ -160,16 +446,191 @@ impl Searcher {
         let segment_readers = self.segment_readers();
         let fruits = executor.map(
             |(segment_ord, segment_reader)| {
-                collect_segment(
+                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+            },
+            segment_readers.iter().enumerate(),
+        )?;
+        collector.merge_fruits(fruits)
+    }
+
+    /// Same as [`search(...)`](#method.search), but with a time budget.
+    ///
+    /// Instead of failing when the search exceeds the `timeout`, the search of
+    /// the remaining documents is abandoned, and the collector returns the result
+    /// over the documents searched so far. The result tells whether the search
+    /// timed out, and which segments could be entirely searched.
+    ///
+    /// The deadline is only checked every few documents, so that
+    /// the search may slightly exceed its time budget.
+    pub fn search_with_timeout<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        timeout: Duration,
+    ) -> Result<PartialSearchResult<C::Fruit>> {
+        let deadline = Instant::now() + timeout;
+        let scoring_enabled = collector.requires_scoring();
+        let weight = query.weight(self, scoring_enabled)?;
+        let segment_results = self.executor.map(
+            |(segment_ord, segment_reader)| {
+                collect_segment_until(
                     collector,
                     weight.as_ref(),
                     segment_ord as u32,
                     segment_reader,
+                    deadline,
                 )
             },
-            segment_readers.iter().enumerate(),
+            self.segment_readers.iter().enumerate(),
         )?;
-        collector.merge_fruits(fruits)
+        let (fruits, segment_completions): (Vec<C::Fruit>, Vec<SegmentCompletion>) =
+            segment_results.into_iter().unzip();
+        let timed_out = segment_completions
+            .iter()
+            .any(|&completion| completion != SegmentCompletion::Completed);
+        Ok(PartialSearchResult {
+            fruit: collector.merge_fruits(fruits)?,
+            timed_out,
+            segment_completions,
+        })
+    }
+
+    /// Returns the weight of `query`.
+    ///
+    /// Queries wrapping other queries should build the weights of their subqueries
+    /// with this method rather than with `Query::weight`, so that the subqueries
+    /// are profiled by [`.search_with_profile(...)`](#method.search_with_profile).
+    pub fn weight(&self, query: &dyn Query, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        match self.query_profiler {
+            Some(ref query_profiler) => {
+                query_profiler.weight(query, || query.weight(self, scoring_enabled))
+            }
+            None => query.weight(self, scoring_enabled),
+        }
+    }
+
+    /// Same as [`search(...)`](#method.search), but also records where the
+    /// time of the search was spent: for each node of the tree of weights of
+    /// the query, building its weight, creating its scorers and matching documents,
+    /// and for each segment, the overall time of its search.
+    ///
+    /// The segments are searched as with `.search(...)`, through
+    /// `Collector::collect_segment`, and the documents are only scored if the
+    /// collector requires it.
+    ///
+    /// This is meant to diagnose slow queries. The time spent retrieving the
+    /// documents of the result can be added to the report with
+    /// [`.docs_with_profile(...)`](#method.docs_with_profile).
+    pub fn search_with_profile<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> Result<(C::Fruit, SearchProfile)> {
+        let search_start = Instant::now();
+        let query_profiler = Arc::new(QueryProfiler::default());
+        let profiled_searcher = Searcher {
+            query_cache: self.query_cache.clone(),
+            filter_cache: self.filter_cache.clone(),
+            pinned_generation: self.pinned_generation.clone(),
+            global_statistics: self.global_statistics.clone(),
+            query_profiler: Some(query_profiler.clone()),
+            ..Searcher::new(
+                self.schema.clone(),
+                self.index.clone(),
+                self.segment_readers.clone(),
+                self.executor.clone(),
+            )
+        };
+        let weight = profiled_searcher.weight(query, collector.requires_scoring())?;
+        let segment_results = self.executor.map(
+            |(segment_ord, segment_reader)| {
+                let start = Instant::now();
+                let fruit = collector.collect_segment(
+                    weight.as_ref(),
+                    segment_ord as u32,
+                    segment_reader,
+                )?;
+                let segment_profile = SegmentProfile {
+                    segment_ord: segment_ord as u32,
+                    search: start.elapsed(),
+                };
+                Ok((fruit, segment_profile))
+            },
+            self.segment_readers.iter().enumerate(),
+        )?;
+        let (fruits, segment_profiles): (Vec<C::Fruit>, Vec<SegmentProfile>) =
+            segment_results.into_iter().unzip();
+        let start = Instant::now();
+        let fruit = collector.merge_fruits(fruits)?;
+        let merge = start.elapsed();
+        let (query_profile, collection) = query_profiler.profile();
+        let profile = SearchProfile {
+            query: query_profile,
+            segments: segment_profiles,
+            collection,
+            merge,
+            total: search_start.elapsed(),
+            ..SearchProfile::default()
+        };
+        Ok((fruit, profile))
+    }
+
+    /// Same as [`docs(...)`](#method.docs), but also adds the time spent
+    /// retrieving the documents to the profile of the search.
+    pub fn docs_with_profile(
+        &self,
+        doc_addresses: &[DocAddress],
+        profile: &mut SearchProfile,
+    ) -> Result<Vec<Document>> {
+        let start = Instant::now();
+        let docs = self.docs(doc_addresses)?;
+        let elapsed = start.elapsed();
+        profile.doc_retrieval += elapsed;
+        profile.total += elapsed;
+        profile.num_retrieved_docs += docs.len() as u64;
+        Ok(docs)
+    }
+
+    /// Runs a query asynchronously, returning a `Future` of the result of the collector.
+    ///
+    /// The weight of the query is created in the caller thread, and the segments
+    /// are then searched one after the other in a task spawned on the search
+    /// executor (see [`.search_executor()`](#method.search_executor)), so that
+    /// the caller thread, typically the thread of an event loop, is not blocked
+    /// while the search is running. Searching several queries concurrently
+    /// therefore requires a multithread executor.
+    ///
+    /// If the search executor is single thread, the search is run
+    /// before this method returns.
+    pub fn search_async<C>(
+        &self,
+        query: &dyn Query,
+        collector: C,
+    ) -> impl Future<Item = C::Fruit, Error = TantivyError>
+    where
+        C: Collector + Send + 'static,
+    {
+        let (fruit_sender, fruit_receiver) = oneshot::channel();
+        match query.weight(self, collector.requires_scoring()) {
+            Ok(weight) => {
+                let segment_readers = self.segment_readers.clone();
+                self.executor.spawn(move || {
+                    let fruit = collect_segments(&collector, weight.as_ref(), &segment_readers);
+                    // The future may have been dropped.
+                    let _ = fruit_sender.send(fruit);
+                });
+            }
+            Err(err) => {
+                let _ = fruit_sender.send(Err(err));
+            }
+        }
+        fruit_receiver.then(|fruit_res| {
+            fruit_res.unwrap_or_else(|_| {
+                Err(TantivyError::ErrorInThread(
+                    "The search task was canceled.".to_string(),
+                ))
+            })
+        })
     }
 
     /// Return the field searcher associated to a `Field`.
@@ -190,6 +651,22 @@ impl Searcher {
         }
         space_usage
     }
+
+    /// Returns the estimated memory footprint of the searcher, broken down by
+    /// segment and component.
+    ///
+    /// See [`SearcherMemoryUsage`](../space_usage/struct.SearcherMemoryUsage.html).
+    pub fn memory_usage(&self) -> SearcherMemoryUsage {
+        let mut memory_usage = SearcherMemoryUsage::new();
+        for (segment_reader, store_reader) in
+            self.segment_readers.iter().zip(self.store_readers.iter())
+        {
+            let mut segment_memory_usage = segment_reader.memory_usage();
+            segment_memory_usage.store_cache = store_reader.cache_num_bytes();
+            memory_usage.add_segment(segment_memory_usage);
+        }
+        memory_usage
+    }
 }
 
 pub struct FieldSearcher {
@@ -223,3 +700,430 @@ impl fmt::Debug for Searcher {
         write!(f, "Searcher({:?})", segment_ids)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SegmentCompletion;
+    use crate::collector::{Collector, Count, SegmentCollector, TopDocs};
+    use crate::query::{AllQuery, BooleanQuery, FilterQuery, Occur, Query, TermQuery};
+    use crate::schema::{Document, IndexRecordOption, Schema, FAST, STORED, STRING, TEXT};
+    use crate::DocAddress;
+    use crate::DocId;
+    use crate::Executor;
+    use crate::Index;
+    use crate::ReloadPolicy;
+    use crate::Result;
+    use crate::Score;
+    use crate::SegmentLocalId;
+    use crate::SegmentReader;
+    use crate::Term;
+    use futures::Future;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Counts the documents, taking a millisecond per document.
+    struct SlowCount;
+
+    struct SlowSegmentCount(usize);
+
+    impl Collector for SlowCount {
+        type Fruit = usize;
+        type Child = SlowSegmentCount;
+
+        fn for_segment(&self, _: SegmentLocalId, _: &SegmentReader) -> Result<SlowSegmentCount> {
+            Ok(SlowSegmentCount(0))
+        }
+
+        fn requires_scoring(&self) -> bool {
+            false
+        }
+
+        fn merge_fruits(&self, segment_counts: Vec<usize>) -> Result<usize> {
+            Ok(segment_counts.into_iter().sum())
+        }
+    }
+
+    impl SegmentCollector for SlowSegmentCount {
+        type Fruit = usize;
+
+        fn collect(&mut self, _: DocId, _: Score) {
+            thread::sleep(Duration::from_millis(1));
+            self.0 += 1;
+        }
+
+        fn harvest(self) -> usize {
+            self.0
+        }
+    }
+
+    /// Counts the documents, and the number of searches it was used for.
+    struct CountingCount(Arc<AtomicUsize>);
+
+    impl Collector for CountingCount {
+        type Fruit = usize;
+        type Child = <Count as Collector>::Child;
+
+        fn for_segment(
+            &self,
+            segment_ord: SegmentLocalId,
+            segment_reader: &SegmentReader,
+        ) -> Result<Self::Child> {
+            Count.for_segment(segment_ord, segment_reader)
+        }
+
+        fn requires_scoring(&self) -> bool {
+            false
+        }
+
+        fn merge_fruits(&self, segment_counts: Vec<usize>) -> Result<usize> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Count.merge_fruits(segment_counts)
+        }
+
+        fn cache_key(&self) -> Option<String> {
+            Some("CountingCount".to_string())
+        }
+    }
+
+    #[test]
+    fn test_search_cached() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(field => "a"));
+        index_writer.commit().unwrap();
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .num_searchers(2)
+            .query_cache_size_in_bytes(10_000)
+            .try_into()
+            .unwrap();
+        let num_searches = Arc::new(AtomicUsize::new(0));
+        let collector = CountingCount(num_searches.clone());
+        let term_query =
+            TermQuery::new(Term::from_field_text(field, "a"), IndexRecordOption::Basic);
+        {
+            let searcher = reader.searcher();
+            let other_searcher = reader.searcher();
+            assert_eq!(searcher.search_cached(&AllQuery, &collector).unwrap(), 1);
+            assert_eq!(
+                other_searcher.search_cached(&AllQuery, &collector).unwrap(),
+                1
+            );
+            assert_eq!(num_searches.load(Ordering::SeqCst), 1);
+            assert_eq!(searcher.search_cached(&term_query, &collector).unwrap(), 1);
+            assert_eq!(num_searches.load(Ordering::SeqCst), 2);
+            assert_eq!(searcher.search_cached(&term_query, &Count).unwrap(), 1);
+        }
+        index_writer.add_document(doc!(field => "a"));
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.search_cached(&AllQuery, &collector).unwrap(), 2);
+        assert_eq!(num_searches.load(Ordering::SeqCst), 3);
+        // Without a query cache, the search is always run.
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.search_cached(&AllQuery, &collector).unwrap(), 2);
+        assert_eq!(searcher.search_cached(&AllQuery, &collector).unwrap(), 2);
+        assert_eq!(num_searches.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_search_cached_keys() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_text_field("text", STRING);
+        let price = schema_builder.add_u64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0u64..4u64 {
+            index_writer.add_document(doc!(field => "a", price => i));
+        }
+        index_writer.commit().unwrap();
+        let reader = index
+            .reader_builder()
+            .query_cache_size_in_bytes(10_000)
+            .try_into()
+            .unwrap();
+        let searcher = reader.searcher();
+        // The anchor of the pages is part of the key of `TopDocs`.
+        let first_page = searcher
+            .search_cached(&AllQuery, &TopDocs::with_limit(2))
+            .unwrap();
+        assert_eq!(
+            first_page,
+            vec![(1.0, DocAddress(0, 0)), (1.0, DocAddress(0, 1))]
+        );
+        let second_page = searcher
+            .search_cached(
+                &AllQuery,
+                &TopDocs::with_limit(2).search_after(1.0, DocAddress(0, 1)),
+            )
+            .unwrap();
+        assert_eq!(
+            second_page,
+            vec![(1.0, DocAddress(0, 2)), (1.0, DocAddress(0, 3))]
+        );
+        // Filters over closures have no cache key: their results are not cached.
+        let num_searches = Arc::new(AtomicUsize::new(0));
+        let collector = CountingCount(num_searches.clone());
+        let price_filter = |max_price: u64| {
+            FilterQuery::new(Box::new(AllQuery), move |segment_reader: &SegmentReader| {
+                let price_reader = segment_reader.fast_fields().u64(price).unwrap();
+                move |doc: DocId| price_reader.get(doc) < max_price
+            })
+        };
+        assert!(price_filter(1).cache_key().is_none());
+        assert_eq!(
+            searcher
+                .search_cached(&price_filter(1), &collector)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            searcher
+                .search_cached(&price_filter(3), &collector)
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            searcher
+                .search_cached(&price_filter(3), &collector)
+                .unwrap(),
+            3
+        );
+        assert_eq!(num_searches.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_docs() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("num", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0u64..1_000u64 {
+            index_writer.add_document(doc!(field => i));
+            if i % 400 == 0 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let mut doc_addresses = Vec::new();
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            for doc in (0..segment_reader.max_doc()).rev().step_by(7) {
+                doc_addresses.push(DocAddress(segment_ord as u32, doc));
+            }
+        }
+        doc_addresses.push(doc_addresses[3]);
+        doc_addresses.reverse();
+        doc_addresses.swap(0, 100);
+        let nums = |docs: Vec<Document>| -> Vec<u64> {
+            docs.iter()
+                .map(|doc| doc.get_first(field).unwrap().u64_value())
+                .collect()
+        };
+        let expected_docs = doc_addresses
+            .iter()
+            .map(|&doc_address| searcher.doc(doc_address).unwrap())
+            .collect();
+        assert_eq!(
+            nums(searcher.docs(&doc_addresses).unwrap()),
+            nums(expected_docs)
+        );
+        assert!(searcher.docs(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_with_timeout() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for _ in 0..200 {
+            index_writer.add_document(doc!(field => "a"));
+        }
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(field => "a"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let large_segment_ord = searcher
+            .segment_readers()
+            .iter()
+            .position(|segment_reader| segment_reader.num_docs() == 200)
+            .unwrap();
+
+        let result = searcher
+            .search_with_timeout(&AllQuery, &Count, Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(result.fruit, 201);
+        assert!(!result.timed_out);
+        assert_eq!(
+            result.segment_completions,
+            vec![SegmentCompletion::Completed; 2]
+        );
+
+        let result = searcher
+            .search_with_timeout(&AllQuery, &SlowCount, Duration::from_millis(10))
+            .unwrap();
+        assert!(result.timed_out);
+        assert!(result.fruit < 200);
+        if large_segment_ord == 0 {
+            assert_eq!(
+                result.segment_completions,
+                vec![
+                    SegmentCompletion::Interrupted,
+                    SegmentCompletion::NotStarted
+                ]
+            );
+        }
+
+        let result = searcher
+            .search_with_timeout(&AllQuery, &Count, Duration::from_secs(0))
+            .unwrap();
+        assert!(result.timed_out);
+        assert_eq!(result.fruit, 0);
+        assert_eq!(
+            result.segment_completions,
+            vec![SegmentCompletion::NotStarted; 2]
+        );
+    }
+
+    #[test]
+    fn test_search_async() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for text in &["a", "b", "a"] {
+            index_writer.add_document(doc!(field => *text));
+            index_writer.commit().unwrap();
+        }
+        for &num_threads in &[1, 2] {
+            let executor = if num_threads == 1 {
+                Executor::single_thread()
+            } else {
+                Executor::multi_thread(num_threads, "search-test")
+            };
+            let reader = index
+                .reader_builder()
+                .search_executor(Arc::new(executor))
+                .try_into()
+                .unwrap();
+            let searcher = reader.searcher();
+            let count_future = searcher.search_async(&AllQuery, Count);
+            let term_query =
+                TermQuery::new(Term::from_field_text(field, "a"), IndexRecordOption::Basic);
+            let top_docs_future = searcher.search_async(&term_query, TopDocs::with_limit(10));
+            assert_eq!(count_future.wait().unwrap(), 3);
+            assert_eq!(top_docs_future.wait().unwrap().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_search_with_profile() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for text in &["a", "b", "a"] {
+            index_writer.add_document(doc!(text_field => *text));
+        }
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.delete_term(Term::from_field_text(text_field, "b"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        let (top_docs, mut profile) = searcher
+            .search_with_profile(&query, &TopDocs::with_limit(10))
+            .unwrap();
+        assert_eq!(top_docs.len(), 3);
+        assert_eq!(profile.segments.len(), 2);
+        assert!(profile.query.query.starts_with("TermQuery"));
+        assert_eq!(profile.query.num_matching_docs, 3);
+        assert!(profile.query.matching + profile.collection > Duration::default());
+
+        let boolean_query = BooleanQuery::from(vec![
+            (Occur::Should, Box::new(query.clone()) as Box<dyn Query>),
+            (Occur::Should, Box::new(AllQuery) as Box<dyn Query>),
+        ]);
+        let (count, boolean_profile) = searcher
+            .search_with_profile(&boolean_query, &Count)
+            .unwrap();
+        assert_eq!(count, 3);
+        assert!(boolean_profile.query.query.starts_with("BooleanQuery"));
+        // The deleted document matches, but is not collected.
+        assert_eq!(boolean_profile.query.num_matching_docs, 4);
+        let children = &boolean_profile.query.children;
+        assert_eq!(children.len(), 2);
+        assert!(children[0].query.starts_with("TermQuery"));
+        assert_eq!(children[1].query, "AllQuery");
+        assert_eq!(children[1].num_matching_docs, 4);
+
+        let doc_addresses: Vec<DocAddress> = top_docs
+            .iter()
+            .map(|&(_, doc_address)| doc_address)
+            .collect();
+        let total = profile.total;
+        let docs = searcher
+            .docs_with_profile(&doc_addresses, &mut profile)
+            .unwrap();
+        assert_eq!(docs.len(), 3);
+        assert_eq!(profile.num_retrieved_docs, 3);
+        assert_eq!(profile.total, total + profile.doc_retrieval);
+    }
+
+    #[test]
+    fn test_field_term_statistics() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a b a", tag_field => "x"));
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(text_field => "a c", tag_field => "x"));
+        index_writer.add_document(doc!(text_field => "d"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let term_a = Term::from_field_text(text_field, "a");
+        assert_eq!(searcher.doc_freq(&term_a), 2);
+        assert_eq!(searcher.total_term_freq(&term_a), Some(3));
+        assert_eq!(
+            searcher.total_term_freq(&Term::from_field_text(text_field, "z")),
+            Some(0)
+        );
+        // The tag field does not record term frequencies.
+        assert_eq!(
+            searcher.total_term_freq(&Term::from_field_text(tag_field, "x")),
+            None
+        );
+
+        let field_term_statistics = searcher.field_term_statistics(text_field);
+        assert_eq!(field_term_statistics.num_docs, 3);
+        assert_eq!(field_term_statistics.total_num_tokens, 6);
+        assert_eq!(field_term_statistics.num_terms, 4);
+        let mut segment_num_terms: Vec<u64> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.field_term_statistics(text_field).num_terms)
+            .collect();
+        segment_num_terms.sort();
+        assert_eq!(segment_num_terms, vec![2, 3]);
+        assert_eq!(searcher.field_term_statistics(tag_field).num_terms, 1);
+    }
+}
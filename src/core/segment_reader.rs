@@ -1,21 +1,27 @@
 use crate::common::CompositeFile;
 use crate::common::HasLen;
-use crate::core::InvertedIndexReader;
 use crate::core::Segment;
 use crate::core::SegmentComponent;
 use crate::core::SegmentId;
+use crate::core::{FieldTermStatistics, InvertedIndexReader};
 use crate::directory::ReadOnlySource;
 use crate::fastfield::DeleteBitSet;
 use crate::fastfield::FacetReader;
+use crate::fastfield::FastFieldReader;
 use crate::fastfield::FastFieldReaders;
+use crate::fastfield::StrFastFieldReader;
 use crate::fieldnorm::FieldNormReader;
+use crate::positions::PositionLayout;
 use crate::schema::Field;
 use crate::schema::FieldType;
 use crate::schema::Schema;
-use crate::space_usage::SegmentSpaceUsage;
+use crate::schema::{IndexRecordOption, Term};
+use crate::space_usage::{SegmentMemoryUsage, SegmentSpaceUsage};
 use crate::store::StoreReader;
 use crate::termdict::TermDictionary;
 use crate::DocId;
+use crate::DocSet;
+use crate::Opstamp;
 use crate::Result;
 use fail::fail_point;
 use std::collections::HashMap;
@@ -41,6 +47,7 @@ pub struct SegmentReader {
     inv_idx_reader_cache: Arc<RwLock<HashMap<Field, Arc<InvertedIndexReader>>>>,
 
     segment_id: SegmentId,
+    delete_opstamp: Option<Opstamp>,
     max_doc: DocId,
     num_docs: DocId,
 
@@ -49,6 +56,8 @@ pub struct SegmentReader {
     positions_composite: CompositeFile,
     positions_idx_composite: CompositeFile,
     fast_fields_readers: Arc<FastFieldReaders>,
+    boost_reader: Option<FastFieldReader<f64>>,
+    primary_key_reader: Option<(Field, FastFieldReader<u64>)>,
     fieldnorms_composite: CompositeFile,
 
     store_source: ReadOnlySource,
@@ -59,8 +68,9 @@ pub struct SegmentReader {
 impl SegmentReader {
     /// Returns the highest document id ever attributed in
     /// this segment + 1.
-    /// Today, `tantivy` does not handle deletes, so it happens
-    /// to also be the number of documents in the index.
+    ///
+    /// Deleted documents are counted. If the segment has no deletes,
+    /// it is also the number of documents in the segment.
     pub fn max_doc(&self) -> DocId {
         self.max_doc
     }
@@ -68,8 +78,8 @@ impl SegmentReader {
     /// Returns the number of documents.
     /// Deleted documents are not counted.
     ///
-    /// Today, `tantivy` does not handle deletes so max doc and
-    /// num_docs are the same.
+    /// Documents deleted with `IndexWriter::delete_term` are
+    /// accounted for once the deletion is committed.
     pub fn num_docs(&self) -> DocId {
         self.num_docs
     }
@@ -106,6 +116,43 @@ impl SegmentReader {
         &self.fast_fields_readers
     }
 
+    /// Accessor to the reader of the static boost of the documents.
+    ///
+    /// Returns `None` if no boost field was defined in the `IndexSettings`.
+    pub fn boost_reader(&self) -> Option<&FastFieldReader<f64>> {
+        self.boost_reader.as_ref()
+    }
+
+    /// Returns the last alive document containing the given term,
+    /// typically the value of the primary key of a document.
+    ///
+    /// If the field of the term is the primary key field defined in the
+    /// `IndexSettings`, the document is looked up in the column associating
+    /// a `DocId` to each term ordinal. Otherwise, the postings of the term
+    /// are scanned.
+    pub fn doc_by_key(&self, key: &Term) -> Option<DocId> {
+        let inverted_index = self.inverted_index(key.field());
+        if let Some((_, primary_key_reader)) = self
+            .primary_key_reader
+            .as_ref()
+            .filter(|(field, _)| *field == key.field())
+        {
+            let term_ord = inverted_index.terms().term_ord(key.value_bytes())?;
+            let doc = primary_key_reader.get_u64(term_ord) as DocId;
+            if !self.is_deleted(doc) {
+                return Some(doc);
+            }
+        }
+        let mut postings = inverted_index.read_postings(key, IndexRecordOption::Basic)?;
+        let mut last_doc = None;
+        while postings.advance() {
+            if !self.is_deleted(postings.doc()) {
+                last_doc = Some(postings.doc());
+            }
+        }
+        last_doc
+    }
+
     /// Accessor to the `FacetReader` associated to a given `Field`.
     pub fn facet_reader(&self, field: Field) -> Option<FacetReader> {
         let field_entry = self.schema.get_field_entry(field);
@@ -119,6 +166,25 @@ impl SegmentReader {
         Some(facet_reader)
     }
 
+    /// Accessor to the `StrFastFieldReader` associated to a given `Field`.
+    ///
+    /// Returns `None` if the field is not a text field declared as fast.
+    pub fn str_fast_field_reader(&self, field: Field) -> Option<StrFastFieldReader> {
+        let field_type = self.schema.get_field_entry(field).field_type();
+        match *field_type {
+            FieldType::Str(ref text_options) if text_options.is_fast() => {}
+            _ => return None,
+        }
+        let term_ords_reader = self.fast_fields().u64s(field)?;
+        // The term dictionary is missing if the field is not indexed.
+        let termdict = self
+            .termdict_composite
+            .open_read(field)
+            .map(|termdict_source| TermDictionary::from_source(&termdict_source))
+            .unwrap_or_else(|| TermDictionary::empty(field_type));
+        Some(StrFastFieldReader::new(term_ords_reader, termdict))
+    }
+
     /// Accessor to the segment's `Field norms`'s reader.
     ///
     /// Field norms are the length (in tokens) of the fields.
@@ -128,7 +194,12 @@ impl SegmentReader {
     /// the `.fieldnorm` file of the segment.
     pub fn get_fieldnorms_reader(&self, field: Field) -> FieldNormReader {
         if let Some(fieldnorm_source) = self.fieldnorms_composite.open_read(field) {
-            FieldNormReader::open(fieldnorm_source)
+            match self.fieldnorms_composite.open_read_with_idx(field, 1) {
+                Some(exact_fieldnorm_source) => {
+                    FieldNormReader::open_with_exact(fieldnorm_source, exact_fieldnorm_source)
+                }
+                None => FieldNormReader::open(fieldnorm_source),
+            }
         } else {
             let field_name = self.schema.get_field_name(field);
             let err_msg = format!(
@@ -149,6 +220,12 @@ impl SegmentReader {
         let termdict_source = segment.open_read(SegmentComponent::TERMS)?;
         let termdict_composite = CompositeFile::open(&termdict_source)?;
 
+        let primary_key_reader = segment.index().primary_key_field().and_then(|field| {
+            termdict_composite
+                .open_read_with_idx(field, 1)
+                .map(|source| (field, FastFieldReader::open(source)))
+        });
+
         let store_source = segment.open_read(SegmentComponent::STORE)?;
 
         fail_point!("SegmentReader::open#middle");
@@ -176,8 +253,15 @@ impl SegmentReader {
 
         let fast_fields_data = segment.open_read(SegmentComponent::FASTFIELDS)?;
         let fast_fields_composite = CompositeFile::open(&fast_fields_data)?;
-        let fast_field_readers =
-            Arc::new(FastFieldReaders::load_all(&schema, &fast_fields_composite)?);
+        let fast_field_readers = Arc::new(FastFieldReaders::load_all(
+            &schema,
+            &fast_fields_composite,
+            segment.meta().max_doc(),
+        )?);
+        let boost_reader = segment
+            .index()
+            .boost_field()
+            .and_then(|boost_field| fast_field_readers.f64(boost_field));
 
         let fieldnorms_data = segment.open_read(SegmentComponent::FIELDNORMS)?;
         let fieldnorms_composite = CompositeFile::open(&fieldnorms_data)?;
@@ -196,8 +280,11 @@ impl SegmentReader {
             termdict_composite,
             postings_composite,
             fast_fields_readers: fast_field_readers,
+            boost_reader,
+            primary_key_reader,
             fieldnorms_composite,
             segment_id: segment.id(),
+            delete_opstamp: segment.meta().delete_opstamp(),
             store_source,
             delete_bitset_opt,
             positions_composite,
@@ -264,6 +351,8 @@ impl SegmentReader {
             positions_source,
             positions_idx_source,
             record_option,
+            PositionLayout::for_field_type(field_type),
+            field_type.max_doc_freq(),
         ));
 
         // by releasing the lock in between, we may end up opening the inverting index
@@ -281,6 +370,12 @@ impl SegmentReader {
         self.segment_id
     }
 
+    /// Returns the opstamp of the last delete operation
+    /// taken in account in this segment, if any.
+    pub fn delete_opstamp(&self) -> Option<Opstamp> {
+        self.delete_opstamp
+    }
+
     /// Returns the bitset representing
     /// the documents that have been deleted.
     pub fn delete_bitset(&self) -> Option<&DeleteBitSet> {
@@ -303,6 +398,7 @@ impl SegmentReader {
     /// Summarize total space usage of this segment.
     pub fn space_usage(&self) -> SegmentSpaceUsage {
         SegmentSpaceUsage::new(
+            self.segment_id(),
             self.num_docs(),
             self.termdict_composite.space_usage(),
             self.postings_composite.space_usage(),
@@ -317,6 +413,50 @@ impl SegmentReader {
                 .unwrap_or(0),
         )
     }
+
+    /// Returns the aggregate statistics of the terms of a field in the segment.
+    ///
+    /// # Panics
+    /// If the field is not indexed.
+    pub fn field_term_statistics(&self, field: Field) -> FieldTermStatistics {
+        let inverted_index = self.inverted_index(field);
+        FieldTermStatistics {
+            num_docs: u64::from(self.max_doc()),
+            total_num_tokens: inverted_index.total_num_tokens(),
+            num_terms: inverted_index.num_terms() as u64,
+        }
+    }
+
+    /// Returns the estimated memory usage of the segment, excluding
+    /// the store cache of the searchers.
+    pub(crate) fn memory_usage(&self) -> SegmentMemoryUsage {
+        let source_len =
+            |source_opt: Option<ReadOnlySource>| source_opt.map(|source| source.len()).unwrap_or(0);
+        let mut memory_usage = SegmentMemoryUsage::default();
+        let open_fields: Vec<Field> = self
+            .inv_idx_reader_cache
+            .read()
+            .expect("Lock poisoned. This should never happen")
+            .keys()
+            .cloned()
+            .collect();
+        memory_usage.num_open_inverted_indexes = open_fields.len();
+        for field in open_fields {
+            memory_usage.termdict += source_len(self.termdict_composite.open_read(field));
+            memory_usage.postings += source_len(self.postings_composite.open_read(field))
+                + source_len(self.positions_composite.open_read(field))
+                + source_len(self.positions_idx_composite.open_read(field));
+        }
+        memory_usage.fast_fields = self.fast_fields_readers.space_usage().total();
+        memory_usage.fieldnorms = self.fieldnorms_composite.space_usage().total();
+        memory_usage.store = self.store_source.len();
+        memory_usage.deletes = self
+            .delete_bitset_opt
+            .as_ref()
+            .map(DeleteBitSet::space_usage)
+            .unwrap_or(0);
+        memory_usage
+    }
 }
 
 impl fmt::Debug for SegmentReader {
@@ -374,8 +514,11 @@ impl<'a> Iterator for SegmentReaderAliveDocsIterator<'a> {
 #[cfg(test)]
 mod test {
     use crate::core::Index;
-    use crate::schema::{Schema, Term, STORED, TEXT};
+    use crate::directory::RAMDirectory;
+    use crate::schema::{Schema, Term, STORED, STRING, TEXT};
     use crate::DocId;
+    use crate::IndexSettings;
+    use futures::Future;
 
     #[test]
     fn test_alive_docs_iterator() {
@@ -408,4 +551,60 @@ mod test {
         let docs: Vec<DocId> = searcher.segment_reader(0).doc_ids_alive().collect();
         assert_eq!(vec![0u32, 2u32], docs);
     }
+
+    #[test]
+    fn test_doc_by_key() {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let name = schema_builder.add_text_field("name", TEXT | STORED);
+        let settings = IndexSettings {
+            primary_key_field: Some("id".to_string()),
+            ..IndexSettings::default()
+        };
+        let index =
+            Index::create_with_settings(RAMDirectory::create(), schema_builder.build(), settings)
+                .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(id => "a", name => "first"));
+        index_writer.add_document(doc!(id => "b", name => "second"));
+        index_writer.commit().unwrap();
+        // Updates the document "a".
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.add_document(doc!(id => "a", name => "third"));
+        index_writer.add_document(doc!(id => "c", name => "fourth"));
+        index_writer.commit().unwrap();
+
+        let reader = index.reader().unwrap();
+        let name_of_key = |key: &str| {
+            let searcher = reader.searcher();
+            searcher
+                .doc_by_key(&Term::from_field_text(id, key))
+                .map(|doc_address| {
+                    let doc = searcher.doc(doc_address).unwrap();
+                    doc.get_first(name).unwrap().text().unwrap().to_string()
+                })
+        };
+        assert_eq!(name_of_key("a").as_deref(), Some("third"));
+        assert_eq!(name_of_key("b").as_deref(), Some("second"));
+        assert_eq!(name_of_key("c").as_deref(), Some("fourth"));
+        assert_eq!(name_of_key("d"), None);
+        let searcher = reader.searcher();
+        assert!(searcher
+            .doc_by_key(&Term::from_field_text(name, "second"))
+            .is_some());
+
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(reader.searcher().segment_readers().len(), 1);
+        assert!(reader
+            .searcher()
+            .segment_reader(0)
+            .primary_key_reader
+            .is_some());
+        assert_eq!(name_of_key("a").as_deref(), Some("third"));
+        assert_eq!(name_of_key("c").as_deref(), Some("fourth"));
+        assert_eq!(name_of_key("d"), None);
+    }
 }
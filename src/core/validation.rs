@@ -0,0 +1,449 @@
+use crate::common::HasLen;
+use crate::core::{Index, Segment, SegmentComponent, SegmentId, SegmentReader};
+use crate::directory::Directory;
+use crate::fastfield::{FastFieldReader, MultiValueIntFastFieldReader};
+use crate::schema::{Field, IndexRecordOption};
+use crate::DocId;
+use crate::DocSet;
+use crate::Result;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Number of fast field values checked at once.
+const FAST_FIELD_BUFFER_LEN: usize = 1_024;
+
+/// A problem detected by [`Index::validate`](./struct.Index.html#method.validate).
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegrityProblem {
+    /// The file of a component of the segment is missing.
+    MissingFile {
+        /// Segment the file belongs to.
+        segment_id: SegmentId,
+        /// Path of the missing file.
+        path: PathBuf,
+    },
+    /// The checksum of a file does not match its content.
+    ChecksumMismatch {
+        /// Segment the file belongs to.
+        segment_id: SegmentId,
+        /// Path of the damaged file.
+        path: PathBuf,
+    },
+    /// The meta information of the index or of a segment is inconsistent.
+    InvalidMeta {
+        /// Segment the meta information relates to, if any.
+        segment_id: Option<SegmentId>,
+        /// Description of the problem.
+        reason: String,
+    },
+    /// The segment could not be opened.
+    UnreadableSegment {
+        /// The segment that could not be opened.
+        segment_id: SegmentId,
+        /// Error returned when opening the segment.
+        reason: String,
+    },
+    /// The postings list of a term is not valid.
+    InvalidPostings {
+        /// Segment containing the term.
+        segment_id: SegmentId,
+        /// Field of the term.
+        field: Field,
+        /// Value bytes of the term.
+        term: Vec<u8>,
+        /// Description of the problem.
+        reason: String,
+    },
+    /// The values of a fast field are not valid.
+    InvalidFastField {
+        /// Segment containing the fast field.
+        segment_id: SegmentId,
+        /// The fast field.
+        field: Field,
+        /// Description of the problem.
+        reason: String,
+    },
+    /// The doc store of the segment cannot be decoded.
+    InvalidStore {
+        /// Segment containing the doc store.
+        segment_id: SegmentId,
+        /// Description of the problem.
+        reason: String,
+    },
+}
+
+/// Checks the integrity of the last commit of `index`.
+///
+/// The files of all segments are checked first. The content of a segment
+/// is only decoded if none of its files is missing or damaged, as reading
+/// corrupted data may panic.
+pub(crate) fn validate_index(index: &Index) -> Result<Vec<IntegrityProblem>> {
+    let index_meta = index.load_metas()?;
+    let mut problems = Vec::new();
+    let mut segment_ids = HashSet::new();
+    for segment_meta in &index_meta.segments {
+        let segment_id = segment_meta.id();
+        if !segment_ids.insert(segment_id) {
+            problems.push(IntegrityProblem::InvalidMeta {
+                segment_id: Some(segment_id),
+                reason: "The segment is listed several times.".to_string(),
+            });
+            continue;
+        }
+        if let Some(delete_opstamp) = segment_meta.delete_opstamp() {
+            if delete_opstamp > index_meta.opstamp {
+                problems.push(IntegrityProblem::InvalidMeta {
+                    segment_id: Some(segment_id),
+                    reason: format!(
+                        "Delete opstamp {} is greater than the commit opstamp {}.",
+                        delete_opstamp, index_meta.opstamp
+                    ),
+                });
+            }
+        }
+        if segment_meta.num_deleted_docs() > segment_meta.max_doc() {
+            problems.push(IntegrityProblem::InvalidMeta {
+                segment_id: Some(segment_id),
+                reason: format!(
+                    "{} deleted documents out of {} documents.",
+                    segment_meta.num_deleted_docs(),
+                    segment_meta.max_doc()
+                ),
+            });
+            continue;
+        }
+        let segment = index.segment(segment_meta.clone());
+        if validate_files(index, &segment, &mut problems)? {
+            validate_segment(&segment, &mut problems);
+        }
+    }
+    Ok(problems)
+}
+
+/// Checks that the files of the segment exist and match their checksum.
+///
+/// Returns true iff all files are valid.
+fn validate_files(
+    index: &Index,
+    segment: &Segment,
+    problems: &mut Vec<IntegrityProblem>,
+) -> Result<bool> {
+    let directory = index.directory();
+    let mut is_valid = true;
+    for &component in SegmentComponent::iterator() {
+        if let SegmentComponent::DELETE = component {
+            if segment.meta().delete_opstamp().is_none() {
+                continue;
+            }
+        }
+        let path = segment.relative_path(component);
+        if !directory.exists(&path) {
+            is_valid = false;
+            problems.push(IntegrityProblem::MissingFile {
+                segment_id: segment.id(),
+                path,
+            });
+        } else if !directory.validate_checksum(&path)? {
+            is_valid = false;
+            problems.push(IntegrityProblem::ChecksumMismatch {
+                segment_id: segment.id(),
+                path,
+            });
+        }
+    }
+    Ok(is_valid)
+}
+
+fn validate_segment(segment: &Segment, problems: &mut Vec<IntegrityProblem>) {
+    let segment_reader = match SegmentReader::open(segment) {
+        Ok(segment_reader) => segment_reader,
+        Err(err) => {
+            problems.push(IntegrityProblem::UnreadableSegment {
+                segment_id: segment.id(),
+                reason: format!("{:?}", err),
+            });
+            return;
+        }
+    };
+    validate_delete_bitset(segment, &segment_reader, problems);
+    validate_postings(&segment_reader, problems);
+    validate_fast_fields(&segment_reader, problems);
+    validate_store(&segment_reader, problems);
+}
+
+fn validate_delete_bitset(
+    segment: &Segment,
+    segment_reader: &SegmentReader,
+    problems: &mut Vec<IntegrityProblem>,
+) {
+    if let Some(delete_bitset) = segment_reader.delete_bitset() {
+        let max_doc = segment_reader.max_doc() as usize;
+        let reason = if delete_bitset.space_usage() * 8 < max_doc {
+            Some(format!(
+                "The delete bitset is too short for {} documents.",
+                max_doc
+            ))
+        } else if delete_bitset.len() != segment.meta().num_deleted_docs() as usize {
+            Some(format!(
+                "The delete bitset contains {} deleted documents, the meta {}.",
+                delete_bitset.len(),
+                segment.meta().num_deleted_docs()
+            ))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            problems.push(IntegrityProblem::InvalidMeta {
+                segment_id: Some(segment.id()),
+                reason,
+            });
+        }
+    }
+}
+
+fn validate_postings(segment_reader: &SegmentReader, problems: &mut Vec<IntegrityProblem>) {
+    let max_doc = segment_reader.max_doc();
+    for (field_id, field_entry) in segment_reader.schema().fields().iter().enumerate() {
+        if !field_entry.is_indexed() {
+            continue;
+        }
+        let field = Field(field_id as u32);
+        let inverted_index = segment_reader.inverted_index(field);
+        let mut term_stream = inverted_index.terms().stream();
+        while term_stream.advance() {
+            let term_info = term_stream.value();
+            if inverted_index.is_pruned(term_info) {
+                continue;
+            }
+            let mut postings =
+                inverted_index.read_postings_from_terminfo(term_info, IndexRecordOption::Basic);
+            let mut num_docs = 0u32;
+            let mut previous_doc: Option<DocId> = None;
+            let mut reason = None;
+            while postings.advance() {
+                let doc = postings.doc();
+                if let Some(previous) = previous_doc {
+                    if previous >= doc {
+                        reason = Some(format!("Document {} follows document {}.", doc, previous));
+                        break;
+                    }
+                }
+                if doc >= max_doc {
+                    reason = Some(format!("Document {} is out of the segment.", doc));
+                    break;
+                }
+                previous_doc = Some(doc);
+                num_docs += 1;
+            }
+            if reason.is_none() && num_docs != term_info.doc_freq {
+                reason = Some(format!(
+                    "The postings contain {} documents, the doc freq is {}.",
+                    num_docs, term_info.doc_freq
+                ));
+            }
+            if let Some(reason) = reason {
+                problems.push(IntegrityProblem::InvalidPostings {
+                    segment_id: segment_reader.segment_id(),
+                    field,
+                    term: term_stream.key().to_vec(),
+                    reason,
+                });
+            }
+        }
+    }
+}
+
+fn validate_fast_fields(segment_reader: &SegmentReader, problems: &mut Vec<IntegrityProblem>) {
+    let fast_fields = segment_reader.fast_fields();
+    let max_doc = segment_reader.max_doc();
+    for field_id in 0..segment_reader.schema().fields().len() {
+        let field = Field(field_id as u32);
+        let reason = if let Some(reader) = fast_fields.u64_lenient(field) {
+            validate_column(&reader, u64::from(max_doc))
+        } else if let Some(reader) = fast_fields.u64s_lenient(field) {
+            validate_multivalued(&reader, max_doc)
+        } else if let Some(reader) = fast_fields.bytes(field) {
+            validate_offsets(
+                reader.idx_reader(),
+                max_doc,
+                reader.all_bytes().len() as u64,
+            )
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            problems.push(IntegrityProblem::InvalidFastField {
+                segment_id: segment_reader.segment_id(),
+                field,
+                reason,
+            });
+        }
+    }
+}
+
+/// Checks that the `num_vals` first values of the column are within
+/// the bounds recorded in its header.
+fn validate_column(reader: &FastFieldReader<u64>, num_vals: u64) -> Option<String> {
+    let (min_value, max_value) = (reader.min_value(), reader.max_value());
+    let mut buffer = vec![0u64; FAST_FIELD_BUFFER_LEN];
+    let mut start = 0u64;
+    while start < num_vals {
+        let len = (num_vals - start).min(FAST_FIELD_BUFFER_LEN as u64) as usize;
+        reader.get_range_u64(start, &mut buffer[..len]);
+        if let Some(pos) = buffer[..len]
+            .iter()
+            .position(|&val| val < min_value || val > max_value)
+        {
+            return Some(format!(
+                "Value {} of index {} is out of the bounds [{}, {}].",
+                buffer[pos],
+                start + pos as u64,
+                min_value,
+                max_value
+            ));
+        }
+        start += len as u64;
+    }
+    None
+}
+
+/// Checks that the `max_doc + 1` offsets of a column index start at 0, are
+/// increasing, and do not exceed `num_vals`.
+fn validate_offsets(
+    idx_reader: &FastFieldReader<u64>,
+    max_doc: DocId,
+    num_vals: u64,
+) -> Option<String> {
+    let mut previous_offset = 0u64;
+    for doc in 0..=max_doc {
+        let offset = idx_reader.get(doc);
+        if doc == 0 && offset != 0 {
+            return Some(format!("The first offset is {}.", offset));
+        }
+        if offset < previous_offset {
+            return Some(format!(
+                "The offset of document {} is lower than the offset of the previous document.",
+                doc
+            ));
+        }
+        previous_offset = offset;
+    }
+    if previous_offset > num_vals {
+        return Some(format!(
+            "The offsets end at {}, past the {} values of the field.",
+            previous_offset, num_vals
+        ));
+    }
+    None
+}
+
+fn validate_multivalued(
+    reader: &MultiValueIntFastFieldReader<u64>,
+    max_doc: DocId,
+) -> Option<String> {
+    let num_vals = reader.idx_reader().get(max_doc);
+    validate_offsets(reader.idx_reader(), max_doc, reader.total_num_vals())
+        .or_else(|| validate_column(reader.vals_reader(), num_vals))
+}
+
+fn validate_store(segment_reader: &SegmentReader, problems: &mut Vec<IntegrityProblem>) {
+    let store_reader = segment_reader.get_store_reader();
+    let mut num_docs = 0u32;
+    for doc_res in store_reader.iter(None) {
+        if let Err(err) = doc_res {
+            problems.push(IntegrityProblem::InvalidStore {
+                segment_id: segment_reader.segment_id(),
+                reason: format!("Document {} cannot be decoded: {:?}", num_docs, err),
+            });
+            return;
+        }
+        num_docs += 1;
+    }
+    if num_docs != segment_reader.max_doc() {
+        problems.push(IntegrityProblem::InvalidStore {
+            segment_id: segment_reader.segment_id(),
+            reason: format!(
+                "The store contains {} documents instead of {}.",
+                num_docs,
+                segment_reader.max_doc()
+            ),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntegrityProblem;
+    use crate::directory::{Directory, RAMDirectory};
+    use crate::schema::{Cardinality, IntOptions, Schema, FAST, STORED, STRING, TEXT};
+    use crate::{Index, SegmentComponent, Term};
+
+    fn create_index(directory: RAMDirectory) -> Index {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let num_field = schema_builder.add_u64_field("num", FAST);
+        let nums_field = schema_builder.add_i64_field(
+            "nums",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let bytes_field = schema_builder.add_bytes_field("bytes", FAST);
+        let index = Index::create(directory, schema_builder.build()).unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0u64..300u64 {
+            index_writer.add_document(doc!(
+                text_field => if i % 2 == 0 { "hello happy tax payer" } else { "hello" },
+                id_field => format!("{}", i),
+                num_field => i * 3,
+                nums_field => -(i as i64),
+                nums_field => i as i64,
+                bytes_field => vec![i as u8; (i % 5) as usize]
+            ));
+        }
+        index_writer.commit().unwrap();
+        index_writer.delete_term(Term::from_field_text(id_field, "7"));
+        index_writer.commit().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+        index
+    }
+
+    #[test]
+    fn test_validate_valid_index() {
+        let index = create_index(RAMDirectory::create());
+        assert_eq!(index.validate().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_validate_damaged_file() {
+        let mut directory = RAMDirectory::create();
+        let index = create_index(directory.clone());
+        let segment = index.searchable_segments().unwrap().pop().unwrap();
+        let path = segment.relative_path(SegmentComponent::STORE);
+        let mut data = directory.atomic_read(&path).unwrap();
+        data[0] ^= 1;
+        directory.atomic_write(&path, &data).unwrap();
+        assert_eq!(
+            index.validate().unwrap(),
+            vec![IntegrityProblem::ChecksumMismatch {
+                segment_id: segment.id(),
+                path
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_missing_file() {
+        let directory = RAMDirectory::create();
+        let index = create_index(directory.clone());
+        let segment = index.searchable_segments().unwrap().pop().unwrap();
+        let path = segment.relative_path(SegmentComponent::DELETE);
+        directory.delete(&path).unwrap();
+        assert_eq!(
+            index.validate().unwrap(),
+            vec![IntegrityProblem::MissingFile {
+                segment_id: segment.id(),
+                path
+            }]
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use crate::common::HasLen;
+use crate::directory::error::{
+    DeleteError, IOError, LockError, OpenDirectoryError, OpenReadError, OpenWriteError,
+};
+#[cfg(feature = "mmap")]
+use crate::directory::mmap_directory::open_mmap;
+use crate::directory::{Directory, DirectoryLock, Lock, ReadOnlySource, WritePtr};
+use crate::directory::{WatchCallback, WatchCallbackList, WatchHandle, INDEX_WRITER_LOCK};
+use byteorder::{ByteOrder, LittleEndian};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::result;
+use std::sync::Arc;
+
+const TAR_BLOCK_SIZE: usize = 512;
+
+const ZIP_LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const ZIP_CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const ZIP_END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP_END_OF_CENTRAL_DIRECTORY_LEN: usize = 22;
+
+fn invalid_archive(msg: String) -> OpenDirectoryError {
+    OpenDirectoryError::IoError(io::Error::new(io::ErrorKind::InvalidData, msg))
+}
+
+fn read_only_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "The archive directory is read-only.",
+    )
+}
+
+/// Normalizes the path of an archive entry, removing the leading `./`.
+fn entry_path(name: &str) -> PathBuf {
+    PathBuf::from(name.trim_start_matches("./"))
+}
+
+fn parse_tar_str(bytes: &[u8]) -> &str {
+    let len = bytes.iter().position(|&b| b == 0u8).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..len]).unwrap_or("")
+}
+
+/// Parses a numeric field of a tar header.
+///
+/// Numbers are encoded in octal, or in base-256 for large
+/// values (GNU extension).
+fn parse_tar_number(bytes: &[u8]) -> Result<usize, OpenDirectoryError> {
+    if bytes[0] & 0x80 != 0 {
+        let mut val = (bytes[0] & 0x7f) as usize;
+        for &b in &bytes[1..] {
+            val = val
+                .checked_mul(256)
+                .ok_or_else(|| invalid_archive("Number overflow in tar header".to_string()))?
+                | b as usize;
+        }
+        return Ok(val);
+    }
+    let num_str = parse_tar_str(bytes).trim_matches(|c| c == ' ' || c == '\0');
+    if num_str.is_empty() {
+        return Ok(0);
+    }
+    usize::from_str_radix(num_str, 8)
+        .map_err(|_| invalid_archive(format!("Invalid number in tar header: {:?}", num_str)))
+}
+
+/// Checks the checksum of the tar header at `offset`: the sum of the bytes
+/// of the header, its checksum field being counted as spaces.
+///
+/// Some old archivers compute the sum over signed bytes, which is also accepted.
+fn check_tar_checksum(header: &[u8], offset: usize) -> Result<(), OpenDirectoryError> {
+    let checksum = parse_tar_number(&header[148..156])?;
+    let header_bytes = || {
+        header
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| if (148..156).contains(&i) { b' ' } else { b })
+    };
+    let unsigned_sum: usize = header_bytes().map(|b| b as usize).sum();
+    let signed_sum: isize = header_bytes().map(|b| b as i8 as isize).sum();
+    if checksum != unsigned_sum && checksum as isize != signed_sum {
+        return Err(invalid_archive(format!(
+            "Invalid checksum for the tar header at offset {}",
+            offset
+        )));
+    }
+    Ok(())
+}
+
+/// Parses a tar (ustar, gnu or pax) archive, and returns the range of
+/// the data of each regular file.
+fn parse_tar(data: &[u8]) -> Result<HashMap<PathBuf, Range<usize>>, OpenDirectoryError> {
+    let mut files = HashMap::new();
+    let mut offset = 0;
+    let mut long_name: Option<String> = None;
+    while offset + TAR_BLOCK_SIZE <= data.len() {
+        let header = &data[offset..offset + TAR_BLOCK_SIZE];
+        if header.iter().all(|&b| b == 0u8) {
+            // End of archive marker
+            break;
+        }
+        check_tar_checksum(header, offset)?;
+        let size = parse_tar_number(&header[124..136])?;
+        let typeflag = header[156];
+        let data_start = offset + TAR_BLOCK_SIZE;
+        let data_end = match data_start.checked_add(size) {
+            Some(data_end) if data_end <= data.len() => data_end,
+            _ => {
+                return Err(invalid_archive(format!(
+                    "Tar entry at offset {} exceeds the archive length",
+                    offset
+                )));
+            }
+        };
+        let entry_data = &data[data_start..data_end];
+        match typeflag {
+            b'L' => {
+                // GNU long name: the data is the name of the next entry.
+                long_name = Some(parse_tar_str(entry_data).to_string());
+            }
+            b'x' => {
+                // Pax extended header. We only care about the `path` record.
+                let records = String::from_utf8_lossy(entry_data);
+                for record in records.lines() {
+                    if let Some(pos) = record.find(" path=") {
+                        long_name = Some(record[pos + " path=".len()..].to_string());
+                    }
+                }
+            }
+            b'0' | b'\0' | b'7' => {
+                let name = long_name.take().unwrap_or_else(|| {
+                    let prefix = parse_tar_str(&header[345..500]);
+                    let name = parse_tar_str(&header[0..100]);
+                    if prefix.is_empty() || &header[257..262] != b"ustar" {
+                        name.to_string()
+                    } else {
+                        format!("{}/{}", prefix, name)
+                    }
+                });
+                files.insert(entry_path(&name), data_start..data_end);
+            }
+            _ => {
+                // Directories, links and other special entries are ignored.
+                long_name = None;
+            }
+        }
+        // Entry data is padded to a multiple of the block size.
+        let padding = (TAR_BLOCK_SIZE - size % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+        offset = data_end + padding;
+    }
+    Ok(files)
+}
+
+/// Parses a zip archive, and returns the range of the data of each file.
+///
+/// Only entries that are stored without compression are supported,
+/// as they can be read directly out of the archive.
+fn parse_zip(data: &[u8]) -> Result<HashMap<PathBuf, Range<usize>>, OpenDirectoryError> {
+    if data.len() < ZIP_END_OF_CENTRAL_DIRECTORY_LEN {
+        return Err(invalid_archive("Zip archive is too short".to_string()));
+    }
+    // The end of central directory record is followed by a comment of at most 65535 bytes.
+    let search_start = data
+        .len()
+        .saturating_sub(ZIP_END_OF_CENTRAL_DIRECTORY_LEN + 65_535);
+    let eocd_offset = (search_start..=data.len() - ZIP_END_OF_CENTRAL_DIRECTORY_LEN)
+        .rev()
+        .find(|&offset| {
+            LittleEndian::read_u32(&data[offset..]) == ZIP_END_OF_CENTRAL_DIRECTORY_SIGNATURE
+        })
+        .ok_or_else(|| invalid_archive("End of central directory not found".to_string()))?;
+    let eocd = &data[eocd_offset..];
+    let num_entries = LittleEndian::read_u16(&eocd[10..]) as usize;
+    let central_directory_offset = LittleEndian::read_u32(&eocd[16..]) as usize;
+    if num_entries == 0xFFFF || central_directory_offset == 0xFFFF_FFFF {
+        return Err(invalid_archive(
+            "Zip64 archives are not supported".to_string(),
+        ));
+    }
+    let read_header = |offset: usize, len: usize| -> Result<&[u8], OpenDirectoryError> {
+        data.get(offset..offset + len)
+            .ok_or_else(|| invalid_archive(format!("Zip header at offset {} is truncated", offset)))
+    };
+    let mut files = HashMap::new();
+    let mut offset = central_directory_offset;
+    for _ in 0..num_entries {
+        let header = read_header(offset, 46)?;
+        if LittleEndian::read_u32(header) != ZIP_CENTRAL_HEADER_SIGNATURE {
+            return Err(invalid_archive(format!(
+                "Invalid central directory header at offset {}",
+                offset
+            )));
+        }
+        let compression_method = LittleEndian::read_u16(&header[10..]);
+        let compressed_size = LittleEndian::read_u32(&header[20..]) as usize;
+        let name_len = LittleEndian::read_u16(&header[28..]) as usize;
+        let extra_len = LittleEndian::read_u16(&header[30..]) as usize;
+        let comment_len = LittleEndian::read_u16(&header[32..]) as usize;
+        let local_header_offset = LittleEndian::read_u32(&header[42..]) as usize;
+        let name_bytes = read_header(offset + 46, name_len)?;
+        let name = String::from_utf8_lossy(name_bytes).into_owned();
+        offset += 46 + name_len + extra_len + comment_len;
+        if name.ends_with('/') {
+            // directory entry
+            continue;
+        }
+        if compression_method != 0 {
+            return Err(invalid_archive(format!(
+                "Zip entry {:?} is compressed. Only stored entries are supported.",
+                name
+            )));
+        }
+        let local_header = read_header(local_header_offset, 30)?;
+        if LittleEndian::read_u32(local_header) != ZIP_LOCAL_HEADER_SIGNATURE {
+            return Err(invalid_archive(format!(
+                "Invalid local header for zip entry {:?}",
+                name
+            )));
+        }
+        let local_name_len = LittleEndian::read_u16(&local_header[26..]) as usize;
+        let local_extra_len = LittleEndian::read_u16(&local_header[28..]) as usize;
+        let data_start = local_header_offset + 30 + local_name_len + local_extra_len;
+        let data_end = data_start + compressed_size;
+        if data_end > data.len() {
+            return Err(invalid_archive(format!(
+                "Zip entry {:?} exceeds the archive length",
+                name
+            )));
+        }
+        files.insert(entry_path(&name), data_start..data_end);
+    }
+    Ok(files)
+}
+
+/// Read-only directory serving the files of a `tar` or `zip` archive.
+///
+/// The archive is parsed once upon opening to build a table
+/// of the offsets of its files. Files are then served as slices
+/// of the archive, without any extraction or copy.
+///
+/// The index files are expected to be at the root of the archive.
+/// In zip archives, files need to be stored without compression
+/// (e.g. `zip -0`).
+///
+/// Write operations fail, and creating an `IndexWriter` is not possible.
+#[derive(Clone)]
+pub struct ArchiveDirectory {
+    inner: Arc<ArchiveDirectoryInner>,
+}
+
+struct ArchiveDirectoryInner {
+    archive: ReadOnlySource,
+    files: HashMap<PathBuf, Range<usize>>,
+    watch_router: WatchCallbackList,
+}
+
+impl ArchiveDirectory {
+    fn new(archive: ReadOnlySource, files: HashMap<PathBuf, Range<usize>>) -> ArchiveDirectory {
+        ArchiveDirectory {
+            inner: Arc::new(ArchiveDirectoryInner {
+                archive,
+                files,
+                watch_router: WatchCallbackList::default(),
+            }),
+        }
+    }
+
+    /// Opens a directory over the content of a `tar` archive.
+    pub fn from_tar(archive: ReadOnlySource) -> Result<ArchiveDirectory, OpenDirectoryError> {
+        let files = parse_tar(archive.as_slice())?;
+        Ok(ArchiveDirectory::new(archive, files))
+    }
+
+    /// Opens a directory over the content of a `zip` archive.
+    pub fn from_zip(archive: ReadOnlySource) -> Result<ArchiveDirectory, OpenDirectoryError> {
+        let files = parse_zip(archive.as_slice())?;
+        Ok(ArchiveDirectory::new(archive, files))
+    }
+
+    /// Opens a directory over an archive file.
+    ///
+    /// The archive is mmapped, and its format is detected from its content.
+    #[cfg(feature = "mmap")]
+    pub fn open<P: AsRef<Path>>(archive_path: P) -> Result<ArchiveDirectory, OpenDirectoryError> {
+        let archive_path = archive_path.as_ref();
+        let archive = match open_mmap(archive_path) {
+            Ok(Some(mmap)) => ReadOnlySource::new(mmap),
+            Ok(None) => ReadOnlySource::empty(),
+            Err(OpenReadError::FileDoesNotExist(path)) => {
+                return Err(OpenDirectoryError::DoesNotExist(path));
+            }
+            Err(OpenReadError::IOError(io_error)) => {
+                return Err(OpenDirectoryError::IoError(io_error.into()));
+            }
+        };
+        if archive.as_slice().starts_with(b"PK") {
+            ArchiveDirectory::from_zip(archive)
+        } else {
+            ArchiveDirectory::from_tar(archive)
+        }
+    }
+
+    /// Returns the number of files in the archive.
+    pub fn num_files(&self) -> usize {
+        self.inner.files.len()
+    }
+}
+
+impl fmt::Debug for ArchiveDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ArchiveDirectory(num_files={}, len={})",
+            self.inner.files.len(),
+            self.inner.archive.len()
+        )
+    }
+}
+
+impl Directory for ArchiveDirectory {
+    fn open_read(&self, path: &Path) -> result::Result<ReadOnlySource, OpenReadError> {
+        self.inner
+            .files
+            .get(path)
+            .map(|range| self.inner.archive.slice(range.start, range.end))
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(path.to_owned()))
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        if !self.exists(path) {
+            return Err(DeleteError::FileDoesNotExist(path.to_owned()));
+        }
+        Err(IOError::with_path(path.to_owned(), read_only_error()).into())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.files.contains_key(path)
+    }
+
+    fn open_write(&mut self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        Err(IOError::with_path(path.to_owned(), read_only_error()).into())
+    }
+
+    fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        Ok(self.open_read(path)?.as_slice().to_owned())
+    }
+
+    fn atomic_write(&mut self, _path: &Path, _data: &[u8]) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    /// The archive is immutable. Locks are only there to protect
+    /// against concurrent modifications, so they are always granted,
+    /// except for the `IndexWriter` lock.
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        if lock.filepath == INDEX_WRITER_LOCK.filepath {
+            return Err(LockError::IOError(read_only_error()));
+        }
+        Ok(DirectoryLock::from(Box::new(())))
+    }
+
+    /// The archive never changes, so callbacks are never called.
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(self.inner.watch_router.subscribe(watch_callback))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArchiveDirectory;
+    use crate::collector::Count;
+    use crate::core::MANAGED_FILEPATH;
+    use crate::directory::{Directory, RAMDirectory, ReadOnlySource};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Index, Term};
+    use byteorder::{ByteOrder, LittleEndian};
+    use std::path::{Path, PathBuf};
+
+    fn build_tar(files: &[(PathBuf, Vec<u8>)]) -> Vec<u8> {
+        let mut tar = Vec::new();
+        for (path, data) in files {
+            let mut header = [0u8; 512];
+            let name = path.to_str().unwrap().as_bytes();
+            header[..name.len()].copy_from_slice(name);
+            header[100..107].copy_from_slice(b"0000644");
+            header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+            header[156] = b'0';
+            header[257..263].copy_from_slice(b"ustar\0");
+            header[263..265].copy_from_slice(b"00");
+            header[148..156].copy_from_slice(b"        ");
+            let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+            header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+            tar.extend_from_slice(&header[..]);
+            tar.extend_from_slice(data);
+            let padding = (512 - data.len() % 512) % 512;
+            tar.resize(tar.len() + padding, 0u8);
+        }
+        tar.resize(tar.len() + 1024, 0u8);
+        tar
+    }
+
+    fn build_zip(files: &[(PathBuf, Vec<u8>)]) -> Vec<u8> {
+        let mut zip = Vec::new();
+        let mut central_directory = Vec::new();
+        for (path, data) in files {
+            let name = path.to_str().unwrap().as_bytes();
+            let local_header_offset = zip.len();
+            let mut local_header = [0u8; 30];
+            LittleEndian::write_u32(&mut local_header[0..], 0x0403_4b50);
+            LittleEndian::write_u32(&mut local_header[18..], data.len() as u32);
+            LittleEndian::write_u32(&mut local_header[22..], data.len() as u32);
+            LittleEndian::write_u16(&mut local_header[26..], name.len() as u16);
+            zip.extend_from_slice(&local_header[..]);
+            zip.extend_from_slice(name);
+            zip.extend_from_slice(data);
+            let mut central_header = [0u8; 46];
+            LittleEndian::write_u32(&mut central_header[0..], 0x0201_4b50);
+            LittleEndian::write_u32(&mut central_header[20..], data.len() as u32);
+            LittleEndian::write_u32(&mut central_header[24..], data.len() as u32);
+            LittleEndian::write_u16(&mut central_header[28..], name.len() as u16);
+            LittleEndian::write_u32(&mut central_header[42..], local_header_offset as u32);
+            central_directory.extend_from_slice(&central_header[..]);
+            central_directory.extend_from_slice(name);
+        }
+        let central_directory_offset = zip.len();
+        zip.extend_from_slice(&central_directory);
+        let mut eocd = [0u8; 22];
+        LittleEndian::write_u32(&mut eocd[0..], 0x0605_4b50);
+        LittleEndian::write_u16(&mut eocd[8..], files.len() as u16);
+        LittleEndian::write_u16(&mut eocd[10..], files.len() as u16);
+        LittleEndian::write_u32(&mut eocd[12..], central_directory.len() as u32);
+        LittleEndian::write_u32(&mut eocd[16..], central_directory_offset as u32);
+        zip.extend_from_slice(&eocd[..]);
+        zip
+    }
+
+    /// Creates a small index and returns its files.
+    fn index_files() -> Vec<(PathBuf, Vec<u8>)> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let ram_directory = RAMDirectory::create();
+        let index = Index::create(ram_directory.clone(), schema).unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field=>"a b"));
+        index_writer.add_document(doc!(text_field=>"a c"));
+        index_writer.commit().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+        let mut paths: Vec<PathBuf> = index.directory().list_managed_files().into_iter().collect();
+        paths.push(MANAGED_FILEPATH.to_path_buf());
+        paths
+            .into_iter()
+            .map(|path| {
+                let data = ram_directory.open_read(&path).unwrap().as_slice().to_vec();
+                (path, data)
+            })
+            .collect()
+    }
+
+    fn check_index(directory: ArchiveDirectory) {
+        let index = Index::open(directory).unwrap();
+        let text_field = index.schema().get_field("text").unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 2);
+        assert!(index.writer_with_num_threads(1, 3_000_000).is_err());
+    }
+
+    #[test]
+    fn test_tar_directory() {
+        let files = index_files();
+        let tar = build_tar(&files);
+        let directory = ArchiveDirectory::from_tar(ReadOnlySource::from(tar)).unwrap();
+        assert_eq!(directory.num_files(), files.len());
+        for (path, data) in &files {
+            assert_eq!(directory.open_read(path).unwrap().as_slice(), &data[..]);
+        }
+        assert!(!directory.exists(Path::new("missing")));
+        check_index(directory);
+    }
+
+    #[test]
+    fn test_zip_directory() {
+        let files = index_files();
+        let zip = build_zip(&files);
+        let directory = ArchiveDirectory::from_zip(ReadOnlySource::from(zip)).unwrap();
+        assert_eq!(directory.num_files(), files.len());
+        for (path, data) in &files {
+            assert_eq!(directory.open_read(path).unwrap().as_slice(), &data[..]);
+        }
+        check_index(directory);
+    }
+
+    #[test]
+    fn test_archive_directory_is_read_only() {
+        let files = vec![(PathBuf::from("./some_file"), b"abc".to_vec())];
+        let mut directory =
+            ArchiveDirectory::from_tar(ReadOnlySource::from(build_tar(&files))).unwrap();
+        let path = Path::new("some_file");
+        assert!(directory.exists(path));
+        assert!(directory.open_write(Path::new("other_file")).is_err());
+        assert!(directory.atomic_write(path, b"def").is_err());
+        assert!(directory.delete(path).is_err());
+        assert_eq!(directory.atomic_read(path).unwrap(), b"abc".to_vec());
+    }
+
+    #[test]
+    fn test_invalid_tar() {
+        let files = vec![(PathBuf::from("some_file"), b"abc".to_vec())];
+        let tar = build_tar(&files);
+        let mut corrupted_tar = tar.clone();
+        corrupted_tar[0] = b'o';
+        assert!(ArchiveDirectory::from_tar(ReadOnlySource::from(corrupted_tar)).is_err());
+        // The end of the entry overflows.
+        let mut huge_tar = tar;
+        huge_tar[124..136].copy_from_slice(&[
+            0x80, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        ]);
+        huge_tar[148..156].copy_from_slice(b"        ");
+        let checksum: u32 = huge_tar[..512].iter().map(|&b| u32::from(b)).sum();
+        huge_tar[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+        assert!(ArchiveDirectory::from_tar(ReadOnlySource::from(huge_tar)).is_err());
+    }
+
+    #[test]
+    fn test_invalid_zip() {
+        assert!(ArchiveDirectory::from_zip(ReadOnlySource::from(b"PKnotazip".to_vec())).is_err());
+    }
+}
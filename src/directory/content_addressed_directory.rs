@@ -0,0 +1,396 @@
+use crate::directory::error::{DeleteError, IOError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{AntiCallToken, TerminatingWrite};
+use crate::directory::{Directory, DirectoryLock, Lock, ReadOnlySource, WritePtr};
+use crate::directory::{WatchCallback, WatchHandle};
+use crate::error::DataCorruption;
+use crate::Result;
+use once_cell::sync::Lazy;
+use serde_json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::result;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use uuid::Uuid;
+
+/// Path of the file storing the mapping from logical paths to content hashes.
+pub static CONTENT_ADDRESSES_FILEPATH: Lazy<&'static Path> =
+    Lazy::new(|| Path::new(".content_addresses.json"));
+
+/// Returns the hex encoding of a sha256 digest.
+fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Wrapper of directories storing files under the hash of their content.
+///
+/// Files created using `open_write` are physically stored in the wrapped
+/// directory under the name `<sha256>.blob`. Two files with the same content
+/// hence share the same physical file.
+/// The mapping from the logical paths to the content hashes is persisted in the
+/// wrapped directory in the `.content_addresses.json` file.
+///
+/// Since a physical file name identifies its content, replication or backup
+/// tools can skip transferring the blobs they already have.
+///
+/// Files written via `atomic_write` (e.g. `meta.json`) are mutable and are
+/// stored as is in the wrapped directory.
+///
+/// Files being written are streamed to a temporary file of the wrapped directory,
+/// and hashed on the fly. They are only published under their content hash,
+/// and added to the persisted mapping, once their writer is terminated.
+pub struct ContentAddressedDirectory {
+    directory: Box<dyn Directory>,
+    content_addresses: Arc<RwLock<HashMap<PathBuf, String>>>,
+    // Temporary files of the logical files being written.
+    pending_files: Arc<RwLock<HashMap<PathBuf, PathBuf>>>,
+}
+
+/// Saves the mapping from logical paths to content hashes.
+fn save_content_addresses(
+    directory: &mut dyn Directory,
+    wlock: &RwLockWriteGuard<'_, HashMap<PathBuf, String>>,
+) -> io::Result<()> {
+    let mut w = serde_json::to_vec(&**wlock)?;
+    writeln!(&mut w)?;
+    directory.atomic_write(&CONTENT_ADDRESSES_FILEPATH, &w[..])
+}
+
+impl ContentAddressedDirectory {
+    /// Wraps a directory as a content addressed directory.
+    pub fn wrap<Dir: Directory>(directory: Dir) -> Result<ContentAddressedDirectory> {
+        let content_addresses = match directory.atomic_read(&CONTENT_ADDRESSES_FILEPATH) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|e| {
+                DataCorruption::new(
+                    CONTENT_ADDRESSES_FILEPATH.to_path_buf(),
+                    format!("Content addresses cannot be deserialized: {:?}. ", e),
+                )
+            })?,
+            Err(OpenReadError::FileDoesNotExist(_)) => HashMap::new(),
+            Err(OpenReadError::IOError(e)) => return Err(From::from(e)),
+        };
+        Ok(ContentAddressedDirectory {
+            directory: Box::new(directory),
+            content_addresses: Arc::new(RwLock::new(content_addresses)),
+            pending_files: Arc::default(),
+        })
+    }
+
+    /// Returns the path of the physical file storing the content
+    /// with the given hash.
+    pub fn blob_path(hash: &str) -> PathBuf {
+        PathBuf::from(format!("{}.blob", hash))
+    }
+
+    /// Returns the content hash of a file, or `None` if the file
+    /// is not content addressed.
+    pub fn content_hash(&self, path: &Path) -> Option<String> {
+        self.content_addresses
+            .read()
+            .expect("Content addresses lock poisoned")
+            .get(path)
+            .cloned()
+    }
+
+    /// Returns a snapshot of the mapping from logical paths to content hashes.
+    pub fn content_addresses(&self) -> HashMap<PathBuf, String> {
+        self.content_addresses
+            .read()
+            .expect("Content addresses lock poisoned")
+            .clone()
+    }
+
+    /// Returns the temporary file of a logical file being written, if any.
+    fn pending_file(&self, path: &Path) -> Option<PathBuf> {
+        self.pending_files
+            .read()
+            .expect("Pending files lock poisoned")
+            .get(path)
+            .cloned()
+    }
+
+    /// Publishes the content of the temporary file `temp_path`, whose hash is `hash`,
+    /// as the content of the logical file `path`.
+    ///
+    /// Nothing is published if the logical file was deleted while being written.
+    fn publish(&mut self, path: &Path, temp_path: &Path, hash: String) -> io::Result<()> {
+        let mut wlock = self
+            .content_addresses
+            .write()
+            .expect("Content addresses lock poisoned");
+        {
+            let mut pending_files = self
+                .pending_files
+                .write()
+                .expect("Pending files lock poisoned");
+            if pending_files.get(path).map(PathBuf::as_path) != Some(temp_path) {
+                return Ok(());
+            }
+            pending_files.remove(path);
+        }
+        let blob_path = Self::blob_path(&hash);
+        if !self.directory.exists(&blob_path) {
+            let data = self.directory.open_read(temp_path).map_err(|e| match e {
+                OpenReadError::FileDoesNotExist(path) => io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Temporary file {:?} does not exist", path),
+                ),
+                OpenReadError::IOError(io_error) => io_error.into(),
+            })?;
+            match self.directory.open_write(&blob_path) {
+                Ok(mut write) => {
+                    write.write_all(data.as_slice())?;
+                    write.terminate()?;
+                }
+                Err(OpenWriteError::FileAlreadyExists(_)) => {}
+                Err(OpenWriteError::IOError(io_error)) => {
+                    return Err(io_error.into());
+                }
+            }
+        }
+        wlock.insert(path.to_owned(), hash);
+        save_content_addresses(self.directory.as_mut(), &wlock)?;
+        if let Err(e) = self.directory.delete(temp_path) {
+            error!("Failed to delete temporary file {:?}: {:?}", temp_path, e);
+        }
+        Ok(())
+    }
+
+    fn delete_blob_if_unreferenced(
+        &self,
+        wlock: &RwLockWriteGuard<'_, HashMap<PathBuf, String>>,
+        hash: &str,
+    ) {
+        if wlock.values().any(|other_hash| other_hash == hash) {
+            return;
+        }
+        let blob_path = Self::blob_path(hash);
+        if let Err(e) = self.directory.delete(&blob_path) {
+            error!("Failed to delete blob {:?}: {:?}", blob_path, e);
+        }
+    }
+}
+
+impl Clone for ContentAddressedDirectory {
+    fn clone(&self) -> ContentAddressedDirectory {
+        ContentAddressedDirectory {
+            directory: self.directory.box_clone(),
+            content_addresses: Arc::clone(&self.content_addresses),
+            pending_files: Arc::clone(&self.pending_files),
+        }
+    }
+}
+
+impl fmt::Debug for ContentAddressedDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ContentAddressedDirectory({:?})", self.directory)
+    }
+}
+
+/// Writer associated with the `ContentAddressedDirectory`.
+///
+/// The content is written to a temporary file while being hashed,
+/// and published upon termination.
+struct ContentAddressedWriter {
+    path: PathBuf,
+    temp_path: PathBuf,
+    directory: ContentAddressedDirectory,
+    write: WritePtr,
+    hasher: Sha256,
+}
+
+impl Write for ContentAddressedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.write.write(buf)?;
+        self.hasher.input(&buf[..num_bytes]);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write.flush()
+    }
+}
+
+impl TerminatingWrite for ContentAddressedWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.write.terminate_ref(token)?;
+        let hash = hex_digest(&self.hasher.clone().result());
+        let (path, temp_path) = (self.path.clone(), self.temp_path.clone());
+        self.directory.publish(&path, &temp_path, hash)
+    }
+}
+
+impl Directory for ContentAddressedDirectory {
+    fn open_read(&self, path: &Path) -> result::Result<ReadOnlySource, OpenReadError> {
+        if let Some(hash) = self.content_hash(path) {
+            return self.directory.open_read(&Self::blob_path(&hash));
+        }
+        match self.pending_file(path) {
+            Some(temp_path) => self.directory.open_read(&temp_path),
+            None => self.directory.open_read(path),
+        }
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        let mut wlock = self
+            .content_addresses
+            .write()
+            .expect("Content addresses lock poisoned");
+        match wlock.remove(path) {
+            Some(hash) => {
+                let mut directory = self.directory.box_clone();
+                save_content_addresses(directory.as_mut(), &wlock)
+                    .map_err(|e| IOError::with_path(path.to_owned(), e))?;
+                self.delete_blob_if_unreferenced(&wlock, &hash);
+                Ok(())
+            }
+            None => {
+                let temp_path_opt = self
+                    .pending_files
+                    .write()
+                    .expect("Pending files lock poisoned")
+                    .remove(path);
+                match temp_path_opt {
+                    Some(temp_path) => self.directory.delete(&temp_path),
+                    None => self.directory.delete(path),
+                }
+            }
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.content_hash(path).is_some()
+            || self.pending_file(path).is_some()
+            || self.directory.exists(path)
+    }
+
+    fn open_write(&mut self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        if self.exists(path) {
+            return Err(OpenWriteError::FileAlreadyExists(path.to_owned()));
+        }
+        let temp_path = PathBuf::from(format!("{}.tmp", Uuid::new_v4().to_simple()));
+        let write = self.directory.open_write(&temp_path)?;
+        self.pending_files
+            .write()
+            .expect("Pending files lock poisoned")
+            .insert(path.to_owned(), temp_path.clone());
+        let writer = ContentAddressedWriter {
+            path: path.to_owned(),
+            temp_path,
+            directory: self.clone(),
+            write,
+            hasher: Sha256::new(),
+        };
+        Ok(BufWriter::new(Box::new(writer)))
+    }
+
+    fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        self.directory.atomic_read(path)
+    }
+
+    fn atomic_write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.directory.atomic_write(path, data)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.directory.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.directory.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentAddressedDirectory;
+    use crate::directory::{Directory, RAMDirectory, TerminatingWrite};
+    use crate::schema::{Schema, TEXT};
+    use crate::Index;
+    use std::io::Write;
+    use std::path::Path;
+
+    fn write_file(directory: &mut dyn Directory, path: &Path, data: &[u8]) {
+        let mut write = directory.open_write(path).unwrap();
+        write.write_all(data).unwrap();
+        write.terminate().unwrap();
+    }
+
+    #[test]
+    fn test_content_addressed_dedup() {
+        let ram_directory = RAMDirectory::create();
+        let mut directory = ContentAddressedDirectory::wrap(ram_directory.clone()).unwrap();
+        let path1 = Path::new("file1");
+        let path2 = Path::new("file2");
+        write_file(&mut directory, path1, b"hello");
+        write_file(&mut directory, path2, b"hello");
+        assert_eq!(directory.open_read(path2).unwrap().as_slice(), b"hello");
+        let hash = directory.content_hash(path1).unwrap();
+        assert_eq!(
+            hash,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(directory.content_hash(path2), Some(hash.clone()));
+        let blob_path = ContentAddressedDirectory::blob_path(&hash);
+        assert!(ram_directory.exists(&blob_path));
+        assert!(!ram_directory.exists(path1));
+
+        directory.delete(path1).unwrap();
+        assert!(!directory.exists(path1));
+        assert!(ram_directory.exists(&blob_path));
+        directory.delete(path2).unwrap();
+        assert!(!ram_directory.exists(&blob_path));
+        assert!(directory.delete(path2).is_err());
+    }
+
+    #[test]
+    fn test_content_addressed_publish_on_terminate() {
+        let ram_directory = RAMDirectory::create();
+        let mut directory = ContentAddressedDirectory::wrap(ram_directory.clone()).unwrap();
+        let path = Path::new("file");
+        let mut write = directory.open_write(path).unwrap();
+        write.write_all(b"hel").unwrap();
+        write.write_all(b"lo").unwrap();
+        write.flush().unwrap();
+        assert!(directory.exists(path));
+        assert_eq!(directory.open_read(path).unwrap().as_slice(), b"hello");
+        assert_eq!(directory.content_hash(path), None);
+        write.terminate().unwrap();
+        assert_eq!(
+            directory.content_hash(path).unwrap(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(directory.open_read(path).unwrap().as_slice(), b"hello");
+        assert!(directory.pending_files.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_content_addressed_mapping_is_persisted() {
+        let ram_directory = RAMDirectory::create();
+        let path = Path::new("file");
+        {
+            let mut directory = ContentAddressedDirectory::wrap(ram_directory.clone()).unwrap();
+            write_file(&mut directory, path, b"hello");
+        }
+        let directory = ContentAddressedDirectory::wrap(ram_directory.clone()).unwrap();
+        assert_eq!(directory.content_addresses().len(), 1);
+        assert_eq!(directory.open_read(path).unwrap().as_slice(), b"hello");
+    }
+
+    #[test]
+    fn test_content_addressed_index() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let ram_directory = RAMDirectory::create();
+        let directory = ContentAddressedDirectory::wrap(ram_directory.clone()).unwrap();
+        let index = Index::create(directory, schema).unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field=>"a b"));
+        index_writer.commit().unwrap();
+        let reader = index.reader().unwrap();
+        assert_eq!(reader.searcher().num_docs(), 1);
+    }
+}
@@ -210,6 +210,16 @@ impl ManagedDirectory {
         Ok(())
     }
 
+    /// Returns the list of the files that were created by tantivy
+    /// and are not deleted yet.
+    pub fn list_managed_files(&self) -> HashSet<PathBuf> {
+        self.meta_informations
+            .read()
+            .expect("Managed directory rlock poisoned in list managed files.")
+            .managed_paths
+            .clone()
+    }
+
     /// Verify checksum of a managed file
     pub fn validate_checksum(&self, path: &Path) -> result::Result<bool, OpenReadError> {
         let reader = self.directory.open_read(path)?;
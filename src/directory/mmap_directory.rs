@@ -46,7 +46,7 @@ pub(crate) fn make_io_err(msg: String) -> io::Error {
 
 /// Returns None iff the file exists, can be read, but is empty (and hence
 /// cannot be mmapped)
-fn open_mmap(full_path: &Path) -> result::Result<Option<Mmap>, OpenReadError> {
+pub(crate) fn open_mmap(full_path: &Path) -> result::Result<Option<Mmap>, OpenReadError> {
     let file = File::open(full_path).map_err(|e| {
         if e.kind() == io::ErrorKind::NotFound {
             OpenReadError::FileDoesNotExist(full_path.to_owned())
@@ -84,47 +84,147 @@ pub struct CacheCounters {
 pub struct CacheInfo {
     pub counters: CacheCounters,
     pub mmapped: Vec<PathBuf>,
+    /// Total number of bytes currently mapped.
+    pub mapped_bytes: usize,
 }
 
-struct MmapCache {
-    counters: CacheCounters,
-    cache: HashMap<PathBuf, Weak<BoxedData>>,
+struct MmapCacheEntry {
+    mmap: Weak<BoxedData>,
+    num_bytes: usize,
+    last_access: u64,
+    // Keeps the file mapped when no `ReadOnlySource` points to it anymore.
+    // Only set when the cache has a budget of mapped bytes.
+    retained: Option<Arc<BoxedData>>,
 }
 
-impl Default for MmapCache {
-    fn default() -> MmapCache {
-        MmapCache {
-            counters: CacheCounters::default(),
-            cache: HashMap::new(),
-        }
+impl MmapCacheEntry {
+    fn is_mapped(&self) -> bool {
+        self.mmap.upgrade().is_some()
+    }
+
+    // Returns true iff some `ReadOnlySource` points to the mmap.
+    fn is_referenced(&self) -> bool {
+        let num_retained = if self.retained.is_some() { 1 } else { 0 };
+        self.mmap.strong_count() > num_retained
     }
 }
 
+#[derive(Default)]
+struct MmapCache {
+    counters: CacheCounters,
+    cache: HashMap<PathBuf, MmapCacheEntry>,
+    max_mapped_bytes: Option<usize>,
+    clock: u64,
+}
+
 impl MmapCache {
     fn get_info(&self) -> CacheInfo {
         let paths: Vec<PathBuf> = self.cache.keys().cloned().collect();
         CacheInfo {
             counters: self.counters.clone(),
             mmapped: paths,
+            mapped_bytes: self.mapped_bytes(),
         }
     }
 
-    fn remove_weak_ref(&mut self) {
+    fn mapped_bytes(&self) -> usize {
+        self.cache
+            .values()
+            .filter(|entry| entry.is_mapped())
+            .map(|entry| entry.num_bytes)
+            .sum()
+    }
+
+    // Removes the entries of the files that are not mapped anymore.
+    // Returns the number of removed entries.
+    fn remove_weak_ref(&mut self) -> usize {
         let keys_to_remove: Vec<PathBuf> = self
             .cache
             .iter()
-            .filter(|(_, mmap_weakref)| mmap_weakref.upgrade().is_none())
+            .filter(|(_, entry)| !entry.is_mapped())
             .map(|(key, _)| key.clone())
             .collect();
-        for key in keys_to_remove {
-            self.cache.remove(&key);
+        for key in &keys_to_remove {
+            self.cache.remove(key);
+        }
+        keys_to_remove.len()
+    }
+
+    // Unmaps the files that are only kept mapped by the cache, and removes
+    // the entries of the files that are not mapped anymore.
+    // Returns the number of removed entries.
+    fn release_unreferenced(&mut self) -> usize {
+        for entry in self.cache.values_mut() {
+            if !entry.is_referenced() {
+                entry.retained = None;
+            }
+        }
+        self.remove_weak_ref()
+    }
+
+    // Stops retaining the mmap of a file, typically before it gets deleted.
+    fn release(&mut self, full_path: &Path) {
+        if let Some(entry) = self.cache.get_mut(full_path) {
+            entry.retained = None;
+        }
+    }
+
+    fn set_max_mapped_bytes(&mut self, max_mapped_bytes: Option<usize>) {
+        self.max_mapped_bytes = max_mapped_bytes;
+        if max_mapped_bytes.is_none() {
+            for entry in self.cache.values_mut() {
+                entry.retained = None;
+            }
+        }
+        self.enforce_max_mapped_bytes();
+    }
+
+    // Enforces `max_mapped_bytes` by releasing the mmaps retained by the cache,
+    // the least recently used first, until the mapped bytes fit in the budget.
+    //
+    // Files still referenced by a `ReadOnlySource` stay mapped until their
+    // last source is dropped.
+    fn enforce_max_mapped_bytes(&mut self) {
+        let max_mapped_bytes = if let Some(max_mapped_bytes) = self.max_mapped_bytes {
+            max_mapped_bytes
+        } else {
+            return;
+        };
+        let mut mapped_bytes = self.mapped_bytes();
+        if mapped_bytes <= max_mapped_bytes {
+            return;
+        }
+        let mut retained_paths: Vec<(u64, PathBuf)> = self
+            .cache
+            .iter()
+            .filter(|(_, entry)| entry.retained.is_some())
+            .map(|(path, entry)| (entry.last_access, path.clone()))
+            .collect();
+        retained_paths.sort();
+        for (_, path) in retained_paths {
+            if let Some(entry) = self.cache.get_mut(&path) {
+                entry.retained = None;
+                if !entry.is_mapped() {
+                    mapped_bytes -= entry.num_bytes;
+                    self.cache.remove(&path);
+                }
+            }
+            if mapped_bytes <= max_mapped_bytes {
+                break;
+            }
         }
     }
 
     // Returns None if the file exists but as a len of 0 (and hence is not mmappable).
     fn get_mmap(&mut self, full_path: &Path) -> Result<Option<Arc<BoxedData>>, OpenReadError> {
-        if let Some(mmap_weak) = self.cache.get(full_path) {
-            if let Some(mmap_arc) = mmap_weak.upgrade() {
+        self.clock += 1;
+        let retain = self.max_mapped_bytes.is_some();
+        if let Some(entry) = self.cache.get_mut(full_path) {
+            if let Some(mmap_arc) = entry.mmap.upgrade() {
+                entry.last_access = self.clock;
+                if retain && entry.retained.is_none() {
+                    entry.retained = Some(mmap_arc.clone());
+                }
                 self.counters.hit += 1;
                 return Ok(Some(mmap_arc));
             }
@@ -132,9 +232,19 @@ impl MmapCache {
         self.cache.remove(full_path);
         self.counters.miss += 1;
         Ok(if let Some(mmap) = open_mmap(full_path)? {
+            let num_bytes = mmap.len();
             let mmap_arc: Arc<BoxedData> = Arc::new(Box::new(mmap));
             let mmap_weak = Arc::downgrade(&mmap_arc);
-            self.cache.insert(full_path.to_owned(), mmap_weak);
+            self.cache.insert(
+                full_path.to_owned(),
+                MmapCacheEntry {
+                    mmap: mmap_weak,
+                    num_bytes,
+                    last_access: self.clock,
+                    retained: if retain { Some(mmap_arc.clone()) } else { None },
+                },
+            );
+            self.enforce_max_mapped_bytes();
             Some(mmap_arc)
         } else {
             None
@@ -357,6 +467,44 @@ impl MmapDirectory {
             .expect("Mmap cache lock is poisoned.")
             .get_info()
     }
+
+    /// Unmaps the files that are not referenced by any `ReadOnlySource`
+    /// anymore, typically the files of the segments that were merged or
+    /// garbage collected, and releases their cache entries.
+    ///
+    /// Without a budget (see `set_max_mapped_bytes`), a file is unmapped
+    /// as soon as the last `ReadOnlySource` pointing to it is dropped, and
+    /// this only cleans the cache up.
+    ///
+    /// Returns the number of released entries.
+    pub fn release_unreferenced_mmaps(&self) -> usize {
+        self.inner
+            .mmap_cache
+            .write()
+            .expect("mmap cache lock is poisoned")
+            .release_unreferenced()
+    }
+
+    /// Sets a budget on the total number of bytes mapped by the directory.
+    ///
+    /// With a budget, the directory keeps the files it maps mapped after
+    /// their last `ReadOnlySource` is dropped, so that opening them again does
+    /// not call `mmap`. Whenever the mapped bytes exceed the budget, these
+    /// files are unmapped, the least recently opened first.
+    ///
+    /// The files still referenced by a `ReadOnlySource` (for instance by a
+    /// living `Searcher`) cannot be unmapped, and may exceed the budget
+    /// on their own.
+    ///
+    /// `None` (the default) means no budget: files are unmapped as soon as
+    /// they are not referenced anymore.
+    pub fn set_max_mapped_bytes(&self, max_mapped_bytes: Option<usize>) {
+        self.inner
+            .mmap_cache
+            .write()
+            .expect("mmap cache lock is poisoned")
+            .set_max_mapped_bytes(max_mapped_bytes);
+    }
 }
 
 /// We rely on fs2 for file locking. On Windows & MacOS this
@@ -430,6 +578,9 @@ impl Directory for MmapDirectory {
     /// removed before the file is deleted.
     fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
         let full_path = self.resolve_path(path);
+        if let Ok(mut mmap_cache) = self.inner.mmap_cache.write() {
+            mmap_cache.release(&full_path);
+        }
         match fs::remove_file(&full_path) {
             Ok(_) => self
                 .sync_directory()
@@ -633,6 +784,99 @@ mod tests {
         assert_eq!(mmap_directory.get_cache_info().mmapped.len(), 0);
     }
 
+    #[test]
+    fn test_mapped_bytes_and_release() {
+        let mut mmap_directory = MmapDirectory::create_from_tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| PathBuf::from(&*format!("file_{}", i)))
+            .collect();
+        for path in &paths {
+            let mut w = mmap_directory.open_write(path).unwrap();
+            w.write_all(b"abcd").unwrap();
+            w.flush().unwrap();
+        }
+        let first = mmap_directory.open_read(&paths[0]).unwrap();
+        let second = mmap_directory.open_read(&paths[1]).unwrap();
+        let _third = mmap_directory.open_read(&paths[2]).unwrap();
+        {
+            let cache = mmap_directory.inner.mmap_cache.read().unwrap();
+            assert_eq!(cache.mapped_bytes(), 12);
+        }
+        // Dropping the last source of a file unmaps it.
+        drop(second);
+        {
+            let cache = mmap_directory.inner.mmap_cache.read().unwrap();
+            assert_eq!(cache.cache.len(), 3);
+            assert_eq!(cache.mapped_bytes(), 8);
+        }
+        drop(first);
+        assert_eq!(mmap_directory.release_unreferenced_mmaps(), 2);
+        let cache_info = mmap_directory.get_cache_info();
+        assert_eq!(
+            cache_info.mmapped,
+            vec![mmap_directory.resolve_path(&paths[2])]
+        );
+        assert_eq!(cache_info.mapped_bytes, 4);
+    }
+
+    #[test]
+    fn test_max_mapped_bytes() {
+        let mut mmap_directory = MmapDirectory::create_from_tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| PathBuf::from(&*format!("file_{}", i)))
+            .collect();
+        for path in &paths {
+            let mut w = mmap_directory.open_write(path).unwrap();
+            w.write_all(b"abcd").unwrap();
+            w.flush().unwrap();
+        }
+        mmap_directory.set_max_mapped_bytes(Some(8));
+        // Files stay mapped once their sources are dropped.
+        drop(mmap_directory.open_read(&paths[0]).unwrap());
+        drop(mmap_directory.open_read(&paths[1]).unwrap());
+        assert_eq!(mmap_directory.get_cache_info().mapped_bytes, 8);
+        drop(mmap_directory.open_read(&paths[0]).unwrap());
+        assert_eq!(mmap_directory.get_cache_info().counters.hit, 1);
+        // The budget is exceeded: the least recently opened file gets unmapped.
+        drop(mmap_directory.open_read(&paths[2]).unwrap());
+        let mut cache_info = mmap_directory.get_cache_info();
+        assert_eq!(cache_info.mapped_bytes, 8);
+        cache_info.mmapped.sort();
+        assert_eq!(
+            cache_info.mmapped,
+            vec![
+                mmap_directory.resolve_path(&paths[0]),
+                mmap_directory.resolve_path(&paths[2])
+            ]
+        );
+        assert_eq!(mmap_directory.release_unreferenced_mmaps(), 2);
+        assert_eq!(mmap_directory.get_cache_info().mapped_bytes, 0);
+    }
+
+    #[test]
+    fn test_max_mapped_bytes_referenced() {
+        let mut mmap_directory = MmapDirectory::create_from_tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..2)
+            .map(|i| PathBuf::from(&*format!("file_{}", i)))
+            .collect();
+        for path in &paths {
+            let mut w = mmap_directory.open_write(path).unwrap();
+            w.write_all(b"abcd").unwrap();
+            w.flush().unwrap();
+        }
+        mmap_directory.set_max_mapped_bytes(Some(4));
+        let first = mmap_directory.open_read(&paths[0]).unwrap();
+        let second = mmap_directory.open_read(&paths[1]).unwrap();
+        // Referenced files cannot be unmapped...
+        assert_eq!(mmap_directory.get_cache_info().mapped_bytes, 8);
+        assert_eq!(first.as_slice(), b"abcd");
+        // ... but are not retained anymore once the budget is exceeded.
+        drop(first);
+        assert_eq!(mmap_directory.get_cache_info().mapped_bytes, 4);
+        drop(second);
+        assert_eq!(mmap_directory.get_cache_info().mapped_bytes, 0);
+    }
+
     #[test]
     fn test_watch_wrapper() {
         let counter: Arc<AtomicUsize> = Default::default();
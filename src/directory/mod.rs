@@ -7,10 +7,13 @@ WORM directory abstraction.
 #[cfg(feature = "mmap")]
 mod mmap_directory;
 
+mod archive_directory;
+mod content_addressed_directory;
 mod directory;
 mod directory_lock;
 mod footer;
 mod managed_directory;
+mod overlay_directory;
 mod ram_directory;
 mod read_only_source;
 mod watch_event_router;
@@ -18,6 +21,8 @@ mod watch_event_router;
 /// Errors specific to the directory module.
 pub mod error;
 
+pub use self::archive_directory::ArchiveDirectory;
+pub use self::content_addressed_directory::ContentAddressedDirectory;
 pub use self::directory::DirectoryLock;
 pub use self::directory::{Directory, DirectoryClone};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
@@ -31,6 +36,7 @@ use std::io::{self, BufWriter, Write};
 pub use self::mmap_directory::MmapDirectory;
 
 pub use self::managed_directory::ManagedDirectory;
+pub use self::overlay_directory::OverlayDirectory;
 
 /// Struct used to prevent from calling [`terminate_ref`](trait.TerminatingWrite#method.terminate_ref) directly
 pub struct AntiCallToken(());
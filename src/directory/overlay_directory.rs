@@ -0,0 +1,188 @@
+use crate::directory::error::LockError;
+use crate::directory::error::{DeleteError, OpenReadError, OpenWriteError};
+use crate::directory::{Directory, DirectoryLock, Lock, ReadOnlySource, WritePtr};
+use crate::directory::{WatchCallback, WatchHandle};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::result;
+
+/// Directory layering a writable overlay over a read-only base directory.
+///
+/// Reads are first attempted on the overlay, and fall back to the base.
+/// All writes (including `meta.json` and the managed file list) go to the overlay.
+/// The base directory is never modified.
+///
+/// This makes it possible to serve a large immutable index shared between
+/// several tenants, while each tenant adds its own incremental segments
+/// on top of it without copying the base.
+///
+/// Deleting a file that only exists in the base is a no-op. The file
+/// remains visible through the overlay directory.
+pub struct OverlayDirectory {
+    base: Box<dyn Directory>,
+    overlay: Box<dyn Directory>,
+}
+
+impl OverlayDirectory {
+    /// Creates a new `OverlayDirectory`.
+    ///
+    /// `base` is only used for reads, while `overlay` receives all of the writes.
+    pub fn new<B: Directory, O: Directory>(base: B, overlay: O) -> OverlayDirectory {
+        OverlayDirectory {
+            base: Box::new(base),
+            overlay: Box::new(overlay),
+        }
+    }
+
+    /// Returns true iff the file exists in the read-only base directory,
+    /// and is not shadowed by the overlay.
+    pub fn is_in_base(&self, path: &Path) -> bool {
+        !self.overlay.exists(path) && self.base.exists(path)
+    }
+}
+
+impl Clone for OverlayDirectory {
+    fn clone(&self) -> OverlayDirectory {
+        OverlayDirectory {
+            base: self.base.box_clone(),
+            overlay: self.overlay.box_clone(),
+        }
+    }
+}
+
+impl fmt::Debug for OverlayDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OverlayDirectory({:?}, {:?})", self.base, self.overlay)
+    }
+}
+
+impl Directory for OverlayDirectory {
+    fn open_read(&self, path: &Path) -> result::Result<ReadOnlySource, OpenReadError> {
+        match self.overlay.open_read(path) {
+            Err(OpenReadError::FileDoesNotExist(_)) => self.base.open_read(path),
+            read_res => read_res,
+        }
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        match self.overlay.delete(path) {
+            Err(DeleteError::FileDoesNotExist(_)) if self.base.exists(path) => {
+                debug!("Ignoring deletion of {:?}, as it belongs to the base", path);
+                Ok(())
+            }
+            delete_res => delete_res,
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.overlay.exists(path) || self.base.exists(path)
+    }
+
+    fn open_write(&mut self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        if self.base.exists(path) {
+            return Err(OpenWriteError::FileAlreadyExists(path.to_owned()));
+        }
+        self.overlay.open_write(path)
+    }
+
+    fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        match self.overlay.atomic_read(path) {
+            Err(OpenReadError::FileDoesNotExist(_)) => self.base.atomic_read(path),
+            read_res => read_res,
+        }
+    }
+
+    fn atomic_write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.overlay.atomic_write(path, data)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.overlay.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.overlay.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OverlayDirectory;
+    use crate::collector::Count;
+    use crate::directory::{Directory, RAMDirectory, TerminatingWrite};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Index, Term};
+    use std::io::Write;
+    use std::path::Path;
+
+    #[test]
+    fn test_overlay_reads_from_base_and_writes_to_overlay() {
+        let path = Path::new("some_file");
+        let mut base = RAMDirectory::create();
+        let mut write = base.open_write(path).unwrap();
+        write.write_all(b"base").unwrap();
+        write.terminate().unwrap();
+
+        let overlay = RAMDirectory::create();
+        let mut directory = OverlayDirectory::new(base.clone(), overlay.clone());
+        assert!(directory.exists(path));
+        assert!(directory.is_in_base(path));
+        assert_eq!(directory.open_read(path).unwrap().as_slice(), b"base");
+        assert!(directory.open_write(path).is_err());
+
+        let other_path = Path::new("other_file");
+        let mut write = directory.open_write(other_path).unwrap();
+        write.write_all(b"overlay").unwrap();
+        write.terminate().unwrap();
+        assert!(overlay.exists(other_path));
+        assert!(!base.exists(other_path));
+        assert!(!directory.is_in_base(other_path));
+
+        assert!(directory.delete(path).is_ok());
+        assert!(base.exists(path));
+        assert!(directory.delete(other_path).is_ok());
+        assert!(!overlay.exists(other_path));
+        assert!(directory.delete(other_path).is_err());
+    }
+
+    #[test]
+    fn test_overlay_index_does_not_modify_base() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let base = RAMDirectory::create();
+        {
+            let index = Index::create(base.clone(), schema).unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text_field=>"a b"));
+            index_writer.add_document(doc!(text_field=>"a c"));
+            index_writer.commit().unwrap();
+        }
+        let base_mem_usage = base.total_mem_usage();
+        let index =
+            Index::open(OverlayDirectory::new(base.clone(), RAMDirectory::create())).unwrap();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text_field=>"a d"));
+            index_writer.delete_term(Term::from_field_text(text_field, "b"));
+            index_writer.commit().unwrap();
+            index_writer.wait_merging_threads().unwrap();
+        }
+        let count_docs = |index: &Index, text: &str| {
+            let searcher = index.reader().unwrap().searcher();
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count_docs(&index, "a"), 2);
+        assert_eq!(count_docs(&index, "b"), 0);
+        let base_index = Index::open(base.clone()).unwrap();
+        assert_eq!(count_docs(&base_index, "a"), 2);
+        assert_eq!(count_docs(&base_index, "b"), 1);
+        assert_eq!(base.total_mem_usage(), base_mem_usage);
+    }
+}
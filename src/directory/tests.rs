@@ -15,6 +15,20 @@ fn test_ram_directory() {
     test_directory(&mut ram_directory);
 }
 
+#[test]
+fn test_content_addressed_directory() {
+    let mut content_addressed_directory =
+        ContentAddressedDirectory::wrap(RAMDirectory::create()).unwrap();
+    test_directory(&mut content_addressed_directory);
+}
+
+#[test]
+fn test_overlay_directory() {
+    let mut overlay_directory =
+        OverlayDirectory::new(RAMDirectory::create(), RAMDirectory::create());
+    test_directory(&mut overlay_directory);
+}
+
 #[test]
 #[cfg(feature = "mmap")]
 fn test_mmap_directory() {
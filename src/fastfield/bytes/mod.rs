@@ -1,18 +1,26 @@
+mod multi_value_reader;
+mod multi_value_writer;
 mod reader;
 mod writer;
 
+pub use self::multi_value_reader::MultiValueBytesFastFieldReader;
+pub use self::multi_value_writer::MultiValueBytesFastFieldWriter;
 pub use self::reader::BytesFastFieldReader;
 pub use self::writer::BytesFastFieldWriter;
 
 #[cfg(test)]
 mod tests {
-    use crate::schema::Schema;
-    use crate::Index;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{ExistsQuery, QueryParser, TermQuery};
+    use crate::schema::{BytesOptions, Cardinality, IndexRecordOption, Schema};
+    use crate::schema::{FAST, INDEXED, STRING};
+    use crate::{DocAddress, DocId, Index, Score, SegmentReader, Term};
+    use futures::Future;
 
     #[test]
     fn test_bytes() {
         let mut schema_builder = Schema::builder();
-        let field = schema_builder.add_bytes_field("bytesfield");
+        let field = schema_builder.add_bytes_field("bytesfield", FAST);
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
@@ -32,5 +40,178 @@ mod tests {
         assert_eq!(bytes_reader.get_bytes(3), &[1u8, 3, 5, 7, 9]);
         let long = vec![0u8; 1000];
         assert_eq!(bytes_reader.get_bytes(4), long.as_slice());
+        assert_eq!(bytes_reader.num_bytes(0), 4);
+        assert_eq!(bytes_reader.num_bytes(1), 0);
+        assert_eq!(bytes_reader.num_bytes(4), 1000);
+    }
+
+    fn features_to_bytes(features: &[f32]) -> Vec<u8> {
+        features
+            .iter()
+            .flat_map(|feature| feature.to_bits().to_le_bytes().to_vec())
+            .collect()
+    }
+
+    fn bytes_to_features(bytes: &[u8]) -> Vec<f32> {
+        bytes
+            .chunks(4)
+            .map(|chunk| {
+                let mut buf = [0u8; 4];
+                buf.copy_from_slice(chunk);
+                f32::from_bits(u32::from_le_bytes(buf))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bytes_feature_vector_scoring() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let features_field = schema_builder.add_bytes_field("features", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(
+            text_field => "a",
+            features_field => features_to_bytes(&[1.0, 0.0])
+        ));
+        index_writer.add_document(doc!(
+            text_field => "a",
+            features_field => features_to_bytes(&[0.5, 3.0])
+        ));
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let weights = [2.0f32, 1.0f32];
+        let collector =
+            TopDocs::with_limit(3).tweak_score(move |segment_reader: &SegmentReader| {
+                let features_reader = segment_reader.fast_fields().bytes(features_field).unwrap();
+                move |doc: DocId, _original_score: Score| {
+                    bytes_to_features(features_reader.get_bytes(doc))
+                        .iter()
+                        .zip(weights.iter())
+                        .map(|(feature, weight)| feature * weight)
+                        .sum::<f32>()
+                }
+            });
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        let top_docs = searcher.search(&query, &collector).unwrap();
+        assert_eq!(
+            top_docs,
+            vec![
+                (4.0f32, DocAddress(0, 1)),
+                (2.0f32, DocAddress(0, 0)),
+                (0.0f32, DocAddress(0, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_indexed_bytes() {
+        let mut schema_builder = Schema::builder();
+        let hash_field = schema_builder.add_bytes_field("hash", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(hash_field => vec![0u8, 1, 2]));
+        index_writer.add_document(doc!(hash_field => vec![0u8, 1]));
+        index_writer.add_document(doc!(hash_field => vec![0u8, 1, 2]));
+        index_writer.add_document(doc!(hash_field => vec![255u8]));
+        index_writer.commit().unwrap();
+        let reader = index.reader().unwrap();
+        let count = |bytes: &[u8]| {
+            let query = TermQuery::new(
+                Term::from_field_bytes(hash_field, bytes),
+                IndexRecordOption::Basic,
+            );
+            reader.searcher().search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(&[0u8, 1, 2]), 2);
+        assert_eq!(count(&[0u8, 1]), 1);
+        assert_eq!(count(&[0u8]), 0);
+        assert_eq!(count(&[255u8]), 1);
+        {
+            let searcher = reader.searcher();
+            let fast_fields = searcher.segment_reader(0).fast_fields();
+            assert!(fast_fields.bytes(hash_field).is_none());
+        }
+
+        let query_parser = QueryParser::for_index(&index, vec![hash_field]);
+        let query = query_parser.parse_query("hash:AAEC").unwrap();
+        assert_eq!(reader.searcher().search(&query, &Count).unwrap(), 2);
+        assert!(query_parser.parse_query("hash:-").is_err());
+
+        index_writer.delete_term(Term::from_field_bytes(hash_field, &[0u8, 1, 2]));
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(count(&[0u8, 1, 2]), 0);
+        assert_eq!(count(&[0u8, 1]), 1);
+    }
+
+    #[test]
+    fn test_multi_valued_bytes() {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let hashes_field = schema_builder.add_bytes_field(
+            "hashes",
+            BytesOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(
+            id_field => 0u64,
+            hashes_field => vec![1u8, 2],
+            hashes_field => vec![],
+            hashes_field => vec![3u8],
+        ));
+        index_writer.add_document(doc!(id_field => 1u64));
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(id_field => 2u64, hashes_field => vec![4u8; 100]));
+        index_writer.add_document(doc!(id_field => 3u64, hashes_field => vec![5u8]));
+        index_writer.commit().unwrap();
+
+        let reader = index.reader().unwrap();
+        {
+            let searcher = reader.searcher();
+            let multi_bytes_reader = searcher
+                .segment_reader(0)
+                .fast_fields()
+                .multi_bytes(hashes_field)
+                .unwrap();
+            let mut vals = Vec::new();
+            multi_bytes_reader.get_vals(0, &mut vals);
+            assert_eq!(vals, vec![&[1u8, 2][..], &[][..], &[3u8][..]]);
+            multi_bytes_reader.get_vals(1, &mut vals);
+            assert!(vals.is_empty());
+            assert_eq!(multi_bytes_reader.num_vals(0), 3);
+            assert_eq!(multi_bytes_reader.num_vals(1), 0);
+            assert_eq!(multi_bytes_reader.total_num_vals(), 3);
+            let exists_query = ExistsQuery::new(hashes_field);
+            assert_eq!(searcher.search(&exists_query, &Count).unwrap(), 3);
+        }
+
+        index_writer.delete_term(Term::from_field_u64(id_field, 2u64));
+        index_writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.num_docs(), 3);
+        let multi_bytes_reader = segment_reader
+            .fast_fields()
+            .multi_bytes(hashes_field)
+            .unwrap();
+        let mut vals = Vec::new();
+        multi_bytes_reader.get_vals(0, &mut vals);
+        assert_eq!(vals, vec![&[1u8, 2][..], &[][..], &[3u8][..]]);
+        multi_bytes_reader.get_vals(1, &mut vals);
+        assert!(vals.is_empty());
+        multi_bytes_reader.get_vals(2, &mut vals);
+        assert_eq!(vals, vec![&[5u8][..]]);
+        assert_eq!(multi_bytes_reader.total_num_vals(), 4);
     }
 }
@@ -0,0 +1,56 @@
+use crate::fastfield::{BytesFastFieldReader, FastFieldReader};
+use crate::DocId;
+
+/// Reader for multi-valued bytes fast fields.
+///
+/// The reader is implemented as a `u64` fast field and a bytes fast field.
+///
+/// The `vals_reader` will access the concatenated list of all values for
+/// all documents, as if each value was a document of its own.
+///
+/// The `idx_reader` associates, for each document, the index of its first value.
+#[derive(Clone)]
+pub struct MultiValueBytesFastFieldReader {
+    idx_reader: FastFieldReader<u64>,
+    vals_reader: BytesFastFieldReader,
+}
+
+impl MultiValueBytesFastFieldReader {
+    pub(crate) fn open(
+        idx_reader: FastFieldReader<u64>,
+        vals_reader: BytesFastFieldReader,
+    ) -> MultiValueBytesFastFieldReader {
+        MultiValueBytesFastFieldReader {
+            idx_reader,
+            vals_reader,
+        }
+    }
+
+    /// Returns `(start, stop)`, such that the values associated
+    /// to the given document are `start..stop`.
+    fn range(&self, doc: DocId) -> (u64, u64) {
+        let mut offsets = [0u64; 2];
+        self.idx_reader.get_range(doc, &mut offsets[..]);
+        (offsets[0], offsets[1])
+    }
+
+    /// Returns the values associated to the given `doc`.
+    pub fn get_vals<'a>(&'a self, doc: DocId, vals: &mut Vec<&'a [u8]>) {
+        let (start, stop) = self.range(doc);
+        vals.clear();
+        for val_ord in start..stop {
+            vals.push(self.vals_reader.get_bytes(val_ord as DocId));
+        }
+    }
+
+    /// Returns the number of values associated with the document `DocId`.
+    pub fn num_vals(&self, doc: DocId) -> usize {
+        let (start, stop) = self.range(doc);
+        (stop - start) as usize
+    }
+
+    /// Returns the overall number of values in this field.
+    pub fn total_num_vals(&self) -> u64 {
+        self.idx_reader.max_value()
+    }
+}
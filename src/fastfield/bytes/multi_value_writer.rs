@@ -0,0 +1,98 @@
+use std::io;
+
+use crate::fastfield::serializer::FastFieldSerializer;
+use crate::fastfield::BytesFastFieldWriter;
+use crate::schema::{Document, Field, Value};
+use crate::DocId;
+
+/// Writer for multi-valued bytes fast fields.
+///
+/// The values are written by a `BytesFastFieldWriter`, as if each
+/// value was a document of its own. The `MultiValueBytesFastFieldWriter`
+/// additionally keeps track of the index of the first value of each document,
+/// similarly to the `MultiValueIntFastFieldWriter`.
+///
+/// The `MultiValueBytesFastFieldWriter` can be acquired from the
+/// fast field writer by calling
+/// [`.get_multi_bytes_writer(...)`](./struct.FastFieldsWriter.html#method.get_multi_bytes_writer).
+///
+/// Once acquired, writing is done by calling `.add_document_vals(&[&[u8]])`
+/// once per document, even if there are no values associated to it.
+pub struct MultiValueBytesFastFieldWriter {
+    doc_index: Vec<u64>,
+    vals_writer: BytesFastFieldWriter,
+}
+
+impl MultiValueBytesFastFieldWriter {
+    /// Creates a new `MultiValueBytesFastFieldWriter`
+    pub fn new(field: Field) -> Self {
+        MultiValueBytesFastFieldWriter {
+            doc_index: Vec::new(),
+            vals_writer: BytesFastFieldWriter::new(field),
+        }
+    }
+
+    /// Access the field associated to the `MultiValueBytesFastFieldWriter`
+    pub fn field(&self) -> Field {
+        self.vals_writer.field()
+    }
+
+    /// Finalize the current document.
+    fn next_doc(&mut self) {
+        self.doc_index.push(self.num_vals());
+    }
+
+    fn num_vals(&self) -> u64 {
+        self.vals_writer.num_docs() as u64
+    }
+
+    /// Shift to the next document and add all of the
+    /// matching field values present in the document.
+    pub fn add_document(&mut self, doc: &Document) {
+        self.next_doc();
+        for field_value in doc.field_values() {
+            if field_value.field() == self.field() {
+                if let Value::Bytes(ref bytes) = *field_value.value() {
+                    self.vals_writer.add_document_val(bytes);
+                } else {
+                    panic!(
+                        "Bytes field contained non-Bytes Value!. Field {:?} = {:?}",
+                        self.field(),
+                        field_value
+                    );
+                }
+            }
+        }
+    }
+
+    /// Register the values associated to a document.
+    ///
+    /// The method returns the `DocId` of the document that was
+    /// just written.
+    pub fn add_document_vals(&mut self, vals: &[&[u8]]) -> DocId {
+        let doc = self.doc_index.len() as DocId;
+        self.next_doc();
+        for val in vals {
+            self.vals_writer.add_document_val(val);
+        }
+        doc
+    }
+
+    /// Serializes the fast field values by pushing them to the `FastFieldSerializer`.
+    ///
+    /// The index of the first value of each document is written with the index `0`.
+    /// The values are then written with the indexes `1` and `2`.
+    pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
+        {
+            let num_vals = self.num_vals();
+            let mut doc_index_serializer =
+                serializer.new_u64_fast_field_with_idx(self.field(), 0, num_vals, 0)?;
+            for &offset in &self.doc_index {
+                doc_index_serializer.add_val(offset)?;
+            }
+            doc_index_serializer.add_val(num_vals)?;
+            doc_index_serializer.close_field()?;
+        }
+        self.vals_writer.serialize_with_idx(serializer, 1)
+    }
+}
@@ -14,6 +14,10 @@ use crate::DocId;
 ///
 /// Reading the value for a document is done by reading the start index for it,
 /// and the start index for the next document, and keeping the bytes in between.
+///
+/// The bytes of a document are an arbitrary payload: they can for instance
+/// hold a serialized feature vector, decoded at scoring time
+/// (see [`TopDocs::tweak_score`](../collector/struct.TopDocs.html#method.tweak_score)).
 #[derive(Clone)]
 pub struct BytesFastFieldReader {
     idx_reader: FastFieldReader<u64>,
@@ -29,10 +33,15 @@ impl BytesFastFieldReader {
         BytesFastFieldReader { idx_reader, values }
     }
 
+    /// Returns the reader of the offset of the first byte of each document.
+    pub(crate) fn idx_reader(&self) -> &FastFieldReader<u64> {
+        &self.idx_reader
+    }
+
     fn range(&self, doc: DocId) -> (usize, usize) {
-        let start = self.idx_reader.get(doc) as usize;
-        let stop = self.idx_reader.get(doc + 1) as usize;
-        (start, stop)
+        let mut offsets = [0u64; 2];
+        self.idx_reader.get_range(doc, &mut offsets[..]);
+        (offsets[0] as usize, offsets[1] as usize)
     }
 
     /// Returns the bytes associated to the given `doc`
@@ -41,6 +50,17 @@ impl BytesFastFieldReader {
         &self.values[start..stop]
     }
 
+    /// Returns the number of bytes associated to the given `doc`
+    pub fn num_bytes(&self, doc: DocId) -> usize {
+        let (start, stop) = self.range(doc);
+        stop - start
+    }
+
+    /// Returns the concatenated bytes of all of the documents.
+    pub(crate) fn all_bytes(&self) -> &[u8] {
+        &self.values[..]
+    }
+
     /// Returns the overall number of bytes in this bytes fast field.
     pub fn total_num_bytes(&self) -> usize {
         self.values.len()
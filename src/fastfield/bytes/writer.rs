@@ -40,6 +40,11 @@ impl BytesFastFieldWriter {
         self.field
     }
 
+    /// Returns the number of documents written so far.
+    pub(crate) fn num_docs(&self) -> usize {
+        self.doc_index.len()
+    }
+
     /// Finalize the current document.
     pub(crate) fn next_doc(&mut self) {
         self.doc_index.push(self.vals.len() as u64);
@@ -76,10 +81,24 @@ impl BytesFastFieldWriter {
 
     /// Serializes the fast field values by pushing them to the `FastFieldSerializer`.
     pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
+        self.serialize_with_idx(serializer, 0)
+    }
+
+    /// Serializes the offset index with the index `idx`, and the values
+    /// themselves with the index `idx + 1`.
+    pub(crate) fn serialize_with_idx(
+        &self,
+        serializer: &mut FastFieldSerializer,
+        idx: usize,
+    ) -> io::Result<()> {
         {
             // writing the offset index
-            let mut doc_index_serializer =
-                serializer.new_u64_fast_field_with_idx(self.field, 0, self.vals.len() as u64, 0)?;
+            let mut doc_index_serializer = serializer.new_u64_fast_field_with_idx(
+                self.field,
+                0,
+                self.vals.len() as u64,
+                idx,
+            )?;
             for &offset in &self.doc_index {
                 doc_index_serializer.add_val(offset)?;
             }
@@ -88,7 +107,8 @@ impl BytesFastFieldWriter {
         }
         {
             // writing the values themselves
-            let mut value_serializer = serializer.new_bytes_fast_field_with_idx(self.field, 1)?;
+            let mut value_serializer =
+                serializer.new_bytes_fast_field_with_idx(self.field, idx + 1)?;
             value_serializer.write_all(&self.vals)?;
         }
         Ok(())
@@ -0,0 +1,814 @@
+use crate::common::bitpacker::{BitPacker, BitUnpacker};
+use crate::common::compute_num_bits;
+use crate::common::BinarySerializable;
+use crate::directory::ReadOnlySource;
+use owning_ref::OwningRef;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+/// Number of values in a block of the `Blockwise` codec.
+const BLOCK_SIZE: usize = 128;
+
+/// Codecs available to encode a fast field column.
+///
+/// The codec is picked automatically upon serialization, by
+/// estimating the size of the column with each of them.
+/// All codecs offer O(1) random access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FastFieldCodec {
+    /// Bitpacks `val - min_value`.
+    Bitpacked = 0,
+    /// Bitpacks `(val - min_value) / gcd`, where `gcd` is the greatest
+    /// common divisor of all of the `val - min_value`.
+    /// This is typically useful for timestamps with a coarse precision.
+    Gcd = 1,
+    /// Bitpacks the difference between the values and a linear interpolation
+    /// of the first and the last values.
+    /// This is typically useful for monotonic values, like ids.
+    ///
+    /// It stands in for a delta codec: bitpacking the difference between
+    /// consecutive values would be as compact for regularly increasing values,
+    /// but reading a value would require summing all of the previous deltas.
+    Linear = 2,
+    /// Splits the values in blocks of 128 values, each of them bitpacked
+    /// with its own minimum value (a.k.a. frame-of-reference) and its own
+    /// number of bits.
+    Blockwise = 3,
+    /// Stacks several columns, each of them encoded with one of the
+    /// codecs above, and adds a constant offset to each of them.
+    ///
+    /// It is only produced by merges, which can then avoid decoding and
+    /// re-encoding the columns of the merged segments.
+    Stacked = 4,
+}
+
+impl FastFieldCodec {
+    fn from_code(code: u8) -> Option<FastFieldCodec> {
+        match code {
+            0 => Some(FastFieldCodec::Bitpacked),
+            1 => Some(FastFieldCodec::Gcd),
+            2 => Some(FastFieldCodec::Linear),
+            3 => Some(FastFieldCodec::Blockwise),
+            4 => Some(FastFieldCodec::Stacked),
+            _ => None,
+        }
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// Parameters of the linear interpolation of the `Linear` codec.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Line {
+    first_val: u64,
+    last_val: u64,
+    num_vals: u64,
+}
+
+impl Line {
+    fn new(vals: &[u64]) -> Line {
+        Line {
+            first_val: vals.first().cloned().unwrap_or(0u64),
+            last_val: vals.last().cloned().unwrap_or(0u64),
+            num_vals: vals.len() as u64,
+        }
+    }
+
+    fn predict(&self, idx: u64) -> u64 {
+        if self.num_vals < 2 {
+            return self.first_val;
+        }
+        let slope_num = i128::from(self.last_val) - i128::from(self.first_val);
+        let slope_den = i128::from(self.num_vals - 1);
+        // The prediction lies between the first and the last values,
+        // so the cast is lossless.
+        (i128::from(self.first_val) + slope_num * i128::from(idx) / slope_den) as u64
+    }
+}
+
+/// Returns the minimum offset and the amplitude of the offsets between
+/// the values and their linear interpolation, or `None` if the amplitude
+/// does not fit in a `u64`.
+fn linear_offsets(line: &Line, vals: &[u64]) -> Option<(i128, u64)> {
+    let mut min_offset = 0i128;
+    let mut max_offset = 0i128;
+    for (idx, &val) in vals.iter().enumerate() {
+        let offset = i128::from(val) - i128::from(line.predict(idx as u64));
+        if idx == 0 || offset < min_offset {
+            min_offset = offset;
+        }
+        if idx == 0 || offset > max_offset {
+            max_offset = offset;
+        }
+    }
+    let amplitude = max_offset - min_offset;
+    if amplitude > i128::from(u64::MAX) {
+        return None;
+    }
+    Some((min_offset, amplitude as u64))
+}
+
+fn block_min_max(block: &[u64]) -> (u64, u64) {
+    let min = block.iter().cloned().min().unwrap_or(0u64);
+    let max = block.iter().cloned().max().unwrap_or(0u64);
+    (min, max)
+}
+
+/// Estimates the size in bits of the column once encoded with
+/// the given codec, or `None` if the codec is not applicable.
+fn estimate_num_bits(
+    codec: FastFieldCodec,
+    vals: &[u64],
+    min_value: u64,
+    amplitude: u64,
+) -> Option<u64> {
+    let num_vals = vals.len() as u64;
+    match codec {
+        FastFieldCodec::Bitpacked => Some(num_vals * u64::from(compute_num_bits(amplitude))),
+        FastFieldCodec::Gcd => {
+            let gcd = vals
+                .iter()
+                .fold(0u64, |acc, &val| gcd(acc, val - min_value));
+            if gcd <= 1 {
+                return None;
+            }
+            Some(64 + 8 + num_vals * u64::from(compute_num_bits(amplitude / gcd)))
+        }
+        FastFieldCodec::Linear => {
+            if num_vals < 2 {
+                return None;
+            }
+            let line = Line::new(vals);
+            let (_, offset_amplitude) = linear_offsets(&line, vals)?;
+            Some(64 * 4 + 8 + num_vals * u64::from(compute_num_bits(offset_amplitude)))
+        }
+        FastFieldCodec::Blockwise => {
+            if vals.len() <= BLOCK_SIZE {
+                return None;
+            }
+            let num_bits = vals
+                .chunks(BLOCK_SIZE)
+                .map(|block| {
+                    let (block_min, block_max) = block_min_max(block);
+                    let block_num_bits = u64::from(compute_num_bits(block_max - block_min));
+                    64 + 8 + block.len() as u64 * block_num_bits
+                })
+                .sum::<u64>();
+            Some(64 + num_bits)
+        }
+        FastFieldCodec::Stacked => None,
+    }
+}
+
+/// Picks the codec yielding the smallest column.
+///
+/// In case of a tie, the simplest codec wins.
+fn select_codec(vals: &[u64], min_value: u64, amplitude: u64) -> FastFieldCodec {
+    let mut best_codec = FastFieldCodec::Bitpacked;
+    let mut best_num_bits = u64::MAX;
+    for &codec in &[
+        FastFieldCodec::Bitpacked,
+        FastFieldCodec::Gcd,
+        FastFieldCodec::Linear,
+        FastFieldCodec::Blockwise,
+    ] {
+        if let Some(num_bits) = estimate_num_bits(codec, vals, min_value, amplitude) {
+            if num_bits < best_num_bits {
+                best_codec = codec;
+                best_num_bits = num_bits;
+            }
+        }
+    }
+    best_codec
+}
+
+/// Serializes a column of values.
+///
+/// `min_value` and `amplitude` are a lower bound and the amplitude of the values,
+/// and are assumed to have been serialized already.
+pub(crate) fn serialize_vals<W: Write>(
+    write: &mut W,
+    vals: &[u64],
+    min_value: u64,
+    amplitude: u64,
+) -> io::Result<()> {
+    let codec = select_codec(vals, min_value, amplitude);
+    (codec as u8).serialize(write)?;
+    let mut bit_packer = BitPacker::new();
+    match codec {
+        FastFieldCodec::Bitpacked => {
+            let num_bits = compute_num_bits(amplitude);
+            for &val in vals {
+                bit_packer.write(val - min_value, num_bits, write)?;
+            }
+        }
+        FastFieldCodec::Gcd => {
+            let gcd = vals
+                .iter()
+                .fold(0u64, |acc, &val| gcd(acc, val - min_value));
+            let num_bits = compute_num_bits(amplitude / gcd);
+            gcd.serialize(write)?;
+            num_bits.serialize(write)?;
+            for &val in vals {
+                bit_packer.write((val - min_value) / gcd, num_bits, write)?;
+            }
+        }
+        FastFieldCodec::Linear => {
+            let line = Line::new(vals);
+            let (min_offset, offset_amplitude) =
+                linear_offsets(&line, vals).expect("Linear codec selected for invalid values");
+            let num_bits = compute_num_bits(offset_amplitude);
+            line.first_val.serialize(write)?;
+            line.last_val.serialize(write)?;
+            line.num_vals.serialize(write)?;
+            // Written in two's complement: readers add it with a wrapping addition.
+            (min_offset as u64).serialize(write)?;
+            num_bits.serialize(write)?;
+            for (idx, &val) in vals.iter().enumerate() {
+                let offset = i128::from(val) - i128::from(line.predict(idx as u64));
+                // `offset - min_offset` is at most `offset_amplitude`, so the cast is lossless.
+                bit_packer.write((offset - min_offset) as u64, num_bits, write)?;
+            }
+        }
+        FastFieldCodec::Blockwise => {
+            (vals.len() as u64).serialize(write)?;
+            let mut block_num_bits = Vec::new();
+            for block in vals.chunks(BLOCK_SIZE) {
+                let (block_min, block_max) = block_min_max(block);
+                let num_bits = compute_num_bits(block_max - block_min);
+                block_min.serialize(write)?;
+                num_bits.serialize(write)?;
+                block_num_bits.push((block_min, num_bits));
+            }
+            for (block, &(block_min, num_bits)) in vals.chunks(BLOCK_SIZE).zip(&block_num_bits) {
+                for &val in block {
+                    bit_packer.write(val - block_min, num_bits, write)?;
+                }
+                // Blocks start on a byte boundary.
+                bit_packer.flush(write)?;
+            }
+        }
+        FastFieldCodec::Stacked => {
+            unreachable!("The stacked codec is never selected to encode values")
+        }
+    }
+    bit_packer.close(write)
+}
+
+/// A range of values of an encoded column, that can be
+/// stacked with other ones without being decoded.
+#[derive(Clone)]
+pub(crate) struct ColumnPart {
+    /// The column data, starting with its `min_value` and `amplitude` header.
+    pub data: ReadOnlySource,
+    /// The number of values of the column that belong to the part.
+    pub num_vals: u64,
+    /// The offset added to the values of the column.
+    pub offset: u64,
+}
+
+/// Serializes a column stacking the given `parts`, with the `Stacked` codec.
+///
+/// The parts are written as is, so that they do not need to be decoded.
+pub(crate) fn serialize_stacked<W: Write>(write: &mut W, parts: &[ColumnPart]) -> io::Result<()> {
+    let parts: Vec<&ColumnPart> = parts.iter().filter(|part| part.num_vals > 0).collect();
+    (FastFieldCodec::Stacked as u8).serialize(write)?;
+    (parts.len() as u64).serialize(write)?;
+    for part in &parts {
+        part.num_vals.serialize(write)?;
+        part.offset.serialize(write)?;
+        (part.data.len() as u64).serialize(write)?;
+    }
+    for part in &parts {
+        write.write_all(part.data.as_slice())?;
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub(crate) struct StackedPart {
+    /// Index of the first value of the part within the stacked column.
+    start: u64,
+    num_vals: u64,
+    offset: u64,
+    data: ReadOnlySource,
+    reader: CodecReader,
+}
+
+impl StackedPart {
+    fn get(&self, idx: u64) -> u64 {
+        self.reader.get(idx - self.start).wrapping_add(self.offset)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct BlockReader {
+    min_value: u64,
+    bit_unpacker: BitUnpacker<OwningRef<ReadOnlySource, [u8]>>,
+}
+
+/// Opens a column serialized with a `min_value` and `amplitude` header.
+pub(crate) fn open_column(data: ReadOnlySource) -> (CodecReader, u64, u64) {
+    let mut cursor = data.as_slice();
+    let min_value =
+        u64::deserialize(&mut cursor).expect("Failed to read the min_value of fast field.");
+    let amplitude =
+        u64::deserialize(&mut cursor).expect("Failed to read the amplitude of fast field.");
+    let codec_reader = CodecReader::open(data.slice_from(16), min_value, amplitude);
+    (codec_reader, min_value, amplitude)
+}
+
+/// Random access reader over a column encoded with one of the `FastFieldCodec`s.
+#[derive(Clone)]
+pub(crate) enum CodecReader {
+    Bitpacked {
+        min_value: u64,
+        bit_unpacker: BitUnpacker<OwningRef<ReadOnlySource, [u8]>>,
+    },
+    Gcd {
+        min_value: u64,
+        gcd: u64,
+        bit_unpacker: BitUnpacker<OwningRef<ReadOnlySource, [u8]>>,
+    },
+    Linear {
+        line: Line,
+        min_offset: u64,
+        bit_unpacker: BitUnpacker<OwningRef<ReadOnlySource, [u8]>>,
+    },
+    Blockwise {
+        blocks: Arc<Vec<BlockReader>>,
+    },
+    Stacked {
+        parts: Arc<Vec<StackedPart>>,
+    },
+}
+
+fn bit_unpacker_from(
+    data: &ReadOnlySource,
+    start: usize,
+    num_bits: u8,
+) -> BitUnpacker<OwningRef<ReadOnlySource, [u8]>> {
+    let owning_ref = OwningRef::new(data.clone()).map(|data| &data[start..]);
+    BitUnpacker::new(owning_ref, num_bits)
+}
+
+impl CodecReader {
+    /// Opens a column serialized with `serialize_vals`.
+    ///
+    /// `data` starts with the codec tag, right after the header
+    /// containing the `min_value` and the `amplitude` of the column.
+    // `usize::div_ceil` is not available on the supported Rust versions.
+    #[allow(clippy::manual_div_ceil)]
+    pub fn open(data: ReadOnlySource, min_value: u64, amplitude: u64) -> CodecReader {
+        let mut cursor = data.as_slice();
+        let code = u8::deserialize(&mut cursor).expect("Failed to read the fast field codec.");
+        let codec = FastFieldCodec::from_code(code)
+            .unwrap_or_else(|| panic!("Unknown fast field codec {}.", code));
+        match codec {
+            FastFieldCodec::Bitpacked => CodecReader::Bitpacked {
+                min_value,
+                bit_unpacker: bit_unpacker_from(&data, 1, compute_num_bits(amplitude)),
+            },
+            FastFieldCodec::Gcd => {
+                let gcd = u64::deserialize(&mut cursor).expect("Failed to read the gcd.");
+                let num_bits = u8::deserialize(&mut cursor).expect("Failed to read num bits.");
+                CodecReader::Gcd {
+                    min_value,
+                    gcd,
+                    bit_unpacker: bit_unpacker_from(&data, 1 + 8 + 1, num_bits),
+                }
+            }
+            FastFieldCodec::Linear => {
+                let mut read_u64 = || {
+                    u64::deserialize(&mut cursor).expect("Failed to read the linear codec header.")
+                };
+                let line = Line {
+                    first_val: read_u64(),
+                    last_val: read_u64(),
+                    num_vals: read_u64(),
+                };
+                let min_offset = read_u64();
+                let num_bits = u8::deserialize(&mut cursor).expect("Failed to read num bits.");
+                CodecReader::Linear {
+                    line,
+                    min_offset,
+                    bit_unpacker: bit_unpacker_from(&data, 1 + 8 * 4 + 1, num_bits),
+                }
+            }
+            FastFieldCodec::Blockwise => {
+                let num_vals =
+                    u64::deserialize(&mut cursor).expect("Failed to read the number of vals.");
+                let num_blocks = (num_vals as usize + BLOCK_SIZE - 1) / BLOCK_SIZE;
+                let mut block_start = 1 + 8 + num_blocks * (8 + 1);
+                let mut blocks = Vec::with_capacity(num_blocks);
+                for block_ord in 0..num_blocks {
+                    let block_min =
+                        u64::deserialize(&mut cursor).expect("Failed to read the block min.");
+                    let num_bits = u8::deserialize(&mut cursor).expect("Failed to read num bits.");
+                    blocks.push(BlockReader {
+                        min_value: block_min,
+                        bit_unpacker: bit_unpacker_from(&data, block_start, num_bits),
+                    });
+                    let block_len = BLOCK_SIZE.min(num_vals as usize - block_ord * BLOCK_SIZE);
+                    block_start += (block_len * num_bits as usize + 7) / 8;
+                }
+                CodecReader::Blockwise {
+                    blocks: Arc::new(blocks),
+                }
+            }
+            FastFieldCodec::Stacked => {
+                let mut read_u64 = || {
+                    u64::deserialize(&mut cursor).expect("Failed to read the stacked codec header.")
+                };
+                let num_parts = read_u64() as usize;
+                let part_headers: Vec<(u64, u64, u64)> = (0..num_parts)
+                    .map(|_| (read_u64(), read_u64(), read_u64()))
+                    .collect();
+                let mut part_start = 1 + 8 + num_parts * 8 * 3;
+                let mut start = 0u64;
+                let mut parts = Vec::with_capacity(num_parts);
+                for (num_vals, offset, num_bytes) in part_headers {
+                    let part_data = data.slice(part_start, part_start + num_bytes as usize);
+                    let (reader, _, _) = open_column(part_data.clone());
+                    parts.push(StackedPart {
+                        start,
+                        num_vals,
+                        offset,
+                        data: part_data,
+                        reader,
+                    });
+                    part_start += num_bytes as usize;
+                    start += num_vals;
+                }
+                CodecReader::Stacked {
+                    parts: Arc::new(parts),
+                }
+            }
+        }
+    }
+
+    /// Returns the parts of the column to stack in order to
+    /// obtain its `num_vals` first values, incremented by `offset`.
+    ///
+    /// `data` is the data of the column, including its header.
+    pub fn parts(&self, data: &ReadOnlySource, num_vals: u64, offset: u64) -> Vec<ColumnPart> {
+        match *self {
+            CodecReader::Stacked { ref parts } => parts
+                .iter()
+                .filter(|part| part.start < num_vals)
+                .map(|part| ColumnPart {
+                    data: part.data.clone(),
+                    num_vals: part.num_vals.min(num_vals - part.start),
+                    offset: part.offset.wrapping_add(offset),
+                })
+                .collect(),
+            _ => vec![ColumnPart {
+                data: data.clone(),
+                num_vals,
+                offset,
+            }],
+        }
+    }
+
+    fn stacked_part(parts: &[StackedPart], idx: u64) -> &StackedPart {
+        match parts.binary_search_by_key(&idx, |part| part.start) {
+            Ok(part_ord) => &parts[part_ord],
+            Err(part_ord) => &parts[part_ord - 1],
+        }
+    }
+
+    /// Returns the codec used to encode the column.
+    #[cfg(test)]
+    pub fn codec(&self) -> FastFieldCodec {
+        match *self {
+            CodecReader::Bitpacked { .. } => FastFieldCodec::Bitpacked,
+            CodecReader::Gcd { .. } => FastFieldCodec::Gcd,
+            CodecReader::Linear { .. } => FastFieldCodec::Linear,
+            CodecReader::Blockwise { .. } => FastFieldCodec::Blockwise,
+            CodecReader::Stacked { .. } => FastFieldCodec::Stacked,
+        }
+    }
+
+    /// Returns the value at the given index.
+    pub fn get(&self, idx: u64) -> u64 {
+        match *self {
+            CodecReader::Bitpacked {
+                min_value,
+                ref bit_unpacker,
+            } => min_value + bit_unpacker.get(idx),
+            CodecReader::Gcd {
+                min_value,
+                gcd,
+                ref bit_unpacker,
+            } => min_value + gcd * bit_unpacker.get(idx),
+            CodecReader::Linear {
+                ref line,
+                min_offset,
+                ref bit_unpacker,
+            } => line
+                .predict(idx)
+                .wrapping_add(min_offset)
+                .wrapping_add(bit_unpacker.get(idx)),
+            CodecReader::Blockwise { ref blocks } => {
+                let block = &blocks[idx as usize / BLOCK_SIZE];
+                block.min_value + block.bit_unpacker.get(idx % BLOCK_SIZE as u64)
+            }
+            CodecReader::Stacked { ref parts } => CodecReader::stacked_part(parts, idx).get(idx),
+        }
+    }
+
+    /// Fills `output` with the values going from `start` to `start + output.len()`.
+    ///
+    /// The bitpacked values are first unpacked all at once,
+    /// and then decoded in a second pass.
+    pub fn get_range(&self, start: u64, output: &mut [u64]) {
+        match *self {
+            CodecReader::Bitpacked {
+                min_value,
+                ref bit_unpacker,
+            } => {
+                bit_unpacker.get_range(start, output);
+                for val in output.iter_mut() {
+                    *val += min_value;
+                }
+            }
+            CodecReader::Gcd {
+                min_value,
+                gcd,
+                ref bit_unpacker,
+            } => {
+                bit_unpacker.get_range(start, output);
+                for val in output.iter_mut() {
+                    *val = min_value + gcd * *val;
+                }
+            }
+            CodecReader::Linear {
+                ref line,
+                min_offset,
+                ref bit_unpacker,
+            } => {
+                bit_unpacker.get_range(start, output);
+                for (idx, val) in (start..).zip(output.iter_mut()) {
+                    *val = line
+                        .predict(idx)
+                        .wrapping_add(min_offset)
+                        .wrapping_add(*val);
+                }
+            }
+            CodecReader::Blockwise { ref blocks } => {
+                let mut idx = start as usize;
+                let mut output = output;
+                while !output.is_empty() {
+                    let block = &blocks[idx / BLOCK_SIZE];
+                    let idx_in_block = idx % BLOCK_SIZE;
+                    let len = output.len().min(BLOCK_SIZE - idx_in_block);
+                    let (block_output, remaining) = output.split_at_mut(len);
+                    block
+                        .bit_unpacker
+                        .get_range(idx_in_block as u64, block_output);
+                    for val in block_output.iter_mut() {
+                        *val += block.min_value;
+                    }
+                    idx += len;
+                    output = remaining;
+                }
+            }
+            CodecReader::Stacked { ref parts } => {
+                let mut idx = start;
+                let mut output = output;
+                while !output.is_empty() {
+                    let part = CodecReader::stacked_part(parts, idx);
+                    let len = (output.len() as u64).min(part.start + part.num_vals - idx);
+                    let (part_output, remaining) = output.split_at_mut(len as usize);
+                    part.reader.get_range(idx - part.start, part_output);
+                    for val in part_output.iter_mut() {
+                        *val = val.wrapping_add(part.offset);
+                    }
+                    idx += len;
+                    output = remaining;
+                }
+            }
+        }
+    }
+
+    /// Fills `output` with the values at the given indexes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idxs` and `output` do not have the same length.
+    pub fn get_batch(&self, idxs: &[u32], output: &mut [u64]) {
+        assert_eq!(idxs.len(), output.len());
+        match *self {
+            CodecReader::Bitpacked {
+                min_value,
+                ref bit_unpacker,
+            } => {
+                for (&idx, val) in idxs.iter().zip(output.iter_mut()) {
+                    *val = min_value + bit_unpacker.get(u64::from(idx));
+                }
+            }
+            CodecReader::Gcd {
+                min_value,
+                gcd,
+                ref bit_unpacker,
+            } => {
+                for (&idx, val) in idxs.iter().zip(output.iter_mut()) {
+                    *val = min_value + gcd * bit_unpacker.get(u64::from(idx));
+                }
+            }
+            CodecReader::Linear {
+                ref line,
+                min_offset,
+                ref bit_unpacker,
+            } => {
+                for (&idx, val) in idxs.iter().zip(output.iter_mut()) {
+                    let idx = u64::from(idx);
+                    *val = line
+                        .predict(idx)
+                        .wrapping_add(min_offset)
+                        .wrapping_add(bit_unpacker.get(idx));
+                }
+            }
+            CodecReader::Blockwise { ref blocks } => {
+                for (&idx, val) in idxs.iter().zip(output.iter_mut()) {
+                    let block = &blocks[idx as usize / BLOCK_SIZE];
+                    *val = block.min_value
+                        + block.bit_unpacker.get(u64::from(idx) % BLOCK_SIZE as u64);
+                }
+            }
+            CodecReader::Stacked { ref parts } => {
+                for (&idx, val) in idxs.iter().zip(output.iter_mut()) {
+                    let idx = u64::from(idx);
+                    *val = CodecReader::stacked_part(parts, idx).get(idx);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        open_column, serialize_stacked, serialize_vals, CodecReader, ColumnPart, FastFieldCodec,
+    };
+    use crate::common::BinarySerializable;
+    use crate::directory::ReadOnlySource;
+
+    fn test_codec_util(vals: &[u64], expected_codec: FastFieldCodec) -> usize {
+        let min_value = vals.iter().cloned().min().unwrap_or(0u64);
+        let max_value = vals.iter().cloned().max().unwrap_or(0u64);
+        let amplitude = max_value - min_value;
+        let mut data = Vec::new();
+        serialize_vals(&mut data, vals, min_value, amplitude).unwrap();
+        let num_bytes = data.len();
+        let codec_reader = CodecReader::open(ReadOnlySource::from(data), min_value, amplitude);
+        assert_eq!(codec_reader.codec(), expected_codec);
+        for (idx, &val) in vals.iter().enumerate() {
+            assert_eq!(codec_reader.get(idx as u64), val);
+        }
+        for start in [0, vals.len() / 3, vals.len()].iter().cloned() {
+            let mut output = vec![0u64; vals.len() - start];
+            codec_reader.get_range(start as u64, &mut output);
+            assert_eq!(&output[..], &vals[start..]);
+        }
+        let idxs: Vec<u32> = (0..vals.len() as u32).rev().step_by(3).collect();
+        let mut output = vec![0u64; idxs.len()];
+        codec_reader.get_batch(&idxs, &mut output);
+        for (&idx, &val) in idxs.iter().zip(output.iter()) {
+            assert_eq!(val, vals[idx as usize]);
+        }
+        num_bytes
+    }
+
+    #[test]
+    fn test_bitpacked_codec() {
+        test_codec_util(&[], FastFieldCodec::Bitpacked);
+        test_codec_util(&[7u64; 1_000], FastFieldCodec::Bitpacked);
+        test_codec_util(&[4, 14_082_001, 3_052, 9_002, 1], FastFieldCodec::Bitpacked);
+    }
+
+    #[test]
+    fn test_gcd_codec() {
+        let vals: Vec<u64> = (0u64..1_000)
+            .map(|i| 1_500_000_000_000 + ((i * 7_919) % 1_000) * 60_000)
+            .collect();
+        let num_bytes = test_codec_util(&vals, FastFieldCodec::Gcd);
+        assert!(num_bytes < 1_000 * 10 / 8 + 30);
+    }
+
+    #[test]
+    fn test_linear_codec() {
+        let vals: Vec<u64> = (0u64..1_000).map(|i| 1_000_000 + i * 3 + i % 2).collect();
+        let num_bytes = test_codec_util(&vals, FastFieldCodec::Linear);
+        assert!(num_bytes < 1_000 / 8 + 50);
+        let decreasing_vals: Vec<u64> = (0u64..1_000).map(|i| 10_000 - i * 7).collect();
+        test_codec_util(&decreasing_vals, FastFieldCodec::Linear);
+        let extreme_vals: Vec<u64> = (0u64..1_000)
+            .map(|i| u64::MAX - 1_000 + i)
+            .chain(Some(0u64))
+            .collect();
+        test_codec_util(&extreme_vals, FastFieldCodec::Blockwise);
+    }
+
+    #[test]
+    fn test_blockwise_codec() {
+        let vals: Vec<u64> = (0u64..1_000)
+            .map(|i| (i / 128) * 1_000_000_000 + (i * 31) % 17)
+            .collect();
+        let num_bytes = test_codec_util(&vals, FastFieldCodec::Blockwise);
+        assert!(num_bytes < 1_000 * 5 / 8 + 8 * 10 + 30);
+    }
+
+    fn column_with_header(vals: &[u64]) -> ReadOnlySource {
+        let min_value = vals.iter().cloned().min().unwrap_or(0u64);
+        let max_value = vals.iter().cloned().max().unwrap_or(0u64);
+        let mut data = Vec::new();
+        min_value.serialize(&mut data).unwrap();
+        (max_value - min_value).serialize(&mut data).unwrap();
+        serialize_vals(&mut data, vals, min_value, max_value - min_value).unwrap();
+        ReadOnlySource::from(data)
+    }
+
+    fn stack(parts: &[ColumnPart], min_value: u64, max_value: u64) -> ReadOnlySource {
+        let mut data = Vec::new();
+        min_value.serialize(&mut data).unwrap();
+        (max_value - min_value).serialize(&mut data).unwrap();
+        serialize_stacked(&mut data, parts).unwrap();
+        ReadOnlySource::from(data)
+    }
+
+    fn check_column(data: &ReadOnlySource, expected: &[u64]) -> CodecReader {
+        let (codec_reader, _, _) = open_column(data.clone());
+        for (idx, &val) in expected.iter().enumerate() {
+            assert_eq!(codec_reader.get(idx as u64), val);
+        }
+        for start in [0, 1, expected.len() / 2, expected.len()].iter().cloned() {
+            let mut output = vec![0u64; expected.len() - start];
+            codec_reader.get_range(start as u64, &mut output);
+            assert_eq!(&output[..], &expected[start..]);
+        }
+        let idxs: Vec<u32> = (0..expected.len() as u32).rev().step_by(3).collect();
+        let mut output = vec![0u64; idxs.len()];
+        codec_reader.get_batch(&idxs, &mut output);
+        for (&idx, &val) in idxs.iter().zip(output.iter()) {
+            assert_eq!(val, expected[idx as usize]);
+        }
+        codec_reader
+    }
+
+    #[test]
+    fn test_stacked_codec() {
+        let first_vals: Vec<u64> = (0u64..300).map(|i| 1_000 + i * 3).collect();
+        let second_vals: Vec<u64> = (0u64..200).map(|i| (i * 7_919) % 1_000).collect();
+        let first = column_with_header(&first_vals);
+        let second = column_with_header(&second_vals);
+        let parts = vec![
+            ColumnPart {
+                data: first.clone(),
+                num_vals: 300,
+                offset: 0,
+            },
+            ColumnPart {
+                data: second.clone(),
+                num_vals: 0,
+                offset: 0,
+            },
+            ColumnPart {
+                data: second.clone(),
+                num_vals: 150,
+                offset: 10_000,
+            },
+        ];
+        let stacked = stack(&parts, 0, 11_000);
+        let expected: Vec<u64> = first_vals
+            .iter()
+            .cloned()
+            .chain(second_vals[..150].iter().map(|&val| val + 10_000))
+            .collect();
+        let codec_reader = check_column(&stacked, &expected);
+        assert_eq!(codec_reader.codec(), FastFieldCodec::Stacked);
+
+        // Stacking a stacked column flattens its parts.
+        let mut nested_parts = codec_reader.parts(&stacked, 400, 5);
+        assert_eq!(nested_parts.len(), 2);
+        nested_parts.push(ColumnPart {
+            data: first,
+            num_vals: 10,
+            offset: 0,
+        });
+        let nested = stack(&nested_parts, 0, 11_005);
+        let nested_expected: Vec<u64> = expected[..400]
+            .iter()
+            .map(|&val| val + 5)
+            .chain(first_vals[..10].iter().cloned())
+            .collect();
+        check_column(&nested, &nested_expected);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+
+/// Calendar unit to which the values of a date fast field can be truncated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DateTruncation {
+    /// Truncates to the start of the minute.
+    Minute,
+    /// Truncates to the start of the hour.
+    Hour,
+    /// Truncates to midnight.
+    Day,
+    /// Truncates to midnight of the monday of the week.
+    Week,
+    /// Truncates to midnight of the first day of the month.
+    Month,
+    /// Truncates to midnight of the first day of the year.
+    Year,
+}
+
+/// Truncates the timestamps of a date fast field to the start
+/// of their bucket (day, hour, month...) in a given timezone.
+///
+/// Date fast fields store their values as a number of seconds since the
+/// Unix epoch (see `FastFieldReaders::i64`), and truncated values are
+/// returned as such.
+///
+/// Consecutive values often fall in the same bucket (e.g. when documents
+/// are indexed in chronological order), so the truncator remembers
+/// the bounds of the last bucket it computed, and only converts
+/// timestamps to calendar dates when they fall out of it.
+///
+/// ```rust
+/// use tantivy::chrono::{FixedOffset, TimeZone, Utc};
+/// use tantivy::fastfield::{DateTruncation, DateTruncator};
+///
+/// let utc_plus_two = FixedOffset::east_opt(2 * 3_600).unwrap();
+/// let mut truncator = DateTruncator::new(DateTruncation::Day, utc_plus_two);
+/// // 2019-10-17 23:10 UTC is 2019-10-18 01:10 in UTC+2.
+/// let timestamp = Utc.with_ymd_and_hms(2019, 10, 17, 23, 10, 0).unwrap().timestamp();
+/// let expected = utc_plus_two.with_ymd_and_hms(2019, 10, 18, 0, 0, 0).unwrap().timestamp();
+/// assert_eq!(truncator.truncate(timestamp), expected);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DateTruncator<Tz: TimeZone> {
+    truncation: DateTruncation,
+    timezone: Tz,
+    // Start (inclusive) and end (exclusive) of the last bucket.
+    bucket: Option<(i64, i64)>,
+}
+
+impl DateTruncator<Utc> {
+    /// Creates a truncator operating in UTC.
+    pub fn utc(truncation: DateTruncation) -> DateTruncator<Utc> {
+        DateTruncator::new(truncation, Utc)
+    }
+}
+
+impl<Tz: TimeZone> DateTruncator<Tz> {
+    /// Creates a truncator operating in the given timezone.
+    pub fn new(truncation: DateTruncation, timezone: Tz) -> DateTruncator<Tz> {
+        DateTruncator {
+            truncation,
+            timezone,
+            bucket: None,
+        }
+    }
+
+    /// Returns the unit to which timestamps are truncated.
+    pub fn truncation(&self) -> DateTruncation {
+        self.truncation
+    }
+
+    /// Truncates a timestamp to the start of its bucket.
+    pub fn truncate(&mut self, timestamp: i64) -> i64 {
+        if let Some((start, end)) = self.bucket {
+            if start <= timestamp && timestamp < end {
+                return start;
+            }
+        }
+        let bucket = self.bucket_bounds(timestamp);
+        self.bucket = Some(bucket);
+        bucket.0
+    }
+
+    /// Truncates a slice of timestamps in place.
+    pub fn truncate_batch(&mut self, timestamps: &mut [i64]) {
+        for timestamp in timestamps.iter_mut() {
+            *timestamp = self.truncate(*timestamp);
+        }
+    }
+
+    /// Returns the start and the end of the bucket of the timestamp.
+    fn bucket_bounds(&self, timestamp: i64) -> (i64, i64) {
+        let local_bounds = Utc
+            .timestamp_opt(timestamp, 0)
+            .single()
+            .and_then(|utc_datetime| {
+                let local = utc_datetime.with_timezone(&self.timezone).naive_local();
+                local_bucket_bounds(self.truncation, &local)
+            });
+        match local_bounds {
+            Some((start, end)) => {
+                let start = self.to_timestamp(&start, timestamp);
+                let end = self.to_timestamp(&end, timestamp + 1);
+                (start.min(timestamp), end.max(timestamp + 1))
+            }
+            // The date is out of the range supported by chrono.
+            None => (timestamp, timestamp.saturating_add(1)),
+        }
+    }
+
+    /// Converts a local date time back to a timestamp.
+    ///
+    /// Local date times skipped by a daylight saving time transition
+    /// fall back to `default`.
+    fn to_timestamp(&self, local: &NaiveDateTime, default: i64) -> i64 {
+        self.timezone
+            .from_local_datetime(local)
+            .earliest()
+            .map(|datetime| datetime.timestamp())
+            .unwrap_or(default)
+    }
+}
+
+/// Returns the start and the end of the bucket of a local date time.
+fn local_bucket_bounds(
+    truncation: DateTruncation,
+    local: &NaiveDateTime,
+) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let date = local.date();
+    let (start, duration) = match truncation {
+        DateTruncation::Minute => (
+            date.and_hms_opt(local.hour(), local.minute(), 0)?,
+            Duration::minutes(1),
+        ),
+        DateTruncation::Hour => (date.and_hms_opt(local.hour(), 0, 0)?, Duration::hours(1)),
+        DateTruncation::Day => (date.and_hms_opt(0, 0, 0)?, Duration::days(1)),
+        DateTruncation::Week => {
+            let days_from_monday = i64::from(date.weekday().num_days_from_monday());
+            let monday = date.checked_sub_signed(Duration::days(days_from_monday))?;
+            (monday.and_hms_opt(0, 0, 0)?, Duration::weeks(1))
+        }
+        DateTruncation::Month => {
+            let start = NaiveDate::from_ymd_opt(date.year(), date.month(), 1)?;
+            let end = if date.month() == 12 {
+                NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)?
+            } else {
+                NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)?
+            };
+            return Some((start.and_hms_opt(0, 0, 0)?, end.and_hms_opt(0, 0, 0)?));
+        }
+        DateTruncation::Year => {
+            let start = NaiveDate::from_ymd_opt(date.year(), 1, 1)?;
+            let end = NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)?;
+            return Some((start.and_hms_opt(0, 0, 0)?, end.and_hms_opt(0, 0, 0)?));
+        }
+    };
+    Some((start, start.checked_add_signed(duration)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DateTruncation, DateTruncator};
+    use crate::chrono::{FixedOffset, TimeZone, Utc};
+    use crate::fastfield::FastFieldReader;
+
+    #[test]
+    fn test_date_truncation_utc() {
+        let timestamp = Utc
+            .with_ymd_and_hms(2019, 12, 31, 13, 47, 12)
+            .unwrap()
+            .timestamp();
+        let truncate =
+            |truncation: DateTruncation| DateTruncator::utc(truncation).truncate(timestamp);
+        assert_eq!(
+            truncate(DateTruncation::Minute),
+            Utc.with_ymd_and_hms(2019, 12, 31, 13, 47, 0)
+                .unwrap()
+                .timestamp()
+        );
+        assert_eq!(
+            truncate(DateTruncation::Hour),
+            Utc.with_ymd_and_hms(2019, 12, 31, 13, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+        assert_eq!(
+            truncate(DateTruncation::Day),
+            Utc.with_ymd_and_hms(2019, 12, 31, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+        assert_eq!(
+            truncate(DateTruncation::Week),
+            Utc.with_ymd_and_hms(2019, 12, 30, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+        assert_eq!(
+            truncate(DateTruncation::Month),
+            Utc.with_ymd_and_hms(2019, 12, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+        assert_eq!(
+            truncate(DateTruncation::Year),
+            Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+    }
+
+    #[test]
+    fn test_date_truncation_timezone() {
+        let timezone = FixedOffset::west_opt(5 * 3_600).unwrap();
+        let mut truncator = DateTruncator::new(DateTruncation::Month, timezone);
+        // 2019-11-01 02:00 UTC is still in october in UTC-5.
+        let timestamp = Utc
+            .with_ymd_and_hms(2019, 11, 1, 2, 0, 0)
+            .unwrap()
+            .timestamp();
+        assert_eq!(
+            truncator.truncate(timestamp),
+            timezone
+                .with_ymd_and_hms(2019, 10, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+        // Negative timestamps are truncated towards the past.
+        let mut truncator = DateTruncator::utc(DateTruncation::Day);
+        assert_eq!(truncator.truncate(-1), -86_400);
+    }
+
+    #[test]
+    fn test_date_truncate_batch() {
+        let start = Utc
+            .with_ymd_and_hms(2019, 2, 27, 0, 0, 0)
+            .unwrap()
+            .timestamp();
+        let mut timestamps: Vec<i64> = (0..5 * 24).map(|hour| start + hour * 3_600 + 7).collect();
+        let mut truncator = DateTruncator::utc(DateTruncation::Day);
+        truncator.truncate_batch(&mut timestamps);
+        let expected: Vec<i64> = (0..5 * 24)
+            .map(|hour| start + (hour / 24) * 86_400)
+            .collect();
+        assert_eq!(timestamps, expected);
+        assert_eq!(truncator.truncation(), DateTruncation::Day);
+    }
+
+    #[test]
+    fn test_fast_field_get_truncated_batch() {
+        let start = Utc
+            .with_ymd_and_hms(2019, 1, 31, 23, 0, 0)
+            .unwrap()
+            .timestamp();
+        let timestamps: Vec<i64> = (0..4).map(|hour| start + hour * 3_600).collect();
+        let fast_field_reader = FastFieldReader::from(timestamps);
+        let mut truncator = DateTruncator::utc(DateTruncation::Month);
+        let mut output = [0i64; 3];
+        fast_field_reader.get_truncated_batch(&[3, 0, 1], &mut truncator, &mut output);
+        let january = Utc
+            .with_ymd_and_hms(2019, 1, 1, 0, 0, 0)
+            .unwrap()
+            .timestamp();
+        let february = Utc
+            .with_ymd_and_hms(2019, 2, 1, 0, 0, 0)
+            .unwrap()
+            .timestamp();
+        assert_eq!(output, [february, january, february]);
+    }
+}
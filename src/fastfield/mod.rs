@@ -14,7 +14,8 @@ the `DocSet` : for instance for scoring, grouping, filtering, or faceting.
 
 Fields have to be declared as `FAST` in the  schema.
 Currently only 64-bits integers (signed or unsigned) are
-supported.
+supported. Text fields declared as `FAST` store the ordinals
+of their terms, which are accessible via a `StrFastFieldReader`.
 
 They are stored in a bit-packed fashion so that their
 memory usage is directly linear with the amplitude of the
@@ -23,29 +24,41 @@ values stored.
 Read access performance is comparable to that of an array lookup.
 */
 
-pub use self::bytes::{BytesFastFieldReader, BytesFastFieldWriter};
+pub use self::bytes::{
+    BytesFastFieldReader, BytesFastFieldWriter, MultiValueBytesFastFieldReader,
+    MultiValueBytesFastFieldWriter,
+};
+pub use self::date::{DateTruncation, DateTruncator};
 pub use self::delete::write_delete_bitset;
 pub use self::delete::DeleteBitSet;
 pub use self::error::{FastFieldNotAvailableError, Result};
 pub use self::facet_reader::FacetReader;
 pub use self::multivalued::{MultiValueIntFastFieldReader, MultiValueIntFastFieldWriter};
+pub use self::range_index::FastFieldRangeIndex;
 pub use self::reader::FastFieldReader;
 pub use self::readers::FastFieldReaders;
 pub use self::serializer::FastFieldSerializer;
+pub(crate) use self::serializer::FastSingleFieldSerializer;
+pub use self::str_reader::StrFastFieldReader;
 pub use self::writer::{FastFieldsWriter, IntFastFieldWriter};
 use crate::common;
 use crate::schema::Cardinality;
 use crate::schema::FieldType;
+use crate::schema::GeoPoint;
 use crate::schema::Value;
 
 mod bytes;
+mod codecs;
+mod date;
 mod delete;
 mod error;
 mod facet_reader;
 mod multivalued;
+mod range_index;
 mod reader;
 mod readers;
 mod serializer;
+mod str_reader;
 mod writer;
 
 /// Trait for types that are allowed for fast fields: (u64, i64 and f64).
@@ -104,7 +117,9 @@ impl FastValue for i64 {
 
     fn fast_field_cardinality(field_type: &FieldType) -> Option<Cardinality> {
         match *field_type {
-            FieldType::I64(ref integer_options) => integer_options.get_fastfield_cardinality(),
+            FieldType::I64(ref integer_options) | FieldType::Date(ref integer_options) => {
+                integer_options.get_fastfield_cardinality()
+            }
             _ => None,
         }
     }
@@ -135,12 +150,35 @@ impl FastValue for f64 {
     }
 }
 
-fn value_to_u64(value: &Value) -> u64 {
+impl FastValue for GeoPoint {
+    fn from_u64(val: u64) -> Self {
+        GeoPoint::from_u64(val)
+    }
+
+    fn to_u64(&self) -> u64 {
+        GeoPoint::to_u64(self)
+    }
+
+    fn fast_field_cardinality(field_type: &FieldType) -> Option<Cardinality> {
+        match *field_type {
+            FieldType::GeoPoint(ref integer_options) => integer_options.get_fastfield_cardinality(),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> u64 {
+        GeoPoint::to_u64(self)
+    }
+}
+
+pub(crate) fn value_to_u64(value: &Value) -> u64 {
     match *value {
         Value::U64(ref val) => *val,
         Value::I64(ref val) => common::i64_to_u64(*val),
         Value::F64(ref val) => common::f64_to_u64(*val),
-        _ => panic!("Expected a u64/i64/f64 field, got {:?} ", value),
+        Value::Date(ref datetime) => common::i64_to_u64(datetime.timestamp()),
+        Value::GeoPoint(ref geo_point) => geo_point.to_u64(),
+        _ => panic!("Expected a numeric or geo point field, got {:?} ", value),
     }
 }
 
@@ -196,7 +234,7 @@ mod tests {
         }
         let source = directory.open_read(&path).unwrap();
         {
-            assert_eq!(source.len(), 36 as usize);
+            assert_eq!(source.len(), 37usize);
         }
         {
             let composite_file = CompositeFile::open(&source).unwrap();
@@ -232,7 +270,7 @@ mod tests {
         }
         let source = directory.open_read(&path).unwrap();
         {
-            assert_eq!(source.len(), 61 as usize);
+            assert_eq!(source.len(), 62usize);
         }
         {
             let fast_fields_composite = CompositeFile::open(&source).unwrap();
@@ -269,7 +307,7 @@ mod tests {
         }
         let source = directory.open_read(&path).unwrap();
         {
-            assert_eq!(source.len(), 34 as usize);
+            assert_eq!(source.len(), 35usize);
         }
         {
             let fast_fields_composite = CompositeFile::open(&source).unwrap();
@@ -302,7 +340,7 @@ mod tests {
         }
         let source = directory.open_read(&path).unwrap();
         {
-            assert_eq!(source.len(), 80042 as usize);
+            assert_eq!(source.len(), 10413usize);
         }
         {
             let fast_fields_composite = CompositeFile::open(&source).unwrap();
@@ -342,7 +380,7 @@ mod tests {
         }
         let source = directory.open_read(&path).unwrap();
         {
-            assert_eq!(source.len(), 17709 as usize);
+            assert_eq!(source.len(), 68usize);
         }
         {
             let fast_fields_composite = CompositeFile::open(&source).unwrap();
@@ -359,6 +397,17 @@ mod tests {
             for i in 0..100 {
                 assert_eq!(buffer[i], -100i64 + 53i64 + i as i64);
             }
+            let mut buffer = vec![0i64; 1_000];
+            fast_field_reader.get_range(9_000, &mut buffer[..]);
+            for (i, &val) in buffer.iter().enumerate() {
+                assert_eq!(val, -100i64 + 9_000i64 + i as i64);
+            }
+            let docs: Vec<u32> = (0..300u32).map(|i| (i * 7_919) % 10_100).collect();
+            let mut buffer = vec![0i64; docs.len()];
+            fast_field_reader.get_batch(&docs, &mut buffer[..]);
+            for (&doc, &val) in docs.iter().zip(buffer.iter()) {
+                assert_eq!(val, -100i64 + doc as i64);
+            }
         }
     }
 
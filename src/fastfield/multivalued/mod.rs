@@ -7,9 +7,6 @@ pub use self::writer::MultiValueIntFastFieldWriter;
 #[cfg(test)]
 mod tests {
 
-    use time;
-
-    use self::time::Duration;
     use crate::collector::TopDocs;
     use crate::query::QueryParser;
     use crate::schema::Cardinality;
@@ -17,6 +14,8 @@ mod tests {
     use crate::schema::IntOptions;
     use crate::schema::Schema;
     use crate::Index;
+    use chrono::Duration;
+    use futures::Future;
 
     #[test]
     fn test_multivalued_u64() {
@@ -85,6 +84,20 @@ mod tests {
         let reader = searcher.segment_reader(0);
         assert_eq!(reader.num_docs(), 4);
 
+        {
+            let multi_value_reader = reader.fast_fields().i64s(date_field).unwrap();
+            let mut vals = Vec::new();
+            multi_value_reader.get_vals(2, &mut vals);
+            assert_eq!(
+                &vals,
+                &[(first_time_stamp + Duration::seconds(1)).timestamp()]
+            );
+            multi_value_reader.get_vals(1, &mut vals);
+            assert!(vals.is_empty());
+            multi_value_reader.get_vals(3, &mut vals);
+            assert_eq!(&vals, &[two_secs_ahead.timestamp(); 3]);
+        }
+
         {
             let parser = QueryParser::for_index(&index, vec![date_field]);
             let query = parser
@@ -216,6 +229,61 @@ mod tests {
             assert_eq!(&vals, &[-5i64, -20i64, 1i64]);
         }
     }
+
+    #[test]
+    fn test_multivalued_f64() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_f64_field(
+            "multifield",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(field=> 1.5f64, field => -3.25f64));
+        index_writer.add_document(doc!());
+        assert!(index_writer.commit().is_ok());
+        index_writer.add_document(doc!(field=> -4f64));
+        index_writer.add_document(doc!(field=> 0.5f64, field => 1e20f64));
+        assert!(index_writer.commit().is_ok());
+
+        let check_vals = |index: &Index| {
+            let reader = index.reader().unwrap();
+            reader.reload().unwrap();
+            let searcher = reader.searcher();
+            let mut all_vals = Vec::new();
+            let mut vals = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let multi_value_reader = segment_reader.fast_fields().f64s(field).unwrap();
+                for doc in 0..segment_reader.max_doc() {
+                    multi_value_reader.get_vals(doc, &mut vals);
+                    all_vals.push(vals.clone());
+                }
+            }
+            all_vals.sort_by_key(|vals| vals.len());
+            assert_eq!(
+                all_vals,
+                vec![
+                    vec![],
+                    vec![-4f64],
+                    vec![1.5f64, -3.25f64],
+                    vec![0.5f64, 1e20f64]
+                ]
+            );
+            searcher.segment_readers().len()
+        };
+        assert_eq!(check_vals(&index), 2);
+
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+        assert_eq!(check_vals(&index), 1);
+    }
+
     #[test]
     #[ignore]
     fn test_many_facets() {
@@ -1,7 +1,7 @@
 use crate::fastfield::{FastFieldReader, FastValue};
 use crate::DocId;
 
-/// Reader for a multivalued `u64` fast field.
+/// Reader for a multivalued `u64`, `i64` or `f64` fast field.
 ///
 /// The reader is implemented as two `u64` fast field.
 ///
@@ -33,6 +33,16 @@ impl<Item: FastValue> MultiValueIntFastFieldReader<Item> {
         }
     }
 
+    /// Returns the reader of the index of the first value of each document.
+    pub(crate) fn idx_reader(&self) -> &FastFieldReader<u64> {
+        &self.idx_reader
+    }
+
+    /// Returns the reader of the concatenated values of all documents.
+    pub(crate) fn vals_reader(&self) -> &FastFieldReader<Item> {
+        &self.vals_reader
+    }
+
     /// Returns `(start, stop)`, such that the values associated
     /// to the given document are `start..stop`.
     fn range(&self, doc: DocId) -> (u64, u64) {
@@ -34,17 +34,21 @@ pub struct MultiValueIntFastFieldWriter {
     field: Field,
     vals: Vec<UnorderedTermId>,
     doc_index: Vec<u64>,
-    is_facet: bool,
+    is_term_id: bool,
 }
 
 impl MultiValueIntFastFieldWriter {
     /// Creates a new `IntFastFieldWriter`
-    pub(crate) fn new(field: Field, is_facet: bool) -> Self {
+    ///
+    /// If `is_term_id` is true, the values are not extracted from the
+    /// documents, but pushed by the `SegmentWriter` as the `UnorderedTermId`s
+    /// of the terms of the field (facets and fast text fields).
+    pub(crate) fn new(field: Field, is_term_id: bool) -> Self {
         MultiValueIntFastFieldWriter {
             field,
             vals: Vec::new(),
             doc_index: Vec::new(),
-            is_facet,
+            is_term_id,
         }
     }
 
@@ -67,8 +71,9 @@ impl MultiValueIntFastFieldWriter {
     /// all of the matching field values present in the document.
     pub fn add_document(&mut self, doc: &Document) {
         self.next_doc();
-        // facets are indexed in the `SegmentWriter` as we encode their unordered id.
-        if !self.is_facet {
+        // facets and fast text fields are indexed in the `SegmentWriter`
+        // as we encode their unordered id.
+        if !self.is_term_id {
             for field_value in doc.field_values() {
                 if field_value.field() == self.field {
                     self.add_val(value_to_u64(field_value.value()));
@@ -91,7 +96,8 @@ impl MultiValueIntFastFieldWriter {
     /// Serializes fast field values by pushing them to the `FastFieldSerializer`.
     ///
     /// If a mapping is given, the values are remapped *and sorted* before serialization.
-    /// This is used when serializing `facets`. Specifically their terms are
+    /// This is used when serializing `facets` and fast text fields.
+    /// Specifically their terms are
     /// first stored in the writer as their position in the `IndexWriter`'s `HashMap`.
     /// This value is called an `UnorderedTermId`.
     ///
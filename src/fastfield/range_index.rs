@@ -0,0 +1,89 @@
+use super::{FastFieldReader, FastValue};
+use crate::common::BitSet;
+use crate::DocId;
+use std::collections::Bound;
+use std::ops::Range;
+
+/// Reverse index of a range indexed single-valued fast field.
+///
+/// It is made of two columns of the same length: the values
+/// of the field sorted in increasing order, and the `DocId` of the
+/// document each value belongs to. The documents whose value falls
+/// within a given range are then found by binary search.
+///
+/// See `IntOptions::set_range_indexed`.
+#[derive(Clone)]
+pub struct FastFieldRangeIndex {
+    sorted_vals: FastFieldReader<u64>,
+    doc_ids: FastFieldReader<u64>,
+    num_vals: u64,
+}
+
+impl FastFieldRangeIndex {
+    pub(crate) fn open(
+        sorted_vals: FastFieldReader<u64>,
+        doc_ids: FastFieldReader<u64>,
+        num_vals: u64,
+    ) -> FastFieldRangeIndex {
+        FastFieldRangeIndex {
+            sorted_vals,
+            doc_ids,
+            num_vals,
+        }
+    }
+
+    /// Returns the index of the first sorted value that is not lower than `val`,
+    /// or that is greater than `val` if `strict` is true.
+    fn partition_point(&self, val: u64, strict: bool) -> u64 {
+        let (mut start, mut end) = (0u64, self.num_vals);
+        while start < end {
+            let mid = start + (end - start) / 2;
+            let mid_val = self.sorted_vals.get_u64(mid);
+            if mid_val < val || (strict && mid_val == val) {
+                start = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+        start
+    }
+
+    /// Returns the range of the sorted values that are within the bounds.
+    fn sorted_range<Item: FastValue>(&self, left: &Bound<Item>, right: &Bound<Item>) -> Range<u64> {
+        let start = match *left {
+            Bound::Included(ref val) => self.partition_point(val.to_u64(), false),
+            Bound::Excluded(ref val) => self.partition_point(val.to_u64(), true),
+            Bound::Unbounded => 0,
+        };
+        let end = match *right {
+            Bound::Included(ref val) => self.partition_point(val.to_u64(), true),
+            Bound::Excluded(ref val) => self.partition_point(val.to_u64(), false),
+            Bound::Unbounded => self.num_vals,
+        };
+        start..end.max(start)
+    }
+
+    /// Returns the number of documents (including deleted ones) whose
+    /// value is within the bounds.
+    pub fn num_docs_in_range<Item: FastValue>(
+        &self,
+        left: &Bound<Item>,
+        right: &Bound<Item>,
+    ) -> u64 {
+        let range = self.sorted_range(left, right);
+        range.end - range.start
+    }
+
+    /// Inserts in `docs` the documents (including deleted ones) whose
+    /// value is within the bounds.
+    pub fn docs_in_range<Item: FastValue>(
+        &self,
+        left: &Bound<Item>,
+        right: &Bound<Item>,
+        docs: &mut BitSet,
+    ) {
+        for sorted_ord in self.sorted_range(left, right) {
+            docs.insert(self.doc_ids.get_u64(sorted_ord) as DocId);
+        }
+    }
+}
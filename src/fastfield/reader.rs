@@ -1,7 +1,8 @@
-use super::FastValue;
-use crate::common::bitpacker::BitUnpacker;
-use crate::common::compute_num_bits;
-use crate::common::BinarySerializable;
+#[cfg(test)]
+use super::codecs::FastFieldCodec;
+use super::codecs::{open_column, CodecReader, ColumnPart};
+use super::{DateTruncator, FastValue};
+use crate::chrono::TimeZone;
 use crate::common::CompositeFile;
 use crate::directory::ReadOnlySource;
 use crate::directory::{Directory, RAMDirectory, WritePtr};
@@ -9,18 +10,21 @@ use crate::fastfield::{FastFieldSerializer, FastFieldsWriter};
 use crate::schema::Schema;
 use crate::schema::FAST;
 use crate::DocId;
-use owning_ref::OwningRef;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::path::Path;
 
+/// Number of values decoded at once by `get_range` and `get_batch`.
+const BUFFER_LEN: usize = 128;
+
 /// Trait for accessing a fastfield.
 ///
 /// Depending on the field type, a different
 /// fast field is required.
 #[derive(Clone)]
 pub struct FastFieldReader<Item: FastValue> {
-    bit_unpacker: BitUnpacker<OwningRef<ReadOnlySource, [u8]>>,
+    data: ReadOnlySource,
+    codec_reader: CodecReader,
     min_value_u64: u64,
     max_value_u64: u64,
     _phantom: PhantomData<Item>,
@@ -29,30 +33,21 @@ pub struct FastFieldReader<Item: FastValue> {
 impl<Item: FastValue> FastFieldReader<Item> {
     /// Opens a fast field given a source.
     pub fn open(data: ReadOnlySource) -> Self {
-        let min_value: u64;
-        let amplitude: u64;
-        {
-            let mut cursor = data.as_slice();
-            min_value =
-                u64::deserialize(&mut cursor).expect("Failed to read the min_value of fast field.");
-            amplitude =
-                u64::deserialize(&mut cursor).expect("Failed to read the amplitude of fast field.");
-        }
+        let (codec_reader, min_value, amplitude) = open_column(data.clone());
         let max_value = min_value + amplitude;
-        let num_bits = compute_num_bits(amplitude);
-        let owning_ref = OwningRef::new(data).map(|data| &data[16..]);
-        let bit_unpacker = BitUnpacker::new(owning_ref, num_bits);
         FastFieldReader {
+            data,
             min_value_u64: min_value,
             max_value_u64: max_value,
-            bit_unpacker,
+            codec_reader,
             _phantom: PhantomData,
         }
     }
 
     pub(crate) fn into_u64_reader(self) -> FastFieldReader<u64> {
         FastFieldReader {
-            bit_unpacker: self.bit_unpacker,
+            data: self.data,
+            codec_reader: self.codec_reader,
             min_value_u64: self.min_value_u64,
             max_value_u64: self.max_value_u64,
             _phantom: PhantomData,
@@ -72,7 +67,7 @@ impl<Item: FastValue> FastFieldReader<Item> {
     }
 
     pub(crate) fn get_u64(&self, doc: u64) -> Item {
-        Item::from_u64(self.min_value_u64 + self.bit_unpacker.get(doc))
+        Item::from_u64(self.codec_reader.get(doc))
     }
 
     /// Internally `multivalued` also use SingleValue Fast fields.
@@ -88,20 +83,41 @@ impl<Item: FastValue> FastFieldReader<Item> {
     ///
     /// See `get_range` for an actual documentation about this method.
     pub(crate) fn get_range_u64(&self, start: u64, output: &mut [Item]) {
-        for (i, out) in output.iter_mut().enumerate() {
-            *out = self.get_u64(start + (i as u64));
+        let mut buffer = [0u64; BUFFER_LEN];
+        for (chunk_ord, output_chunk) in output.chunks_mut(BUFFER_LEN).enumerate() {
+            let chunk_start = start + (chunk_ord * BUFFER_LEN) as u64;
+            let buffer_chunk = &mut buffer[..output_chunk.len()];
+            self.codec_reader.get_range(chunk_start, buffer_chunk);
+            for (out, &val) in output_chunk.iter_mut().zip(buffer_chunk.iter()) {
+                *out = Item::from_u64(val);
+            }
         }
     }
 
+    /// Returns the parts to stack in order to copy the `num_vals` first
+    /// values of the column, incremented by `offset`, without decoding them.
+    pub(crate) fn column_parts(&self, num_vals: u64, offset: u64) -> Vec<ColumnPart> {
+        self.codec_reader.parts(&self.data, num_vals, offset)
+    }
+
+    /// Returns true iff the column was obtained by stacking other columns.
+    #[cfg(test)]
+    pub(crate) fn is_stacked(&self) -> bool {
+        self.codec_reader.codec() == FastFieldCodec::Stacked
+    }
+
     /// Fills an output buffer with the fast field values
     /// associated with the `DocId` going from
     /// `start` to `start + output.len()`.
     ///
     /// Regardless of the type of `Item`, this method works
-    /// - transmuting the output array
-    /// - extracting the `Item`s as if they were `u64`
+    /// - extracting the `Item`s as if they were `u64`, by batch,
     /// - possibly converting the `u64` value to the right type.
     ///
+    /// This is much faster than calling `.get(doc)` for each of the documents,
+    /// and should be preferred by collectors visiting every hit
+    /// of a range of documents.
+    ///
     /// # Panics
     ///
     /// May panic if `start + output.len()` is greater than
@@ -110,6 +126,32 @@ impl<Item: FastValue> FastFieldReader<Item> {
         self.get_range_u64(u64::from(start), output);
     }
 
+    /// Fills an output buffer with the fast field values
+    /// associated with each of the `docs`.
+    ///
+    /// `output[i]` receives the value of `docs[i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `docs` and `output` do not have the same length.
+    /// May panic if one of the `docs` is greater than the segment's `maxdoc`.
+    pub fn get_batch(&self, docs: &[DocId], output: &mut [Item]) {
+        assert_eq!(
+            docs.len(),
+            output.len(),
+            "The output buffer should have the same length as the docs."
+        );
+        let mut buffer = [0u64; BUFFER_LEN];
+        for (docs_chunk, output_chunk) in docs.chunks(BUFFER_LEN).zip(output.chunks_mut(BUFFER_LEN))
+        {
+            let buffer_chunk = &mut buffer[..output_chunk.len()];
+            self.codec_reader.get_batch(docs_chunk, buffer_chunk);
+            for (out, &val) in output_chunk.iter_mut().zip(buffer_chunk.iter()) {
+                *out = Item::from_u64(val);
+            }
+        }
+    }
+
     /// Returns the minimum value for this fast field.
     ///
     /// The max value does not take in account of possible
@@ -129,6 +171,23 @@ impl<Item: FastValue> FastFieldReader<Item> {
     }
 }
 
+impl FastFieldReader<i64> {
+    /// Fills an output buffer with the timestamps of a date fast field for
+    /// a batch of `DocId`s, truncated to the start of their bucket by the
+    /// given `DateTruncator`.
+    ///
+    /// `output` must have the same length as `docs`.
+    pub fn get_truncated_batch<Tz: TimeZone>(
+        &self,
+        docs: &[DocId],
+        truncator: &mut DateTruncator<Tz>,
+        output: &mut [i64],
+    ) {
+        self.get_batch(docs, output);
+        truncator.truncate_batch(output);
+    }
+}
+
 impl<Item: FastValue> From<Vec<Item>> for FastFieldReader<Item> {
     fn from(vals: Vec<Item>) -> FastFieldReader<Item> {
         let mut schema_builder = Schema::builder();
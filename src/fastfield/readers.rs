@@ -1,9 +1,11 @@
 use crate::common::CompositeFile;
 use crate::fastfield::BytesFastFieldReader;
-use crate::fastfield::MultiValueIntFastFieldReader;
+use crate::fastfield::FastFieldRangeIndex;
 use crate::fastfield::{FastFieldNotAvailableError, FastFieldReader};
-use crate::schema::{Cardinality, Field, FieldType, Schema};
+use crate::fastfield::{MultiValueBytesFastFieldReader, MultiValueIntFastFieldReader};
+use crate::schema::{Cardinality, Field, FieldType, GeoPoint, Schema};
 use crate::space_usage::PerFieldSpaceUsage;
+use crate::DocId;
 use crate::Result;
 use std::collections::HashMap;
 
@@ -15,10 +17,14 @@ pub struct FastFieldReaders {
     fast_field_i64: HashMap<Field, FastFieldReader<i64>>,
     fast_field_u64: HashMap<Field, FastFieldReader<u64>>,
     fast_field_f64: HashMap<Field, FastFieldReader<f64>>,
+    fast_field_geo_point: HashMap<Field, FastFieldReader<GeoPoint>>,
     fast_field_i64s: HashMap<Field, MultiValueIntFastFieldReader<i64>>,
     fast_field_u64s: HashMap<Field, MultiValueIntFastFieldReader<u64>>,
     fast_field_f64s: HashMap<Field, MultiValueIntFastFieldReader<f64>>,
+    fast_field_geo_points: HashMap<Field, MultiValueIntFastFieldReader<GeoPoint>>,
     fast_bytes: HashMap<Field, BytesFastFieldReader>,
+    fast_multi_bytes: HashMap<Field, MultiValueBytesFastFieldReader>,
+    range_indexes: HashMap<Field, FastFieldRangeIndex>,
     fast_fields_composite: CompositeFile,
 }
 
@@ -26,6 +32,7 @@ enum FastType {
     I64,
     U64,
     F64,
+    GeoPoint,
 }
 
 fn type_and_cardinality(field_type: &FieldType) -> Option<(FastType, Cardinality)> {
@@ -33,13 +40,19 @@ fn type_and_cardinality(field_type: &FieldType) -> Option<(FastType, Cardinality
         FieldType::U64(options) => options
             .get_fastfield_cardinality()
             .map(|cardinality| (FastType::U64, cardinality)),
-        FieldType::I64(options) => options
+        FieldType::I64(options) | FieldType::Date(options) => options
             .get_fastfield_cardinality()
             .map(|cardinality| (FastType::I64, cardinality)),
         FieldType::F64(options) => options
             .get_fastfield_cardinality()
             .map(|cardinality| (FastType::F64, cardinality)),
+        FieldType::GeoPoint(options) => options
+            .get_fastfield_cardinality()
+            .map(|cardinality| (FastType::GeoPoint, cardinality)),
         FieldType::HierarchicalFacet => Some((FastType::U64, Cardinality::MultiValues)),
+        FieldType::Str(options) if options.is_fast() => {
+            Some((FastType::U64, Cardinality::MultiValues))
+        }
         _ => None,
     }
 }
@@ -48,34 +61,70 @@ impl FastFieldReaders {
     pub(crate) fn load_all(
         schema: &Schema,
         fast_fields_composite: &CompositeFile,
+        max_doc: DocId,
     ) -> Result<FastFieldReaders> {
         let mut fast_field_readers = FastFieldReaders {
             fast_field_i64: Default::default(),
             fast_field_u64: Default::default(),
             fast_field_f64: Default::default(),
+            fast_field_geo_point: Default::default(),
             fast_field_i64s: Default::default(),
             fast_field_u64s: Default::default(),
             fast_field_f64s: Default::default(),
+            fast_field_geo_points: Default::default(),
             fast_bytes: Default::default(),
+            fast_multi_bytes: Default::default(),
+            range_indexes: Default::default(),
             fast_fields_composite: fast_fields_composite.clone(),
         };
         for (field_id, field_entry) in schema.fields().iter().enumerate() {
             let field = Field(field_id as u32);
             let field_type = field_entry.field_type();
-            if field_type == &FieldType::Bytes {
-                let idx_reader = fast_fields_composite
-                    .open_read_with_idx(field, 0)
-                    .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))
-                    .map(FastFieldReader::open)?;
-                let data = fast_fields_composite
-                    .open_read_with_idx(field, 1)
-                    .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))?;
-                fast_field_readers
-                    .fast_bytes
-                    .insert(field, BytesFastFieldReader::open(idx_reader, data));
+            if let FieldType::Bytes(ref bytes_options) = field_type {
+                let open_u64_reader = |idx: usize| -> Result<FastFieldReader<u64>> {
+                    let source = fast_fields_composite
+                        .open_read_with_idx(field, idx)
+                        .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))?;
+                    Ok(FastFieldReader::open(source))
+                };
+                // The offsets of the bytes are written with the index `idx`,
+                // and the bytes themselves with the index `idx + 1`.
+                let open_bytes_reader = |idx: usize| -> Result<BytesFastFieldReader> {
+                    let idx_reader = open_u64_reader(idx)?;
+                    let data = fast_fields_composite
+                        .open_read_with_idx(field, idx + 1)
+                        .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))?;
+                    Ok(BytesFastFieldReader::open(idx_reader, data))
+                };
+                match bytes_options.get_fastfield_cardinality() {
+                    Some(Cardinality::SingleValue) => {
+                        let bytes_reader = open_bytes_reader(0)?;
+                        fast_field_readers.fast_bytes.insert(field, bytes_reader);
+                    }
+                    Some(Cardinality::MultiValues) => {
+                        let multi_bytes_reader = MultiValueBytesFastFieldReader::open(
+                            open_u64_reader(0)?,
+                            open_bytes_reader(1)?,
+                        );
+                        fast_field_readers
+                            .fast_multi_bytes
+                            .insert(field, multi_bytes_reader);
+                    }
+                    None => {}
+                }
             } else if let Some((fast_type, cardinality)) = type_and_cardinality(field_type) {
                 match cardinality {
                     Cardinality::SingleValue => {
+                        let sorted_vals_opt = fast_fields_composite.open_read_with_idx(field, 1);
+                        let doc_ids_opt = fast_fields_composite.open_read_with_idx(field, 2);
+                        if let (Some(sorted_vals), Some(doc_ids)) = (sorted_vals_opt, doc_ids_opt) {
+                            let range_index = FastFieldRangeIndex::open(
+                                FastFieldReader::open(sorted_vals),
+                                FastFieldReader::open(doc_ids),
+                                u64::from(max_doc),
+                            );
+                            fast_field_readers.range_indexes.insert(field, range_index);
+                        }
                         if let Some(fast_field_data) = fast_fields_composite.open_read(field) {
                             match fast_type {
                                 FastType::U64 => {
@@ -96,6 +145,12 @@ impl FastFieldReaders {
                                         FastFieldReader::open(fast_field_data.clone()),
                                     );
                                 }
+                                FastType::GeoPoint => {
+                                    fast_field_readers.fast_field_geo_point.insert(
+                                        field,
+                                        FastFieldReader::open(fast_field_data.clone()),
+                                    );
+                                }
                             }
                         } else {
                             return Err(From::from(FastFieldNotAvailableError::new(field_entry)));
@@ -131,6 +186,14 @@ impl FastFieldReaders {
                                         .fast_field_f64s
                                         .insert(field, multivalued_int_fast_field);
                                 }
+                                FastType::GeoPoint => {
+                                    let vals_reader = FastFieldReader::open(fast_field_data);
+                                    let multivalued_int_fast_field =
+                                        MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
+                                    fast_field_readers
+                                        .fast_field_geo_points
+                                        .insert(field, multivalued_int_fast_field);
+                                }
                             }
                         } else {
                             return Err(From::from(FastFieldNotAvailableError::new(field_entry)));
@@ -146,6 +209,14 @@ impl FastFieldReaders {
         self.fast_fields_composite.space_usage()
     }
 
+    /// Returns the range index associated to `field`.
+    ///
+    /// If `field` is not a range indexed single-valued fast field,
+    /// this method returns `None`.
+    pub fn range_index(&self, field: Field) -> Option<FastFieldRangeIndex> {
+        self.range_indexes.get(&field).cloned()
+    }
+
     /// Returns the `u64` fast field reader reader associated to `field`.
     ///
     /// If `field` is not a u64 fast field, this method returns `None`.
@@ -154,10 +225,9 @@ impl FastFieldReaders {
     }
 
     /// If the field is a u64-fast field return the associated reader.
-    /// If the field is a i64-fast field, return the associated u64 reader. Values are
-    /// mapped from i64 to u64 using a (well the, it is unique) monotonic mapping.    ///
-    ///
-    ///TODO should it also be lenient with f64?
+    /// If the field is a i64-fast field or a f64-fast field, return the associated u64 reader.
+    /// Values are mapped from i64 or f64 to u64 using a (well the, it is unique) monotonic mapping.
+    /// Geo points are mapped to their morton code.
     ///
     /// This method is useful when merging segment reader.
     pub(crate) fn u64_lenient(&self, field: Field) -> Option<FastFieldReader<u64>> {
@@ -167,11 +237,19 @@ impl FastFieldReaders {
         if let Some(i64_ff_reader) = self.i64(field) {
             return Some(i64_ff_reader.into_u64_reader());
         }
+        if let Some(f64_ff_reader) = self.f64(field) {
+            return Some(f64_ff_reader.into_u64_reader());
+        }
+        if let Some(geo_point_ff_reader) = self.geo_point(field) {
+            return Some(geo_point_ff_reader.into_u64_reader());
+        }
         None
     }
 
     /// Returns the `i64` fast field reader reader associated to `field`.
     ///
+    /// Date fast fields are also accessible via this method, as their timestamp.
+    ///
     /// If `field` is not a i64 fast field, this method returns `None`.
     pub fn i64(&self, field: Field) -> Option<FastFieldReader<i64>> {
         self.fast_field_i64.get(&field).cloned()
@@ -184,6 +262,13 @@ impl FastFieldReaders {
         self.fast_field_f64.get(&field).cloned()
     }
 
+    /// Returns the `GeoPoint` fast field reader reader associated to `field`.
+    ///
+    /// If `field` is not a geo point fast field, this method returns `None`.
+    pub fn geo_point(&self, field: Field) -> Option<FastFieldReader<GeoPoint>> {
+        self.fast_field_geo_point.get(&field).cloned()
+    }
+
     /// Returns a `u64s` multi-valued fast field reader reader associated to `field`.
     ///
     /// If `field` is not a u64 multi-valued fast field, this method returns `None`.
@@ -192,8 +277,8 @@ impl FastFieldReaders {
     }
 
     /// If the field is a u64s-fast field return the associated reader.
-    /// If the field is a i64s-fast field, return the associated u64s reader. Values are
-    /// mapped from i64 to u64 using a (well the, it is unique) monotonic mapping.
+    /// If the field is a i64s-fast field or a f64s-fast field, return the associated u64s reader.
+    /// Values are mapped from i64 or f64 to u64 using a (well the, it is unique) monotonic mapping.
     ///
     /// This method is useful when merging segment reader.
     pub(crate) fn u64s_lenient(&self, field: Field) -> Option<MultiValueIntFastFieldReader<u64>> {
@@ -203,11 +288,19 @@ impl FastFieldReaders {
         if let Some(i64s_ff_reader) = self.i64s(field) {
             return Some(i64s_ff_reader.into_u64s_reader());
         }
+        if let Some(f64s_ff_reader) = self.f64s(field) {
+            return Some(f64s_ff_reader.into_u64s_reader());
+        }
+        if let Some(geo_points_ff_reader) = self.geo_points(field) {
+            return Some(geo_points_ff_reader.into_u64s_reader());
+        }
         None
     }
 
     /// Returns a `i64s` multi-valued fast field reader reader associated to `field`.
     ///
+    /// Multi-valued date fast fields are also accessible via this method, as their timestamps.
+    ///
     /// If `field` is not a i64 multi-valued fast field, this method returns `None`.
     pub fn i64s(&self, field: Field) -> Option<MultiValueIntFastFieldReader<i64>> {
         self.fast_field_i64s.get(&field).cloned()
@@ -220,10 +313,24 @@ impl FastFieldReaders {
         self.fast_field_f64s.get(&field).cloned()
     }
 
+    /// Returns a `GeoPoint` multi-valued fast field reader reader associated to `field`.
+    ///
+    /// If `field` is not a geo point multi-valued fast field, this method returns `None`.
+    pub fn geo_points(&self, field: Field) -> Option<MultiValueIntFastFieldReader<GeoPoint>> {
+        self.fast_field_geo_points.get(&field).cloned()
+    }
+
     /// Returns the `bytes` fast field reader associated to `field`.
     ///
     /// If `field` is not a bytes fast field, returns `None`.
     pub fn bytes(&self, field: Field) -> Option<BytesFastFieldReader> {
         self.fast_bytes.get(&field).cloned()
     }
+
+    /// Returns the multi-valued `bytes` fast field reader associated to `field`.
+    ///
+    /// If `field` is not a bytes multi-valued fast field, returns `None`.
+    pub fn multi_bytes(&self, field: Field) -> Option<MultiValueBytesFastFieldReader> {
+        self.fast_multi_bytes.get(&field).cloned()
+    }
 }
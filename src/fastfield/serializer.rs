@@ -1,16 +1,19 @@
-use crate::common::bitpacker::BitPacker;
-use crate::common::compute_num_bits;
 use crate::common::BinarySerializable;
 use crate::common::CompositeWrite;
 use crate::common::CountingWriter;
 use crate::directory::WritePtr;
+use crate::fastfield::codecs::{serialize_stacked, serialize_vals, ColumnPart};
 use crate::schema::Field;
+use crate::DocId;
 use std::io::{self, Write};
 
 /// `FastFieldSerializer` is in charge of serializing
 /// fastfields on disk.
 ///
-/// Fast fields are encoded using bit-packing.
+/// Fast fields are encoded using bit-packing, possibly
+/// after removing a common divisor, a linear trend or
+/// a per-block minimum, depending on which yields
+/// the smallest column.
 ///
 /// `FastFieldWriter`s are in charge of pushing the data to
 /// the serializer.
@@ -61,6 +64,53 @@ impl FastFieldSerializer {
         FastSingleFieldSerializer::open(field_write, min_value, max_value)
     }
 
+    /// Serializes a u64 fast field by stacking the given parts
+    /// of existing columns, without decoding them.
+    ///
+    /// `min_value` and `max_value` should bound the values of the parts.
+    pub(crate) fn stack_u64_fast_field_with_idx(
+        &mut self,
+        field: Field,
+        min_value: u64,
+        max_value: u64,
+        idx: usize,
+        parts: &[ColumnPart],
+    ) -> io::Result<()> {
+        assert!(min_value <= max_value);
+        let field_write = self.composite_write.for_field_with_idx(field, idx);
+        min_value.serialize(field_write)?;
+        (max_value - min_value).serialize(field_write)?;
+        serialize_stacked(field_write, parts)
+    }
+
+    /// Serializes the range index of a single-valued fast field,
+    /// given the value of each document.
+    ///
+    /// The values sorted in increasing order are written with the index `1`,
+    /// and the `DocId` of each of them with the index `2`.
+    pub(crate) fn write_range_index(
+        &mut self,
+        field: Field,
+        mut vals: Vec<(u64, DocId)>,
+    ) -> io::Result<()> {
+        vals.sort_unstable();
+        let min_value = vals.first().map(|&(val, _)| val).unwrap_or(0u64);
+        let max_value = vals.last().map(|&(val, _)| val).unwrap_or(0u64);
+        let mut vals_serializer =
+            self.new_u64_fast_field_with_idx(field, min_value, max_value, 1)?;
+        for &(val, _) in &vals {
+            vals_serializer.add_val(val)?;
+        }
+        vals_serializer.close_field()?;
+        let max_doc = vals.iter().map(|&(_, doc)| doc).max().unwrap_or(0u32);
+        let mut doc_ids_serializer =
+            self.new_u64_fast_field_with_idx(field, 0, u64::from(max_doc), 2)?;
+        for &(_, doc) in &vals {
+            doc_ids_serializer.add_val(u64::from(doc))?;
+        }
+        doc_ids_serializer.close_field()
+    }
+
     /// Start serializing a new [u8] fast field
     pub fn new_bytes_fast_field_with_idx(
         &mut self,
@@ -80,22 +130,22 @@ impl FastFieldSerializer {
 }
 
 pub struct FastSingleFieldSerializer<'a, W: Write> {
-    bit_packer: BitPacker,
     write: &'a mut W,
     min_value: u64,
-    num_bits: u8,
+    amplitude: u64,
+    vals: Vec<u64>,
 }
 
 impl<'a, W: Write> FastSingleFieldSerializer<'a, W> {
     /// Creates a new fast field serializer.
     ///
-    /// The serializer in fact encode the values by bitpacking
-    /// `(val - min_value)`.
+    /// The values are buffered, and encoded upon `close_field`
+    /// with the codec yielding the smallest column.
+    /// See `FastFieldCodec` for the list of codecs.
     ///
-    /// It requires a `min_value` and a `max_value` to compute
-    /// compute the minimum number of bits required to encode
-    /// values.
-    fn open(
+    /// It requires a `min_value` and a `max_value`, which
+    /// are recorded in the header of the column.
+    pub(crate) fn open(
         write: &'a mut W,
         min_value: u64,
         max_value: u64,
@@ -104,26 +154,23 @@ impl<'a, W: Write> FastSingleFieldSerializer<'a, W> {
         min_value.serialize(write)?;
         let amplitude = max_value - min_value;
         amplitude.serialize(write)?;
-        let num_bits = compute_num_bits(amplitude);
-        let bit_packer = BitPacker::new();
         Ok(FastSingleFieldSerializer {
             write,
-            bit_packer,
             min_value,
-            num_bits,
+            amplitude,
+            vals: Vec::new(),
         })
     }
 
     /// Pushes a new value to the currently open u64 fast field.
     pub fn add_val(&mut self, val: u64) -> io::Result<()> {
-        let val_to_write: u64 = val - self.min_value;
-        self.bit_packer
-            .write(val_to_write, self.num_bits, &mut self.write)?;
+        debug_assert!(val >= self.min_value && val - self.min_value <= self.amplitude);
+        self.vals.push(val);
         Ok(())
     }
 
-    pub fn close_field(mut self) -> io::Result<()> {
-        self.bit_packer.close(&mut self.write)
+    pub fn close_field(self) -> io::Result<()> {
+        serialize_vals(self.write, &self.vals, self.min_value, self.amplitude)
     }
 }
 
@@ -0,0 +1,192 @@
+use super::MultiValueIntFastFieldReader;
+use crate::termdict::TermDictionary;
+use crate::termdict::TermOrdinal;
+use crate::DocId;
+use std::str;
+
+/// The str fast field reader makes it possible to access the list
+/// of terms associated to a given document for a text field
+/// declared as fast.
+///
+/// Terms are exposed in the form of term ordinals, that is their
+/// position in the sorted term dictionary of the segment.
+/// Comparing two ordinals is therefore equivalent to comparing the
+/// terms themselves, which makes sorting or grouping on a string
+/// field cheap.
+///
+/// Term ordinals are segment local and only make sense for a
+/// given segment. They can be translated back into a term via
+/// `.ord_to_term(...)`.
+pub struct StrFastFieldReader {
+    term_ords: MultiValueIntFastFieldReader<u64>,
+    term_dict: TermDictionary,
+    buffer: Vec<u8>,
+}
+
+impl StrFastFieldReader {
+    /// Creates a new `StrFastFieldReader`.
+    ///
+    /// A str fast field reader just wraps :
+    /// - a `MultiValueIntFastFieldReader` that makes it possible to
+    ///   access the list of term ords for a given document.
+    /// - a `TermDictionary` that helps associating a term to
+    ///   an ordinal and vice versa.
+    pub fn new(
+        term_ords: MultiValueIntFastFieldReader<u64>,
+        term_dict: TermDictionary,
+    ) -> StrFastFieldReader {
+        StrFastFieldReader {
+            term_ords,
+            term_dict,
+            buffer: vec![],
+        }
+    }
+
+    /// Returns the number of distinct terms in the segment.
+    /// This does not take in account the documents that may be marked
+    /// as deleted.
+    ///
+    /// Term ordinals range from `0` to `num_terms() - 1`.
+    pub fn num_terms(&self) -> usize {
+        self.term_dict.num_terms()
+    }
+
+    /// Accessor for the term dictionary of the field.
+    pub fn term_dict(&self) -> &TermDictionary {
+        &self.term_dict
+    }
+
+    /// Given a term ordinal, writes the associated term in `output`.
+    ///
+    /// Returns false if the term ordinal is out of bounds.
+    pub fn ord_to_term(
+        &mut self,
+        term_ord: TermOrdinal,
+        output: &mut String,
+    ) -> Result<bool, str::Utf8Error> {
+        if !self.term_dict.ord_to_term(term_ord, &mut self.buffer) {
+            return Ok(false);
+        }
+        output.clear();
+        output.push_str(str::from_utf8(&self.buffer[..])?);
+        Ok(true)
+    }
+
+    /// Returns the ordinal associated to the given term, or `None`
+    /// if the term is not present in the segment.
+    pub fn term_to_ord(&self, term: &str) -> Option<TermOrdinal> {
+        self.term_dict.term_ord(term)
+    }
+
+    /// Return the sorted list of the distinct term ordinals associated to a document.
+    ///
+    /// A term appearing several times in the document is only listed once.
+    pub fn term_ords(&self, doc: DocId, output: &mut Vec<TermOrdinal>) {
+        self.term_ords.get_vals(doc, output);
+        output.dedup();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{Schema, FAST, STRING, TEXT};
+    use crate::{Index, Term};
+    use futures::Future;
+
+    #[test]
+    fn test_str_fast_field_reader() {
+        let mut schema_builder = Schema::builder();
+        let city_field = schema_builder.add_text_field("city", STRING | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(city_field=>"Paris"));
+        index_writer.add_document(doc!(city_field=>"Berlin", city_field=>"Amsterdam"));
+        index_writer.add_document(doc!());
+        index_writer.add_document(doc!(city_field=>"Berlin"));
+        index_writer
+            .add_document(doc!(city_field=>"Paris", city_field=>"Berlin", city_field=>"Paris"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let mut str_reader = segment_reader.str_fast_field_reader(city_field).unwrap();
+        assert_eq!(str_reader.num_terms(), 3);
+        assert_eq!(str_reader.term_to_ord("Amsterdam"), Some(0));
+        assert_eq!(str_reader.term_to_ord("Paris"), Some(2));
+        assert_eq!(str_reader.term_to_ord("London"), None);
+        let mut term = String::new();
+        assert!(str_reader.ord_to_term(1, &mut term).unwrap());
+        assert_eq!(term, "Berlin");
+        assert!(!str_reader.ord_to_term(3, &mut term).unwrap());
+        let mut ords = Vec::new();
+        str_reader.term_ords(0, &mut ords);
+        assert_eq!(&ords[..], &[2]);
+        str_reader.term_ords(1, &mut ords);
+        assert_eq!(&ords[..], &[0, 1]);
+        str_reader.term_ords(2, &mut ords);
+        assert!(ords.is_empty());
+        str_reader.term_ords(3, &mut ords);
+        assert_eq!(&ords[..], &[1]);
+        str_reader.term_ords(4, &mut ords);
+        assert_eq!(&ords[..], &[1, 2]);
+    }
+
+    #[test]
+    fn test_str_fast_field_reader_after_merge() {
+        let mut schema_builder = Schema::builder();
+        let city_field = schema_builder.add_text_field("city", STRING | FAST);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(city_field=>"Paris"));
+        index_writer.add_document(doc!(city_field=>"Lyon", text_field=>"deleted"));
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(city_field=>"Berlin"));
+        index_writer.add_document(doc!(city_field=>"Paris"));
+        index_writer.commit().unwrap();
+        index_writer.delete_term(Term::from_field_text(text_field, "deleted"));
+        index_writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+
+        let reader = index.reader().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let mut str_reader = segment_reader.str_fast_field_reader(city_field).unwrap();
+        assert_eq!(str_reader.num_terms(), 2);
+        let mut cities = Vec::new();
+        let mut ords = Vec::new();
+        let mut term = String::new();
+        for doc in 0..segment_reader.max_doc() {
+            str_reader.term_ords(doc, &mut ords);
+            assert_eq!(ords.len(), 1);
+            assert!(str_reader.ord_to_term(ords[0], &mut term).unwrap());
+            cities.push(term.clone());
+        }
+        assert_eq!(cities, vec!["Paris", "Berlin", "Paris"]);
+    }
+
+    #[test]
+    fn test_str_fast_field_reader_not_fast() {
+        let mut schema_builder = Schema::builder();
+        let city_field = schema_builder.add_text_field("city", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(city_field=>"Paris"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert!(searcher
+            .segment_reader(0)
+            .str_fast_field_reader(city_field)
+            .is_none());
+    }
+}
@@ -2,10 +2,11 @@ use super::multivalued::MultiValueIntFastFieldWriter;
 use crate::common;
 use crate::common::BinarySerializable;
 use crate::common::VInt;
-use crate::fastfield::{BytesFastFieldWriter, FastFieldSerializer};
+use crate::fastfield::{BytesFastFieldWriter, FastFieldSerializer, MultiValueBytesFastFieldWriter};
 use crate::postings::UnorderedTermId;
 use crate::schema::{Cardinality, Document, Field, FieldType, Schema};
 use crate::termdict::TermOrdinal;
+use crate::DocId;
 use fnv::FnvHashMap;
 use std::collections::HashMap;
 use std::io;
@@ -15,6 +16,7 @@ pub struct FastFieldsWriter {
     single_value_writers: Vec<IntFastFieldWriter>,
     multi_values_writers: Vec<MultiValueIntFastFieldWriter>,
     bytes_value_writers: Vec<BytesFastFieldWriter>,
+    multi_bytes_value_writers: Vec<MultiValueBytesFastFieldWriter>,
 }
 
 impl FastFieldsWriter {
@@ -23,22 +25,28 @@ impl FastFieldsWriter {
         let mut single_value_writers = Vec::new();
         let mut multi_values_writers = Vec::new();
         let mut bytes_value_writers = Vec::new();
+        let mut multi_bytes_value_writers = Vec::new();
 
         for (field_id, field_entry) in schema.fields().iter().enumerate() {
             let field = Field(field_id as u32);
             let default_value = match *field_entry.field_type() {
-                FieldType::I64(_) => common::i64_to_u64(0i64),
+                FieldType::I64(_) | FieldType::Date(_) => common::i64_to_u64(0i64),
                 FieldType::F64(_) => common::f64_to_u64(0.0f64),
                 _ => 0u64,
             };
             match *field_entry.field_type() {
                 FieldType::I64(ref int_options)
                 | FieldType::U64(ref int_options)
-                | FieldType::F64(ref int_options) => {
+                | FieldType::F64(ref int_options)
+                | FieldType::Date(ref int_options)
+                | FieldType::GeoPoint(ref int_options) => {
                     match int_options.get_fastfield_cardinality() {
                         Some(Cardinality::SingleValue) => {
                             let mut fast_field_writer = IntFastFieldWriter::new(field);
                             fast_field_writer.set_val_if_missing(default_value);
+                            if int_options.is_range_indexed() {
+                                fast_field_writer.set_range_indexed();
+                            }
                             single_value_writers.push(fast_field_writer);
                         }
                         Some(Cardinality::MultiValues) => {
@@ -52,9 +60,22 @@ impl FastFieldsWriter {
                     let fast_field_writer = MultiValueIntFastFieldWriter::new(field, true);
                     multi_values_writers.push(fast_field_writer);
                 }
-                FieldType::Bytes => {
-                    let fast_field_writer = BytesFastFieldWriter::new(field);
-                    bytes_value_writers.push(fast_field_writer);
+                FieldType::Str(ref text_options) if text_options.is_fast() => {
+                    let fast_field_writer = MultiValueIntFastFieldWriter::new(field, true);
+                    multi_values_writers.push(fast_field_writer);
+                }
+                FieldType::Bytes(ref bytes_options) => {
+                    match bytes_options.get_fastfield_cardinality() {
+                        Some(Cardinality::SingleValue) => {
+                            let fast_field_writer = BytesFastFieldWriter::new(field);
+                            bytes_value_writers.push(fast_field_writer);
+                        }
+                        Some(Cardinality::MultiValues) => {
+                            let fast_field_writer = MultiValueBytesFastFieldWriter::new(field);
+                            multi_bytes_value_writers.push(fast_field_writer);
+                        }
+                        None => {}
+                    }
                 }
                 _ => {}
             }
@@ -63,6 +84,7 @@ impl FastFieldsWriter {
             single_value_writers,
             multi_values_writers,
             bytes_value_writers,
+            multi_bytes_value_writers,
         }
     }
 
@@ -99,6 +121,20 @@ impl FastFieldsWriter {
             .find(|field_writer| field_writer.field() == field)
     }
 
+    /// Returns the multi-valued bytes fast field writer for the given field.
+    ///
+    /// Returns None if the field does not exist, or is not
+    /// configured as a multivalued bytes fastfield in the schema.
+    pub fn get_multi_bytes_writer(
+        &mut self,
+        field: Field,
+    ) -> Option<&mut MultiValueBytesFastFieldWriter> {
+        // TODO optimize
+        self.multi_bytes_value_writers
+            .iter_mut()
+            .find(|field_writer| field_writer.field() == field)
+    }
+
     /// Indexes all of the fastfields of a new document.
     pub fn add_document(&mut self, doc: &Document) {
         for field_writer in &mut self.single_value_writers {
@@ -110,6 +146,9 @@ impl FastFieldsWriter {
         for field_writer in &mut self.bytes_value_writers {
             field_writer.add_document(doc);
         }
+        for field_writer in &mut self.multi_bytes_value_writers {
+            field_writer.add_document(doc);
+        }
     }
 
     /// Serializes all of the `FastFieldWriter`s by pushing them in
@@ -129,6 +168,9 @@ impl FastFieldsWriter {
         for field_writer in &self.bytes_value_writers {
             field_writer.serialize(serializer)?;
         }
+        for field_writer in &self.multi_bytes_value_writers {
+            field_writer.serialize(serializer)?;
+        }
         Ok(())
     }
 }
@@ -145,9 +187,11 @@ impl FastFieldsWriter {
 /// bitpacked and the number of bits required for bitpacking
 /// can only been known once we have seen all of the values.
 ///
-/// Both u64, i64 and f64 use the same writer.
+/// Both u64, i64, f64, date and geo point use the same writer.
 /// i64 and f64 are just remapped to the `0..2^64 - 1`
 /// using `common::i64_to_u64` and `common::f64_to_u64`.
+/// Dates are stored as their i64 timestamp, and geo points
+/// as their morton code.
 pub struct IntFastFieldWriter {
     field: Field,
     vals: Vec<u8>,
@@ -155,6 +199,7 @@ pub struct IntFastFieldWriter {
     val_if_missing: u64,
     val_min: u64,
     val_max: u64,
+    range_indexed: bool,
 }
 
 impl IntFastFieldWriter {
@@ -167,6 +212,7 @@ impl IntFastFieldWriter {
             val_if_missing: 0u64,
             val_min: u64::max_value(),
             val_max: 0,
+            range_indexed: false,
         }
     }
 
@@ -179,10 +225,17 @@ impl IntFastFieldWriter {
     ///
     /// This default value is recorded for documents if
     /// a document does not have any value.
-    fn set_val_if_missing(&mut self, val_if_missing: u64) {
+    pub(crate) fn set_val_if_missing(&mut self, val_if_missing: u64) {
         self.val_if_missing = val_if_missing;
     }
 
+    /// Also serializes the range index of the field.
+    ///
+    /// See `IntOptions::set_range_indexed`.
+    pub(crate) fn set_range_indexed(&mut self) {
+        self.range_indexed = true;
+    }
+
     /// Records a new value.
     ///
     /// The n-th value being recorded is implicitely
@@ -213,7 +266,7 @@ impl IntFastFieldWriter {
     /// instead.
     /// If the document has more than one value for the given field,
     /// only the first one is taken in account.
-    fn extract_val(&self, doc: &Document) -> u64 {
+    pub(crate) fn extract_val(&self, doc: &Document) -> u64 {
         match doc.get_first(self.field) {
             Some(v) => super::value_to_u64(v),
             None => self.val_if_missing,
@@ -242,6 +295,16 @@ impl IntFastFieldWriter {
             single_field_serializer.add_val(val)?;
         }
 
-        single_field_serializer.close_field()
+        single_field_serializer.close_field()?;
+
+        if self.range_indexed {
+            let mut cursor = self.vals.as_slice();
+            let mut vals = Vec::with_capacity(self.val_count);
+            while let Ok(VInt(val)) = VInt::deserialize(&mut cursor) {
+                vals.push((val, vals.len() as DocId));
+            }
+            serializer.write_range_index(self.field, vals)?;
+        }
+        Ok(())
     }
 }
@@ -26,3 +26,73 @@ pub use self::serializer::FieldNormsSerializer;
 pub use self::writer::FieldNormsWriter;
 
 use self::code::{fieldnorm_to_id, id_to_fieldnorm};
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::TopDocs;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::{Index, Term};
+    use futures::Future;
+
+    fn text_options(exact_fieldnorms: bool) -> TextOptions {
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqs)
+                .set_exact_fieldnorms(exact_fieldnorms),
+        )
+    }
+
+    #[test]
+    fn test_exact_fieldnorms() {
+        let mut schema_builder = Schema::builder();
+        let exact_field = schema_builder.add_text_field("exact", text_options(true));
+        let quantized_field = schema_builder.add_text_field("quantized", text_options(false));
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for &num_tokens in &[100usize, 101usize] {
+            let text = vec!["a"; num_tokens].join(" ") + " b";
+            index_writer.add_document(doc!(exact_field => text.clone(), quantized_field => text));
+            index_writer.commit().unwrap();
+        }
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        for merge in &[false, true] {
+            if *merge {
+                index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+            }
+            let searcher = index.reader().unwrap().searcher();
+            let mut fieldnorms = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let exact_reader = segment_reader.get_fieldnorms_reader(exact_field);
+                let quantized_reader = segment_reader.get_fieldnorms_reader(quantized_field);
+                assert!(exact_reader.has_exact_fieldnorms());
+                assert!(!quantized_reader.has_exact_fieldnorms());
+                for doc in segment_reader.doc_ids_alive() {
+                    assert_eq!(
+                        exact_reader.fieldnorm_id(doc),
+                        quantized_reader.fieldnorm_id(doc)
+                    );
+                    assert!(quantized_reader.exact_fieldnorm(doc).is_none());
+                    fieldnorms.push(exact_reader.fieldnorm(doc));
+                }
+            }
+            assert_eq!(fieldnorms, vec![101, 102]);
+            let scores = |field| {
+                let query = TermQuery::new(
+                    Term::from_field_text(field, "b"),
+                    IndexRecordOption::WithFreqs,
+                );
+                let mut top_docs = searcher.search(&query, &TopDocs::with_limit(2)).unwrap();
+                top_docs.sort_by_key(|&(_, doc_address)| doc_address);
+                top_docs
+                    .into_iter()
+                    .map(|(score, _)| score)
+                    .collect::<Vec<_>>()
+            };
+            let exact_scores = scores(exact_field);
+            assert!(exact_scores[0] > exact_scores[1]);
+            let quantized_scores = scores(quantized_field);
+            assert_eq!(quantized_scores[0], quantized_scores[1]);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use super::{fieldnorm_to_id, id_to_fieldnorm};
 use crate::directory::ReadOnlySource;
+use crate::fastfield::FastFieldReader;
 use crate::DocId;
 
 /// Reads the fieldnorm associated to a document.
@@ -19,30 +20,68 @@ use crate::DocId;
 /// Apart from compression, this scale also makes it possible to
 /// precompute computationally expensive functions of the fieldnorm
 /// in a very short array.
+///
+/// Fields declared with exact fieldnorms also keep the exact
+/// number of tokens of each document, in a `u32` column.
 pub struct FieldNormReader {
     data: ReadOnlySource,
+    exact_reader: Option<FastFieldReader<u64>>,
 }
 
 impl FieldNormReader {
     /// Opens a field norm reader given its data source.
     pub fn open(data: ReadOnlySource) -> Self {
-        FieldNormReader { data }
+        FieldNormReader {
+            data,
+            exact_reader: None,
+        }
+    }
+
+    /// Opens a field norm reader given its data source,
+    /// and the data source of the exact fieldnorms.
+    pub fn open_with_exact(data: ReadOnlySource, exact_data: ReadOnlySource) -> Self {
+        FieldNormReader {
+            data,
+            exact_reader: Some(FastFieldReader::open(exact_data)),
+        }
     }
 
     /// Returns the `fieldnorm` associated to a doc id.
     /// The fieldnorm is a value approximating the number
     /// of tokens in a given field of the `doc_id`.
     ///
-    /// It is imprecise, and always lower than the actual
-    /// number of tokens.
+    /// Unless the field has exact fieldnorms, it is imprecise,
+    /// and always lower than the actual number of tokens.
     ///
     /// The fieldnorm is effectively decoded from the
     /// `fieldnorm_id` by doing a simple table lookup.
     pub fn fieldnorm(&self, doc_id: DocId) -> u32 {
+        if let Some(fieldnorm) = self.exact_fieldnorm(doc_id) {
+            return fieldnorm;
+        }
         let fieldnorm_id = self.fieldnorm_id(doc_id);
         id_to_fieldnorm(fieldnorm_id)
     }
 
+    /// Returns the `fieldnorm_id`s of all of the documents.
+    pub(crate) fn fieldnorm_ids(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    /// Returns the exact number of tokens of the field for the given `doc_id`,
+    /// or `None` if the field does not have exact fieldnorms.
+    #[inline(always)]
+    pub fn exact_fieldnorm(&self, doc_id: DocId) -> Option<u32> {
+        self.exact_reader
+            .as_ref()
+            .map(|exact_reader| exact_reader.get(doc_id) as u32)
+    }
+
+    /// Returns true iff the field has exact fieldnorms.
+    pub fn has_exact_fieldnorms(&self) -> bool {
+        self.exact_reader.is_some()
+    }
+
     /// Returns the `fieldnorm_id` associated to a document.
     #[inline(always)]
     pub fn fieldnorm_id(&self, doc_id: DocId) -> u8 {
@@ -74,6 +113,7 @@ impl From<Vec<u32>> for FieldNormReader {
         let field_norms_data = ReadOnlySource::from(field_norms_id);
         FieldNormReader {
             data: field_norms_data,
+            exact_reader: None,
         }
     }
 }
@@ -1,5 +1,6 @@
 use crate::common::CompositeWrite;
 use crate::directory::WritePtr;
+use crate::fastfield::FastSingleFieldSerializer;
 use crate::schema::Field;
 use std::io;
 use std::io::Write;
@@ -26,6 +27,21 @@ impl FieldNormsSerializer {
         Ok(())
     }
 
+    /// Serialize the exact fieldnorms of the given field.
+    ///
+    /// They are encoded as a fast field, next to the
+    /// quantized fieldnorms of the field.
+    pub fn serialize_exact_field(&mut self, field: Field, fieldnorms: &[u32]) -> io::Result<()> {
+        let write = self.composite_write.for_field_with_idx(field, 1);
+        let max_fieldnorm = fieldnorms.iter().cloned().max().unwrap_or(0u32);
+        let mut fast_field_serializer =
+            FastSingleFieldSerializer::open(write, 0u64, u64::from(max_fieldnorm))?;
+        for &fieldnorm in fieldnorms {
+            fast_field_serializer.add_val(u64::from(fieldnorm))?;
+        }
+        fast_field_serializer.close_field()
+    }
+
     /// Clean up / flush / close
     pub fn close(self) -> io::Result<()> {
         self.composite_write.close()?;
@@ -3,6 +3,7 @@ use crate::DocId;
 use super::fieldnorm_to_id;
 use super::FieldNormsSerializer;
 use crate::schema::Field;
+use crate::schema::FieldType;
 use crate::schema::Schema;
 use std::io;
 
@@ -11,9 +12,12 @@ use std::io;
 ///
 /// `FieldNormsWriter` stores a Vec<u8> for each tracked field, using a
 /// byte per document per field.
+/// Fields with exact fieldnorms additionally get a Vec<u32>.
 pub struct FieldNormsWriter {
     fields: Vec<Field>,
     fieldnorms_buffer: Vec<Vec<u8>>,
+    exact_fields: Vec<Field>,
+    exact_fieldnorms_buffer: Vec<Vec<u32>>,
 }
 
 impl FieldNormsWriter {
@@ -29,6 +33,26 @@ impl FieldNormsWriter {
             .collect::<Vec<Field>>()
     }
 
+    /// Returns the fields that should have their exact fieldnorms
+    /// stored according to the given schema.
+    pub(crate) fn fields_with_exact_fieldnorm(schema: &Schema) -> Vec<Field> {
+        schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|&(_, field_entry)| match *field_entry.field_type() {
+                FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => {
+                    text_options
+                        .get_indexing_options()
+                        .map(|indexing_options| indexing_options.exact_fieldnorms())
+                        .unwrap_or(false)
+                }
+                _ => false,
+            })
+            .map(|(field, _)| Field(field as u32))
+            .collect::<Vec<Field>>()
+    }
+
     /// Initialize with state for tracking the field norm fields
     /// specified in the schema.
     pub fn for_schema(schema: &Schema) -> FieldNormsWriter {
@@ -42,6 +66,8 @@ impl FieldNormsWriter {
         FieldNormsWriter {
             fields,
             fieldnorms_buffer: (0..max_field).map(|_| Vec::new()).collect::<Vec<_>>(),
+            exact_fields: FieldNormsWriter::fields_with_exact_fieldnorm(schema),
+            exact_fieldnorms_buffer: (0..max_field).map(|_| Vec::new()).collect::<Vec<_>>(),
         }
     }
 
@@ -53,6 +79,9 @@ impl FieldNormsWriter {
         for &field in self.fields.iter() {
             self.fieldnorms_buffer[field.0 as usize].resize(max_doc as usize, 0u8);
         }
+        for &field in self.exact_fields.iter() {
+            self.exact_fieldnorms_buffer[field.0 as usize].resize(max_doc as usize, 0u32);
+        }
     }
 
     /// Set the fieldnorm byte for the given document for the given field.
@@ -72,6 +101,11 @@ impl FieldNormsWriter {
         // we fill intermediary `DocId` as  having a fieldnorm of 0.
         fieldnorm_buffer.resize(doc as usize + 1, 0u8);
         fieldnorm_buffer[doc as usize] = fieldnorm_to_id(fieldnorm);
+        if self.exact_fields.contains(&field) {
+            let exact_fieldnorm_buffer = &mut self.exact_fieldnorms_buffer[field.0 as usize];
+            exact_fieldnorm_buffer.resize(doc as usize + 1, 0u32);
+            exact_fieldnorm_buffer[doc as usize] = fieldnorm;
+        }
     }
 
     /// Serialize the seen fieldnorm values to the serializer for all fields.
@@ -80,6 +114,11 @@ impl FieldNormsWriter {
             let fieldnorm_values: &[u8] = &self.fieldnorms_buffer[field.0 as usize][..];
             fieldnorms_serializer.serialize_field(field, fieldnorm_values)?;
         }
+        for &field in self.exact_fields.iter() {
+            let exact_fieldnorm_values: &[u32] =
+                &self.exact_fieldnorms_buffer[field.0 as usize][..];
+            fieldnorms_serializer.serialize_exact_field(field, exact_fieldnorm_values)?;
+        }
         Ok(())
     }
 }
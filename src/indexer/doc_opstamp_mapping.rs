@@ -14,8 +14,8 @@ use crate::Opstamp;
 // The doc to opstamp mapping stores precisely an array
 // indexed by doc id and storing the opstamp of the document.
 //
-// This mapping is (for the moment) stricly increasing
-// because of the way document id are allocated.
+// This mapping is stricly increasing because of the way document
+// id are allocated, unless the index is sorted by a field.
 #[derive(Clone)]
 pub enum DocToOpstampMapping<'a> {
     WithMap(&'a [Opstamp]),
@@ -29,20 +29,17 @@ impl<'a> From<&'a [u64]> for DocToOpstampMapping<'a> {
 }
 
 impl<'a> DocToOpstampMapping<'a> {
-    /// Given an opstamp return the limit doc id L
-    /// such that all doc id D such that
-    // D >= L iff opstamp(D) >= than `target_opstamp`.
-    //
-    // The edge case opstamp = some doc opstamp is in practise
-    // never called.
-    pub fn compute_doc_limit(&self, target_opstamp: Opstamp) -> DocId {
+    /// Returns true iff the document was added before the
+    /// operation with the opstamp `target_opstamp`.
+    ///
+    /// A document sharing the opstamp of a delete operation is the
+    /// document added by an update: it is not affected by the delete.
+    pub fn is_added_before(&self, doc: DocId, target_opstamp: Opstamp) -> bool {
         match *self {
             DocToOpstampMapping::WithMap(ref doc_opstamps) => {
-                match doc_opstamps.binary_search(&target_opstamp) {
-                    Ok(doc_id) | Err(doc_id) => doc_id as DocId,
-                }
+                doc_opstamps[doc as usize] < target_opstamp
             }
-            DocToOpstampMapping::None => DocId::max_value(),
+            DocToOpstampMapping::None => true,
         }
     }
 }
@@ -55,40 +52,45 @@ mod tests {
     #[test]
     fn test_doc_to_opstamp_mapping_none() {
         let doc_to_opstamp_mapping = DocToOpstampMapping::None;
-        assert_eq!(
-            doc_to_opstamp_mapping.compute_doc_limit(1),
-            u32::max_value()
-        );
+        assert!(doc_to_opstamp_mapping.is_added_before(u32::MAX, 1));
     }
 
     #[test]
     fn test_doc_to_opstamp_mapping_complex() {
-        {
-            let doc_to_opstamp_mapping = DocToOpstampMapping::from(&[][..]);
-            assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(0u64), 0);
-            assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(2u64), 0);
-        }
         {
             let doc_to_opstamp_mapping = DocToOpstampMapping::from(&[1u64][..]);
-            assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(0u64), 0);
-            assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(2u64), 1);
+            assert!(!doc_to_opstamp_mapping.is_added_before(0, 0u64));
+            assert!(!doc_to_opstamp_mapping.is_added_before(0, 1u64));
+            assert!(doc_to_opstamp_mapping.is_added_before(0, 2u64));
         }
         {
             let doc_to_opstamp_mapping =
                 DocToOpstampMapping::from(&[1u64, 12u64, 17u64, 23u64][..]);
-            assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(0u64), 0);
+            let num_docs_added_before = |target_opstamp: u64| {
+                (0..4)
+                    .filter(|&doc| doc_to_opstamp_mapping.is_added_before(doc, target_opstamp))
+                    .count()
+            };
+            assert_eq!(num_docs_added_before(0u64), 0);
             for i in 2u64..13u64 {
-                assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(i), 1);
+                assert_eq!(num_docs_added_before(i), 1);
             }
             for i in 13u64..18u64 {
-                assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(i), 2);
+                assert_eq!(num_docs_added_before(i), 2);
             }
             for i in 18u64..24u64 {
-                assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(i), 3);
+                assert_eq!(num_docs_added_before(i), 3);
             }
             for i in 24u64..30u64 {
-                assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(i), 4);
+                assert_eq!(num_docs_added_before(i), 4);
             }
         }
+        {
+            // The documents of a sorted segment.
+            let doc_to_opstamp_mapping = DocToOpstampMapping::from(&[17u64, 1u64, 12u64][..]);
+            assert!(!doc_to_opstamp_mapping.is_added_before(0, 13u64));
+            assert!(doc_to_opstamp_mapping.is_added_before(1, 13u64));
+            assert!(doc_to_opstamp_mapping.is_added_before(2, 13u64));
+        }
     }
 }
@@ -1,7 +1,9 @@
 use super::operation::{AddOperation, UserOperation};
 use super::segment_updater::SegmentUpdater;
 use super::PreparedCommit;
+use crate::core::Executor;
 use crate::core::Index;
+use crate::core::Searcher;
 use crate::core::Segment;
 use crate::core::SegmentComponent;
 use crate::core::SegmentId;
@@ -11,18 +13,22 @@ use crate::directory::DirectoryLock;
 use crate::directory::TerminatingWrite;
 use crate::docset::DocSet;
 use crate::error::TantivyError;
+use crate::fastfield::value_to_u64;
 use crate::fastfield::write_delete_bitset;
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
-use crate::indexer::operation::DeleteOperation;
+use crate::indexer::operation::{DeleteOperation, DeleteTarget};
 use crate::indexer::stamper::Stamper;
 use crate::indexer::MergePolicy;
 use crate::indexer::SegmentEntry;
 use crate::indexer::SegmentWriter;
+use crate::query::Query;
 use crate::schema::Document;
 use crate::schema::IndexRecordOption;
 use crate::schema::Term;
+use crate::IndexReader;
 use crate::Opstamp;
+use crate::ReloadPolicy;
 use crate::Result;
 use bit_set::BitSet;
 use crossbeam::channel;
@@ -32,6 +38,7 @@ use smallvec::SmallVec;
 use std::mem;
 use std::ops::Range;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::thread;
 use std::thread::JoinHandle;
 
@@ -57,6 +64,9 @@ type OperationGroup = SmallVec<[AddOperation; 4]>;
 type OperationSender = channel::Sender<OperationGroup>;
 type OperationReceiver = channel::Receiver<OperationGroup>;
 
+// Opstamps of the add operations rejected by the indexing workers.
+type RejectionLog = Arc<Mutex<Vec<Opstamp>>>;
+
 /// `IndexWriter` is the user entry-point to add document to an index.
 ///
 /// It manages a small number of indexing thread, as well as a shared
@@ -87,6 +97,8 @@ pub struct IndexWriter {
 
     stamper: Stamper,
     committed_opstamp: Opstamp,
+
+    rejection_log: RejectionLog,
 }
 
 fn compute_deleted_bitset(
@@ -103,20 +115,30 @@ fn compute_deleted_bitset(
         }
 
         // A delete operation should only affect
-        // document that were inserted after it.
-        //
-        // Limit doc helps identify the first document
-        // that may be affected by the delete operation.
-        let limit_doc = doc_opstamps.compute_doc_limit(delete_op.opstamp);
-        let inverted_index = segment_reader.inverted_index(delete_op.term.field());
-        if let Some(mut docset) =
-            inverted_index.read_postings(&delete_op.term, IndexRecordOption::Basic)
-        {
-            while docset.advance() {
-                let deleted_doc = docset.doc();
-                if deleted_doc < limit_doc {
-                    delete_bitset.insert(deleted_doc as usize);
-                    might_have_changed = true;
+        // document that were inserted before it.
+        match delete_op.target {
+            DeleteTarget::Term(ref term) => {
+                let inverted_index = segment_reader.inverted_index(term.field());
+                if let Some(mut docset) =
+                    inverted_index.read_postings(term, IndexRecordOption::Basic)
+                {
+                    while docset.advance() {
+                        let deleted_doc = docset.doc();
+                        if doc_opstamps.is_added_before(deleted_doc, delete_op.opstamp) {
+                            delete_bitset.insert(deleted_doc as usize);
+                            might_have_changed = true;
+                        }
+                    }
+                }
+            }
+            DeleteTarget::Query(ref weight) => {
+                let mut scorer = weight.scorer(segment_reader)?;
+                while scorer.advance() {
+                    let deleted_doc = scorer.doc();
+                    if doc_opstamps.is_added_before(deleted_doc, delete_op.opstamp) {
+                        delete_bitset.insert(deleted_doc as usize);
+                        might_have_changed = true;
+                    }
                 }
             }
         }
@@ -182,14 +204,15 @@ fn index_documents(
     grouped_document_iterator: &mut dyn Iterator<Item = OperationGroup>,
     segment_updater: &mut SegmentUpdater,
     mut delete_cursor: DeleteCursor,
+    rejection_log: &RejectionLog,
 ) -> Result<bool> {
     let schema = segment.schema();
     let segment_id = segment.id();
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone(), &schema)?;
     for document_group in grouped_document_iterator {
-        for doc in document_group {
-            segment_writer.add_document(doc, &schema)?;
-        }
+        // A group of documents is never split across segments,
+        // so that its documents get contiguous doc ids.
+        segment_writer.add_document_group(document_group, &schema)?;
         let mem_usage = segment_writer.mem_usage();
         if mem_usage >= memory_budget - MARGIN_IN_BYTES {
             info!(
@@ -204,19 +227,34 @@ fn index_documents(
         return Ok(false);
     }
 
+    if !segment_writer.rejected_opstamps().is_empty() {
+        rejection_log
+            .lock()
+            .expect("Failed to acquire the rejection log lock.")
+            .extend_from_slice(segment_writer.rejected_opstamps());
+    }
+
     let num_docs = segment_writer.max_doc();
 
-    // this is ensured by the call to peek before starting
-    // the worker thread.
-    assert!(num_docs > 0);
+    // The call to peek before starting the worker thread ensures
+    // that there was at least one document, but it may have been rejected.
+    if num_docs == 0 {
+        return Ok(true);
+    }
 
     let doc_opstamps: Vec<Opstamp> = segment_writer.finalize()?;
     let segment_meta = segment.index().new_segment_meta(segment_id, num_docs);
 
-    let last_docstamp: Opstamp = *(doc_opstamps.last().unwrap());
+    // The documents of a sorted index are not in the order they were added.
+    let last_docstamp: Opstamp = *(doc_opstamps.iter().max().unwrap());
 
-    let delete_bitset_opt =
-        apply_deletes(&segment, &mut delete_cursor, &doc_opstamps, last_docstamp)?;
+    let segment_with_max_doc = segment.index().segment(segment_meta.clone());
+    let delete_bitset_opt = apply_deletes(
+        &segment_with_max_doc,
+        &mut delete_cursor,
+        &doc_opstamps,
+        last_docstamp,
+    )?;
 
     let segment_entry = SegmentEntry::new(segment_meta, delete_cursor, delete_bitset_opt);
     Ok(segment_updater.add_segment(segment_entry))
@@ -314,6 +352,8 @@ impl IndexWriter {
             stamper,
 
             worker_id: 0,
+
+            rejection_log: Arc::default(),
         };
         index_writer.start_workers()?;
         Ok(index_writer)
@@ -356,6 +396,11 @@ impl IndexWriter {
         self.segment_updater.add_segment(segment_entry);
     }
 
+    /// Accessor to the index.
+    pub(crate) fn index(&self) -> &Index {
+        &self.index
+    }
+
     /// Creates a new segment.
     ///
     /// This method is useful only for users trying to do complex
@@ -378,6 +423,7 @@ impl IndexWriter {
 
         let mem_budget = self.heap_size_in_bytes_per_thread;
         let index = self.index.clone();
+        let rejection_log = self.rejection_log.clone();
         let join_handle: JoinHandle<Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
             .spawn(move || {
@@ -411,6 +457,7 @@ impl IndexWriter {
                         &mut document_iterator,
                         &mut segment_updater,
                         delete_cursor.clone(),
+                        &rejection_log,
                     )?;
                 }
             })?;
@@ -594,6 +641,17 @@ impl IndexWriter {
         // committed segments.
         info!("Preparing commit");
 
+        self.flush_workers()?;
+
+        let commit_opstamp = self.stamper.stamp();
+        let prepared_commit = PreparedCommit::new(self, commit_opstamp);
+        info!("Prepared commit {}", commit_opstamp);
+        Ok(prepared_commit)
+    }
+
+    /// Waits for the indexing workers to write all of the documents
+    /// added so far as segments, and restarts them.
+    fn flush_workers(&mut self) -> Result<()> {
         // this will drop the current document channel
         // and recreate a new one.
         self.recreate_document_channel();
@@ -607,11 +665,47 @@ impl IndexWriter {
             indexing_worker_result?;
             self.add_indexing_worker()?;
         }
+        Ok(())
+    }
 
-        let commit_opstamp = self.stamper.stamp();
-        let prepared_commit = PreparedCommit::new(self, commit_opstamp);
-        info!("Prepared commit {}", commit_opstamp);
-        Ok(prepared_commit)
+    /// Flushes all of the pending changes, without committing them.
+    ///
+    /// A call to flush blocks.
+    /// After it returns, all of the documents added, and all of the
+    /// deletes issued, since the last commit are visible to the
+    /// readers created with [`.nrt_reader()`](#method.nrt_reader)
+    /// once they are reloaded.
+    ///
+    /// Unlike `.commit()`, flushing does not persist the changes:
+    /// the `meta.json` file is left untouched, and the changes
+    /// are lost in case of a rollback or a crash.
+    ///
+    /// Flush returns the opstamp of the last operation
+    /// that made it in the flush.
+    pub fn flush(&mut self) -> Result<Opstamp> {
+        info!("Flushing");
+        self.flush_workers()?;
+        let flush_opstamp = self.stamper.stamp();
+        self.segment_updater.flush(flush_opstamp)?;
+        info!("Flushed {}", flush_opstamp);
+        Ok(flush_opstamp)
+    }
+
+    /// Creates a near-real-time reader.
+    ///
+    /// Unlike the readers created with `Index::reader()`, which only
+    /// see committed segments, it searches all of the segments of the writer,
+    /// including the segments written by `.flush()` that have not been committed yet.
+    ///
+    /// The reader is reloaded manually, typically right after a call to `.flush()`
+    /// or `.commit()`. Once the writer has been dropped, it keeps
+    /// serving the segments it had loaded.
+    pub fn nrt_reader(&self) -> Result<IndexReader> {
+        self.index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .nrt_segment_updater(self.segment_updater.clone())
+            .try_into()
     }
 
     /// Commits all of the pending changes
@@ -632,6 +726,24 @@ impl IndexWriter {
         self.prepare_commit()?.commit()
     }
 
+    /// Returns the opstamps of the add operations rejected since the last call,
+    /// and clears them.
+    ///
+    /// An operation is rejected if one of its documents exceeds the `DocTokenLimit`
+    /// of the index, and its policy is `TokenLimitPolicy::Error`. None of the documents
+    /// of a rejected operation are indexed, while the other operations are not affected.
+    ///
+    /// The documents are only checked as they get indexed, so the rejections of
+    /// the operations of a commit are only known once `.commit()` or `.flush()` returns.
+    /// The rejections are cleared by a rollback.
+    pub fn take_rejected_opstamps(&self) -> Vec<Opstamp> {
+        let mut rejection_log = self
+            .rejection_log
+            .lock()
+            .expect("Failed to acquire the rejection log lock.");
+        mem::take(&mut *rejection_log)
+    }
+
     pub(crate) fn segment_updater(&self) -> &SegmentUpdater {
         &self.segment_updater
     }
@@ -646,11 +758,44 @@ impl IndexWriter {
     /// only after calling `commit()`.
     pub fn delete_term(&self, term: Term) -> Opstamp {
         let opstamp = self.stamper.stamp();
-        let delete_operation = DeleteOperation { opstamp, term };
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: DeleteTarget::Term(term),
+        };
         self.delete_queue.push(delete_operation);
         opstamp
     }
 
+    /// Delete all documents matching a given query.
+    ///
+    /// The query is evaluated, with scoring disabled, against the
+    /// documents of each segment when the delete is applied.
+    /// Like `delete_term`, it only affects documents that were added
+    /// before the delete operation.
+    ///
+    /// The deletion itself will be visible only after calling `commit()`.
+    ///
+    /// # Errors
+    /// Returns an error if the query cannot be turned into a `Weight`.
+    pub fn delete_query(&self, query: Box<dyn Query>) -> Result<Opstamp> {
+        // Scoring is disabled, so the weight does not depend on the
+        // statistics of the segments it will be applied to.
+        let searcher = Searcher::new(
+            self.index.schema(),
+            self.index.clone(),
+            Vec::new(),
+            Arc::new(Executor::single_thread()),
+        );
+        let weight = query.weight(&searcher, false)?;
+        let opstamp = self.stamper.stamp();
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: DeleteTarget::Query(Arc::from(weight)),
+        };
+        self.delete_queue.push(delete_operation);
+        Ok(opstamp)
+    }
+
     /// Returns the opstamp of the last successful commit.
     ///
     /// This is, for instance, the opstamp the index will
@@ -682,6 +827,95 @@ impl IndexWriter {
         opstamp
     }
 
+    /// Replaces all documents containing a given term by a new document.
+    ///
+    /// The delete and the add share a single opstamp, and are therefore
+    /// committed together: readers either see the previous documents
+    /// or the new one. The new document is not affected by the delete.
+    ///
+    /// This is typically used with a unique id field, to update a document.
+    pub fn update_document(&self, term: Term, document: Document) -> Opstamp {
+        let opstamp = self.stamper.stamp();
+        // The delete has to be queued before the document reaches
+        // an indexing thread.
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: DeleteTarget::Term(term),
+        };
+        self.delete_queue.push(delete_operation);
+        let add_operation = AddOperation { opstamp, document };
+        let send_result = self.operation_sender.send(smallvec![add_operation]);
+        if let Err(e) = send_result {
+            panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
+        }
+        opstamp
+    }
+
+    /// Adds a block of documents: a parent document, preceded by its children.
+    ///
+    /// The documents of a block are given a single opstamp, and are
+    /// indexed with contiguous doc ids within the same segment.
+    /// Merges preserve the order of the documents, so that the parent
+    /// remains the last document of its block, and the children can be
+    /// searched along with their parent using a
+    /// [`ToParentBlockJoinQuery`](../query/struct.ToParentBlockJoinQuery.html)
+    /// or a [`ToChildBlockJoinQuery`](../query/struct.ToChildBlockJoinQuery.html).
+    ///
+    /// A block has to be deleted as a whole, e.g. by deleting a term
+    /// shared by all of its documents. Otherwise, the remaining children
+    /// would be associated to the next parent.
+    ///
+    /// If the index is sorted, all of the documents of the block must have the
+    /// same value for the sort field, so that merges keep them together.
+    /// Otherwise, an `InvalidArgument` error is returned, and the block is not added.
+    ///
+    /// If the indexing pipeline is full, this call may block.
+    pub fn add_document_block(&self, documents: Vec<Document>) -> Result<Opstamp> {
+        self.check_block_sort_value(&documents)?;
+        let opstamp = self.stamper.stamp();
+        let add_operations: OperationGroup = documents
+            .into_iter()
+            .map(|document| AddOperation { opstamp, document })
+            .collect();
+        if add_operations.is_empty() {
+            return Ok(opstamp);
+        }
+        let send_result = self.operation_sender.send(add_operations);
+        if let Err(e) = send_result {
+            panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
+        }
+        Ok(opstamp)
+    }
+
+    /// Returns an `InvalidArgument` error if the index is sorted, and the documents
+    /// of the block do not all have the same value for the sort field.
+    ///
+    /// Documents without a value get the default value of the fast field,
+    /// as in the `SegmentWriter`.
+    fn check_block_sort_value(&self, documents: &[Document]) -> Result<()> {
+        let sort_field = if let Some((sort_field, _)) = self.index.sort_by_field() {
+            sort_field
+        } else {
+            return Ok(());
+        };
+        let mut sort_values = documents.iter().map(|document| {
+            document
+                .get_first(sort_field)
+                .map(value_to_u64)
+                .unwrap_or(0u64)
+        });
+        if let Some(first_sort_value) = sort_values.next() {
+            if sort_values.any(|sort_value| sort_value != first_sort_value) {
+                return Err(TantivyError::InvalidArgument(
+                    "The documents of a block must have the same value for the sort \
+                     field, so that merges keep them together."
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Gets a range of stamps from the stamper and "pops" the last stamp
     /// from the range returning a tuple of the last optstamp and the popped
     /// range.
@@ -727,13 +961,25 @@ impl IndexWriter {
         for (user_op, opstamp) in user_operations.into_iter().zip(stamps) {
             match user_op {
                 UserOperation::Delete(term) => {
-                    let delete_operation = DeleteOperation { opstamp, term };
+                    let delete_operation = DeleteOperation {
+                        opstamp,
+                        target: DeleteTarget::Term(term),
+                    };
                     self.delete_queue.push(delete_operation);
                 }
                 UserOperation::Add(document) => {
                     let add_operation = AddOperation { opstamp, document };
                     adds.push(add_operation);
                 }
+                UserOperation::Update(term, document) => {
+                    let delete_operation = DeleteOperation {
+                        opstamp,
+                        target: DeleteTarget::Term(term),
+                    };
+                    self.delete_queue.push(delete_operation);
+                    let add_operation = AddOperation { opstamp, document };
+                    adds.push(add_operation);
+                }
             }
         }
         let send_result = self.operation_sender.send(adds);
@@ -749,15 +995,16 @@ impl IndexWriter {
 mod tests {
 
     use super::super::operation::UserOperation;
-    use crate::collector::TopDocs;
+    use crate::collector::{Count, TopDocs};
     use crate::directory::error::LockError;
     use crate::error::*;
     use crate::indexer::NoMergePolicy;
-    use crate::query::TermQuery;
+    use crate::query::{RangeQuery, TermQuery};
     use crate::schema::{self, IndexRecordOption};
     use crate::Index;
     use crate::ReloadPolicy;
     use crate::Term;
+    use futures::Future;
 
     #[test]
     fn test_operations_group() {
@@ -823,6 +1070,92 @@ mod tests {
         assert_eq!(b_docs.len(), 0);
     }
 
+    #[test]
+    fn test_delete_query() {
+        let mut schema_builder = schema::Schema::builder();
+        let year_field = schema_builder.add_u64_field("year", schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for year in 1990u64..2010u64 {
+            index_writer.add_document(doc!(year_field => year));
+        }
+        index_writer.commit().unwrap();
+        for year in 1995u64..2000u64 {
+            index_writer.add_document(doc!(year_field => year));
+        }
+        let old_query = RangeQuery::new_u64(year_field, 0..2000);
+        index_writer.delete_query(Box::new(old_query)).unwrap();
+        // added after the delete, this document must be kept.
+        index_writer.add_document(doc!(year_field => 1980u64));
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 11);
+        let old_docs = searcher
+            .search(&RangeQuery::new_u64(year_field, 0..2000), &Count)
+            .unwrap();
+        assert_eq!(old_docs, 1);
+
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), 11);
+    }
+
+    #[test]
+    fn test_update_document() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", schema::STRING);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(id_field => "1", text_field => "old"));
+        index_writer.add_document(doc!(id_field => "2", text_field => "old"));
+        index_writer.commit().unwrap();
+        index_writer.update_document(
+            Term::from_field_text(id_field, "1"),
+            doc!(id_field => "1", text_field => "new"),
+        );
+        index_writer.run(vec![UserOperation::Update(
+            Term::from_field_text(id_field, "2"),
+            doc!(id_field => "2", text_field => "new"),
+        )]);
+        index_writer.update_document(
+            Term::from_field_text(id_field, "3"),
+            doc!(id_field => "3", text_field => "new"),
+        );
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        let count = |text: &str| {
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(count("old"), 0);
+        assert_eq!(count("new"), 3);
+    }
+
     #[test]
     fn test_empty_operations_group() {
         let schema_builder = schema::Schema::builder();
@@ -1179,4 +1512,54 @@ mod tests {
         assert!(clear_again.is_ok());
         assert!(commit_again.is_ok());
     }
+
+    #[test]
+    fn test_nrt_reader() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.add_document(doc!(text_field => "b"));
+        index_writer.commit().unwrap();
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let nrt_reader = index_writer.nrt_reader().unwrap();
+        let term_a = Term::from_field_text(text_field, "a");
+        let term_b = Term::from_field_text(text_field, "b");
+        assert_eq!(nrt_reader.searcher().num_docs(), 2);
+
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.delete_term(term_b.clone());
+        index_writer.flush().unwrap();
+        nrt_reader.reload().unwrap();
+        reader.reload().unwrap();
+        {
+            let nrt_searcher = nrt_reader.searcher();
+            assert_eq!(nrt_searcher.num_docs(), 2);
+            assert_eq!(nrt_searcher.doc_freq(&term_a), 2);
+            let query = TermQuery::new(term_b.clone(), IndexRecordOption::Basic);
+            assert_eq!(nrt_searcher.search(&query, &Count).unwrap(), 0);
+        }
+        // The flushed changes are not committed.
+        assert_eq!(reader.searcher().num_docs(), 2);
+        assert_eq!(reader.searcher().doc_freq(&term_a), 1);
+
+        index_writer.rollback().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(reader.searcher().num_docs(), 2);
+
+        let nrt_reader = index_writer.nrt_reader().unwrap();
+        assert_eq!(nrt_reader.searcher().num_docs(), 2);
+        index_writer.add_document(doc!(text_field => "c"));
+        index_writer.flush().unwrap();
+        index_writer.commit().unwrap();
+        nrt_reader.reload().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(nrt_reader.searcher().num_docs(), 3);
+        assert_eq!(reader.searcher().num_docs(), 3);
+    }
 }
@@ -0,0 +1,177 @@
+use crate::common;
+use crate::schema::Field;
+use crate::schema::Term;
+use crate::schema::{
+    JSON_F64_CODE, JSON_I64_CODE, JSON_PATH_SEGMENT_SEP, JSON_TEXT_CODE, JSON_U64_CODE,
+};
+use serde_json::Map as JsonObject;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+
+/// Flattens the json objects of a document.
+///
+/// The leaves of the objects are grouped by path. Text (and boolean) leaves
+/// are kept apart so that they can be tokenized, while numeric leaves
+/// are directly converted into terms.
+///
+/// The items of an array are handled as if they were found at
+/// the path of the array itself, and `null` values are ignored.
+pub(crate) struct JsonValues<'a> {
+    field: Field,
+    path: Vec<u8>,
+    texts: BTreeMap<Vec<u8>, Vec<&'a str>>,
+    numeric_terms: Vec<Term>,
+}
+
+impl<'a> JsonValues<'a> {
+    pub fn new(field: Field) -> JsonValues<'a> {
+        JsonValues {
+            field,
+            path: Vec::new(),
+            texts: BTreeMap::new(),
+            numeric_terms: Vec::new(),
+        }
+    }
+
+    /// Adds all of the leaves of a json object.
+    pub fn add_object(&mut self, json_object: &'a JsonObject<String, JsonValue>) {
+        for (key, value) in json_object {
+            let path_len = self.path.len();
+            if path_len > 0 {
+                self.path.push(JSON_PATH_SEGMENT_SEP);
+            }
+            self.path.extend(key.as_bytes());
+            self.add_value(value);
+            self.path.truncate(path_len);
+        }
+    }
+
+    fn add_value(&mut self, value: &'a JsonValue) {
+        match *value {
+            JsonValue::Null => {}
+            JsonValue::Bool(val) => self.add_text(if val { "true" } else { "false" }),
+            JsonValue::String(ref text) => self.add_text(text),
+            JsonValue::Number(ref number) => {
+                let (type_code, val) = if let Some(val) = number.as_i64() {
+                    (JSON_I64_CODE, common::i64_to_u64(val))
+                } else if let Some(val) = number.as_u64() {
+                    (JSON_U64_CODE, val)
+                } else {
+                    let val = number.as_f64().unwrap_or_default();
+                    (JSON_F64_CODE, common::f64_to_u64(val))
+                };
+                let mut term = Term::for_json_path(self.field, &self.path, type_code);
+                term.append_bytes(&val.to_be_bytes());
+                self.numeric_terms.push(term);
+            }
+            JsonValue::Array(ref values) => {
+                for value in values {
+                    self.add_value(value);
+                }
+            }
+            JsonValue::Object(ref json_object) => self.add_object(json_object),
+        }
+    }
+
+    fn add_text(&mut self, text: &'a str) {
+        self.texts.entry(self.path.clone()).or_default().push(text);
+    }
+
+    /// Returns the terms of the numeric leaves.
+    pub fn numeric_terms(&self) -> &[Term] {
+        &self.numeric_terms
+    }
+
+    /// Returns, for each path, the prefix of the terms of its text leaves
+    /// and the text leaves themselves.
+    pub fn texts(&self) -> impl Iterator<Item = (Term, &[&'a str])> + '_ {
+        let field = self.field;
+        self.texts.iter().map(move |(path, texts)| {
+            (Term::for_json_path(field, path, JSON_TEXT_CODE), &texts[..])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::{QueryParser, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Term, STORED, TEXT};
+    use crate::Index;
+
+    #[test]
+    fn test_json_field() {
+        let mut schema_builder = Schema::builder();
+        let attrs = schema_builder.add_json_field("attrs", TEXT | STORED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let doc_json = r#"{"attrs": {
+            "color": "Dark red",
+            "size": {"eu": 42, "us": 9.5},
+            "tags": ["new", "sale", null],
+            "available": true
+        }}"#;
+        index_writer.add_document(schema.parse_document(doc_json).unwrap());
+        index_writer.add_document(
+            schema
+                .parse_document(r#"{"attrs": {"color": "red", "weight": 18446744073709551615}}"#)
+                .unwrap(),
+        );
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count_term = |term: Term| {
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(
+            count_term(Term::from_field_json_text(attrs, "color", "red")),
+            2
+        );
+        assert_eq!(
+            count_term(Term::from_field_json_text(attrs, "color", "dark")),
+            1
+        );
+        assert_eq!(
+            count_term(Term::from_field_json_text(attrs, "tags", "sale")),
+            1
+        );
+        assert_eq!(
+            count_term(Term::from_field_json_text(attrs, "available", "true")),
+            1
+        );
+        assert_eq!(
+            count_term(Term::from_field_json_i64(attrs, "size.eu", 42)),
+            1
+        );
+        assert_eq!(
+            count_term(Term::from_field_json_f64(attrs, "size.us", 9.5)),
+            1
+        );
+        assert_eq!(
+            count_term(Term::from_field_json_u64(attrs, "weight", u64::MAX)),
+            1
+        );
+        assert_eq!(
+            count_term(Term::from_field_json_text(attrs, "size", "42")),
+            0
+        );
+
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let count_query = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count_query("attrs.color:\"dark red\""), 1);
+        assert_eq!(count_query("attrs.size.eu:42"), 1);
+        assert_eq!(count_query("attrs.size.us:9.5"), 1);
+        assert_eq!(count_query("attrs.color:42"), 0);
+
+        let doc = searcher.doc(crate::DocAddress(0, 0)).unwrap();
+        let stored_attrs = doc.get_first(attrs).and_then(|value| value.json_object());
+        assert_eq!(
+            stored_attrs.and_then(|json_object| json_object.get("color")),
+            Some(&serde_json::Value::from("Dark red"))
+        );
+    }
+}
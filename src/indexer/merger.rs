@@ -1,3 +1,4 @@
+use crate::collector::Order;
 use crate::common::MAX_DOC_LIMIT;
 use crate::core::Segment;
 use crate::core::SegmentReader;
@@ -7,25 +8,33 @@ use crate::fastfield::BytesFastFieldReader;
 use crate::fastfield::DeleteBitSet;
 use crate::fastfield::FastFieldReader;
 use crate::fastfield::FastFieldSerializer;
+use crate::fastfield::MultiValueBytesFastFieldReader;
 use crate::fastfield::MultiValueIntFastFieldReader;
 use crate::fieldnorm::FieldNormReader;
 use crate::fieldnorm::FieldNormsSerializer;
 use crate::fieldnorm::FieldNormsWriter;
 use crate::indexer::SegmentSerializer;
-use crate::postings::InvertedIndexSerializer;
+use crate::positions::{PositionLayout, PositionReader};
+use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
 use crate::postings::Postings;
+use crate::postings::{BlockSegmentPostings, SegmentPostings};
+use crate::postings::{FieldSerializer, InvertedIndexSerializer};
 use crate::schema::Cardinality;
 use crate::schema::FieldType;
 use crate::schema::{Field, Schema};
 use crate::store::StoreWriter;
 use crate::termdict::TermMerger;
 use crate::termdict::TermOrdinal;
+use crate::DocAddress;
 use crate::DocId;
 use crate::Result;
+use crate::SegmentLocalId;
 use crate::TantivyError;
 use itertools::Itertools;
 use std::cmp;
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::io;
 
 fn compute_total_num_tokens(readers: &[SegmentReader], field: Field) -> u64 {
     let mut total_tokens = 0u64;
@@ -36,8 +45,12 @@ fn compute_total_num_tokens(readers: &[SegmentReader], field: Field) -> u64 {
             // using the fieldnorm
             let fieldnorms_reader = reader.get_fieldnorms_reader(field);
             for doc in reader.doc_ids_alive() {
-                let fieldnorm_id = fieldnorms_reader.fieldnorm_id(doc);
-                count[fieldnorm_id as usize] += 1;
+                if let Some(fieldnorm) = fieldnorms_reader.exact_fieldnorm(doc) {
+                    total_tokens += u64::from(fieldnorm);
+                } else {
+                    let fieldnorm_id = fieldnorms_reader.fieldnorm_id(doc);
+                    count[fieldnorm_id as usize] += 1;
+                }
             }
         } else {
             total_tokens += reader.inverted_index(field).total_num_tokens();
@@ -54,10 +67,99 @@ fn compute_total_num_tokens(readers: &[SegmentReader], field: Field) -> u64 {
             .sum::<u64>()
 }
 
+/// Maximum number of parts a stacked fast field column can be made of.
+///
+/// Beyond that, the columns are decoded and encoded again when merged,
+/// so that the number of parts does not grow with each merge.
+const MAX_STACKED_PARTS: usize = 32;
+
+/// Stacks fast field columns, without decoding them.
+///
+/// Each column is given with the number of values to copy,
+/// and the offset to add to these values.
+///
+/// Returns `false`, without writing anything, if the stacked column
+/// would have too many parts.
+fn stack_columns(
+    field: Field,
+    idx: usize,
+    columns: &[(&FastFieldReader<u64>, u64, u64)],
+    fast_field_serializer: &mut FastFieldSerializer,
+) -> Result<bool> {
+    let mut parts = Vec::new();
+    let mut min_value = u64::MAX;
+    let mut max_value = u64::MIN;
+    for &(column, num_vals, offset) in columns {
+        if num_vals == 0 {
+            continue;
+        }
+        min_value = cmp::min(min_value, column.min_value() + offset);
+        max_value = cmp::max(max_value, column.max_value() + offset);
+        parts.extend(column.column_parts(num_vals, offset));
+    }
+    if parts.len() > MAX_STACKED_PARTS {
+        return Ok(false);
+    }
+    if min_value > max_value {
+        min_value = 0;
+        max_value = 0;
+    }
+    fast_field_serializer
+        .stack_u64_fast_field_with_idx(field, min_value, max_value, idx, &parts)?;
+    Ok(true)
+}
+
 pub struct IndexMerger {
     schema: Schema,
     readers: Vec<SegmentReader>,
     max_doc: u32,
+    sort_by_field: Option<(Field, Order)>,
+    // Address of the document of the merged segments, for each doc id
+    // of the resulting segment.
+    doc_id_mapping: Vec<DocAddress>,
+}
+
+/// Lists the alive documents of the merged segments, in the order
+/// of the resulting segment.
+///
+/// Documents are stacked segment after segment, unless the index is sorted.
+/// The documents of each segment are then already sorted, so that their
+/// order is preserved.
+fn compute_doc_id_mapping(
+    readers: &[SegmentReader],
+    sort_by_field: Option<(Field, Order)>,
+) -> Vec<DocAddress> {
+    let mut doc_id_mapping: Vec<DocAddress> = readers
+        .iter()
+        .enumerate()
+        .flat_map(|(segment_ord, reader)| {
+            reader
+                .doc_ids_alive()
+                .map(move |doc| DocAddress(segment_ord as SegmentLocalId, doc))
+        })
+        .collect();
+    if let Some((field, order)) = sort_by_field {
+        let sort_readers: Vec<FastFieldReader<u64>> = readers
+            .iter()
+            .map(|reader| {
+                reader.fast_fields().u64_lenient(field).expect(
+                    "Failed to find a reader for the sort field. This is a tantivy bug and it should never happen.",
+                )
+            })
+            .collect();
+        let sort_value = |&DocAddress(segment_ord, doc): &DocAddress| {
+            sort_readers[segment_ord as usize].get(doc)
+        };
+        // The sort is stable: documents with the same sort value
+        // are kept in the order of the segments.
+        match order {
+            Order::Asc => doc_id_mapping.sort_by_key(sort_value),
+            Order::Desc => {
+                doc_id_mapping.sort_by_key(|doc_address| Reverse(sort_value(doc_address)))
+            }
+        }
+    }
+    doc_id_mapping
 }
 
 fn compute_min_max_val(
@@ -142,6 +244,112 @@ impl DeltaComputer {
     }
 }
 
+/// Postings of a term in one of the merged segments.
+enum MergedPostings {
+    /// The segment has no deletes: its postings are read block by block,
+    /// together with its positions as they are stored.
+    Stacked(BlockSegmentPostings, Option<PositionReader>),
+    /// The doc ids of the segment need to be remapped.
+    Remapped(SegmentPostings),
+}
+
+/// Appends the postings of a segment without deletes to the current term,
+/// shifting their doc ids by `doc_id_shift`.
+///
+/// Whenever possible, full blocks are copied in their encoded form,
+/// rather than decoded and encoded again.
+fn stack_postings(
+    field_serializer: &mut FieldSerializer<'_>,
+    mut block_postings: BlockSegmentPostings,
+    mut positions_reader_opt: Option<PositionReader>,
+    num_ints_per_position: usize,
+    doc_id_shift: DocId,
+    positions_buffer: &mut Vec<u32>,
+) -> io::Result<()> {
+    let mut read_positions = |term_freq: u32, positions_buffer: &mut Vec<u32>| {
+        positions_buffer.clear();
+        if let Some(positions_reader) = positions_reader_opt.as_mut() {
+            let num_ints = term_freq as usize * num_ints_per_position;
+            positions_buffer.resize(num_ints, 0u32);
+            positions_reader.read(&mut positions_buffer[..]);
+            positions_reader.skip(num_ints);
+        }
+    };
+    let mut doc_ids = Vec::with_capacity(COMPRESSION_BLOCK_SIZE);
+    loop {
+        let encoded_block_opt = block_postings.advance_bitpacked();
+        if encoded_block_opt.is_none() && !block_postings.advance() {
+            return Ok(());
+        }
+        doc_ids.clear();
+        doc_ids.extend(block_postings.docs().iter().map(|&doc| doc + doc_id_shift));
+        match encoded_block_opt {
+            Some(ref encoded_block)
+                if field_serializer.accepts_encoded_block(encoded_block, doc_id_shift) =>
+            {
+                let term_freqs = block_postings.freqs();
+                read_positions(term_freqs.iter().sum(), positions_buffer);
+                field_serializer.write_encoded_block(
+                    encoded_block,
+                    &doc_ids,
+                    term_freqs,
+                    positions_buffer,
+                )?;
+            }
+            _ => {
+                for (i, &doc) in doc_ids.iter().enumerate() {
+                    let term_freq = block_postings.freq(i);
+                    read_positions(term_freq, positions_buffer);
+                    field_serializer.write_doc_with_stored_positions(
+                        doc,
+                        term_freq,
+                        positions_buffer,
+                    )?;
+                }
+            }
+        }
+    }
+}
+
+/// Writes the current document of postings to the merged term,
+/// with the doc id `remapped_doc_id`.
+fn write_remapped_doc(
+    field_serializer: &mut FieldSerializer<'_>,
+    segment_postings: &mut SegmentPostings,
+    remapped_doc_id: DocId,
+    delta_computer: &mut DeltaComputer,
+    positions_buffer: &mut Vec<u32>,
+    offsets_buffer: &mut Vec<(u32, u32)>,
+    payloads_buffer: &mut Vec<u32>,
+) -> io::Result<()> {
+    let term_freq = segment_postings.term_freq();
+    segment_postings.positions(positions_buffer);
+    segment_postings.offsets(offsets_buffer);
+    segment_postings.payloads(payloads_buffer);
+    let delta_positions = delta_computer.compute_delta(positions_buffer);
+    field_serializer.write_doc_with_offsets_and_payloads(
+        remapped_doc_id,
+        term_freq,
+        delta_positions,
+        offsets_buffer,
+        payloads_buffer,
+    )
+}
+
+/// Advances the postings to their next document that is not deleted,
+/// and returns its doc id in the merged segment.
+fn advance_remapped(
+    segment_postings: &mut SegmentPostings,
+    old_to_new_doc_id: &[Option<DocId>],
+) -> Option<DocId> {
+    while segment_postings.advance() {
+        if let Some(remapped_doc_id) = old_to_new_doc_id[segment_postings.doc() as usize] {
+            return Some(remapped_doc_id);
+        }
+    }
+    None
+}
+
 impl IndexMerger {
     pub fn open(schema: Schema, segments: &[Segment]) -> Result<IndexMerger> {
         let mut readers = vec![];
@@ -161,27 +369,126 @@ impl IndexMerger {
             );
             return Err(TantivyError::InvalidArgument(err_msg));
         }
+        let sort_by_field = segments
+            .first()
+            .and_then(|segment| segment.index().sort_by_field());
+        let doc_id_mapping = compute_doc_id_mapping(&readers, sort_by_field);
         Ok(IndexMerger {
             schema,
             readers,
             max_doc,
+            sort_by_field,
+            doc_id_mapping,
         })
     }
 
+    // Builds the range index of a single-valued fast field from
+    // the values of the alive documents.
+    fn write_range_index(
+        &self,
+        field: Field,
+        fast_field_serializer: &mut FastFieldSerializer,
+    ) -> Result<()> {
+        let u64_readers: Vec<FastFieldReader<u64>> = self
+            .readers
+            .iter()
+            .map(|reader| {
+                reader.fast_fields().u64_lenient(field).expect(
+                    "Failed to find a reader for single fast field. This is a tantivy bug and it should never happen.",
+                )
+            })
+            .collect();
+        let vals = self
+            .doc_id_mapping
+            .iter()
+            .enumerate()
+            .map(|(new_doc, &DocAddress(segment_ord, doc))| {
+                (u64_readers[segment_ord as usize].get(doc), new_doc as DocId)
+            })
+            .collect();
+        fast_field_serializer.write_range_index(field, vals)?;
+        Ok(())
+    }
+
+    /// Returns true iff the index is not sorted and none of the merged segments
+    /// has deletes, so that their fast field columns can be stacked rather than
+    /// decoded and encoded again.
+    fn can_stack(&self) -> bool {
+        self.sort_by_field.is_none() && self.readers.iter().all(|reader| !reader.has_deletes())
+    }
+
+    /// Stacks the columns of the index of a multivalued or bytes fast field.
+    ///
+    /// The index of the last value of each segment but the last is dropped,
+    /// and the indexes of each segment are shifted by the number of values of
+    /// the previous segments.
+    fn stack_idx_columns(
+        &self,
+        field: Field,
+        idx_readers: &[&FastFieldReader<u64>],
+        fast_field_serializer: &mut FastFieldSerializer,
+    ) -> Result<bool> {
+        let mut columns = Vec::with_capacity(idx_readers.len());
+        let mut offset = 0u64;
+        for (segment_ord, (reader, &idx_reader)) in self.readers.iter().zip(idx_readers).enumerate()
+        {
+            let num_vals = if segment_ord + 1 == self.readers.len() {
+                u64::from(reader.max_doc()) + 1
+            } else {
+                u64::from(reader.max_doc())
+            };
+            columns.push((idx_reader, num_vals, offset));
+            offset += idx_reader.get(reader.max_doc());
+        }
+        stack_columns(field, 0, &columns, fast_field_serializer)
+    }
+
     fn write_fieldnorms(&self, fieldnorms_serializer: &mut FieldNormsSerializer) -> Result<()> {
         let fields = FieldNormsWriter::fields_with_fieldnorm(&self.schema);
         let mut fieldnorms_data = Vec::with_capacity(self.max_doc as usize);
         for field in fields {
             fieldnorms_data.clear();
-            for reader in &self.readers {
-                let fieldnorms_reader = reader.get_fieldnorms_reader(field);
-                for doc_id in reader.doc_ids_alive() {
-                    let fieldnorm_id = fieldnorms_reader.fieldnorm_id(doc_id);
+            if self.sort_by_field.is_some() {
+                let fieldnorms_readers: Vec<FieldNormReader> = self
+                    .readers
+                    .iter()
+                    .map(|reader| reader.get_fieldnorms_reader(field))
+                    .collect();
+                for &DocAddress(segment_ord, doc_id) in &self.doc_id_mapping {
+                    let fieldnorm_id =
+                        fieldnorms_readers[segment_ord as usize].fieldnorm_id(doc_id);
                     fieldnorms_data.push(fieldnorm_id);
                 }
+            } else {
+                for reader in &self.readers {
+                    let fieldnorms_reader = reader.get_fieldnorms_reader(field);
+                    if reader.has_deletes() {
+                        for doc_id in reader.doc_ids_alive() {
+                            let fieldnorm_id = fieldnorms_reader.fieldnorm_id(doc_id);
+                            fieldnorms_data.push(fieldnorm_id);
+                        }
+                    } else {
+                        fieldnorms_data.extend_from_slice(fieldnorms_reader.fieldnorm_ids());
+                    }
+                }
             }
             fieldnorms_serializer.serialize_field(field, &fieldnorms_data[..])?;
         }
+        let exact_fields = FieldNormsWriter::fields_with_exact_fieldnorm(&self.schema);
+        let mut exact_fieldnorms_data = Vec::with_capacity(self.max_doc as usize);
+        for field in exact_fields {
+            exact_fieldnorms_data.clear();
+            let fieldnorms_readers: Vec<FieldNormReader> = self
+                .readers
+                .iter()
+                .map(|reader| reader.get_fieldnorms_reader(field))
+                .collect();
+            for &DocAddress(segment_ord, doc_id) in &self.doc_id_mapping {
+                exact_fieldnorms_data
+                    .push(fieldnorms_readers[segment_ord as usize].fieldnorm(doc_id));
+            }
+            fieldnorms_serializer.serialize_exact_field(field, &exact_fieldnorms_data[..])?;
+        }
         Ok(())
     }
 
@@ -199,7 +506,7 @@ impl IndexMerger {
                         .remove(&field)
                         .expect("Logic Error in Tantivy (Please report). HierarchicalFact field should have required a\
                         `term_ordinal_mapping`.");
-                    self.write_hierarchical_facet_field(
+                    self.write_term_id_fast_field(
                         field,
                         &term_ordinal_mapping,
                         fast_field_serializer,
@@ -208,23 +515,45 @@ impl IndexMerger {
                 FieldType::U64(ref options)
                 | FieldType::I64(ref options)
                 | FieldType::F64(ref options)
-                | FieldType::Date(ref options) => match options.get_fastfield_cardinality() {
+                | FieldType::Date(ref options)
+                | FieldType::GeoPoint(ref options) => match options.get_fastfield_cardinality() {
                     Some(Cardinality::SingleValue) => {
                         self.write_single_fast_field(field, fast_field_serializer)?;
+                        if options.is_range_indexed() {
+                            self.write_range_index(field, fast_field_serializer)?;
+                        }
                     }
                     Some(Cardinality::MultiValues) => {
                         self.write_multi_fast_field(field, fast_field_serializer)?;
                     }
                     None => {}
                 },
-                FieldType::Str(_) => {
-                    // We don't handle str fast field for the moment
-                    // They can be implemented using what is done
-                    // for facets in the future.
-                }
-                FieldType::Bytes => {
-                    self.write_bytes_fast_field(field, fast_field_serializer)?;
+                FieldType::Str(ref options) if options.is_fast() => {
+                    match term_ord_mappings.remove(&field) {
+                        Some(term_ordinal_mapping) => {
+                            self.write_term_id_fast_field(
+                                field,
+                                &term_ordinal_mapping,
+                                fast_field_serializer,
+                            )?;
+                        }
+                        None => {
+                            // The field is not indexed, so that its term ordinal
+                            // lists are all empty.
+                            self.write_multi_fast_field(field, fast_field_serializer)?;
+                        }
+                    }
                 }
+                FieldType::Str(_) | FieldType::Json(_) => {}
+                FieldType::Bytes(ref options) => match options.get_fastfield_cardinality() {
+                    Some(Cardinality::SingleValue) => {
+                        self.write_bytes_fast_field(field, fast_field_serializer)?;
+                    }
+                    Some(Cardinality::MultiValues) => {
+                        self.write_multi_bytes_fast_field(field, fast_field_serializer)?;
+                    }
+                    None => {}
+                },
             }
         }
         Ok(())
@@ -236,6 +565,27 @@ impl IndexMerger {
         field: Field,
         fast_field_serializer: &mut FastFieldSerializer,
     ) -> Result<()> {
+        if self.can_stack() {
+            let u64_readers: Vec<FastFieldReader<u64>> = self
+                .readers
+                .iter()
+                .map(|reader| {
+                    reader.fast_fields().u64_lenient(field).expect(
+                        "Failed to find a reader for single fast field. This is a tantivy bug and it should never happen.",
+                    )
+                })
+                .collect();
+            let columns: Vec<(&FastFieldReader<u64>, u64, u64)> = self
+                .readers
+                .iter()
+                .zip(&u64_readers)
+                .map(|(reader, u64_reader)| (u64_reader, u64::from(reader.max_doc()), 0u64))
+                .collect();
+            if stack_columns(field, 0, &columns, fast_field_serializer)? {
+                return Ok(());
+            }
+        }
+
         let mut u64_readers = vec![];
         let mut min_value = u64::max_value();
         let mut max_value = u64::min_value();
@@ -251,10 +601,10 @@ impl IndexMerger {
                 // the segment has some non-deleted documents
                 min_value = cmp::min(min_value, seg_min_val);
                 max_value = cmp::max(max_value, seg_max_val);
-                u64_readers.push((reader.max_doc(), u64_reader, reader.delete_bitset()));
             } else {
                 // all documents have been deleted.
             }
+            u64_readers.push(u64_reader);
         }
 
         if min_value > max_value {
@@ -265,16 +615,9 @@ impl IndexMerger {
 
         let mut fast_single_field_serializer =
             fast_field_serializer.new_u64_fast_field(field, min_value, max_value)?;
-        for (max_doc, u64_reader, delete_bitset_opt) in u64_readers {
-            for doc_id in 0u32..max_doc {
-                let is_deleted = delete_bitset_opt
-                    .map(|delete_bitset| delete_bitset.is_deleted(doc_id))
-                    .unwrap_or(false);
-                if !is_deleted {
-                    let val = u64_reader.get(doc_id);
-                    fast_single_field_serializer.add_val(val)?;
-                }
-            }
+        for &DocAddress(segment_ord, doc_id) in &self.doc_id_mapping {
+            let val = u64_readers[segment_ord as usize].get(doc_id);
+            fast_single_field_serializer.add_val(val)?;
         }
 
         fast_single_field_serializer.close_field()?;
@@ -311,23 +654,31 @@ impl IndexMerger {
             u64s_readers.push(u64s_reader);
         }
 
+        if self.can_stack() {
+            let idx_readers: Vec<&FastFieldReader<u64>> = u64s_readers
+                .iter()
+                .map(MultiValueIntFastFieldReader::idx_reader)
+                .collect();
+            if self.stack_idx_columns(field, &idx_readers, fast_field_serializer)? {
+                return Ok(());
+            }
+        }
+
         // We can now create our `idx` serializer, and in a second pass,
         // can effectively push the different indexes.
         let mut serialize_idx =
             fast_field_serializer.new_u64_fast_field_with_idx(field, 0, total_num_vals, 0)?;
         let mut idx = 0;
-        for (segment_reader, u64s_reader) in self.readers.iter().zip(&u64s_readers) {
-            for doc in segment_reader.doc_ids_alive() {
-                serialize_idx.add_val(idx)?;
-                idx += u64s_reader.num_vals(doc) as u64;
-            }
+        for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
+            serialize_idx.add_val(idx)?;
+            idx += u64s_readers[segment_ord as usize].num_vals(doc) as u64;
         }
         serialize_idx.add_val(idx)?;
         serialize_idx.close_field()?;
         Ok(())
     }
 
-    fn write_hierarchical_facet_field(
+    fn write_term_id_fast_field(
         &self,
         field: Field,
         term_ordinal_mappings: &TermOrdinalMapping,
@@ -341,26 +692,31 @@ impl IndexMerger {
         self.write_fast_field_idx(field, fast_field_serializer)?;
 
         // We can now write the actual fast field values.
-        // In the case of hierarchical facets, they are actually term ordinals.
+        // In the case of hierarchical facets and fast text fields,
+        // they are actually term ordinals.
         let max_term_ord = term_ordinal_mappings.max_term_ord();
         {
             let mut serialize_vals =
                 fast_field_serializer.new_u64_fast_field_with_idx(field, 0u64, max_term_ord, 1)?;
             let mut vals = Vec::with_capacity(100);
-            for (segment_ord, segment_reader) in self.readers.iter().enumerate() {
+            let ff_readers: Vec<MultiValueIntFastFieldReader<u64>> = self
+                .readers
+                .iter()
+                .map(|segment_reader| {
+                    segment_reader
+                        .fast_fields()
+                        .u64s(field)
+                        .expect("Could not find multivalued u64 fast value reader.")
+                })
+                .collect();
+            // TODO optimize if no deletes
+            for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
                 let term_ordinal_mapping: &[TermOrdinal] =
-                    term_ordinal_mappings.get_segment(segment_ord);
-                let ff_reader: MultiValueIntFastFieldReader<u64> = segment_reader
-                    .fast_fields()
-                    .u64s(field)
-                    .expect("Could not find multivalued u64 fast value reader.");
-                // TODO optimize if no deletes
-                for doc in segment_reader.doc_ids_alive() {
-                    ff_reader.get_vals(doc, &mut vals);
-                    for &prev_term_ord in &vals {
-                        let new_term_ord = term_ordinal_mapping[prev_term_ord as usize];
-                        serialize_vals.add_val(new_term_ord)?;
-                    }
+                    term_ordinal_mappings.get_segment(segment_ord as usize);
+                ff_readers[segment_ord as usize].get_vals(doc, &mut vals);
+                for &prev_term_ord in &vals {
+                    let new_term_ord = term_ordinal_mapping[prev_term_ord as usize];
+                    serialize_vals.add_val(new_term_ord)?;
                 }
             }
             serialize_vals.close_field()?;
@@ -380,6 +736,26 @@ impl IndexMerger {
         // First we merge the idx fast field.
         self.write_fast_field_idx(field, fast_field_serializer)?;
 
+        if self.can_stack() {
+            let ff_readers: Vec<MultiValueIntFastFieldReader<u64>> = self
+                .readers
+                .iter()
+                .map(|reader| {
+                    reader.fast_fields().u64s_lenient(field).expect(
+                        "Failed to find multivalued fast field reader. This is a bug in \
+                         tantivy. Please report.",
+                    )
+                })
+                .collect();
+            let columns: Vec<(&FastFieldReader<u64>, u64, u64)> = ff_readers
+                .iter()
+                .map(|ff_reader| (ff_reader.vals_reader(), ff_reader.total_num_vals(), 0u64))
+                .collect();
+            if stack_columns(field, 1, &columns, fast_field_serializer)? {
+                return Ok(());
+            }
+        }
+
         let mut min_value = u64::max_value();
         let mut max_value = u64::min_value();
 
@@ -419,13 +795,11 @@ impl IndexMerger {
         {
             let mut serialize_vals = fast_field_serializer
                 .new_u64_fast_field_with_idx(field, min_value, max_value, 1)?;
-            for (reader, ff_reader) in self.readers.iter().zip(ff_readers) {
-                // TODO optimize if no deletes
-                for doc in reader.doc_ids_alive() {
-                    ff_reader.get_vals(doc, &mut vals);
-                    for &val in &vals {
-                        serialize_vals.add_val(val)?;
-                    }
+            // TODO optimize if no deletes
+            for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
+                ff_readers[segment_ord as usize].get_vals(doc, &mut vals);
+                for &val in &vals {
+                    serialize_vals.add_val(val)?;
                 }
             }
             serialize_vals.close_field()?;
@@ -458,29 +832,104 @@ impl IndexMerger {
             bytes_readers.push(bytes_reader);
         }
 
+        let idx_readers: Vec<&FastFieldReader<u64>> = bytes_readers
+            .iter()
+            .map(BytesFastFieldReader::idx_reader)
+            .collect();
+        if !(self.can_stack()
+            && self.stack_idx_columns(field, &idx_readers, fast_field_serializer)?)
         {
             // We can now create our `idx` serializer, and in a second pass,
             // can effectively push the different indexes.
             let mut serialize_idx =
                 fast_field_serializer.new_u64_fast_field_with_idx(field, 0, total_num_vals, 0)?;
             let mut idx = 0;
-            for (segment_reader, bytes_reader) in self.readers.iter().zip(&bytes_readers) {
-                for doc in segment_reader.doc_ids_alive() {
-                    serialize_idx.add_val(idx)?;
-                    idx += bytes_reader.get_bytes(doc).len() as u64;
-                }
+            for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
+                serialize_idx.add_val(idx)?;
+                idx += bytes_readers[segment_ord as usize].get_bytes(doc).len() as u64;
             }
             serialize_idx.add_val(idx)?;
             serialize_idx.close_field()?;
         }
 
         let mut serialize_vals = fast_field_serializer.new_bytes_fast_field_with_idx(field, 1)?;
-        for segment_reader in &self.readers {
-            let bytes_reader = segment_reader.fast_fields().bytes(field)
-                .expect("Failed to find bytes field in fast field reader. This is a bug in tantivy. Please report.");
-            // TODO: optimize if no deletes
-            for doc in segment_reader.doc_ids_alive() {
-                let val = bytes_reader.get_bytes(doc);
+        if self.sort_by_field.is_some() {
+            for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
+                let val = bytes_readers[segment_ord as usize].get_bytes(doc);
+                serialize_vals.write_all(val)?;
+            }
+            serialize_vals.flush()?;
+            return Ok(());
+        }
+        for (segment_reader, bytes_reader) in self.readers.iter().zip(&bytes_readers) {
+            if segment_reader.has_deletes() {
+                for doc in segment_reader.doc_ids_alive() {
+                    let val = bytes_reader.get_bytes(doc);
+                    serialize_vals.write_all(val)?;
+                }
+            } else {
+                // Without deletes, the values can be copied as is.
+                serialize_vals.write_all(bytes_reader.all_bytes())?;
+            }
+        }
+        serialize_vals.flush()?;
+        Ok(())
+    }
+
+    fn write_multi_bytes_fast_field(
+        &self,
+        field: Field,
+        fast_field_serializer: &mut FastFieldSerializer,
+    ) -> Result<()> {
+        let multi_bytes_readers: Vec<MultiValueBytesFastFieldReader> = self
+            .readers
+            .iter()
+            .map(|reader| {
+                reader.fast_fields().multi_bytes(field).expect(
+                    "Failed to find multi bytes fast field reader. This is a bug in tantivy, \
+                     please report.",
+                )
+            })
+            .collect();
+
+        let mut num_vals = 0u64;
+        let mut total_num_bytes = 0u64;
+        let mut vals = Vec::new();
+        for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
+            multi_bytes_readers[segment_ord as usize].get_vals(doc, &mut vals);
+            num_vals += vals.len() as u64;
+            total_num_bytes += vals.iter().map(|val| val.len() as u64).sum::<u64>();
+        }
+
+        // The index of the first value of each document.
+        let mut serialize_idx =
+            fast_field_serializer.new_u64_fast_field_with_idx(field, 0, num_vals, 0)?;
+        let mut val_ord = 0u64;
+        for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
+            serialize_idx.add_val(val_ord)?;
+            val_ord += multi_bytes_readers[segment_ord as usize].num_vals(doc) as u64;
+        }
+        serialize_idx.add_val(val_ord)?;
+        serialize_idx.close_field()?;
+
+        // The offset of the first byte of each value.
+        let mut serialize_vals_idx =
+            fast_field_serializer.new_u64_fast_field_with_idx(field, 0, total_num_bytes, 1)?;
+        let mut offset = 0u64;
+        for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
+            multi_bytes_readers[segment_ord as usize].get_vals(doc, &mut vals);
+            for val in &vals {
+                serialize_vals_idx.add_val(offset)?;
+                offset += val.len() as u64;
+            }
+        }
+        serialize_vals_idx.add_val(offset)?;
+        serialize_vals_idx.close_field()?;
+
+        let mut serialize_vals = fast_field_serializer.new_bytes_fast_field_with_idx(field, 2)?;
+        for &DocAddress(segment_ord, doc) in &self.doc_id_mapping {
+            multi_bytes_readers[segment_ord as usize].get_vals(doc, &mut vals);
+            for val in &vals {
                 serialize_vals.write_all(val)?;
             }
         }
@@ -495,6 +944,8 @@ impl IndexMerger {
         serializer: &mut InvertedIndexSerializer,
     ) -> Result<Option<TermOrdinalMapping>> {
         let mut positions_buffer: Vec<u32> = Vec::with_capacity(1_000);
+        let mut offsets_buffer: Vec<(u32, u32)> = Vec::new();
+        let mut payloads_buffer: Vec<u32> = Vec::new();
         let mut delta_computer = DeltaComputer::new();
         let field_readers = self
             .readers
@@ -511,29 +962,29 @@ impl IndexMerger {
             max_term_ords.push(terms.num_terms() as u64);
         }
 
-        let mut term_ord_mapping_opt = if *field_type == FieldType::HierarchicalFacet {
-            Some(TermOrdinalMapping::new(max_term_ords))
-        } else {
-            None
+        let mut term_ord_mapping_opt = match *field_type {
+            FieldType::HierarchicalFacet => Some(TermOrdinalMapping::new(max_term_ords)),
+            FieldType::Str(ref options) if options.is_fast() => {
+                Some(TermOrdinalMapping::new(max_term_ords))
+            }
+            _ => None,
         };
 
+        // Number of integers stored in the positions stream for each position.
+        let num_ints_per_position =
+            PositionLayout::for_field_type(field_type).num_ints_per_position();
+
         let mut merged_terms = TermMerger::new(field_term_streams);
-        let mut max_doc = 0;
 
         // map from segment doc ids to the resulting merged segment doc id.
-        let mut merged_doc_id_map: Vec<Vec<Option<DocId>>> = Vec::with_capacity(self.readers.len());
-
-        for reader in &self.readers {
-            let mut segment_local_map = Vec::with_capacity(reader.max_doc() as usize);
-            for doc_id in 0..reader.max_doc() {
-                if reader.is_deleted(doc_id) {
-                    segment_local_map.push(None);
-                } else {
-                    segment_local_map.push(Some(max_doc));
-                    max_doc += 1u32;
-                }
-            }
-            merged_doc_id_map.push(segment_local_map);
+        let mut merged_doc_id_map: Vec<Vec<Option<DocId>>> = self
+            .readers
+            .iter()
+            .map(|reader| vec![None; reader.max_doc() as usize])
+            .collect();
+        for (new_doc_id, &DocAddress(segment_ord, doc_id)) in self.doc_id_mapping.iter().enumerate()
+        {
+            merged_doc_id_map[segment_ord as usize][doc_id as usize] = Some(new_doc_id as DocId);
         }
 
         // The total number of tokens will only be exact when there has been no deletes.
@@ -551,6 +1002,8 @@ impl IndexMerger {
         // - Segment 2's doc ids become  [seg0.max_doc + seg1.max_doc,
         //                                seg0.max_doc + seg1.max_doc + seg2.max_doc]
         // ...
+        //
+        // If the index is sorted, the doc ids of the segments are interleaved instead.
         let mut field_serializer = serializer.new_field(indexed_field, total_num_tokens)?;
 
         let field_entry = self.schema.get_field_entry(indexed_field);
@@ -564,6 +1017,27 @@ impl IndexMerger {
         while merged_terms.advance() {
             let term_bytes: &[u8] = merged_terms.key();
 
+            // The document frequency of the merged term is at most the sum
+            // of the document frequencies in each of the segments.
+            //
+            // If it exceeds the threshold of the field, or if the postings were
+            // already pruned in one of the segments, the merged term is pruned.
+            // The deleted documents are then accounted for in its `doc_freq`.
+            let max_doc_freq: u32 = merged_terms
+                .current_kvs()
+                .iter()
+                .map(|heap_item| heap_item.streamer.value().doc_freq)
+                .sum();
+            if field_serializer.is_pruned(max_doc_freq) {
+                let to_term_ord = field_serializer.write_pruned_term(term_bytes, max_doc_freq)?;
+                if let Some(ref mut term_ord_mapping) = term_ord_mapping_opt {
+                    for (segment_ord, from_term_ord) in merged_terms.matching_segments() {
+                        term_ord_mapping.register_from_to(segment_ord, from_term_ord, to_term_ord);
+                    }
+                }
+                continue;
+            }
+
             // Let's compute the list of non-empty posting lists
             let segment_postings: Vec<_> = merged_terms
                 .current_kvs()
@@ -573,11 +1047,25 @@ impl IndexMerger {
                     let term_info = heap_item.streamer.value();
                     let segment_reader = &self.readers[heap_item.segment_ord];
                     let inverted_index = segment_reader.inverted_index(indexed_field);
+                    if !segment_reader.has_deletes() && self.sort_by_field.is_none() {
+                        // All of the documents are kept, and their doc ids are
+                        // simply shifted: the postings can be stacked.
+                        let block_postings = inverted_index
+                            .read_block_postings_from_terminfo(term_info, segment_postings_option);
+                        let positions_reader_opt = if segment_postings_option.has_positions() {
+                            Some(inverted_index.read_positions_from_terminfo(term_info))
+                        } else {
+                            None
+                        };
+                        let stacked_postings =
+                            MergedPostings::Stacked(block_postings, positions_reader_opt);
+                        return Some((segment_ord, stacked_postings));
+                    }
                     let mut segment_postings = inverted_index
                         .read_postings_from_terminfo(term_info, segment_postings_option);
                     while segment_postings.advance() {
                         if !segment_reader.is_deleted(segment_postings.doc()) {
-                            return Some((segment_ord, segment_postings));
+                            return Some((segment_ord, MergedPostings::Remapped(segment_postings)));
                         }
                     }
                     None
@@ -601,10 +1089,73 @@ impl IndexMerger {
                     }
                 }
 
+                if self.sort_by_field.is_some() {
+                    // The doc ids of each segment are still increasing once remapped:
+                    // the postings of the segments are merged by remapped doc id.
+                    let mut heads: Vec<(DocId, usize, SegmentPostings)> = segment_postings
+                        .into_iter()
+                        .flat_map(|(segment_ord, merged_postings)| match merged_postings {
+                            MergedPostings::Remapped(segment_postings) => {
+                                let old_to_new_doc_id = &merged_doc_id_map[segment_ord];
+                                old_to_new_doc_id[segment_postings.doc() as usize].map(
+                                    |remapped_doc_id| {
+                                        (remapped_doc_id, segment_ord, segment_postings)
+                                    },
+                                )
+                            }
+                            MergedPostings::Stacked(..) => None,
+                        })
+                        .collect();
+                    while let Some(head_ord) = heads
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|&(_, &(remapped_doc_id, _, _))| remapped_doc_id)
+                        .map(|(head_ord, _)| head_ord)
+                    {
+                        let head = &mut heads[head_ord];
+                        write_remapped_doc(
+                            &mut field_serializer,
+                            &mut head.2,
+                            head.0,
+                            &mut delta_computer,
+                            &mut positions_buffer,
+                            &mut offsets_buffer,
+                            &mut payloads_buffer,
+                        )?;
+                        match advance_remapped(&mut head.2, &merged_doc_id_map[head.1]) {
+                            Some(next_remapped_doc_id) => head.0 = next_remapped_doc_id,
+                            None => {
+                                heads.swap_remove(head_ord);
+                            }
+                        }
+                    }
+                    field_serializer.close_term()?;
+                    continue;
+                }
+
                 // We can now serialize this postings, by pushing each document to the
                 // postings serializer.
-                for (segment_ord, mut segment_postings) in segment_postings {
+                for (segment_ord, merged_postings) in segment_postings {
                     let old_to_new_doc_id = &merged_doc_id_map[segment_ord];
+                    let mut segment_postings = match merged_postings {
+                        MergedPostings::Stacked(block_postings, positions_reader_opt) => {
+                            let doc_id_shift = old_to_new_doc_id
+                                .first()
+                                .cloned()
+                                .and_then(|doc| doc)
+                                .unwrap_or(0u32);
+                            stack_postings(
+                                &mut field_serializer,
+                                block_postings,
+                                positions_reader_opt,
+                                num_ints_per_position,
+                                doc_id_shift,
+                                &mut positions_buffer,
+                            )?;
+                            continue;
+                        }
+                        MergedPostings::Remapped(segment_postings) => segment_postings,
+                    };
                     loop {
                         let doc = segment_postings.doc();
 
@@ -620,14 +1171,14 @@ impl IndexMerger {
                         if let Some(remapped_doc_id) = old_to_new_doc_id[doc as usize] {
                             // we make sure to only write the term iff
                             // there is at least one document.
-                            let term_freq = segment_postings.term_freq();
-                            segment_postings.positions(&mut positions_buffer);
-
-                            let delta_positions = delta_computer.compute_delta(&positions_buffer);
-                            field_serializer.write_doc(
+                            write_remapped_doc(
+                                &mut field_serializer,
+                                &mut segment_postings,
                                 remapped_doc_id,
-                                term_freq,
-                                delta_positions,
+                                &mut delta_computer,
+                                &mut positions_buffer,
+                                &mut offsets_buffer,
+                                &mut payloads_buffer,
                             )?;
                         }
                         if !segment_postings.advance() {
@@ -665,11 +1216,23 @@ impl IndexMerger {
     }
 
     fn write_storable_fields(&self, store_writer: &mut StoreWriter) -> Result<()> {
+        if self.sort_by_field.is_some() {
+            let store_readers: Vec<_> = self
+                .readers
+                .iter()
+                .map(SegmentReader::get_store_reader)
+                .collect();
+            for &DocAddress(segment_ord, doc_id) in &self.doc_id_mapping {
+                let doc = store_readers[segment_ord as usize].get(doc_id)?;
+                store_writer.store(&doc)?;
+            }
+            return Ok(());
+        }
         for reader in &self.readers {
             let store_reader = reader.get_store_reader();
             if reader.num_deleted_docs() > 0 {
-                for doc_id in reader.doc_ids_alive() {
-                    let doc = store_reader.get(doc_id)?;
+                for doc_res in store_reader.iter(reader.delete_bitset()) {
+                    let (_, doc) = doc_res?;
                     store_writer.store(&doc)?;
                 }
             } else {
@@ -695,8 +1258,12 @@ impl SerializableSegment for IndexMerger {
 mod tests {
     use crate::collector::tests::TEST_COLLECTOR_WITH_SCORE;
     use crate::collector::tests::{BytesFastFieldTestCollector, FastFieldTestCollector};
+    use crate::collector::Order;
     use crate::collector::{Count, FacetCollector};
     use crate::core::Index;
+    use crate::directory::RAMDirectory;
+    use crate::docset::{DocSet, SkipResult};
+    use crate::postings::Postings;
     use crate::query::AllQuery;
     use crate::query::BooleanQuery;
     use crate::query::TermQuery;
@@ -708,12 +1275,16 @@ mod tests {
     use crate::schema::IntOptions;
     use crate::schema::Term;
     use crate::schema::TextFieldIndexing;
+    use crate::schema::FAST;
     use crate::schema::INDEXED;
     use crate::DocAddress;
+    use crate::DocId;
+    use crate::IndexSettings;
     use crate::IndexWriter;
     use crate::Searcher;
     use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
     use futures::Future;
+    use std::collections::HashMap;
     use std::io::Cursor;
 
     #[test]
@@ -730,7 +1301,7 @@ mod tests {
         let date_field = schema_builder.add_date_field("date", INDEXED);
         let score_fieldtype = schema::IntOptions::default().set_fast(Cardinality::SingleValue);
         let score_field = schema_builder.add_u64_field("score", score_fieldtype);
-        let bytes_score_field = schema_builder.add_bytes_field("score_bytes");
+        let bytes_score_field = schema_builder.add_bytes_field("score_bytes", FAST);
         let index = Index::create_in_ram(schema_builder.build());
         let reader = index.reader().unwrap();
         let curr_time = chrono::Utc::now();
@@ -898,7 +1469,7 @@ mod tests {
         let text_field = schema_builder.add_text_field("text", text_fieldtype);
         let score_fieldtype = schema::IntOptions::default().set_fast(Cardinality::SingleValue);
         let score_field = schema_builder.add_u64_field("score", score_fieldtype);
-        let bytes_score_field = schema_builder.add_bytes_field("score_bytes");
+        let bytes_score_field = schema_builder.add_bytes_field("score_bytes", FAST);
         let index = Index::create_in_ram(schema_builder.build());
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
         let reader = index.reader().unwrap();
@@ -1351,6 +1922,329 @@ mod tests {
         assert_eq!(reader.searcher().num_docs(), 2);
     }
 
+    #[test]
+    fn test_merge_f64_and_date_fast_fields() {
+        let mut schema_builder = schema::Schema::builder();
+        let f64_field = schema_builder.add_f64_field("f64", schema::FAST);
+        let date_field = schema_builder.add_date_field("date", schema::FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let first_time = chrono::Utc::now();
+        let second_time = first_time + chrono::Duration::seconds(10);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(f64_field => -1.5f64, date_field => first_time));
+            index_writer.commit().expect("commit failed");
+            index_writer.add_document(doc!(f64_field => 2.25f64, date_field => second_time));
+            index_writer.commit().expect("commit failed");
+            let segment_ids = index
+                .searchable_segment_ids()
+                .expect("Searchable segments failed.");
+            index_writer
+                .merge(&segment_ids)
+                .expect("Failed to initiate merge")
+                .wait()
+                .expect("Merging failed");
+            index_writer.wait_merging_threads().unwrap();
+        }
+        let reader = index.reader().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        let f64_reader = fast_fields.f64(f64_field).unwrap();
+        assert_eq!(f64_reader.get(0), -1.5f64);
+        assert_eq!(f64_reader.get(1), 2.25f64);
+        let date_reader = fast_fields.i64(date_field).unwrap();
+        assert_eq!(date_reader.get(0), first_time.timestamp());
+        assert_eq!(date_reader.get(1), second_time.timestamp());
+    }
+
+    #[test]
+    fn test_merge_stacks_fast_fields_without_deletes() {
+        let mut schema_builder = schema::Schema::builder();
+        let int_field = schema_builder.add_u64_field("int", schema::FAST | INDEXED);
+        let multi_field = schema_builder.add_i64_field(
+            "multi",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let merge_all = |index_writer: &mut IndexWriter| {
+            let segment_ids = index
+                .searchable_segment_ids()
+                .expect("Searchable segments failed.");
+            index_writer
+                .merge(&segment_ids)
+                .expect("Failed to initiate merge")
+                .wait()
+                .expect("Merging failed");
+        };
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let mut expected: HashMap<u64, Vec<i64>> = HashMap::new();
+        for segment_ord in 0u64..4 {
+            for i in 0u64..100 {
+                let val = segment_ord * 1_000_000 + i * 3;
+                let multi_vals: Vec<i64> = (0..(i % 3) as i64).map(|j| j - val as i64).collect();
+                let mut doc = Document::default();
+                doc.add_u64(int_field, val);
+                for &multi_val in &multi_vals {
+                    doc.add_i64(multi_field, multi_val);
+                }
+                index_writer.add_document(doc);
+                expected.insert(val, multi_vals);
+            }
+            index_writer.commit().expect("commit failed");
+            if segment_ord == 1 {
+                // Stacked columns are stacked again in the next merge.
+                merge_all(&mut index_writer);
+            }
+        }
+        merge_all(&mut index_writer);
+        index_writer.wait_merging_threads().unwrap();
+
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        let int_reader = fast_fields.u64(int_field).unwrap();
+        assert!(int_reader.is_stacked());
+        assert_eq!(int_reader.min_value(), 0);
+        let multi_reader = fast_fields.i64s(multi_field).unwrap();
+        assert_eq!(searcher.segment_reader(0).max_doc(), 400);
+        let mut vals = Vec::new();
+        for doc in 0..400 {
+            // Segments are merged in the order of their ids.
+            let multi_vals = expected
+                .remove(&int_reader.get(doc))
+                .expect("Unexpected fast field value");
+            multi_reader.get_vals(doc, &mut vals);
+            assert_eq!(vals, multi_vals);
+        }
+    }
+
+    #[test]
+    fn test_merge_stacks_postings_without_deletes() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_options = schema::TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_offsets(true),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let id_field = schema_builder.add_u64_field("id", schema::FAST | INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let mut id = 0u64;
+        // The segments hold a whole number of blocks, so that whatever their order,
+        // all of their blocks but the first one are stacked.
+        for &num_docs in &[256u64, 384, 128] {
+            for _ in 0..num_docs {
+                let text = vec!["a"; 1 + (id % 3) as usize].join(" ");
+                index_writer.add_document(doc!(text_field => text, id_field => id));
+                id += 1;
+            }
+            index_writer.commit().expect("commit failed");
+        }
+        let segment_ids = index
+            .searchable_segment_ids()
+            .expect("Searchable segments failed.");
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let id_reader = segment_reader.fast_fields().u64(id_field).unwrap();
+        let inverted_index = segment_reader.inverted_index(text_field);
+        let term_a = Term::from_field_text(text_field, "a");
+        let mut postings = inverted_index
+            .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)
+            .unwrap();
+        let mut positions = Vec::new();
+        let mut offsets = Vec::new();
+        for doc in 0..768 {
+            assert!(postings.advance());
+            assert_eq!(postings.doc(), doc);
+            let term_freq = 1 + (id_reader.get(doc) % 3) as u32;
+            assert_eq!(postings.term_freq(), term_freq);
+            postings.positions(&mut positions);
+            assert_eq!(positions, (0..term_freq).collect::<Vec<u32>>());
+            postings.offsets(&mut offsets);
+            let expected_offsets: Vec<(u32, u32)> =
+                (0..term_freq).map(|i| (i * 2, i * 2 + 1)).collect();
+            assert_eq!(offsets, expected_offsets);
+        }
+        assert!(!postings.advance());
+
+        // The skip data is consistent with the stacked blocks.
+        let mut postings = inverted_index
+            .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)
+            .unwrap();
+        for &target in &[3u32, 300, 301, 555, 767] {
+            assert_eq!(postings.skip_next(target), SkipResult::Reached);
+            assert_eq!(postings.doc(), target);
+            let term_freq = 1 + (id_reader.get(target) % 3) as u32;
+            assert_eq!(postings.term_freq(), term_freq);
+            postings.positions(&mut positions);
+            assert_eq!(positions, (0..term_freq).collect::<Vec<u32>>());
+        }
+        let id_term = Term::from_field_u64(id_field, 600);
+        assert_eq!(searcher.doc_freq(&id_term), 1);
+    }
+
+    #[test]
+    fn test_merge_sorted_index() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_options = schema::TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default().set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let timestamp_field =
+            schema_builder.add_i64_field("timestamp", schema::FAST | INDEXED | schema::STORED);
+        let bytes_field = schema_builder.add_bytes_field("bytes", FAST);
+        let settings = IndexSettings::default().sort_by_field("timestamp", Order::Desc);
+        let index =
+            Index::create_with_settings(RAMDirectory::create(), schema_builder.build(), settings)
+                .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        // The timestamps of the segments are interleaved.
+        for segment_ord in 0i64..3 {
+            for i in 0i64..60 {
+                let timestamp = ((i * 7) % 60) * 3 + segment_ord;
+                let text = vec!["a"; 1 + (timestamp % 3) as usize].join(" ");
+                let mut timestamp_bytes = vec![];
+                timestamp_bytes.write_i64::<BigEndian>(timestamp).unwrap();
+                index_writer.add_document(doc!(
+                    text_field => format!("{} w{}", text, timestamp % 4),
+                    timestamp_field => timestamp,
+                    bytes_field => timestamp_bytes
+                ));
+            }
+            if segment_ord == 2 {
+                for timestamp in (0i64..180).step_by(10) {
+                    index_writer.delete_term(Term::from_field_i64(timestamp_field, timestamp));
+                }
+            }
+            index_writer.commit().expect("commit failed");
+        }
+        let segment_ids = index
+            .searchable_segment_ids()
+            .expect("Searchable segments failed.");
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let expected_timestamps: Vec<i64> = (0i64..180)
+            .rev()
+            .filter(|timestamp| timestamp % 10 != 0)
+            .collect();
+        assert_eq!(segment_reader.max_doc(), expected_timestamps.len() as u32);
+        assert_eq!(segment_reader.num_docs(), segment_reader.max_doc());
+        let timestamp_reader = segment_reader.fast_fields().i64(timestamp_field).unwrap();
+        let bytes_reader = segment_reader.fast_fields().bytes(bytes_field).unwrap();
+        let store_reader = segment_reader.get_store_reader();
+        for (doc, &timestamp) in expected_timestamps.iter().enumerate() {
+            let doc = doc as DocId;
+            assert_eq!(timestamp_reader.get(doc), timestamp);
+            let mut timestamp_bytes = Cursor::new(bytes_reader.get_bytes(doc));
+            assert_eq!(timestamp_bytes.read_i64::<BigEndian>().unwrap(), timestamp);
+            let stored_doc = store_reader.get(doc).unwrap();
+            assert_eq!(
+                stored_doc.get_first(timestamp_field).unwrap().i64_value(),
+                timestamp
+            );
+        }
+
+        let inverted_index = segment_reader.inverted_index(text_field);
+        let term_a = Term::from_field_text(text_field, "a");
+        let mut postings = inverted_index
+            .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)
+            .unwrap();
+        let mut positions = Vec::new();
+        for (doc, &timestamp) in expected_timestamps.iter().enumerate() {
+            assert!(postings.advance());
+            assert_eq!(postings.doc(), doc as DocId);
+            let term_freq = 1 + (timestamp % 3) as u32;
+            assert_eq!(postings.term_freq(), term_freq);
+            postings.positions(&mut positions);
+            assert_eq!(positions, (0..term_freq).collect::<Vec<u32>>());
+        }
+        assert!(!postings.advance());
+        let term_w1 = Term::from_field_text(text_field, "w1");
+        let mut postings = inverted_index
+            .read_postings(&term_w1, IndexRecordOption::Basic)
+            .unwrap();
+        for (doc, &timestamp) in expected_timestamps.iter().enumerate() {
+            if timestamp % 4 == 1 {
+                assert!(postings.advance());
+                assert_eq!(postings.doc(), doc as DocId);
+            }
+        }
+        assert!(!postings.advance());
+    }
+
+    #[test]
+    fn test_merge_sorted_index_keeps_blocks_together() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::STRING | schema::STORED);
+        let timestamp_field = schema_builder.add_i64_field("timestamp", FAST);
+        let settings = IndexSettings::default().sort_by_field("timestamp", Order::Asc);
+        let index =
+            Index::create_with_settings(RAMDirectory::create(), schema_builder.build(), settings)
+                .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a", timestamp_field => 5i64));
+        index_writer
+            .add_document_block(vec![
+                doc!(text_field => "child", timestamp_field => 5i64),
+                doc!(text_field => "parent", timestamp_field => 5i64),
+            ])
+            .unwrap();
+        index_writer.add_document(doc!(text_field => "b", timestamp_field => 7i64));
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(text_field => "c", timestamp_field => 5i64));
+        index_writer.add_document(doc!(text_field => "d", timestamp_field => 3i64));
+        index_writer.commit().unwrap();
+        let segment_ids = index
+            .searchable_segment_ids()
+            .expect("Searchable segments failed.");
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let store_reader = searcher.segment_reader(0).get_store_reader();
+        let texts: Vec<String> = (0..6)
+            .map(|doc| {
+                let stored_doc = store_reader.get(doc).unwrap();
+                stored_doc
+                    .get_first(text_field)
+                    .unwrap()
+                    .text()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        // Documents with the same value keep the order of their segments,
+        // so that the parent still directly follows its child.
+        assert_eq!(texts, vec!["d", "a", "child", "parent", "c", "b"]);
+    }
+
     #[test]
     fn test_merge_multivalued_int_fields_all_deleted() {
         let mut schema_builder = schema::Schema::builder();
@@ -1,12 +1,34 @@
+use crate::query::Weight;
 use crate::schema::Document;
 use crate::schema::Term;
 use crate::Opstamp;
+use std::fmt;
+use std::sync::Arc;
+
+/// Documents targeted by a delete operation.
+#[derive(Clone)]
+pub enum DeleteTarget {
+    /// All documents containing the term.
+    Term(Term),
+    /// All documents matching a query, given the weight of
+    /// the query with scoring disabled.
+    Query(Arc<dyn Weight>),
+}
+
+impl fmt::Debug for DeleteTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeleteTarget::Term(term) => write!(f, "Term({:?})", term),
+            DeleteTarget::Query(_) => write!(f, "Query"),
+        }
+    }
+}
 
 /// Timestamped Delete operation.
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Debug)]
 pub struct DeleteOperation {
     pub opstamp: Opstamp,
-    pub term: Term,
+    pub target: DeleteTarget,
 }
 
 /// Timestamped Add operation.
@@ -21,4 +43,9 @@ pub struct AddOperation {
 pub enum UserOperation {
     Add(Document),
     Delete(Term),
+    /// Deletes the documents containing the term, and adds the document.
+    ///
+    /// Both operations share the same opstamp: the document added by the
+    /// update is not affected by its delete.
+    Update(Term, Document),
 }
@@ -118,6 +118,23 @@ impl SegmentManager {
         }
     }
 
+    /// Replaces the segment entries with the given ones, typically after
+    /// their deletes have been advanced, without committing them.
+    ///
+    /// Entries of segments that are not registered anymore (e.g. segments
+    /// that were merged in the meantime) are ignored.
+    pub fn update_segment_entries(&self, segment_entries: Vec<SegmentEntry>) {
+        let mut registers_lock = self.write();
+        for segment_entry in segment_entries {
+            let segment_id = segment_entry.segment_id();
+            if registers_lock.committed.get(&segment_id).is_some() {
+                registers_lock.committed.add_segment_entry(segment_entry);
+            } else if registers_lock.uncommitted.get(&segment_id).is_some() {
+                registers_lock.uncommitted.add_segment_entry(segment_entry);
+            }
+        }
+    }
+
     /// Marks a list of segments as in merge.
     ///
     /// Returns an error if some segments are missing, or if
@@ -1,12 +1,15 @@
 use super::segment_manager::{get_mergeable_segments, SegmentManager};
 use crate::core::Index;
 use crate::core::IndexMeta;
+use crate::core::IndexSettings;
 use crate::core::Segment;
 use crate::core::SegmentId;
 use crate::core::SegmentMeta;
+use crate::core::SegmentMetaInventory;
 use crate::core::SerializableSegment;
 use crate::core::META_FILEPATH;
-use crate::directory::{Directory, DirectoryClone};
+use crate::core::{kept_meta_generations, list_meta_generations, meta_generation_filepath};
+use crate::directory::{Directory, ManagedDirectory};
 use crate::error::TantivyError;
 use crate::indexer::delete_queue::DeleteCursor;
 use crate::indexer::index_writer::advance_deletes;
@@ -27,7 +30,6 @@ use futures_cpupool::Builder as CpuPoolBuilder;
 use futures_cpupool::CpuFuture;
 use futures_cpupool::CpuPool;
 use serde_json;
-use std::borrow::BorrowMut;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::io::Write;
@@ -49,14 +51,13 @@ use std::thread::JoinHandle;
 /// and flushed.
 ///
 /// This method is not part of tantivy's public API
-pub fn save_new_metas(schema: Schema, directory: &mut dyn Directory) -> Result<()> {
+pub fn save_new_metas(
+    schema: Schema,
+    index_settings: IndexSettings,
+    directory: &mut ManagedDirectory,
+) -> Result<()> {
     save_metas(
-        &IndexMeta {
-            segments: Vec::new(),
-            schema,
-            opstamp: 0u64,
-            payload: None,
-        },
+        &IndexMeta::with_schema_and_settings(schema, index_settings),
         directory,
     )
 }
@@ -69,12 +70,20 @@ pub fn save_new_metas(schema: Schema, directory: &mut dyn Directory) -> Result<(
 /// - it success, and `meta.json` is written
 /// and flushed.
 ///
+/// The metas are first saved as a new generation `meta.json.<generation>`,
+/// which `meta.json` is then replaced with.
+///
 /// This method is not part of tantivy's public API
-fn save_metas(metas: &IndexMeta, directory: &mut dyn Directory) -> Result<()> {
+fn save_metas(metas: &IndexMeta, directory: &mut ManagedDirectory) -> Result<()> {
     info!("save metas");
     let mut buffer = serde_json::to_vec_pretty(metas)?;
     // Just adding a new line at the end of the buffer.
     writeln!(&mut buffer)?;
+    let generation = list_meta_generations(directory)
+        .last()
+        .map(|&last_generation| last_generation + 1)
+        .unwrap_or(0u64);
+    directory.atomic_write(&meta_generation_filepath(generation), &buffer[..])?;
     directory.atomic_write(&META_FILEPATH, &buffer[..])?;
     debug!("Saved metas {:?}", serde_json::to_string_pretty(&metas));
     Ok(())
@@ -256,13 +265,13 @@ impl SegmentUpdater {
             // Segment 1 from disk 1, Segment 1 from disk 2, etc.
             commited_segment_metas.sort_by_key(|segment_meta| -(segment_meta.max_doc() as i32));
             let index_meta = IndexMeta {
+                index_settings: index.settings().clone(),
                 segments: commited_segment_metas,
                 schema: index.schema(),
                 opstamp,
                 payload: commit_message,
             };
-            save_metas(&index_meta, directory.box_clone().borrow_mut())
-                .expect("Could not save metas.");
+            save_metas(&index_meta, &mut directory.clone()).expect("Could not save metas.");
             self.store_meta(&index_meta);
         }
     }
@@ -277,12 +286,36 @@ impl SegmentUpdater {
     ///
     /// This does not include lock files, or files that are obsolete
     /// but have not yet been deleted by the garbage collector.
+    ///
+    /// The last generations of the meta file and the generations pinned
+    /// by a `Searcher`, as well as the segments they reference,
+    /// are considered useful.
     fn list_files(&self) -> HashSet<PathBuf> {
         let mut files = HashSet::new();
         files.insert(META_FILEPATH.to_path_buf());
         for segment_meta in self.0.index.list_all_segment_metas() {
             files.extend(segment_meta.list_files());
         }
+        let directory = self.0.index.directory();
+        let num_generations_kept = self.0.index.num_meta_generations_kept();
+        let mut generations = kept_meta_generations(directory, num_generations_kept);
+        generations.extend(self.0.index.pinned_meta_generations());
+        generations.sort();
+        generations.dedup();
+        for generation in generations {
+            let generation_filepath = meta_generation_filepath(generation);
+            if let Ok(meta_data) = directory.atomic_read(&generation_filepath) {
+                let meta_string = String::from_utf8_lossy(&meta_data);
+                if let Ok(metas) =
+                    IndexMeta::deserialize(&meta_string, &SegmentMetaInventory::default())
+                {
+                    for segment_meta in metas.segments {
+                        files.extend(segment_meta.list_files());
+                    }
+                }
+            }
+            files.insert(generation_filepath);
+        }
         files
     }
 
@@ -307,6 +340,33 @@ impl SegmentUpdater {
         .wait()
     }
 
+    /// Applies the deletes up to `opstamp` to all of the segments, committed or not,
+    /// without committing them.
+    pub fn flush(&self, opstamp: Opstamp) -> Result<()> {
+        self.run_async(move |segment_updater| {
+            if segment_updater.is_alive() {
+                let segment_entries = segment_updater
+                    .purge_deletes(opstamp)
+                    .expect("Failed purge deletes");
+                segment_updater
+                    .0
+                    .segment_manager
+                    .update_segment_entries(segment_entries);
+            }
+        })
+        .wait()
+    }
+
+    /// Returns the metas of all of the segments, committed or not.
+    pub(crate) fn segment_metas(&self) -> Vec<SegmentMeta> {
+        self.0
+            .segment_manager
+            .segment_entries()
+            .iter()
+            .map(|segment_entry| segment_entry.meta().clone())
+            .collect()
+    }
+
     pub fn start_merge(&self, segment_ids: &[SegmentId]) -> Result<Receiver<SegmentMeta>> {
         let commit_opstamp = self.load_metas().opstamp;
         let merge_operation = MergeOperation::new(
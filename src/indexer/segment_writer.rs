@@ -1,24 +1,29 @@
 use super::operation::AddOperation;
-use crate::core::Segment;
+use crate::collector::Order;
+use crate::common;
 use crate::core::SerializableSegment;
+use crate::core::{DocTokenLimit, Segment, TokenLimitPolicy};
 use crate::fastfield::FastFieldsWriter;
 use crate::fieldnorm::FieldNormsWriter;
+use crate::indexer::json_values::JsonValues;
 use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::postings::compute_table_size;
 use crate::postings::MultiFieldPostingsWriter;
-use crate::schema::FieldEntry;
 use crate::schema::FieldType;
 use crate::schema::Schema;
 use crate::schema::Term;
 use crate::schema::Value;
+use crate::schema::{cell_code, NUM_CELL_LEVELS};
+use crate::schema::{Document, Field, FieldEntry, FieldValue};
 use crate::tokenizer::BoxedTokenizer;
 use crate::tokenizer::FacetTokenizer;
-use crate::tokenizer::{TokenStream, Tokenizer};
+use crate::tokenizer::{Token, TokenStream, Tokenizer};
 use crate::DocId;
 use crate::Opstamp;
 use crate::Result;
 use crate::TantivyError;
-use std::io;
+use std::cmp::Reverse;
+use std::mem;
 use std::str;
 
 /// Computes the initial size of the hash table.
@@ -49,7 +54,16 @@ pub struct SegmentWriter {
     fast_field_writers: FastFieldsWriter,
     fieldnorms_writer: FieldNormsWriter,
     doc_opstamps: Vec<Opstamp>,
+    rejected_opstamps: Vec<Opstamp>,
     tokenizers: Vec<Option<BoxedTokenizer>>,
+    doc_token_limit: Option<DocTokenLimit>,
+    sort_by_field: Option<(Field, Order)>,
+    schema: Schema,
+    // Blocks of documents of a sorted index, with their sort value.
+    // They are only indexed when the segment is finalized.
+    buffered_blocks: Vec<(u64, Vec<AddOperation>)>,
+    num_buffered_docs: u32,
+    buffered_mem_usage: usize,
 }
 
 impl SegmentWriter {
@@ -70,29 +84,44 @@ impl SegmentWriter {
         let table_num_bits = initial_table_size(memory_budget)?;
         let segment_serializer = SegmentSerializer::for_segment(&mut segment)?;
         let multifield_postings = MultiFieldPostingsWriter::new(schema, table_num_bits);
-        let tokenizers =
-            schema
-                .fields()
-                .iter()
-                .map(FieldEntry::field_type)
-                .map(|field_type| match *field_type {
-                    FieldType::Str(ref text_options) => text_options
+        let tokenizers = schema
+            .fields()
+            .iter()
+            .map(FieldEntry::field_type)
+            .map(|field_type| match *field_type {
+                FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => {
+                    text_options
                         .get_indexing_options()
                         .and_then(|text_index_option| {
                             let tokenizer_name = &text_index_option.tokenizer();
                             segment.index().tokenizers().get(tokenizer_name)
-                        }),
-                    _ => None,
-                })
-                .collect();
+                        })
+                }
+                _ => None,
+            })
+            .collect();
+        let mut fast_field_writers = FastFieldsWriter::from_schema(schema);
+        if let Some(boost_field) = segment.index().boost_field() {
+            // Documents without a boost should not see their score altered.
+            if let Some(boost_writer) = fast_field_writers.get_field_writer(boost_field) {
+                boost_writer.set_val_if_missing(common::f64_to_u64(1.0f64));
+            }
+        }
         Ok(SegmentWriter {
             max_doc: 0,
             multifield_postings,
             fieldnorms_writer: FieldNormsWriter::for_schema(schema),
             segment_serializer,
-            fast_field_writers: FastFieldsWriter::from_schema(schema),
+            fast_field_writers,
             doc_opstamps: Vec::with_capacity(1_000),
+            rejected_opstamps: Vec::new(),
             tokenizers,
+            doc_token_limit: segment.index().settings().doc_token_limit,
+            sort_by_field: segment.index().sort_by_field(),
+            schema: schema.clone(),
+            buffered_blocks: Vec::new(),
+            num_buffered_docs: 0,
+            buffered_mem_usage: 0,
         })
     }
 
@@ -100,7 +129,26 @@ impl SegmentWriter {
     ///
     /// Finalize consumes the `SegmentWriter`, so that it cannot
     /// be used afterwards.
+    ///
+    /// If the index is sorted, the buffered documents are indexed first,
+    /// in the order of the sort field.
     pub fn finalize(mut self) -> Result<Vec<u64>> {
+        if let Some((_, order)) = self.sort_by_field {
+            let mut buffered_blocks = mem::take(&mut self.buffered_blocks);
+            // The sort is stable: blocks with the same sort value
+            // are kept in the order they were added.
+            match order {
+                Order::Asc => buffered_blocks.sort_by_key(|&(sort_value, _)| sort_value),
+                Order::Desc => buffered_blocks.sort_by_key(|&(sort_value, _)| Reverse(sort_value)),
+            }
+            self.num_buffered_docs = 0;
+            let schema = self.schema.clone();
+            for (_, add_operations) in buffered_blocks {
+                for add_operation in add_operations {
+                    self.add_document(add_operation, &schema)?;
+                }
+            }
+        }
         self.fieldnorms_writer.fill_up_to_max_doc(self.max_doc);
         write(
             &self.multifield_postings,
@@ -111,16 +159,175 @@ impl SegmentWriter {
         Ok(self.doc_opstamps)
     }
 
+    /// Returns the opstamps of the operations rejected because their document
+    /// exceeds the `DocTokenLimit` of the index.
+    pub fn rejected_opstamps(&self) -> &[Opstamp] {
+        &self.rejected_opstamps[..]
+    }
+
     pub fn mem_usage(&self) -> usize {
-        self.multifield_postings.mem_usage()
+        self.multifield_postings.mem_usage() + self.buffered_mem_usage
+    }
+
+    /// Indexes a group of documents, which get contiguous doc ids.
+    ///
+    /// If the index is sorted, the documents are only buffered until the segment
+    /// is finalized, and sorted block by block. The documents of a block share
+    /// the same opstamp, and have the same sort value, as checked by
+    /// `IndexWriter::add_document_block`.
+    ///
+    /// If the policy of the `DocTokenLimit` of the index is `TokenLimitPolicy::Error`,
+    /// the operations with a document exceeding the limit are rejected before anything
+    /// gets indexed. The other documents of their block are rejected too, so that
+    /// the block stays atomic. See `.rejected_opstamps()`.
+    pub fn add_document_group<TAddOperations>(
+        &mut self,
+        add_operations: TAddOperations,
+        schema: &Schema,
+    ) -> Result<()>
+    where
+        TAddOperations: IntoIterator<Item = AddOperation>,
+    {
+        let add_operations = self.reject_over_token_limit(add_operations, schema);
+        let sort_field = if let Some((sort_field, _)) = self.sort_by_field {
+            sort_field
+        } else {
+            for add_operation in add_operations {
+                self.add_document(add_operation, schema)?;
+            }
+            return Ok(());
+        };
+        let sort_field_writer = self
+            .fast_field_writers
+            .get_field_writer(sort_field)
+            .expect("The sort field should be a single-valued fast field.");
+        let mut blocks: Vec<(u64, Vec<AddOperation>)> = Vec::new();
+        for add_operation in add_operations {
+            let sort_value = sort_field_writer.extract_val(&add_operation.document);
+            match blocks.last_mut() {
+                Some((block_sort_value, block)) if block[0].opstamp == add_operation.opstamp => {
+                    debug_assert_eq!(*block_sort_value, sort_value);
+                    block.push(add_operation);
+                }
+                _ => blocks.push((sort_value, vec![add_operation])),
+            }
+        }
+        for (_, block) in &blocks {
+            self.num_buffered_docs += block.len() as u32;
+            self.buffered_mem_usage += block
+                .iter()
+                .map(|add_operation| document_mem_usage(&add_operation.document))
+                .sum::<usize>();
+        }
+        self.buffered_blocks.extend(blocks);
+        Ok(())
+    }
+
+    /// Filters out the operations with a document exceeding the `DocTokenLimit`
+    /// of the index, if its policy is `TokenLimitPolicy::Error`, and records their opstamps.
+    fn reject_over_token_limit<TAddOperations>(
+        &mut self,
+        add_operations: TAddOperations,
+        schema: &Schema,
+    ) -> Vec<AddOperation>
+    where
+        TAddOperations: IntoIterator<Item = AddOperation>,
+    {
+        let add_operations: Vec<AddOperation> = add_operations.into_iter().collect();
+        let max_num_tokens = match self.doc_token_limit {
+            Some(DocTokenLimit {
+                max_num_tokens,
+                policy: TokenLimitPolicy::Error,
+            }) => max_num_tokens,
+            _ => return add_operations,
+        };
+        let num_rejected_opstamps = self.rejected_opstamps.len();
+        for add_operation in &add_operations {
+            if self.rejected_opstamps[num_rejected_opstamps..].contains(&add_operation.opstamp) {
+                continue;
+            }
+            if self.exceeds_token_limit(&add_operation.document, schema, max_num_tokens) {
+                warn!(
+                    "Rejecting the document with opstamp {}: it has more than {} tokens.",
+                    add_operation.opstamp, max_num_tokens
+                );
+                self.rejected_opstamps.push(add_operation.opstamp);
+            }
+        }
+        if self.rejected_opstamps.len() == num_rejected_opstamps {
+            return add_operations;
+        }
+        let rejected_opstamps = &self.rejected_opstamps[num_rejected_opstamps..];
+        add_operations
+            .into_iter()
+            .filter(|add_operation| !rejected_opstamps.contains(&add_operation.opstamp))
+            .collect()
+    }
+
+    /// Returns true if the text and json fields of the document have more
+    /// than `max_num_tokens` tokens, without indexing them.
+    fn exceeds_token_limit(&self, doc: &Document, schema: &Schema, max_num_tokens: usize) -> bool {
+        let mut num_tokens = 0;
+        for (field, field_values) in doc.get_sorted_field_values() {
+            let field_entry = schema.get_field_entry(field);
+            let tokenizer = match self.tokenizers[field.0 as usize] {
+                Some(ref tokenizer) if field_entry.is_indexed() => tokenizer,
+                _ => continue,
+            };
+            match *field_entry.field_type() {
+                FieldType::Str(_) => {
+                    let texts: Vec<&str> = field_values
+                        .iter()
+                        .flat_map(|field_value| match *field_value.value() {
+                            Value::Str(ref text) => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    if !texts.is_empty() {
+                        num_tokens += count_tokens(
+                            tokenizer.token_stream_texts(&texts[..]),
+                            max_num_tokens - num_tokens,
+                        );
+                    }
+                }
+                FieldType::Json(_) => {
+                    let mut json_values = JsonValues::new(field);
+                    for field_value in field_values {
+                        if let Value::JsonObject(ref json_object) = *field_value.value() {
+                            json_values.add_object(json_object);
+                        }
+                    }
+                    for (_, texts) in json_values.texts() {
+                        if num_tokens > max_num_tokens {
+                            break;
+                        }
+                        num_tokens += count_tokens(
+                            tokenizer.token_stream_texts(texts),
+                            max_num_tokens - num_tokens,
+                        );
+                    }
+                }
+                _ => {}
+            }
+            if num_tokens > max_num_tokens {
+                return true;
+            }
+        }
+        false
     }
 
     /// Indexes a new document
     ///
     /// As a user, you should rather use `IndexWriter`'s add_document.
-    pub fn add_document(&mut self, add_operation: AddOperation, schema: &Schema) -> io::Result<()> {
+    ///
+    /// If the document exceeds the `DocTokenLimit` of the index, its tokens beyond
+    /// the limit are not indexed.
+    pub fn add_document(&mut self, add_operation: AddOperation, schema: &Schema) -> Result<()> {
         let doc_id = self.max_doc;
         let mut doc = add_operation.document;
+        let mut remaining_tokens = self
+            .doc_token_limit
+            .map(|doc_token_limit| doc_token_limit.max_num_tokens);
         self.doc_opstamps.push(add_operation.opstamp);
 
         self.fast_field_writers.add_document(&doc);
@@ -159,26 +366,64 @@ impl SegmentWriter {
                     }
                 }
                 FieldType::Str(_) => {
-                    let num_tokens = if let Some(ref mut tokenizer) =
-                        self.tokenizers[field.0 as usize]
-                    {
-                        let texts: Vec<&str> = field_values
-                            .iter()
-                            .flat_map(|field_value| match *field_value.value() {
-                                Value::Str(ref text) => Some(text.as_str()),
-                                _ => None,
-                            })
-                            .collect();
-                        if texts.is_empty() {
-                            0
+                    let num_tokens =
+                        if let Some(ref mut tokenizer) = self.tokenizers[field.0 as usize] {
+                            let texts: Vec<&str> = field_values
+                                .iter()
+                                .flat_map(|field_value| match *field_value.value() {
+                                    Value::Str(ref text) => Some(text.as_str()),
+                                    _ => None,
+                                })
+                                .collect();
+                            if texts.is_empty() {
+                                0
+                            } else {
+                                let mut token_stream = LimitedTokenStream::new(
+                                    tokenizer.token_stream_texts(&texts[..]),
+                                    remaining_tokens,
+                                );
+                                let term_id_fast_field_writer_opt =
+                                    self.fast_field_writers.get_multivalue_writer(field);
+                                let num_tokens = self.multifield_postings.index_text(
+                                    doc_id,
+                                    &Term::for_field(field),
+                                    &mut token_stream,
+                                    term_id_fast_field_writer_opt,
+                                );
+                                remaining_tokens = token_stream.remaining_tokens;
+                                num_tokens
+                            }
                         } else {
-                            let mut token_stream = tokenizer.token_stream_texts(&texts[..]);
-                            self.multifield_postings
-                                .index_text(doc_id, field, &mut token_stream)
+                            0
+                        };
+                    self.fieldnorms_writer.record(doc_id, field, num_tokens);
+                }
+                FieldType::Json(_) => {
+                    let mut json_values = JsonValues::new(field);
+                    for field_value in field_values {
+                        if let Value::JsonObject(ref json_object) = *field_value.value() {
+                            json_values.add_object(json_object);
+                        }
+                    }
+                    for term in json_values.numeric_terms() {
+                        self.multifield_postings.subscribe(doc_id, term);
+                    }
+                    let mut num_tokens = 0;
+                    if let Some(ref mut tokenizer) = self.tokenizers[field.0 as usize] {
+                        for (term_prefix, texts) in json_values.texts() {
+                            let mut token_stream = LimitedTokenStream::new(
+                                tokenizer.token_stream_texts(texts),
+                                remaining_tokens,
+                            );
+                            num_tokens += self.multifield_postings.index_text(
+                                doc_id,
+                                &term_prefix,
+                                &mut token_stream,
+                                None,
+                            );
+                            remaining_tokens = token_stream.remaining_tokens;
                         }
-                    } else {
-                        0
-                    };
+                    }
                     self.fieldnorms_writer.record(doc_id, field, num_tokens);
                 }
                 FieldType::U64(ref int_option) => {
@@ -225,8 +470,29 @@ impl SegmentWriter {
                         }
                     }
                 }
-                FieldType::Bytes => {
-                    // Do nothing. Bytes only supports fast fields.
+                FieldType::GeoPoint(ref int_option) => {
+                    if int_option.is_indexed() {
+                        for field_value in field_values {
+                            let morton_code = field_value.value().geo_point_value().to_u64();
+                            for level in 1..=NUM_CELL_LEVELS {
+                                let term = Term::from_field_u64(
+                                    field_value.field(),
+                                    cell_code(morton_code, level),
+                                );
+                                self.multifield_postings.subscribe(doc_id, &term);
+                            }
+                        }
+                    }
+                }
+                FieldType::Bytes(ref bytes_options) => {
+                    if bytes_options.is_indexed() {
+                        for field_value in field_values {
+                            if let Value::Bytes(ref bytes) = *field_value.value() {
+                                let term = Term::from_field_bytes(field_value.field(), bytes);
+                                self.multifield_postings.subscribe(doc_id, &term);
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -241,20 +507,94 @@ impl SegmentWriter {
     /// - the number of documents in the segment assuming there is no deletes
     /// - the maximum document id (including deleted documents) + 1
     ///
-    /// Currently, **tantivy** does not handle deletes anyway,
-    /// so `max_doc == num_docs`
+    /// Deletes are only applied once the segment is finalized,
+    /// so `max_doc == num_docs` while the segment is being written.
     pub fn max_doc(&self) -> u32 {
-        self.max_doc
+        self.max_doc + self.num_buffered_docs
     }
 
     /// Number of documents in the index.
     /// Deleted documents are not counted.
     ///
-    /// Currently, **tantivy** does not handle deletes anyway,
-    /// so `max_doc == num_docs`
+    /// Deletes are only applied once the segment is finalized,
+    /// so `max_doc == num_docs` while the segment is being written.
     #[allow(dead_code)]
     pub fn num_docs(&self) -> u32 {
-        self.max_doc
+        self.max_doc()
+    }
+}
+
+/// Estimates the memory used by a document buffered by the `SegmentWriter`.
+fn document_mem_usage(doc: &Document) -> usize {
+    let values_mem_usage: usize = doc
+        .field_values()
+        .iter()
+        .map(|field_value| {
+            let heap_mem_usage = match *field_value.value() {
+                Value::Str(ref text) => text.len(),
+                Value::Facet(ref facet) => facet.encoded_str().len(),
+                Value::Bytes(ref bytes) => bytes.len(),
+                Value::JsonObject(ref json_object) => serde_json::to_vec(json_object)
+                    .map(|json_bytes| json_bytes.len())
+                    .unwrap_or(0),
+                _ => 0,
+            };
+            mem::size_of::<FieldValue>() + heap_mem_usage
+        })
+        .sum();
+    mem::size_of::<AddOperation>() + values_mem_usage
+}
+
+/// Counts the tokens of a token stream, stopping once it exceeds `max_num_tokens`.
+fn count_tokens(mut token_stream: Box<dyn TokenStream + '_>, max_num_tokens: usize) -> usize {
+    let mut num_tokens = 0;
+    while num_tokens <= max_num_tokens && token_stream.advance() {
+        num_tokens += 1;
+    }
+    num_tokens
+}
+
+/// `TokenStream` stopping once the tokens remaining for the document are spent.
+///
+/// There is no limit if `remaining_tokens` is `None`.
+struct LimitedTokenStream<TailTokenStream> {
+    tail: TailTokenStream,
+    remaining_tokens: Option<usize>,
+}
+
+impl<TailTokenStream: TokenStream> LimitedTokenStream<TailTokenStream> {
+    fn new(
+        tail: TailTokenStream,
+        remaining_tokens: Option<usize>,
+    ) -> LimitedTokenStream<TailTokenStream> {
+        LimitedTokenStream {
+            tail,
+            remaining_tokens,
+        }
+    }
+}
+
+impl<TailTokenStream: TokenStream> TokenStream for LimitedTokenStream<TailTokenStream> {
+    fn advance(&mut self) -> bool {
+        match self.remaining_tokens {
+            Some(0) => false,
+            Some(ref mut remaining_tokens) => {
+                if !self.tail.advance() {
+                    return false;
+                }
+                *remaining_tokens -= 1;
+                true
+            }
+            None => self.tail.advance(),
+        }
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
     }
 }
 
@@ -288,6 +628,12 @@ impl SerializableSegment for SegmentWriter {
 #[cfg(test)]
 mod tests {
     use super::initial_table_size;
+    use crate::collector::{Count, Order};
+    use crate::docset::DocSet;
+    use crate::indexer::operation::UserOperation;
+    use crate::query::TermQuery;
+    use crate::schema::{Field, IndexRecordOption, Schema, Value, FAST, INDEXED, STORED, TEXT};
+    use crate::{DocTokenLimit, Index, IndexSettings, TantivyError, Term, TokenLimitPolicy};
 
     #[test]
     fn test_hashmap_size() {
@@ -296,4 +642,222 @@ mod tests {
         assert_eq!(initial_table_size(10_000_000).unwrap(), 17);
         assert_eq!(initial_table_size(1_000_000_000).unwrap(), 19);
     }
+
+    fn create_index_with_token_limit(policy: TokenLimitPolicy) -> (Index, Field, Field) {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let settings = IndexSettings {
+            doc_token_limit: Some(DocTokenLimit {
+                max_num_tokens: 4,
+                policy,
+            }),
+            ..IndexSettings::default()
+        };
+        let index = Index::create_with_settings(
+            crate::directory::RAMDirectory::create(),
+            schema_builder.build(),
+            settings,
+        )
+        .unwrap();
+        (index, title_field, body_field)
+    }
+
+    #[test]
+    fn test_doc_token_limit_truncate() {
+        let (index, title_field, body_field) =
+            create_index_with_token_limit(TokenLimitPolicy::Truncate);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(title_field => "a b", body_field => "c d e f"));
+        index_writer.add_document(doc!(body_field => "e f"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count = |field: Field, text: &str| {
+            let query =
+                TermQuery::new(Term::from_field_text(field, text), IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(title_field, "b"), 1);
+        assert_eq!(count(body_field, "d"), 1);
+        // The limit is shared by the fields of a document.
+        assert_eq!(count(body_field, "e"), 1);
+        let fieldnorm_reader = searcher.segment_reader(0).get_fieldnorms_reader(body_field);
+        assert_eq!(fieldnorm_reader.fieldnorm(0), 2);
+    }
+
+    #[test]
+    fn test_doc_token_limit_error() {
+        let (index, title_field, body_field) =
+            create_index_with_token_limit(TokenLimitPolicy::Error);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(title_field => "a b", body_field => "c d"));
+        let rejected_opstamp =
+            index_writer.add_document(doc!(title_field => "a b", body_field => "c d e"));
+        let rejected_block_opstamp = index_writer
+            .add_document_block(vec![
+                doc!(title_field => "a"),
+                doc!(title_field => "a b c d e"),
+            ])
+            .unwrap();
+        index_writer.add_document(doc!(title_field => "a"));
+        index_writer.commit().unwrap();
+        assert_eq!(
+            index_writer.take_rejected_opstamps(),
+            vec![rejected_opstamp, rejected_block_opstamp]
+        );
+        assert!(index_writer.take_rejected_opstamps().is_empty());
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        let query = TermQuery::new(
+            Term::from_field_text(body_field, "e"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_doc_token_limit_error_all_rejected() {
+        let (index, title_field, _) = create_index_with_token_limit(TokenLimitPolicy::Error);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let rejected_opstamp = index_writer.add_document(doc!(title_field => "a b c d e"));
+        index_writer.commit().unwrap();
+        assert_eq!(
+            index_writer.take_rejected_opstamps(),
+            vec![rejected_opstamp]
+        );
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.num_docs(), 0);
+        assert!(searcher.segment_readers().is_empty());
+    }
+
+    #[test]
+    fn test_doc_token_limit_invalid() {
+        let settings = IndexSettings {
+            doc_token_limit: Some(DocTokenLimit {
+                max_num_tokens: 0,
+                policy: TokenLimitPolicy::Truncate,
+            }),
+            ..IndexSettings::default()
+        };
+        let index = Index::create_with_settings(
+            crate::directory::RAMDirectory::create(),
+            Schema::builder().build(),
+            settings,
+        );
+        assert!(index.is_err());
+    }
+
+    #[test]
+    fn test_sort_by_field() {
+        let mut schema_builder = Schema::builder();
+        let timestamp_field = schema_builder.add_i64_field("timestamp", FAST | INDEXED | STORED);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let settings = IndexSettings::default().sort_by_field("timestamp", Order::Asc);
+        let index = Index::create_with_settings(
+            crate::directory::RAMDirectory::create(),
+            schema_builder.build(),
+            settings,
+        )
+        .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(timestamp_field => 3i64, text_field => "c"));
+        index_writer.add_document(doc!(timestamp_field => -1i64, text_field => "deleted"));
+        // The delete only affects the documents added before it.
+        index_writer.delete_term(Term::from_field_i64(timestamp_field, -1i64));
+        index_writer.add_document(doc!(timestamp_field => -1i64, text_field => "a"));
+        index_writer
+            .add_document_block(vec![
+                doc!(timestamp_field => 1i64, text_field => "child"),
+                doc!(timestamp_field => 1i64, text_field => "parent"),
+            ])
+            .unwrap();
+        index_writer.add_document(doc!(text_field => "missing"));
+        index_writer.commit().unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.max_doc(), 6);
+        assert_eq!(segment_reader.num_docs(), 5);
+        let timestamp_reader = segment_reader.fast_fields().i64(timestamp_field).unwrap();
+        let timestamps: Vec<i64> = (0..6).map(|doc| timestamp_reader.get(doc)).collect();
+        assert_eq!(timestamps, vec![-1, -1, 0, 1, 1, 3]);
+        let store_reader = segment_reader.get_store_reader();
+        let stored_timestamp = |doc| {
+            store_reader
+                .get(doc)
+                .unwrap()
+                .get_first(timestamp_field)
+                .map(Value::i64_value)
+        };
+        assert_eq!(stored_timestamp(1), Some(-1));
+        assert_eq!(stored_timestamp(2), None);
+        assert_eq!(stored_timestamp(5), Some(3));
+        let inverted_index = segment_reader.inverted_index(text_field);
+        let term_doc = |text: &str| {
+            let term = Term::from_field_text(text_field, text);
+            let mut postings = inverted_index
+                .read_postings(&term, IndexRecordOption::Basic)
+                .unwrap();
+            assert!(postings.advance());
+            postings.doc()
+        };
+        assert_eq!(term_doc("deleted"), 0);
+        assert_eq!(term_doc("a"), 1);
+        assert_eq!(term_doc("missing"), 2);
+        assert_eq!(term_doc("child"), 3);
+        assert_eq!(term_doc("parent"), 4);
+        assert!(segment_reader.is_deleted(0));
+        assert!(!segment_reader.is_deleted(1));
+    }
+
+    #[test]
+    fn test_sort_by_field_block_with_different_values() {
+        let mut schema_builder = Schema::builder();
+        let timestamp_field = schema_builder.add_i64_field("timestamp", FAST);
+        let settings = IndexSettings::default().sort_by_field("timestamp", Order::Asc);
+        let index = Index::create_with_settings(
+            crate::directory::RAMDirectory::create(),
+            schema_builder.build(),
+            settings,
+        )
+        .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let block_result = index_writer.add_document_block(vec![
+            doc!(timestamp_field => 5i64),
+            doc!(timestamp_field => 1i64),
+        ]);
+        match block_result {
+            Err(TantivyError::InvalidArgument(msg)) => assert!(msg.contains("same value")),
+            _ => panic!("Expected an invalid argument error"),
+        }
+        // Documents of a batch are not a block, and are sorted individually.
+        index_writer.run(vec![
+            UserOperation::Add(doc!(timestamp_field => 5i64)),
+            UserOperation::Add(doc!(timestamp_field => 1i64)),
+        ]);
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let timestamp_reader = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .i64(timestamp_field)
+            .unwrap();
+        assert_eq!(searcher.num_docs(), 2);
+        assert_eq!(timestamp_reader.get(0), 1);
+        assert_eq!(timestamp_reader.get(1), 5);
+    }
+
+    #[test]
+    fn test_sort_by_field_invalid() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_i64_field("timestamp", INDEXED);
+        let settings = IndexSettings::default().sort_by_field("timestamp", Order::Desc);
+        let index = Index::create_with_settings(
+            crate::directory::RAMDirectory::create(),
+            schema_builder.build(),
+            settings,
+        );
+        assert!(index.is_err());
+    }
 }
@@ -0,0 +1,308 @@
+/*!
+Introspection of the content of an index.
+
+The [`IndexInspector`](./struct.IndexInspector.html) exposes the building blocks of
+an index explorer: the list of segments, the number of terms of each field,
+the most frequent terms of a field and a sample of the stored documents.
+
+```rust
+# use tantivy::inspect::IndexInspector;
+# use tantivy::schema::{Schema, STORED, TEXT};
+# use tantivy::{doc, Index};
+# fn main() -> tantivy::Result<()> {
+let mut schema_builder = Schema::builder();
+let title = schema_builder.add_text_field("title", TEXT | STORED);
+let index = Index::create_in_ram(schema_builder.build());
+let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+index_writer.add_document(doc!(title => "The Old Man and the Sea"));
+index_writer.add_document(doc!(title => "Of Mice and Men"));
+index_writer.commit()?;
+
+let searcher = index.reader()?.searcher();
+let inspector = IndexInspector::new(&searcher);
+assert_eq!(inspector.segments()[0].num_docs(), 2);
+let top_terms = inspector.top_terms(title, 1);
+assert_eq!(top_terms[0].0.text(), "and");
+assert_eq!(top_terms[0].1, 2);
+# Ok(())
+# }
+```
+*/
+
+use crate::schema::{Document, Field, Term};
+use crate::DocAddress;
+use crate::Opstamp;
+use crate::Result;
+use crate::Searcher;
+use crate::SegmentId;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Description of a segment of the index.
+#[derive(Clone, Debug, Serialize)]
+pub struct SegmentInfo {
+    segment_id: SegmentId,
+    max_doc: u32,
+    num_docs: u32,
+    delete_opstamp: Option<Opstamp>,
+}
+
+impl SegmentInfo {
+    /// Id of the segment
+    pub fn segment_id(&self) -> SegmentId {
+        self.segment_id
+    }
+
+    /// Number of documents of the segment, including deleted documents
+    pub fn max_doc(&self) -> u32 {
+        self.max_doc
+    }
+
+    /// Number of alive documents of the segment
+    pub fn num_docs(&self) -> u32 {
+        self.num_docs
+    }
+
+    /// Number of deleted documents of the segment
+    pub fn num_deleted_docs(&self) -> u32 {
+        self.max_doc - self.num_docs
+    }
+
+    /// Opstamp of the last delete operation applied to the segment, if any.
+    ///
+    /// This is the generation of the delete bitset of the segment.
+    pub fn delete_opstamp(&self) -> Option<Opstamp> {
+        self.delete_opstamp
+    }
+}
+
+/// Description of an indexed field.
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldInfo {
+    field: Field,
+    name: String,
+    num_terms: u64,
+}
+
+impl FieldInfo {
+    /// The field
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Name of the field
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sum of the number of terms of the field in each segment.
+    ///
+    /// A term present in several segments is counted several times.
+    pub fn num_terms(&self) -> u64 {
+        self.num_terms
+    }
+}
+
+/// Inspects the content of the index, as seen by a `Searcher`.
+pub struct IndexInspector<'a> {
+    searcher: &'a Searcher,
+}
+
+impl<'a> IndexInspector<'a> {
+    /// Creates an inspector of the segments of `searcher`.
+    pub fn new(searcher: &'a Searcher) -> IndexInspector<'a> {
+        IndexInspector { searcher }
+    }
+
+    /// Returns the generation of the meta file the searcher was opened at,
+    /// if it is known.
+    pub fn generation(&self) -> Option<u64> {
+        self.searcher.pinned_generation()
+    }
+
+    /// Returns the description of the segments of the searcher,
+    /// in the order of their `SegmentLocalId`.
+    pub fn segments(&self) -> Vec<SegmentInfo> {
+        self.searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| SegmentInfo {
+                segment_id: segment_reader.segment_id(),
+                max_doc: segment_reader.max_doc(),
+                num_docs: segment_reader.num_docs(),
+                delete_opstamp: segment_reader.delete_opstamp(),
+            })
+            .collect()
+    }
+
+    /// Returns the description of the indexed fields of the schema.
+    pub fn fields(&self) -> Vec<FieldInfo> {
+        let schema = self.searcher.schema();
+        schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(_, field_entry)| field_entry.is_indexed())
+            .map(|(field_id, field_entry)| {
+                let field = Field(field_id as u32);
+                let num_terms = self
+                    .searcher
+                    .segment_readers()
+                    .iter()
+                    .map(|segment_reader| segment_reader.inverted_index(field).num_terms() as u64)
+                    .sum();
+                FieldInfo {
+                    field,
+                    name: field_entry.name().to_string(),
+                    num_terms,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the `top_n` terms of `field` with the highest document frequency,
+    /// with their document frequency, by decreasing document frequency.
+    ///
+    /// Document frequencies include the deleted documents.
+    /// Terms with the same document frequency are sorted in the order of the term dictionary.
+    ///
+    /// This method walks through the whole term dictionary of the field.
+    ///
+    /// # Panics
+    /// If the field is not indexed.
+    pub fn top_terms(&self, field: Field, top_n: usize) -> Vec<(Term, u64)> {
+        if top_n == 0 {
+            return Vec::new();
+        }
+        let field_searcher = self.searcher.field(field);
+        let mut term_merger = field_searcher.terms();
+        let mut heap: BinaryHeap<Reverse<(u64, Reverse<Vec<u8>>)>> = BinaryHeap::new();
+        while term_merger.advance() {
+            let doc_freq: u64 = term_merger
+                .current_kvs()
+                .iter()
+                .map(|heap_item| u64::from(heap_item.streamer.value().doc_freq))
+                .sum();
+            if heap.len() == top_n {
+                let (min_doc_freq, _) = &(heap.peek().unwrap().0);
+                if doc_freq <= *min_doc_freq {
+                    continue;
+                }
+                heap.pop();
+            }
+            heap.push(Reverse((doc_freq, Reverse(term_merger.key().to_vec()))));
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((doc_freq, Reverse(term_bytes)))| {
+                (Term::from_field_bytes(field, &term_bytes), doc_freq)
+            })
+            .collect()
+    }
+
+    /// Returns up to `num_docs` stored documents, evenly spread over the alive
+    /// documents of the searcher.
+    pub fn sample_docs(&self, num_docs: usize) -> Result<Vec<(DocAddress, Document)>> {
+        let total_num_docs = self.searcher.num_docs() as usize;
+        if num_docs == 0 || total_num_docs == 0 {
+            return Ok(Vec::new());
+        }
+        let step = (total_num_docs / num_docs).max(1);
+        let mut doc_addresses = Vec::with_capacity(num_docs);
+        let mut alive_doc_ord = 0usize;
+        let mut next_sample_ord = 0usize;
+        'segments: for (segment_ord, segment_reader) in
+            self.searcher.segment_readers().iter().enumerate()
+        {
+            for doc in segment_reader.doc_ids_alive() {
+                if alive_doc_ord == next_sample_ord {
+                    next_sample_ord += step;
+                    doc_addresses.push(DocAddress(segment_ord as u32, doc));
+                    if doc_addresses.len() == num_docs {
+                        break 'segments;
+                    }
+                }
+                alive_doc_ord += 1;
+            }
+        }
+        let docs = self.searcher.docs(&doc_addresses)?;
+        Ok(doc_addresses.into_iter().zip(docs).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexInspector;
+    use crate::schema::{Schema, FAST, STORED, STRING, TEXT};
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_index_inspector() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let num = schema_builder.add_u64_field("num", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let texts = ["a b c", "a b", "a", "b d", "a e"];
+        for (i, text_value) in texts.iter().enumerate() {
+            index_writer.add_document(doc!(
+                text => *text_value,
+                id => format!("{}", i),
+                num => i as u64
+            ));
+            if i == 2 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.delete_term(Term::from_field_text(id, "3"));
+        index_writer.commit().unwrap();
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let inspector = IndexInspector::new(&searcher);
+
+        let mut segments = inspector.segments();
+        segments.sort_by_key(|segment| segment.max_doc());
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].max_doc(), 2);
+        assert_eq!(segments[0].num_deleted_docs(), 1);
+        assert!(segments[0].delete_opstamp().is_some());
+        assert_eq!(segments[1].num_docs(), 3);
+        assert_eq!(segments[1].delete_opstamp(), None);
+
+        let fields = inspector.fields();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name(), "text");
+        assert_eq!(fields[0].num_terms(), 3 + 4);
+        assert_eq!(fields[1].field(), id);
+        assert_eq!(fields[1].num_terms(), 5);
+
+        let top_terms: Vec<(String, u64)> = inspector
+            .top_terms(text, 3)
+            .into_iter()
+            .map(|(term, doc_freq)| (term.text().to_string(), doc_freq))
+            .collect();
+        assert_eq!(
+            top_terms,
+            vec![
+                ("a".to_string(), 4),
+                ("b".to_string(), 3),
+                ("c".to_string(), 1)
+            ]
+        );
+        assert!(inspector.top_terms(text, 0).is_empty());
+        assert_eq!(inspector.top_terms(text, 10).len(), 5);
+
+        let sample = inspector.sample_docs(2).unwrap();
+        assert_eq!(sample.len(), 2);
+        let mut ids: Vec<String> = sample
+            .iter()
+            .map(|(_, doc)| doc.get_first(id).unwrap().text().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains(&"3".to_string()));
+        assert_eq!(inspector.sample_docs(10).unwrap().len(), 4);
+    }
+}
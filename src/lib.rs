@@ -140,10 +140,14 @@ mod indexer;
 mod error;
 pub mod tokenizer;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod collector;
 pub mod directory;
 pub mod fastfield;
 pub mod fieldnorm;
+pub mod inspect;
+pub mod lucene;
 pub(crate) mod positions;
 pub mod postings;
 pub mod query;
@@ -154,7 +158,7 @@ pub mod termdict;
 
 mod reader;
 
-pub use self::reader::{IndexReader, IndexReaderBuilder, ReloadPolicy};
+pub use self::reader::{AliasedIndexReader, IndexReader, IndexReaderBuilder, ReloadPolicy, Warmer};
 mod snippet;
 pub use self::snippet::{Snippet, SnippetGenerator};
 
@@ -163,8 +167,13 @@ pub use self::docset::{DocSet, SkipResult};
 
 pub use crate::common::{f64_to_u64, i64_to_u64, u64_to_f64, u64_to_i64};
 pub use crate::core::SegmentComponent;
-pub use crate::core::{Index, IndexMeta, Searcher, Segment, SegmentId, SegmentMeta};
-pub use crate::core::{InvertedIndexReader, SegmentReader};
+pub use crate::core::{
+    DocTokenLimit, Executor, FieldStatistics, GlobalStatistics, Index, IndexAliases, IndexMeta,
+    IndexSettings, IndexSortByField, IntegrityProblem, MultiSearcher, PartialSearchResult,
+    QueryProfile, SearchProfile, Searcher, Segment, SegmentCompletion, SegmentId, SegmentMeta,
+    SegmentProfile, TokenLimitPolicy,
+};
+pub use crate::core::{FieldTermStatistics, InvertedIndexReader, SegmentReader};
 pub use crate::directory::Directory;
 pub use crate::indexer::IndexWriter;
 pub use crate::postings::Postings;
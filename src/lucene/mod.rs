@@ -0,0 +1,450 @@
+/*!
+Import of Lucene segments written with the plain text `SimpleTextCodec`.
+
+This module is a test and interchange utility: it makes it possible to compare
+tantivy with Lucene on the same documents, or to move small indexes between them.
+It is not meant to migrate production indexes. Only the segments written with
+Lucene's `SimpleTextCodec` can be read, not those of the default codec.
+An index written with another codec can be rewritten with it beforehand, e.g. by
+adding it with `IndexWriter::addIndexes` to an index configured with
+`IndexWriterConfig::setCodec(new SimpleTextCodec())` and `setUseCompoundFile(false)`.
+
+The following parts of a segment are imported:
+- the stored fields (`.fld` file),
+- the `NUMERIC` doc values (`.dat` file),
+- the deleted documents (`.liv` file), which are not imported.
+
+The postings are not imported: the text of a field cannot be rebuilt from its terms
+without loss, so a Lucene field that is indexed but neither stored nor associated
+to doc values is left empty.
+
+The documents are rebuilt according to a [`LuceneFieldMapping`](./struct.LuceneFieldMapping.html),
+associating the Lucene fields to the fields of the tantivy schema, and added to an
+`IndexWriter`. They are then indexed in tantivy segments like any other document
+upon commit. The values of a field come from the first of these sources having
+a value for the document:
+- The stored values. `string`, `binary`, `int` and `long`, `float` and `double`
+  values are respectively imported into text, bytes, `i64` (or `u64`, if positive,
+  or date, as milliseconds since the epoch) and `f64` fields.
+- The `NUMERIC` doc value, imported into `i64`, `u64`, date (as milliseconds since
+  the epoch) or `f64` fields. Doc values of `f64` fields are decoded as Lucene's
+  `NumericUtils.doubleToSortableLong`, as written by Elasticsearch `double` fields.
+
+```rust,no_run
+use tantivy::lucene::{import_lucene_segment, LuceneFieldMapping, LuceneSegment};
+use tantivy::schema::{Schema, STORED, TEXT};
+use tantivy::Index;
+use std::path::Path;
+
+# fn main() -> tantivy::Result<()> {
+let mut schema_builder = Schema::builder();
+let title = schema_builder.add_text_field("title", TEXT | STORED);
+let index = Index::create_in_ram(schema_builder.build());
+let mut index_writer = index.writer(50_000_000)?;
+let mapping = LuceneFieldMapping::default().map_field("title", title);
+let lucene_segment = LuceneSegment::open(Path::new("/path/to/lucene/index"), "_0")?;
+import_lucene_segment(&index_writer, &lucene_segment, &mapping)?;
+index_writer.commit()?;
+# Ok(())
+# }
+```
+!*/
+
+mod simple_text;
+
+pub use self::simple_text::LuceneValue;
+
+use crate::common::BitSet;
+use crate::schema::{Document, Field, FieldEntry, FieldType, FieldValue, Schema, Value};
+use crate::DocId;
+use crate::IndexWriter;
+use crate::Result;
+use crate::TantivyError;
+use chrono::{TimeZone, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Segment of a Lucene index written with the `SimpleTextCodec`.
+///
+/// See the [module documentation](./index.html).
+pub struct LuceneSegment {
+    max_doc: u32,
+    live_docs: Option<BitSet>,
+    stored_docs: Vec<Vec<(String, LuceneValue)>>,
+    numeric_doc_values: HashMap<String, Vec<Option<i64>>>,
+}
+
+impl LuceneSegment {
+    /// Reads the segment `segment_name` (e.g. `_0`) of the Lucene index in `directory`.
+    ///
+    /// The whole segment is loaded in memory.
+    pub fn open(directory: &Path, segment_name: &str) -> Result<LuceneSegment> {
+        let segment_path =
+            |extension: &str| directory.join(format!("{}.{}", segment_name, extension));
+        let max_doc = simple_text::read_max_doc(&segment_path("si"))?;
+        let live_docs = match simple_text::live_docs_path(directory, segment_name)? {
+            Some(live_docs_path) => Some(simple_text::read_live_docs(&live_docs_path, max_doc)?),
+            None => None,
+        };
+        let stored_fields_path = segment_path("fld");
+        let stored_docs = if stored_fields_path.exists() {
+            simple_text::read_stored_fields(&stored_fields_path)?
+        } else {
+            vec![Vec::new(); max_doc as usize]
+        };
+        if stored_docs.len() != max_doc as usize {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The stored fields of the Lucene segment {:?} have {} documents, expected {}.",
+                segment_name,
+                stored_docs.len(),
+                max_doc
+            )));
+        }
+        let doc_values_path = segment_path("dat");
+        let numeric_doc_values = if doc_values_path.exists() {
+            simple_text::read_numeric_doc_values(&doc_values_path, max_doc)?
+        } else {
+            HashMap::new()
+        };
+        Ok(LuceneSegment {
+            max_doc,
+            live_docs,
+            stored_docs,
+            numeric_doc_values,
+        })
+    }
+
+    /// Returns the number of documents of the segment, including the deleted ones.
+    pub fn max_doc(&self) -> u32 {
+        self.max_doc
+    }
+
+    /// Returns the number of documents of the segment that are not deleted.
+    pub fn num_docs(&self) -> u32 {
+        match self.live_docs {
+            Some(ref live_docs) => live_docs.len() as u32,
+            None => self.max_doc,
+        }
+    }
+
+    /// Returns true if the document is not deleted.
+    pub fn is_alive(&self, doc: DocId) -> bool {
+        match self.live_docs {
+            Some(ref live_docs) => live_docs.contains(doc),
+            None => true,
+        }
+    }
+
+    /// Returns the stored values of a document.
+    pub fn stored_values(&self, doc: DocId) -> &[(String, LuceneValue)] {
+        &self.stored_docs[doc as usize]
+    }
+
+    /// Rebuilds the documents of the segment that are not deleted, in the order
+    /// of their Lucene `DocId`.
+    ///
+    /// Returns an `InvalidArgument` error if a value cannot be imported into the
+    /// field it is mapped to.
+    pub fn documents(
+        &self,
+        schema: &Schema,
+        mapping: &LuceneFieldMapping,
+    ) -> Result<Vec<Document>> {
+        let mut documents = Vec::with_capacity(self.num_docs() as usize);
+        for doc in (0..self.max_doc).filter(|&doc| self.is_alive(doc)) {
+            let mut document = Document::default();
+            for (lucene_field_name, field) in &mapping.fields {
+                let field_entry = schema.get_field_entry(*field);
+                let mut has_stored_value = false;
+                for (_, stored_value) in self
+                    .stored_values(doc)
+                    .iter()
+                    .filter(|(name, _)| name == lucene_field_name)
+                {
+                    let value = convert_stored_value(lucene_field_name, field_entry, stored_value)?;
+                    document.add(FieldValue::new(*field, value));
+                    has_stored_value = true;
+                }
+                if has_stored_value {
+                    continue;
+                }
+                let doc_value_opt = self
+                    .numeric_doc_values
+                    .get(lucene_field_name)
+                    .and_then(|doc_values| doc_values[doc as usize]);
+                if let Some(doc_value) = doc_value_opt {
+                    let value = convert_doc_value(lucene_field_name, field_entry, doc_value)?;
+                    document.add(FieldValue::new(*field, value));
+                }
+            }
+            documents.push(document);
+        }
+        Ok(documents)
+    }
+}
+
+fn unsupported_value(
+    lucene_field_name: &str,
+    field_entry: &FieldEntry,
+    value_description: &str,
+) -> TantivyError {
+    TantivyError::InvalidArgument(format!(
+        "The {} of the Lucene field {:?} cannot be imported into the field {:?}.",
+        value_description,
+        lucene_field_name,
+        field_entry.name()
+    ))
+}
+
+fn millis_to_date(lucene_field_name: &str, field_entry: &FieldEntry, millis: i64) -> Result<Value> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(Value::Date)
+        .ok_or_else(|| unsupported_value(lucene_field_name, field_entry, "out of range date"))
+}
+
+fn convert_stored_value(
+    lucene_field_name: &str,
+    field_entry: &FieldEntry,
+    stored_value: &LuceneValue,
+) -> Result<Value> {
+    match (field_entry.field_type(), stored_value) {
+        (FieldType::Str(_), LuceneValue::Str(text)) => Ok(Value::Str(text.clone())),
+        (FieldType::Bytes(_), LuceneValue::Bytes(bytes)) => Ok(Value::Bytes(bytes.clone())),
+        (FieldType::I64(_), &LuceneValue::Long(val)) => Ok(Value::I64(val)),
+        (FieldType::U64(_), &LuceneValue::Long(val)) if val >= 0 => Ok(Value::U64(val as u64)),
+        (FieldType::Date(_), &LuceneValue::Long(millis)) => {
+            millis_to_date(lucene_field_name, field_entry, millis)
+        }
+        (FieldType::F64(_), &LuceneValue::Double(val)) => Ok(Value::F64(val)),
+        _ => Err(unsupported_value(
+            lucene_field_name,
+            field_entry,
+            &format!("stored value {:?}", stored_value),
+        )),
+    }
+}
+
+/// Decodes a double encoded with Lucene's `NumericUtils.doubleToSortableLong`.
+fn sortable_long_to_f64(val: i64) -> f64 {
+    f64::from_bits((val ^ ((val >> 63) & i64::MAX)) as u64)
+}
+
+fn convert_doc_value(
+    lucene_field_name: &str,
+    field_entry: &FieldEntry,
+    doc_value: i64,
+) -> Result<Value> {
+    match field_entry.field_type() {
+        FieldType::I64(_) => Ok(Value::I64(doc_value)),
+        FieldType::U64(_) if doc_value >= 0 => Ok(Value::U64(doc_value as u64)),
+        FieldType::F64(_) => Ok(Value::F64(sortable_long_to_f64(doc_value))),
+        FieldType::Date(_) => millis_to_date(lucene_field_name, field_entry, doc_value),
+        _ => Err(unsupported_value(
+            lucene_field_name,
+            field_entry,
+            &format!("doc value {}", doc_value),
+        )),
+    }
+}
+
+/// Associates the fields of a Lucene segment to the fields of a tantivy schema.
+///
+/// The Lucene fields that are not mapped are not imported.
+#[derive(Clone, Debug, Default)]
+pub struct LuceneFieldMapping {
+    fields: Vec<(String, Field)>,
+}
+
+impl LuceneFieldMapping {
+    /// Imports the values of the Lucene field `lucene_field_name` into `field`.
+    pub fn map_field(mut self, lucene_field_name: &str, field: Field) -> LuceneFieldMapping {
+        self.fields.push((lucene_field_name.to_string(), field));
+        self
+    }
+}
+
+/// Adds the documents of a Lucene segment that are not deleted to the `IndexWriter`.
+///
+/// The documents are indexed in tantivy segments upon the next commit.
+/// Returns the number of documents added.
+pub fn import_lucene_segment(
+    index_writer: &IndexWriter,
+    lucene_segment: &LuceneSegment,
+    mapping: &LuceneFieldMapping,
+) -> Result<u64> {
+    let schema = index_writer.index().schema();
+    let documents = lucene_segment.documents(&schema, mapping)?;
+    let num_docs = documents.len() as u64;
+    for document in documents {
+        index_writer.add_document(document);
+    }
+    Ok(num_docs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import_lucene_segment, LuceneFieldMapping, LuceneSegment, LuceneValue};
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, Value, FAST, INDEXED, STORED, TEXT};
+    use crate::{DocAddress, Index, TantivyError, Term};
+    use crc32fast::Hasher;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn write_simple_text(directory: &Path, file_name: &str, content: &str) {
+        let mut hasher = Hasher::new();
+        hasher.update(content.as_bytes());
+        let content_with_footer = format!("{}checksum {:020}\n", content, hasher.finalize());
+        fs::write(directory.join(file_name), content_with_footer).unwrap();
+    }
+
+    fn f64_to_sortable_long(val: f64) -> i64 {
+        let bits = val.to_bits() as i64;
+        bits ^ ((bits >> 63) & i64::MAX)
+    }
+
+    fn numeric_doc_values(field_name: &str, values: &[Option<i64>]) -> String {
+        let min_value = values.iter().map(|val| val.unwrap_or(0)).min().unwrap();
+        let max_value = values.iter().map(|val| val.unwrap_or(0)).max().unwrap();
+        let width = ((max_value as i128 - min_value as i128) as u64)
+            .to_string()
+            .len();
+        let mut content = format!(
+            "field {}\n  type NUMERIC\n  minvalue {}\n  pattern {}\n",
+            field_name,
+            min_value,
+            "0".repeat(width)
+        );
+        for val_opt in values {
+            let delta = (val_opt.unwrap_or(0) as i128 - min_value as i128) as u64;
+            let presence = if val_opt.is_some() { "T" } else { "F" };
+            content.push_str(&format!("{:0width$}\n{}\n", delta, presence, width = width));
+        }
+        content
+    }
+
+    fn create_lucene_segment(directory: &Path) {
+        write_simple_text(
+            directory,
+            "_0.si",
+            "    version 8.4.0\n    number of documents 3\n    uses compound file false\n",
+        );
+        write_simple_text(directory, "_0_1.liv", "size 3\n  doc 0\n  doc 2\nEND\n");
+        write_simple_text(
+            directory,
+            "_0.fld",
+            "doc 0\n  field 0\n    name title\n    type string\n    value first\\\nline\n\
+             \x20 field 3\n    name count\n    type long\n    value 7\n\
+             doc 1\n  field 0\n    name title\n    type string\n    value deleted\n\
+             doc 2\n  field 0\n    name title\n    type string\n    value third\n\
+             \x20 field 4\n    name raw\n    type binary\n    value a\\\\b\n\
+             END\n",
+        );
+        write_simple_text(
+            directory,
+            "_0.pst",
+            "field body\n\
+             \x20 term fox\n    doc 0\n      freq 1\n      pos 0\n    doc 2\n      freq 1\n      pos 0\n\
+             END\n",
+        );
+        let prices = [
+            Some(f64_to_sortable_long(1.5)),
+            Some(f64_to_sortable_long(3.0)),
+            Some(f64_to_sortable_long(-2.25)),
+        ];
+        let doc_values = format!(
+            "{}field blob\n  type BINARY\n  maxlength 3\n  pattern 0\nlength 3\na\\b\nT\n\
+             length 0\n   \nF\nlength 1\nc  \nT\n{}END\n",
+            numeric_doc_values("price", &prices),
+            numeric_doc_values("count", &[Some(7), None, Some(-3)]),
+        );
+        write_simple_text(directory, "_0.dat", &doc_values);
+    }
+
+    #[test]
+    fn test_import_lucene_segment() {
+        let lucene_directory = TempDir::new().unwrap();
+        create_lucene_segment(lucene_directory.path());
+        let lucene_segment = LuceneSegment::open(lucene_directory.path(), "_0").unwrap();
+        assert_eq!(lucene_segment.max_doc(), 3);
+        assert_eq!(lucene_segment.num_docs(), 2);
+        assert!(!lucene_segment.is_alive(1));
+        assert_eq!(
+            lucene_segment.stored_values(2)[1],
+            ("raw".to_string(), LuceneValue::Bytes(b"a\\b".to_vec()))
+        );
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let count = schema_builder.add_i64_field("count", INDEXED | STORED);
+        let price = schema_builder.add_f64_field("price", FAST | STORED);
+        let raw = schema_builder.add_bytes_field("raw", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let mapping = LuceneFieldMapping::default()
+            .map_field("title", title)
+            .map_field("body", body)
+            .map_field("count", count)
+            .map_field("price", price)
+            .map_field("raw", raw);
+        assert_eq!(
+            import_lucene_segment(&index_writer, &lucene_segment, &mapping).unwrap(),
+            2
+        );
+        index_writer.commit().unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        // The postings are not imported.
+        let fox_query =
+            TermQuery::new(Term::from_field_text(body, "fox"), IndexRecordOption::Basic);
+        assert_eq!(searcher.search(&fox_query, &Count).unwrap(), 0);
+
+        let first_doc = searcher.doc(DocAddress(0, 0)).unwrap();
+        assert_eq!(
+            first_doc.get_first(title).unwrap().text(),
+            Some("first\nline")
+        );
+        assert_eq!(first_doc.get_first(body), None);
+        // The stored value has precedence over the doc value.
+        assert_eq!(first_doc.get_first(count), Some(&Value::I64(7)));
+        assert_eq!(first_doc.get_first(price), Some(&Value::F64(1.5)));
+
+        let second_doc = searcher.doc(DocAddress(0, 1)).unwrap();
+        assert_eq!(second_doc.get_first(title).unwrap().text(), Some("third"));
+        assert_eq!(second_doc.get_first(count), Some(&Value::I64(-3)));
+        assert_eq!(second_doc.get_first(price), Some(&Value::F64(-2.25)));
+    }
+
+    #[test]
+    fn test_lucene_segment_invalid_checksum() {
+        let lucene_directory = TempDir::new().unwrap();
+        create_lucene_segment(lucene_directory.path());
+        let stored_fields_path = lucene_directory.path().join("_0.fld");
+        let stored_fields = fs::read_to_string(&stored_fields_path).unwrap();
+        fs::write(&stored_fields_path, stored_fields.replace("third", "thirb")).unwrap();
+        match LuceneSegment::open(lucene_directory.path(), "_0") {
+            Err(TantivyError::DataCorruption(_)) => {}
+            _ => panic!("The checksum mismatch should be detected"),
+        }
+    }
+
+    #[test]
+    fn test_lucene_field_mapping_type_mismatch() {
+        let lucene_directory = TempDir::new().unwrap();
+        create_lucene_segment(lucene_directory.path());
+        let lucene_segment = LuceneSegment::open(lucene_directory.path(), "_0").unwrap();
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_u64_field("title", STORED);
+        let schema = schema_builder.build();
+        let mapping = LuceneFieldMapping::default().map_field("title", title);
+        match lucene_segment.documents(&schema, &mapping) {
+            Err(TantivyError::InvalidArgument(_)) => {}
+            _ => panic!("A text cannot be imported into a u64 field"),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use crate::common::BitSet;
+use crate::error::DataCorruption;
+use crate::DocId;
+use crate::Result;
+use crate::TantivyError;
+use crc32fast::Hasher;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const END: &str = "END";
+const CHECKSUM: &str = "checksum ";
+
+const SI_DOC_COUNT: &str = "    number of documents ";
+const SI_USE_COMPOUND: &str = "    uses compound file ";
+
+const STORED_DOC: &str = "doc ";
+const STORED_FIELD: &str = "  field ";
+const STORED_NAME: &str = "    name ";
+const STORED_TYPE: &str = "    type ";
+const STORED_VALUE: &str = "    value ";
+
+const DOC_VALUES_FIELD: &str = "field ";
+const DOC_VALUES_TYPE: &str = "  type ";
+const DOC_VALUES_MIN_VALUE: &str = "  minvalue ";
+const DOC_VALUES_PATTERN: &str = "  pattern ";
+
+const LIVE_DOCS_SIZE: &str = "size ";
+const LIVE_DOCS_DOC: &str = "  doc ";
+
+/// Value of a stored field of a Lucene document.
+///
+/// Lucene `int` and `long` values are read as `Long`,
+/// and `float` and `double` values as `Double`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LuceneValue {
+    /// Text value.
+    Str(String),
+    /// Binary value.
+    Bytes(Vec<u8>),
+    /// Integer value.
+    Long(i64),
+    /// Floating point value.
+    Double(f64),
+}
+
+/// Reads the lines of a file written by the Lucene `SimpleTextCodec`.
+///
+/// Within a line, newlines and backslashes are escaped with a backslash.
+struct LineReader {
+    path: PathBuf,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl LineReader {
+    fn open(path: &Path) -> Result<LineReader> {
+        Ok(LineReader {
+            path: path.to_path_buf(),
+            data: fs::read(path)?,
+            offset: 0,
+        })
+    }
+
+    fn corrupted(&self, comment: String) -> TantivyError {
+        DataCorruption::new(self.path.clone(), comment).into()
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.offset == self.data.len()
+    }
+
+    /// Reads the next line, without its newline.
+    fn read_line(&mut self) -> Result<Vec<u8>> {
+        let mut line = Vec::new();
+        loop {
+            let byte = self.next_byte()?;
+            match byte {
+                b'\\' => line.push(self.next_byte()?),
+                b'\n' => return Ok(line),
+                _ => line.push(byte),
+            }
+        }
+    }
+
+    fn next_byte(&mut self) -> Result<u8> {
+        let byte = *self
+            .data
+            .get(self.offset)
+            .ok_or_else(|| self.corrupted("Unexpected end of file.".to_string()))?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    /// Skips the next line, without unescaping it.
+    ///
+    /// The binary doc values are written without escaping.
+    fn skip_raw_line(&mut self) {
+        self.offset = self.data[self.offset..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map(|newline_pos| self.offset + newline_pos + 1)
+            .unwrap_or_else(|| self.data.len());
+    }
+
+    /// Returns the next line without consuming it.
+    fn peek_line(&mut self) -> Result<Vec<u8>> {
+        let offset = self.offset;
+        let line_res = self.read_line();
+        self.offset = offset;
+        line_res
+    }
+
+    fn next_line_starts_with(&mut self, prefix: &str) -> Result<bool> {
+        Ok(self.peek_line()?.starts_with(prefix.as_bytes()))
+    }
+
+    /// Reads a line starting with `prefix`, and returns the rest of the line.
+    fn read_value(&mut self, prefix: &str) -> Result<Vec<u8>> {
+        let line = self.read_line()?;
+        if !line.starts_with(prefix.as_bytes()) {
+            return Err(self.corrupted(format!(
+                "Expected a line starting with {:?}, got {:?}.",
+                prefix,
+                String::from_utf8_lossy(&line)
+            )));
+        }
+        Ok(line[prefix.len()..].to_vec())
+    }
+
+    fn read_str(&mut self, prefix: &str) -> Result<String> {
+        let value = self.read_value(prefix)?;
+        String::from_utf8(value).map_err(|_| self.corrupted("Invalid utf-8 text.".to_string()))
+    }
+
+    fn read_number<T: FromStr>(&mut self, prefix: &str) -> Result<T> {
+        let value = self.read_str(prefix)?;
+        value
+            .parse()
+            .map_err(|_| self.corrupted(format!("Invalid number {:?}.", value)))
+    }
+
+    fn read_end(&mut self) -> Result<()> {
+        let line = self.read_line()?;
+        if line != END.as_bytes() {
+            return Err(self.corrupted(format!(
+                "Expected {:?}, got {:?}.",
+                END,
+                String::from_utf8_lossy(&line)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks the checksum line following the `END` line, which holds the
+    /// CRC32 of all of the preceding bytes, and that it ends the file.
+    fn check_footer(&mut self) -> Result<()> {
+        let mut hasher = Hasher::new();
+        hasher.update(&self.data[..self.offset]);
+        let expected_checksum = format!("{:020}", hasher.finalize());
+        let checksum = self.read_str(CHECKSUM)?;
+        if checksum != expected_checksum {
+            return Err(self.corrupted(format!(
+                "Checksum mismatch: expected {}, got {}.",
+                expected_checksum, checksum
+            )));
+        }
+        if !self.is_at_end() {
+            return Err(self.corrupted("Unexpected data after the checksum.".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Reads the number of documents of a segment from its segment info (`.si`) file.
+///
+/// Returns an error if the segment uses a compound file.
+pub(crate) fn read_max_doc(path: &Path) -> Result<u32> {
+    let mut reader = LineReader::open(path)?;
+    let mut max_doc_opt = None;
+    while !reader.is_at_end() {
+        if reader.next_line_starts_with(SI_DOC_COUNT)? {
+            max_doc_opt = Some(reader.read_number(SI_DOC_COUNT)?);
+        } else if reader.next_line_starts_with(SI_USE_COMPOUND)? {
+            if reader.read_str(SI_USE_COMPOUND)? != "false" {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The Lucene segment {:?} uses a compound file, which is not supported.",
+                    path
+                )));
+            }
+        } else {
+            reader.read_line()?;
+        }
+    }
+    max_doc_opt.ok_or_else(|| reader.corrupted("Missing number of documents.".to_string()))
+}
+
+/// Reads the stored fields (`.fld`) file of a segment.
+pub(crate) fn read_stored_fields(path: &Path) -> Result<Vec<Vec<(String, LuceneValue)>>> {
+    let mut reader = LineReader::open(path)?;
+    let mut docs = Vec::new();
+    while reader.peek_line()? != END.as_bytes() {
+        let doc: usize = reader.read_number(STORED_DOC)?;
+        if doc != docs.len() {
+            return Err(reader.corrupted(format!("Unexpected document {}.", doc)));
+        }
+        let mut fields = Vec::new();
+        while reader.next_line_starts_with(STORED_FIELD)? {
+            reader.read_line()?;
+            let name = reader.read_str(STORED_NAME)?;
+            let value_type = reader.read_str(STORED_TYPE)?;
+            let value = match value_type.as_str() {
+                "string" => LuceneValue::Str(reader.read_str(STORED_VALUE)?),
+                "binary" => LuceneValue::Bytes(reader.read_value(STORED_VALUE)?),
+                "int" | "long" => LuceneValue::Long(reader.read_number(STORED_VALUE)?),
+                "float" | "double" => LuceneValue::Double(reader.read_number(STORED_VALUE)?),
+                _ => {
+                    return Err(
+                        reader.corrupted(format!("Unknown stored value type {:?}.", value_type))
+                    );
+                }
+            };
+            fields.push((name, value));
+        }
+        docs.push(fields);
+    }
+    reader.read_end()?;
+    reader.check_footer()?;
+    Ok(docs)
+}
+
+/// Reads the `NUMERIC` doc values of the doc values (`.dat`) file of a segment,
+/// by field name. The doc values of the other types are skipped.
+///
+/// The value of a document is `None` if it has no value for the field.
+pub(crate) fn read_numeric_doc_values(
+    path: &Path,
+    max_doc: u32,
+) -> Result<HashMap<String, Vec<Option<i64>>>> {
+    let mut reader = LineReader::open(path)?;
+    let mut fields = HashMap::new();
+    while reader.peek_line()? != END.as_bytes() {
+        let field_name = reader.read_str(DOC_VALUES_FIELD)?;
+        let doc_values_type = reader.read_str(DOC_VALUES_TYPE)?;
+        if doc_values_type != "NUMERIC" {
+            while !reader.is_at_end()
+                && !reader.next_line_starts_with(DOC_VALUES_FIELD)?
+                && reader.peek_line()? != END.as_bytes()
+            {
+                reader.skip_raw_line();
+            }
+            continue;
+        }
+        let min_value: i64 = reader.read_number(DOC_VALUES_MIN_VALUE)?;
+        reader.read_value(DOC_VALUES_PATTERN)?;
+        let mut values = Vec::with_capacity(max_doc as usize);
+        for _ in 0..max_doc {
+            // Values are stored as their difference to the minimum value.
+            let delta: u64 = reader.read_number("")?;
+            let value = min_value.wrapping_add(delta as i64);
+            match reader.read_line()?.as_slice() {
+                b"T" => values.push(Some(value)),
+                b"F" => values.push(None),
+                _ => {
+                    return Err(reader.corrupted(format!(
+                        "Invalid doc value presence for the field {:?}.",
+                        field_name
+                    )));
+                }
+            }
+        }
+        fields.insert(field_name, values);
+    }
+    reader.read_end()?;
+    reader.check_footer()?;
+    Ok(fields)
+}
+
+/// Reads a live docs (`.liv`) file, listing the documents that are not deleted.
+pub(crate) fn read_live_docs(path: &Path, max_doc: u32) -> Result<BitSet> {
+    let mut reader = LineReader::open(path)?;
+    let size: u32 = reader.read_number(LIVE_DOCS_SIZE)?;
+    if size != max_doc {
+        return Err(reader.corrupted(format!("Expected {} documents, got {}.", max_doc, size)));
+    }
+    let mut live_docs = BitSet::with_max_value(max_doc);
+    while reader.next_line_starts_with(LIVE_DOCS_DOC)? {
+        let doc: DocId = reader.read_number(LIVE_DOCS_DOC)?;
+        if doc >= max_doc {
+            return Err(reader.corrupted(format!("Invalid document {}.", doc)));
+        }
+        live_docs.insert(doc);
+    }
+    reader.read_end()?;
+    reader.check_footer()?;
+    Ok(live_docs)
+}
+
+/// Returns the path of the live docs file of the latest deletion
+/// generation of the segment, if any.
+///
+/// Live docs files are named `<segment>_<generation>.liv`,
+/// the generation being encoded in base 36.
+pub(crate) fn live_docs_path(directory: &Path, segment_name: &str) -> io::Result<Option<PathBuf>> {
+    let prefix = format!("{}_", segment_name);
+    let mut latest: Option<(u64, PathBuf)> = None;
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let generation_opt = path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.strip_prefix(prefix.as_str()))
+            .and_then(|file_name| file_name.strip_suffix(".liv"))
+            .and_then(|generation| u64::from_str_radix(generation, 36).ok());
+        if let Some(generation) = generation_opt {
+            let is_latest = match latest {
+                Some((latest_generation, _)) => generation > latest_generation,
+                None => true,
+            };
+            if is_latest {
+                latest = Some((generation, path));
+            }
+        }
+    }
+    Ok(latest.map(|(_, path)| path))
+}
@@ -11,6 +11,14 @@
 /// If the last block is incomplete, it is simply padded with zeros.
 ///
 ///
+/// If the field stores the byte offsets of its tokens, each position delta is followed
+/// by the delta of the `offset_from` of the token with the one of the previous token of
+/// the document, and by the length of the token in bytes. The positions of such a field
+/// therefore take three integers each.
+///
+/// If the field stores the payloads of its tokens, the payload of each token follows
+/// (after its offsets, if any), taking one more integer.
+///
 /// The `SegmentComponent::POSITIONSSKIP` file contains the number of bits used in each block in `u8`
 /// stream.
 ///
@@ -28,12 +36,45 @@ mod serializer;
 
 pub use self::reader::PositionReader;
 pub use self::serializer::PositionSerializer;
+use crate::schema::FieldType;
 use bitpacking::{BitPacker, BitPacker4x};
 
 const COMPRESSION_BLOCK_SIZE: usize = BitPacker4x::BLOCK_LEN;
 const LONG_SKIP_IN_BLOCKS: usize = 1_024;
 const LONG_SKIP_INTERVAL: u64 = (LONG_SKIP_IN_BLOCKS * COMPRESSION_BLOCK_SIZE) as u64;
 
+/// Describes the integers stored for each position in the positions stream
+/// of a field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PositionLayout {
+    /// The position delta is followed by the byte offsets of the token.
+    pub has_offsets: bool,
+    /// The position delta is followed by the payload of the token.
+    pub has_payloads: bool,
+}
+
+impl PositionLayout {
+    /// Returns the layout of the positions of a field.
+    pub fn for_field_type(field_type: &FieldType) -> PositionLayout {
+        PositionLayout {
+            has_offsets: field_type.has_offsets(),
+            has_payloads: field_type.has_payloads(),
+        }
+    }
+
+    /// Returns the number of integers stored for each position.
+    pub fn num_ints_per_position(self) -> usize {
+        let mut num_ints = 1;
+        if self.has_offsets {
+            num_ints += 2;
+        }
+        if self.has_payloads {
+            num_ints += 1;
+        }
+        num_ints
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
 
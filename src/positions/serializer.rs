@@ -1,6 +1,6 @@
 use crate::common::BinarySerializable;
 use crate::common::CountingWriter;
-use crate::positions::{COMPRESSION_BLOCK_SIZE, LONG_SKIP_INTERVAL};
+use crate::positions::{PositionLayout, COMPRESSION_BLOCK_SIZE, LONG_SKIP_INTERVAL};
 use bitpacking::BitPacker;
 use bitpacking::BitPacker4x;
 use std::io::{self, Write};
@@ -50,6 +50,47 @@ impl<W: io::Write> PositionSerializer<W> {
         Ok(())
     }
 
+    /// Writes the position deltas of the tokens of a document,
+    /// each interleaved with the byte offsets and the payload of the token,
+    /// as described by `layout`.
+    ///
+    /// If the layout has offsets, two integers follow the position delta:
+    /// the delta between its `offset_from` and the `offset_from` of the
+    /// previous token of the document, and its length in bytes.
+    /// If it has payloads, the payload of the token comes last.
+    ///
+    /// Tokens without offsets are recorded with the offsets `(0, 0)`,
+    /// and tokens without payload with the payload `0`.
+    pub(crate) fn write_all_with_layout(
+        &mut self,
+        layout: PositionLayout,
+        position_deltas: &[u32],
+        offsets: &[(u32, u32)],
+        payloads: &[u32],
+    ) -> io::Result<()> {
+        let mut prev_offset_from = 0u32;
+        let mut ints = [0u32; 4];
+        for (i, &position_delta) in position_deltas.iter().enumerate() {
+            ints[0] = position_delta;
+            let mut num_ints = 1;
+            if layout.has_offsets {
+                let (offset_from, offset_to) = offsets.get(i).cloned().unwrap_or((0u32, 0u32));
+                // Offsets are not necessarily increasing (e.g. synonyms),
+                // hence the wrapping arithmetic.
+                ints[1] = offset_from.wrapping_sub(prev_offset_from);
+                ints[2] = offset_to.wrapping_sub(offset_from);
+                num_ints = 3;
+                prev_offset_from = offset_from;
+            }
+            if layout.has_payloads {
+                ints[num_ints] = payloads.get(i).cloned().unwrap_or(0u32);
+                num_ints += 1;
+            }
+            self.write_all(&ints[..num_ints])?;
+        }
+        Ok(())
+    }
+
     fn flush_block(&mut self) -> io::Result<()> {
         let num_bits = self.bit_packer.num_bits(&self.block[..]);
         self.write_skiplist.write_all(&[num_bits])?;
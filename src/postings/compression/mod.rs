@@ -4,6 +4,8 @@ use bitpacking::{BitPacker, BitPacker4x};
 pub const COMPRESSION_BLOCK_SIZE: usize = BitPacker4x::BLOCK_LEN;
 const COMPRESSED_BLOCK_MAX_SIZE: usize = COMPRESSION_BLOCK_SIZE * u32::SIZE_IN_BYTES;
 
+#[cfg(feature = "simd")]
+mod simd;
 mod vint;
 
 /// Returns the size in bytes of a compressed block, given `num_bits`.
@@ -50,6 +52,8 @@ pub(crate) struct AlignedBuffer(pub [u32; COMPRESSION_BLOCK_SIZE]);
 
 pub struct BlockDecoder {
     bitpacker: BitPacker4x,
+    #[cfg(feature = "simd")]
+    simd_unpacker: Option<simd::SimdUnpacker>,
     output: AlignedBuffer,
     pub output_len: usize,
 }
@@ -62,6 +66,8 @@ impl BlockDecoder {
     pub fn with_val(val: u32) -> BlockDecoder {
         BlockDecoder {
             bitpacker: BitPacker4x::new(),
+            #[cfg(feature = "simd")]
+            simd_unpacker: simd::SimdUnpacker::new(),
             output: AlignedBuffer([val; COMPRESSION_BLOCK_SIZE]),
             output_len: 0,
         }
@@ -74,16 +80,46 @@ impl BlockDecoder {
         num_bits: u8,
     ) -> usize {
         self.output_len = COMPRESSION_BLOCK_SIZE;
+        #[cfg(feature = "simd")]
+        {
+            if let Some(simd_unpacker) = self.simd_unpacker {
+                return simd_unpacker.decompress_sorted(
+                    offset,
+                    compressed_data,
+                    &mut self.output.0,
+                    num_bits,
+                );
+            }
+        }
         self.bitpacker
             .decompress_sorted(offset, &compressed_data, &mut self.output.0, num_bits)
     }
 
     pub fn uncompress_block_unsorted(&mut self, compressed_data: &[u8], num_bits: u8) -> usize {
         self.output_len = COMPRESSION_BLOCK_SIZE;
+        #[cfg(feature = "simd")]
+        {
+            if let Some(simd_unpacker) = self.simd_unpacker {
+                return simd_unpacker.decompress(compressed_data, &mut self.output.0, num_bits);
+            }
+        }
         self.bitpacker
             .decompress(&compressed_data, &mut self.output.0, num_bits)
     }
 
+    /// Fills the output with the first values of `vals`, up to a full block.
+    ///
+    /// Returns the number of values that were written.
+    pub fn fill_from<I: Iterator<Item = u32>>(&mut self, vals: I) -> usize {
+        let mut len = 0;
+        for (dest, val) in self.output.0.iter_mut().zip(vals) {
+            *dest = val;
+            len += 1;
+        }
+        self.output_len = len;
+        len
+    }
+
     #[inline]
     pub fn output_array(&self) -> &[u32] {
         &self.output.0[..self.output_len]
@@ -0,0 +1,345 @@
+//! Explicit SIMD decoding of the bitpacked blocks of the postings.
+//!
+//! Blocks are encoded with `bitpacking`'s `BitPacker4x`: each of the 32 `u32` words
+//! of the 4 lanes of a 128-bit register packs the values `4 * k + lane`
+//! on `num_bits` bits. Sorted blocks are delta-encoded beforehand.
+//!
+//! The decoders of this module read the exact same format, but they
+//! are selected at runtime depending on the instruction sets supported by the CPU:
+//! - AVX2 on `x86` and `x86_64`, decoding two registers at a time,
+//! - NEON on `aarch64`.
+//!
+//! If none of them is available, `SimdUnpacker::new()` returns `None` and
+//! the `BitPacker4x` decoder is used.
+
+use super::COMPRESSION_BLOCK_SIZE;
+
+/// Number of values packed in each of the 4 lanes.
+const NUM_VALUES_PER_LANE: usize = COMPRESSION_BLOCK_SIZE / 4;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum InstructionSet {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Avx2,
+    #[cfg(target_arch = "aarch64")]
+    Neon,
+}
+
+/// Decoder of bitpacked blocks relying on the best instruction set available.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SimdUnpacker {
+    instruction_set: InstructionSet,
+}
+
+impl SimdUnpacker {
+    /// Returns a decoder for the current CPU, or `None` if
+    /// none of the supported instruction sets is available.
+    pub fn new() -> Option<SimdUnpacker> {
+        detect_instruction_set().map(|instruction_set| SimdUnpacker { instruction_set })
+    }
+
+    /// Decodes a block of `num_bits` bits integers into `output`.
+    ///
+    /// Returns the number of bytes read from `compressed`.
+    pub fn decompress(
+        self,
+        compressed: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        num_bits: u8,
+    ) -> usize {
+        self.unpack(compressed, output, num_bits, None)
+    }
+
+    /// Decodes a block of delta-encoded sorted integers into `output`,
+    /// given the value preceding the block.
+    ///
+    /// Returns the number of bytes read from `compressed`.
+    pub fn decompress_sorted(
+        self,
+        offset: u32,
+        compressed: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        num_bits: u8,
+    ) -> usize {
+        self.unpack(compressed, output, num_bits, Some(offset))
+    }
+
+    fn unpack(
+        self,
+        compressed: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        num_bits: u8,
+        offset_opt: Option<u32>,
+    ) -> usize {
+        assert!(num_bits <= 32, "Invalid number of bits {}", num_bits);
+        let num_bytes = num_bits as usize * COMPRESSION_BLOCK_SIZE / 8;
+        // the decoders below rely on this check to read `compressed`
+        // without any bound checking.
+        assert!(
+            compressed.len() >= num_bytes,
+            "Compressed block too short: {} bytes instead of {}",
+            compressed.len(),
+            num_bytes
+        );
+        match self.instruction_set {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            InstructionSet::Avx2 => unsafe {
+                avx2::unpack(compressed, output, u32::from(num_bits), offset_opt)
+            },
+            #[cfg(target_arch = "aarch64")]
+            InstructionSet::Neon => unsafe {
+                neon::unpack(compressed, output, u32::from(num_bits), offset_opt)
+            },
+        }
+        num_bytes
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn detect_instruction_set() -> Option<InstructionSet> {
+    if is_x86_feature_detected!("avx2") {
+        Some(InstructionSet::Avx2)
+    } else {
+        None
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn detect_instruction_set() -> Option<InstructionSet> {
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        Some(InstructionSet::Neon)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn detect_instruction_set() -> Option<InstructionSet> {
+    None
+}
+
+// Returns the index of the word holding the first bit of the `k`-th
+// value of each lane, and the position of this bit within the word.
+#[inline(always)]
+fn bit_position(k: usize, num_bits: u32) -> (usize, u32) {
+    let bit = k as u32 * num_bits;
+    ((bit / 32) as usize, bit % 32)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod avx2 {
+    use super::{bit_position, NUM_VALUES_PER_LANE};
+    use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    const HALF: usize = NUM_VALUES_PER_LANE / 2;
+
+    // Each iteration decodes the registers `k` and `k + 16`, respectively
+    // in the low and the high half of a 256-bit register.
+    // Shifting by 32 bits or more yields 0, which takes care of the values
+    // that do not overlap two words.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn unpack(
+        compressed: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        num_bits: u32,
+        offset_opt: Option<u32>,
+    ) {
+        let input = compressed.as_ptr() as *const __m128i;
+        let output_ptr = output.as_mut_ptr() as *mut __m128i;
+        if num_bits == 0 {
+            output.iter_mut().for_each(|val| *val = 0u32);
+        } else {
+            let mask = _mm256_set1_epi32((((1u64 << num_bits) - 1) as u32) as i32);
+            for k in 0..HALF {
+                let (word_lo, shift_lo) = bit_position(k, num_bits);
+                let (word_hi, shift_hi) = bit_position(k + HALF, num_bits);
+                let words = _mm256_inserti128_si256::<1>(
+                    _mm256_castsi128_si256(_mm_loadu_si128(input.add(word_lo))),
+                    _mm_loadu_si128(input.add(word_hi)),
+                );
+                let shifts = _mm256_setr_epi32(
+                    shift_lo as i32,
+                    shift_lo as i32,
+                    shift_lo as i32,
+                    shift_lo as i32,
+                    shift_hi as i32,
+                    shift_hi as i32,
+                    shift_hi as i32,
+                    shift_hi as i32,
+                );
+                let mut vals = _mm256_srlv_epi32(words, shifts);
+                let overlap_lo = shift_lo + num_bits > 32;
+                let overlap_hi = shift_hi + num_bits > 32;
+                if overlap_lo || overlap_hi {
+                    // the next word is only read if it holds some bits of the values.
+                    let next_word_lo = if overlap_lo {
+                        _mm_loadu_si128(input.add(word_lo + 1))
+                    } else {
+                        _mm_setzero_si128()
+                    };
+                    let next_word_hi = if overlap_hi {
+                        _mm_loadu_si128(input.add(word_hi + 1))
+                    } else {
+                        _mm_setzero_si128()
+                    };
+                    let next_words = _mm256_inserti128_si256::<1>(
+                        _mm256_castsi128_si256(next_word_lo),
+                        next_word_hi,
+                    );
+                    let (left_shift_lo, left_shift_hi) = (32 - shift_lo, 32 - shift_hi);
+                    let left_shifts = _mm256_setr_epi32(
+                        left_shift_lo as i32,
+                        left_shift_lo as i32,
+                        left_shift_lo as i32,
+                        left_shift_lo as i32,
+                        left_shift_hi as i32,
+                        left_shift_hi as i32,
+                        left_shift_hi as i32,
+                        left_shift_hi as i32,
+                    );
+                    vals = _mm256_or_si256(vals, _mm256_sllv_epi32(next_words, left_shifts));
+                }
+                vals = _mm256_and_si256(vals, mask);
+                _mm_storeu_si128(output_ptr.add(k), _mm256_castsi256_si128(vals));
+                _mm_storeu_si128(
+                    output_ptr.add(k + HALF),
+                    _mm256_extracti128_si256::<1>(vals),
+                );
+            }
+        }
+        if let Some(offset) = offset_opt {
+            integrate_deltas(output_ptr, offset);
+        }
+    }
+
+    // Computes the prefix sums of the deltas, register by register.
+    #[target_feature(enable = "avx2")]
+    unsafe fn integrate_deltas(output_ptr: *mut __m128i, offset: u32) {
+        let mut prev = _mm_set1_epi32(offset as i32);
+        for k in 0..NUM_VALUES_PER_LANE {
+            let deltas = _mm_loadu_si128(output_ptr.add(k));
+            let sums = _mm_add_epi32(deltas, _mm_slli_si128::<8>(deltas));
+            let sums = _mm_add_epi32(sums, _mm_slli_si128::<4>(sums));
+            let vals = _mm_add_epi32(sums, _mm_shuffle_epi32::<0xff>(prev));
+            _mm_storeu_si128(output_ptr.add(k), vals);
+            prev = vals;
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use super::{bit_position, NUM_VALUES_PER_LANE};
+    use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn unpack(
+        compressed: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        num_bits: u32,
+        offset_opt: Option<u32>,
+    ) {
+        let input = compressed.as_ptr() as *const u32;
+        let output_ptr = output.as_mut_ptr();
+        if num_bits == 0 {
+            output.iter_mut().for_each(|val| *val = 0u32);
+        } else {
+            let mask = vdupq_n_u32(((1u64 << num_bits) - 1) as u32);
+            for k in 0..NUM_VALUES_PER_LANE {
+                let (word, shift) = bit_position(k, num_bits);
+                let words = vld1q_u32(input.add(word * 4));
+                // shifting by a negative amount shifts to the right.
+                let mut vals = vshlq_u32(words, vdupq_n_s32(-(shift as i32)));
+                if shift + num_bits > 32 {
+                    let next_words = vld1q_u32(input.add((word + 1) * 4));
+                    vals = vorrq_u32(vals, vshlq_u32(next_words, vdupq_n_s32(32 - shift as i32)));
+                }
+                vst1q_u32(output_ptr.add(k * 4), vandq_u32(vals, mask));
+            }
+        }
+        if let Some(offset) = offset_opt {
+            // Computes the prefix sums of the deltas, register by register.
+            let zero = vdupq_n_u32(0u32);
+            let mut prev = offset;
+            for k in 0..NUM_VALUES_PER_LANE {
+                let deltas = vld1q_u32(output_ptr.add(k * 4));
+                let sums = vaddq_u32(deltas, vextq_u32::<2>(zero, deltas));
+                let sums = vaddq_u32(sums, vextq_u32::<3>(zero, sums));
+                let vals = vaddq_u32(sums, vdupq_n_u32(prev));
+                vst1q_u32(output_ptr.add(k * 4), vals);
+                prev = vgetq_lane_u32::<3>(vals);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::SimdUnpacker;
+    use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+    use bitpacking::{BitPacker, BitPacker4x};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_block(rng: &mut StdRng, num_bits: u8) -> Vec<u32> {
+        let max_val = ((1u64 << num_bits) - 1) as u32;
+        (0..COMPRESSION_BLOCK_SIZE)
+            .map(|_| rng.gen_range(0, u64::from(max_val) + 1) as u32)
+            .collect()
+    }
+
+    #[test]
+    fn test_simd_unpacker() {
+        let unpacker = match SimdUnpacker::new() {
+            Some(unpacker) => unpacker,
+            None => return,
+        };
+        let bitpacker = BitPacker4x::new();
+        let mut rng = StdRng::from_seed([3u8; 32]);
+        let mut compressed = [0u8; COMPRESSION_BLOCK_SIZE * 4];
+        let mut output = [0u32; COMPRESSION_BLOCK_SIZE];
+        for num_bits in 0..=32u8 {
+            let vals = random_block(&mut rng, num_bits);
+            let num_bytes = bitpacker.compress(&vals, &mut compressed[..], num_bits);
+            assert_eq!(
+                unpacker.decompress(&compressed[..num_bytes], &mut output, num_bits),
+                num_bytes
+            );
+            assert_eq!(&output[..], &vals[..]);
+        }
+    }
+
+    #[test]
+    fn test_simd_unpacker_sorted() {
+        let unpacker = match SimdUnpacker::new() {
+            Some(unpacker) => unpacker,
+            None => return,
+        };
+        let bitpacker = BitPacker4x::new();
+        let mut rng = StdRng::from_seed([5u8; 32]);
+        let mut compressed = [0u8; COMPRESSION_BLOCK_SIZE * 4];
+        let mut output = [0u32; COMPRESSION_BLOCK_SIZE];
+        for max_delta in &[0u32, 1, 2, 5, 100, 1_000, 100_000, 1 << 24] {
+            let offset = rng.gen_range(0, 1_000u32);
+            let mut vals = Vec::with_capacity(COMPRESSION_BLOCK_SIZE);
+            let mut val = offset;
+            for _ in 0..COMPRESSION_BLOCK_SIZE {
+                val += rng.gen_range(0, max_delta + 1);
+                vals.push(val);
+            }
+            let num_bits = bitpacker.num_bits_sorted(offset, &vals);
+            let num_bytes = bitpacker.compress_sorted(offset, &vals, &mut compressed[..], num_bits);
+            assert_eq!(
+                unpacker.decompress_sorted(offset, &compressed[..num_bytes], &mut output, num_bits),
+                num_bytes
+            );
+            assert_eq!(&output[..], &vals[..]);
+        }
+    }
+}
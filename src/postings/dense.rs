@@ -0,0 +1,180 @@
+/*!
+Dense postings.
+
+When a term appears in a large fraction of the documents spanned by its
+postings list, a bitset of these documents is both compact and cheap to
+decode. Skipping then boils down to jumping to the right bit.
+
+Dense postings are only used for terms that do not record term
+frequencies, and whose number of documents would otherwise call for skip
+data. They are encoded as follows:
+
+* `VInt(0)` in place of the skip data length (skip data is never empty),
+* `VInt(first_doc)`,
+* `VInt(num_bits)`, the number of documents spanned by the bitset,
+* the bitset itself, as little endian `u64` words. Bit `i` is set iff
+  `first_doc + i` contains the term.
+*/
+
+use crate::common::{BinarySerializable, VInt};
+use crate::DocId;
+use byteorder::{ByteOrder, LittleEndian};
+use owned_read::OwnedRead;
+use std::io::{self, Write};
+
+/// A postings list is encoded as a bitset if the term appears in at
+/// least one document out of `DENSE_POSTINGS_RATIO` documents of its span.
+const DENSE_POSTINGS_RATIO: u64 = 8;
+
+/// Returns the number of `u64` words of a bitset of `num_bits` bits.
+// `usize::div_ceil` is not available on the supported Rust versions.
+#[allow(clippy::manual_div_ceil)]
+fn num_words(num_bits: u32) -> usize {
+    (num_bits as usize + 63) / 64
+}
+
+/// Returns true iff the sorted list of documents `docs` should be
+/// encoded as a bitset.
+pub(crate) fn is_dense(docs: &[DocId]) -> bool {
+    match (docs.first(), docs.last()) {
+        (Some(&first_doc), Some(&last_doc)) => {
+            let span = u64::from(last_doc - first_doc) + 1;
+            docs.len() as u64 * DENSE_POSTINGS_RATIO >= span
+        }
+        _ => false,
+    }
+}
+
+/// Serializes a non-empty sorted list of documents as a bitset.
+pub(crate) fn serialize_dense<W: Write>(docs: &[DocId], write: &mut W) -> io::Result<()> {
+    let first_doc = docs[0];
+    let num_bits = docs[docs.len() - 1] - first_doc + 1;
+    VInt(0).serialize(write)?;
+    VInt(u64::from(first_doc)).serialize(write)?;
+    VInt(u64::from(num_bits)).serialize(write)?;
+    let mut words = vec![0u64; num_words(num_bits)];
+    for &doc in docs {
+        let bit = doc - first_doc;
+        words[(bit / 64) as usize] |= 1u64 << (bit % 64);
+    }
+    for word in words {
+        word.serialize(write)?;
+    }
+    Ok(())
+}
+
+/// Reader over the documents of dense postings.
+pub(crate) struct DenseDocs {
+    data: OwnedRead,
+    first_doc: DocId,
+    num_bits: u32,
+    // index of the next bit to consider.
+    cursor: u32,
+}
+
+impl DenseDocs {
+    /// Opens dense postings, given the data following the `VInt(0)` marker.
+    pub fn open(mut data: OwnedRead) -> DenseDocs {
+        let first_doc = VInt::deserialize(&mut data).expect("Data corrupted").0 as DocId;
+        let num_bits = VInt::deserialize(&mut data).expect("Data corrupted").0 as u32;
+        let num_bytes = num_words(num_bits) * 8;
+        data.clip(num_bytes);
+        DenseDocs {
+            data,
+            first_doc,
+            num_bits,
+            cursor: 0,
+        }
+    }
+
+    /// Moves the cursor to the first document greater or equal to `target`.
+    ///
+    /// The cursor never goes backward.
+    pub fn seek(&mut self, target: DocId) {
+        let bit = target.saturating_sub(self.first_doc).min(self.num_bits);
+        self.cursor = self.cursor.max(bit);
+    }
+
+    /// Returns an iterator over the documents following the cursor.
+    ///
+    /// The cursor is not moved: call `seek` with the successor of the last
+    /// consumed document.
+    pub fn docs(&self) -> impl Iterator<Item = DocId> + '_ {
+        let bytes = self.data.as_ref();
+        let first_doc = self.first_doc;
+        let start_word = (self.cursor / 64) as usize;
+        let num_words = num_words(self.num_bits);
+        let start_mask = !0u64 << (self.cursor % 64);
+        (start_word..num_words).flat_map(move |word_id| {
+            let mut word = LittleEndian::read_u64(&bytes[word_id * 8..]);
+            if word_id == start_word {
+                word &= start_mask;
+            }
+            let word_first_doc = first_doc + (word_id as u32) * 64;
+            BitIter(word).map(move |bit| word_first_doc + bit)
+        })
+    }
+}
+
+// Iterates over the positions of the bits set in a word.
+struct BitIter(u64);
+
+impl Iterator for BitIter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.0 == 0 {
+            return None;
+        }
+        let bit = self.0.trailing_zeros();
+        self.0 ^= 1u64 << bit;
+        Some(bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{is_dense, serialize_dense, DenseDocs};
+    use crate::common::{BinarySerializable, VInt};
+    use owned_read::OwnedRead;
+
+    fn open(docs: &[u32]) -> DenseDocs {
+        let mut buffer = Vec::new();
+        serialize_dense(docs, &mut buffer).unwrap();
+        let mut data = OwnedRead::new(buffer);
+        assert_eq!(VInt::deserialize(&mut data).unwrap().0, 0);
+        DenseDocs::open(data)
+    }
+
+    #[test]
+    fn test_is_dense() {
+        assert!(!is_dense(&[]));
+        assert!(is_dense(&[3]));
+        assert!(is_dense(&[0, 8]));
+        assert!(!is_dense(&[0, 16]));
+        let docs: Vec<u32> = (0..100).map(|i| 1_000 + i * 8).collect();
+        assert!(is_dense(&docs[..]));
+        let docs: Vec<u32> = (0..100).map(|i| i * 9).collect();
+        assert!(!is_dense(&docs[..]));
+    }
+
+    #[test]
+    fn test_dense_docs() {
+        let docs: Vec<u32> = (0..500u32).filter(|i| i % 3 != 1).map(|i| 70 + i).collect();
+        let mut dense_docs = open(&docs[..]);
+        assert_eq!(dense_docs.docs().collect::<Vec<_>>(), docs);
+        dense_docs.seek(10);
+        assert_eq!(dense_docs.docs().next(), Some(70));
+        dense_docs.seek(200);
+        assert_eq!(dense_docs.docs().next(), Some(201));
+        dense_docs.seek(202);
+        assert_eq!(dense_docs.docs().next(), Some(202));
+        dense_docs.seek(100);
+        assert_eq!(dense_docs.docs().next(), Some(202));
+        dense_docs.seek(568);
+        assert_eq!(dense_docs.docs().next(), Some(568));
+        dense_docs.seek(569);
+        assert_eq!(dense_docs.docs().next(), None);
+    }
+}
@@ -4,6 +4,7 @@ Postings module (also called inverted index)
 
 mod block_search;
 pub(crate) mod compression;
+mod dense;
 /// Postings module
 ///
 /// Postings, also called inverted lists, is the key datastructure
@@ -34,6 +35,12 @@ pub(crate) use self::stacker::compute_table_size;
 pub use crate::common::HasLen;
 
 pub(crate) const USE_SKIP_INFO_LIMIT: u32 = COMPRESSION_BLOCK_SIZE as u32;
+
+/// Default number of entries of a level of the skip data covered
+/// by each entry of the level above.
+///
+/// See `IndexSettings::postings_skip_interval`.
+pub const DEFAULT_POSTINGS_SKIP_INTERVAL: usize = 8;
 pub(crate) type UnorderedTermId = u64;
 
 #[cfg_attr(feature = "cargo-clippy", allow(clippy::enum_variant_names))]
@@ -49,8 +56,10 @@ pub mod tests {
 
     use super::*;
     use crate::core::Index;
+    use crate::core::IndexSettings;
     use crate::core::SegmentComponent;
     use crate::core::SegmentReader;
+    use crate::directory::RAMDirectory;
     use crate::docset::{DocSet, SkipResult};
     use crate::fieldnorm::FieldNormReader;
     use crate::indexer::operation::AddOperation;
@@ -60,9 +69,12 @@ pub mod tests {
     use crate::schema::{Document, Schema, Term, INDEXED, STRING, TEXT};
     use crate::schema::{Field, TextOptions};
     use crate::schema::{IndexRecordOption, TextFieldIndexing};
-    use crate::tokenizer::{SimpleTokenizer, MAX_TOKEN_LEN};
+    use crate::tokenizer::{
+        SimpleTokenizer, Token, TokenFilter, TokenStream, Tokenizer, MAX_TOKEN_LEN,
+    };
     use crate::DocId;
     use crate::Score;
+    use futures::Future;
     use once_cell::sync::Lazy;
     use rand::rngs::StdRng;
     use rand::{Rng, SeedableRng};
@@ -354,6 +366,295 @@ pub mod tests {
         assert_eq!(&positions[..], &[1u32, 4]);
     }
 
+    #[test]
+    fn test_skip_multi_level() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let settings = IndexSettings {
+            postings_skip_interval: Some(2),
+            ..IndexSettings::default()
+        };
+        let index = Index::create_with_settings(RAMDirectory::create(), schema, settings).unwrap();
+        let num_docs = 20_000u32;
+        let term_freq = |doc: DocId| 1 + doc % 4;
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 30_000_000).unwrap();
+            for doc in 0..num_docs {
+                let text = if doc % 3 == 0 {
+                    "b".to_string()
+                } else {
+                    vec!["a"; term_freq(doc) as usize].join(" b ")
+                };
+                index_writer.add_document(doc!(text_field => text));
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.reader().unwrap().searcher();
+        let inverted_index = searcher.segment_reader(0).inverted_index(text_field);
+        let term_a = Term::from_field_text(text_field, "a");
+        let mut positions = Vec::new();
+        for &step in &[1, 997, 5_000] {
+            let mut postings = inverted_index
+                .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)
+                .unwrap();
+            let mut target = 0u32;
+            while target < num_docs {
+                let expected_doc = (target..num_docs).find(|doc| doc % 3 != 0).unwrap();
+                assert_ne!(postings.skip_next(target), SkipResult::End);
+                assert_eq!(postings.doc(), expected_doc);
+                assert_eq!(postings.term_freq(), term_freq(expected_doc));
+                postings.positions(&mut positions);
+                let expected_positions: Vec<u32> =
+                    (0..term_freq(expected_doc)).map(|i| i * 2).collect();
+                assert_eq!(positions, expected_positions);
+                target = expected_doc + step;
+            }
+        }
+    }
+
+    #[test]
+    fn test_offsets() {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("en_stem")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_offsets(true),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for _ in 0..2 {
+            for i in 0..200 {
+                if i % 2 == 0 {
+                    index_writer.add_document(doc!(text_field => "Running fast, the runner runs."));
+                } else {
+                    index_writer.add_document(doc!(text_field => "The runner ran."));
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        let term_run = Term::from_field_text(text_field, "run");
+        let check_postings = |segment_reader: &SegmentReader, num_docs: u32| {
+            let mut positions = Vec::new();
+            let mut offsets = Vec::new();
+            let inverted_index = segment_reader.inverted_index(text_field);
+            let mut postings = inverted_index
+                .read_postings(&term_run, IndexRecordOption::WithFreqsAndPositions)
+                .unwrap();
+            assert!(postings.advance());
+            postings.offsets(&mut offsets);
+            assert_eq!(&offsets[..], &[(0, 7), (25, 29)]);
+            assert_eq!(postings.skip_next(num_docs - 2), SkipResult::Reached);
+            postings.positions(&mut positions);
+            postings.offsets(&mut offsets);
+            assert_eq!(&positions[..], &[0, 4]);
+            assert_eq!(&offsets[..], &[(0, 7), (25, 29)]);
+            assert!(!postings.advance());
+
+            // Offsets are not available without positions.
+            let mut postings = inverted_index
+                .read_postings(&term_run, IndexRecordOption::WithFreqs)
+                .unwrap();
+            assert!(postings.advance());
+            postings.offsets(&mut offsets);
+            assert!(offsets.is_empty());
+        };
+        {
+            let searcher = index.reader().unwrap().searcher();
+            assert_eq!(searcher.segment_readers().len(), 2);
+            check_postings(searcher.segment_reader(0), 200);
+        }
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        check_postings(searcher.segment_reader(0), 400);
+    }
+
+    /// Attaches to each token a payload equal to its position plus one.
+    #[derive(Clone)]
+    struct PositionPayloadFilter;
+
+    impl<TailTokenStream: TokenStream> TokenFilter<TailTokenStream> for PositionPayloadFilter {
+        type ResultTokenStream = PositionPayloadStream<TailTokenStream>;
+
+        fn transform(&self, token_stream: TailTokenStream) -> Self::ResultTokenStream {
+            PositionPayloadStream { tail: token_stream }
+        }
+    }
+
+    struct PositionPayloadStream<TailTokenStream> {
+        tail: TailTokenStream,
+    }
+
+    impl<TailTokenStream: TokenStream> TokenStream for PositionPayloadStream<TailTokenStream> {
+        fn advance(&mut self) -> bool {
+            if !self.tail.advance() {
+                return false;
+            }
+            let token = self.tail.token_mut();
+            token.payload = token.position as u32 + 1;
+            true
+        }
+
+        fn token(&self) -> &Token {
+            self.tail.token()
+        }
+
+        fn token_mut(&mut self) -> &mut Token {
+            self.tail.token_mut()
+        }
+    }
+
+    #[test]
+    fn test_payloads() {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("payloads")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_offsets(true)
+                .set_payloads(true),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        index
+            .tokenizers()
+            .register("payloads", SimpleTokenizer.filter(PositionPayloadFilter));
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for _ in 0..2 {
+            index_writer.add_document(doc!(text_field => "a b"));
+            index_writer.add_document(doc!(text_field => "c"));
+            index_writer.add_document(doc!(text_field => "b a a"));
+            index_writer.commit().unwrap();
+        }
+        let term_a = Term::from_field_text(text_field, "a");
+        let check_postings = |segment_reader: &SegmentReader| {
+            let mut positions = Vec::new();
+            let mut offsets = Vec::new();
+            let mut payloads = Vec::new();
+            let inverted_index = segment_reader.inverted_index(text_field);
+            let mut postings = inverted_index
+                .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)
+                .unwrap();
+            assert!(postings.advance());
+            postings.payloads(&mut payloads);
+            assert_eq!(&payloads[..], &[1]);
+            assert!(postings.advance());
+            postings.positions(&mut positions);
+            postings.offsets(&mut offsets);
+            postings.payloads(&mut payloads);
+            assert_eq!(&positions[..], &[1, 2]);
+            assert_eq!(&offsets[..], &[(2, 3), (4, 5)]);
+            assert_eq!(&payloads[..], &[2, 3]);
+        };
+        {
+            let searcher = index.reader().unwrap().searcher();
+            assert_eq!(searcher.segment_readers().len(), 2);
+            check_postings(searcher.segment_reader(0));
+        }
+        index_writer.delete_term(Term::from_field_text(text_field, "c"));
+        index_writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        check_postings(searcher.segment_reader(0));
+    }
+
+    #[test]
+    fn test_max_doc_freq() {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_max_doc_freq(2),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "a b"));
+        index_writer.add_document(doc!(text_field => "a c"));
+        index_writer.add_document(doc!(text_field => "a b"));
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(text_field => "a b"));
+        index_writer.add_document(doc!(text_field => "d"));
+        index_writer.commit().unwrap();
+
+        let term_a = Term::from_field_text(text_field, "a");
+        let term_b = Term::from_field_text(text_field, "b");
+        let term_c = Term::from_field_text(text_field, "c");
+        let num_docs = |segment_reader: &SegmentReader, term: &Term| {
+            let inverted_index = segment_reader.inverted_index(text_field);
+            let mut postings = inverted_index
+                .read_postings(term, IndexRecordOption::WithFreqsAndPositions)
+                .unwrap();
+            let mut num_docs = 0;
+            while postings.advance() {
+                num_docs += 1;
+            }
+            num_docs
+        };
+        {
+            let searcher = index.reader().unwrap().searcher();
+            assert_eq!(searcher.segment_readers().len(), 2);
+            assert_eq!(searcher.doc_freq(&term_a), 4);
+            let segment_reader = searcher
+                .segment_readers()
+                .iter()
+                .find(|segment_reader| segment_reader.max_doc() == 3)
+                .unwrap();
+            let inverted_index = segment_reader.inverted_index(text_field);
+            let term_info_a = inverted_index.get_term_info(&term_a).unwrap();
+            assert!(inverted_index.is_pruned(&term_info_a));
+            assert_eq!(term_info_a.doc_freq, 3);
+            assert_eq!(num_docs(segment_reader, &term_a), 0);
+            let term_info_b = inverted_index.get_term_info(&term_b).unwrap();
+            assert!(!inverted_index.is_pruned(&term_info_b));
+            assert_eq!(num_docs(segment_reader, &term_b), 2);
+            // fieldnorms still account for pruned terms.
+            let fieldnorm_reader = segment_reader.get_fieldnorms_reader(text_field);
+            assert_eq!(fieldnorm_reader.fieldnorm(0), 2);
+        }
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer
+            .merge(&segment_ids)
+            .expect("Failed to initiate merge")
+            .wait()
+            .expect("Merging failed");
+        index_writer.wait_merging_threads().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let inverted_index = segment_reader.inverted_index(text_field);
+        // `b` appears in 3 documents once the segments are merged.
+        for (term, doc_freq) in &[(&term_a, 4), (&term_b, 3)] {
+            let term_info = inverted_index.get_term_info(term).unwrap();
+            assert!(inverted_index.is_pruned(&term_info));
+            assert_eq!(term_info.doc_freq, *doc_freq);
+            assert_eq!(num_docs(segment_reader, term), 0);
+        }
+        assert_eq!(num_docs(segment_reader, &term_c), 1);
+    }
+
     #[test]
     fn test_skip_next() {
         let term_0 = Term::from_field_u64(Field(0), 0);
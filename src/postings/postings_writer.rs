@@ -1,7 +1,9 @@
 use super::stacker::{Addr, MemoryArena, TermHashMap};
 
+use crate::fastfield::MultiValueIntFastFieldWriter;
 use crate::postings::recorder::{
-    BufferLender, NothingRecorder, Recorder, TFAndPositionRecorder, TermFrequencyRecorder,
+    BufferLender, NothingRecorder, Recorder, TFAndPositionRecorder,
+    TFPositionOffsetAndPayloadRecorder, TermFrequencyRecorder,
 };
 use crate::postings::UnorderedTermId;
 use crate::postings::{FieldSerializer, InvertedIndexSerializer};
@@ -20,7 +22,7 @@ use std::ops::DerefMut;
 
 fn posting_from_field_entry(field_entry: &FieldEntry) -> Box<dyn PostingsWriter> {
     match *field_entry.field_type() {
-        FieldType::Str(ref text_options) => text_options
+        FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => text_options
             .get_indexing_options()
             .map(|indexing_options| match indexing_options.index_option() {
                 IndexRecordOption::Basic => {
@@ -29,8 +31,13 @@ fn posting_from_field_entry(field_entry: &FieldEntry) -> Box<dyn PostingsWriter>
                 IndexRecordOption::WithFreqs => {
                     SpecializedPostingsWriter::<TermFrequencyRecorder>::new_boxed()
                 }
-                IndexRecordOption::WithFreqsAndPositions => {
-                    SpecializedPostingsWriter::<TFAndPositionRecorder>::new_boxed()
+                IndexRecordOption::WithFreqsAndPositions
+                | IndexRecordOption::WithFreqsAndPositionsAndOffsets => {
+                    if indexing_options.has_offsets() || indexing_options.has_payloads() {
+                        SpecializedPostingsWriter::<TFPositionOffsetAndPayloadRecorder>::new_boxed()
+                    } else {
+                        SpecializedPostingsWriter::<TFAndPositionRecorder>::new_boxed()
+                    }
                 }
             })
             .unwrap_or_else(|| SpecializedPostingsWriter::<NothingRecorder>::new_boxed()),
@@ -38,12 +45,9 @@ fn posting_from_field_entry(field_entry: &FieldEntry) -> Box<dyn PostingsWriter>
         | FieldType::I64(_)
         | FieldType::F64(_)
         | FieldType::Date(_)
-        | FieldType::HierarchicalFacet => SpecializedPostingsWriter::<NothingRecorder>::new_boxed(),
-        FieldType::Bytes => {
-            // FieldType::Bytes cannot actually be indexed.
-            // TODO fix during the indexer refactoring described in #276
-            SpecializedPostingsWriter::<NothingRecorder>::new_boxed()
-        }
+        | FieldType::GeoPoint(_)
+        | FieldType::HierarchicalFacet
+        | FieldType::Bytes(_) => SpecializedPostingsWriter::<NothingRecorder>::new_boxed(),
     }
 }
 
@@ -104,16 +108,19 @@ impl MultiFieldPostingsWriter {
     pub fn index_text(
         &mut self,
         doc: DocId,
-        field: Field,
+        term_prefix: &Term,
         token_stream: &mut dyn TokenStream,
+        term_id_fast_field_writer_opt: Option<&mut MultiValueIntFastFieldWriter>,
     ) -> u32 {
-        let postings_writer = self.per_field_postings_writers[field.0 as usize].deref_mut();
+        let postings_writer =
+            self.per_field_postings_writers[term_prefix.field().0 as usize].deref_mut();
         postings_writer.index_text(
             &mut self.term_index,
             doc,
-            field,
+            term_prefix,
             token_stream,
             &mut self.heap,
+            term_id_fast_field_writer_opt,
         )
     }
 
@@ -156,8 +163,13 @@ impl MultiFieldPostingsWriter {
                         .collect();
                     unordered_term_mappings.insert(field, mapping);
                 }
-                FieldType::U64(_) | FieldType::I64(_) | FieldType::F64(_) | FieldType::Date(_) => {}
-                FieldType::Bytes => {}
+                FieldType::U64(_)
+                | FieldType::I64(_)
+                | FieldType::F64(_)
+                | FieldType::Date(_)
+                | FieldType::GeoPoint(_)
+                | FieldType::Bytes(_)
+                | FieldType::Json(_) => {}
             }
 
             let postings_writer = &self.per_field_postings_writers[field.0 as usize];
@@ -194,6 +206,25 @@ pub trait PostingsWriter {
         pos: u32,
         term: &Term,
         heap: &mut MemoryArena,
+    ) -> UnorderedTermId {
+        self.subscribe_token(term_index, doc, pos, (0u32, 0u32), 0u32, term, heap)
+    }
+
+    /// Record that a document contains a term at a given position,
+    /// for a token spanning the bytes `[offset_from, offset_to)` of the text,
+    /// and carrying the given payload.
+    ///
+    /// The offsets and the payload are only recorded if the field stores them.
+    #[allow(clippy::too_many_arguments)]
+    fn subscribe_token(
+        &mut self,
+        term_index: &mut TermHashMap,
+        doc: DocId,
+        pos: u32,
+        offsets: (u32, u32),
+        payload: u32,
+        term: &Term,
+        heap: &mut MemoryArena,
     ) -> UnorderedTermId;
 
     /// Serializes the postings on disk.
@@ -207,20 +238,52 @@ pub trait PostingsWriter {
     ) -> io::Result<()>;
 
     /// Tokenize a text and subscribe all of its token.
+    ///
+    /// The terms are built by appending the text of each token
+    /// to `term_prefix`.
+    ///
+    /// If a term id fast field writer is given, the `UnorderedTermId`
+    /// of each token is also pushed to it.
+    ///
+    /// Returns the number of tokens, not counting the tokens stacked
+    /// at the same position as the previous token.
     fn index_text(
         &mut self,
         term_index: &mut TermHashMap,
         doc_id: DocId,
-        field: Field,
+        term_prefix: &Term,
         token_stream: &mut dyn TokenStream,
         heap: &mut MemoryArena,
+        mut term_id_fast_field_writer_opt: Option<&mut MultiValueIntFastFieldWriter>,
     ) -> u32 {
-        let mut term = Term::for_field(field);
+        let mut term = term_prefix.clone();
+        let prefix_len = term.as_slice().len();
+        // Tokens stacked at the same position as the previous token
+        // (e.g. synonyms) do not count in the number of tokens.
+        let mut num_tokens = 0u32;
+        let mut last_position = None;
         let mut sink = |token: &Token| {
+            if last_position != Some(token.position) {
+                num_tokens += 1;
+                last_position = Some(token.position);
+            }
             // We skip all tokens with a len greater than u16.
             if token.text.len() <= MAX_TOKEN_LEN {
-                term.set_text(token.text.as_str());
-                self.subscribe(term_index, doc_id, token.position as u32, &term, heap);
+                term.truncate(prefix_len);
+                term.append_bytes(token.text.as_bytes());
+                let offsets = (token.offset_from as u32, token.offset_to as u32);
+                let unordered_term_id = self.subscribe_token(
+                    term_index,
+                    doc_id,
+                    token.position as u32,
+                    offsets,
+                    token.payload,
+                    &term,
+                    heap,
+                );
+                if let Some(term_id_fast_field_writer) = term_id_fast_field_writer_opt.as_mut() {
+                    term_id_fast_field_writer.add_val(unordered_term_id);
+                }
             } else {
                 info!(
                     "A token exceeding MAX_TOKEN_LEN ({}>{}) was dropped. Search for \
@@ -230,7 +293,8 @@ pub trait PostingsWriter {
                 );
             }
         };
-        token_stream.process(&mut sink)
+        token_stream.process(&mut sink);
+        num_tokens
     }
 
     fn total_num_tokens(&self) -> u64;
@@ -259,11 +323,13 @@ impl<Rec: Recorder + 'static> SpecializedPostingsWriter<Rec> {
 }
 
 impl<Rec: Recorder + 'static> PostingsWriter for SpecializedPostingsWriter<Rec> {
-    fn subscribe(
+    fn subscribe_token(
         &mut self,
         term_index: &mut TermHashMap,
         doc: DocId,
         position: u32,
+        offsets: (u32, u32),
+        payload: u32,
         term: &Term,
         heap: &mut MemoryArena,
     ) -> UnorderedTermId {
@@ -276,12 +342,12 @@ impl<Rec: Recorder + 'static> PostingsWriter for SpecializedPostingsWriter<Rec>
                     recorder.close_doc(heap);
                     recorder.new_doc(doc, heap);
                 }
-                recorder.record_position(position, heap);
+                recorder.record_position(position, offsets, payload, heap);
                 recorder
             } else {
                 let mut recorder = Rec::new();
                 recorder.new_doc(doc, heap);
-                recorder.record_position(position, heap);
+                recorder.record_position(position, offsets, payload, heap);
                 recorder
             }
         }) as UnorderedTermId
@@ -297,6 +363,11 @@ impl<Rec: Recorder + 'static> PostingsWriter for SpecializedPostingsWriter<Rec>
         let mut buffer_lender = BufferLender::default();
         for &(term_bytes, addr, _) in term_addrs {
             let recorder: Rec = termdict_heap.read(addr);
+            let term_doc_freq = recorder.term_doc_freq();
+            if serializer.is_pruned(term_doc_freq) {
+                serializer.write_pruned_term(&term_bytes[4..], term_doc_freq)?;
+                continue;
+            }
             serializer.new_term(&term_bytes[4..])?;
             recorder.serialize(&mut buffer_lender, serializer, heap)?;
             serializer.close_term()?;
@@ -6,10 +6,20 @@ use std::io;
 
 const POSITION_END: u32 = 0;
 
+/// Buffers for the bytes, positions, offsets and payloads of a term.
+type LentBuffers<'a> = (
+    &'a mut Vec<u8>,
+    &'a mut Vec<u32>,
+    &'a mut Vec<(u32, u32)>,
+    &'a mut Vec<u32>,
+);
+
 #[derive(Default)]
 pub(crate) struct BufferLender {
     buffer_u8: Vec<u8>,
     buffer_u32: Vec<u32>,
+    buffer_offsets: Vec<(u32, u32)>,
+    buffer_payloads: Vec<u32>,
 }
 
 impl BufferLender {
@@ -22,6 +32,18 @@ impl BufferLender {
         self.buffer_u32.clear();
         (&mut self.buffer_u8, &mut self.buffer_u32)
     }
+    pub fn lend_all_with_offsets_and_payloads(&mut self) -> LentBuffers<'_> {
+        self.buffer_u8.clear();
+        self.buffer_u32.clear();
+        self.buffer_offsets.clear();
+        self.buffer_payloads.clear();
+        (
+            &mut self.buffer_u8,
+            &mut self.buffer_u32,
+            &mut self.buffer_offsets,
+            &mut self.buffer_payloads,
+        )
+    }
 }
 
 pub struct VInt32Reader<'a> {
@@ -55,17 +77,28 @@ impl<'a> Iterator for VInt32Reader<'a> {
 ///   * the document id
 ///   * the term frequency
 ///   * the term positions
+///   * the byte offsets of the tokens
+///   * the payloads of the tokens
 pub(crate) trait Recorder: Copy + 'static {
     ///
     fn new() -> Self;
     /// Returns the current document
     fn current_doc(&self) -> u32;
+    /// Returns the number of documents recorded so far.
+    fn term_doc_freq(&self) -> u32;
     /// Starts recording information about a new document
     /// This method shall only be called if the term is within the document.
     fn new_doc(&mut self, doc: DocId, heap: &mut MemoryArena);
-    /// Record the position of a term. For each document,
-    /// this method will be called `term_freq` times.
-    fn record_position(&mut self, position: u32, heap: &mut MemoryArena);
+    /// Record the position of a term, the byte offsets `(offset_from, offset_to)`
+    /// and the payload of its token.
+    /// For each document, this method will be called `term_freq` times.
+    fn record_position(
+        &mut self,
+        position: u32,
+        offsets: (u32, u32),
+        payload: u32,
+        heap: &mut MemoryArena,
+    );
     /// Close the document. It will help record the term frequency.
     fn close_doc(&mut self, heap: &mut MemoryArena);
     /// Pushes the postings information to the serializer.
@@ -82,6 +115,7 @@ pub(crate) trait Recorder: Copy + 'static {
 pub struct NothingRecorder {
     stack: ExpUnrolledLinkedList,
     current_doc: DocId,
+    term_doc_freq: u32,
 }
 
 impl Recorder for NothingRecorder {
@@ -89,6 +123,7 @@ impl Recorder for NothingRecorder {
         NothingRecorder {
             stack: ExpUnrolledLinkedList::new(),
             current_doc: u32::max_value(),
+            term_doc_freq: 0u32,
         }
     }
 
@@ -96,12 +131,24 @@ impl Recorder for NothingRecorder {
         self.current_doc
     }
 
+    fn term_doc_freq(&self) -> u32 {
+        self.term_doc_freq
+    }
+
     fn new_doc(&mut self, doc: DocId, heap: &mut MemoryArena) {
         self.current_doc = doc;
+        self.term_doc_freq += 1;
         let _ = write_u32_vint(doc, &mut self.stack.writer(heap));
     }
 
-    fn record_position(&mut self, _position: u32, _heap: &mut MemoryArena) {}
+    fn record_position(
+        &mut self,
+        _position: u32,
+        _offsets: (u32, u32),
+        _payload: u32,
+        _heap: &mut MemoryArena,
+    ) {
+    }
 
     fn close_doc(&mut self, _heap: &mut MemoryArena) {}
 
@@ -125,6 +172,7 @@ impl Recorder for NothingRecorder {
 pub struct TermFrequencyRecorder {
     stack: ExpUnrolledLinkedList,
     current_doc: DocId,
+    term_doc_freq: u32,
     current_tf: u32,
 }
 
@@ -133,6 +181,7 @@ impl Recorder for TermFrequencyRecorder {
         TermFrequencyRecorder {
             stack: ExpUnrolledLinkedList::new(),
             current_doc: u32::max_value(),
+            term_doc_freq: 0u32,
             current_tf: 0u32,
         }
     }
@@ -141,12 +190,23 @@ impl Recorder for TermFrequencyRecorder {
         self.current_doc
     }
 
+    fn term_doc_freq(&self) -> u32 {
+        self.term_doc_freq
+    }
+
     fn new_doc(&mut self, doc: DocId, heap: &mut MemoryArena) {
         self.current_doc = doc;
+        self.term_doc_freq += 1;
         let _ = write_u32_vint(doc, &mut self.stack.writer(heap));
     }
 
-    fn record_position(&mut self, _position: u32, _heap: &mut MemoryArena) {
+    fn record_position(
+        &mut self,
+        _position: u32,
+        _offsets: (u32, u32),
+        _payload: u32,
+        _heap: &mut MemoryArena,
+    ) {
         self.current_tf += 1;
     }
 
@@ -179,12 +239,14 @@ impl Recorder for TermFrequencyRecorder {
 pub struct TFAndPositionRecorder {
     stack: ExpUnrolledLinkedList,
     current_doc: DocId,
+    term_doc_freq: u32,
 }
 impl Recorder for TFAndPositionRecorder {
     fn new() -> Self {
         TFAndPositionRecorder {
             stack: ExpUnrolledLinkedList::new(),
             current_doc: u32::max_value(),
+            term_doc_freq: 0u32,
         }
     }
 
@@ -192,12 +254,23 @@ impl Recorder for TFAndPositionRecorder {
         self.current_doc
     }
 
+    fn term_doc_freq(&self) -> u32 {
+        self.term_doc_freq
+    }
+
     fn new_doc(&mut self, doc: DocId, heap: &mut MemoryArena) {
         self.current_doc = doc;
+        self.term_doc_freq += 1;
         let _ = write_u32_vint(doc, &mut self.stack.writer(heap));
     }
 
-    fn record_position(&mut self, position: u32, heap: &mut MemoryArena) {
+    fn record_position(
+        &mut self,
+        position: u32,
+        _offsets: (u32, u32),
+        _payload: u32,
+        heap: &mut MemoryArena,
+    ) {
         let _ = write_u32_vint(position + 1u32, &mut self.stack.writer(heap));
     }
 
@@ -235,6 +308,99 @@ impl Recorder for TFAndPositionRecorder {
     }
 }
 
+/// Recorder encoding term frequencies, positions, and the byte offsets
+/// and the payloads of the tokens.
+#[derive(Clone, Copy)]
+pub struct TFPositionOffsetAndPayloadRecorder {
+    stack: ExpUnrolledLinkedList,
+    current_doc: DocId,
+    term_doc_freq: u32,
+}
+impl Recorder for TFPositionOffsetAndPayloadRecorder {
+    fn new() -> Self {
+        TFPositionOffsetAndPayloadRecorder {
+            stack: ExpUnrolledLinkedList::new(),
+            current_doc: u32::MAX,
+            term_doc_freq: 0u32,
+        }
+    }
+
+    fn current_doc(&self) -> DocId {
+        self.current_doc
+    }
+
+    fn term_doc_freq(&self) -> u32 {
+        self.term_doc_freq
+    }
+
+    fn new_doc(&mut self, doc: DocId, heap: &mut MemoryArena) {
+        self.current_doc = doc;
+        self.term_doc_freq += 1;
+        let _ = write_u32_vint(doc, &mut self.stack.writer(heap));
+    }
+
+    fn record_position(
+        &mut self,
+        position: u32,
+        offsets: (u32, u32),
+        payload: u32,
+        heap: &mut MemoryArena,
+    ) {
+        let (offset_from, offset_to) = offsets;
+        let mut writer = self.stack.writer(heap);
+        let _ = write_u32_vint(position + 1u32, &mut writer);
+        let _ = write_u32_vint(offset_from, &mut writer);
+        let _ = write_u32_vint(offset_to, &mut writer);
+        let _ = write_u32_vint(payload, &mut writer);
+    }
+
+    fn close_doc(&mut self, heap: &mut MemoryArena) {
+        let _ = write_u32_vint(POSITION_END, &mut self.stack.writer(heap));
+    }
+
+    fn serialize(
+        &self,
+        buffer_lender: &mut BufferLender,
+        serializer: &mut FieldSerializer<'_>,
+        heap: &MemoryArena,
+    ) -> io::Result<()> {
+        let (buffer_u8, buffer_positions, buffer_offsets, buffer_payloads) =
+            buffer_lender.lend_all_with_offsets_and_payloads();
+        self.stack.read_to_end(heap, buffer_u8);
+        let mut u32_it = VInt32Reader::new(&buffer_u8[..]);
+        while let Some(doc) = u32_it.next() {
+            let mut prev_position_plus_one = 1u32;
+            buffer_positions.clear();
+            buffer_offsets.clear();
+            buffer_payloads.clear();
+            loop {
+                match u32_it.next() {
+                    Some(POSITION_END) | None => {
+                        break;
+                    }
+                    Some(position_plus_one) => {
+                        let delta_position = position_plus_one - prev_position_plus_one;
+                        buffer_positions.push(delta_position);
+                        prev_position_plus_one = position_plus_one;
+                        let offset_from = u32_it.next().unwrap_or(0u32);
+                        let offset_to = u32_it.next().unwrap_or(offset_from);
+                        buffer_offsets.push((offset_from, offset_to));
+                        buffer_payloads.push(u32_it.next().unwrap_or(0u32));
+                    }
+                }
+            }
+            serializer.write_doc_with_offsets_and_payloads(
+                doc,
+                buffer_positions.len() as u32,
+                &buffer_positions[..],
+                &buffer_offsets[..],
+                &buffer_payloads[..],
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -2,14 +2,16 @@ use crate::common::BitSet;
 use crate::common::HasLen;
 use crate::common::{BinarySerializable, VInt};
 use crate::docset::{DocSet, SkipResult};
-use crate::positions::PositionReader;
+use crate::positions::{PositionLayout, PositionReader};
 use crate::postings::compression::{compressed_block_size, AlignedBuffer};
 use crate::postings::compression::{BlockDecoder, VIntDecoder, COMPRESSION_BLOCK_SIZE};
+use crate::postings::dense::DenseDocs;
 use crate::postings::serializer::PostingsSerializer;
 use crate::postings::BlockSearcher;
 use crate::postings::FreqReadingOption;
 use crate::postings::Postings;
 use crate::postings::SkipReader;
+use crate::postings::DEFAULT_POSTINGS_SKIP_INTERVAL;
 use crate::postings::USE_SKIP_INFO_LIMIT;
 use crate::schema::IndexRecordOption;
 use crate::DocId;
@@ -25,31 +27,79 @@ struct PositionComputer {
     // the positions vec.
     position_to_skip: usize,
     position_reader: PositionReader,
+    // integers stored for each position.
+    layout: PositionLayout,
+    buffer: Vec<u32>,
 }
 
 impl PositionComputer {
-    pub fn new(position_reader: PositionReader) -> PositionComputer {
+    pub fn new(position_reader: PositionReader, layout: PositionLayout) -> PositionComputer {
         PositionComputer {
             position_to_skip: 0,
             position_reader,
+            layout,
+            buffer: Vec::new(),
         }
     }
 
     pub fn add_skip(&mut self, num_skip: usize) {
-        self.position_to_skip += num_skip;
+        self.position_to_skip += num_skip * self.layout.num_ints_per_position();
     }
 
-    // Positions can only be read once.
-    pub fn positions_with_offset(&mut self, offset: u32, output: &mut [u32]) {
+    // Reads the integers encoding the next `num_positions` positions
+    // into the buffer.
+    fn read_interleaved(&mut self, num_positions: usize) {
         self.position_reader.skip(self.position_to_skip);
         self.position_to_skip = 0;
-        self.position_reader.read(output);
+        let num_ints_per_position = self.layout.num_ints_per_position();
+        self.buffer
+            .resize(num_positions * num_ints_per_position, 0u32);
+        self.position_reader.read(&mut self.buffer[..]);
+    }
+
+    // Positions can only be read once.
+    pub fn positions_with_offset(&mut self, offset: u32, output: &mut [u32]) {
+        let num_ints_per_position = self.layout.num_ints_per_position();
+        if num_ints_per_position > 1 {
+            self.read_interleaved(output.len());
+            let interleaved_it = self.buffer.chunks(num_ints_per_position);
+            for (output_mut, interleaved) in output.iter_mut().zip(interleaved_it) {
+                *output_mut = interleaved[0];
+            }
+        } else {
+            self.position_reader.skip(self.position_to_skip);
+            self.position_to_skip = 0;
+            self.position_reader.read(output);
+        }
         let mut cum = offset;
         for output_mut in output.iter_mut() {
             cum += *output_mut;
             *output_mut = cum;
         }
     }
+
+    pub fn offsets(&mut self, output: &mut [(u32, u32)]) {
+        debug_assert!(self.layout.has_offsets);
+        self.read_interleaved(output.len());
+        let num_ints_per_position = self.layout.num_ints_per_position();
+        let mut offset_from = 0u32;
+        let interleaved_it = self.buffer.chunks(num_ints_per_position);
+        for (output_mut, interleaved) in output.iter_mut().zip(interleaved_it) {
+            offset_from = offset_from.wrapping_add(interleaved[1]);
+            *output_mut = (offset_from, offset_from.wrapping_add(interleaved[2]));
+        }
+    }
+
+    pub fn payloads(&mut self, output: &mut [u32]) {
+        debug_assert!(self.layout.has_payloads);
+        self.read_interleaved(output.len());
+        let num_ints_per_position = self.layout.num_ints_per_position();
+        let interleaved_it = self.buffer.chunks(num_ints_per_position);
+        for (output_mut, interleaved) in output.iter_mut().zip(interleaved_it) {
+            // the payload is the last integer of the position.
+            *output_mut = interleaved[num_ints_per_position - 1];
+        }
+    }
 }
 
 /// `SegmentPostings` represents the inverted list or postings associated to
@@ -87,7 +137,8 @@ impl SegmentPostings {
     pub fn create_from_docs(docs: &[u32]) -> SegmentPostings {
         let mut buffer = Vec::new();
         {
-            let mut postings_serializer = PostingsSerializer::new(&mut buffer, false, false);
+            let mut postings_serializer =
+                PostingsSerializer::new(&mut buffer, false, false, DEFAULT_POSTINGS_SKIP_INTERVAL);
             for &doc in docs {
                 postings_serializer.write_doc(doc, 1u32);
             }
@@ -101,7 +152,11 @@ impl SegmentPostings {
             IndexRecordOption::Basic,
             IndexRecordOption::Basic,
         );
-        SegmentPostings::from_block_postings(block_segment_postings, None)
+        SegmentPostings::from_block_postings(
+            block_segment_postings,
+            None,
+            PositionLayout::default(),
+        )
     }
 }
 
@@ -112,17 +167,69 @@ impl SegmentPostings {
     /// * `data` - data array. The complete data is not necessarily used.
     /// * `freq_handler` - the freq handler is in charge of decoding
     ///   frequencies and/or positions
+    /// * `position_layout` - the integers stored for each position, e.g. the offsets
     pub(crate) fn from_block_postings(
         segment_block_postings: BlockSegmentPostings,
         positions_stream_opt: Option<PositionReader>,
+        position_layout: PositionLayout,
     ) -> SegmentPostings {
         SegmentPostings {
             block_cursor: segment_block_postings,
             cur: COMPRESSION_BLOCK_SIZE, // cursor within the block
-            position_computer: positions_stream_opt.map(PositionComputer::new),
+            position_computer: positions_stream_opt
+                .map(|position_reader| PositionComputer::new(position_reader, position_layout)),
             block_searcher: BlockSearcher::default(),
         }
     }
+
+    /// Returns the largest term frequency of the block containing the
+    /// current document, or `None` if term frequencies are not available.
+    ///
+    /// See [`BlockSegmentPostings::block_max_term_freq`](./struct.BlockSegmentPostings.html#method.block_max_term_freq).
+    pub fn block_max_term_freq(&self) -> Option<u32> {
+        self.block_cursor.block_max_term_freq()
+    }
+
+    /// Returns the last document of the block containing the current document.
+    pub(crate) fn block_last_doc(&self) -> DocId {
+        self.block_cursor.docs()[self.block_cursor.block_len() - 1]
+    }
+
+    /// Returns the byte offsets `(offset_from, offset_to)` of the tokens of the
+    /// term in the current document, in the order of their positions.
+    /// The output vector will be resized to the `term_freq`.
+    ///
+    /// The output vector is emptied if the field does not store offsets,
+    /// or if the positions were not requested.
+    /// See [`TextFieldIndexing::set_offsets`](../schema/struct.TextFieldIndexing.html#method.set_offsets).
+    pub fn offsets(&mut self, output: &mut Vec<(u32, u32)>) {
+        let term_freq = self.term_freq() as usize;
+        match self.position_computer.as_mut() {
+            Some(position_computer) if position_computer.layout.has_offsets => {
+                output.resize(term_freq, (0u32, 0u32));
+                position_computer.offsets(&mut output[..]);
+            }
+            _ => output.clear(),
+        }
+    }
+
+    /// Returns the payloads of the tokens of the term in the current document,
+    /// in the order of their positions, `0` meaning no payload.
+    /// The output vector will be resized to the `term_freq`.
+    ///
+    /// The output vector is emptied if the field does not store payloads,
+    /// or if the positions were not requested.
+    /// See [`TextFieldIndexing::set_payloads`](../schema/struct.TextFieldIndexing.html#method.set_payloads).
+    pub fn payloads(&mut self, output: &mut Vec<u32>) {
+        let term_freq = self.term_freq() as usize;
+        match self.position_computer.as_mut() {
+            Some(position_computer) if position_computer.layout.has_payloads => {
+                output.resize(term_freq, 0u32);
+                position_computer.payloads(&mut output[..]);
+            }
+            _ => output.clear(),
+        }
+    }
 }
 
 impl DocSet for SegmentPostings {
@@ -315,26 +422,49 @@ pub struct BlockSegmentPostings {
     doc_offset: DocId,
 
     num_vint_docs: usize,
+    block_max_term_freq: Option<u32>,
 
     remaining_data: OwnedRead,
     skip_reader: SkipReader,
+    // set iff the postings are encoded as a bitset.
+    dense_docs: Option<DenseDocs>,
 }
 
-fn split_into_skips_and_postings(
-    doc_freq: u32,
-    mut data: OwnedRead,
-) -> (Option<OwnedRead>, OwnedRead) {
+enum PostingsData {
+    Blocks(Option<OwnedRead>, OwnedRead),
+    Dense(DenseDocs),
+}
+
+fn split_into_skips_and_postings(doc_freq: u32, mut data: OwnedRead) -> PostingsData {
     if doc_freq >= USE_SKIP_INFO_LIMIT {
         let skip_len = VInt::deserialize(&mut data).expect("Data corrupted").0 as usize;
+        if skip_len == 0 {
+            // skip data is never empty: the postings are a bitset.
+            return PostingsData::Dense(DenseDocs::open(data));
+        }
         let mut postings_data = data.clone();
         postings_data.advance(skip_len);
         data.clip(skip_len);
-        (Some(data), postings_data)
+        PostingsData::Blocks(Some(data), postings_data)
     } else {
-        (None, data)
+        PostingsData::Blocks(None, data)
     }
 }
 
+/// Full block of postings, as it is encoded on disk.
+///
+/// See `BlockSegmentPostings::advance_bitpacked`.
+pub(crate) struct EncodedBlock {
+    /// The document preceding the block, from which its
+    /// doc ids are delta-encoded.
+    pub doc_offset: DocId,
+    pub doc_num_bits: u8,
+    pub tf_num_bits: u8,
+    /// The bitpacked doc ids, followed by the bitpacked
+    /// term frequencies if they are recorded.
+    pub data: OwnedRead,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum BlockSegmentPostingsSkipResult {
     Terminated,
@@ -354,15 +484,28 @@ impl BlockSegmentPostings {
             (_, _) => FreqReadingOption::ReadFreq,
         };
 
-        let (skip_data_opt, postings_data) = split_into_skips_and_postings(doc_freq, data);
+        let (skip_data_opt, postings_data, dense_docs) =
+            match split_into_skips_and_postings(doc_freq, data) {
+                PostingsData::Blocks(skip_data_opt, postings_data) => {
+                    (skip_data_opt, postings_data, None)
+                }
+                PostingsData::Dense(dense_docs) => {
+                    (None, OwnedRead::new(&[][..]), Some(dense_docs))
+                }
+            };
         let skip_reader = match skip_data_opt {
             Some(skip_data) => SkipReader::new(skip_data, record_option),
             None => SkipReader::new(OwnedRead::new(&[][..]), record_option),
         };
         let doc_freq = doc_freq as usize;
-        let num_vint_docs = doc_freq % COMPRESSION_BLOCK_SIZE;
+        let num_vint_docs = if dense_docs.is_some() {
+            0
+        } else {
+            doc_freq % COMPRESSION_BLOCK_SIZE
+        };
         BlockSegmentPostings {
             num_vint_docs,
+            block_max_term_freq: None,
             doc_decoder: BlockDecoder::new(),
             freq_decoder: BlockDecoder::with_val(1),
             freq_reading_option,
@@ -370,6 +513,7 @@ impl BlockSegmentPostings {
             doc_freq,
             remaining_data: postings_data,
             skip_reader,
+            dense_docs,
         }
     }
 
@@ -384,17 +528,28 @@ impl BlockSegmentPostings {
     //
     // This does not reset the positions list.
     pub(crate) fn reset(&mut self, doc_freq: u32, postings_data: OwnedRead) {
-        let (skip_data_opt, postings_data) = split_into_skips_and_postings(doc_freq, postings_data);
-        let num_vint_docs = (doc_freq as usize) & (COMPRESSION_BLOCK_SIZE - 1);
-        self.num_vint_docs = num_vint_docs;
-        self.remaining_data = postings_data;
-        if let Some(skip_data) = skip_data_opt {
-            self.skip_reader.reset(skip_data);
-        } else {
-            self.skip_reader.reset(OwnedRead::new(&[][..]))
+        match split_into_skips_and_postings(doc_freq, postings_data) {
+            PostingsData::Blocks(skip_data_opt, postings_data) => {
+                let num_vint_docs = (doc_freq as usize) & (COMPRESSION_BLOCK_SIZE - 1);
+                self.num_vint_docs = num_vint_docs;
+                self.remaining_data = postings_data;
+                if let Some(skip_data) = skip_data_opt {
+                    self.skip_reader.reset(skip_data);
+                } else {
+                    self.skip_reader.reset(OwnedRead::new(&[][..]))
+                }
+                self.dense_docs = None;
+            }
+            PostingsData::Dense(dense_docs) => {
+                self.num_vint_docs = 0;
+                self.remaining_data = OwnedRead::new(&[][..]);
+                self.skip_reader.reset(OwnedRead::new(&[][..]));
+                self.dense_docs = Some(dense_docs);
+            }
         }
         self.doc_offset = 0;
         self.doc_freq = doc_freq as usize;
+        self.block_max_term_freq = None;
     }
 
     /// Returns the document frequency associated to this block postings.
@@ -436,6 +591,36 @@ impl BlockSegmentPostings {
         self.freq_decoder.output(idx)
     }
 
+    /// Returns the largest term frequency of the current block,
+    /// or `None` if term frequencies are not available.
+    ///
+    /// For full blocks, it is read from the skip data without having
+    /// to decode the term frequencies. Together with the fieldnorms, it
+    /// bounds the score any document of the block can reach.
+    #[inline]
+    pub fn block_max_term_freq(&self) -> Option<u32> {
+        self.block_max_term_freq
+    }
+
+    // Records the largest term frequency of the block that was just read
+    // with the help of the skip data.
+    fn load_skip_block_max_term_freq(&mut self) {
+        self.block_max_term_freq = match self.freq_reading_option {
+            FreqReadingOption::NoFreq => None,
+            FreqReadingOption::SkipFreq | FreqReadingOption::ReadFreq => {
+                Some(self.skip_reader.block_max_term_freq())
+            }
+        };
+    }
+
+    // Records the largest term frequency of the last, variable encoded, block.
+    fn load_vint_block_max_term_freq(&mut self) {
+        self.block_max_term_freq = match self.freq_reading_option {
+            FreqReadingOption::NoFreq | FreqReadingOption::SkipFreq => None,
+            FreqReadingOption::ReadFreq => self.freqs().iter().cloned().max(),
+        };
+    }
+
     /// Returns the length of the current block.
     ///
     /// All blocks have a length of `NUM_DOCS_PER_BLOCK`,
@@ -457,35 +642,27 @@ impl BlockSegmentPostings {
     /// `doc_id`. In that case, all of these document are consumed.
     ///
     pub fn skip_to(&mut self, target_doc: DocId) -> BlockSegmentPostingsSkipResult {
-        let mut skip_freqs = 0u32;
+        if let Some(dense_docs) = self.dense_docs.as_mut() {
+            dense_docs.seek(target_doc);
+            return if self.advance() {
+                BlockSegmentPostingsSkipResult::Success(0)
+            } else {
+                BlockSegmentPostingsSkipResult::Terminated
+            };
+        }
+        // Skips as many blocks as possible thanks to the upper levels of the skip data.
+        let (num_skipped_bytes, mut skip_freqs) = self.skip_reader.seek(target_doc);
+        if num_skipped_bytes > 0 {
+            self.remaining_data.advance(num_skipped_bytes);
+            self.doc_offset = self.skip_reader.doc();
+        }
         while self.skip_reader.advance() {
             if self.skip_reader.doc() >= target_doc {
                 // the last document of the current block is larger
                 // than the target.
                 //
                 // We found our block!
-                let num_bits = self.skip_reader.doc_num_bits();
-                let num_consumed_bytes = self.doc_decoder.uncompress_block_sorted(
-                    self.remaining_data.as_ref(),
-                    self.doc_offset,
-                    num_bits,
-                );
-                self.remaining_data.advance(num_consumed_bytes);
-                let tf_num_bits = self.skip_reader.tf_num_bits();
-                match self.freq_reading_option {
-                    FreqReadingOption::NoFreq => {}
-                    FreqReadingOption::SkipFreq => {
-                        let num_bytes_to_skip = compressed_block_size(tf_num_bits);
-                        self.remaining_data.advance(num_bytes_to_skip);
-                    }
-                    FreqReadingOption::ReadFreq => {
-                        let num_consumed_bytes = self
-                            .freq_decoder
-                            .uncompress_block_unsorted(self.remaining_data.as_ref(), tf_num_bits);
-                        self.remaining_data.advance(num_consumed_bytes);
-                    }
-                }
-                self.doc_offset = self.skip_reader.doc();
+                self.read_bitpacked_block();
                 return BlockSegmentPostingsSkipResult::Success(skip_freqs);
             } else {
                 skip_freqs += self.skip_reader.tf_sum();
@@ -511,6 +688,7 @@ impl BlockSegmentPostings {
                 }
             }
             self.num_vint_docs = 0;
+            self.load_vint_block_max_term_freq();
             return self
                 .docs()
                 .last()
@@ -530,30 +708,15 @@ impl BlockSegmentPostings {
     ///
     /// Returns false iff there was no remaining blocks.
     pub fn advance(&mut self) -> bool {
-        if self.skip_reader.advance() {
-            let num_bits = self.skip_reader.doc_num_bits();
-            let num_consumed_bytes = self.doc_decoder.uncompress_block_sorted(
-                self.remaining_data.as_ref(),
-                self.doc_offset,
-                num_bits,
-            );
-            self.remaining_data.advance(num_consumed_bytes);
-            let tf_num_bits = self.skip_reader.tf_num_bits();
-            match self.freq_reading_option {
-                FreqReadingOption::NoFreq => {}
-                FreqReadingOption::SkipFreq => {
-                    let num_bytes_to_skip = compressed_block_size(tf_num_bits);
-                    self.remaining_data.advance(num_bytes_to_skip);
-                }
-                FreqReadingOption::ReadFreq => {
-                    let num_consumed_bytes = self
-                        .freq_decoder
-                        .uncompress_block_unsorted(self.remaining_data.as_ref(), tf_num_bits);
-                    self.remaining_data.advance(num_consumed_bytes);
-                }
+        if let Some(dense_docs) = self.dense_docs.as_mut() {
+            if self.doc_decoder.fill_from(dense_docs.docs()) == 0 {
+                return false;
             }
-            // it will be used as the next offset.
-            self.doc_offset = self.doc_decoder.output(COMPRESSION_BLOCK_SIZE - 1);
+            let last_doc = self.doc_decoder.output(self.doc_decoder.output_len - 1);
+            dense_docs.seek(last_doc + 1);
+            true
+        } else if self.skip_reader.advance() {
+            self.read_bitpacked_block();
             true
         } else if self.num_vint_docs > 0 {
             let num_compressed_bytes = self.doc_decoder.uncompress_vint_sorted(
@@ -570,16 +733,68 @@ impl BlockSegmentPostings {
                 }
             }
             self.num_vint_docs = 0;
+            self.load_vint_block_max_term_freq();
             true
         } else {
             false
         }
     }
 
+    // Decodes the full block the skip reader was just advanced to.
+    fn read_bitpacked_block(&mut self) {
+        let num_bits = self.skip_reader.doc_num_bits();
+        let num_consumed_bytes = self.doc_decoder.uncompress_block_sorted(
+            self.remaining_data.as_ref(),
+            self.doc_offset,
+            num_bits,
+        );
+        self.remaining_data.advance(num_consumed_bytes);
+        let tf_num_bits = self.skip_reader.tf_num_bits();
+        match self.freq_reading_option {
+            FreqReadingOption::NoFreq => {}
+            FreqReadingOption::SkipFreq => {
+                let num_bytes_to_skip = compressed_block_size(tf_num_bits);
+                self.remaining_data.advance(num_bytes_to_skip);
+            }
+            FreqReadingOption::ReadFreq => {
+                let num_consumed_bytes = self
+                    .freq_decoder
+                    .uncompress_block_unsorted(self.remaining_data.as_ref(), tf_num_bits);
+                self.remaining_data.advance(num_consumed_bytes);
+            }
+        }
+        // it will be used as the next offset.
+        self.doc_offset = self.doc_decoder.output(COMPRESSION_BLOCK_SIZE - 1);
+        self.load_skip_block_max_term_freq();
+    }
+
+    /// Advances to the next block if it is a full, bitpacked block,
+    /// and returns its encoded form along with it.
+    ///
+    /// Returns `None` without advancing otherwise, that is if the postings
+    /// are encoded as a bitset or if only the last, variable encoded, block
+    /// remains. `.advance()` should then be used to read the remaining blocks.
+    pub(crate) fn advance_bitpacked(&mut self) -> Option<EncodedBlock> {
+        if self.dense_docs.is_some() || !self.skip_reader.advance() {
+            return None;
+        }
+        let mut data = self.remaining_data.clone();
+        data.clip(self.skip_reader.total_block_len());
+        let encoded_block = EncodedBlock {
+            doc_offset: self.doc_offset,
+            doc_num_bits: self.skip_reader.doc_num_bits(),
+            tf_num_bits: self.skip_reader.tf_num_bits(),
+            data,
+        };
+        self.read_bitpacked_block();
+        Some(encoded_block)
+    }
+
     /// Returns an empty segment postings object
     pub fn empty() -> BlockSegmentPostings {
         BlockSegmentPostings {
             num_vint_docs: 0,
+            block_max_term_freq: None,
 
             doc_decoder: BlockDecoder::new(),
             freq_decoder: BlockDecoder::with_val(1),
@@ -590,6 +805,7 @@ impl BlockSegmentPostings {
 
             remaining_data: OwnedRead::new(vec![]),
             skip_reader: SkipReader::new(OwnedRead::new(vec![]), IndexRecordOption::Basic),
+            dense_docs: None,
         }
     }
 }
@@ -614,11 +830,13 @@ mod tests {
     use crate::common::HasLen;
     use crate::core::Index;
     use crate::docset::DocSet;
+    use crate::positions::PositionLayout;
     use crate::postings::postings::Postings;
     use crate::schema::IndexRecordOption;
     use crate::schema::Schema;
     use crate::schema::Term;
     use crate::schema::INDEXED;
+    use crate::schema::TEXT;
     use crate::DocId;
     use crate::SkipResult;
     use tantivy_fst::Streamer;
@@ -673,7 +891,11 @@ mod tests {
         doc_ids.push(130);
         {
             let block_segments = build_block_postings(&doc_ids);
-            let mut docset = SegmentPostings::from_block_postings(block_segments, None);
+            let mut docset = SegmentPostings::from_block_postings(
+                block_segments,
+                None,
+                PositionLayout::default(),
+            );
             assert_eq!(docset.skip_next(128), SkipResult::OverStep);
             assert_eq!(docset.doc(), 129);
             assert!(docset.advance());
@@ -682,7 +904,11 @@ mod tests {
         }
         {
             let block_segments = build_block_postings(&doc_ids);
-            let mut docset = SegmentPostings::from_block_postings(block_segments, None);
+            let mut docset = SegmentPostings::from_block_postings(
+                block_segments,
+                None,
+                PositionLayout::default(),
+            );
             assert_eq!(docset.skip_next(129), SkipResult::Reached);
             assert_eq!(docset.doc(), 129);
             assert!(docset.advance());
@@ -691,11 +917,47 @@ mod tests {
         }
         {
             let block_segments = build_block_postings(&doc_ids);
-            let mut docset = SegmentPostings::from_block_postings(block_segments, None);
+            let mut docset = SegmentPostings::from_block_postings(
+                block_segments,
+                None,
+                PositionLayout::default(),
+            );
             assert_eq!(docset.skip_next(131), SkipResult::End);
         }
     }
 
+    #[test]
+    fn test_dense_block_segment_postings() {
+        let docs: Vec<DocId> = (0..10_000).filter(|i| i % 3 != 1).collect();
+        let mut block_postings = build_block_postings(&docs[..]);
+        assert!(block_postings.dense_docs.is_some());
+        assert_eq!(block_postings.doc_freq(), docs.len());
+        let mut read_docs = Vec::new();
+        while let Some(block) = block_postings.next() {
+            assert!(block.len() <= 128);
+            read_docs.extend_from_slice(block);
+        }
+        assert_eq!(read_docs, docs);
+
+        let block_postings = build_block_postings(&docs[..]);
+        let mut docset =
+            SegmentPostings::from_block_postings(block_postings, None, PositionLayout::default());
+        assert_eq!(docset.len(), docs.len());
+        assert_eq!(docset.skip_next(1), SkipResult::OverStep);
+        assert_eq!(docset.doc(), 2);
+        assert_eq!(docset.skip_next(3_000), SkipResult::Reached);
+        assert_eq!(docset.skip_next(3_001), SkipResult::OverStep);
+        assert_eq!(docset.doc(), 3_002);
+        assert!(docset.advance());
+        assert_eq!(docset.doc(), 3_003);
+        assert_eq!(docset.skip_next(9_999), SkipResult::Reached);
+        assert!(!docset.advance());
+
+        let sparse_docs: Vec<DocId> = (0..1_000).map(|i| i * 10).collect();
+        let block_postings = build_block_postings(&sparse_docs[..]);
+        assert!(block_postings.dense_docs.is_none());
+    }
+
     fn build_block_postings(docs: &[DocId]) -> BlockSegmentPostings {
         let mut schema_builder = Schema::builder();
         let int_field = schema_builder.add_u64_field("id", INDEXED);
@@ -801,4 +1063,51 @@ mod tests {
         assert!(block_segments.advance());
         assert_eq!(block_segments.docs(), &[1, 3, 5]);
     }
+
+    #[test]
+    fn test_block_max_term_freq() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for doc in 0..300 {
+            let text = match doc {
+                50 => "a a a a a a a",
+                200 => "a a a",
+                _ => "a",
+            };
+            index_writer.add_document(doc!(text_field => text));
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let inverted_index = searcher.segment_reader(0).inverted_index(text_field);
+        let term = Term::from_field_text(text_field, "a");
+
+        let mut block_postings = inverted_index
+            .read_block_postings(&term, IndexRecordOption::WithFreqs)
+            .unwrap();
+        assert_eq!(block_postings.block_max_term_freq(), None);
+        let mut block_max_term_freqs = vec![];
+        while block_postings.advance() {
+            block_max_term_freqs.push(block_postings.block_max_term_freq());
+        }
+        assert_eq!(block_max_term_freqs, vec![Some(7), Some(3), Some(1)]);
+
+        // Term frequencies are not decoded, but the skip data still gives the
+        // block maximum of the full blocks.
+        let mut postings = inverted_index
+            .read_postings(&term, IndexRecordOption::Basic)
+            .unwrap();
+        assert_eq!(postings.skip_next(130), SkipResult::Reached);
+        assert_eq!(postings.block_max_term_freq(), Some(3));
+        assert_eq!(postings.skip_next(280), SkipResult::Reached);
+        assert_eq!(postings.block_max_term_freq(), None);
+
+        let mut postings = inverted_index
+            .read_postings(&term, IndexRecordOption::WithFreqs)
+            .unwrap();
+        assert_eq!(postings.skip_next(10), SkipResult::Reached);
+        assert_eq!(postings.block_max_term_freq(), Some(7));
+    }
 }
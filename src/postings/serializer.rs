@@ -3,8 +3,11 @@ use crate::common::{BinarySerializable, VInt};
 use crate::common::{CompositeWrite, CountingWriter};
 use crate::core::Segment;
 use crate::directory::WritePtr;
-use crate::positions::PositionSerializer;
+use crate::fastfield::FastSingleFieldSerializer;
+use crate::positions::{PositionLayout, PositionSerializer};
 use crate::postings::compression::{BlockEncoder, VIntEncoder, COMPRESSION_BLOCK_SIZE};
+use crate::postings::dense::{is_dense, serialize_dense};
+use crate::postings::segment_postings::EncodedBlock;
 use crate::postings::skip::SkipSerializer;
 use crate::postings::USE_SKIP_INFO_LIMIT;
 use crate::schema::Schema;
@@ -43,6 +46,10 @@ use std::io::{self, Write};
 /// Terms have to be pushed in a lexicographically-sorted order.
 /// Within a term, document have to be pushed in increasing order.
 ///
+/// If the index defines a primary key field, the last `DocId` of each
+/// of its terms is recorded, and written upon `close()` in the `.term`
+/// file, as a fast field column indexed by term ordinal.
+///
 /// A description of the serialization format is
 /// [available here](https://fulmicoton.gitbooks.io/tantivy-doc/content/inverted-index.html).
 pub struct InvertedIndexSerializer {
@@ -51,6 +58,9 @@ pub struct InvertedIndexSerializer {
     positions_write: CompositeWrite<WritePtr>,
     positionsidx_write: CompositeWrite<WritePtr>,
     schema: Schema,
+    primary_key_field: Option<Field>,
+    primary_key_docs: Option<Vec<DocId>>,
+    skip_interval: usize,
 }
 
 impl InvertedIndexSerializer {
@@ -61,6 +71,8 @@ impl InvertedIndexSerializer {
         positions_write: CompositeWrite<WritePtr>,
         positionsidx_write: CompositeWrite<WritePtr>,
         schema: Schema,
+        primary_key_field: Option<Field>,
+        skip_interval: usize,
     ) -> Result<InvertedIndexSerializer> {
         Ok(InvertedIndexSerializer {
             terms_write,
@@ -68,6 +80,9 @@ impl InvertedIndexSerializer {
             positions_write,
             positionsidx_write,
             schema,
+            primary_key_field,
+            primary_key_docs: None,
+            skip_interval,
         })
     }
 
@@ -80,6 +95,8 @@ impl InvertedIndexSerializer {
             CompositeWrite::wrap(segment.open_write(POSITIONS)?),
             CompositeWrite::wrap(segment.open_write(POSITIONSSKIP)?),
             segment.schema(),
+            segment.index().primary_key_field(),
+            segment.index().settings().postings_skip_interval(),
         )
     }
 
@@ -99,17 +116,33 @@ impl InvertedIndexSerializer {
         let positions_write = self.positions_write.for_field(field);
         let positionsidx_write = self.positionsidx_write.for_field(field);
         let field_type: FieldType = (*field_entry.field_type()).clone();
+        let primary_key_docs = if self.primary_key_field == Some(field) {
+            Some(self.primary_key_docs.get_or_insert_with(Vec::new))
+        } else {
+            None
+        };
         FieldSerializer::create(
             &field_type,
             term_dictionary_write,
             postings_write,
             positions_write,
             positionsidx_write,
+            primary_key_docs,
+            self.skip_interval,
         )
     }
 
     /// Closes the serializer.
-    pub fn close(self) -> io::Result<()> {
+    pub fn close(mut self) -> io::Result<()> {
+        if let (Some(field), Some(docs)) = (self.primary_key_field, self.primary_key_docs) {
+            let max_doc = docs.iter().cloned().max().unwrap_or(0u32);
+            let write = self.terms_write.for_field_with_idx(field, 1);
+            let mut serializer = FastSingleFieldSerializer::open(write, 0, u64::from(max_doc))?;
+            for doc in docs {
+                serializer.add_val(u64::from(doc))?;
+            }
+            serializer.close_field()?;
+        }
         self.terms_write.close()?;
         self.postings_write.close()?;
         self.positions_write.close()?;
@@ -124,9 +157,12 @@ pub struct FieldSerializer<'a> {
     term_dictionary_builder: TermDictionaryBuilder<&'a mut CountingWriter<WritePtr>>,
     postings_serializer: PostingsSerializer<&'a mut CountingWriter<WritePtr>>,
     positions_serializer_opt: Option<PositionSerializer<&'a mut CountingWriter<WritePtr>>>,
+    position_layout: PositionLayout,
+    max_doc_freq: Option<u32>,
     current_term_info: TermInfo,
     term_open: bool,
     num_terms: TermOrdinal,
+    primary_key_docs: Option<&'a mut Vec<DocId>>,
 }
 
 impl<'a> FieldSerializer<'a> {
@@ -136,9 +172,11 @@ impl<'a> FieldSerializer<'a> {
         postings_write: &'a mut CountingWriter<WritePtr>,
         positions_write: &'a mut CountingWriter<WritePtr>,
         positionsidx_write: &'a mut CountingWriter<WritePtr>,
+        primary_key_docs: Option<&'a mut Vec<DocId>>,
+        skip_interval: usize,
     ) -> io::Result<FieldSerializer<'a>> {
         let (term_freq_enabled, position_enabled): (bool, bool) = match field_type {
-            FieldType::Str(ref text_options) => {
+            FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => {
                 if let Some(text_indexing_options) = text_options.get_indexing_options() {
                     let index_option = text_indexing_options.index_option();
                     (index_option.has_freq(), index_option.has_positions())
@@ -148,10 +186,16 @@ impl<'a> FieldSerializer<'a> {
             }
             _ => (false, false),
         };
+        let position_layout = PositionLayout::for_field_type(field_type);
+        let max_doc_freq = field_type.max_doc_freq();
         let term_dictionary_builder =
             TermDictionaryBuilder::create(term_dictionary_write, &field_type)?;
-        let postings_serializer =
-            PostingsSerializer::new(postings_write, term_freq_enabled, position_enabled);
+        let postings_serializer = PostingsSerializer::new(
+            postings_write,
+            term_freq_enabled,
+            position_enabled,
+            skip_interval,
+        );
         let positions_serializer_opt = if position_enabled {
             Some(PositionSerializer::new(positions_write, positionsidx_write))
         } else {
@@ -162,9 +206,12 @@ impl<'a> FieldSerializer<'a> {
             term_dictionary_builder,
             postings_serializer,
             positions_serializer_opt,
+            position_layout,
+            max_doc_freq,
             current_term_info: TermInfo::default(),
             term_open: false,
             num_terms: TermOrdinal::default(),
+            primary_key_docs,
         })
     }
 
@@ -196,6 +243,34 @@ impl<'a> FieldSerializer<'a> {
         self.term_dictionary_builder.insert_key(term)?;
         let term_ordinal = self.num_terms;
         self.num_terms += 1;
+        if let Some(primary_key_docs) = self.primary_key_docs.as_mut() {
+            primary_key_docs.push(0u32);
+        }
+        Ok(term_ordinal)
+    }
+
+    /// Returns true iff the postings of a term appearing in `doc_freq`
+    /// documents should not be indexed.
+    ///
+    /// See [`TextFieldIndexing::set_max_doc_freq`](../schema/struct.TextFieldIndexing.html#method.set_max_doc_freq).
+    pub fn is_pruned(&self, doc_freq: u32) -> bool {
+        self.max_doc_freq
+            .map(|max_doc_freq| doc_freq > max_doc_freq)
+            .unwrap_or(false)
+    }
+
+    /// Records a term whose postings are pruned, together with
+    /// its document frequency.
+    ///
+    /// No postings are written for such a term: it must not be followed
+    /// by calls to `write_doc(...)` or `close_term()`.
+    pub fn write_pruned_term(&mut self, term: &[u8], doc_freq: u32) -> io::Result<TermOrdinal> {
+        debug_assert!(self.is_pruned(doc_freq));
+        let term_ordinal = self.new_term(term)?;
+        self.current_term_info.doc_freq = doc_freq;
+        self.term_dictionary_builder
+            .insert_value(&self.current_term_info)?;
+        self.term_open = false;
         Ok(term_ordinal)
     }
 
@@ -213,15 +288,119 @@ impl<'a> FieldSerializer<'a> {
         doc_id: DocId,
         term_freq: u32,
         position_deltas: &[u32],
+    ) -> io::Result<()> {
+        self.write_doc_with_offsets(doc_id, term_freq, position_deltas, &[])
+    }
+
+    /// Same as `write_doc(...)`, also serializing the byte offsets
+    /// `(offset_from, offset_to)` of each of the positions.
+    ///
+    /// Offsets are ignored if the field does not store them.
+    /// See [`TextFieldIndexing::set_offsets`](../schema/struct.TextFieldIndexing.html#method.set_offsets).
+    pub fn write_doc_with_offsets(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        position_deltas: &[u32],
+        offsets: &[(u32, u32)],
+    ) -> io::Result<()> {
+        self.write_doc_with_offsets_and_payloads(doc_id, term_freq, position_deltas, offsets, &[])
+    }
+
+    /// Same as `write_doc_with_offsets(...)`, also serializing the payload
+    /// of each of the positions.
+    ///
+    /// Payloads are ignored if the field does not store them.
+    /// See [`TextFieldIndexing::set_payloads`](../schema/struct.TextFieldIndexing.html#method.set_payloads).
+    pub fn write_doc_with_offsets_and_payloads(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        position_deltas: &[u32],
+        offsets: &[(u32, u32)],
+        payloads: &[u32],
     ) -> io::Result<()> {
         self.current_term_info.doc_freq += 1;
         self.postings_serializer.write_doc(doc_id, term_freq);
+        self.record_last_doc(doc_id);
         if let Some(ref mut positions_serializer) = self.positions_serializer_opt.as_mut() {
-            positions_serializer.write_all(position_deltas)?;
+            positions_serializer.write_all_with_layout(
+                self.position_layout,
+                position_deltas,
+                offsets,
+                payloads,
+            )?;
         }
         Ok(())
     }
 
+    /// Returns true iff `encoded_block`, once its doc ids are shifted by
+    /// `doc_id_shift`, can be appended as is to the current term
+    /// with `write_encoded_block(...)`.
+    ///
+    /// This is the case if all of the previous documents of the term were
+    /// written in full blocks, the last one ending with the document the
+    /// block is delta-encoded from.
+    pub(crate) fn accepts_encoded_block(
+        &self,
+        encoded_block: &EncodedBlock,
+        doc_id_shift: DocId,
+    ) -> bool {
+        self.postings_serializer
+            .accepts_encoded_block(encoded_block.doc_offset + doc_id_shift)
+    }
+
+    /// Appends a full block of documents to the current term,
+    /// by copying its encoded form.
+    ///
+    /// `doc_ids` and `term_freqs` are the decoded content of the block, and
+    /// `positions` the positions of its documents, as they are stored:
+    /// position deltas, interleaved with the byte offsets and the payloads
+    /// if the field stores them.
+    pub(crate) fn write_encoded_block(
+        &mut self,
+        encoded_block: &EncodedBlock,
+        doc_ids: &[DocId],
+        term_freqs: &[u32],
+        positions: &[u32],
+    ) -> io::Result<()> {
+        self.current_term_info.doc_freq += doc_ids.len() as u32;
+        self.postings_serializer
+            .write_encoded_block(encoded_block, doc_ids, term_freqs);
+        self.record_last_doc(doc_ids[doc_ids.len() - 1]);
+        if let Some(positions_serializer) = self.positions_serializer_opt.as_mut() {
+            positions_serializer.write_all(positions)?;
+        }
+        Ok(())
+    }
+
+    /// Same as `write_doc(...)`, but the positions are given as
+    /// they are stored, as in `write_encoded_block(...)`.
+    pub(crate) fn write_doc_with_stored_positions(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        positions: &[u32],
+    ) -> io::Result<()> {
+        self.current_term_info.doc_freq += 1;
+        self.postings_serializer.write_doc(doc_id, term_freq);
+        self.record_last_doc(doc_id);
+        if let Some(positions_serializer) = self.positions_serializer_opt.as_mut() {
+            positions_serializer.write_all(positions)?;
+        }
+        Ok(())
+    }
+
+    fn record_last_doc(&mut self, doc_id: DocId) {
+        if let Some(last_doc) = self
+            .primary_key_docs
+            .as_mut()
+            .and_then(|primary_key_docs| primary_key_docs.last_mut())
+        {
+            *last_doc = doc_id;
+        }
+    }
+
     /// Finish the serialization for this term postings.
     ///
     /// If the current block is incomplete, it need to be encoded
@@ -306,6 +485,9 @@ pub struct PostingsSerializer<W: Write> {
 
     postings_write: Vec<u8>,
     skip_write: SkipSerializer,
+    // documents of the current term, kept when term frequencies are
+    // not recorded, in case they are better encoded as a bitset.
+    term_docs: Vec<DocId>,
 
     termfreq_enabled: bool,
     termfreq_sum_enabled: bool,
@@ -316,6 +498,7 @@ impl<W: Write> PostingsSerializer<W> {
         write: W,
         termfreq_enabled: bool,
         termfreq_sum_enabled: bool,
+        skip_interval: usize,
     ) -> PostingsSerializer<W> {
         PostingsSerializer {
             output_write: CountingWriter::wrap(write),
@@ -324,7 +507,8 @@ impl<W: Write> PostingsSerializer<W> {
             block: Box::new(Block::new()),
 
             postings_write: Vec::new(),
-            skip_write: SkipSerializer::new(),
+            skip_write: SkipSerializer::new(skip_interval),
+            term_docs: Vec::new(),
 
             last_doc_id_encoded: 0u32,
             termfreq_enabled,
@@ -350,22 +534,58 @@ impl<W: Write> PostingsSerializer<W> {
                 .block_encoder
                 .compress_block_unsorted(&self.block.term_freqs());
             self.postings_write.extend(block_encoded);
-            self.skip_write.write_term_freq(num_bits);
+            let block_max_term_freq = self.block.term_freqs().iter().cloned().max().unwrap_or(0);
+            self.skip_write
+                .write_term_freq(num_bits, block_max_term_freq);
             if self.termfreq_sum_enabled {
                 let sum_freq = self.block.term_freqs().iter().cloned().sum();
                 self.skip_write.write_total_term_freq(sum_freq);
             }
         }
+        self.skip_write.finish_block();
         self.block.clear();
     }
 
     pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32) {
         self.block.append_doc(doc_id, term_freq);
+        if !self.termfreq_enabled {
+            self.term_docs.push(doc_id);
+        }
         if self.block.is_full() {
             self.write_block();
         }
     }
 
+    fn accepts_encoded_block(&self, doc_offset: DocId) -> bool {
+        self.block.is_empty() && self.last_doc_id_encoded == doc_offset
+    }
+
+    fn write_encoded_block(
+        &mut self,
+        encoded_block: &EncodedBlock,
+        doc_ids: &[DocId],
+        term_freqs: &[u32],
+    ) {
+        debug_assert!(self.block.is_empty());
+        debug_assert_eq!(doc_ids.len(), COMPRESSION_BLOCK_SIZE);
+        self.last_doc_id_encoded = doc_ids[COMPRESSION_BLOCK_SIZE - 1];
+        self.skip_write
+            .write_doc(self.last_doc_id_encoded, encoded_block.doc_num_bits);
+        self.postings_write.extend(encoded_block.data.as_ref());
+        if self.termfreq_enabled {
+            let block_max_term_freq = term_freqs.iter().cloned().max().unwrap_or(0);
+            self.skip_write
+                .write_term_freq(encoded_block.tf_num_bits, block_max_term_freq);
+            if self.termfreq_sum_enabled {
+                let sum_freq = term_freqs.iter().cloned().sum();
+                self.skip_write.write_total_term_freq(sum_freq);
+            }
+        } else {
+            self.term_docs.extend_from_slice(doc_ids);
+        }
+        self.skip_write.finish_block();
+    }
+
     fn close(mut self) -> io::Result<()> {
         self.postings_write.flush()
     }
@@ -393,7 +613,9 @@ impl<W: Write> PostingsSerializer<W> {
             }
             self.block.clear();
         }
-        if doc_freq >= USE_SKIP_INFO_LIMIT {
+        if doc_freq >= USE_SKIP_INFO_LIMIT && !self.termfreq_enabled && is_dense(&self.term_docs) {
+            serialize_dense(&self.term_docs, &mut self.output_write)?;
+        } else if doc_freq >= USE_SKIP_INFO_LIMIT {
             let skip_data = self.skip_write.data();
             VInt(skip_data.len() as u64).serialize(&mut self.output_write)?;
             self.output_write.write_all(skip_data)?;
@@ -403,6 +625,7 @@ impl<W: Write> PostingsSerializer<W> {
         }
         self.skip_write.clear();
         self.postings_write.clear();
+        self.term_docs.clear();
         Ok(())
     }
 
@@ -1,19 +1,64 @@
-use crate::common::BinarySerializable;
-use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+use crate::common::{BinarySerializable, VInt};
+use crate::postings::compression::{compressed_block_size, COMPRESSION_BLOCK_SIZE};
 use crate::schema::IndexRecordOption;
 use crate::DocId;
 use owned_read::OwnedRead;
 
+/// Skip data is organized in several levels.
+///
+/// Level 0 has one entry per block of `COMPRESSION_BLOCK_SIZE` documents,
+/// holding the last doc of the block and the number of bits used to encode it.
+///
+/// Each entry of the level `k >= 1` covers `skip_interval` entries of the level `k - 1`.
+/// It holds the absolute state of the postings at the end of the covered blocks:
+/// * the last doc of the covered blocks,
+/// * the number of bytes of the postings of the covered blocks,
+/// * the sum of the term frequencies of the covered blocks
+///   (only if positions are recorded, `0` otherwise),
+/// * the position, in each of the levels `0..k`, right after the covered entries.
+///
+/// The levels are serialized from the top level to the level 0,
+/// each upper level being prefixed by its length in bytes.
+/// Seeking a far target first goes through the upper levels, so that it
+/// only needs to read a number of entries logarithmic in the length of the postings.
 pub struct SkipSerializer {
+    skip_interval: usize,
     buffer: Vec<u8>,
+    upper_levels: Vec<UpperLevelWriter>,
     prev_doc: DocId,
+    block: BlockSkipInfo,
+    postings_num_bytes: u64,
+    tf_cumul: u64,
+    output: Vec<u8>,
+}
+
+#[derive(Default)]
+struct BlockSkipInfo {
+    last_doc: DocId,
+    doc_num_bits: u8,
+    tf_num_bits: u8,
+    tf_sum: u32,
+}
+
+#[derive(Default)]
+struct UpperLevelWriter {
+    buffer: Vec<u8>,
+    // number of entries of the level below, since the last entry.
+    num_entries_below: usize,
 }
 
 impl SkipSerializer {
-    pub fn new() -> SkipSerializer {
+    pub fn new(skip_interval: usize) -> SkipSerializer {
+        assert!(skip_interval >= 2, "The skip interval must be at least 2.");
         SkipSerializer {
+            skip_interval,
             buffer: Vec::new(),
+            upper_levels: Vec::new(),
             prev_doc: 0u32,
+            block: BlockSkipInfo::default(),
+            postings_num_bytes: 0u64,
+            tf_cumul: 0u64,
+            output: Vec::new(),
         }
     }
 
@@ -27,55 +72,204 @@ impl SkipSerializer {
         self.prev_doc = last_doc;
         delta_doc.serialize(&mut self.buffer).unwrap();
         self.buffer.push(doc_num_bits);
+        self.block = BlockSkipInfo {
+            last_doc,
+            doc_num_bits,
+            ..BlockSkipInfo::default()
+        };
     }
 
-    pub fn write_term_freq(&mut self, tf_num_bits: u8) {
+    /// Records the number of bits used to encode the term frequencies of the block,
+    /// and the largest term frequency of the block.
+    ///
+    /// The latter is an upper bound of the score impact of the block, which allows
+    /// scorers to skip the blocks that cannot produce competitive documents.
+    pub fn write_term_freq(&mut self, tf_num_bits: u8, block_max_term_freq: u32) {
         self.buffer.push(tf_num_bits);
+        VInt(u64::from(block_max_term_freq))
+            .serialize(&mut self.buffer)
+            .expect("Should never fail");
+        self.block.tf_num_bits = tf_num_bits;
     }
 
     pub fn write_total_term_freq(&mut self, tf_sum: u32) {
         tf_sum
             .serialize(&mut self.buffer)
             .expect("Should never fail");
+        self.block.tf_sum = tf_sum;
+    }
+
+    /// Must be called once all of the information of a block has been written.
+    ///
+    /// It updates the upper levels of the skip data.
+    pub fn finish_block(&mut self) {
+        self.postings_num_bytes += (compressed_block_size(self.block.doc_num_bits)
+            + compressed_block_size(self.block.tf_num_bits))
+            as u64;
+        self.tf_cumul += u64::from(self.block.tf_sum);
+        let mut level = 0;
+        loop {
+            if self.upper_levels.len() == level {
+                self.upper_levels.push(UpperLevelWriter::default());
+            }
+            self.upper_levels[level].num_entries_below += 1;
+            if self.upper_levels[level].num_entries_below < self.skip_interval {
+                break;
+            }
+            self.upper_levels[level].num_entries_below = 0;
+            // The entry holds the position of each of the levels below.
+            let mut entry = Vec::new();
+            for &val in &[
+                u64::from(self.block.last_doc),
+                self.postings_num_bytes,
+                self.tf_cumul,
+                self.buffer.len() as u64,
+            ] {
+                VInt(val).serialize(&mut entry).expect("Should never fail");
+            }
+            for lower_level in &self.upper_levels[..level] {
+                VInt(lower_level.buffer.len() as u64)
+                    .serialize(&mut entry)
+                    .expect("Should never fail");
+            }
+            self.upper_levels[level].buffer.extend_from_slice(&entry);
+            level += 1;
+        }
     }
 
-    pub fn data(&self) -> &[u8] {
-        &self.buffer[..]
+    pub fn data(&mut self) -> &[u8] {
+        self.output.clear();
+        let num_upper_levels = self
+            .upper_levels
+            .iter()
+            .take_while(|upper_level| !upper_level.buffer.is_empty())
+            .count();
+        VInt(num_upper_levels as u64)
+            .serialize(&mut self.output)
+            .expect("Should never fail");
+        for upper_level in self.upper_levels[..num_upper_levels].iter().rev() {
+            VInt(upper_level.buffer.len() as u64)
+                .serialize(&mut self.output)
+                .expect("Should never fail");
+            self.output.extend_from_slice(&upper_level.buffer[..]);
+        }
+        self.output.extend_from_slice(&self.buffer[..]);
+        &self.output[..]
     }
 
     pub fn clear(&mut self) {
         self.prev_doc = 0u32;
         self.buffer.clear();
+        self.upper_levels.clear();
+        self.block = BlockSkipInfo::default();
+        self.postings_num_bytes = 0u64;
+        self.tf_cumul = 0u64;
+    }
+}
+
+// Entry of an upper level of the skip data.
+struct UpperLevelEntry {
+    last_doc: DocId,
+    postings_num_bytes: u64,
+    tf_cumul: u64,
+    // positions in the levels below, starting from the level 0.
+    level_positions: Vec<usize>,
+    num_bytes: usize,
+}
+
+struct UpperLevelReader {
+    data: OwnedRead,
+    len: usize,
+}
+
+impl UpperLevelReader {
+    fn position(&self) -> usize {
+        self.len - self.data.as_ref().len()
+    }
+
+    // Reads the next entry of the `level`-th level (starting at 1),
+    // without consuming it.
+    fn peek(&self, level: usize) -> Option<UpperLevelEntry> {
+        let mut data = self.data.as_ref();
+        if data.is_empty() {
+            return None;
+        }
+        let len = data.len();
+        let mut read_val = || VInt::deserialize_u64(&mut data).expect("Skip data corrupted");
+        let last_doc = read_val() as DocId;
+        let postings_num_bytes = read_val();
+        let tf_cumul = read_val();
+        let level_positions = (0..level).map(|_| read_val() as usize).collect();
+        Some(UpperLevelEntry {
+            last_doc,
+            postings_num_bytes,
+            tf_cumul,
+            level_positions,
+            num_bytes: len - data.len(),
+        })
     }
 }
 
 pub(crate) struct SkipReader {
     doc: DocId,
     owned_read: OwnedRead,
+    len: usize,
+    upper_levels: Vec<UpperLevelReader>,
     doc_num_bits: u8,
     tf_num_bits: u8,
     tf_sum: u32,
+    block_max_term_freq: u32,
+    postings_num_bytes: u64,
+    tf_cumul: u64,
     skip_info: IndexRecordOption,
 }
 
 impl SkipReader {
     pub fn new(data: OwnedRead, skip_info: IndexRecordOption) -> SkipReader {
-        SkipReader {
+        let mut skip_reader = SkipReader {
             doc: 0u32,
-            owned_read: data,
+            owned_read: OwnedRead::new(&[][..]),
+            len: 0,
+            upper_levels: Vec::new(),
             skip_info,
             doc_num_bits: 0u8,
             tf_num_bits: 0u8,
             tf_sum: 0u32,
-        }
+            block_max_term_freq: 0u32,
+            postings_num_bytes: 0u64,
+            tf_cumul: 0u64,
+        };
+        skip_reader.reset(data);
+        skip_reader
     }
 
-    pub fn reset(&mut self, data: OwnedRead) {
+    pub fn reset(&mut self, mut data: OwnedRead) {
+        self.upper_levels.clear();
+        if !data.as_ref().is_empty() {
+            let num_upper_levels =
+                VInt::deserialize_u64(&mut data).expect("Skip data corrupted") as usize;
+            for _ in 0..num_upper_levels {
+                let len = VInt::deserialize_u64(&mut data).expect("Skip data corrupted") as usize;
+                let mut level_data = data.clone();
+                level_data.clip(len);
+                data.advance(len);
+                self.upper_levels.push(UpperLevelReader {
+                    data: level_data,
+                    len,
+                });
+            }
+            // Levels are stored from the top level down.
+            self.upper_levels.reverse();
+        }
         self.doc = 0u32;
+        self.len = data.as_ref().len();
         self.owned_read = data;
         self.doc_num_bits = 0u8;
         self.tf_num_bits = 0u8;
         self.tf_sum = 0u32;
+        self.block_max_term_freq = 0u32;
+        self.postings_num_bytes = 0u64;
+        self.tf_cumul = 0u64;
     }
 
     pub fn total_block_len(&self) -> usize {
@@ -101,6 +295,63 @@ impl SkipReader {
         self.tf_sum
     }
 
+    /// Largest term frequency of the block
+    ///
+    /// 0 if term frequencies are not enabled.
+    pub fn block_max_term_freq(&self) -> u32 {
+        self.block_max_term_freq
+    }
+
+    fn read_block_max_term_freq(&mut self) {
+        self.block_max_term_freq = VInt::deserialize_u64(&mut self.owned_read)
+            .expect("Failed reading block max tf") as u32;
+    }
+
+    fn position(&self) -> usize {
+        self.len - self.owned_read.as_ref().len()
+    }
+
+    /// Skips all of the blocks whose last doc is lower than `target`,
+    /// as far as the upper levels of the skip data make it possible.
+    ///
+    /// The blocks that remain to be skipped are skipped by calling `.advance()`.
+    ///
+    /// Returns the number of bytes of the postings skipped, and the sum of
+    /// the term frequencies of the blocks skipped (`0` if positions are not recorded).
+    pub fn seek(&mut self, target: DocId) -> (usize, u32) {
+        let start_postings_num_bytes = self.postings_num_bytes;
+        let start_tf_cumul = self.tf_cumul;
+        for level in (0..self.upper_levels.len()).rev() {
+            while let Some(entry) = self.upper_levels[level].peek(level + 1) {
+                if entry.last_doc >= target {
+                    break;
+                }
+                self.upper_levels[level].data.advance(entry.num_bytes);
+                for (lower_level, &position) in entry.level_positions[1..].iter().enumerate() {
+                    let lower_level_reader = &mut self.upper_levels[lower_level];
+                    let lower_level_position = lower_level_reader.position();
+                    if position > lower_level_position {
+                        lower_level_reader
+                            .data
+                            .advance(position - lower_level_position);
+                    }
+                }
+                let current_position = self.position();
+                if entry.level_positions[0] > current_position {
+                    self.owned_read
+                        .advance(entry.level_positions[0] - current_position);
+                    self.doc = entry.last_doc;
+                    self.postings_num_bytes = entry.postings_num_bytes;
+                    self.tf_cumul = entry.tf_cumul;
+                }
+            }
+        }
+        (
+            (self.postings_num_bytes - start_postings_num_bytes) as usize,
+            (self.tf_cumul - start_tf_cumul) as u32,
+        )
+    }
+
     pub fn advance(&mut self) -> bool {
         if self.owned_read.as_ref().is_empty() {
             false
@@ -115,14 +366,19 @@ impl SkipReader {
                 IndexRecordOption::WithFreqs => {
                     self.tf_num_bits = self.owned_read.get(1);
                     self.owned_read.advance(2);
+                    self.read_block_max_term_freq();
                 }
-                IndexRecordOption::WithFreqsAndPositions => {
+                IndexRecordOption::WithFreqsAndPositions
+                | IndexRecordOption::WithFreqsAndPositionsAndOffsets => {
                     self.tf_num_bits = self.owned_read.get(1);
                     self.owned_read.advance(2);
+                    self.read_block_max_term_freq();
                     self.tf_sum =
                         u32::deserialize(&mut self.owned_read).expect("Failed reading tf_sum");
                 }
             }
+            self.postings_num_bytes += self.total_block_len() as u64;
+            self.tf_cumul += u64::from(self.tf_sum);
             true
         }
     }
@@ -138,11 +394,11 @@ mod tests {
     #[test]
     fn test_skip_with_freq() {
         let buf = {
-            let mut skip_serializer = SkipSerializer::new();
+            let mut skip_serializer = SkipSerializer::new(8);
             skip_serializer.write_doc(1u32, 2u8);
-            skip_serializer.write_term_freq(3u8);
+            skip_serializer.write_term_freq(3u8, 7u32);
             skip_serializer.write_doc(5u32, 5u8);
-            skip_serializer.write_term_freq(2u8);
+            skip_serializer.write_term_freq(2u8, 300u32);
             skip_serializer.data().to_owned()
         };
         let mut skip_reader = SkipReader::new(OwnedRead::new(buf), IndexRecordOption::WithFreqs);
@@ -150,17 +406,39 @@ mod tests {
         assert_eq!(skip_reader.doc(), 1u32);
         assert_eq!(skip_reader.doc_num_bits(), 2u8);
         assert_eq!(skip_reader.tf_num_bits(), 3u8);
+        assert_eq!(skip_reader.block_max_term_freq(), 7u32);
         assert!(skip_reader.advance());
         assert_eq!(skip_reader.doc(), 5u32);
         assert_eq!(skip_reader.doc_num_bits(), 5u8);
         assert_eq!(skip_reader.tf_num_bits(), 2u8);
+        assert_eq!(skip_reader.block_max_term_freq(), 300u32);
+        assert!(!skip_reader.advance());
+    }
+
+    #[test]
+    fn test_skip_with_freq_and_positions() {
+        let buf = {
+            let mut skip_serializer = SkipSerializer::new(8);
+            skip_serializer.write_doc(1u32, 2u8);
+            skip_serializer.write_term_freq(3u8, 7u32);
+            skip_serializer.write_total_term_freq(200u32);
+            skip_serializer.data().to_owned()
+        };
+        let mut skip_reader = SkipReader::new(
+            OwnedRead::new(buf),
+            IndexRecordOption::WithFreqsAndPositions,
+        );
+        assert!(skip_reader.advance());
+        assert_eq!(skip_reader.tf_num_bits(), 3u8);
+        assert_eq!(skip_reader.block_max_term_freq(), 7u32);
+        assert_eq!(skip_reader.tf_sum(), 200u32);
         assert!(!skip_reader.advance());
     }
 
     #[test]
     fn test_skip_no_freq() {
         let buf = {
-            let mut skip_serializer = SkipSerializer::new();
+            let mut skip_serializer = SkipSerializer::new(8);
             skip_serializer.write_doc(1u32, 2u8);
             skip_serializer.write_doc(5u32, 5u8);
             skip_serializer.data().to_owned()
@@ -174,4 +452,47 @@ mod tests {
         assert_eq!(skip_reader.doc_num_bits(), 5u8);
         assert!(!skip_reader.advance());
     }
+
+    #[test]
+    fn test_skip_multi_level() {
+        // 100 blocks of 10 docs each, encoded with 8 bits,
+        // each block having a total term frequency of 3.
+        let buf = {
+            let mut skip_serializer = SkipSerializer::new(4);
+            for block in 0..100u32 {
+                skip_serializer.write_doc((block + 1) * 10, 8u8);
+                skip_serializer.write_term_freq(1u8, 1u32);
+                skip_serializer.write_total_term_freq(3u32);
+                skip_serializer.finish_block();
+            }
+            skip_serializer.data().to_owned()
+        };
+        let block_len = 128 * 9 / 8;
+        let mut skip_reader = SkipReader::new(
+            OwnedRead::new(buf),
+            IndexRecordOption::WithFreqsAndPositions,
+        );
+        assert_eq!(skip_reader.upper_levels.len(), 3);
+        assert!(skip_reader.advance());
+        assert_eq!(skip_reader.doc(), 10);
+        // Blocks 2 to 68 are skipped thanks to the upper levels.
+        assert_eq!(skip_reader.seek(715), (block_len * 67, 3 * 67));
+        assert_eq!(skip_reader.doc(), 680);
+        // Blocks 69 to 71 remain to be skipped.
+        let mut num_skipped_blocks = 67;
+        while skip_reader.advance() && skip_reader.doc() < 715 {
+            num_skipped_blocks += 1;
+        }
+        assert_eq!(num_skipped_blocks, 70);
+        assert_eq!(skip_reader.doc(), 720);
+        // Seeking a doc of the current block does not skip anything.
+        assert_eq!(skip_reader.seek(720), (0, 0));
+        assert_eq!(skip_reader.seek(995), (block_len * 24, 3 * 24));
+        assert_eq!(skip_reader.doc(), 960);
+        for doc in &[970, 980, 990, 1000] {
+            assert!(skip_reader.advance());
+            assert_eq!(skip_reader.doc(), *doc);
+        }
+        assert!(!skip_reader.advance());
+    }
 }
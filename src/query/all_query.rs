@@ -17,6 +17,10 @@ impl Query for AllQuery {
     fn weight(&self, _: &Searcher, _: bool) -> Result<Box<dyn Weight>> {
         Ok(Box::new(AllWeight))
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some("AllQuery".to_string())
+    }
 }
 
 /// Weight associated to the `AllQuery` query.
@@ -1,12 +1,12 @@
 use crate::common::BitSet;
 use crate::core::SegmentReader;
+use crate::query::explanation::does_not_match;
 use crate::query::ConstScorer;
 use crate::query::{BitSetDocSet, Explanation};
 use crate::query::{Scorer, Weight};
 use crate::schema::{Field, IndexRecordOption};
 use crate::termdict::{TermDictionary, TermStreamer};
 use crate::DocId;
-use crate::TantivyError;
 use crate::{Result, SkipResult};
 use std::sync::Arc;
 use tantivy_fst::Automaton;
@@ -66,9 +66,7 @@ where
         if scorer.skip_next(doc) == SkipResult::Reached {
             Ok(Explanation::new("AutomatonScorer", 1.0f32))
         } else {
-            Err(TantivyError::InvalidArgument(
-                "Document does not exist".to_string(),
-            ))
+            Err(does_not_match(doc))
         }
     }
 }
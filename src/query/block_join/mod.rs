@@ -0,0 +1,171 @@
+mod to_child_block_join_query;
+mod to_parent_block_join_query;
+
+pub use self::to_child_block_join_query::ToChildBlockJoinQuery;
+pub use self::to_parent_block_join_query::{BlockJoinScoreMode, ToParentBlockJoinQuery};
+
+use crate::core::SegmentReader;
+use crate::docset::DocSet;
+use crate::query::{Scorer, Weight};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+
+/// Returns the sorted parent documents of the segment,
+/// including the deleted ones.
+///
+/// Within a block of documents, the parent is the last document:
+/// the children of a parent are the documents between the previous
+/// parent and itself.
+fn parent_docs(parents_weight: &dyn Weight, reader: &SegmentReader) -> Result<Vec<DocId>> {
+    let mut parent_docs = Vec::new();
+    let mut scorer = parents_weight.scorer(reader)?;
+    while scorer.advance() {
+        parent_docs.push(scorer.doc());
+    }
+    Ok(parent_docs)
+}
+
+/// Scorer of the block join queries, iterating over
+/// documents whose scores were computed beforehand.
+struct BlockJoinScorer {
+    docs: Vec<DocId>,
+    scores: Vec<Score>,
+    cursor: Option<usize>,
+}
+
+impl BlockJoinScorer {
+    fn new(docs: Vec<DocId>, scores: Vec<Score>) -> BlockJoinScorer {
+        debug_assert_eq!(docs.len(), scores.len());
+        BlockJoinScorer {
+            docs,
+            scores,
+            cursor: None,
+        }
+    }
+
+    fn cursor(&self) -> usize {
+        self.cursor.expect("advance() must be called first")
+    }
+}
+
+impl DocSet for BlockJoinScorer {
+    fn advance(&mut self) -> bool {
+        let cursor = self.cursor.map(|cursor| cursor + 1).unwrap_or(0);
+        self.cursor = Some(cursor);
+        cursor < self.docs.len()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs[self.cursor()]
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len() as u32
+    }
+}
+
+impl Scorer for BlockJoinScorer {
+    fn score(&mut self) -> Score {
+        self.scores[self.cursor()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::TopDocs;
+    use crate::query::{
+        BlockJoinScoreMode, Query, TermQuery, ToChildBlockJoinQuery, ToParentBlockJoinQuery,
+    };
+    use crate::schema::{IndexRecordOption, Schema, STORED, STRING};
+    use crate::{DocAddress, Index, Term};
+    use futures::Future;
+
+    #[test]
+    fn test_block_join_after_merge() {
+        let mut schema_builder = Schema::builder();
+        let kind = schema_builder.add_text_field("kind", STRING);
+        let name = schema_builder.add_text_field("name", STRING | STORED);
+        let color = schema_builder.add_text_field("color", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let product = |product_name: &str, offer_colors: &[&str]| {
+            let mut block: Vec<_> = offer_colors
+                .iter()
+                .map(|&offer_color| {
+                    doc!(kind => "offer", name => product_name, color => offer_color)
+                })
+                .collect();
+            block.push(doc!(kind => "product", name => product_name));
+            block
+        };
+        index_writer
+            .add_document_block(product("shirt", &["red", "blue"]))
+            .unwrap();
+        index_writer
+            .add_document_block(product("hat", &["red"]))
+            .unwrap();
+        index_writer.commit().unwrap();
+        index_writer
+            .add_document_block(product("shoes", &["blue", "green"]))
+            .unwrap();
+        index_writer
+            .add_document_block(product("scarf", &[]))
+            .unwrap();
+        index_writer.delete_term(Term::from_field_text(name, "hat"));
+        index_writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+
+        let reader = index.reader().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let term_query = |field, text| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, text),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let names = |query: &dyn Query| -> Vec<String> {
+            let top_docs = searcher.search(query, &TopDocs::with_limit(10)).unwrap();
+            let mut names: Vec<String> = top_docs
+                .into_iter()
+                .map(|(_, doc_address)| {
+                    let doc = searcher.doc(doc_address).unwrap();
+                    doc.get_first(name).unwrap().text().unwrap().to_string()
+                })
+                .collect();
+            names.sort();
+            names
+        };
+        let products_with_red_offers = ToParentBlockJoinQuery::new(
+            term_query(color, "red"),
+            term_query(kind, "product"),
+            BlockJoinScoreMode::Max,
+        );
+        assert_eq!(names(&products_with_red_offers), vec!["shirt"]);
+        let products_with_blue_offers = ToParentBlockJoinQuery::new(
+            term_query(color, "blue"),
+            term_query(kind, "product"),
+            BlockJoinScoreMode::Total,
+        );
+        assert_eq!(names(&products_with_blue_offers), vec!["shirt", "shoes"]);
+        let offers_of_shoes =
+            ToChildBlockJoinQuery::new(term_query(name, "shoes"), term_query(kind, "product"));
+        let top_docs = searcher
+            .search(&offers_of_shoes, &TopDocs::with_limit(10))
+            .unwrap();
+        let mut doc_addresses: Vec<DocAddress> = top_docs
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect();
+        doc_addresses.sort();
+        // The hat block was deleted, and the shirt block has 3 documents.
+        assert_eq!(doc_addresses, vec![DocAddress(0, 3), DocAddress(0, 4)]);
+        let scarf_offers =
+            ToChildBlockJoinQuery::new(term_query(name, "scarf"), term_query(kind, "product"));
+        assert!(names(&scarf_offers).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use super::{parent_docs, BlockJoinScorer};
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::query::explanation::does_not_match;
+use crate::query::{Explanation, Query, Scorer, Weight};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// `ToChildBlockJoinQuery` matches the children of the documents matched by a query.
+///
+/// This is the counterpart of the
+/// [`ToParentBlockJoinQuery`](./struct.ToParentBlockJoinQuery.html):
+/// the `parent_query` should only match parents, which are identified by the
+/// `parents_filter` query. Each child is scored with the score of its parent.
+pub struct ToChildBlockJoinQuery {
+    parent_query: Box<dyn Query>,
+    parents_filter: Box<dyn Query>,
+}
+
+impl ToChildBlockJoinQuery {
+    /// Creates a query matching the children of the documents matched by `parent_query`.
+    ///
+    /// `parents_filter` identifies the parent documents.
+    pub fn new(
+        parent_query: Box<dyn Query>,
+        parents_filter: Box<dyn Query>,
+    ) -> ToChildBlockJoinQuery {
+        ToChildBlockJoinQuery {
+            parent_query,
+            parents_filter,
+        }
+    }
+
+    /// Returns the query matching the parents.
+    pub fn parent_query(&self) -> &dyn Query {
+        self.parent_query.as_ref()
+    }
+
+    /// Returns the query identifying the parents.
+    pub fn parents_filter(&self) -> &dyn Query {
+        self.parents_filter.as_ref()
+    }
+}
+
+impl Clone for ToChildBlockJoinQuery {
+    fn clone(&self) -> Self {
+        ToChildBlockJoinQuery {
+            parent_query: self.parent_query.box_clone(),
+            parents_filter: self.parents_filter.box_clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ToChildBlockJoinQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ToChildBlockJoin(parent_query={:?}, parents_filter={:?})",
+            self.parent_query, self.parents_filter
+        )
+    }
+}
+
+impl Query for ToChildBlockJoinQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        Ok(Box::new(ToChildBlockJoinWeight {
+            parent_weight: searcher.weight(self.parent_query.as_ref(), scoring_enabled)?,
+            parents_weight: searcher.weight(self.parents_filter.as_ref(), false)?,
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.parent_query.query_terms(term_set);
+    }
+}
+
+/// Weight associated to the `ToChildBlockJoinQuery`.
+struct ToChildBlockJoinWeight {
+    parent_weight: Box<dyn Weight>,
+    parents_weight: Box<dyn Weight>,
+}
+
+impl Weight for ToChildBlockJoinWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let parent_docs = parent_docs(self.parents_weight.as_ref(), reader)?;
+        let delete_bitset_opt = reader.delete_bitset();
+        let mut docs: Vec<DocId> = Vec::new();
+        let mut scores: Vec<Score> = Vec::new();
+        let mut parent_scorer = self.parent_weight.scorer(reader)?;
+        while parent_scorer.advance() {
+            let parent = parent_scorer.doc();
+            if let Some(delete_bitset) = delete_bitset_opt {
+                if delete_bitset.is_deleted(parent) {
+                    continue;
+                }
+            }
+            // The documents matched by the parent query which are
+            // not parents have no children.
+            let first_child = match parent_docs.binary_search(&parent) {
+                Ok(0) => 0,
+                Ok(parent_ord) => parent_docs[parent_ord - 1] + 1,
+                Err(_) => continue,
+            };
+            let score = parent_scorer.score();
+            for child in first_child..parent {
+                docs.push(child);
+                scores.push(score);
+            }
+        }
+        Ok(Box::new(BlockJoinScorer::new(docs, scores)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.scorer(reader)?;
+        if scorer.skip_next(doc) != SkipResult::Reached {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("ToChildBlockJoinQuery", scorer.score()))
+    }
+}
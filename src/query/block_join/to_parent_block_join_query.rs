@@ -0,0 +1,238 @@
+use super::{parent_docs, BlockJoinScorer};
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::query::explanation::does_not_match;
+use crate::query::{Explanation, Query, Scorer, Weight};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Defines how the scores of the matching children of a parent
+/// are combined into the score of the parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockJoinScoreMode {
+    /// The children are not scored, and all of the parents have a score of 1.
+    None,
+    /// The score of a parent is the average score of its matching children.
+    Avg,
+    /// The score of a parent is the maximum score of its matching children.
+    Max,
+    /// The score of a parent is the sum of the scores of its matching children.
+    Total,
+}
+
+impl BlockJoinScoreMode {
+    fn first(self, child_score: Score) -> Score {
+        match self {
+            BlockJoinScoreMode::None => 1.0,
+            _ => child_score,
+        }
+    }
+
+    fn combine(self, score: Score, child_score: Score) -> Score {
+        match self {
+            BlockJoinScoreMode::None => 1.0,
+            BlockJoinScoreMode::Max => score.max(child_score),
+            BlockJoinScoreMode::Avg | BlockJoinScoreMode::Total => score + child_score,
+        }
+    }
+
+    fn finalize(self, score: Score, num_children: u32) -> Score {
+        match self {
+            BlockJoinScoreMode::Avg => score / num_children as Score,
+            _ => score,
+        }
+    }
+}
+
+/// `ToParentBlockJoinQuery` matches the parents of the documents matched by a query.
+///
+/// The parents and their children have to be indexed together as a block of documents,
+/// using [`IndexWriter::add_document_block`](../struct.IndexWriter.html#method.add_document_block):
+/// the parent is the last document of its block. The parents are identified at search time
+/// by the `parents_filter` query, which should match the parents but none of their children.
+///
+/// The score of a parent is computed from the score of its matching children,
+/// according to the `BlockJoinScoreMode`.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{BlockJoinScoreMode, Query, TermQuery, ToParentBlockJoinQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, STORED, STRING};
+/// use tantivy::{doc, Index, Result, Term};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let kind = schema_builder.add_text_field("kind", STRING);
+///     let name = schema_builder.add_text_field("name", STRING | STORED);
+///     let color = schema_builder.add_text_field("color", STRING);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document_block(vec![
+///             doc!(kind => "offer", color => "red"),
+///             doc!(kind => "offer", color => "blue"),
+///             doc!(kind => "product", name => "shirt"),
+///         ])?;
+///         index_writer.add_document_block(vec![
+///             doc!(kind => "offer", color => "green"),
+///             doc!(kind => "product", name => "hat"),
+///         ])?;
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     let term_query = |field, text| -> Box<dyn Query> {
+///         Box::new(TermQuery::new(
+///             Term::from_field_text(field, text),
+///             IndexRecordOption::Basic,
+///         ))
+///     };
+///     let query = ToParentBlockJoinQuery::new(
+///         term_query(color, "blue"),
+///         term_query(kind, "product"),
+///         BlockJoinScoreMode::Max,
+///     );
+///     let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+///     assert_eq!(top_docs.len(), 1);
+///     let product = searcher.doc(top_docs[0].1)?;
+///     assert_eq!(product.get_first(name).and_then(|value| value.text()), Some("shirt"));
+///     Ok(())
+/// }
+/// ```
+pub struct ToParentBlockJoinQuery {
+    child_query: Box<dyn Query>,
+    parents_filter: Box<dyn Query>,
+    score_mode: BlockJoinScoreMode,
+}
+
+impl ToParentBlockJoinQuery {
+    /// Creates a query matching the parents of the documents matched by `child_query`.
+    ///
+    /// `parents_filter` identifies the parent documents.
+    pub fn new(
+        child_query: Box<dyn Query>,
+        parents_filter: Box<dyn Query>,
+        score_mode: BlockJoinScoreMode,
+    ) -> ToParentBlockJoinQuery {
+        ToParentBlockJoinQuery {
+            child_query,
+            parents_filter,
+            score_mode,
+        }
+    }
+
+    /// Returns the query matching the children.
+    pub fn child_query(&self) -> &dyn Query {
+        self.child_query.as_ref()
+    }
+
+    /// Returns the query identifying the parents.
+    pub fn parents_filter(&self) -> &dyn Query {
+        self.parents_filter.as_ref()
+    }
+
+    /// Returns how the scores of the children are combined.
+    pub fn score_mode(&self) -> BlockJoinScoreMode {
+        self.score_mode
+    }
+}
+
+impl Clone for ToParentBlockJoinQuery {
+    fn clone(&self) -> Self {
+        ToParentBlockJoinQuery {
+            child_query: self.child_query.box_clone(),
+            parents_filter: self.parents_filter.box_clone(),
+            score_mode: self.score_mode,
+        }
+    }
+}
+
+impl fmt::Debug for ToParentBlockJoinQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ToParentBlockJoin(child_query={:?}, parents_filter={:?}, score_mode={:?})",
+            self.child_query, self.parents_filter, self.score_mode
+        )
+    }
+}
+
+impl Query for ToParentBlockJoinQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let scoring_enabled = scoring_enabled && self.score_mode != BlockJoinScoreMode::None;
+        Ok(Box::new(ToParentBlockJoinWeight {
+            child_weight: searcher.weight(self.child_query.as_ref(), scoring_enabled)?,
+            parents_weight: searcher.weight(self.parents_filter.as_ref(), false)?,
+            score_mode: self.score_mode,
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.child_query.query_terms(term_set);
+    }
+}
+
+/// Weight associated to the `ToParentBlockJoinQuery`.
+struct ToParentBlockJoinWeight {
+    child_weight: Box<dyn Weight>,
+    parents_weight: Box<dyn Weight>,
+    score_mode: BlockJoinScoreMode,
+}
+
+impl Weight for ToParentBlockJoinWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let parent_docs = parent_docs(self.parents_weight.as_ref(), reader)?;
+        let delete_bitset_opt = reader.delete_bitset();
+        let mut docs: Vec<DocId> = Vec::new();
+        let mut scores: Vec<Score> = Vec::new();
+        let mut num_children = 0u32;
+        let mut child_scorer = self.child_weight.scorer(reader)?;
+        while child_scorer.advance() {
+            let child = child_scorer.doc();
+            if let Some(delete_bitset) = delete_bitset_opt {
+                if delete_bitset.is_deleted(child) {
+                    continue;
+                }
+            }
+            // Parents are not children, and the documents after
+            // the last parent are orphans.
+            let parent = match parent_docs.binary_search(&child) {
+                Ok(_) => continue,
+                Err(parent_ord) => match parent_docs.get(parent_ord) {
+                    Some(&parent) => parent,
+                    None => break,
+                },
+            };
+            let child_score = child_scorer.score();
+            if docs.last() == Some(&parent) {
+                let score = scores.last_mut().expect("a score per parent");
+                *score = self.score_mode.combine(*score, child_score);
+                num_children += 1;
+            } else {
+                if let Some(score) = scores.last_mut() {
+                    *score = self.score_mode.finalize(*score, num_children);
+                }
+                docs.push(parent);
+                scores.push(self.score_mode.first(child_score));
+                num_children = 1;
+            }
+        }
+        if let Some(score) = scores.last_mut() {
+            *score = self.score_mode.finalize(*score, num_children);
+        }
+        Ok(Box::new(BlockJoinScorer::new(docs, scores)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.scorer(reader)?;
+        if scorer.skip_next(doc) != SkipResult::Reached {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("ToParentBlockJoinQuery", scorer.score()))
+    }
+}
@@ -1,62 +1,92 @@
+use crate::core::FieldStatistics;
 use crate::fieldnorm::FieldNormReader;
-use crate::query::Explanation;
+use crate::query::{Explanation, Similarity, SimilarityWeight};
+use crate::DocId;
 use crate::Score;
-use crate::Searcher;
 use crate::Term;
 
-const K1: f32 = 1.2;
-const B: f32 = 0.75;
+/// Default value of the term frequency saturation parameter `k1`.
+pub const DEFAULT_K1: f32 = 1.2;
+/// Default value of the length normalization parameter `b`.
+pub const DEFAULT_B: f32 = 0.75;
 
 fn idf(doc_freq: u64, doc_count: u64) -> f32 {
     let x = ((doc_count - doc_freq) as f32 + 0.5) / (doc_freq as f32 + 0.5);
     (1f32 + x).ln()
 }
 
-fn cached_tf_component(fieldnorm: u32, average_fieldnorm: f32) -> f32 {
-    K1 * (1f32 - B + B * fieldnorm as f32 / average_fieldnorm)
+/// The Okapi BM25 similarity, used by default.
+///
+/// The score of a document is `idf * (k1 + 1) * tf / (tf + k1 * (1 - b + b * dl / avgdl))`,
+/// where `tf` is the number of occurrences of the term in the document, `dl` the
+/// number of tokens of the field of the document, and `avgdl` its average over
+/// all of the documents. The idf of a phrase is the sum of the idf of its terms.
+///
+/// - `k1` controls how quickly the contribution of the term frequency saturates.
+///   With `k1 = 0`, the term frequency is ignored.
+/// - `b` controls how much long fields are penalized, from `0` (not at all)
+///   to `1` (the term frequency is divided by the relative length of the field).
+#[derive(Clone, Copy, Debug)]
+pub struct BM25Similarity {
+    k1: f32,
+    b: f32,
 }
 
-fn compute_tf_cache(average_fieldnorm: f32) -> [f32; 256] {
-    let mut cache = [0f32; 256];
-    for (fieldnorm_id, cache_mut) in cache.iter_mut().enumerate() {
-        let fieldnorm = FieldNormReader::id_to_fieldnorm(fieldnorm_id as u8);
-        *cache_mut = cached_tf_component(fieldnorm, average_fieldnorm);
+impl BM25Similarity {
+    /// Creates a BM25 similarity with the given parameters.
+    ///
+    /// # Panics
+    /// The method panics if `k1` is negative, or if `b` is not within `[0, 1]`.
+    pub fn new(k1: f32, b: f32) -> BM25Similarity {
+        assert!(k1 >= 0.0, "k1 must be positive.");
+        assert!((0.0..=1.0).contains(&b), "b must be within [0, 1].");
+        BM25Similarity { k1, b }
+    }
+
+    /// Returns the term frequency saturation parameter.
+    pub fn k1(&self) -> f32 {
+        self.k1
+    }
+
+    /// Returns the length normalization parameter.
+    pub fn b(&self) -> f32 {
+        self.b
+    }
+
+    fn tf_component(&self, fieldnorm: u32, average_fieldnorm: f32) -> f32 {
+        self.k1 * (1f32 - self.b + self.b * fieldnorm as f32 / average_fieldnorm)
+    }
+
+    fn compute_tf_cache(&self, average_fieldnorm: f32) -> [f32; 256] {
+        let mut cache = [0f32; 256];
+        for (fieldnorm_id, cache_mut) in cache.iter_mut().enumerate() {
+            let fieldnorm = FieldNormReader::id_to_fieldnorm(fieldnorm_id as u8);
+            *cache_mut = self.tf_component(fieldnorm, average_fieldnorm);
+        }
+        cache
     }
-    cache
 }
 
-#[derive(Clone)]
-pub struct BM25Weight {
-    idf_explain: Explanation,
-    weight: f32,
-    cache: [f32; 256],
-    average_fieldnorm: f32,
+impl Default for BM25Similarity {
+    fn default() -> BM25Similarity {
+        BM25Similarity::new(DEFAULT_K1, DEFAULT_B)
+    }
 }
 
-impl BM25Weight {
-    pub fn for_terms(searcher: &Searcher, terms: &[Term]) -> BM25Weight {
+impl Similarity for BM25Similarity {
+    fn weight(
+        &self,
+        field_statistics: &FieldStatistics,
+        terms: &[(Term, u64)],
+    ) -> Box<dyn SimilarityWeight> {
         assert!(!terms.is_empty(), "BM25 requires at least one term");
-        let field = terms[0].field();
-        for term in &terms[1..] {
-            assert_eq!(
-                term.field(),
-                field,
-                "All terms must belong to the same field."
-            );
-        }
-
-        let mut total_num_tokens = 0u64;
-        let mut total_num_docs = 0u64;
-        for segment_reader in searcher.segment_readers() {
-            let inverted_index = segment_reader.inverted_index(field);
-            total_num_tokens += inverted_index.total_num_tokens();
-            total_num_docs += u64::from(segment_reader.max_doc());
-        }
+        let total_num_tokens = field_statistics.total_num_tokens;
+        let total_num_docs = field_statistics.num_docs;
         let average_fieldnorm = total_num_tokens as f32 / total_num_docs as f32;
 
         let mut idf_explain: Explanation;
         if terms.len() == 1 {
-            let term_doc_freq = searcher.doc_freq(&terms[0]);
+            let term_doc_freq = terms[0].1;
             let idf = idf(term_doc_freq, total_num_docs);
             idf_explain =
                 Explanation::new("idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))", idf);
@@ -66,43 +96,107 @@ impl BM25Weight {
             );
             idf_explain.add_const("N, total number of docs", total_num_docs as f32);
         } else {
-            let idf = terms
+            let term_idf_explains: Vec<Explanation> = terms
                 .iter()
-                .map(|term| {
-                    let term_doc_freq = searcher.doc_freq(term);
-                    idf(term_doc_freq, total_num_docs)
+                .map(|&(ref term, term_doc_freq)| {
+                    let mut term_idf_explain = Explanation::new(
+                        format!("idf({})", String::from_utf8_lossy(term.value_bytes())),
+                        idf(term_doc_freq, total_num_docs),
+                    );
+                    term_idf_explain.add_const(
+                        "n, number of docs containing this term",
+                        term_doc_freq as f32,
+                    );
+                    term_idf_explain
                 })
+                .collect();
+            let idf = term_idf_explains
+                .iter()
+                .map(Explanation::value)
                 .sum::<f32>();
-            idf_explain = Explanation::new("idf", idf);
+            idf_explain = Explanation::new("idf, sum of the idf of the terms", idf);
+            idf_explain.add_const("N, total number of docs", total_num_docs as f32);
+            for term_idf_explain in term_idf_explains {
+                idf_explain.add_detail(term_idf_explain);
+            }
         }
-        BM25Weight::new(idf_explain, average_fieldnorm)
+        Box::new(BM25Weight::new(*self, idf_explain, average_fieldnorm))
     }
+}
+
+struct BM25Weight {
+    similarity: BM25Similarity,
+    idf_explain: Explanation,
+    weight: f32,
+    cache: [f32; 256],
+    average_fieldnorm: f32,
+}
 
-    fn new(idf_explain: Explanation, average_fieldnorm: f32) -> BM25Weight {
-        let weight = idf_explain.value() * (1f32 + K1);
+impl BM25Weight {
+    fn new(
+        similarity: BM25Similarity,
+        idf_explain: Explanation,
+        average_fieldnorm: f32,
+    ) -> BM25Weight {
+        let weight = idf_explain.value() * (1f32 + similarity.k1);
         BM25Weight {
+            similarity,
             idf_explain,
             weight,
-            cache: compute_tf_cache(average_fieldnorm),
+            cache: similarity.compute_tf_cache(average_fieldnorm),
             average_fieldnorm,
         }
     }
 
+    /// Returns the length normalization component of the score of `doc`.
+    ///
+    /// The exact fieldnorm is used if the field has one, the
+    /// precomputed value for the quantized fieldnorm otherwise.
+    #[inline(always)]
+    fn norm(&self, fieldnorm_reader: &FieldNormReader, doc: DocId) -> f32 {
+        if let Some(fieldnorm) = fieldnorm_reader.exact_fieldnorm(doc) {
+            self.similarity
+                .tf_component(fieldnorm, self.average_fieldnorm)
+        } else {
+            self.cache[fieldnorm_reader.fieldnorm_id(doc) as usize]
+        }
+    }
+}
+
+impl SimilarityWeight for BM25Weight {
     #[inline(always)]
-    pub fn score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
-        let norm = self.cache[fieldnorm_id as usize];
-        let term_freq = term_freq as f32;
+    fn score(&self, fieldnorm_reader: &FieldNormReader, doc: DocId, term_freq: f32) -> Score {
+        let norm = self.norm(fieldnorm_reader, doc);
         self.weight * term_freq / (term_freq + norm)
     }
 
-    pub fn explain(&self, fieldnorm_id: u8, term_freq: u32) -> Explanation {
+    fn max_score(&self) -> Score {
+        self.weight
+    }
+
+    fn max_score_for_term_freq(&self, max_term_freq: u32) -> Score {
+        // The length normalization component is the smallest for an empty field.
+        let min_norm = self.similarity.k1 * (1f32 - self.similarity.b);
+        let max_term_freq = max_term_freq as f32;
+        if max_term_freq + min_norm > 0.0 {
+            self.weight * max_term_freq / (max_term_freq + min_norm)
+        } else {
+            self.weight
+        }
+    }
+
+    fn explain(
+        &self,
+        fieldnorm_reader: &FieldNormReader,
+        doc: DocId,
+        term_freq: f32,
+    ) -> Explanation {
         // The explain format is directly copied from Lucene's.
         // (So, Kudos to Lucene)
 
-        let score = self.score(fieldnorm_id, term_freq);
+        let score = self.score(fieldnorm_reader, doc, term_freq);
 
-        let norm = self.cache[fieldnorm_id as usize];
-        let term_freq = term_freq as f32;
+        let norm = self.norm(fieldnorm_reader, doc);
         let right_factor = term_freq / (term_freq + norm);
 
         let mut tf_explanation = Explanation::new(
@@ -111,16 +205,16 @@ impl BM25Weight {
         );
 
         tf_explanation.add_const("freq, occurrences of term within document", term_freq);
-        tf_explanation.add_const("k1, term saturation parameter", K1);
-        tf_explanation.add_const("b, length normalization parameter", B);
+        tf_explanation.add_const("k1, term saturation parameter", self.similarity.k1);
+        tf_explanation.add_const("b, length normalization parameter", self.similarity.b);
         tf_explanation.add_const(
             "dl, length of field",
-            FieldNormReader::id_to_fieldnorm(fieldnorm_id) as f32,
+            fieldnorm_reader.fieldnorm(doc) as f32,
         );
         tf_explanation.add_const("avgdl, average length of field", self.average_fieldnorm);
 
         let mut explanation = Explanation::new("TermQuery, product of...", score);
-        explanation.add_detail(Explanation::new("(K1+1)", K1 + 1f32));
+        explanation.add_detail(Explanation::new("(K1+1)", self.similarity.k1 + 1f32));
         explanation.add_detail(self.idf_explain.clone());
         explanation.add_detail(tf_explanation);
         explanation
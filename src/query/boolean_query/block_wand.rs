@@ -0,0 +1,250 @@
+use crate::docset::{DocSet, SkipResult};
+use crate::query::term_query::TermScorer;
+use crate::query::Scorer;
+use crate::DocId;
+use crate::Score;
+
+struct TermScorerWithMaxScore {
+    // Position of the scorer in the disjunction.
+    ord: usize,
+    scorer: TermScorer,
+    max_score: Score,
+}
+
+/// Returns the number of scorers up to, and including, the pivot,
+/// and the document of the pivot.
+///
+/// The pivot is the first scorer, in the order of their current
+/// document, such that the sum of the max scores of the scorers
+/// up to the pivot exceeds the threshold. All of the scorers
+/// positioned on the document of the pivot are included.
+///
+/// Returns `None` if no document can exceed the threshold anymore.
+fn find_pivot(scorers: &[TermScorerWithMaxScore], threshold: Score) -> Option<(usize, DocId)> {
+    let mut max_score = 0f32;
+    for (ord, term_scorer) in scorers.iter().enumerate() {
+        max_score += term_scorer.max_score;
+        if max_score > threshold {
+            let pivot_doc = term_scorer.scorer.doc();
+            let num_same_doc = scorers[ord + 1..]
+                .iter()
+                .take_while(|term_scorer| term_scorer.scorer.doc() == pivot_doc)
+                .count();
+            return Some((ord + 1 + num_same_doc, pivot_doc));
+        }
+    }
+    None
+}
+
+/// Moves the `num_scorers` first scorers to the first document greater or
+/// equal to `target`, and removes the scorers that are exhausted.
+fn skip_scorers(scorers: &mut Vec<TermScorerWithMaxScore>, num_scorers: usize, target: DocId) {
+    for ord in (0..num_scorers).rev() {
+        let scorer = &mut scorers[ord].scorer;
+        if scorer.doc() < target && scorer.skip_next(target) == SkipResult::End {
+            scorers.swap_remove(ord);
+        }
+    }
+}
+
+/// Advances the `num_scorers` first scorers, and removes the scorers that are exhausted.
+fn advance_scorers(scorers: &mut Vec<TermScorerWithMaxScore>, num_scorers: usize) {
+    for ord in (0..num_scorers).rev() {
+        if !scorers[ord].scorer.advance() {
+            scorers.swap_remove(ord);
+        }
+    }
+}
+
+/// Calls `callback` with the documents matching at least one of the `scorers`,
+/// whose score is greater than the threshold.
+///
+/// The score of a document is the sum of the scores of the scorers matching it.
+/// `callback` returns the new threshold, which should never decrease.
+///
+/// This is the Block-Max WAND algorithm. Thanks to the upper bound of the
+/// scores of each term, and of each of their blocks, the documents that cannot
+/// exceed the threshold are skipped, often without being decoded.
+pub(crate) fn block_wand(
+    scorers: Vec<TermScorer>,
+    mut threshold: Score,
+    callback: &mut dyn FnMut(DocId, Score) -> Score,
+) {
+    let mut scorers: Vec<TermScorerWithMaxScore> = scorers
+        .into_iter()
+        .enumerate()
+        .filter_map(|(ord, mut scorer)| {
+            if scorer.advance() {
+                let max_score = scorer.max_score();
+                Some(TermScorerWithMaxScore {
+                    ord,
+                    scorer,
+                    max_score,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    let mut scores: Vec<(usize, Score)> = Vec::with_capacity(scorers.len());
+    loop {
+        scorers.sort_by_key(|term_scorer| term_scorer.scorer.doc());
+        let (pivot_len, pivot_doc) = match find_pivot(&scorers, threshold) {
+            Some(pivot) => pivot,
+            None => return,
+        };
+
+        if scorers[0].scorer.doc() < pivot_doc {
+            // The documents before the pivot document can only be matched by the
+            // scorers before the pivot, whose max scores do not exceed the threshold.
+            skip_scorers(&mut scorers, pivot_len, pivot_doc);
+            continue;
+        }
+
+        // All of the scorers up to the pivot are positioned on the pivot document.
+        let block_max_score: Score = scorers[..pivot_len]
+            .iter()
+            .map(|term_scorer| term_scorer.scorer.block_max_score())
+            .sum();
+        if block_max_score <= threshold {
+            // Until the end of the first of their blocks to end, and until the
+            // document of the next scorer, the documents can only be matched by
+            // these scorers, and cannot exceed the threshold.
+            let mut target = scorers[..pivot_len]
+                .iter()
+                .map(|term_scorer| term_scorer.scorer.block_last_doc())
+                .min()
+                .unwrap_or(pivot_doc)
+                + 1;
+            if let Some(next_scorer) = scorers.get(pivot_len) {
+                target = target.min(next_scorer.scorer.doc());
+            }
+            skip_scorers(&mut scorers, pivot_len, target);
+            continue;
+        }
+
+        // The scores are summed in the order of the scorers in the disjunction,
+        // so that the score does not depend on the order the documents were visited.
+        scores.clear();
+        for term_scorer in &mut scorers[..pivot_len] {
+            scores.push((term_scorer.ord, term_scorer.scorer.score()));
+        }
+        scores.sort_by_key(|&(ord, _)| ord);
+        let score: Score = scores.iter().map(|&(_, score)| score).sum();
+        if score > threshold {
+            threshold = callback(pivot_doc, score);
+        }
+        advance_scorers(&mut scorers, pivot_len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::TopDocs;
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::tests::assert_nearly_equals;
+    use crate::{DocAddress, Index, Score, Searcher, Term};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    fn all_scores(searcher: &Searcher, query: &dyn Query) -> BTreeMap<DocAddress, Score> {
+        let weight = query.weight(searcher, true).unwrap();
+        let mut scores = BTreeMap::new();
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let mut scorer = weight.scorer(segment_reader).unwrap();
+            scorer.for_each(&mut |doc, score| {
+                if !segment_reader.is_deleted(doc) {
+                    scores.insert(DocAddress(segment_ord as u32, doc), score);
+                }
+            });
+        }
+        scores
+    }
+
+    #[test]
+    fn test_block_wand_top_docs() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut rng = StdRng::from_seed([7u8; 32]);
+        let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
+        for doc_id in 0..5_000 {
+            let mut words = Vec::new();
+            for &(word, probability) in &[("a", 0.5), ("b", 0.1), ("c", 0.02)] {
+                if rng.gen_bool(probability) {
+                    for _ in 0..rng.gen_range(1, 6) {
+                        words.push(word);
+                    }
+                }
+            }
+            words.extend(vec!["z"; rng.gen_range(0, 20)]);
+            index_writer.add_document(doc!(
+                text => words.join(" "),
+                id => format!("{}", doc_id)
+            ));
+            if doc_id == 3_000 {
+                index_writer.commit().unwrap();
+            }
+        }
+        for doc_id in (0..5_000).step_by(7) {
+            index_writer.delete_term(Term::from_field_text(id, &format!("{}", doc_id)));
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let term_query = |word: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text, word),
+                IndexRecordOption::WithFreqs,
+            ))
+        };
+        let query = BooleanQuery::from(vec![
+            (Occur::Should, term_query("a")),
+            (Occur::Should, term_query("b")),
+            (Occur::Should, term_query("c")),
+            (Occur::Should, term_query("missing")),
+        ]);
+        let scores = all_scores(&searcher, &query);
+        let mut sorted_scores: Vec<Score> = scores.values().cloned().collect();
+        sorted_scores.sort_by(|left, right| right.partial_cmp(left).unwrap());
+
+        for &limit in &[1, 10, 100, 10_000] {
+            let top_docs = searcher
+                .search(&query, &TopDocs::with_limit(limit))
+                .unwrap();
+            assert_eq!(top_docs.len(), limit.min(scores.len()));
+            for (rank, (score, doc_address)) in top_docs.iter().enumerate() {
+                assert_nearly_equals(*score, sorted_scores[rank]);
+                assert_nearly_equals(*score, scores[doc_address]);
+            }
+        }
+
+        // With a fixed threshold, exactly the documents exceeding it are pushed.
+        let threshold = sorted_scores[50];
+        let weight = query.weight(&searcher, true).unwrap();
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let mut pushed_docs = Vec::new();
+            weight
+                .for_each_pruning(threshold, segment_reader, &mut |doc, score| {
+                    assert!(score > threshold);
+                    if !segment_reader.is_deleted(doc) {
+                        pushed_docs.push(doc);
+                    }
+                    threshold
+                })
+                .unwrap();
+            let mut expected_docs: Vec<_> = scores
+                .iter()
+                .filter(|&(doc_address, &score)| {
+                    doc_address.segment_ord() == segment_ord as u32 && score > threshold
+                })
+                .map(|(doc_address, _)| doc_address.doc())
+                .collect();
+            expected_docs.sort();
+            assert_eq!(pushed_docs, expected_docs);
+        }
+    }
+}
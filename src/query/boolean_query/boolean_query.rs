@@ -21,6 +21,9 @@ use std::collections::BTreeSet;
 /// * match at least one of the subqueries that is not
 /// a `MustNot` occurence.
 ///
+/// The minimum number of `Should` subqueries a document must match
+/// can be raised with
+/// [`set_minimum_should_match`](#method.set_minimum_should_match).
 ///
 /// You can combine other query types and their `Occur`ances into one `BooleanQuery`
 ///
@@ -130,21 +133,29 @@ use std::collections::BTreeSet;
 #[derive(Debug)]
 pub struct BooleanQuery {
     subqueries: Vec<(Occur, Box<dyn Query>)>,
+    minimum_should_match: usize,
 }
 
 impl Clone for BooleanQuery {
     fn clone(&self) -> Self {
-        self.subqueries
+        let subqueries = self
+            .subqueries
             .iter()
             .map(|(occur, subquery)| (*occur, subquery.box_clone()))
-            .collect::<Vec<_>>()
-            .into()
+            .collect::<Vec<_>>();
+        BooleanQuery {
+            subqueries,
+            minimum_should_match: self.minimum_should_match,
+        }
     }
 }
 
 impl From<Vec<(Occur, Box<dyn Query>)>> for BooleanQuery {
     fn from(subqueries: Vec<(Occur, Box<dyn Query>)>) -> BooleanQuery {
-        BooleanQuery { subqueries }
+        BooleanQuery {
+            subqueries,
+            minimum_should_match: 0,
+        }
     }
 }
 
@@ -154,10 +165,14 @@ impl Query for BooleanQuery {
             .subqueries
             .iter()
             .map(|&(ref occur, ref subquery)| {
-                Ok((*occur, subquery.weight(searcher, scoring_enabled)?))
+                Ok((*occur, searcher.weight(subquery.as_ref(), scoring_enabled)?))
             })
             .collect::<Result<_>>()?;
-        Ok(Box::new(BooleanWeight::new(sub_weights, scoring_enabled)))
+        Ok(Box::new(BooleanWeight::new(
+            sub_weights,
+            self.minimum_should_match,
+            scoring_enabled,
+        )))
     }
 
     fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
@@ -165,6 +180,19 @@ impl Query for BooleanQuery {
             subquery.query_terms(term_set);
         }
     }
+
+    fn cache_key(&self) -> Option<String> {
+        let clauses = self
+            .subqueries
+            .iter()
+            .map(|(occur, subquery)| Some(format!("{:?}({})", occur, subquery.cache_key()?)))
+            .collect::<Option<Vec<String>>>()?;
+        Some(format!(
+            "BooleanQuery({}, [{}])",
+            self.minimum_should_match,
+            clauses.join(", ")
+        ))
+    }
 }
 
 impl BooleanQuery {
@@ -186,4 +214,20 @@ impl BooleanQuery {
     pub fn clauses(&self) -> &[(Occur, Box<dyn Query>)] {
         &self.subqueries[..]
     }
+
+    /// Sets the minimum number of `Should` clauses a document must match.
+    ///
+    /// By default, it is `0`: the `Should` clauses are optional if there is a
+    /// `Must` clause, and at least one of them must match otherwise.
+    ///
+    /// If it is greater than the number of `Should` clauses, the query
+    /// matches no document.
+    pub fn set_minimum_should_match(&mut self, minimum_should_match: usize) {
+        self.minimum_should_match = minimum_should_match;
+    }
+
+    /// Returns the minimum number of `Should` clauses a document must match.
+    pub fn minimum_should_match(&self) -> usize {
+        self.minimum_should_match
+    }
 }
@@ -1,3 +1,5 @@
+use super::block_wand::block_wand;
+use super::minimum_should_match::MinimumShouldMatch;
 use crate::core::SegmentReader;
 use crate::query::explanation::does_not_match;
 use crate::query::score_combiner::{DoNothingCombiner, ScoreCombiner, SumWithCoordsCombiner};
@@ -9,9 +11,9 @@ use crate::query::RequiredOptionalScorer;
 use crate::query::Scorer;
 use crate::query::Union;
 use crate::query::Weight;
-use crate::query::{intersect_scorers, Explanation};
+use crate::query::{for_each_pruning_scorer, intersect_scorers, Explanation};
 use crate::Result;
-use crate::{DocId, SkipResult};
+use crate::{DocId, Score, SkipResult};
 use std::collections::HashMap;
 
 fn scorer_union<TScoreCombiner>(scorers: Vec<Box<dyn Scorer>>) -> Box<dyn Scorer>
@@ -42,17 +44,30 @@ where
 
 pub struct BooleanWeight {
     weights: Vec<(Occur, Box<dyn Weight>)>,
+    minimum_should_match: usize,
     scoring_enabled: bool,
 }
 
 impl BooleanWeight {
-    pub fn new(weights: Vec<(Occur, Box<dyn Weight>)>, scoring_enabled: bool) -> BooleanWeight {
+    pub fn new(
+        weights: Vec<(Occur, Box<dyn Weight>)>,
+        minimum_should_match: usize,
+        scoring_enabled: bool,
+    ) -> BooleanWeight {
         BooleanWeight {
             weights,
+            minimum_should_match,
             scoring_enabled,
         }
     }
 
+    fn num_should_clauses(&self) -> usize {
+        self.weights
+            .iter()
+            .filter(|&&(occur, _)| occur == Occur::Should)
+            .count()
+    }
+
     fn per_occur_scorers(
         &self,
         reader: &SegmentReader,
@@ -74,9 +89,21 @@ impl BooleanWeight {
     ) -> Result<Box<dyn Scorer>> {
         let mut per_occur_scorers = self.per_occur_scorers(reader)?;
 
+        let minimum_should_match = self.minimum_should_match;
         let should_scorer_opt: Option<Box<dyn Scorer>> = per_occur_scorers
             .remove(&Occur::Should)
-            .map(scorer_union::<TScoreCombiner>);
+            .map(|should_scorers| {
+                if minimum_should_match > 1 {
+                    let scorer: Box<dyn Scorer> =
+                        Box::new(MinimumShouldMatch::<_, TScoreCombiner>::new(
+                            should_scorers,
+                            minimum_should_match,
+                        ));
+                    scorer
+                } else {
+                    scorer_union::<TScoreCombiner>(should_scorers)
+                }
+            });
 
         let exclude_scorer_opt: Option<Box<dyn Scorer>> = per_occur_scorers
             .remove(&Occur::MustNot)
@@ -88,7 +115,9 @@ impl BooleanWeight {
 
         let positive_scorer: Box<dyn Scorer> = match (should_scorer_opt, must_scorer_opt) {
             (Some(should_scorer), Some(must_scorer)) => {
-                if self.scoring_enabled {
+                if minimum_should_match > 0 {
+                    intersect_scorers(vec![must_scorer, should_scorer])
+                } else if self.scoring_enabled {
                     Box::new(RequiredOptionalScorer::<_, _, TScoreCombiner>::new(
                         must_scorer,
                         should_scorer,
@@ -114,7 +143,7 @@ impl BooleanWeight {
 
 impl Weight for BooleanWeight {
     fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
-        if self.weights.is_empty() {
+        if self.weights.is_empty() || self.minimum_should_match > self.num_should_clauses() {
             Ok(Box::new(EmptyScorer))
         } else if self.weights.len() == 1 {
             let &(occur, ref weight) = &self.weights[0];
@@ -130,6 +159,52 @@ impl Weight for BooleanWeight {
         }
     }
 
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> Result<()> {
+        let is_disjunction = self.scoring_enabled
+            && self.minimum_should_match <= 1
+            && self.weights.len() > 1
+            && self
+                .weights
+                .iter()
+                .all(|&(occur, _)| occur == Occur::Should);
+        if !is_disjunction {
+            let mut scorer = self.scorer(reader)?;
+            for_each_pruning_scorer(scorer.as_mut(), threshold, callback);
+            return Ok(());
+        }
+        let mut scorers = self
+            .weights
+            .iter()
+            .map(|(_, weight)| weight.scorer(reader))
+            .collect::<Result<Vec<Box<dyn Scorer>>>>()?;
+        scorers.retain(|scorer| !scorer.is::<EmptyScorer>());
+        if scorers.is_empty() {
+            return Ok(());
+        }
+        let is_block_wand_compatible = scorers.iter().all(|scorer| {
+            scorer
+                .downcast_ref::<TermScorer>()
+                .map(|term_scorer| !term_scorer.has_static_boost())
+                .unwrap_or(false)
+        });
+        if is_block_wand_compatible {
+            let scorers: Vec<TermScorer> = scorers
+                .into_iter()
+                .map(|scorer| *(scorer.downcast::<TermScorer>().map_err(|_| ()).unwrap()))
+                .collect();
+            block_wand(scorers, threshold, callback);
+        } else {
+            let mut scorer = scorer_union::<SumWithCoordsCombiner>(scorers);
+            for_each_pruning_scorer(scorer.as_mut(), threshold, callback);
+        }
+        Ok(())
+    }
+
     fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
         let mut scorer = self.scorer(reader)?;
         if scorer.skip_next(doc) != SkipResult::Reached {
@@ -0,0 +1,111 @@
+use crate::docset::{DocSet, SkipResult};
+use crate::query::score_combiner::ScoreCombiner;
+use crate::query::union::unordered_drain_filter;
+use crate::query::Scorer;
+use crate::DocId;
+use crate::Score;
+use std::marker::PhantomData;
+
+/// Disjunction of scorers only matching the documents
+/// matched by at least `minimum_should_match` of them.
+///
+/// Unlike the `Union`, the scorers are not buffered: they are
+/// all moved forward together, one document at a time, so as to
+/// count the scorers positioned on each document.
+pub struct MinimumShouldMatch<TScorer, TScoreCombiner> {
+    scorers: Vec<TScorer>,
+    minimum_should_match: usize,
+    doc: DocId,
+    score: Score,
+    _score_combiner: PhantomData<TScoreCombiner>,
+}
+
+impl<TScorer, TScoreCombiner> MinimumShouldMatch<TScorer, TScoreCombiner>
+where
+    TScorer: Scorer,
+    TScoreCombiner: ScoreCombiner,
+{
+    pub fn new(
+        scorers: Vec<TScorer>,
+        minimum_should_match: usize,
+    ) -> MinimumShouldMatch<TScorer, TScoreCombiner> {
+        let non_empty_scorers: Vec<TScorer> = scorers
+            .into_iter()
+            .filter_map(|mut scorer| if scorer.advance() { Some(scorer) } else { None })
+            .collect();
+        MinimumShouldMatch {
+            scorers: non_empty_scorers,
+            minimum_should_match,
+            doc: 0,
+            score: 0f32,
+            _score_combiner: PhantomData,
+        }
+    }
+}
+
+impl<TScorer, TScoreCombiner> DocSet for MinimumShouldMatch<TScorer, TScoreCombiner>
+where
+    TScorer: Scorer,
+    TScoreCombiner: ScoreCombiner,
+{
+    fn advance(&mut self) -> bool {
+        while self.scorers.len() >= self.minimum_should_match {
+            let candidate = match self.scorers.iter().map(DocSet::doc).min() {
+                Some(candidate) => candidate,
+                None => return false,
+            };
+            let mut score_combiner = TScoreCombiner::default();
+            let mut num_matching_scorers = 0;
+            unordered_drain_filter(&mut self.scorers, |scorer| {
+                if scorer.doc() != candidate {
+                    return false;
+                }
+                score_combiner.update(scorer);
+                num_matching_scorers += 1;
+                !scorer.advance()
+            });
+            if num_matching_scorers >= self.minimum_should_match {
+                self.doc = candidate;
+                self.score = score_combiner.score();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        unordered_drain_filter(&mut self.scorers, |scorer| {
+            scorer.doc() < target && scorer.skip_next(target) == SkipResult::End
+        });
+        if !self.advance() {
+            return SkipResult::End;
+        }
+        if self.doc == target {
+            SkipResult::Reached
+        } else {
+            SkipResult::OverStep
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorers
+            .iter()
+            .map(DocSet::size_hint)
+            .max()
+            .unwrap_or(0u32)
+    }
+}
+
+impl<TScorer, TScoreCombiner> Scorer for MinimumShouldMatch<TScorer, TScoreCombiner>
+where
+    TScorer: Scorer,
+    TScoreCombiner: ScoreCombiner,
+{
+    fn score(&mut self) -> Score {
+        self.score
+    }
+}
@@ -1,5 +1,7 @@
+mod block_wand;
 mod boolean_query;
 mod boolean_weight;
+mod minimum_should_match;
 
 pub use self::boolean_query::BooleanQuery;
 
@@ -18,6 +20,7 @@ mod tests {
     use crate::query::Scorer;
     use crate::query::TermQuery;
     use crate::schema::*;
+    use crate::tests::assert_nearly_equals;
     use crate::Index;
     use crate::{DocAddress, DocId};
 
@@ -179,6 +182,68 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_boolean_minimum_should_match() {
+        let (index, text_field) = aux_test_helper();
+
+        let make_term_query = |text: &str| {
+            let term_query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::WithFreqs,
+            );
+            let query: Box<dyn Query> = Box::new(term_query);
+            query
+        };
+        let make_boolean_query = |clauses: &[(Occur, &str)], minimum_should_match: usize| {
+            let mut boolean_query = BooleanQuery::from(
+                clauses
+                    .iter()
+                    .map(|&(occur, text)| (occur, make_term_query(text)))
+                    .collect::<Vec<_>>(),
+            );
+            boolean_query.set_minimum_should_match(minimum_should_match);
+            boolean_query
+        };
+
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let search = |boolean_query: &dyn Query| {
+            let fruit = searcher
+                .search(boolean_query, &TEST_COLLECTOR_WITH_SCORE)
+                .unwrap();
+            let docs = fruit.docs().iter().map(|doc| doc.1).collect::<Vec<DocId>>();
+            (docs, fruit.scores().to_vec())
+        };
+
+        let should_a_b_d = [
+            (Occur::Should, "a"),
+            (Occur::Should, "b"),
+            (Occur::Should, "d"),
+        ];
+        let (docs, scores) = search(&make_boolean_query(&should_a_b_d, 2));
+        assert_eq!(docs, vec![0, 3]);
+        let (disjunction_docs, disjunction_scores) = search(&make_boolean_query(&should_a_b_d, 0));
+        assert_eq!(disjunction_docs, vec![0, 1, 2, 3, 4]);
+        assert_nearly_equals(scores[0], disjunction_scores[0]);
+        assert_nearly_equals(scores[1], disjunction_scores[3]);
+        assert_eq!(search(&make_boolean_query(&should_a_b_d, 3)).0, vec![3]);
+        assert_eq!(
+            search(&make_boolean_query(&should_a_b_d, 4)).0,
+            Vec::<DocId>::new()
+        );
+
+        let must_c = |shoulds: &[&str], minimum_should_match: usize| {
+            let mut clauses = vec![(Occur::Must, "c")];
+            clauses.extend(shoulds.iter().map(|&text| (Occur::Should, text)));
+            make_boolean_query(&clauses, minimum_should_match)
+        };
+        assert_eq!(search(&must_c(&["d"], 0)).0, vec![0, 1, 2, 3]);
+        assert_eq!(search(&must_c(&["d"], 1)).0, vec![3]);
+        assert_eq!(search(&must_c(&["a", "b"], 2)).0, vec![0, 3]);
+        assert_eq!(must_c(&["a", "b", "d"], 2).count(&searcher).unwrap(), 2);
+        assert_eq!(must_c(&[], 1).count(&searcher).unwrap(), 0);
+    }
+
     #[test]
     pub fn test_intersection_score() {
         let (index, text_field) = aux_test_helper();
@@ -0,0 +1,327 @@
+use crate::common::BitSet;
+use crate::core::Searcher;
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, SkipResult};
+use crate::query::{for_each_pruning_scorer, Explanation, Query, Scorer, Weight};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// `BoostQuery` is a wrapper over a query, multiplying its score by a boost factor.
+///
+/// The documents matched by the `BoostQuery` are exactly the documents
+/// matched by the underlying query.
+///
+/// Boosting the clauses of a [`BooleanQuery`](./struct.BooleanQuery.html) makes
+/// it possible to give more importance to some of them, e.g. to the matches
+/// in the title of a document rather than in its body.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{BooleanQuery, BoostQuery, Occur, Query, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, TEXT};
+/// use tantivy::{doc, DocAddress, Index, Result, Term};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let body = schema_builder.add_text_field("body", TEXT);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(title => "A trip", body => "Far away, in Japan"));
+///         index_writer.add_document(doc!(title => "Japan", body => "A trip far away"));
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     let term_query = |field| -> Box<dyn Query> {
+///         Box::new(TermQuery::new(
+///             Term::from_field_text(field, "japan"),
+///             IndexRecordOption::WithFreqs,
+///         ))
+///     };
+///     let query = BooleanQuery::from(vec![
+///         (Occur::Should, Box::new(BoostQuery::new(term_query(title), 3.0)) as Box<dyn Query>),
+///         (Occur::Should, term_query(body)),
+///     ]);
+///     let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+///     assert_eq!(top_docs[0].1, DocAddress(0, 1));
+///     Ok(())
+/// }
+/// ```
+pub struct BoostQuery {
+    query: Box<dyn Query>,
+    boost: Score,
+}
+
+impl BoostQuery {
+    /// Creates a query multiplying the score of `query` by `boost`.
+    ///
+    /// The boost is expected to be positive: a boost between
+    /// 0 and 1 decreases the score of the documents.
+    pub fn new(query: Box<dyn Query>, boost: Score) -> BoostQuery {
+        BoostQuery { query, boost }
+    }
+
+    /// Returns the boosted query.
+    pub fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
+
+    /// Returns the boost factor.
+    pub fn boost(&self) -> Score {
+        self.boost
+    }
+}
+
+impl Clone for BoostQuery {
+    fn clone(&self) -> Self {
+        BoostQuery {
+            query: self.query.box_clone(),
+            boost: self.boost,
+        }
+    }
+}
+
+impl fmt::Debug for BoostQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Boost(query={:?}, boost={})", self.query, self.boost)
+    }
+}
+
+impl Query for BoostQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let weight = searcher.weight(self.query.as_ref(), scoring_enabled)?;
+        if scoring_enabled {
+            Ok(Box::new(BoostWeight::new(weight, self.boost)))
+        } else {
+            Ok(weight)
+        }
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "BoostQuery({}, {:?})",
+            self.query.cache_key()?,
+            self.boost
+        ))
+    }
+}
+
+/// Weight associated to the `BoostQuery`.
+pub struct BoostWeight {
+    weight: Box<dyn Weight>,
+    boost: Score,
+}
+
+impl BoostWeight {
+    /// Creates a weight multiplying the scores of `weight` by `boost`.
+    pub fn new(weight: Box<dyn Weight>, boost: Score) -> BoostWeight {
+        BoostWeight { weight, boost }
+    }
+}
+
+impl Weight for BoostWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader)?;
+        Ok(Box::new(BoostScorer::new(scorer, self.boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let underlying_explanation = self.weight.explain(reader, doc)?;
+        let mut explanation = Explanation::new(
+            "Boost, product of...",
+            self.boost * underlying_explanation.value(),
+        );
+        explanation.add_const("boost", self.boost);
+        explanation.add_detail(underlying_explanation);
+        Ok(explanation)
+    }
+
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> Result<()> {
+        let boost = self.boost;
+        if boost > 0.0 {
+            // Thresholds are translated back and forth, so that the underlying
+            // weight can skip the documents that cannot make it once boosted.
+            self.weight
+                .for_each_pruning(threshold / boost, reader, &mut |doc, score| {
+                    callback(doc, score * boost) / boost
+                })
+        } else {
+            let mut scorer = self.scorer(reader)?;
+            for_each_pruning_scorer(scorer.as_mut(), threshold, callback);
+            Ok(())
+        }
+    }
+
+    fn count(&self, reader: &SegmentReader) -> Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+/// Scorer multiplying the score of an underlying scorer by a boost factor.
+pub struct BoostScorer<TScorer: Scorer> {
+    scorer: TScorer,
+    boost: Score,
+}
+
+impl<TScorer: Scorer> BoostScorer<TScorer> {
+    /// Creates a scorer multiplying the scores of `scorer` by `boost`.
+    pub fn new(scorer: TScorer, boost: Score) -> BoostScorer<TScorer> {
+        BoostScorer { scorer, boost }
+    }
+}
+
+impl<TScorer: Scorer> DocSet for BoostScorer<TScorer> {
+    fn advance(&mut self) -> bool {
+        self.scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.scorer.skip_next(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId]) -> usize {
+        self.scorer.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+
+    fn append_to_bitset(&mut self, bitset: &mut BitSet) {
+        self.scorer.append_to_bitset(bitset);
+    }
+}
+
+impl<TScorer: Scorer> Scorer for BoostScorer<TScorer> {
+    fn score(&mut self) -> Score {
+        self.boost * self.scorer.score()
+    }
+
+    fn for_each(&mut self, callback: &mut dyn FnMut(DocId, Score)) {
+        let boost = self.boost;
+        self.scorer
+            .for_each(&mut |doc, score| callback(doc, boost * score));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoostQuery;
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::tests::assert_nearly_equals;
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_boost_query() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..50 {
+            if i % 2 == 0 {
+                index_writer.add_document(doc!(title => "beer", body => "wine"));
+            } else {
+                index_writer.add_document(doc!(title => "wine", body => "beer"));
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let term_query = |field, text| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, text),
+                IndexRecordOption::WithFreqs,
+            ))
+        };
+        let unboosted_score = searcher
+            .search(&term_query(title, "beer"), &TopDocs::with_limit(1))
+            .unwrap()[0]
+            .0;
+        let boost_query = BoostQuery::new(term_query(title, "beer"), 2.5);
+        let top_docs = searcher
+            .search(&boost_query, &TopDocs::with_limit(1))
+            .unwrap();
+        assert_nearly_equals(top_docs[0].0, 2.5 * unboosted_score);
+        let explanation = boost_query.explain(&searcher, top_docs[0].1).unwrap();
+        assert_nearly_equals(explanation.value(), top_docs[0].0);
+        assert_eq!(boost_query.count(&searcher).unwrap(), 25);
+
+        // Boosting the body rather than the title flips the ranking.
+        for &(boosted_field, best_doc) in &[(title, 0), (body, 1)] {
+            let query = BooleanQuery::from(vec![
+                (Occur::Should, term_query(title, "beer")),
+                (Occur::Should, term_query(body, "beer")),
+                (
+                    Occur::Should,
+                    Box::new(BoostQuery::new(term_query(boosted_field, "beer"), 2.0)),
+                ),
+            ]);
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(3)).unwrap();
+            assert_eq!(top_docs[0].1, DocAddress(0, best_doc));
+            assert_nearly_equals(top_docs[0].0, 3.0 * unboosted_score);
+        }
+    }
+
+    #[test]
+    fn test_boost_query_pruning() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..100 {
+            let doc_text = format!("{} {}", "a ".repeat(i % 7), "b ".repeat(i % 5));
+            index_writer.add_document(doc!(text => doc_text));
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let disjunction = BooleanQuery::new_multiterms_query(vec![
+            Term::from_field_text(text, "a"),
+            Term::from_field_text(text, "b"),
+        ]);
+        let boost_query = BoostQuery::new(Box::new(disjunction.clone()), 0.5);
+        let top_docs = searcher
+            .search(&boost_query, &TopDocs::with_limit(10))
+            .unwrap();
+        let expected_top_docs = searcher
+            .search(&disjunction, &TopDocs::with_limit(10))
+            .unwrap();
+        assert_eq!(top_docs.len(), 10);
+        for ((score, doc), (expected_score, expected_doc)) in
+            top_docs.into_iter().zip(expected_top_docs)
+        {
+            assert_eq!(doc, expected_doc);
+            assert_nearly_equals(score, 0.5 * expected_score);
+        }
+
+        let zero_boost_query = BoostQuery::new(Box::new(AllQuery), 0.0);
+        let top_docs = searcher
+            .search(&zero_boost_query, &TopDocs::with_limit(3))
+            .unwrap();
+        assert_eq!(top_docs.len(), 3);
+        assert!(top_docs.iter().all(|&(score, _)| score == 0.0));
+    }
+}
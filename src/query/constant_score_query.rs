@@ -0,0 +1,214 @@
+use crate::core::Searcher;
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, SkipResult};
+use crate::query::explanation::does_not_match;
+use crate::query::{ConstScorer, Explanation, Query, Scorer, Weight};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// `ConstantScoreQuery` is a wrapper over a query, giving the same
+/// score to all of the documents it matches.
+///
+/// The underlying query is run with scoring disabled: neither the
+/// term frequencies nor the fieldnorms are read. This makes it a cheap
+/// way to express filters, e.g. as a clause of a
+/// [`BooleanQuery`](./struct.BooleanQuery.html) that should restrict
+/// the matching documents without contributing to their relevance.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{BooleanQuery, ConstantScoreQuery, Occur, Query, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, STRING, TEXT};
+/// use tantivy::{doc, Index, Result, Term};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let body = schema_builder.add_text_field("body", TEXT);
+///     let lang = schema_builder.add_text_field("lang", STRING);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(body => "a trip to Japan", lang => "en"));
+///         index_writer.add_document(doc!(body => "un voyage au Japan", lang => "fr"));
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     let body_query = TermQuery::new(
+///         Term::from_field_text(body, "japan"),
+///         IndexRecordOption::WithFreqs,
+///     );
+///     let lang_filter = TermQuery::new(
+///         Term::from_field_text(lang, "fr"),
+///         IndexRecordOption::Basic,
+///     );
+///     let query = BooleanQuery::from(vec![
+///         (Occur::Must, Box::new(body_query.clone()) as Box<dyn Query>),
+///         (Occur::Must, Box::new(ConstantScoreQuery::new(Box::new(lang_filter), 0.0))),
+///     ]);
+///     let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+///     assert_eq!(top_docs.len(), 1);
+///     let unfiltered_top_docs = searcher.search(&body_query, &TopDocs::with_limit(2))?;
+///     assert_eq!(top_docs[0].0, unfiltered_top_docs[0].0);
+///     Ok(())
+/// }
+/// ```
+pub struct ConstantScoreQuery {
+    query: Box<dyn Query>,
+    score: Score,
+}
+
+impl ConstantScoreQuery {
+    /// Creates a query matching the documents of `query`, all with the given `score`.
+    pub fn new(query: Box<dyn Query>, score: Score) -> ConstantScoreQuery {
+        ConstantScoreQuery { query, score }
+    }
+
+    /// Returns the wrapped query.
+    pub fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
+
+    /// Returns the score given to the matching documents.
+    pub fn score(&self) -> Score {
+        self.score
+    }
+}
+
+impl Clone for ConstantScoreQuery {
+    fn clone(&self) -> Self {
+        ConstantScoreQuery {
+            query: self.query.box_clone(),
+            score: self.score,
+        }
+    }
+}
+
+impl fmt::Debug for ConstantScoreQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Const(query={:?}, score={})", self.query, self.score)
+    }
+}
+
+impl Query for ConstantScoreQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let weight = searcher.weight(self.query.as_ref(), false)?;
+        if scoring_enabled {
+            Ok(Box::new(ConstantScoreWeight::new(weight, self.score)))
+        } else {
+            Ok(weight)
+        }
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "ConstantScoreQuery({}, {:?})",
+            self.query.cache_key()?,
+            self.score
+        ))
+    }
+}
+
+/// Weight associated to the `ConstantScoreQuery`.
+pub struct ConstantScoreWeight {
+    weight: Box<dyn Weight>,
+    score: Score,
+}
+
+impl ConstantScoreWeight {
+    /// Creates a weight giving the same `score` to all of the documents
+    /// matched by `weight`.
+    ///
+    /// `weight` is expected to have been created with scoring disabled.
+    pub fn new(weight: Box<dyn Weight>, score: Score) -> ConstantScoreWeight {
+        ConstantScoreWeight { weight, score }
+    }
+}
+
+impl Weight for ConstantScoreWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let mut scorer = ConstScorer::new(self.weight.scorer(reader)?);
+        scorer.set_score(self.score);
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.weight.scorer(reader)?;
+        if scorer.skip_next(doc) != SkipResult::Reached {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("ConstantScore", self.score))
+    }
+
+    fn count(&self, reader: &SegmentReader) -> Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConstantScoreQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::tests::assert_nearly_equals;
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_constant_score_query() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(body => "beer", tag => "drink"));
+        index_writer.add_document(doc!(body => "beer beer wine", tag => "drink"));
+        index_writer.add_document(doc!(body => "beer", tag => "food"));
+        index_writer.add_document(doc!(body => "bread", tag => "food"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let body_query = TermQuery::new(
+            Term::from_field_text(body, "beer"),
+            IndexRecordOption::WithFreqs,
+        );
+        let query = ConstantScoreQuery::new(Box::new(body_query.clone()), 2.5);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        assert_eq!(top_docs.len(), 3);
+        assert!(top_docs.iter().all(|&(score, _)| score == 2.5));
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 3);
+        let explanation = query.explain(&searcher, DocAddress(0, 1)).unwrap();
+        assert_nearly_equals(explanation.value(), 2.5);
+        assert!(query.explain(&searcher, DocAddress(0, 3)).is_err());
+
+        // As a filter, the query restricts the documents without changing their scores.
+        let tag_query = TermQuery::new(
+            Term::from_field_text(tag, "drink"),
+            IndexRecordOption::Basic,
+        );
+        let filtered_query = BooleanQuery::from(vec![
+            (Occur::Must, Box::new(body_query.clone()) as Box<dyn Query>),
+            (
+                Occur::Must,
+                Box::new(ConstantScoreQuery::new(Box::new(tag_query), 0.0)),
+            ),
+        ]);
+        let filtered_top_docs = searcher
+            .search(&filtered_query, &TopDocs::with_limit(10))
+            .unwrap();
+        assert_eq!(filtered_top_docs.len(), 2);
+        for (score, doc_address) in filtered_top_docs {
+            let explanation = body_query.explain(&searcher, doc_address).unwrap();
+            assert_nearly_equals(score, explanation.value());
+        }
+    }
+}
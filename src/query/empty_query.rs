@@ -23,6 +23,10 @@ impl Query for EmptyQuery {
     fn count(&self, _searcher: &Searcher) -> Result<usize> {
         Ok(0)
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some("EmptyQuery".to_string())
+    }
 }
 
 /// `EmptyWeight` is a dummy `Weight` in which no document matches.
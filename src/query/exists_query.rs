@@ -0,0 +1,234 @@
+use crate::common::BitSet;
+use crate::core::Searcher;
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, SkipResult};
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    AutomatonWeight, BitSetDocSet, ConstScorer, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::{Cardinality, Field, FieldType};
+use crate::DocId;
+use crate::Result;
+use crate::TantivyError;
+use tantivy_fst::automaton::AlwaysMatch;
+
+/// `ExistsQuery` matches the documents having at least one value for a given field.
+///
+/// Depending on the type of the field, the values are detected with:
+/// - the fieldnorms for indexed text fields. A text yielding no token, e.g. an empty
+///   text, does not count as a value.
+/// - the postings of all of the terms of the field for the other indexed fields.
+/// - the fast field for the multivalued fast fields and the bytes fields.
+///
+/// Creating the weight of an `ExistsQuery` on a field that is neither indexed
+/// nor a multivalued fast field returns a `SchemaError`. (Single-valued fast
+/// fields cannot tell the documents without value apart.)
+///
+/// Like the [`RangeQuery`](./struct.RangeQuery.html), it does not score
+/// the documents: all of them get a score of `1`.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::ExistsQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, Result};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let subtitle = schema_builder.add_text_field("subtitle", TEXT);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(title => "Dune", subtitle => "Book one"));
+///         index_writer.add_document(doc!(title => "The Name of the Wind"));
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///     assert_eq!(searcher.search(&ExistsQuery::new(subtitle), &Count)?, 1);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExistsQuery {
+    field: Field,
+}
+
+impl ExistsQuery {
+    /// Creates a query matching the documents having a value for `field`.
+    pub fn new(field: Field) -> ExistsQuery {
+        ExistsQuery { field }
+    }
+
+    /// Returns the field of the query.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+impl Query for ExistsQuery {
+    fn weight(&self, searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let field_entry = searcher.schema().get_field_entry(self.field);
+        let source = match *field_entry.field_type() {
+            FieldType::Str(ref text_options) if text_options.get_indexing_options().is_some() => {
+                ValueSource::FieldNorms
+            }
+            _ if field_entry.is_indexed() => {
+                ValueSource::Terms(AutomatonWeight::new(self.field, AlwaysMatch))
+            }
+            FieldType::Bytes(ref bytes_options) if bytes_options.is_fast() => {
+                ValueSource::FastField
+            }
+            FieldType::U64(ref int_options)
+            | FieldType::I64(ref int_options)
+            | FieldType::F64(ref int_options)
+            | FieldType::Date(ref int_options)
+            | FieldType::GeoPoint(ref int_options)
+                if int_options.get_fastfield_cardinality() == Some(Cardinality::MultiValues) =>
+            {
+                ValueSource::FastField
+            }
+            _ => {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is neither indexed nor a multivalued fast field",
+                    field_entry.name()
+                )));
+            }
+        };
+        Ok(Box::new(ExistsWeight {
+            field: self.field,
+            source,
+        }))
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!("ExistsQuery({:?})", self.field))
+    }
+}
+
+enum ValueSource {
+    FieldNorms,
+    Terms(AutomatonWeight<AlwaysMatch>),
+    FastField,
+}
+
+struct ExistsWeight {
+    field: Field,
+    source: ValueSource,
+}
+
+impl Weight for ExistsWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        match self.source {
+            ValueSource::FieldNorms => {
+                let fieldnorm_reader = reader.get_fieldnorms_reader(self.field);
+                for doc in 0..max_doc {
+                    if fieldnorm_reader.fieldnorm_id(doc) > 0 {
+                        doc_bitset.insert(doc);
+                    }
+                }
+            }
+            ValueSource::Terms(ref automaton_weight) => {
+                return automaton_weight.scorer(reader);
+            }
+            ValueSource::FastField => {
+                let fast_fields = reader.fast_fields();
+                if let Some(bytes_reader) = fast_fields.bytes(self.field) {
+                    for doc in 0..max_doc {
+                        if bytes_reader.num_bytes(doc) > 0 {
+                            doc_bitset.insert(doc);
+                        }
+                    }
+                } else if let Some(multi_bytes_reader) = fast_fields.multi_bytes(self.field) {
+                    for doc in 0..max_doc {
+                        if multi_bytes_reader.num_vals(doc) > 0 {
+                            doc_bitset.insert(doc);
+                        }
+                    }
+                } else {
+                    let vals_reader = fast_fields.u64s_lenient(self.field).ok_or_else(|| {
+                        TantivyError::SchemaError(format!(
+                            "Fast field reader not found for {:?}",
+                            reader.schema().get_field_name(self.field)
+                        ))
+                    })?;
+                    for doc in 0..max_doc {
+                        if vals_reader.num_vals(doc) > 0 {
+                            doc_bitset.insert(doc);
+                        }
+                    }
+                }
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.scorer(reader)?;
+        if scorer.skip_next(doc) != SkipResult::Reached {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("ExistsQuery", 1.0f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExistsQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::Query;
+    use crate::schema::{Cardinality, IntOptions, Schema, FAST, STORED, TEXT};
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_exists_query() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let year = schema_builder.add_u64_field("year", IntOptions::default().set_indexed());
+        let tags = schema_builder.add_i64_field(
+            "tags",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let thumbnail = schema_builder.add_bytes_field("thumbnail", FAST);
+        let comment = schema_builder.add_text_field("comment", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(title => "a", year => 2000u64, tags => -1i64));
+        index_writer.add_document(doc!(title => "", thumbnail => vec![1u8]));
+        index_writer.add_document(doc!(year => 2010u64, tags => 1i64, tags => 2i64));
+        index_writer.add_document(doc!(title => "b", comment => "c"));
+        index_writer.commit().unwrap();
+        index_writer.delete_term(Term::from_field_text(title, "b"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let matching_docs = |query: &ExistsQuery| {
+            let mut docs: Vec<u32> = searcher
+                .search(query, &TopDocs::with_limit(10))
+                .unwrap()
+                .into_iter()
+                .map(|(_, doc_address)| doc_address.doc())
+                .collect();
+            docs.sort();
+            docs
+        };
+        assert_eq!(matching_docs(&ExistsQuery::new(title)), vec![0]);
+        assert_eq!(matching_docs(&ExistsQuery::new(year)), vec![0, 2]);
+        assert_eq!(matching_docs(&ExistsQuery::new(tags)), vec![0, 2]);
+        assert_eq!(matching_docs(&ExistsQuery::new(thumbnail)), vec![1]);
+        assert_eq!(searcher.search(&ExistsQuery::new(year), &Count).unwrap(), 2);
+
+        let query = ExistsQuery::new(year);
+        assert_eq!(
+            query.explain(&searcher, DocAddress(0, 2)).unwrap().value(),
+            1.0
+        );
+        assert!(query.explain(&searcher, DocAddress(0, 1)).is_err());
+
+        assert!(ExistsQuery::new(comment).weight(&searcher, false).is_err());
+    }
+}
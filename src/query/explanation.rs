@@ -9,7 +9,7 @@ pub(crate) fn does_not_match(doc: DocId) -> TantivyError {
 ///
 /// `.to_pretty_json()` can be useful to print out a human readable
 /// representation of this tree when debugging a given score.
-#[derive(Clone, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Explanation {
     value: f32,
     description: String,
@@ -32,6 +32,17 @@ impl Explanation {
         self.value
     }
 
+    /// Returns the description of the current node.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the children of the current node, explaining
+    /// how its value was computed.
+    pub fn details(&self) -> &[Explanation] {
+        &self.details
+    }
+
     /// Add some detail, explaining some part of the current node formula.
     ///
     /// Details are treated as child of the current node.
@@ -0,0 +1,533 @@
+use crate::collector::Order;
+use crate::common::{f64_to_u64, i64_to_u64, BitSet};
+use crate::core::Searcher;
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, SkipResult};
+use crate::error::TantivyError;
+use crate::fastfield::{FastFieldReader, MultiValueIntFastFieldReader};
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, EmptyScorer, Explanation, Query, Scorer, Weight};
+use crate::schema::{Field, Type};
+use crate::DocId;
+use crate::Result;
+use std::collections::Bound;
+use std::ops::Range;
+
+/// `FastFieldRangeQuery` matches all documents having at least one value
+/// of a fast field within a defined range.
+///
+/// Unlike the [`RangeQuery`](./struct.RangeQuery.html), it does not require
+/// the field to be indexed: the values of the documents are directly read
+/// from the fast field. Matched documents all get a constant `Score` of one.
+///
+/// # Implementation
+///
+/// The documents are lazily scanned as the query advances, which makes the query
+/// cheap when intersected with a more selective query. The segments whose
+/// fast field values all lie outside of the range are skipped altogether.
+///
+/// The documents within the range are rather found by binary search if the field
+/// is range indexed (see `IntOptions::set_range_indexed`), or if the index is
+/// sorted by the field (see `IndexSettings::sort_by_field`).
+///
+/// # Example
+///
+/// ```rust
+/// # use tantivy::collector::Count;
+/// # use tantivy::query::FastFieldRangeQuery;
+/// # use tantivy::schema::{Schema, FAST};
+/// # use tantivy::{doc, Index, Result};
+/// #
+/// # fn run() -> Result<()> {
+/// #     let mut schema_builder = Schema::builder();
+/// #     let year_field = schema_builder.add_u64_field("year", FAST);
+/// #     let schema = schema_builder.build();
+/// #
+/// #     let index = Index::create_in_ram(schema);
+/// #     {
+/// #         let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+/// #         for year in 1950u64..2017u64 {
+/// #             let num_docs_within_year = 10 + (year - 1950) * (year - 1950);
+/// #             for _ in 0..num_docs_within_year {
+/// #                 index_writer.add_document(doc!(year_field => year));
+/// #             }
+/// #         }
+/// #         index_writer.commit().unwrap();
+/// #     }
+/// #   let reader = index.reader()?;
+/// let searcher = reader.searcher();
+///
+/// let docs_in_the_sixties = FastFieldRangeQuery::new_u64(year_field, 1960..1970);
+///
+/// let num_60s_books = searcher.search(&docs_in_the_sixties, &Count)?;
+///
+/// #     assert_eq!(num_60s_books, 2285);
+/// #     Ok(())
+/// # }
+/// #
+/// # fn main() {
+/// #   run().unwrap()
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FastFieldRangeQuery {
+    field: Field,
+    value_type: Type,
+    left_bound: Bound<u64>,
+    right_bound: Bound<u64>,
+}
+
+impl FastFieldRangeQuery {
+    /// Creates a new `FastFieldRangeQuery` over a `u64` fast field.
+    ///
+    /// Creating the `Weight` of the query returns a `SchemaError` if
+    /// the field is not a `u64` fast field.
+    pub fn new_u64(field: Field, range: Range<u64>) -> FastFieldRangeQuery {
+        FastFieldRangeQuery::new_u64_bounds(
+            field,
+            Bound::Included(range.start),
+            Bound::Excluded(range.end),
+        )
+    }
+
+    /// Creates a new `FastFieldRangeQuery` over a `u64` fast field.
+    ///
+    /// The two `Bound` arguments make it possible to create more complex
+    /// ranges than semi-inclusive range.
+    pub fn new_u64_bounds(
+        field: Field,
+        left_bound: Bound<u64>,
+        right_bound: Bound<u64>,
+    ) -> FastFieldRangeQuery {
+        FastFieldRangeQuery {
+            field,
+            value_type: Type::U64,
+            left_bound,
+            right_bound,
+        }
+    }
+
+    /// Creates a new `FastFieldRangeQuery` over a `i64` fast field.
+    ///
+    /// Creating the `Weight` of the query returns a `SchemaError` if
+    /// the field is not a `i64` fast field.
+    pub fn new_i64(field: Field, range: Range<i64>) -> FastFieldRangeQuery {
+        FastFieldRangeQuery::new_i64_bounds(
+            field,
+            Bound::Included(range.start),
+            Bound::Excluded(range.end),
+        )
+    }
+
+    /// Creates a new `FastFieldRangeQuery` over a `i64` fast field.
+    ///
+    /// The two `Bound` arguments make it possible to create more complex
+    /// ranges than semi-inclusive range.
+    pub fn new_i64_bounds(
+        field: Field,
+        left_bound: Bound<i64>,
+        right_bound: Bound<i64>,
+    ) -> FastFieldRangeQuery {
+        let to_u64 = |val: &i64| i64_to_u64(*val);
+        FastFieldRangeQuery {
+            field,
+            value_type: Type::I64,
+            left_bound: map_bound(&left_bound, &to_u64),
+            right_bound: map_bound(&right_bound, &to_u64),
+        }
+    }
+
+    /// Creates a new `FastFieldRangeQuery` over a `f64` fast field.
+    ///
+    /// Creating the `Weight` of the query returns a `SchemaError` if
+    /// the field is not a `f64` fast field.
+    pub fn new_f64(field: Field, range: Range<f64>) -> FastFieldRangeQuery {
+        FastFieldRangeQuery::new_f64_bounds(
+            field,
+            Bound::Included(range.start),
+            Bound::Excluded(range.end),
+        )
+    }
+
+    /// Creates a new `FastFieldRangeQuery` over a `f64` fast field.
+    ///
+    /// The two `Bound` arguments make it possible to create more complex
+    /// ranges than semi-inclusive range.
+    pub fn new_f64_bounds(
+        field: Field,
+        left_bound: Bound<f64>,
+        right_bound: Bound<f64>,
+    ) -> FastFieldRangeQuery {
+        let to_u64 = |val: &f64| f64_to_u64(*val);
+        FastFieldRangeQuery {
+            field,
+            value_type: Type::F64,
+            left_bound: map_bound(&left_bound, &to_u64),
+            right_bound: map_bound(&right_bound, &to_u64),
+        }
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+fn map_bound<TFrom, TTo, Transform: Fn(&TFrom) -> TTo>(
+    bound: &Bound<TFrom>,
+    transform: &Transform,
+) -> Bound<TTo> {
+    match bound {
+        Bound::Excluded(ref from_val) => Bound::Excluded(transform(from_val)),
+        Bound::Included(ref from_val) => Bound::Included(transform(from_val)),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl Query for FastFieldRangeQuery {
+    fn weight(&self, searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let field_entry = searcher.schema().get_field_entry(self.field);
+        let value_type = field_entry.field_type().value_type();
+        if value_type != self.value_type {
+            return Err(TantivyError::SchemaError(format!(
+                "Create a fast field range query of the type {:?}, when the field given was of \
+                 type {:?}",
+                self.value_type, value_type
+            )));
+        }
+        if !field_entry.is_int_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a fast field",
+                field_entry.name()
+            )));
+        }
+        // The range is turned into an inclusive one, `None` meaning that it is empty.
+        let low = match self.left_bound {
+            Bound::Included(val) => Some(val),
+            Bound::Excluded(val) => val.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let high = match self.right_bound {
+            Bound::Included(val) => Some(val),
+            Bound::Excluded(val) => val.checked_sub(1),
+            Bound::Unbounded => Some(u64::MAX),
+        };
+        let range = match (low, high) {
+            (Some(low), Some(high)) if low <= high => Some((low, high)),
+            _ => None,
+        };
+        let sort_order = searcher
+            .index()
+            .sort_by_field()
+            .filter(|&(sort_field, _)| sort_field == self.field)
+            .map(|(_, order)| order);
+        Ok(Box::new(FastFieldRangeWeight {
+            field: self.field,
+            range,
+            sort_order,
+        }))
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "FastFieldRangeQuery({:?}, {:?}, {:?}, {:?})",
+            self.field, self.value_type, self.left_bound, self.right_bound
+        ))
+    }
+}
+
+struct FastFieldRangeWeight {
+    field: Field,
+    range: Option<(u64, u64)>,
+    // Order of the values of the field within the segments, if the index is sorted by it.
+    sort_order: Option<Order>,
+}
+
+/// Returns the range of the documents whose value is within `[low, high]`,
+/// by binary search over a column sorted in the given order.
+fn sorted_doc_range(
+    vals_reader: &FastFieldReader<u64>,
+    max_doc: DocId,
+    low: u64,
+    high: u64,
+    order: Order,
+) -> Range<DocId> {
+    let partition_point = |is_before: &dyn Fn(u64) -> bool| {
+        let (mut start, mut end) = (0, max_doc);
+        while start < end {
+            let mid = start + (end - start) / 2;
+            if is_before(vals_reader.get(mid)) {
+                start = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+        start
+    };
+    match order {
+        Order::Asc => partition_point(&|val| val < low)..partition_point(&|val| val <= high),
+        Order::Desc => partition_point(&|val| val > high)..partition_point(&|val| val >= low),
+    }
+}
+
+impl Weight for FastFieldRangeWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let (low, high) = match self.range {
+            Some(range) => range,
+            None => return Ok(Box::new(EmptyScorer)),
+        };
+        let fast_fields = reader.fast_fields();
+        let mut docs = 0..reader.max_doc();
+        let values = if let Some(vals_reader) = fast_fields.u64_lenient(self.field) {
+            if vals_reader.max_value() < low || vals_reader.min_value() > high {
+                return Ok(Box::new(EmptyScorer));
+            }
+            if let Some(range_index) = fast_fields.range_index(self.field) {
+                let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+                range_index.docs_in_range(
+                    &Bound::Included(low),
+                    &Bound::Included(high),
+                    &mut doc_bitset,
+                );
+                return Ok(Box::new(ConstScorer::new(BitSetDocSet::from(doc_bitset))));
+            }
+            if let Some(order) = self.sort_order {
+                docs = sorted_doc_range(&vals_reader, reader.max_doc(), low, high, order);
+            }
+            FastFieldValues::SingleValue(vals_reader)
+        } else if let Some(vals_reader) = fast_fields.u64s_lenient(self.field) {
+            if vals_reader.total_num_vals() == 0
+                || vals_reader.vals_reader().max_value() < low
+                || vals_reader.vals_reader().min_value() > high
+            {
+                return Ok(Box::new(EmptyScorer));
+            }
+            FastFieldValues::MultiValues(vals_reader, Vec::new())
+        } else {
+            return Err(TantivyError::SchemaError(format!(
+                "Fast field reader not found for {:?}",
+                reader.schema().get_field_name(self.field)
+            )));
+        };
+        Ok(Box::new(ConstScorer::new(FastFieldRangeDocSet {
+            values,
+            low,
+            high,
+            doc: 0,
+            next_doc: docs.start,
+            max_doc: docs.end,
+        })))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.scorer(reader)?;
+        if scorer.skip_next(doc) != SkipResult::Reached {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("FastFieldRangeQuery", 1.0f32))
+    }
+}
+
+/// Values of a fast field, mapped to `u64`.
+enum FastFieldValues {
+    SingleValue(FastFieldReader<u64>),
+    MultiValues(MultiValueIntFastFieldReader<u64>, Vec<u64>),
+}
+
+/// `DocSet` scanning the fast field values of the documents,
+/// and emitting those having a value within `[low, high]`.
+struct FastFieldRangeDocSet {
+    values: FastFieldValues,
+    low: u64,
+    high: u64,
+    doc: DocId,
+    next_doc: DocId,
+    max_doc: DocId,
+}
+
+impl FastFieldRangeDocSet {
+    fn matches(&mut self, doc: DocId) -> bool {
+        let (low, high) = (self.low, self.high);
+        match self.values {
+            FastFieldValues::SingleValue(ref vals_reader) => {
+                let val = vals_reader.get(doc);
+                low <= val && val <= high
+            }
+            FastFieldValues::MultiValues(ref vals_reader, ref mut vals) => {
+                vals_reader.get_vals(doc, vals);
+                vals.iter().any(|&val| low <= val && val <= high)
+            }
+        }
+    }
+}
+
+impl DocSet for FastFieldRangeDocSet {
+    fn advance(&mut self) -> bool {
+        while self.next_doc < self.max_doc {
+            let doc = self.next_doc;
+            self.next_doc += 1;
+            if self.matches(doc) {
+                self.doc = doc;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        if self.next_doc < target {
+            self.next_doc = target;
+        }
+        if !self.advance() {
+            return SkipResult::End;
+        }
+        if self.doc == target {
+            SkipResult::Reached
+        } else {
+            SkipResult::OverStep
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.max_doc - self.next_doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FastFieldRangeQuery;
+    use crate::collector::{Count, Order};
+    use crate::directory::RAMDirectory;
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{
+        Cardinality, IndexRecordOption, IntOptions, Schema, Term, FAST, INDEXED, STRING,
+    };
+    use crate::{Index, IndexSettings, TantivyError};
+    use futures::Future;
+    use std::collections::Bound;
+    use std::ops::RangeBounds;
+
+    fn test_fast_field_range_query_aux(int_options: IntOptions, settings: IndexSettings) {
+        let mut schema_builder = Schema::builder();
+        let int_field = schema_builder.add_i64_field("intfield", int_options);
+        let multi_field = schema_builder.add_f64_field(
+            "multifield",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let parity = schema_builder.add_text_field("parity", STRING);
+        let index =
+            Index::create_with_settings(RAMDirectory::create(), schema_builder.build(), settings)
+                .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+        for i in -50i64..50 {
+            let parity_text = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(
+                int_field => i * 3 % 100,
+                multi_field => i as f64,
+                multi_field => (i * 2) as f64,
+                parity => parity_text,
+            ));
+            if i == 0 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.delete_term(Term::from_field_text(parity, "odd"));
+        index_writer.commit().unwrap();
+        let reader = index.reader().unwrap();
+        let count = |left: Bound<i64>, right: Bound<i64>| {
+            let searcher = reader.searcher();
+            let range_query = FastFieldRangeQuery::new_i64_bounds(int_field, left, right);
+            searcher.search(&range_query, &Count).unwrap()
+        };
+        let expected_count = |left: Bound<i64>, right: Bound<i64>| {
+            (-50i64..50)
+                .filter(|i| i % 2 == 0)
+                .map(|i| i * 3 % 100)
+                .filter(|val| (left, right).contains(val))
+                .count()
+        };
+        let bounds = [
+            (Bound::Included(-60), Bound::Excluded(0)),
+            (Bound::Excluded(-60), Bound::Included(0)),
+            (Bound::Included(-6), Bound::Included(6)),
+            (Bound::Unbounded, Bound::Excluded(-200)),
+            (Bound::Included(12), Bound::Unbounded),
+            (Bound::Included(12), Bound::Excluded(12)),
+            (Bound::Unbounded, Bound::Unbounded),
+        ];
+        for &(left, right) in &bounds {
+            assert_eq!(count(left, right), expected_count(left, right));
+        }
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+        reader.reload().unwrap();
+        for &(left, right) in &bounds {
+            assert_eq!(count(left, right), expected_count(left, right));
+        }
+
+        let searcher = reader.searcher();
+        let multi_query = FastFieldRangeQuery::new_f64(multi_field, 40.0..50.0);
+        // 40, 42, 44, 46 and 48 as values, and 20, 22 and 24 as doubled values.
+        assert_eq!(searcher.search(&multi_query, &Count).unwrap(), 8);
+        let even_query = TermQuery::new(
+            Term::from_field_text(parity, "even"),
+            IndexRecordOption::Basic,
+        );
+        let intersection = BooleanQuery::from(vec![
+            (Occur::Must, Box::new(multi_query.clone()) as Box<dyn Query>),
+            (Occur::Must, Box::new(even_query)),
+        ]);
+        assert_eq!(searcher.search(&intersection, &Count).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_fast_field_range_query() {
+        test_fast_field_range_query_aux(IntOptions::from(FAST), IndexSettings::default());
+    }
+
+    #[test]
+    fn test_fast_field_range_query_range_indexed() {
+        let int_options = IntOptions::from(FAST).set_range_indexed();
+        test_fast_field_range_query_aux(int_options, IndexSettings::default());
+    }
+
+    #[test]
+    fn test_fast_field_range_query_sorted_index() {
+        for &order in &[Order::Asc, Order::Desc] {
+            let settings = IndexSettings::default().sort_by_field("intfield", order);
+            test_fast_field_range_query_aux(IntOptions::from(FAST), settings);
+        }
+    }
+
+    #[test]
+    fn test_fast_field_range_query_invalid_field() {
+        let mut schema_builder = Schema::builder();
+        let int_field = schema_builder.add_i64_field("intfield", FAST);
+        let indexed_field = schema_builder.add_u64_field("indexedfield", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader().unwrap().searcher();
+        let is_schema_error = |query: &dyn Query| {
+            matches!(
+                query.weight(&searcher, false),
+                Err(TantivyError::SchemaError(_))
+            )
+        };
+        assert!(is_schema_error(&FastFieldRangeQuery::new_u64(
+            int_field,
+            0..10
+        )));
+        assert!(is_schema_error(&FastFieldRangeQuery::new_u64(
+            indexed_field,
+            0..10
+        )));
+        assert!(!is_schema_error(&FastFieldRangeQuery::new_i64(
+            int_field,
+            0..10
+        )));
+    }
+}
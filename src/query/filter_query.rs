@@ -0,0 +1,575 @@
+use crate::common::BitSet;
+use crate::core::FilterCache;
+use crate::core::Searcher;
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, SkipResult};
+use crate::query::explanation::does_not_match;
+use crate::query::{Explanation, Query, Scorer, Weight};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// A `SegmentDocFilter` tells which documents of a segment are accepted.
+///
+/// It is the segment local version of the [`DocFilter`](./trait.DocFilter.html).
+pub trait SegmentDocFilter: 'static {
+    /// Returns true if `doc` is accepted by the filter.
+    fn accept(&self, doc: DocId) -> bool;
+}
+
+/// `DocFilter` makes it possible to restrict the documents matched by a query
+/// with a predicate, typically over the fast fields of the documents.
+///
+/// Like the [`ScoreFunction`](./trait.ScoreFunction.html), it builds
+/// `Self::Child` instances filtering the documents of a specific segment.
+pub trait DocFilter: Send + Sync + 'static {
+    /// Type of the associated [`SegmentDocFilter`](./trait.SegmentDocFilter.html).
+    type Child: SegmentDocFilter;
+
+    /// Builds the filter of a specific segment.
+    fn segment_filter(&self, segment_reader: &SegmentReader) -> Result<Self::Child>;
+
+    /// Returns a key identifying the documents accepted by the filter, used to
+    /// cache the results of its `FilterQuery` (see `Query::cache_key`).
+    ///
+    /// Closures return `None` (the default), so that their queries are never cached.
+    fn cache_key(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<F, TSegmentDocFilter> DocFilter for F
+where
+    F: 'static + Send + Sync + Fn(&SegmentReader) -> TSegmentDocFilter,
+    TSegmentDocFilter: SegmentDocFilter,
+{
+    type Child = TSegmentDocFilter;
+
+    fn segment_filter(&self, segment_reader: &SegmentReader) -> Result<Self::Child> {
+        Ok((self)(segment_reader))
+    }
+}
+
+impl<F> SegmentDocFilter for F
+where
+    F: 'static + Fn(DocId) -> bool,
+{
+    fn accept(&self, doc: DocId) -> bool {
+        (self)(doc)
+    }
+}
+
+/// `SegmentDocFilter` accepting the documents of a precomputed set.
+#[derive(Clone)]
+pub struct BitSetFilter {
+    bitset: Arc<BitSet>,
+}
+
+impl BitSetFilter {
+    /// Creates a filter accepting the documents of `docset`.
+    ///
+    /// `max_doc` must be greater than all of the documents of `docset`.
+    pub fn for_docset<TDocSet: DocSet>(docset: &mut TDocSet, max_doc: DocId) -> BitSetFilter {
+        let mut bitset = BitSet::with_max_value(max_doc);
+        docset.append_to_bitset(&mut bitset);
+        BitSetFilter {
+            bitset: Arc::new(bitset),
+        }
+    }
+
+    /// Returns the number of documents accepted by the filter.
+    pub fn len(&self) -> usize {
+        self.bitset.len()
+    }
+
+    /// Returns true if the filter does not accept any document.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bytes taken by the filter.
+    pub(crate) fn num_bytes(&self) -> usize {
+        self.bitset.num_bytes()
+    }
+}
+
+impl SegmentDocFilter for BitSetFilter {
+    fn accept(&self, doc: DocId) -> bool {
+        doc < self.bitset.max_value() && self.bitset.contains(doc)
+    }
+}
+
+/// `DocFilter` accepting the documents matched by a query.
+///
+/// The documents of each segment are collected into a
+/// [`BitSetFilter`](./struct.BitSetFilter.html), without being scored.
+///
+/// If the reader of the searcher has a filter cache (see
+/// [`IndexReaderBuilder::filter_cache_size_in_bytes`](../struct.IndexReaderBuilder.html#method.filter_cache_size_in_bytes)),
+/// the filters are cached per segment and shared by the searchers of the reader,
+/// so that frequently used filters are only computed once per segment.
+/// A filter is identified by the cache key of its query (see `Query::cache_key`):
+/// the filters of the queries without a cache key are not cached.
+pub struct QueryFilter {
+    weight: Box<dyn Weight>,
+    cache_key: Option<String>,
+    filter_cache: Option<Arc<FilterCache>>,
+}
+
+impl QueryFilter {
+    /// Creates a filter accepting the documents matching `query`
+    /// within the segments of `searcher`.
+    pub fn new(query: &dyn Query, searcher: &Searcher) -> Result<QueryFilter> {
+        Ok(QueryFilter {
+            weight: searcher.weight(query, false)?,
+            cache_key: query.cache_key(),
+            filter_cache: searcher.filter_cache().cloned(),
+        })
+    }
+}
+
+impl DocFilter for QueryFilter {
+    type Child = BitSetFilter;
+
+    fn segment_filter(&self, segment_reader: &SegmentReader) -> Result<BitSetFilter> {
+        let segment_id = segment_reader.segment_id();
+        let filter_cache = match (&self.filter_cache, &self.cache_key) {
+            (Some(filter_cache), Some(cache_key)) => Some((filter_cache, cache_key)),
+            _ => None,
+        };
+        if let Some((filter_cache, cache_key)) = filter_cache {
+            if let Some(filter) = filter_cache.get(segment_id, cache_key) {
+                return Ok(filter);
+            }
+        }
+        let mut scorer = self.weight.scorer(segment_reader)?;
+        let filter = BitSetFilter::for_docset(&mut scorer, segment_reader.max_doc());
+        if let Some((filter_cache, cache_key)) = filter_cache {
+            filter_cache.insert(segment_id, cache_key.clone(), filter.clone());
+        }
+        Ok(filter)
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        let cache_key = self.cache_key.as_ref()?;
+        Some(format!("QueryFilter({})", cache_key))
+    }
+}
+
+/// `FilterQuery` is a wrapper over a query, only matching the documents
+/// accepted by a [`DocFilter`](./trait.DocFilter.html).
+///
+/// Unlike filtering the documents within a collector, the filter is evaluated
+/// as the scorer advances: the rejected documents are skipped before being scored,
+/// and the top documents can still be collected with the pruning of the
+/// underlying query. The score of the documents is left unchanged.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{FilterQuery, QueryParser};
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, DocId, Index, Result, SegmentReader};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let price = schema_builder.add_u64_field("price", FAST);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(title => "The Diary of Muadib", price => 40u64));
+///         index_writer.add_document(doc!(title => "The Diary of a Young Girl", price => 8u64));
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+///     let query = FilterQuery::new(query, move |segment_reader: &SegmentReader| {
+///         let price_reader = segment_reader.fast_fields().u64(price).unwrap();
+///         move |doc: DocId| price_reader.get(doc) < 10
+///     });
+///     let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+///     assert_eq!(top_docs.len(), 1);
+///     assert_eq!(top_docs[0].1, DocAddress(0, 1));
+///     Ok(())
+/// }
+/// ```
+pub struct FilterQuery<TDocFilter: DocFilter> {
+    query: Box<dyn Query>,
+    filter: Arc<TDocFilter>,
+}
+
+impl<TDocFilter: DocFilter> FilterQuery<TDocFilter> {
+    /// Creates a query matching the documents of `query` accepted by `filter`.
+    pub fn new(query: Box<dyn Query>, filter: TDocFilter) -> FilterQuery<TDocFilter> {
+        FilterQuery {
+            query,
+            filter: Arc::new(filter),
+        }
+    }
+
+    /// Returns the wrapped query.
+    pub fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
+}
+
+impl<TDocFilter: DocFilter> Clone for FilterQuery<TDocFilter> {
+    fn clone(&self) -> Self {
+        FilterQuery {
+            query: self.query.box_clone(),
+            filter: self.filter.clone(),
+        }
+    }
+}
+
+impl<TDocFilter: DocFilter> fmt::Debug for FilterQuery<TDocFilter> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Filter(query={:?})", self.query)
+    }
+}
+
+impl<TDocFilter: DocFilter> Query for FilterQuery<TDocFilter> {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        Ok(Box::new(FilterWeight {
+            weight: searcher.weight(self.query.as_ref(), scoring_enabled)?,
+            filter: self.filter.clone(),
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "FilterQuery({}, {})",
+            self.query.cache_key()?,
+            self.filter.cache_key()?
+        ))
+    }
+}
+
+/// Weight associated to the `FilterQuery`.
+pub struct FilterWeight<TDocFilter: DocFilter> {
+    weight: Box<dyn Weight>,
+    filter: Arc<TDocFilter>,
+}
+
+impl<TDocFilter: DocFilter> Weight for FilterWeight<TDocFilter> {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader)?;
+        let segment_filter = self.filter.segment_filter(reader)?;
+        Ok(Box::new(FilterWrapper::new(scorer, segment_filter)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let segment_filter = self.filter.segment_filter(reader)?;
+        if !segment_filter.accept(doc) {
+            return Err(does_not_match(doc));
+        }
+        self.weight.explain(reader, doc)
+    }
+
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> Result<()> {
+        let segment_filter = self.filter.segment_filter(reader)?;
+        let mut current_threshold = threshold;
+        self.weight
+            .for_each_pruning(threshold, reader, &mut |doc, score| {
+                if segment_filter.accept(doc) {
+                    current_threshold = callback(doc, score);
+                }
+                current_threshold
+            })
+    }
+}
+
+/// Scorer skipping the documents of an underlying scorer that are not
+/// accepted by a [`SegmentDocFilter`](./trait.SegmentDocFilter.html).
+pub struct FilterWrapper<TScorer: Scorer, TSegmentDocFilter: SegmentDocFilter> {
+    scorer: TScorer,
+    segment_filter: TSegmentDocFilter,
+}
+
+impl<TScorer: Scorer, TSegmentDocFilter: SegmentDocFilter>
+    FilterWrapper<TScorer, TSegmentDocFilter>
+{
+    /// Creates a scorer emitting the documents of `scorer` accepted by `segment_filter`.
+    pub fn new(
+        scorer: TScorer,
+        segment_filter: TSegmentDocFilter,
+    ) -> FilterWrapper<TScorer, TSegmentDocFilter> {
+        FilterWrapper {
+            scorer,
+            segment_filter,
+        }
+    }
+
+    /// Advances the underlying scorer up to an accepted document,
+    /// if it is not already on one.
+    fn seek_accepted(&mut self) -> bool {
+        while !self.segment_filter.accept(self.scorer.doc()) {
+            if !self.scorer.advance() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<TScorer: Scorer, TSegmentDocFilter: SegmentDocFilter> DocSet
+    for FilterWrapper<TScorer, TSegmentDocFilter>
+{
+    fn advance(&mut self) -> bool {
+        self.scorer.advance() && self.seek_accepted()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        match self.scorer.skip_next(target) {
+            SkipResult::End => SkipResult::End,
+            SkipResult::Reached if self.segment_filter.accept(target) => SkipResult::Reached,
+            SkipResult::Reached | SkipResult::OverStep => {
+                if self.seek_accepted() {
+                    SkipResult::OverStep
+                } else {
+                    SkipResult::End
+                }
+            }
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl<TScorer: Scorer, TSegmentDocFilter: SegmentDocFilter> Scorer
+    for FilterWrapper<TScorer, TSegmentDocFilter>
+{
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DocFilter, FilterQuery, QueryFilter};
+    use crate::collector::{Count, TopDocs};
+    use crate::merge_policy::NoMergePolicy;
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING, TEXT};
+    use crate::tests::assert_nearly_equals;
+    use crate::{DocAddress, DocId, Index, ReloadPolicy, Score, SegmentReader, Term};
+    use futures::Future;
+
+    #[test]
+    // `is_multiple_of` is not available on the supported Rust versions.
+    #[allow(clippy::manual_is_multiple_of)]
+    fn test_filter_query() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let price = schema_builder.add_u64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..100u64 {
+            let doc_text = format!(
+                "{} {}",
+                "a ".repeat(i as usize % 7),
+                "b ".repeat(i as usize % 5)
+            );
+            index_writer.add_document(doc!(text => doc_text, price => i));
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let disjunction = BooleanQuery::new_multiterms_query(vec![
+            Term::from_field_text(text, "a"),
+            Term::from_field_text(text, "b"),
+        ]);
+        let is_cheap = |doc: DocId| doc % 3 == 0;
+        let query = FilterQuery::new(
+            Box::new(disjunction.clone()),
+            move |segment_reader: &SegmentReader| {
+                let price_reader = segment_reader.fast_fields().u64(price).unwrap();
+                move |doc: DocId| price_reader.get(doc) % 3 == 0
+            },
+        );
+        let expected_docs: Vec<(Score, DocAddress)> = searcher
+            .search(&disjunction, &TopDocs::with_limit(100))
+            .unwrap()
+            .into_iter()
+            .filter(|(_, doc_address)| is_cheap(doc_address.doc()))
+            .collect();
+        assert_eq!(
+            searcher.search(&query, &Count).unwrap(),
+            expected_docs.len()
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        assert_eq!(top_docs.len(), 10);
+        for ((score, doc), (expected_score, expected_doc)) in
+            top_docs.into_iter().zip(expected_docs)
+        {
+            assert_eq!(doc, expected_doc);
+            assert_nearly_equals(score, expected_score);
+            let explanation = query.explain(&searcher, doc).unwrap();
+            assert_nearly_equals(explanation.value(), score);
+        }
+        assert!(query.explain(&searcher, DocAddress(0, 1)).is_err());
+
+        // The filtered scorer can be intersected with other scorers.
+        let conjunction = BooleanQuery::from(vec![
+            (Occur::Must, Box::new(query.clone()) as Box<dyn Query>),
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(text, "b"),
+                    IndexRecordOption::Basic,
+                )),
+            ),
+        ]);
+        let expected_count = (0..100u32)
+            .filter(|&doc| is_cheap(doc) && doc % 5 != 0)
+            .count();
+        assert_eq!(
+            searcher.search(&conjunction, &Count).unwrap(),
+            expected_count
+        );
+    }
+
+    #[test]
+    fn test_query_filter() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let status = schema_builder.add_text_field("status", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text => "a", status => "published"));
+        index_writer.add_document(doc!(text => "a b", status => "draft"));
+        index_writer.add_document(doc!(text => "a a", status => "published"));
+        index_writer.add_document(doc!(text => "b", status => "published"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let published = TermQuery::new(
+            Term::from_field_text(status, "published"),
+            IndexRecordOption::Basic,
+        );
+        let filter = QueryFilter::new(&published, &searcher).unwrap();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let query = FilterQuery::new(Box::new(term_query), filter);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        let docs: Vec<DocAddress> = top_docs.into_iter().map(|(_, doc)| doc).collect();
+        assert_eq!(docs, vec![DocAddress(0, 2), DocAddress(0, 0)]);
+        let query: Box<dyn Query> = Box::new(query);
+        assert!(format!("{:?}", query).starts_with("Filter("));
+    }
+
+    #[test]
+    fn test_query_filter_cache() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let status = schema_builder.add_text_field("status", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text => "a", status => "published"));
+        index_writer.add_document(doc!(text => "a", status => "draft"));
+        index_writer.commit().unwrap();
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .filter_cache_size_in_bytes(10_000)
+            .try_into()
+            .unwrap();
+        let published = TermQuery::new(
+            Term::from_field_text(status, "published"),
+            IndexRecordOption::Basic,
+        );
+        let term_query = TermQuery::new(Term::from_field_text(text, "a"), IndexRecordOption::Basic);
+        let count_published = || {
+            let searcher = reader.searcher();
+            let filter = QueryFilter::new(&published, &searcher).unwrap();
+            let query = FilterQuery::new(Box::new(term_query.clone()), filter);
+            let count = searcher.search(&query, &Count).unwrap();
+            (count, searcher.filter_cache().unwrap().len())
+        };
+        assert_eq!(count_published(), (1, 1));
+        assert_eq!(count_published(), (1, 1));
+
+        // The filter of the first segment is kept when a segment is added...
+        index_writer.add_document(doc!(text => "a", status => "published"));
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(count_published(), (2, 2));
+
+        // ... and dropped once the segment is merged.
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(reader.searcher().filter_cache().unwrap().len(), 0);
+        assert_eq!(count_published(), (2, 1));
+    }
+
+    #[test]
+    fn test_query_filter_cache_key() {
+        let mut schema_builder = Schema::builder();
+        let status = schema_builder.add_text_field("status", STRING);
+        let price = schema_builder.add_u64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0u64..4u64 {
+            index_writer.add_document(doc!(status => "published", price => i));
+        }
+        index_writer.commit().unwrap();
+        let reader = index
+            .reader_builder()
+            .filter_cache_size_in_bytes(10_000)
+            .try_into()
+            .unwrap();
+        let searcher = reader.searcher();
+        let published = TermQuery::new(
+            Term::from_field_text(status, "published"),
+            IndexRecordOption::Basic,
+        );
+        let count_below = |max_price: u64| {
+            // The query of the filter wraps a closure, and has no cache key.
+            let cheap = FilterQuery::new(
+                Box::new(published.clone()),
+                move |segment_reader: &SegmentReader| {
+                    let price_reader = segment_reader.fast_fields().u64(price).unwrap();
+                    move |doc: DocId| price_reader.get(doc) < max_price
+                },
+            );
+            let filter = QueryFilter::new(&cheap, &searcher).unwrap();
+            assert!(filter.cache_key().is_none());
+            let query = FilterQuery::new(Box::new(published.clone()), filter);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count_below(1), 1);
+        assert_eq!(count_below(3), 3);
+        assert_eq!(searcher.filter_cache().unwrap().len(), 0);
+        let filter = QueryFilter::new(&published, &searcher).unwrap();
+        let query = FilterQuery::new(Box::new(published.clone()), filter);
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 4);
+        assert_eq!(searcher.filter_cache().unwrap().len(), 1);
+        assert!(query.cache_key().is_some());
+    }
+}
@@ -0,0 +1,382 @@
+use crate::common::BitSet;
+use crate::core::Searcher;
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, SkipResult};
+use crate::query::{for_each_pruning_scorer, Explanation, Query, Scorer, Weight};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// A `SegmentScoreFunction` computes the final score of the documents
+/// of a segment, given the score computed by the underlying query.
+///
+/// It is the segment local version of the [`ScoreFunction`](./trait.ScoreFunction.html).
+pub trait SegmentScoreFunction: 'static {
+    /// Returns the final score of `doc`, given its original `score`.
+    fn score(&self, doc: DocId, score: Score) -> Score;
+
+    /// Returns a factor `f` such that the final score of a document never
+    /// exceeds `f` times its original score, if there is such a factor.
+    ///
+    /// This makes it possible to skip the documents whose original score is too
+    /// low to make it to the top documents. By default, no bound is known
+    /// and all of the matching documents are scored.
+    fn max_factor(&self) -> Option<Score> {
+        None
+    }
+}
+
+/// `ScoreFunction` makes it possible to combine the score of a query
+/// with a function of the document, typically of its fast fields.
+///
+/// Like the [`ScoreTweaker`](../collector/trait.ScoreTweaker.html), it does not
+/// do much of the computation itself: it builds `Self::Child` instances
+/// scoring the documents of a specific segment.
+pub trait ScoreFunction: Send + Sync + 'static {
+    /// Type of the associated [`SegmentScoreFunction`](./trait.SegmentScoreFunction.html).
+    type Child: SegmentScoreFunction;
+
+    /// Builds the score function of a specific segment.
+    fn segment_function(&self, segment_reader: &SegmentReader) -> Result<Self::Child>;
+}
+
+impl<F, TSegmentScoreFunction> ScoreFunction for F
+where
+    F: 'static + Send + Sync + Fn(&SegmentReader) -> TSegmentScoreFunction,
+    TSegmentScoreFunction: SegmentScoreFunction,
+{
+    type Child = TSegmentScoreFunction;
+
+    fn segment_function(&self, segment_reader: &SegmentReader) -> Result<Self::Child> {
+        Ok((self)(segment_reader))
+    }
+}
+
+impl<F> SegmentScoreFunction for F
+where
+    F: 'static + Fn(DocId, Score) -> Score,
+{
+    fn score(&self, doc: DocId, score: Score) -> Score {
+        (self)(doc, score)
+    }
+}
+
+/// `FunctionScoreQuery` is a wrapper over a query, computing the score of the
+/// documents it matches with a [`ScoreFunction`](./trait.ScoreFunction.html).
+///
+/// Unlike [`TopDocs::tweak_score`](../collector/struct.TopDocs.html#method.tweak_score),
+/// the function is evaluated within the scorer. The query can then be combined
+/// with other queries, and the documents that cannot make it to the top
+/// documents can be skipped if the function declares a
+/// [`max_factor`](./trait.SegmentScoreFunction.html#method.max_factor).
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{FunctionScoreQuery, QueryParser};
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, DocId, Index, Result, Score, SegmentReader};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let popularity = schema_builder.add_u64_field("popularity", FAST);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(title => "The Diary of Muadib", popularity => 1u64));
+///         index_writer.add_document(doc!(title => "The Diary of a Young Girl", popularity => 15u64));
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+///     let query = FunctionScoreQuery::new(query, move |segment_reader: &SegmentReader| {
+///         let popularity_reader = segment_reader.fast_fields().u64(popularity).unwrap();
+///         move |doc: DocId, score: Score| {
+///             let popularity_boost = ((2u64 + popularity_reader.get(doc)) as f32).log2();
+///             popularity_boost * score
+///         }
+///     });
+///     let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+///     assert_eq!(top_docs[0].1, DocAddress(0, 1));
+///     Ok(())
+/// }
+/// ```
+pub struct FunctionScoreQuery<TScoreFunction: ScoreFunction> {
+    query: Box<dyn Query>,
+    score_function: Arc<TScoreFunction>,
+}
+
+impl<TScoreFunction: ScoreFunction> FunctionScoreQuery<TScoreFunction> {
+    /// Creates a query scoring the documents of `query` with `score_function`.
+    pub fn new(
+        query: Box<dyn Query>,
+        score_function: TScoreFunction,
+    ) -> FunctionScoreQuery<TScoreFunction> {
+        FunctionScoreQuery {
+            query,
+            score_function: Arc::new(score_function),
+        }
+    }
+
+    /// Returns the wrapped query.
+    pub fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
+}
+
+impl<TScoreFunction: ScoreFunction> Clone for FunctionScoreQuery<TScoreFunction> {
+    fn clone(&self) -> Self {
+        FunctionScoreQuery {
+            query: self.query.box_clone(),
+            score_function: self.score_function.clone(),
+        }
+    }
+}
+
+impl<TScoreFunction: ScoreFunction> fmt::Debug for FunctionScoreQuery<TScoreFunction> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FunctionScore(query={:?})", self.query)
+    }
+}
+
+impl<TScoreFunction: ScoreFunction> Query for FunctionScoreQuery<TScoreFunction> {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let weight = searcher.weight(self.query.as_ref(), scoring_enabled)?;
+        if scoring_enabled {
+            Ok(Box::new(FunctionScoreWeight {
+                weight,
+                score_function: self.score_function.clone(),
+            }))
+        } else {
+            Ok(weight)
+        }
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+}
+
+/// Weight associated to the `FunctionScoreQuery`.
+pub struct FunctionScoreWeight<TScoreFunction: ScoreFunction> {
+    weight: Box<dyn Weight>,
+    score_function: Arc<TScoreFunction>,
+}
+
+impl<TScoreFunction: ScoreFunction> Weight for FunctionScoreWeight<TScoreFunction> {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader)?;
+        let segment_function = self.score_function.segment_function(reader)?;
+        Ok(Box::new(FunctionScorer {
+            scorer,
+            segment_function,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let underlying_explanation = self.weight.explain(reader, doc)?;
+        let segment_function = self.score_function.segment_function(reader)?;
+        let mut explanation = Explanation::new(
+            "FunctionScore, function of...",
+            segment_function.score(doc, underlying_explanation.value()),
+        );
+        explanation.add_detail(underlying_explanation);
+        Ok(explanation)
+    }
+
+    fn for_each_pruning(
+        &self,
+        mut threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> Result<()> {
+        let segment_function = self.score_function.segment_function(reader)?;
+        match segment_function.max_factor() {
+            Some(max_factor) if max_factor > 0.0 => {
+                // A document whose original score does not exceed `threshold / max_factor`
+                // cannot exceed `threshold` once scored with the function.
+                self.weight
+                    .for_each_pruning(threshold / max_factor, reader, &mut |doc, score| {
+                        let score = segment_function.score(doc, score);
+                        if score > threshold {
+                            threshold = callback(doc, score);
+                        }
+                        threshold / max_factor
+                    })
+            }
+            _ => {
+                let mut scorer = self.scorer(reader)?;
+                for_each_pruning_scorer(scorer.as_mut(), threshold, callback);
+                Ok(())
+            }
+        }
+    }
+
+    fn count(&self, reader: &SegmentReader) -> Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+/// Scorer computing the score of the documents of an underlying scorer with a
+/// [`SegmentScoreFunction`](./trait.SegmentScoreFunction.html).
+pub struct FunctionScorer<TSegmentScoreFunction: SegmentScoreFunction> {
+    scorer: Box<dyn Scorer>,
+    segment_function: TSegmentScoreFunction,
+}
+
+impl<TSegmentScoreFunction: SegmentScoreFunction> DocSet for FunctionScorer<TSegmentScoreFunction> {
+    fn advance(&mut self) -> bool {
+        self.scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.scorer.skip_next(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId]) -> usize {
+        self.scorer.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+
+    fn append_to_bitset(&mut self, bitset: &mut BitSet) {
+        self.scorer.append_to_bitset(bitset);
+    }
+}
+
+impl<TSegmentScoreFunction: SegmentScoreFunction> Scorer for FunctionScorer<TSegmentScoreFunction> {
+    fn score(&mut self) -> Score {
+        let doc = self.scorer.doc();
+        let score = self.scorer.score();
+        self.segment_function.score(doc, score)
+    }
+
+    fn for_each(&mut self, callback: &mut dyn FnMut(DocId, Score)) {
+        let segment_function = &self.segment_function;
+        self.scorer
+            .for_each(&mut |doc, score| callback(doc, segment_function.score(doc, score)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FunctionScoreQuery, SegmentScoreFunction};
+    use crate::collector::{Count, TopDocs};
+    use crate::fastfield::FastFieldReader;
+    use crate::query::{BooleanQuery, Query};
+    use crate::schema::{Field, Schema, FAST, TEXT};
+    use crate::tests::assert_nearly_equals;
+    use crate::{DocAddress, DocId, Index, Score, SegmentReader, Term};
+
+    struct RecencyDecay {
+        timestamp_reader: FastFieldReader<u64>,
+        now: u64,
+    }
+
+    impl SegmentScoreFunction for RecencyDecay {
+        fn score(&self, doc: DocId, score: Score) -> Score {
+            let age = self.now - self.timestamp_reader.get(doc);
+            score / (1.0 + age as f32 / 10.0)
+        }
+
+        fn max_factor(&self) -> Option<Score> {
+            Some(1.0)
+        }
+    }
+
+    fn recency_decay(timestamp: Field) -> impl Fn(&SegmentReader) -> RecencyDecay {
+        move |segment_reader: &SegmentReader| RecencyDecay {
+            timestamp_reader: segment_reader.fast_fields().u64(timestamp).unwrap(),
+            now: 100,
+        }
+    }
+
+    #[test]
+    fn test_function_score_query() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let timestamp = schema_builder.add_u64_field("timestamp", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..100u64 {
+            let doc_text = format!(
+                "{} {}",
+                "a ".repeat(i as usize % 7),
+                "b ".repeat(i as usize % 5)
+            );
+            index_writer.add_document(doc!(text => doc_text, timestamp => i));
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let disjunction = BooleanQuery::new_multiterms_query(vec![
+            Term::from_field_text(text, "a"),
+            Term::from_field_text(text, "b"),
+        ]);
+        let query =
+            FunctionScoreQuery::new(Box::new(disjunction.clone()), recency_decay(timestamp));
+        assert_eq!(
+            searcher.search(&query, &Count).unwrap(),
+            searcher.search(&disjunction, &Count).unwrap()
+        );
+
+        // The top documents are the same with and without pruning.
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        let mut expected_top_docs: Vec<(Score, DocAddress)> = searcher
+            .search(&disjunction, &TopDocs::with_limit(100))
+            .unwrap()
+            .into_iter()
+            .map(|(score, doc_address)| {
+                let age = 100 - doc_address.doc() as u64;
+                (score / (1.0 + age as f32 / 10.0), doc_address)
+            })
+            .collect();
+        expected_top_docs.sort_by(|left, right| right.0.partial_cmp(&left.0).unwrap());
+        assert_eq!(top_docs.len(), 10);
+        for ((score, doc), (expected_score, expected_doc)) in
+            top_docs.into_iter().zip(expected_top_docs)
+        {
+            assert_eq!(doc, expected_doc);
+            assert_nearly_equals(score, expected_score);
+            let explanation = query.explain(&searcher, doc).unwrap();
+            assert_nearly_equals(explanation.value(), score);
+        }
+    }
+
+    #[test]
+    fn test_function_score_query_closure() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text => "a"));
+        index_writer.add_document(doc!(text => "a"));
+        index_writer.add_document(doc!(text => "b"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let term_query = BooleanQuery::new_multiterms_query(vec![Term::from_field_text(text, "a")]);
+        let query = FunctionScoreQuery::new(Box::new(term_query), |_: &SegmentReader| {
+            |doc: DocId, _score: Score| doc as Score + 1.0
+        });
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        assert_eq!(
+            top_docs,
+            vec![(2.0, DocAddress(0, 1)), (1.0, DocAddress(0, 0))]
+        );
+        let query: Box<dyn Query> = Box::new(query);
+        assert!(format!("{:?}", query).starts_with("FunctionScore("));
+    }
+}
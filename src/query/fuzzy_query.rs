@@ -5,8 +5,9 @@ use crate::Result;
 use crate::Searcher;
 use levenshtein_automata::{LevenshteinAutomatonBuilder, DFA};
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Range;
+use tantivy_fst::Automaton;
 
 /// A range of Levenshtein distances that we will build DFAs for our terms
 /// The computation is exponential, so best keep it to low single digits
@@ -27,6 +28,13 @@ static LEV_BUILDER: Lazy<HashMap<(u8, bool), LevenshteinAutomatonBuilder>> = Laz
 /// A Fuzzy Query matches all of the documents
 /// containing a specific term that is within
 /// Levenshtein distance
+///
+/// The query is expanded to all of the terms of the term dictionary
+/// accepted by a Levenshtein automaton. In prefix mode, a term matches
+/// if one of its prefixes is within the Levenshtein distance.
+///
+/// The number of expanded terms can be capped with
+/// [`set_max_expansions`](#method.set_max_expansions).
 /// ```rust
 /// use tantivy::collector::{Count, TopDocs};
 /// use tantivy::query::FuzzyTermQuery;
@@ -78,8 +86,10 @@ pub struct FuzzyTermQuery {
     distance: u8,
     /// Should a transposition cost 1 or 2?
     transposition_cost_one: bool,
-    ///
+    /// Should the term only match the prefix of the indexed terms?
     prefix: bool,
+    /// Maximum number of terms the query expands to
+    max_expansions: Option<usize>,
 }
 
 impl FuzzyTermQuery {
@@ -90,26 +100,40 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: false,
+            max_expansions: None,
         }
     }
 
-    /// Creates a new Fuzzy Query that treats transpositions as cost one rather than two
+    /// Creates a new Fuzzy Query matching the terms starting with
+    /// a prefix within the Levenshtein distance of `term`.
     pub fn new_prefix(term: Term, distance: u8, transposition_cost_one: bool) -> FuzzyTermQuery {
         FuzzyTermQuery {
             term,
             distance,
             transposition_cost_one,
             prefix: true,
+            max_expansions: None,
         }
     }
 
-    fn specialized_weight(&self) -> Result<AutomatonWeight<DFA>> {
+    /// Limits the number of terms the query expands to.
+    ///
+    /// Only the `max_expansions` terms of the searcher closest to
+    /// the query term are kept. Terms at the same distance are
+    /// kept in the order of the term dictionary.
+    pub fn set_max_expansions(&mut self, max_expansions: usize) {
+        self.max_expansions = Some(max_expansions);
+    }
+
+    fn build_dfa(&self) -> Result<DFA> {
         // LEV_BUILDER is a HashMap, whose `get` method returns an Option
-        match LEV_BUILDER.get(&(self.distance, false)) {
-            // Unwrap the option and build the Ok(AutomatonWeight)
+        match LEV_BUILDER.get(&(self.distance, self.transposition_cost_one)) {
             Some(automaton_builder) => {
-                let automaton = automaton_builder.build_dfa(self.term.text());
-                Ok(AutomatonWeight::new(self.term.field(), automaton))
+                if self.prefix {
+                    Ok(automaton_builder.build_prefix_dfa(self.term.text()))
+                } else {
+                    Ok(automaton_builder.build_dfa(self.term.text()))
+                }
             }
             None => Err(InvalidArgument(format!(
                 "Levenshtein distance of {} is not allowed. Choose a value in the {:?} range",
@@ -117,22 +141,117 @@ impl FuzzyTermQuery {
             ))),
         }
     }
+
+    fn specialized_weight(&self) -> Result<AutomatonWeight<DFA>> {
+        let automaton = self.build_dfa()?;
+        Ok(AutomatonWeight::new(self.term.field(), automaton))
+    }
+
+    /// Returns the `max_expansions` terms of the searcher accepted by `dfa`
+    /// with the lowest distance, sorted in the term dictionary order.
+    fn closest_terms(&self, searcher: &Searcher, dfa: &DFA, max_expansions: usize) -> Vec<Vec<u8>> {
+        let mut distances: BTreeMap<Vec<u8>, u8> = BTreeMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(self.term.field());
+            let mut term_stream = inverted_index.terms().search(dfa).into_stream();
+            while term_stream.advance() {
+                let key = term_stream.key();
+                if !distances.contains_key(key) {
+                    distances.insert(key.to_vec(), dfa.eval(key).to_u8());
+                }
+            }
+        }
+        let mut terms: Vec<(u8, Vec<u8>)> = distances
+            .into_iter()
+            .map(|(term, distance)| (distance, term))
+            .collect();
+        terms.sort();
+        terms.truncate(max_expansions);
+        let mut terms: Vec<Vec<u8>> = terms.into_iter().map(|(_, term)| term).collect();
+        terms.sort();
+        terms
+    }
 }
 
 impl Query for FuzzyTermQuery {
-    fn weight(&self, _searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+    fn weight(&self, searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        if let Some(max_expansions) = self.max_expansions {
+            let dfa = self.build_dfa()?;
+            let terms = self.closest_terms(searcher, &dfa, max_expansions);
+            let automaton = SortedTermsAutomaton { terms };
+            return Ok(Box::new(AutomatonWeight::new(self.term.field(), automaton)));
+        }
         Ok(Box::new(self.specialized_weight()?))
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "FuzzyTermQuery({:?}, {}, {}, {}, {:?})",
+            self.term, self.distance, self.transposition_cost_one, self.prefix, self.max_expansions
+        ))
+    }
+}
+
+/// Automaton accepting exactly the terms of a sorted list.
+///
+/// Its state is the range of the terms sharing the bytes
+/// consumed so far, and the number of bytes consumed.
+struct SortedTermsAutomaton {
+    terms: Vec<Vec<u8>>,
+}
+
+impl Automaton for SortedTermsAutomaton {
+    type State = Option<(usize, usize, usize)>;
+
+    fn start(&self) -> Self::State {
+        Some((0, self.terms.len(), 0))
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        match *state {
+            // A term equal to the consumed bytes sorts first in the range.
+            Some((start, end, depth)) => start < end && self.terms[start].len() == depth,
+            None => false,
+        }
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        match *state {
+            Some((start, end, _)) => start < end,
+            None => false,
+        }
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let (start, end, depth) = (*state)?;
+        let candidates = &self.terms[start..end];
+        // The terms shorter than `depth + 1` are sorted first.
+        let lower = candidates.partition_point(|term| match term.get(depth) {
+            Some(&b) => b < byte,
+            None => true,
+        });
+        let upper = candidates.partition_point(|term| match term.get(depth) {
+            Some(&b) => b <= byte,
+            None => true,
+        });
+        if lower < upper {
+            Some((start + lower, start + upper, depth + 1))
+        } else {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::FuzzyTermQuery;
-    use crate::collector::TopDocs;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::Query;
     use crate::schema::Schema;
-    use crate::schema::TEXT;
+    use crate::schema::{STORED, TEXT};
     use crate::tests::assert_nearly_equals;
     use crate::Index;
+    use crate::Searcher;
     use crate::Term;
 
     #[test]
@@ -165,4 +284,63 @@ mod test {
             assert_nearly_equals(1f32, score);
         }
     }
+
+    fn fuzzy_count(searcher: &Searcher, query: &FuzzyTermQuery) -> usize {
+        searcher.search(query, &Count).unwrap()
+    }
+
+    #[test]
+    pub fn test_fuzzy_term_options() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
+            index_writer.add_document(doc!(text => "abcd"));
+            index_writer.add_document(doc!(text => "abdc"));
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text => "abcdefgh"));
+            index_writer.add_document(doc!(text => "abce"));
+            index_writer.add_document(doc!(text => "xbce"));
+            index_writer.commit().unwrap();
+        }
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let term = Term::from_field_text(text, "abcd");
+
+        // "abdc" is a transposition of "abcd"
+        let query = FuzzyTermQuery::new(term.clone(), 1, true);
+        assert_eq!(fuzzy_count(&searcher, &query), 3);
+        let query = FuzzyTermQuery::new(term.clone(), 1, false);
+        assert_eq!(fuzzy_count(&searcher, &query), 2);
+
+        let query = FuzzyTermQuery::new_prefix(term.clone(), 0, false);
+        assert_eq!(fuzzy_count(&searcher, &query), 2);
+        let query = FuzzyTermQuery::new_prefix(term.clone(), 1, false);
+        assert_eq!(fuzzy_count(&searcher, &query), 4);
+
+        // "abcd" is at distance 0, "abdc" and "abce" at distance 1, "xbce" at distance 2
+        let mut query = FuzzyTermQuery::new(term.clone(), 2, true);
+        assert_eq!(fuzzy_count(&searcher, &query), 4);
+        query.set_max_expansions(0);
+        assert_eq!(fuzzy_count(&searcher, &query), 0);
+        query.set_max_expansions(1);
+        assert_eq!(fuzzy_count(&searcher, &query), 1);
+        query.set_max_expansions(3);
+        assert_eq!(fuzzy_count(&searcher, &query), 3);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(4)).unwrap();
+        let docs: Vec<String> = top_docs
+            .iter()
+            .map(|&(_, doc_address)| {
+                let doc = searcher.doc(doc_address).unwrap();
+                doc.get_first(text).unwrap().text().unwrap().to_string()
+            })
+            .collect();
+        assert!(!docs.contains(&"xbce".to_string()));
+        query.set_max_expansions(10);
+        assert_eq!(fuzzy_count(&searcher, &query), 4);
+
+        let query = FuzzyTermQuery::new(term, 3, true);
+        assert!(query.weight(&searcher, false).is_err());
+    }
 }
@@ -0,0 +1,169 @@
+use super::{geo_shape_weight, CellRelation, GeoRect, GeoShape};
+use crate::query::{Query, Weight};
+use crate::schema::{Field, GeoPoint};
+use crate::Result;
+use crate::Searcher;
+
+/// `GeoBoundingBoxQuery` matches the documents having a point
+/// within a bounding box, typically the viewport of a map.
+///
+/// The bounding box is given by its top left and bottom right corners.
+/// If the longitude of the top left corner is greater than the one of
+/// the bottom right corner, the bounding box crosses the antimeridian.
+///
+/// The geo point field must be indexed, and must also be a fast field:
+/// the points of the documents close to the border of the bounding box
+/// are checked using the fast field.
+///
+/// All matching documents get a score of `1.0`.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::GeoBoundingBoxQuery;
+/// use tantivy::schema::{GeoPoint, Schema, FAST, INDEXED};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_geo_point_field("location", INDEXED | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+/// index_writer.add_document(doc!(location => GeoPoint::new(48.8566, 2.3522))); // Paris
+/// index_writer.add_document(doc!(location => GeoPoint::new(45.7640, 4.8357))); // Lyon
+/// index_writer.add_document(doc!(location => GeoPoint::new(51.5074, -0.1278))); // London
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+/// let france = GeoBoundingBoxQuery::new(
+///     location,
+///     GeoPoint::new(51.1, -5.2),
+///     GeoPoint::new(42.3, 8.3),
+/// );
+/// assert_eq!(searcher.search(&france, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GeoBoundingBoxQuery {
+    field: Field,
+    top_left: GeoPoint,
+    bottom_right: GeoPoint,
+}
+
+impl GeoBoundingBoxQuery {
+    /// Creates a query matching the documents with a point of `field`
+    /// within the bounding box delimited by `top_left` and `bottom_right`.
+    pub fn new(field: Field, top_left: GeoPoint, bottom_right: GeoPoint) -> GeoBoundingBoxQuery {
+        GeoBoundingBoxQuery {
+            field,
+            top_left,
+            bottom_right,
+        }
+    }
+
+    /// Returns the field of the query.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Returns the top left corner of the bounding box.
+    pub fn top_left(&self) -> GeoPoint {
+        self.top_left
+    }
+
+    /// Returns the bottom right corner of the bounding box.
+    pub fn bottom_right(&self) -> GeoPoint {
+        self.bottom_right
+    }
+}
+
+impl Query for GeoBoundingBoxQuery {
+    fn weight(&self, searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let (min_lat, max_lat) = (self.bottom_right.lat, self.top_left.lat);
+        let rect = |min_lon, max_lon| GeoRect {
+            min_lat,
+            max_lat,
+            min_lon,
+            max_lon,
+        };
+        let rects = if self.top_left.lon <= self.bottom_right.lon {
+            vec![rect(self.top_left.lon, self.bottom_right.lon)]
+        } else {
+            vec![
+                rect(self.top_left.lon, 180f64),
+                rect(-180f64, self.bottom_right.lon),
+            ]
+        };
+        geo_shape_weight(
+            searcher,
+            self.field,
+            Box::new(GeoBoundingBox { rects }),
+            "GeoBoundingBoxQuery",
+        )
+    }
+}
+
+/// A bounding box, split in two rectangles if it crosses the antimeridian.
+#[derive(Debug)]
+struct GeoBoundingBox {
+    rects: Vec<GeoRect>,
+}
+
+impl GeoShape for GeoBoundingBox {
+    fn relate(&self, cell_rect: &GeoRect) -> CellRelation {
+        let mut relation = CellRelation::Disjoint;
+        for rect in &self.rects {
+            if rect.contains_rect(cell_rect) {
+                return CellRelation::Within;
+            }
+            if rect.intersects_rect(cell_rect) {
+                relation = CellRelation::Crosses;
+            }
+        }
+        relation
+    }
+
+    fn contains(&self, geo_point: &GeoPoint) -> bool {
+        self.rects
+            .iter()
+            .any(|rect| rect.contains_lat(geo_point.lat) && rect.contains_lon(geo_point.lon))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeoBoundingBoxQuery;
+    use crate::collector::Count;
+    use crate::schema::{GeoPoint, Schema, FAST, INDEXED};
+    use crate::Index;
+
+    #[test]
+    fn test_geo_bounding_box_query() {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for lat in -9..=9 {
+            for lon in -18..=18 {
+                let geo_point = GeoPoint::new(f64::from(lat * 10), f64::from(lon * 10));
+                index_writer.add_document(doc!(location => geo_point));
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count = |top_left: (f64, f64), bottom_right: (f64, f64)| {
+            let query = GeoBoundingBoxQuery::new(
+                location,
+                GeoPoint::new(top_left.0, top_left.1),
+                GeoPoint::new(bottom_right.0, bottom_right.1),
+            );
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count((1.0, 1.0), (-1.0, 9.0)), 0);
+        assert_eq!(count((1.0, -1.0), (-1.0, 1.0)), 1);
+        assert_eq!(count((35.0, -15.0), (5.0, 25.0)), 3 * 4);
+        assert_eq!(count((90.0, -180.0), (-90.0, 180.0)), 19 * 37);
+        // crossing the antimeridian
+        assert_eq!(count((15.0, 165.0), (-15.0, -165.0)), 3 * 4);
+        assert_eq!(count((15.0, 175.0), (-15.0, -175.0)), 3 * 2);
+    }
+}
@@ -0,0 +1,338 @@
+use super::{geo_shape_weight, CellRelation, GeoRect, GeoShape};
+use crate::query::{Query, Weight};
+use crate::schema::{Field, GeoPoint};
+use crate::Result;
+use crate::Searcher;
+
+/// Margin, in meters, absorbing the rounding errors when relating a cell to a circle.
+const RELATE_MARGIN_METERS: f64 = 1f64;
+
+/// `GeoDistanceQuery` matches the documents having a point
+/// within a given distance of a center.
+///
+/// The geo point field must be indexed, and must also be a fast field:
+/// the points of the documents close to the border of the circle are
+/// checked using the fast field.
+///
+/// All matching documents get a score of `1.0`.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::GeoDistanceQuery;
+/// use tantivy::schema::{GeoPoint, Schema, FAST, INDEXED};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_geo_point_field("location", INDEXED | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+/// index_writer.add_document(doc!(location => GeoPoint::new(48.8566, 2.3522))); // Paris
+/// index_writer.add_document(doc!(location => GeoPoint::new(48.8049, 2.1204))); // Versailles
+/// index_writer.add_document(doc!(location => GeoPoint::new(51.5074, -0.1278))); // London
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+/// let around_paris = GeoDistanceQuery::new(location, GeoPoint::new(48.8566, 2.3522), 50_000.0);
+/// assert_eq!(searcher.search(&around_paris, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GeoDistanceQuery {
+    field: Field,
+    center: GeoPoint,
+    radius: f64,
+}
+
+impl GeoDistanceQuery {
+    /// Creates a query matching the documents with a point of `field`
+    /// at most `radius` meters away from `center`.
+    pub fn new(field: Field, center: GeoPoint, radius: f64) -> GeoDistanceQuery {
+        GeoDistanceQuery {
+            field,
+            center,
+            radius,
+        }
+    }
+
+    /// Returns the field of the query.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Returns the center of the circle.
+    pub fn center(&self) -> GeoPoint {
+        self.center
+    }
+
+    /// Returns the radius of the circle, in meters.
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+}
+
+impl Query for GeoDistanceQuery {
+    fn weight(&self, searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let circle = GeoCircle {
+            center: self.center,
+            radius: self.radius,
+        };
+        geo_shape_weight(searcher, self.field, Box::new(circle), "GeoDistanceQuery")
+    }
+}
+
+#[derive(Debug)]
+struct GeoCircle {
+    center: GeoPoint,
+    radius: f64,
+}
+
+/// Returns the difference between two longitudes, within `[0, 180]`.
+fn lon_delta(lon: f64, other_lon: f64) -> f64 {
+    let delta = (lon - other_lon).abs() % 360f64;
+    delta.min(360f64 - delta)
+}
+
+impl GeoCircle {
+    /// Returns the latitude of the point of the meridian `lon` closest to the center.
+    ///
+    /// The result is out of `[-90, 90]` when this point is on the other
+    /// half of the great circle of the meridian.
+    fn closest_lat(&self, lon: f64) -> f64 {
+        let center_lat = self.center.lat.to_radians();
+        let cos_lon_delta = lon_delta(lon, self.center.lon).to_radians().cos();
+        center_lat
+            .sin()
+            .atan2(center_lat.cos() * cos_lon_delta)
+            .to_degrees()
+    }
+
+    /// Returns the extreme distance to the center of the points of the meridian `lon`
+    /// in `rect`, given the latitude `extremum_lat` at which it is reached, if
+    /// the meridian is not limited by `rect`.
+    ///
+    /// The distance varies monotonically between the extremum and its
+    /// opposite along the meridian, so the extreme distance within `rect`
+    /// is reached at the extremum, or at one of the bounds of `rect`.
+    fn extreme_distance(&self, rect: &GeoRect, lon: f64, extremum_lat: f64, max: bool) -> f64 {
+        let distance = |lat: f64| self.center.distance(&GeoPoint { lat, lon });
+        let bound_distances = [distance(rect.min_lat), distance(rect.max_lat)];
+        let mut extreme = if max {
+            bound_distances[0].max(bound_distances[1])
+        } else {
+            bound_distances[0].min(bound_distances[1])
+        };
+        if rect.contains_lat(extremum_lat) {
+            extreme = distance(extremum_lat);
+        }
+        extreme
+    }
+
+    /// The distance to the center decreases as the longitude gets closer to
+    /// the one of the center, whatever the latitude.
+    fn min_distance(&self, rect: &GeoRect) -> f64 {
+        let lon = if rect.contains_lon(self.center.lon) {
+            self.center.lon
+        } else if lon_delta(rect.min_lon, self.center.lon)
+            < lon_delta(rect.max_lon, self.center.lon)
+        {
+            rect.min_lon
+        } else {
+            rect.max_lon
+        };
+        self.extreme_distance(rect, lon, self.closest_lat(lon), false)
+    }
+
+    fn max_distance(&self, rect: &GeoRect) -> f64 {
+        let antipode_lon = if self.center.lon > 0f64 {
+            self.center.lon - 180f64
+        } else {
+            self.center.lon + 180f64
+        };
+        let lon = if rect.contains_lon(antipode_lon) {
+            antipode_lon
+        } else if lon_delta(rect.min_lon, self.center.lon)
+            > lon_delta(rect.max_lon, self.center.lon)
+        {
+            rect.min_lon
+        } else {
+            rect.max_lon
+        };
+        let closest_lat = self.closest_lat(lon);
+        let farthest_lat = if closest_lat > 0f64 {
+            closest_lat - 180f64
+        } else {
+            closest_lat + 180f64
+        };
+        self.extreme_distance(rect, lon, farthest_lat, true)
+    }
+}
+
+impl GeoShape for GeoCircle {
+    fn relate(&self, rect: &GeoRect) -> CellRelation {
+        if self.min_distance(rect) > self.radius + RELATE_MARGIN_METERS {
+            CellRelation::Disjoint
+        } else if self.max_distance(rect) < self.radius - RELATE_MARGIN_METERS {
+            CellRelation::Within
+        } else {
+            CellRelation::Crosses
+        }
+    }
+
+    fn contains(&self, geo_point: &GeoPoint) -> bool {
+        self.center.distance(geo_point) <= self.radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GeoCircle, GeoDistanceQuery};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::geo::{CellRelation, GeoRect, GeoShape};
+    use crate::query::Query;
+    use crate::schema::{Cardinality, GeoPoint, IntOptions, Schema, FAST, INDEXED};
+    use crate::{Index, TantivyError};
+
+    fn grid_points() -> Vec<GeoPoint> {
+        let mut geo_points = Vec::new();
+        for lat in -9..=9 {
+            for lon in -18..=18 {
+                geo_points.push(GeoPoint::new(f64::from(lat * 10), f64::from(lon * 10)));
+            }
+        }
+        geo_points
+    }
+
+    #[test]
+    fn test_geo_circle_relate() {
+        let circle = GeoCircle {
+            center: GeoPoint::new(0.0, 0.0),
+            radius: 200_000.0,
+        };
+        let rect = |min_lat, max_lat, min_lon, max_lon| GeoRect {
+            min_lat,
+            max_lat,
+            min_lon,
+            max_lon,
+        };
+        assert_eq!(
+            circle.relate(&rect(-1.0, 1.0, -1.0, 1.0)),
+            CellRelation::Within
+        );
+        assert_eq!(
+            circle.relate(&rect(1.0, 3.0, -1.0, 1.0)),
+            CellRelation::Crosses
+        );
+        assert_eq!(
+            circle.relate(&rect(-1.0, 1.0, 3.0, 5.0)),
+            CellRelation::Disjoint
+        );
+        assert_eq!(
+            circle.relate(&rect(-90.0, 90.0, -180.0, 180.0)),
+            CellRelation::Crosses
+        );
+        // the closest point of the cell is not one of its corners.
+        let polar_circle = GeoCircle {
+            center: GeoPoint::new(80.0, 0.0),
+            radius: 1_000_000.0,
+        };
+        assert_eq!(
+            polar_circle.relate(&rect(70.0, 89.0, 60.0, 70.0)),
+            CellRelation::Crosses
+        );
+        let pole_circle = GeoCircle {
+            center: GeoPoint::new(89.0, 0.0),
+            radius: 1_000_000.0,
+        };
+        assert_eq!(
+            pole_circle.relate(&rect(85.0, 90.0, -180.0, 180.0)),
+            CellRelation::Within
+        );
+    }
+
+    #[test]
+    fn test_geo_distance_query() {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        let geo_points = grid_points();
+        for &geo_point in &geo_points {
+            index_writer.add_document(doc!(location => geo_point));
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let test_query = |center: GeoPoint, radius: f64| {
+            let expected = geo_points
+                .iter()
+                .filter(|geo_point| center.distance(geo_point) <= radius)
+                .count();
+            let query = GeoDistanceQuery::new(location, center, radius);
+            assert_eq!(
+                searcher.search(&query, &Count).unwrap(),
+                expected,
+                "{} {}",
+                center,
+                radius
+            );
+            expected
+        };
+        assert_eq!(test_query(GeoPoint::new(0.0, 0.0), 1_000.0), 1);
+        assert_eq!(test_query(GeoPoint::new(0.0, 0.0), 1_200_000.0), 5);
+        assert_eq!(test_query(GeoPoint::new(90.0, 0.0), 1_000_000.0), 37);
+        assert_eq!(test_query(GeoPoint::new(5.0, 179.0), 800_000.0), 4);
+        test_query(GeoPoint::new(48.8566, 2.3522), 3_000_000.0);
+        test_query(GeoPoint::new(-33.8688, 151.2093), 5_000_000.0);
+        test_query(GeoPoint::new(-33.8688, 151.2093), 20_000_000.0);
+        let explanation = GeoDistanceQuery::new(location, GeoPoint::new(0.0, 0.0), 1_000.0)
+            .explain(&searcher, crate::DocAddress(0, 9 * 37 + 18))
+            .unwrap();
+        assert_eq!(explanation.value(), 1.0f32);
+        let top_docs = searcher
+            .search(
+                &GeoDistanceQuery::new(location, GeoPoint::new(0.0, 0.0), 1_000.0),
+                &TopDocs::with_limit(1),
+            )
+            .unwrap();
+        assert_eq!(top_docs[0].1, crate::DocAddress(0, 9 * 37 + 18));
+    }
+
+    #[test]
+    fn test_geo_distance_query_multivalued() {
+        let mut schema_builder = Schema::builder();
+        let options = IntOptions::default()
+            .set_indexed()
+            .set_fast(Cardinality::MultiValues);
+        let location = schema_builder.add_geo_point_field("location", options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(
+            location => GeoPoint::new(48.8566, 2.3522),
+            location => GeoPoint::new(51.5074, -0.1278)
+        ));
+        index_writer.add_document(doc!(location => GeoPoint::new(40.7128, -74.0060)));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count = |center: GeoPoint| {
+            let query = GeoDistanceQuery::new(location, center, 10_000.0);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(GeoPoint::new(51.5, -0.12)), 1);
+        assert_eq!(count(GeoPoint::new(48.85, 2.35)), 1);
+        assert_eq!(count(GeoPoint::new(40.71, -74.0)), 1);
+        assert_eq!(count(GeoPoint::new(0.0, 0.0)), 0);
+    }
+
+    #[test]
+    fn test_geo_distance_query_requires_fast_field() {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader().unwrap().searcher();
+        let query = GeoDistanceQuery::new(location, GeoPoint::new(0.0, 0.0), 1_000.0);
+        match query.weight(&searcher, false) {
+            Err(TantivyError::SchemaError(_)) => {}
+            _ => panic!("Expected a schema error"),
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use super::{geo_shape_weight, CellRelation, GeoRect, GeoShape};
+use crate::query::{Query, Weight};
+use crate::schema::{Field, GeoPoint};
+use crate::Result;
+use crate::Searcher;
+
+/// `GeoPolygonQuery` matches the documents having a point within a polygon.
+///
+/// The edges of the polygon are straight lines in the latitude and
+/// longitude space, and the polygon may not cross the antimeridian.
+/// The polygon is implicitly closed: its last point is linked to its
+/// first point.
+///
+/// The geo point field must be indexed, and must also be a fast field:
+/// the points of the documents close to the border of the polygon are
+/// checked using the fast field.
+///
+/// All matching documents get a score of `1.0`.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::GeoPolygonQuery;
+/// use tantivy::schema::{GeoPoint, Schema, FAST, INDEXED};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_geo_point_field("location", INDEXED | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+/// index_writer.add_document(doc!(location => GeoPoint::new(48.8566, 2.3522))); // Paris
+/// index_writer.add_document(doc!(location => GeoPoint::new(45.7640, 4.8357))); // Lyon
+/// index_writer.add_document(doc!(location => GeoPoint::new(51.5074, -0.1278))); // London
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+/// let triangle = GeoPolygonQuery::new(
+///     location,
+///     vec![
+///         GeoPoint::new(52.0, -1.0),
+///         GeoPoint::new(49.0, 3.0),
+///         GeoPoint::new(48.0, 1.0),
+///     ],
+/// );
+/// assert_eq!(searcher.search(&triangle, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// Creating a `GeoPolygonQuery` panics if the polygon has less than 3 points.
+#[derive(Clone, Debug)]
+pub struct GeoPolygonQuery {
+    field: Field,
+    points: Vec<GeoPoint>,
+}
+
+impl GeoPolygonQuery {
+    /// Creates a query matching the documents with a point of `field`
+    /// within the polygon `points`.
+    pub fn new(field: Field, points: Vec<GeoPoint>) -> GeoPolygonQuery {
+        assert!(points.len() >= 3, "A polygon requires at least 3 points.");
+        GeoPolygonQuery { field, points }
+    }
+
+    /// Returns the field of the query.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Returns the points of the polygon.
+    pub fn points(&self) -> &[GeoPoint] {
+        &self.points
+    }
+}
+
+impl Query for GeoPolygonQuery {
+    fn weight(&self, searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let polygon = GeoPolygon::new(self.points.clone());
+        geo_shape_weight(searcher, self.field, Box::new(polygon), "GeoPolygonQuery")
+    }
+}
+
+#[derive(Debug)]
+struct GeoPolygon {
+    points: Vec<GeoPoint>,
+    bounding_rect: GeoRect,
+}
+
+/// Returns the cross product of `(b - a)` and `(c - a)`, positive if
+/// `a`, `b`, `c` turn counterclockwise.
+fn orientation(a: &GeoPoint, b: &GeoPoint, c: &GeoPoint) -> f64 {
+    (b.lon - a.lon) * (c.lat - a.lat) - (b.lat - a.lat) * (c.lon - a.lon)
+}
+
+/// Returns true iff the segments `[a, b]` and `[c, d]` intersect.
+fn segments_intersect(a: &GeoPoint, b: &GeoPoint, c: &GeoPoint, d: &GeoPoint) -> bool {
+    let on_sides = |side_1: f64, side_2: f64| side_1 * side_2 <= 0f64;
+    let boxes_intersect = a.lat.min(b.lat) <= c.lat.max(d.lat)
+        && c.lat.min(d.lat) <= a.lat.max(b.lat)
+        && a.lon.min(b.lon) <= c.lon.max(d.lon)
+        && c.lon.min(d.lon) <= a.lon.max(b.lon);
+    boxes_intersect
+        && on_sides(orientation(a, b, c), orientation(a, b, d))
+        && on_sides(orientation(c, d, a), orientation(c, d, b))
+}
+
+impl GeoPolygon {
+    fn new(points: Vec<GeoPoint>) -> GeoPolygon {
+        let mut bounding_rect = GeoRect {
+            min_lat: points[0].lat,
+            max_lat: points[0].lat,
+            min_lon: points[0].lon,
+            max_lon: points[0].lon,
+        };
+        for point in &points[1..] {
+            bounding_rect.min_lat = bounding_rect.min_lat.min(point.lat);
+            bounding_rect.max_lat = bounding_rect.max_lat.max(point.lat);
+            bounding_rect.min_lon = bounding_rect.min_lon.min(point.lon);
+            bounding_rect.max_lon = bounding_rect.max_lon.max(point.lon);
+        }
+        GeoPolygon {
+            points,
+            bounding_rect,
+        }
+    }
+
+    fn edges<'a>(&'a self) -> impl Iterator<Item = (&'a GeoPoint, &'a GeoPoint)> + 'a {
+        let next_points = self.points.iter().skip(1).chain(self.points.first());
+        self.points.iter().zip(next_points)
+    }
+
+    /// Returns true iff an edge of the polygon intersects `rect`, or its border.
+    fn has_edge_in_rect(&self, rect: &GeoRect) -> bool {
+        let corners = [
+            GeoPoint {
+                lat: rect.min_lat,
+                lon: rect.min_lon,
+            },
+            GeoPoint {
+                lat: rect.min_lat,
+                lon: rect.max_lon,
+            },
+            GeoPoint {
+                lat: rect.max_lat,
+                lon: rect.max_lon,
+            },
+            GeoPoint {
+                lat: rect.max_lat,
+                lon: rect.min_lon,
+            },
+        ];
+        self.edges().any(|(start, end)| {
+            let in_rect =
+                |point: &GeoPoint| rect.contains_lat(point.lat) && rect.contains_lon(point.lon);
+            in_rect(start)
+                || in_rect(end)
+                || (0..4)
+                    .any(|i| segments_intersect(start, end, &corners[i], &corners[(i + 1) % 4]))
+        })
+    }
+}
+
+impl GeoShape for GeoPolygon {
+    /// If no edge of the polygon goes through the cell, the cell is either
+    /// entirely within, or entirely out of the polygon.
+    fn relate(&self, rect: &GeoRect) -> CellRelation {
+        if !self.bounding_rect.intersects_rect(rect) {
+            return CellRelation::Disjoint;
+        }
+        if self.has_edge_in_rect(rect) {
+            return CellRelation::Crosses;
+        }
+        let cell_center = GeoPoint {
+            lat: (rect.min_lat + rect.max_lat) / 2f64,
+            lon: (rect.min_lon + rect.max_lon) / 2f64,
+        };
+        if self.contains(&cell_center) {
+            CellRelation::Within
+        } else {
+            CellRelation::Disjoint
+        }
+    }
+
+    /// Counts the edges crossed by a ray going from the point towards the east.
+    fn contains(&self, geo_point: &GeoPoint) -> bool {
+        let mut inside = false;
+        for (start, end) in self.edges() {
+            if (start.lat > geo_point.lat) != (end.lat > geo_point.lat) {
+                let crossing_lon = start.lon
+                    + (geo_point.lat - start.lat) / (end.lat - start.lat) * (end.lon - start.lon);
+                if geo_point.lon < crossing_lon {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GeoPolygon, GeoPolygonQuery};
+    use crate::collector::Count;
+    use crate::query::geo::{CellRelation, GeoRect, GeoShape};
+    use crate::schema::{GeoPoint, Schema, FAST, INDEXED};
+    use crate::Index;
+
+    fn triangle() -> Vec<GeoPoint> {
+        vec![
+            GeoPoint::new(-5.0, -5.0),
+            GeoPoint::new(-5.0, 44.0),
+            GeoPoint::new(44.0, -5.0),
+        ]
+    }
+
+    #[test]
+    fn test_geo_polygon_relate() {
+        let polygon = GeoPolygon::new(triangle());
+        let rect = |min_lat, max_lat, min_lon, max_lon| GeoRect {
+            min_lat,
+            max_lat,
+            min_lon,
+            max_lon,
+        };
+        assert_eq!(
+            polygon.relate(&rect(0.0, 10.0, 0.0, 10.0)),
+            CellRelation::Within
+        );
+        assert_eq!(
+            polygon.relate(&rect(10.0, 30.0, 10.0, 30.0)),
+            CellRelation::Crosses
+        );
+        assert_eq!(
+            polygon.relate(&rect(30.0, 40.0, 30.0, 40.0)),
+            CellRelation::Disjoint
+        );
+        assert_eq!(
+            polygon.relate(&rect(50.0, 60.0, 0.0, 10.0)),
+            CellRelation::Disjoint
+        );
+        // the polygon is within the cell.
+        assert_eq!(
+            polygon.relate(&rect(-90.0, 90.0, -180.0, 180.0)),
+            CellRelation::Crosses
+        );
+        assert!(polygon.contains(&GeoPoint::new(0.0, 0.0)));
+        assert!(polygon.contains(&GeoPoint::new(19.0, 19.0)));
+        assert!(!polygon.contains(&GeoPoint::new(21.0, 20.0)));
+        assert!(!polygon.contains(&GeoPoint::new(-6.0, 0.0)));
+    }
+
+    #[test]
+    fn test_geo_polygon_query() {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for lat in -9..=9 {
+            for lon in -18..=18 {
+                let geo_point = GeoPoint::new(f64::from(lat * 10), f64::from(lon * 10));
+                index_writer.add_document(doc!(location => geo_point));
+            }
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count = |points: Vec<GeoPoint>| {
+            let query = GeoPolygonQuery::new(location, points);
+            searcher.search(&query, &Count).unwrap()
+        };
+        // (0, 0), (0, 10), (0, 20), (0, 30), (10, 0), (10, 10), ...
+        assert_eq!(count(triangle()), 4 + 3 + 2 + 1);
+        // a concave polygon.
+        let concave = vec![
+            GeoPoint::new(-5.0, -5.0),
+            GeoPoint::new(-5.0, 25.0),
+            GeoPoint::new(5.0, 25.0),
+            GeoPoint::new(5.0, 5.0),
+            GeoPoint::new(25.0, 5.0),
+            GeoPoint::new(25.0, -5.0),
+        ];
+        assert_eq!(count(concave), 5);
+        let tiny = vec![
+            GeoPoint::new(-1.0, -1.0),
+            GeoPoint::new(-1.0, 1.0),
+            GeoPoint::new(1.0, 0.0),
+        ];
+        assert_eq!(count(tiny), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_geo_polygon_query_requires_3_points() {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", INDEXED | FAST);
+        GeoPolygonQuery::new(
+            location,
+            vec![GeoPoint::new(0.0, 0.0), GeoPoint::new(1.0, 1.0)],
+        );
+    }
+}
@@ -0,0 +1,361 @@
+mod geo_bounding_box_query;
+mod geo_distance_query;
+mod geo_polygon_query;
+
+pub use self::geo_bounding_box_query::GeoBoundingBoxQuery;
+pub use self::geo_distance_query::GeoDistanceQuery;
+pub use self::geo_polygon_query::GeoPolygonQuery;
+
+use crate::common::BitSet;
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::SkipResult;
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, Explanation, Scorer, Weight};
+use crate::schema::{cell_code, compact_bits, CELL_LEVEL_BITS, NUM_CELL_LEVELS};
+use crate::schema::{Field, FieldType, GeoPoint, IndexRecordOption, Term};
+use crate::DocId;
+use crate::Result;
+use crate::TantivyError;
+use std::fmt;
+
+/// Maximum number of cells used to cover a shape.
+///
+/// Covering a shape with smaller cells requires to read more
+/// posting lists, but less points have to be checked against the shape.
+const MAX_NUM_CELLS: usize = 256;
+
+/// A rectangle in the latitude and longitude space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct GeoRect {
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lon: f64,
+    pub max_lon: f64,
+}
+
+impl GeoRect {
+    pub fn contains_lon(&self, lon: f64) -> bool {
+        self.min_lon <= lon && lon <= self.max_lon
+    }
+
+    pub fn contains_lat(&self, lat: f64) -> bool {
+        self.min_lat <= lat && lat <= self.max_lat
+    }
+
+    pub fn contains_rect(&self, other: &GeoRect) -> bool {
+        self.min_lat <= other.min_lat
+            && other.max_lat <= self.max_lat
+            && self.min_lon <= other.min_lon
+            && other.max_lon <= self.max_lon
+    }
+
+    pub fn intersects_rect(&self, other: &GeoRect) -> bool {
+        self.min_lat <= other.max_lat
+            && other.min_lat <= self.max_lat
+            && self.min_lon <= other.max_lon
+            && other.min_lon <= self.max_lon
+    }
+}
+
+/// How a cell relates to a shape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CellRelation {
+    /// None of the points of the cell are in the shape.
+    Disjoint,
+    /// Some of the points of the cell may be in the shape.
+    Crosses,
+    /// All of the points of the cell are in the shape.
+    Within,
+}
+
+/// A shape searched by a geo query.
+pub(crate) trait GeoShape: fmt::Debug + Send + Sync + 'static {
+    /// Returns how the cell delimited by `rect` relates to the shape.
+    ///
+    /// The relation may be pessimistic: a cell which is actually
+    /// within or disjoint of the shape may be reported as crossing it.
+    fn relate(&self, rect: &GeoRect) -> CellRelation;
+
+    /// Returns true iff the shape contains `geo_point`.
+    fn contains(&self, geo_point: &GeoPoint) -> bool;
+}
+
+/// A cell of the grid in which geo points are indexed.
+///
+/// A cell of level `n` is identified by the `CELL_LEVEL_BITS * n` first bits
+/// of the morton code of its points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GeoCell {
+    morton_prefix: u64,
+    level: u8,
+}
+
+impl GeoCell {
+    /// The cell covering the whole earth.
+    fn root() -> GeoCell {
+        GeoCell {
+            morton_prefix: 0u64,
+            level: 0u8,
+        }
+    }
+
+    fn children(self) -> impl Iterator<Item = GeoCell> {
+        let level = self.level + 1;
+        let shift = 64 - CELL_LEVEL_BITS * u32::from(level);
+        (0u64..1u64 << CELL_LEVEL_BITS).map(move |child| GeoCell {
+            morton_prefix: self.morton_prefix | (child << shift),
+            level,
+        })
+    }
+
+    fn rect(&self) -> GeoRect {
+        let num_bits_per_coordinate = CELL_LEVEL_BITS * u32::from(self.level) / 2;
+        let ratio = |bits: u32| f64::from(bits) / 4_294_967_296f64;
+        let size_ratio = 1f64 / (1u64 << num_bits_per_coordinate) as f64;
+        let min_lat = -90f64 + ratio(compact_bits(self.morton_prefix)) * 180f64;
+        let min_lon = -180f64 + ratio(compact_bits(self.morton_prefix >> 1)) * 360f64;
+        GeoRect {
+            min_lat,
+            max_lat: min_lat + size_ratio * 180f64,
+            min_lon,
+            max_lon: min_lon + size_ratio * 360f64,
+        }
+    }
+
+    fn term(&self, field: Field) -> Term {
+        Term::from_field_u64(field, cell_code(self.morton_prefix, self.level))
+    }
+}
+
+/// Covers `shape` with the cells of the grid.
+///
+/// Returns the cells within the shape, and the cells crossing it.
+/// The crossing cells are refined as long as the total number of
+/// cells remains under `MAX_NUM_CELLS`.
+fn cover(shape: &dyn GeoShape) -> (Vec<GeoCell>, Vec<GeoCell>) {
+    let mut within_cells = Vec::new();
+    let mut crossing_cells = vec![GeoCell::root()];
+    for _ in 0..NUM_CELL_LEVELS {
+        let mut children_within = Vec::new();
+        let mut children_crossing = Vec::new();
+        for child in crossing_cells.iter().flat_map(|cell| cell.children()) {
+            match shape.relate(&child.rect()) {
+                CellRelation::Disjoint => {}
+                CellRelation::Crosses => children_crossing.push(child),
+                CellRelation::Within => children_within.push(child),
+            }
+        }
+        // The root cell is not indexed, and has to be refined.
+        let num_cells = within_cells.len() + children_within.len() + children_crossing.len();
+        if crossing_cells[0].level > 0 && num_cells > MAX_NUM_CELLS {
+            break;
+        }
+        within_cells.extend(children_within);
+        crossing_cells = children_crossing;
+        if crossing_cells.is_empty() {
+            break;
+        }
+    }
+    (within_cells, crossing_cells)
+}
+
+/// Returns the weight searching the points of `field` within `shape`.
+///
+/// Returns an error if `field` is not an indexed geo point fast field.
+pub(crate) fn geo_shape_weight(
+    searcher: &Searcher,
+    field: Field,
+    shape: Box<dyn GeoShape>,
+    query_name: &'static str,
+) -> Result<Box<dyn Weight>> {
+    let field_entry = searcher.schema().get_field_entry(field);
+    match *field_entry.field_type() {
+        FieldType::GeoPoint(ref options)
+            if options.is_indexed() && options.get_fastfield_cardinality().is_some() => {}
+        _ => {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not an indexed geo point fast field",
+                field_entry.name()
+            )));
+        }
+    }
+    let (within_cells, crossing_cells) = cover(shape.as_ref());
+    let terms = |cells: Vec<GeoCell>| cells.iter().map(|cell| cell.term(field)).collect();
+    Ok(Box::new(GeoShapeWeight {
+        field,
+        shape,
+        within_terms: terms(within_cells),
+        crossing_terms: terms(crossing_cells),
+        query_name,
+    }))
+}
+
+/// Weight of the geo queries.
+///
+/// The documents indexed in the cells within the shape match the query,
+/// while the points of the documents indexed in the cells crossing the shape
+/// are checked against the shape, using the fast field.
+struct GeoShapeWeight {
+    field: Field,
+    shape: Box<dyn GeoShape>,
+    within_terms: Vec<Term>,
+    crossing_terms: Vec<Term>,
+    query_name: &'static str,
+}
+
+impl GeoShapeWeight {
+    /// Returns a function returning true iff the document has a point in the shape.
+    fn contains_fn<'a>(
+        &'a self,
+        reader: &SegmentReader,
+    ) -> Result<Box<dyn Fn(DocId) -> bool + 'a>> {
+        let fast_fields = reader.fast_fields();
+        if let Some(ff_reader) = fast_fields.geo_point(self.field) {
+            return Ok(Box::new(move |doc| {
+                self.shape.contains(&ff_reader.get(doc))
+            }));
+        }
+        if let Some(ff_reader) = fast_fields.geo_points(self.field) {
+            return Ok(Box::new(move |doc| {
+                let mut geo_points = Vec::new();
+                ff_reader.get_vals(doc, &mut geo_points);
+                geo_points
+                    .iter()
+                    .any(|geo_point| self.shape.contains(geo_point))
+            }));
+        }
+        Err(TantivyError::SchemaError(format!(
+            "Field {:?} has no fast field reader",
+            reader.schema().get_field_name(self.field)
+        )))
+    }
+}
+
+impl Weight for GeoShapeWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+        let inverted_index = reader.inverted_index(self.field);
+        for term in &self.within_terms {
+            if let Some(mut block_postings) =
+                inverted_index.read_block_postings(term, IndexRecordOption::Basic)
+            {
+                while block_postings.advance() {
+                    for &doc in block_postings.docs() {
+                        doc_bitset.insert(doc);
+                    }
+                }
+            }
+        }
+        let contains = self.contains_fn(reader)?;
+        for term in &self.crossing_terms {
+            if let Some(mut block_postings) =
+                inverted_index.read_block_postings(term, IndexRecordOption::Basic)
+            {
+                while block_postings.advance() {
+                    for &doc in block_postings.docs() {
+                        if contains(doc) {
+                            doc_bitset.insert(doc);
+                        }
+                    }
+                }
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.scorer(reader)?;
+        if scorer.skip_next(doc) != SkipResult::Reached {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new(self.query_name, 1.0f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cover, CellRelation, GeoCell, GeoRect, GeoShape};
+    use crate::schema::{cell_code, GeoPoint};
+
+    #[derive(Debug)]
+    struct RectShape(GeoRect);
+
+    impl GeoShape for RectShape {
+        fn relate(&self, rect: &GeoRect) -> CellRelation {
+            if self.0.contains_rect(rect) {
+                CellRelation::Within
+            } else if self.0.intersects_rect(rect) {
+                CellRelation::Crosses
+            } else {
+                CellRelation::Disjoint
+            }
+        }
+
+        fn contains(&self, geo_point: &GeoPoint) -> bool {
+            self.0.contains_lat(geo_point.lat) && self.0.contains_lon(geo_point.lon)
+        }
+    }
+
+    #[test]
+    fn test_geo_cell_rect() {
+        let children: Vec<GeoCell> = GeoCell::root().children().collect();
+        assert_eq!(children.len(), 16);
+        assert_eq!(
+            children[0].rect(),
+            GeoRect {
+                min_lat: -90.0,
+                max_lat: -45.0,
+                min_lon: -180.0,
+                max_lon: -90.0,
+            }
+        );
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let mut cell = GeoCell::root();
+        for level in 1..=13 {
+            cell = cell
+                .children()
+                .find(|child| {
+                    let rect = child.rect();
+                    rect.contains_lat(paris.lat) && rect.contains_lon(paris.lon)
+                })
+                .unwrap();
+            let morton_code = paris.to_u64();
+            assert_eq!(
+                cell_code(cell.morton_prefix, level),
+                cell_code(morton_code, level)
+            );
+        }
+    }
+
+    #[test]
+    fn test_cover() {
+        let shape = RectShape(GeoRect {
+            min_lat: 10.0,
+            max_lat: 20.0,
+            min_lon: 10.0,
+            max_lon: 20.0,
+        });
+        let (within_cells, crossing_cells) = cover(&shape);
+        assert!(!within_cells.is_empty());
+        assert!(within_cells.len() + crossing_cells.len() <= super::MAX_NUM_CELLS);
+        for cell in &within_cells {
+            assert_eq!(shape.relate(&cell.rect()), CellRelation::Within);
+        }
+        for cell in &crossing_cells {
+            assert_eq!(shape.relate(&cell.rect()), CellRelation::Crosses);
+        }
+        let covered = |geo_point: GeoPoint| {
+            within_cells
+                .iter()
+                .chain(crossing_cells.iter())
+                .any(|cell| {
+                    let rect = cell.rect();
+                    rect.contains_lat(geo_point.lat) && rect.contains_lon(geo_point.lon)
+                })
+        };
+        assert!(covered(GeoPoint::new(15.0, 15.0)));
+        assert!(covered(GeoPoint::new(10.0, 20.0)));
+        assert!(!covered(GeoPoint::new(25.0, 15.0)));
+    }
+}
@@ -5,21 +5,33 @@ Query
 mod all_query;
 mod automaton_weight;
 mod bitset;
+mod block_join;
 mod bm25;
 mod boolean_query;
+mod boost_query;
+mod constant_score_query;
 mod empty_query;
 mod exclude;
+mod exists_query;
 mod explanation;
+mod fast_field_range_query;
+mod filter_query;
+mod function_score_query;
 mod fuzzy_query;
+mod geo;
 mod intersection;
 mod phrase_query;
+mod prefix_query;
 mod query;
 mod query_parser;
 mod range_query;
 mod regex_query;
 mod reqopt_scorer;
 mod scorer;
+mod similarity;
+mod span;
 mod term_query;
+mod term_set_query;
 mod union;
 mod weight;
 
@@ -36,13 +48,28 @@ pub use self::vec_docset::VecDocSet;
 pub use self::all_query::{AllQuery, AllScorer, AllWeight};
 pub use self::automaton_weight::AutomatonWeight;
 pub use self::bitset::BitSetDocSet;
+pub use self::block_join::{BlockJoinScoreMode, ToChildBlockJoinQuery, ToParentBlockJoinQuery};
+pub use self::bm25::{BM25Similarity, DEFAULT_B, DEFAULT_K1};
 pub use self::boolean_query::BooleanQuery;
+pub use self::boost_query::{BoostQuery, BoostScorer, BoostWeight};
+pub use self::constant_score_query::{ConstantScoreQuery, ConstantScoreWeight};
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
+pub use self::exists_query::ExistsQuery;
 pub use self::explanation::Explanation;
+pub use self::fast_field_range_query::FastFieldRangeQuery;
+pub use self::filter_query::{
+    BitSetFilter, DocFilter, FilterQuery, FilterWeight, FilterWrapper, QueryFilter,
+    SegmentDocFilter,
+};
+pub use self::function_score_query::{
+    FunctionScoreQuery, FunctionScoreWeight, FunctionScorer, ScoreFunction, SegmentScoreFunction,
+};
 pub use self::fuzzy_query::FuzzyTermQuery;
+pub use self::geo::{GeoBoundingBoxQuery, GeoDistanceQuery, GeoPolygonQuery};
 pub use self::intersection::intersect_scorers;
 pub use self::phrase_query::PhraseQuery;
+pub use self::prefix_query::PrefixQuery;
 pub use self::query::Query;
 pub use self::query_parser::QueryParser;
 pub use self::query_parser::QueryParserError;
@@ -51,7 +78,19 @@ pub use self::regex_query::RegexQuery;
 pub use self::reqopt_scorer::RequiredOptionalScorer;
 pub use self::scorer::ConstScorer;
 pub use self::scorer::Scorer;
+pub use self::similarity::{
+    BooleanSimilarity, ConstantSimilarity, Similarity, SimilarityDef, SimilarityWeight,
+    TfIdfSimilarity,
+};
+pub use self::span::{
+    PayloadFunction, PayloadScoreQuery, Span, SpanFirstQuery, SpanNearQuery, SpanNotQuery,
+    SpanOrQuery, SpanQuery, SpanQueryClone, SpanScorer, SpanScoringWeight, SpanTermQuery,
+    SpanWeight,
+};
 pub use self::term_query::TermQuery;
+pub(crate) use self::term_query::TermScorer;
+pub use self::term_set_query::TermSetQuery;
+pub(crate) use self::weight::for_each_pruning_scorer;
 pub use self::weight::Weight;
 pub use tantivy_query_grammar::Occur;
 
@@ -13,6 +13,7 @@ mod tests {
     use crate::collector::tests::{TEST_COLLECTOR_WITHOUT_SCORE, TEST_COLLECTOR_WITH_SCORE};
     use crate::core::Index;
     use crate::error::TantivyError;
+    use crate::query::{Query, Scorer};
     use crate::schema::{Schema, Term, TEXT};
     use crate::tests::assert_nearly_equals;
     use crate::DocId;
@@ -191,6 +192,50 @@ mod tests {
         assert_nearly_equals(scores[1], 0.46844664);
     }
 
+    #[test]
+    pub fn test_phrase_query_explain() {
+        let index = create_index(&["a b c", "a b c a b", "c d"]);
+        let schema = index.schema();
+        let text_field = schema.get_field("text").unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let terms: Vec<Term> = vec!["a", "c"]
+            .into_iter()
+            .map(|text| Term::from_field_text(text_field, text))
+            .collect();
+        let phrase_query = PhraseQuery::new(terms);
+        assert!(phrase_query.explain(&searcher, DocAddress(0, 0)).is_err());
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text_field, "b"),
+            Term::from_field_text(text_field, "c"),
+        ]);
+        let explanation = phrase_query.explain(&searcher, DocAddress(0, 1)).unwrap();
+        let scores = searcher
+            .search(&phrase_query, &TEST_COLLECTOR_WITH_SCORE)
+            .unwrap()
+            .scores()
+            .to_vec();
+        assert_nearly_equals(explanation.value(), scores[1]);
+        let bm25_explanation = &explanation.details()[0];
+        let idf_explanation = &bm25_explanation.details()[1];
+        assert_eq!(
+            idf_explanation.description(),
+            "idf, sum of the idf of the terms"
+        );
+        let term_idf_explanations: Vec<(&str, f32)> = idf_explanation.details()[1..]
+            .iter()
+            .map(|term_idf| (term_idf.description(), term_idf.details()[0].value()))
+            .collect();
+        assert_eq!(
+            term_idf_explanations,
+            vec![("idf(b)", 2.0), ("idf(c)", 3.0)]
+        );
+        let term_idf_sum: f32 = idf_explanation.details()[1..]
+            .iter()
+            .map(|term_idf| term_idf.value())
+            .sum();
+        assert_nearly_equals(idf_explanation.value(), term_idf_sum);
+    }
+
     #[test] // motivated by #234
     pub fn test_phrase_query_docfreq_order() {
         let mut schema_builder = Schema::builder();
@@ -258,4 +303,82 @@ mod tests {
         assert!(test_query(vec![(0, "a"), (2, "d")]).is_empty());
         assert_eq!(test_query(vec![(1, "a"), (3, "c")]), vec![0]);
     }
+
+    #[test]
+    pub fn test_phrase_query_slop() {
+        let index = create_index(&[
+            "quick fox",
+            "quick brown fox",
+            "quick brown lazy fox",
+            "fox quick",
+            "quick brown dog jumps quick fox",
+        ]);
+        let schema = index.schema();
+        let text_field = schema.get_field("text").unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let test_query = |texts: Vec<&str>, slop: u32| {
+            let terms: Vec<Term> = texts
+                .iter()
+                .map(|text| Term::from_field_text(text_field, text))
+                .collect();
+            let mut phrase_query = PhraseQuery::new(terms);
+            phrase_query.set_slop(slop);
+            let with_score = searcher
+                .search(&phrase_query, &TEST_COLLECTOR_WITH_SCORE)
+                .expect("search should succeed");
+            let without_score = searcher
+                .search(&phrase_query, &TEST_COLLECTOR_WITHOUT_SCORE)
+                .expect("search should succeed");
+            assert_eq!(with_score.docs(), without_score.docs());
+            with_score
+                .docs()
+                .iter()
+                .map(|doc_address| doc_address.1)
+                .collect::<Vec<DocId>>()
+        };
+        assert_eq!(test_query(vec!["quick", "fox"], 0), vec![0, 4]);
+        assert_eq!(test_query(vec!["quick", "fox"], 1), vec![0, 1, 4]);
+        assert_eq!(test_query(vec!["quick", "fox"], 2), vec![0, 1, 2, 4]);
+        assert_eq!(test_query(vec!["quick", "brown", "fox"], 1), vec![1, 2]);
+        assert_eq!(test_query(vec!["quick", "lazy", "fox"], 1), vec![2]);
+        assert!(test_query(vec!["quick", "dog", "fox"], 2).is_empty());
+        assert_eq!(test_query(vec!["fox", "quick"], 0), vec![3]);
+        assert_eq!(test_query(vec!["fox", "quick"], 3), vec![3]);
+    }
+
+    #[test]
+    pub fn test_phrase_query_slop_score() {
+        let index = create_index(&["a b c d", "a c b d", "a c d b"]);
+        let schema = index.schema();
+        let text_field = schema.get_field("text").unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let mut phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text_field, "a"),
+            Term::from_field_text(text_field, "b"),
+        ]);
+        phrase_query.set_slop(2);
+        let phrase_weight = phrase_query.phrase_weight(&searcher, true).unwrap();
+        let mut phrase_scorer = phrase_weight
+            .phrase_scorer(searcher.segment_reader(0u32))
+            .unwrap()
+            .unwrap();
+        let mut scores = Vec::new();
+        while phrase_scorer.advance() {
+            assert_eq!(phrase_scorer.phrase_count(), 1);
+            assert_nearly_equals(
+                phrase_scorer.phrase_freq(),
+                1f32 / (1 + phrase_scorer.doc()) as f32,
+            );
+            scores.push(phrase_scorer.score());
+        }
+        assert_eq!(scores.len(), 3);
+        assert!(scores[0] > scores[1]);
+        assert!(scores[1] > scores[2]);
+        let explanation = phrase_query
+            .weight(&searcher, true)
+            .unwrap()
+            .explain(searcher.segment_reader(0u32), 1)
+            .unwrap();
+        assert_nearly_equals(explanation.value(), scores[1]);
+    }
 }
@@ -1,7 +1,7 @@
 use super::PhraseWeight;
 use crate::core::searcher::Searcher;
 use crate::error::TantivyError;
-use crate::query::bm25::BM25Weight;
+use crate::query::similarity::similarity_weight;
 use crate::query::Query;
 use crate::query::Weight;
 use crate::schema::IndexRecordOption;
@@ -23,10 +23,17 @@ use std::collections::BTreeSet;
 /// Using a `PhraseQuery` on a field requires positions
 /// to be indexed for this field.
 ///
+/// With a slop, the terms may be further apart than their offsets in
+/// the phrase, as long as they appear in the same order and the match
+/// spans at most `slop` extra positions. For instance, with a slop of 1,
+/// `"part job"` matches the first sentence above. Closer matches
+/// score higher.
+///
 #[derive(Clone, Debug)]
 pub struct PhraseQuery {
     field: Field,
     phrase_terms: Vec<(usize, Term)>,
+    slop: u32,
 }
 
 impl PhraseQuery {
@@ -57,9 +64,21 @@ impl PhraseQuery {
         PhraseQuery {
             field,
             phrase_terms: terms,
+            slop: 0,
         }
     }
 
+    /// Sets the slop of the phrase query: the number of extra positions
+    /// a match may span. The default slop of 0 requires the exact phrase.
+    pub fn set_slop(&mut self, slop: u32) {
+        self.slop = slop;
+    }
+
+    /// The slop of the phrase query.
+    pub fn slop(&self) -> u32 {
+        self.slop
+    }
+
     /// The `Field` this `PhraseQuery` is targeting.
     pub fn field(&self) -> Field {
         self.field
@@ -97,11 +116,12 @@ impl PhraseQuery {
             )));
         }
         let terms = self.phrase_terms();
-        let bm25_weight = BM25Weight::for_terms(searcher, &terms);
+        let similarity_weight = similarity_weight(searcher, &terms);
         Ok(PhraseWeight::new(
             self.phrase_terms.clone(),
-            bm25_weight,
+            similarity_weight,
             scoring_enabled,
+            self.slop,
         ))
     }
 }
@@ -120,4 +140,11 @@ impl Query for PhraseQuery {
             term_set.insert(query_term.clone());
         }
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "PhraseQuery({:?}, {:?}, {})",
+            self.field, self.phrase_terms, self.slop
+        ))
+    }
 }
@@ -1,9 +1,13 @@
 use crate::docset::{DocSet, SkipResult};
+use crate::fastfield::FastFieldReader;
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::Postings;
-use crate::query::bm25::BM25Weight;
+use crate::query::SimilarityWeight;
 use crate::query::{Intersection, Scorer};
 use crate::DocId;
+use crate::Score;
+use std::cmp::Reverse;
+use std::sync::Arc;
 
 struct PostingsWithOffset<TPostings> {
     offset: u32,
@@ -44,11 +48,19 @@ impl<TPostings: Postings> DocSet for PostingsWithOffset<TPostings> {
 pub struct PhraseScorer<TPostings: Postings> {
     intersection_docset: Intersection<PostingsWithOffset<TPostings>, PostingsWithOffset<TPostings>>,
     num_terms: usize,
+    slop: u32,
+    // `intersection_docset` reorders the docsets by their size,
+    // the ords of the docsets in the order of the phrase.
+    phrase_order: Vec<usize>,
     left: Vec<u32>,
     right: Vec<u32>,
+    sloppy_left: Vec<(u32, u32)>,
+    sloppy_right: Vec<(u32, u32)>,
     phrase_count: u32,
+    phrase_freq: f32,
     fieldnorm_reader: FieldNormReader,
-    similarity_weight: BM25Weight,
+    similarity_weight: Arc<dyn SimilarityWeight>,
+    boost_reader: Option<FastFieldReader<f64>>,
     score_needed: bool,
 }
 
@@ -117,11 +129,43 @@ fn intersection(left: &mut [u32], right: &[u32]) -> usize {
     count
 }
 
+/// Extends the partial sloppy matches of `left` with the positions of `right`,
+/// and writes the resulting partial matches in `output`.
+///
+/// Partial matches are `(start, end)` pairs of positions, sorted by `end`.
+/// A position of `right` extends the partial match ending at or before it
+/// with the latest start, provided the span of the resulting match does
+/// not exceed `slop`.
+fn intersection_with_slop(
+    left: &[(u32, u32)],
+    right: &[u32],
+    slop: u32,
+    output: &mut Vec<(u32, u32)>,
+) {
+    output.clear();
+    let mut left_i = 0;
+    let mut latest_start: Option<u32> = None;
+    for &right_val in right {
+        while left_i < left.len() && left[left_i].1 <= right_val {
+            let start = left[left_i].0;
+            latest_start = Some(latest_start.map_or(start, |latest| latest.max(start)));
+            left_i += 1;
+        }
+        if let Some(start) = latest_start {
+            if right_val - start <= slop {
+                output.push((start, right_val));
+            }
+        }
+    }
+}
+
 impl<TPostings: Postings> PhraseScorer<TPostings> {
     pub fn new(
         term_postings: Vec<(usize, TPostings)>,
-        similarity_weight: BM25Weight,
+        slop: u32,
+        similarity_weight: Arc<dyn SimilarityWeight>,
         fieldnorm_reader: FieldNormReader,
+        boost_reader: Option<FastFieldReader<f64>>,
         score_needed: bool,
     ) -> PhraseScorer<TPostings> {
         let max_offset = term_postings
@@ -136,14 +180,24 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
                 PostingsWithOffset::new(postings, (max_offset - offset) as u32)
             })
             .collect::<Vec<_>>();
+        let mut intersection_docset = Intersection::new(postings_with_offsets);
+        let mut phrase_order: Vec<usize> = (0..num_docsets).collect();
+        phrase_order
+            .sort_by_key(|&ord| Reverse(intersection_docset.docset_mut_specialized(ord).offset));
         PhraseScorer {
-            intersection_docset: Intersection::new(postings_with_offsets),
+            intersection_docset,
             num_terms: num_docsets,
+            slop,
+            phrase_order,
             left: Vec::with_capacity(100),
             right: Vec::with_capacity(100),
+            sloppy_left: Vec::new(),
+            sloppy_right: Vec::new(),
             phrase_count: 0u32,
+            phrase_freq: 0f32,
             similarity_weight,
             fieldnorm_reader,
+            boost_reader,
             score_needed,
         }
     }
@@ -152,16 +206,70 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
         self.phrase_count
     }
 
+    /// Returns the frequency of the phrase used for scoring.
+    ///
+    /// Each match contributes `1 / (1 + distance)` to the frequency, where
+    /// `distance` is the number of extra positions spanned by the match.
+    /// Without slop, this is the phrase count.
+    pub fn phrase_freq(&self) -> f32 {
+        self.phrase_freq
+    }
+
+    /// Returns the static boost of the current document.
+    pub fn boost(&self) -> Option<Score> {
+        self.boost_reader
+            .as_ref()
+            .map(|boost_reader| boost_reader.get(self.doc()) as Score)
+    }
+
     fn phrase_match(&mut self) -> bool {
-        if self.score_needed {
+        if self.slop > 0 {
+            self.compute_sloppy_matches();
+            let count = self.sloppy_left.len() as u32;
+            if self.score_needed {
+                self.phrase_count = count;
+                self.phrase_freq = self
+                    .sloppy_left
+                    .iter()
+                    .map(|&(start, end)| 1f32 / (1 + end - start) as f32)
+                    .sum();
+            }
+            count > 0u32
+        } else if self.score_needed {
             let count = self.compute_phrase_count();
             self.phrase_count = count;
+            self.phrase_freq = count as f32;
             count > 0u32
         } else {
             self.phrase_exists()
         }
     }
 
+    /// Computes the sloppy matches of the current document in `sloppy_left`.
+    fn compute_sloppy_matches(&mut self) {
+        self.intersection_docset
+            .docset_mut_specialized(self.phrase_order[0])
+            .positions(&mut self.left);
+        self.sloppy_left.clear();
+        self.sloppy_left
+            .extend(self.left.iter().map(|&position| (position, position)));
+        for i in 1..self.num_terms {
+            self.intersection_docset
+                .docset_mut_specialized(self.phrase_order[i])
+                .positions(&mut self.right);
+            intersection_with_slop(
+                &self.sloppy_left,
+                &self.right,
+                self.slop,
+                &mut self.sloppy_right,
+            );
+            std::mem::swap(&mut self.sloppy_left, &mut self.sloppy_right);
+            if self.sloppy_left.is_empty() {
+                return;
+            }
+        }
+    }
+
     fn phrase_exists(&mut self) -> bool {
         self.intersection_docset
             .docset_mut_specialized(0)
@@ -251,16 +359,17 @@ impl<TPostings: Postings> DocSet for PhraseScorer<TPostings> {
 impl<TPostings: Postings> Scorer for PhraseScorer<TPostings> {
     fn score(&mut self) -> f32 {
         let doc = self.doc();
-        let fieldnorm_id = self.fieldnorm_reader.fieldnorm_id(doc);
-        self.similarity_weight
-            .score(fieldnorm_id, self.phrase_count)
+        let score = self
+            .similarity_weight
+            .score(&self.fieldnorm_reader, doc, self.phrase_freq);
+        score * self.boost().unwrap_or(1.0)
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::{intersection, intersection_count};
+    use super::{intersection, intersection_count, intersection_with_slop};
 
     fn test_intersection_sym(left: &[u32], right: &[u32], expected: &[u32]) {
         test_intersection_aux(left, right, expected);
@@ -283,6 +392,26 @@ mod tests {
         test_intersection_sym(&[5, 7], &[1, 5, 10, 12], &[5]);
         test_intersection_sym(&[1, 5, 6, 9, 10, 12], &[6, 8, 9, 12], &[6, 9, 12]);
     }
+
+    fn test_intersection_with_slop_aux(
+        left: &[(u32, u32)],
+        right: &[u32],
+        slop: u32,
+        expected: &[(u32, u32)],
+    ) {
+        let mut output = Vec::new();
+        intersection_with_slop(left, right, slop, &mut output);
+        assert_eq!(&output[..], expected);
+    }
+
+    #[test]
+    fn test_intersection_with_slop() {
+        test_intersection_with_slop_aux(&[(1, 1)], &[1], 0, &[(1, 1)]);
+        test_intersection_with_slop_aux(&[(1, 1)], &[0, 2, 3, 4], 2, &[(1, 2), (1, 3)]);
+        test_intersection_with_slop_aux(&[(0, 2), (3, 3)], &[2, 4], 1, &[(3, 4)]);
+        test_intersection_with_slop_aux(&[(0, 2), (3, 3)], &[2, 4], 2, &[(0, 2), (3, 4)]);
+        test_intersection_with_slop_aux(&[], &[2, 4], 2, &[]);
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
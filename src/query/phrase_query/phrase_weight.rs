@@ -2,33 +2,37 @@ use super::PhraseScorer;
 use crate::core::SegmentReader;
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::SegmentPostings;
-use crate::query::bm25::BM25Weight;
 use crate::query::explanation::does_not_match;
 use crate::query::Scorer;
+use crate::query::SimilarityWeight;
 use crate::query::Weight;
 use crate::query::{EmptyScorer, Explanation};
 use crate::schema::IndexRecordOption;
 use crate::schema::Term;
 use crate::{DocId, DocSet};
 use crate::{Result, SkipResult};
+use std::sync::Arc;
 
 pub struct PhraseWeight {
     phrase_terms: Vec<(usize, Term)>,
-    similarity_weight: BM25Weight,
+    similarity_weight: Arc<dyn SimilarityWeight>,
     score_needed: bool,
+    slop: u32,
 }
 
 impl PhraseWeight {
     /// Creates a new phrase weight.
     pub fn new(
         phrase_terms: Vec<(usize, Term)>,
-        similarity_weight: BM25Weight,
+        similarity_weight: Arc<dyn SimilarityWeight>,
         score_needed: bool,
+        slop: u32,
     ) -> PhraseWeight {
         PhraseWeight {
             phrase_terms,
             similarity_weight,
             score_needed,
+            slop,
         }
     }
 
@@ -43,6 +47,7 @@ impl PhraseWeight {
     ) -> Result<Option<PhraseScorer<SegmentPostings>>> {
         let similarity_weight = self.similarity_weight.clone();
         let fieldnorm_reader = self.fieldnorm_reader(reader);
+        let boost_reader = reader.boost_reader().cloned();
         if reader.has_deletes() {
             let mut term_postings_list = Vec::new();
             for &(offset, ref term) in &self.phrase_terms {
@@ -57,8 +62,10 @@ impl PhraseWeight {
             }
             Ok(Some(PhraseScorer::new(
                 term_postings_list,
+                self.slop,
                 similarity_weight,
                 fieldnorm_reader,
+                boost_reader,
                 self.score_needed,
             )))
         } else {
@@ -75,8 +82,10 @@ impl PhraseWeight {
             }
             Ok(Some(PhraseScorer::new(
                 term_postings_list,
+                self.slop,
                 similarity_weight,
                 fieldnorm_reader,
+                boost_reader,
                 self.score_needed,
             )))
         }
@@ -102,10 +111,18 @@ impl Weight for PhraseWeight {
             return Err(does_not_match(doc));
         }
         let fieldnorm_reader = self.fieldnorm_reader(reader);
-        let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
-        let phrase_count = scorer.phrase_count();
+        let phrase_freq = scorer.phrase_freq();
         let mut explanation = Explanation::new("Phrase Scorer", scorer.score());
-        explanation.add_detail(self.similarity_weight.explain(fieldnorm_id, phrase_count));
+        if let Some(boost) = scorer.boost() {
+            explanation.add_const("Static boost", boost);
+        }
+        if self.slop > 0 {
+            explanation.add_const("Number of sloppy matches", scorer.phrase_count() as f32);
+        }
+        explanation.add_detail(
+            self.similarity_weight
+                .explain(&fieldnorm_reader, doc, phrase_freq),
+        );
         Ok(explanation)
     }
 }
@@ -0,0 +1,175 @@
+use crate::query::{AutomatonWeight, Query, Weight};
+use crate::Result;
+use crate::Searcher;
+use crate::Term;
+use std::sync::Arc;
+use tantivy_fst::Automaton;
+
+/// An automaton accepting all of the keys starting with a given prefix.
+///
+/// Its state is the length of the prefix matched so far, or `None`
+/// once the key diverged from the prefix.
+#[derive(Debug)]
+pub(crate) struct PrefixAutomaton {
+    prefix: Vec<u8>,
+}
+
+impl Automaton for PrefixAutomaton {
+    type State = Option<usize>;
+
+    fn start(&self) -> Self::State {
+        Some(0)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        *state == Some(self.prefix.len())
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
+
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        self.is_match(state)
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let matched_len = (*state)?;
+        if matched_len == self.prefix.len() {
+            Some(matched_len)
+        } else if self.prefix[matched_len] == byte {
+            Some(matched_len + 1)
+        } else {
+            None
+        }
+    }
+}
+
+/// A Prefix Query matches all of the documents
+/// containing a term starting with a given prefix.
+///
+/// The terms are expanded by walking the term dictionary, along the
+/// prefix only. Like the [`RegexQuery`](./struct.RegexQuery.html),
+/// the matching documents all get a score of `1`.
+///
+/// The query parser also produces prefix queries for the words
+/// ending with a `*`, e.g. `title:dia*`.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::PrefixQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, Result, Term};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         index_writer.add_document(doc!(
+///             title => "The Name of the Wind",
+///         ));
+///         index_writer.add_document(doc!(
+///             title => "The Diary of Muadib",
+///         ));
+///         index_writer.add_document(doc!(
+///             title => "A Dairy Cow",
+///         ));
+///         index_writer.add_document(doc!(
+///             title => "The Diary of a Young Girl",
+///         ));
+///         index_writer.commit().unwrap();
+///     }
+///
+///     let reader = index.reader()?;
+///     let searcher = reader.searcher();
+///
+///     let query = PrefixQuery::new(Term::from_field_text(title, "dia"));
+///     let count = searcher.search(&query, &Count)?;
+///     assert_eq!(count, 2);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrefixQuery {
+    prefix: Term,
+    automaton: Arc<PrefixAutomaton>,
+}
+
+impl PrefixQuery {
+    /// Creates a query matching the terms of the field of `prefix`
+    /// whose value starts with the value of `prefix`.
+    pub fn new(prefix: Term) -> PrefixQuery {
+        let automaton = PrefixAutomaton {
+            prefix: prefix.value_bytes().to_vec(),
+        };
+        PrefixQuery {
+            prefix,
+            automaton: Arc::new(automaton),
+        }
+    }
+
+    /// Returns the prefix of the query.
+    pub fn prefix(&self) -> &Term {
+        &self.prefix
+    }
+
+    fn specialized_weight(&self) -> AutomatonWeight<PrefixAutomaton> {
+        AutomatonWeight::new(self.prefix.field(), self.automaton.clone())
+    }
+}
+
+impl Query for PrefixQuery {
+    fn weight(&self, _searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        Ok(Box::new(self.specialized_weight()))
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!("PrefixQuery({:?})", self.prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrefixQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_prefix_query() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let code = schema_builder.add_text_field("code", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(title => "Japan", code => "JP"));
+        index_writer.add_document(doc!(title => "Jamaica", code => "JM"));
+        index_writer.add_document(doc!(title => "Korea", code => "KR"));
+        index_writer.add_document(doc!(title => "Ja", code => "J"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let count = |prefix: Term| searcher.search(&PrefixQuery::new(prefix), &Count).unwrap();
+        assert_eq!(count(Term::from_field_text(title, "ja")), 3);
+        assert_eq!(count(Term::from_field_text(title, "jap")), 1);
+        assert_eq!(count(Term::from_field_text(title, "japanese")), 0);
+        assert_eq!(count(Term::from_field_text(title, "")), 4);
+        assert_eq!(count(Term::from_field_text(code, "J")), 3);
+        assert_eq!(count(Term::from_field_text(code, "j")), 0);
+
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let query = query_parser.parse_query("JAM* OR code:K*").unwrap();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        let mut docs: Vec<DocAddress> = top_docs
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect();
+        docs.sort();
+        assert_eq!(docs, vec![DocAddress(0, 1), DocAddress(0, 2)]);
+    }
+}
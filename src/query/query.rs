@@ -70,6 +70,17 @@ pub trait Query: QueryClone + downcast_rs::Downcast + fmt::Debug {
     /// Extract all of the terms associated to the query and insert them in the
     /// term set given in arguments.
     fn query_terms(&self, _term_set: &mut BTreeSet<Term>) {}
+
+    /// Returns a key identifying the documents matched by the query and their
+    /// scores, used to cache its results (see `Searcher::search_cached`).
+    ///
+    /// Two queries with the same key must match the same documents with the
+    /// same scores, so the key must reflect all of the parameters of the query.
+    /// Queries that cannot be identified this way, for instance because they
+    /// wrap a closure, return `None` (the default) and are never cached.
+    fn cache_key(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait QueryClone {
@@ -97,6 +108,10 @@ impl Query for Box<dyn Query> {
     fn query_terms(&self, term_set: &mut BTreeSet<Term<Vec<u8>>>) {
         self.as_ref().query_terms(term_set);
     }
+
+    fn cache_key(&self) -> Option<String> {
+        self.as_ref().cache_key()
+    }
 }
 
 impl QueryClone for Box<dyn Query> {
@@ -2,13 +2,15 @@ use crate::query::Occur;
 use crate::schema::Field;
 use crate::schema::Term;
 use crate::schema::Type;
+use crate::Score;
 use std::fmt;
 use std::ops::Bound;
 
 #[derive(Clone)]
 pub enum LogicalLiteral {
     Term(Term),
-    Phrase(Vec<(usize, Term)>),
+    Phrase(Vec<(usize, Term)>, u32),
+    Prefix(Term),
     Range {
         field: Field,
         value_type: Type,
@@ -21,6 +23,18 @@ pub enum LogicalLiteral {
 pub enum LogicalAST {
     Clause(Vec<(Occur, LogicalAST)>),
     Leaf(Box<LogicalLiteral>),
+    Boost(Box<LogicalAST>, Score),
+}
+
+impl LogicalAST {
+    /// Multiplies the score of the AST by `boost`, unless it is 1.
+    pub fn boost(self, boost: Score) -> LogicalAST {
+        if (boost - 1.0).abs() < Score::EPSILON {
+            self
+        } else {
+            LogicalAST::Boost(Box::new(self), boost)
+        }
+    }
 }
 
 fn occur_letter(occur: Occur) -> &'static str {
@@ -48,6 +62,7 @@ impl fmt::Debug for LogicalAST {
                 Ok(())
             }
             LogicalAST::Leaf(ref literal) => write!(formatter, "{:?}", literal),
+            LogicalAST::Boost(ref ast, boost) => write!(formatter, "{:?}^{}", ast, boost),
         }
     }
 }
@@ -62,7 +77,15 @@ impl fmt::Debug for LogicalLiteral {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match *self {
             LogicalLiteral::Term(ref term) => write!(formatter, "{:?}", term),
-            LogicalLiteral::Phrase(ref terms) => write!(formatter, "\"{:?}\"", terms),
+            LogicalLiteral::Phrase(ref terms, slop) => {
+                write!(formatter, "\"{:?}\"", terms)?;
+                if slop > 0 {
+                    write!(formatter, "~{:?}", slop)
+                } else {
+                    Ok(())
+                }
+            }
+            LogicalLiteral::Prefix(ref term) => write!(formatter, "{:?}*", term),
             LogicalLiteral::Range {
                 ref lower,
                 ref upper,
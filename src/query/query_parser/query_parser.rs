@@ -2,17 +2,22 @@ use super::logical_ast::*;
 use crate::core::Index;
 use crate::query::AllQuery;
 use crate::query::BooleanQuery;
+use crate::query::BoostQuery;
 use crate::query::EmptyQuery;
 use crate::query::Occur;
 use crate::query::PhraseQuery;
+use crate::query::PrefixQuery;
 use crate::query::Query;
 use crate::query::RangeQuery;
 use crate::query::TermQuery;
 use crate::schema::IndexRecordOption;
 use crate::schema::{Field, Schema};
-use crate::schema::{FieldType, Term};
+use crate::schema::{FieldType, Term, Type};
 use crate::tokenizer::TokenizerManager;
+use crate::Score;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::num::{ParseFloatError, ParseIntError};
 use std::ops::Bound;
 use std::str::FromStr;
@@ -62,9 +67,21 @@ pub enum QueryParserError {
     /// Only terms can be used as bounds.
     #[fail(display = "A range query cannot have a phrase as one of the bounds")]
     RangeMustNotHavePhrase,
+    /// The query contains a prefix query yielding several terms once tokenized.
+    /// Only terms can be used as prefixes.
+    #[fail(display = "A prefix query cannot be a phrase")]
+    PrefixMustNotHavePhrase,
     /// The format for the date field is not RFC 3339 compliant.
     #[fail(display = "The date field has an invalid format")]
     DateFormatError(chrono::ParseError),
+    /// The query contains a term for a bytes field, but the value
+    /// is not valid base64.
+    #[fail(display = "Expected a valid base64 value: '{:?}'", _0)]
+    ExpectedBase64(base64::DecodeError),
+    /// The query contains a range query on a field that does not support them,
+    /// e.g. a json field.
+    #[fail(display = "Range queries are not supported on the field '{:?}'", _0)]
+    RangeNotSupported(String),
 }
 
 impl From<ParseIntError> for QueryParserError {
@@ -73,6 +90,12 @@ impl From<ParseIntError> for QueryParserError {
     }
 }
 
+impl From<base64::DecodeError> for QueryParserError {
+    fn from(err: base64::DecodeError) -> QueryParserError {
+        QueryParserError::ExpectedBase64(err)
+    }
+}
+
 impl From<ParseFloatError> for QueryParserError {
     fn from(err: ParseFloatError) -> QueryParserError {
         QueryParserError::ExpectedFloat(err)
@@ -85,6 +108,22 @@ impl From<chrono::ParseError> for QueryParserError {
     }
 }
 
+/// Parses a date formatted as rfc3339, or as a plain `YYYY-MM-DD` date
+/// standing for midnight UTC.
+fn parse_date(date: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
+    match DateTime::parse_from_rfc3339(date) {
+        Ok(datetime) => Ok(datetime.with_timezone(&Utc)),
+        Err(rfc3339_error) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|naive_date| {
+                let midnight = naive_date
+                    .and_hms_opt(0, 0, 0)
+                    .expect("Midnight is a valid time");
+                Utc.from_utc_datetime(&midnight)
+            })
+            .map_err(|_| rfc3339_error),
+    }
+}
+
 /// Recursively remove empty clause from the AST
 ///
 /// Returns `None` iff the `logical_ast` ended up being empty.
@@ -103,6 +142,9 @@ fn trim_ast(logical_ast: LogicalAST) -> Option<LogicalAST> {
                 Some(LogicalAST::Clause(trimmed_children))
             }
         }
+        LogicalAST::Boost(child, boost) => {
+            trim_ast(*child).map(|trimmed_child| LogicalAST::Boost(Box::new(trimmed_child), boost))
+        }
         _ => Some(logical_ast),
     }
 }
@@ -132,6 +174,7 @@ fn trim_ast(logical_ast: LogicalAST) -> Option<LogicalAST> {
 ///
 /// * boolean operators `AND`, `OR`. `AND` takes precedence over `OR`, so that `a AND b OR c` is interpreted
 /// as `(a AND b) OR c`.
+///   Parentheses group the terms, e.g. `a AND (b OR c)`, and `NOT a` is equivalent to `-a`.
 ///
 /// * In addition to the boolean operators, the `-`, `+` can help define. These operators
 ///   are sufficient to express all queries using boolean operators. For instance `x AND y OR z` can
@@ -147,23 +190,50 @@ fn trim_ast(logical_ast: LogicalAST) -> Option<LogicalAST> {
 ///
 /// * phrase terms: Quoted terms become phrase searches on fields that have positions indexed.
 ///   e.g., `title:"Barack Obama"` will only find documents that have "barack" immediately followed
-///   by "obama".
+///   by "obama". A slop can be given after the phrase, e.g. `title:"Barack Obama"~1` also
+///   matches "Barack Hussein Obama".
 ///
 /// * range terms: Range searches can be done by specifying the start and end bound. These can be
 ///   inclusive or exclusive. e.g., `title:[a TO c}` will find all documents whose title contains
 ///   a word lexicographically between `a` and `c` (inclusive lower bound, exclusive upper bound).
-///   Inclusive bounds are `[]`, exclusive are `{}`.
+///   Inclusive bounds are `[]`, exclusive are `{}`. A `*` leaves a bound open, e.g. `price:[10 TO *]`,
+///   and `price:>=10`, `price:<10`... are shortcuts for the ranges with one open bound.
+///   The bounds of numeric and date fields are parsed according to the type of the field, e.g.
+///   `date:[2019-01-01 TO 2019-02-01}`. The bounds containing a `:` must be quoted.
 ///
-/// * date values: The query parser supports rfc3339 formatted dates. For example "2002-10-02T15:00:00.05Z"
+/// * date values: The query parser supports rfc3339 formatted dates, e.g. "2002-10-02T15:00:00.05Z",
+///   and plain dates standing for midnight UTC, e.g. `2002-10-02`.
+///
+/// * prefix terms: A word ending with a `*` matches all of the terms starting with it,
+///   e.g. `title:dia*` matches "diary" and "diamond". The word is tokenized like the
+///   other terms, and must yield a single token.
 ///
 /// *  all docs query: A plain `*` will match all documents in the index.
 ///
+/// * boosts: A `^` followed by a number multiplies the score of the preceding
+///   term, phrase or group, e.g. `title:diary^2 (cow OR dairy)^0.5`.
+///
+/// * escaping: The characters with a special meaning (`:`, `^`, `(`, `)`, `[`, `]`,
+///   `{`, `}`, `"` and `\`) can be escaped with a `\` to be part of a word,
+///   e.g. `title:std\:\:vec`. A `-` or a `*` can be escaped the same way, to prevent a
+///   word from being negated or matched as a prefix. Within a phrase, only `"` and `\`
+///   need to be escaped.
+///
+/// * json fields: The values of a json field are searched by appending their path
+///   to the name of the field, e.g. `attrs.color:red` or `attrs.size:42`. A value that
+///   looks like a number matches both the numeric and the text leaves of the path.
+///   Range queries are not supported on json fields.
+///
+/// The matches in some fields can be given more importance than in others
+/// with `.set_field_boost(field, boost)`.
+///
 #[derive(Clone)]
 pub struct QueryParser {
     schema: Schema,
     default_fields: Vec<Field>,
     conjunction_by_default: bool,
     tokenizer_manager: TokenizerManager,
+    field_boosts: HashMap<Field, Score>,
 }
 
 impl QueryParser {
@@ -181,6 +251,7 @@ impl QueryParser {
             default_fields,
             tokenizer_manager,
             conjunction_by_default: false,
+            field_boosts: HashMap::new(),
         }
     }
 
@@ -201,6 +272,20 @@ impl QueryParser {
         self.conjunction_by_default = true;
     }
 
+    /// Multiplies the score of the matches in `field` by `boost`.
+    ///
+    /// The boost applies whether the field is a default field, or is
+    /// explicitly targeted by the query. For instance, with a boost of 3
+    /// for `title` and default fields `title` and `body`, the query `trip`
+    /// is equivalent to `title:trip^3 OR body:trip`.
+    pub fn set_field_boost(&mut self, field: Field, boost: Score) {
+        self.field_boosts.insert(field, boost);
+    }
+
+    fn field_boost(&self, field: Field) -> Score {
+        self.field_boosts.get(&field).cloned().unwrap_or(1.0)
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
@@ -223,10 +308,27 @@ impl QueryParser {
         self.compute_logical_ast(user_input_ast)
     }
 
-    fn resolve_field_name(&self, field_name: &str) -> Result<Field, QueryParserError> {
-        self.schema
-            .get_field(field_name)
-            .ok_or_else(|| QueryParserError::FieldDoesNotExist(String::from(field_name)))
+    /// Resolves a field name into a field, and the path of the searched values
+    /// if the field is a json field.
+    ///
+    /// For instance, `attrs.color` resolves into the json field `attrs`
+    /// and the path `color`. The path is empty for the other fields.
+    fn resolve_field_name<'a>(
+        &self,
+        field_name: &'a str,
+    ) -> Result<(Field, &'a str), QueryParserError> {
+        if let Some(field) = self.schema.get_field(field_name) {
+            return Ok((field, ""));
+        }
+        let mut segments = field_name.splitn(2, '.');
+        if let (Some(root), Some(json_path)) = (segments.next(), segments.next()) {
+            if let Some(field) = self.schema.get_field(root) {
+                if let FieldType::Json(_) = *self.schema.get_field_entry(field).field_type() {
+                    return Ok((field, json_path));
+                }
+            }
+        }
+        Err(QueryParserError::FieldDoesNotExist(field_name.to_string()))
     }
 
     fn compute_logical_ast(
@@ -243,6 +345,7 @@ impl QueryParser {
     fn compute_terms_for_string(
         &self,
         field: Field,
+        json_path: &str,
         phrase: &str,
     ) -> Result<Vec<(usize, Term)>, QueryParserError> {
         let field_entry = self.schema.get_field_entry(field);
@@ -262,19 +365,18 @@ impl QueryParser {
                 let term = Term::from_field_f64(field, val);
                 Ok(vec![(0, term)])
             }
-            FieldType::Date(_) => match chrono::DateTime::parse_from_rfc3339(phrase) {
-                Ok(x) => Ok(vec![(
-                    0,
-                    Term::from_field_date(field, &x.with_timezone(&chrono::Utc)),
-                )]),
-                Err(e) => Err(QueryParserError::DateFormatError(e)),
-            },
+            FieldType::Date(_) => {
+                let date = parse_date(phrase)?;
+                let term = Term::from_field_date(field, &date);
+                Ok(vec![(0, term)])
+            }
             FieldType::U64(_) => {
                 let val: u64 = u64::from_str(phrase)?;
                 let term = Term::from_field_u64(field, val);
                 Ok(vec![(0, term)])
             }
-            FieldType::Str(ref str_options) => {
+            FieldType::Str(ref str_options) | FieldType::Json(ref str_options) => {
+                let is_json = field_type.value_type() == Type::Json;
                 if let Some(option) = str_options.get_indexing_options() {
                     let tokenizer =
                         self.tokenizer_manager
@@ -288,7 +390,11 @@ impl QueryParser {
                     let mut terms: Vec<(usize, Term)> = Vec::new();
                     let mut token_stream = tokenizer.token_stream(phrase);
                     token_stream.process(&mut |token| {
-                        let term = Term::from_field_text(field, &token.text);
+                        let term = if is_json {
+                            Term::from_field_json_text(field, json_path, &token.text)
+                        } else {
+                            Term::from_field_text(field, &token.text)
+                        };
                         terms.push((token.position, term));
                     });
                     if terms.is_empty() {
@@ -320,23 +426,62 @@ impl QueryParser {
                 }
             }
             FieldType::HierarchicalFacet => Ok(vec![(0, Term::from_field_text(field, phrase))]),
-            FieldType::Bytes => {
+            FieldType::Bytes(_) => {
+                let bytes = base64::decode(phrase)?;
+                Ok(vec![(0, Term::from_field_bytes(field, &bytes))])
+            }
+            // Geo points can only be searched with geo queries.
+            FieldType::GeoPoint(_) => {
                 let field_name = self.schema.get_field_name(field).to_string();
                 Err(QueryParserError::FieldNotIndexed(field_name))
             }
         }
     }
 
+    /// Returns the term matching the numeric leaves of a json field
+    /// equal to `phrase`, if `phrase` is a number.
+    ///
+    /// Integers are searched as i64-values, unless they are too large.
+    fn compute_json_numeric_term(
+        &self,
+        field: Field,
+        json_path: &str,
+        phrase: &str,
+    ) -> Option<Term> {
+        if self.schema.get_field_entry(field).field_type().value_type() != Type::Json {
+            return None;
+        }
+        if let Ok(val) = i64::from_str(phrase) {
+            Some(Term::from_field_json_i64(field, json_path, val))
+        } else if let Ok(val) = u64::from_str(phrase) {
+            Some(Term::from_field_json_u64(field, json_path, val))
+        } else if let Ok(val) = f64::from_str(phrase) {
+            Some(Term::from_field_json_f64(field, json_path, val))
+        } else {
+            None
+        }
+    }
+
     fn compute_logical_ast_for_leaf(
         &self,
         field: Field,
+        json_path: &str,
         phrase: &str,
+        slop: u32,
+        prefix: bool,
     ) -> Result<Option<LogicalLiteral>, QueryParserError> {
-        let terms = self.compute_terms_for_string(field, phrase)?;
+        let terms = self.compute_terms_for_string(field, json_path, phrase)?;
+        if prefix {
+            return match &terms[..] {
+                [] => Ok(None),
+                [(_, term)] => Ok(Some(LogicalLiteral::Prefix(term.clone()))),
+                _ => Err(QueryParserError::PrefixMustNotHavePhrase),
+            };
+        }
         match &terms[..] {
             [] => Ok(None),
             [(_, term)] => Ok(Some(LogicalLiteral::Term(term.clone()))),
-            _ => Ok(Some(LogicalLiteral::Phrase(terms.clone()))),
+            _ => Ok(Some(LogicalLiteral::Phrase(terms.clone(), slop))),
         }
     }
 
@@ -356,7 +501,7 @@ impl QueryParser {
         if bound.term_str() == "*" {
             return Ok(Bound::Unbounded);
         }
-        let terms = self.compute_terms_for_string(field, bound.term_str())?;
+        let terms = self.compute_terms_for_string(field, "", bound.term_str())?;
         if terms.len() != 1 {
             return Err(QueryParserError::RangeMustNotHavePhrase);
         }
@@ -380,7 +525,7 @@ impl QueryParser {
                     Ok(Cow::from(&self.default_fields[..]))
                 }
             }
-            Some(ref field) => Ok(Cow::from(vec![self.resolve_field_name(&*field)?])),
+            Some(ref field) => Ok(Cow::from(vec![self.resolve_field_name(field)?.0])),
         }
     }
 
@@ -408,6 +553,11 @@ impl QueryParser {
                 let result_ast = self.compute_logical_ast_from_leaf(*leaf)?;
                 Ok((Occur::Should, result_ast))
             }
+            UserInputAST::Boost(subquery, boost) => {
+                let (occur, logical_sub_queries) =
+                    self.compute_logical_ast_with_occur(*subquery)?;
+                Ok((occur, logical_sub_queries.boost(boost)))
+            }
         }
     }
 
@@ -417,10 +567,10 @@ impl QueryParser {
     ) -> Result<LogicalAST, QueryParserError> {
         match leaf {
             UserInputLeaf::Literal(literal) => {
-                let term_phrases: Vec<(Field, String)> = match literal.field_name {
+                let term_phrases: Vec<(Field, &str, String)> = match literal.field_name {
                     Some(ref field_name) => {
-                        let field = self.resolve_field_name(field_name)?;
-                        vec![(field, literal.phrase.clone())]
+                        let (field, json_path) = self.resolve_field_name(field_name)?;
+                        vec![(field, json_path, literal.phrase.clone())]
                     }
                     None => {
                         if self.default_fields.is_empty() {
@@ -428,15 +578,29 @@ impl QueryParser {
                         } else {
                             self.default_fields
                                 .iter()
-                                .map(|default_field| (*default_field, literal.phrase.clone()))
-                                .collect::<Vec<(Field, String)>>()
+                                .map(|default_field| (*default_field, "", literal.phrase.clone()))
+                                .collect::<Vec<(Field, &str, String)>>()
                         }
                     }
                 };
                 let mut asts: Vec<LogicalAST> = Vec::new();
-                for (field, phrase) in term_phrases {
-                    if let Some(ast) = self.compute_logical_ast_for_leaf(field, &phrase)? {
-                        asts.push(LogicalAST::Leaf(Box::new(ast)));
+                for (field, json_path, phrase) in term_phrases {
+                    if let Some(ast) = self.compute_logical_ast_for_leaf(
+                        field,
+                        json_path,
+                        &phrase,
+                        literal.slop,
+                        literal.prefix,
+                    )? {
+                        asts.push(LogicalAST::Leaf(Box::new(ast)).boost(self.field_boost(field)));
+                    }
+                    if !literal.prefix {
+                        if let Some(term) =
+                            self.compute_json_numeric_term(field, json_path, &phrase)
+                        {
+                            let ast = LogicalAST::Leaf(Box::new(LogicalLiteral::Term(term)));
+                            asts.push(ast.boost(self.field_boost(field)));
+                        }
                     }
                 }
                 let result_ast: LogicalAST = if asts.len() == 1 {
@@ -458,12 +622,17 @@ impl QueryParser {
                     .map(|&field| {
                         let field_entry = self.schema.get_field_entry(field);
                         let value_type = field_entry.field_type().value_type();
-                        Ok(LogicalAST::Leaf(Box::new(LogicalLiteral::Range {
+                        if value_type == Type::Json {
+                            let field_name = field_entry.name().to_string();
+                            return Err(QueryParserError::RangeNotSupported(field_name));
+                        }
+                        let range_ast = LogicalAST::Leaf(Box::new(LogicalLiteral::Range {
                             field,
                             value_type,
                             lower: self.resolve_bound(field, &lower)?,
                             upper: self.resolve_bound(field, &upper)?,
-                        })))
+                        }));
+                        Ok(range_ast.boost(self.field_boost(field)))
                     })
                     .collect::<Result<Vec<_>, QueryParserError>>()?;
                 let result_ast = if clauses.len() == 1 {
@@ -485,9 +654,12 @@ impl QueryParser {
 fn convert_literal_to_query(logical_literal: LogicalLiteral) -> Box<dyn Query> {
     match logical_literal {
         LogicalLiteral::Term(term) => Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs)),
-        LogicalLiteral::Phrase(term_with_offsets) => {
-            Box::new(PhraseQuery::new_with_offset(term_with_offsets))
+        LogicalLiteral::Phrase(term_with_offsets, slop) => {
+            let mut phrase_query = PhraseQuery::new_with_offset(term_with_offsets);
+            phrase_query.set_slop(slop);
+            Box::new(phrase_query)
         }
+        LogicalLiteral::Prefix(term) => Box::new(PrefixQuery::new(term)),
         LogicalLiteral::Range {
             field,
             value_type,
@@ -516,6 +688,9 @@ fn convert_to_query(logical_ast: LogicalAST) -> Box<dyn Query> {
         Some(LogicalAST::Leaf(trimmed_logical_literal)) => {
             convert_literal_to_query(*trimmed_logical_literal)
         }
+        Some(LogicalAST::Boost(trimmed_ast, boost)) => {
+            Box::new(BoostQuery::new(convert_to_query(*trimmed_ast), boost))
+        }
         None => Box::new(EmptyQuery),
     }
 }
@@ -525,7 +700,7 @@ mod test {
     use super::super::logical_ast::*;
     use super::QueryParser;
     use super::QueryParserError;
-    use crate::query::Query;
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
     use crate::schema::Field;
     use crate::schema::{IndexRecordOption, TextFieldIndexing, TextOptions};
     use crate::schema::{Schema, Term, INDEXED, STORED, STRING, TEXT};
@@ -554,6 +729,7 @@ mod test {
         schema_builder.add_text_field("with_stop_words", text_options);
         schema_builder.add_date_field("date", INDEXED);
         schema_builder.add_f64_field("float", INDEXED);
+        schema_builder.add_json_field("attrs", TEXT);
         let schema = schema_builder.build();
         let default_fields = vec![title, text];
         let tokenizer_manager = TokenizerManager::default();
@@ -725,6 +901,40 @@ mod test {
              (1, Term(field=0,bytes=[98]))]\"",
             false,
         );
+        test_parse_query_to_logical_ast_helper(
+            "title:\"a b\"~2",
+            "\"[(0, Term(field=0,bytes=[97])), \
+             (1, Term(field=0,bytes=[98]))]\"~2",
+            false,
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_to_ast_field_boost() {
+        let mut query_parser = make_query_parser();
+        let title = query_parser.schema.get_field("title").unwrap();
+        query_parser.set_field_boost(title, 2.0);
+        let query_str = |query| {
+            let logical_ast = query_parser.parse_query_to_logical_ast(query).unwrap();
+            format!("{:?}", logical_ast)
+        };
+        assert_eq!(
+            query_str("a"),
+            "(Term(field=0,bytes=[97])^2 Term(field=1,bytes=[97]))"
+        );
+        assert_eq!(
+            query_str("text:a title:b"),
+            "(Term(field=1,bytes=[97]) Term(field=0,bytes=[98])^2)"
+        );
+        assert_eq!(
+            query_str("title:[a TO b]"),
+            "(Included(Term(field=0,bytes=[97])) TO Included(Term(field=0,bytes=[98])))^2"
+        );
+        let query = query_parser.parse_query("title:a").unwrap();
+        assert_eq!(
+            format!("{:?}", query),
+            "Boost(query=TermQuery(Term(field=0,bytes=[97])), boost=2)"
+        );
     }
 
     #[test]
@@ -849,6 +1059,72 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_to_ast_prefix() {
+        test_parse_query_to_logical_ast_helper(
+            "title:To*",
+            "Term(field=0,bytes=[116, 111])*",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "to*",
+            "(Term(field=0,bytes=[116, 111])* Term(field=1,bytes=[116, 111])*)",
+            false,
+        );
+        assert_matches!(
+            parse_query_to_logical_ast("title:a-b*", false),
+            Err(QueryParserError::PrefixMustNotHavePhrase)
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_to_ast_boost() {
+        test_parse_query_to_logical_ast_helper("title:a^2", "Term(field=0,bytes=[97])^2", false);
+        test_parse_query_to_logical_ast_helper(
+            "(title:a OR title:b)^0.5",
+            "(Term(field=0,bytes=[97]) Term(field=0,bytes=[98]))^0.5",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "+title:a^2 title:b",
+            "(+Term(field=0,bytes=[97])^2 Term(field=0,bytes=[98]))",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:\"a b\"~1^3",
+            "\"[(0, Term(field=0,bytes=[97])), (1, Term(field=0,bytes=[98]))]\"~1^3",
+            false,
+        );
+        assert_matches!(
+            parse_query_to_logical_ast("title:a^b", false),
+            Err(QueryParserError::SyntaxError)
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_to_ast_escaping() {
+        test_parse_query_to_logical_ast_helper(
+            r#"nottokenized:a\:b"#,
+            "Term(field=7,bytes=[97, 58, 98])",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            r#"nottokenized:\(a\)\^2"#,
+            "Term(field=7,bytes=[40, 97, 41, 94, 50])",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            r#"nottokenized:a\*"#,
+            "Term(field=7,bytes=[97, 42])",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            r#"nottokenized:"a \"b\"""#,
+            "Term(field=7,bytes=[97, 32, 34, 98, 34])",
+            false,
+        );
+    }
+
     #[test]
     pub fn test_query_parser_expected_date() {
         let query_parser = make_query_parser();
@@ -859,6 +1135,79 @@ mod test {
         assert!(query_parser
             .parse_query("date:\"1985-04-12T23:20:50.52Z\"")
             .is_ok());
+        assert_eq!(
+            format!("{:?}", query_parser.parse_query("date:1985-04-12").unwrap()),
+            format!(
+                "{:?}",
+                query_parser
+                    .parse_query("date:\"1985-04-12T00:00:00Z\"")
+                    .unwrap()
+            )
+        );
+        assert_matches!(
+            query_parser.parse_query("date:1985-04-32"),
+            Err(QueryParserError::DateFormatError(_))
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_date_and_number_ranges() {
+        let query_parser = make_query_parser();
+        let parse = |query: &str| format!("{:?}", query_parser.parse_query(query).unwrap());
+        assert_eq!(
+            parse("date:[2019-01-01 TO 2019-02-01}"),
+            parse("date:[\"2019-01-01T00:00:00Z\" TO \"2019-02-01T00:00:00+00:00\"}")
+        );
+        assert_eq!(parse("date:>=2019-01-01"), parse("date:[2019-01-01 TO *]"));
+        assert_matches!(
+            query_parser.parse_query("date:[2019-01-01 TO 2019-13-01]"),
+            Err(QueryParserError::DateFormatError(_))
+        );
+        assert_eq!(
+            parse("unsigned:{10 TO 100}"),
+            parse("unsigned:{\"10\" TO \"100\"}")
+        );
+        assert_matches!(
+            query_parser.parse_query("unsigned:[ten TO 100]"),
+            Err(QueryParserError::ExpectedInt(_))
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_json_path() {
+        let query_parser = make_query_parser();
+        let attrs = Field(11);
+        let parse = |query: &str| format!("{:?}", query_parser.parse_query(query).unwrap());
+        let text_query = TermQuery::new(
+            Term::from_field_json_text(attrs, "color", "red"),
+            IndexRecordOption::WithFreqs,
+        );
+        assert_eq!(parse("attrs.color:Red"), format!("{:?}", text_query));
+        let numeric_query = BooleanQuery::from(vec![
+            (
+                Occur::Should,
+                Box::new(TermQuery::new(
+                    Term::from_field_json_text(attrs, "size.eu", "42"),
+                    IndexRecordOption::WithFreqs,
+                )) as Box<dyn Query>,
+            ),
+            (
+                Occur::Should,
+                Box::new(TermQuery::new(
+                    Term::from_field_json_i64(attrs, "size.eu", 42),
+                    IndexRecordOption::WithFreqs,
+                )),
+            ),
+        ]);
+        assert_eq!(parse("attrs.size.eu:42"), format!("{:?}", numeric_query));
+        assert_matches!(
+            query_parser.parse_query("title.color:red"),
+            Err(QueryParserError::FieldDoesNotExist(_))
+        );
+        assert_matches!(
+            query_parser.parse_query("attrs.size:[1 TO 2]"),
+            Err(QueryParserError::RangeNotSupported(_))
+        );
     }
 
     #[test]
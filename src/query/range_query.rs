@@ -11,6 +11,7 @@ use crate::schema::{Field, IndexRecordOption, Term};
 use crate::termdict::{TermDictionary, TermStreamer};
 use crate::DocId;
 use crate::{Result, SkipResult};
+use byteorder::{BigEndian, ByteOrder};
 use std::collections::Bound;
 use std::ops::Range;
 
@@ -35,6 +36,9 @@ fn map_bound<TFrom, TTo, Transform: Fn(&TFrom) -> TTo>(
 /// The current implement will iterate over the terms within the range
 /// and append all of the document cross into a `BitSet`.
 ///
+/// If the field is a range indexed fast field (see `IntOptions::set_range_indexed`),
+/// the documents are directly read from the range index of each segment instead.
+///
 /// # Example
 ///
 /// ```rust
@@ -270,6 +274,13 @@ impl Query for RangeQuery {
             right_bound: self.right_bound.clone(),
         }))
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "RangeQuery({:?}, {:?}, {:?}, {:?})",
+            self.field, self.value_type, self.left_bound, self.right_bound
+        ))
+    }
 }
 
 pub struct RangeWeight {
@@ -301,6 +312,17 @@ impl Weight for RangeWeight {
         let max_doc = reader.max_doc();
         let mut doc_bitset = BitSet::with_max_value(max_doc);
 
+        if let Some(range_index) = reader.fast_fields().range_index(self.field) {
+            let to_u64 = |term_val: &Vec<u8>| BigEndian::read_u64(term_val);
+            range_index.docs_in_range(
+                &map_bound(&self.left_bound, &to_u64),
+                &map_bound(&self.right_bound, &to_u64),
+                &mut doc_bitset,
+            );
+            let doc_bitset = BitSetDocSet::from(doc_bitset);
+            return Ok(Box::new(ConstScorer::new(doc_bitset)));
+        }
+
         let inverted_index = reader.inverted_index(self.field);
         let term_dict = inverted_index.terms();
         let mut term_range = self.term_range(term_dict);
@@ -332,9 +354,13 @@ mod tests {
 
     use super::RangeQuery;
     use crate::collector::Count;
-    use crate::schema::{Document, Field, Schema, INDEXED};
+    use crate::query::QueryParser;
+    use crate::schema::{Cardinality, Document, Field, IntOptions, Schema, Term, INDEXED};
     use crate::Index;
+    use chrono::{Duration, TimeZone, Utc};
+    use futures::Future;
     use std::collections::Bound;
+    use std::ops::RangeBounds;
 
     #[test]
     fn test_range_query_simple() {
@@ -363,6 +389,37 @@ mod tests {
         assert_eq!(count, 2285);
     }
 
+    #[test]
+    fn test_range_query_parser() {
+        let mut schema_builder = Schema::builder();
+        let date_field = schema_builder.add_date_field("date", INDEXED);
+        let price_field = schema_builder.add_u64_field("price", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+            for (day, price) in [(1, 5u64), (15, 10), (31, 50), (32, 100), (45, 200)].iter() {
+                let date =
+                    Utc.with_ymd_and_hms(2019, 1, 1, 12, 0, 0).unwrap() + Duration::days(day - 1);
+                index_writer.add_document(doc!(date_field => date, price_field => *price));
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.reader().unwrap().searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("date:[2019-01-01 TO 2019-02-01]"), 3);
+        assert_eq!(count("date:{2019-01-01 TO 2019-02-01}"), 3);
+        assert_eq!(count("date:[2019-01-15 TO *]"), 4);
+        assert_eq!(count("date:<\"2019-01-15T12:00:00Z\""), 1);
+        assert_eq!(count("price:{10 TO 100}"), 1);
+        assert_eq!(count("price:[10 TO 100]"), 3);
+        assert_eq!(count("price:>=50"), 3);
+        assert_eq!(count("price:[* TO 10}"), 1);
+    }
+
     #[test]
     fn test_range_query() {
         let int_field: Field;
@@ -479,4 +536,68 @@ mod tests {
             91
         );
     }
+
+    #[test]
+    fn test_range_query_range_indexed_fast_field() {
+        let mut schema_builder = Schema::builder();
+        let int_options = IntOptions::default()
+            .set_indexed()
+            .set_fast(Cardinality::SingleValue)
+            .set_range_indexed();
+        let int_field = schema_builder.add_i64_field("intfield", int_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+        for i in -50i64..50 {
+            index_writer.add_document(doc!(int_field => i * 3 % 100));
+            if i == 0 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.delete_term(Term::from_field_i64(int_field, 3));
+        index_writer.commit().unwrap();
+        let reader = index.reader().unwrap();
+        let count = |left: Bound<i64>, right: Bound<i64>| {
+            let searcher = reader.searcher();
+            let range_query = RangeQuery::new_i64_bounds(int_field, left, right);
+            searcher.search(&range_query, &Count).unwrap()
+        };
+        let expected_count = |left: Bound<i64>, right: Bound<i64>| {
+            (-50i64..50)
+                .map(|i| i * 3 % 100)
+                .filter(|&val| val != 3)
+                .filter(|val| (left, right).contains(val))
+                .count()
+        };
+        let bounds = [
+            (Bound::Included(-60), Bound::Excluded(0)),
+            (Bound::Excluded(-60), Bound::Included(0)),
+            (Bound::Included(-3), Bound::Included(3)),
+            (Bound::Unbounded, Bound::Excluded(-200)),
+            (Bound::Included(12), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Unbounded),
+        ];
+        for &(left, right) in &bounds {
+            assert_eq!(count(left, right), expected_count(left, right));
+        }
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+        reader.reload().unwrap();
+        {
+            let searcher = reader.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let range_index = searcher
+                .segment_reader(0)
+                .fast_fields()
+                .range_index(int_field)
+                .unwrap();
+            assert_eq!(
+                range_index.num_docs_in_range(&Bound::Included(-3i64), &Bound::Included(3i64)),
+                4
+            );
+        }
+        for &(left, right) in &bounds {
+            assert_eq!(count(left, right), expected_count(left, right));
+        }
+    }
 }
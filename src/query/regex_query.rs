@@ -58,9 +58,12 @@ pub struct RegexQuery {
 
 impl RegexQuery {
     /// Creates a new RegexQuery from a given pattern
+    ///
+    /// Returns an `InvalidArgument` error if the pattern cannot be compiled.
     pub fn from_pattern(regex_pattern: &str, field: Field) -> Result<Self> {
-        let regex = Regex::new(&regex_pattern)
-            .map_err(|_| TantivyError::InvalidArgument(regex_pattern.to_string()))?;
+        let regex = Regex::new(regex_pattern).map_err(|err| {
+            TantivyError::InvalidArgument(format!("Invalid regex {:?}: {}", regex_pattern, err))
+        })?;
         Ok(RegexQuery::from_regex(regex, field))
     }
 
@@ -86,11 +89,11 @@ impl Query for RegexQuery {
 #[cfg(test)]
 mod test {
     use super::RegexQuery;
-    use crate::collector::TopDocs;
+    use crate::collector::{Count, TopDocs};
     use crate::schema::TEXT;
     use crate::schema::{Field, Schema};
     use crate::tests::assert_nearly_equals;
-    use crate::{Index, IndexReader};
+    use crate::{Index, IndexReader, TantivyError};
     use std::sync::Arc;
     use tantivy_fst::Regex;
 
@@ -144,6 +147,31 @@ mod test {
         verify_regex_query(matching_one, matching_zero, reader);
     }
 
+    #[test]
+    pub fn test_regex_query_several_terms() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
+        index_writer.add_document(doc!(text => "errtimeout"));
+        index_writer.add_document(doc!(text => "errconntimeout"));
+        index_writer.add_document(doc!(text => "timeout"));
+        index_writer.add_document(doc!(text => "errconn errtimeout"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query = RegexQuery::from_pattern("err.*timeout", text).unwrap();
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 3);
+    }
+
+    #[test]
+    pub fn test_invalid_pattern() {
+        let (_, field) = build_test_index();
+        match RegexQuery::from_pattern("jap[ao", field) {
+            Err(TantivyError::InvalidArgument(msg)) => assert!(msg.contains("jap[ao")),
+            _ => panic!("Expected an invalid argument error"),
+        }
+    }
+
     #[test]
     pub fn test_construct_from_regex() {
         let (reader, field) = build_test_index();
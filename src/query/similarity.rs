@@ -0,0 +1,525 @@
+use crate::core::FieldStatistics;
+use crate::fieldnorm::FieldNormReader;
+use crate::query::{BM25Similarity, Explanation};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::Searcher;
+use crate::TantivyError;
+use crate::Term;
+use std::sync::Arc;
+
+/// A `Similarity` defines how the documents matching a term (or a phrase)
+/// are scored.
+///
+/// The similarity of a field is persisted in the `IndexSettings` for the
+/// built-in similarities (see [`SimilarityDef`](./enum.SimilarityDef.html)),
+/// or set with [`Index::set_similarity`](../struct.Index.html#method.set_similarity),
+/// and defaults to [`BM25Similarity`](./struct.BM25Similarity.html).
+/// It is used by the `TermQuery` and the `PhraseQuery`.
+pub trait Similarity: Send + Sync + 'static {
+    /// Returns the weight scoring the documents matching `terms`.
+    ///
+    /// `terms` lists the terms of the query along with the number of documents
+    /// containing them. It contains a single term for a `TermQuery`, and all
+    /// of the terms of the phrase for a `PhraseQuery`.
+    fn weight(
+        &self,
+        field_statistics: &FieldStatistics,
+        terms: &[(Term, u64)],
+    ) -> Box<dyn SimilarityWeight>;
+}
+
+/// The specialization of a `Similarity` for the terms of a query.
+///
+/// See [`Similarity`](./trait.Similarity.html).
+pub trait SimilarityWeight: Send + Sync + 'static {
+    /// Returns the score of `doc`, given the number of occurrences of the
+    /// term (or phrase) in the document.
+    ///
+    /// The frequency is fractional for the sloppy matches of a phrase,
+    /// which count as less than one occurrence.
+    fn score(&self, fieldnorm_reader: &FieldNormReader, doc: DocId, term_freq: f32) -> Score;
+
+    /// Returns an upper bound of the score of a document, whatever its
+    /// term frequency and the length of its field.
+    ///
+    /// Upper bounds make it possible to skip the documents that cannot
+    /// make it to the top documents. They should be as tight as possible.
+    fn max_score(&self) -> Score;
+
+    /// Returns an upper bound of the score of a document whose term
+    /// frequency is at most `max_term_freq`, whatever the length of its field.
+    fn max_score_for_term_freq(&self, _max_term_freq: u32) -> Score {
+        self.max_score()
+    }
+
+    /// Returns an `Explanation` of the score of `doc`.
+    fn explain(
+        &self,
+        fieldnorm_reader: &FieldNormReader,
+        doc: DocId,
+        term_freq: f32,
+    ) -> Explanation;
+}
+
+/// Returns the weight of the similarity of the field of `terms`.
+pub(crate) fn similarity_weight(searcher: &Searcher, terms: &[Term]) -> Arc<dyn SimilarityWeight> {
+    assert!(!terms.is_empty(), "Scoring requires at least one term");
+    let field = terms[0].field();
+    for term in &terms[1..] {
+        assert_eq!(
+            term.field(),
+            field,
+            "All terms must belong to the same field."
+        );
+    }
+    let field_statistics = searcher.scoring_field_statistics(field);
+    let terms: Vec<(Term, u64)> = terms
+        .iter()
+        .map(|term| (term.clone(), searcher.scoring_doc_freq(term)))
+        .collect();
+    let similarity = searcher.index().similarity_for_field(field);
+    Arc::from(similarity.weight(&field_statistics, &terms))
+}
+
+fn term_label(term: &Term) -> String {
+    String::from_utf8_lossy(term.value_bytes()).to_string()
+}
+
+/// The classic TF-IDF similarity, as it was used by Lucene before BM25.
+///
+/// The score of a document is `idf² * sqrt(tf / dl)`, where:
+/// - `idf = 1 + ln((N + 1) / (n + 1))`, `N` being the number of documents and `n`
+///   the number of documents containing the term. The idf of a phrase is the sum
+///   of the idf of its terms.
+/// - `tf` is the number of occurrences of the term in the document.
+/// - `dl` is the number of tokens of the field of the document.
+///
+/// Unlike with BM25, the contribution of the term frequency does not saturate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TfIdfSimilarity;
+
+fn tf_idf_idf(doc_freq: u64, num_docs: u64) -> f32 {
+    1.0 + ((num_docs as f32 + 1.0) / (doc_freq as f32 + 1.0)).ln()
+}
+
+impl Similarity for TfIdfSimilarity {
+    fn weight(
+        &self,
+        field_statistics: &FieldStatistics,
+        terms: &[(Term, u64)],
+    ) -> Box<dyn SimilarityWeight> {
+        let mut idf_explain = Explanation::new("idf, sum of 1 + log((N + 1) / (n + 1))", 0.0);
+        let mut idf = 0.0;
+        for (term, doc_freq) in terms {
+            let term_idf = tf_idf_idf(*doc_freq, field_statistics.num_docs);
+            let mut term_idf_explain =
+                Explanation::new(format!("idf({})", term_label(term)), term_idf);
+            term_idf_explain.add_const("n, number of docs containing this term", *doc_freq as f32);
+            idf_explain.add_detail(term_idf_explain);
+            idf += term_idf;
+        }
+        idf_explain.add_const("N, total number of docs", field_statistics.num_docs as f32);
+        Box::new(TfIdfWeight { idf, idf_explain })
+    }
+}
+
+struct TfIdfWeight {
+    idf: f32,
+    idf_explain: Explanation,
+}
+
+impl TfIdfWeight {
+    fn tf_norm(&self, fieldnorm_reader: &FieldNormReader, doc: DocId, term_freq: f32) -> f32 {
+        // The fieldnorm may be lower than the actual length of the field.
+        // A field has at least as many tokens as occurrences of the term.
+        let fieldnorm = (fieldnorm_reader.fieldnorm(doc) as f32).max(term_freq);
+        if fieldnorm > 0.0 {
+            (term_freq / fieldnorm).sqrt()
+        } else {
+            0.0
+        }
+    }
+}
+
+impl SimilarityWeight for TfIdfWeight {
+    fn score(&self, fieldnorm_reader: &FieldNormReader, doc: DocId, term_freq: f32) -> Score {
+        self.idf * self.idf * self.tf_norm(fieldnorm_reader, doc, term_freq)
+    }
+
+    fn max_score(&self) -> Score {
+        self.idf * self.idf
+    }
+
+    fn explain(
+        &self,
+        fieldnorm_reader: &FieldNormReader,
+        doc: DocId,
+        term_freq: f32,
+    ) -> Explanation {
+        let tf_norm = self.tf_norm(fieldnorm_reader, doc, term_freq);
+        let mut tf_explanation = Explanation::new("sqrt(freq / dl)", tf_norm);
+        tf_explanation.add_const("freq, occurrences of term within document", term_freq);
+        tf_explanation.add_const(
+            "dl, length of field",
+            fieldnorm_reader.fieldnorm(doc) as f32,
+        );
+        let mut explanation = Explanation::new(
+            "TF-IDF, product of...",
+            self.score(fieldnorm_reader, doc, term_freq),
+        );
+        explanation.add_detail(self.idf_explain.clone());
+        explanation.add_detail(self.idf_explain.clone());
+        explanation.add_detail(tf_explanation);
+        explanation
+    }
+}
+
+/// Similarity giving the same score of `1` to all of the documents matching a
+/// term or a phrase, whatever the frequency of the term and the length of the field.
+///
+/// The score of a document for a boolean query is then the number of clauses it matches,
+/// which is useful when the text of the documents is not relevant for ranking,
+/// e.g. for tags or keywords.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BooleanSimilarity;
+
+impl Similarity for BooleanSimilarity {
+    fn weight(&self, _: &FieldStatistics, _: &[(Term, u64)]) -> Box<dyn SimilarityWeight> {
+        Box::new(BooleanSimilarity)
+    }
+}
+
+impl SimilarityWeight for BooleanSimilarity {
+    fn score(&self, _: &FieldNormReader, _: DocId, _: f32) -> Score {
+        1.0
+    }
+
+    fn max_score(&self) -> Score {
+        1.0
+    }
+
+    fn explain(&self, _: &FieldNormReader, _: DocId, _: f32) -> Explanation {
+        Explanation::new("BooleanSimilarity, constant score of a match", 1.0)
+    }
+}
+
+/// Similarity giving the same configurable score to all of the documents matching
+/// a term or a phrase, whatever the frequency of the term and the length of the field.
+///
+/// Unlike the `BooleanSimilarity`, the score can weigh the field against the others.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantSimilarity {
+    score: Score,
+}
+
+impl ConstantSimilarity {
+    /// Creates a similarity giving `score` to all of the matching documents.
+    ///
+    /// # Panics
+    /// The method panics if `score` is negative or not finite.
+    pub fn new(score: Score) -> ConstantSimilarity {
+        assert!(
+            score.is_finite() && score >= 0.0,
+            "The score must be positive and finite."
+        );
+        ConstantSimilarity { score }
+    }
+
+    /// Returns the score of the matching documents.
+    pub fn score(&self) -> Score {
+        self.score
+    }
+}
+
+impl Similarity for ConstantSimilarity {
+    fn weight(&self, _: &FieldStatistics, _: &[(Term, u64)]) -> Box<dyn SimilarityWeight> {
+        Box::new(*self)
+    }
+}
+
+impl SimilarityWeight for ConstantSimilarity {
+    fn score(&self, _: &FieldNormReader, _: DocId, _: f32) -> Score {
+        self.score
+    }
+
+    fn max_score(&self) -> Score {
+        self.score
+    }
+
+    fn explain(&self, _: &FieldNormReader, _: DocId, _: f32) -> Explanation {
+        Explanation::new("ConstantSimilarity, constant score of a match", self.score)
+    }
+}
+
+/// Definition of a built-in `Similarity`, as persisted in the `IndexSettings`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SimilarityDef {
+    /// The `BM25Similarity`.
+    Bm25 {
+        /// Term frequency saturation parameter. It must be positive.
+        k1: f32,
+        /// Length normalization parameter. It must be within `[0, 1]`.
+        b: f32,
+    },
+    /// The `TfIdfSimilarity`.
+    TfIdf,
+    /// The `BooleanSimilarity`.
+    Boolean,
+    /// The `ConstantSimilarity`.
+    Constant {
+        /// Score of the matching documents. It must be positive and finite.
+        score: Score,
+    },
+}
+
+impl SimilarityDef {
+    /// Builds the similarity.
+    ///
+    /// Returns an `InvalidArgument` error if its parameters are invalid.
+    pub fn build(&self) -> Result<Arc<dyn Similarity>> {
+        match *self {
+            SimilarityDef::Bm25 { k1, b } => {
+                if !(k1 >= 0.0 && (0.0..=1.0).contains(&b)) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Invalid BM25 parameters: k1={}, b={}",
+                        k1, b
+                    )));
+                }
+                Ok(Arc::new(BM25Similarity::new(k1, b)))
+            }
+            SimilarityDef::TfIdf => Ok(Arc::new(TfIdfSimilarity)),
+            SimilarityDef::Boolean => Ok(Arc::new(BooleanSimilarity)),
+            SimilarityDef::Constant { score } => {
+                if !(score.is_finite() && score >= 0.0) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Invalid constant score: {}",
+                        score
+                    )));
+                }
+                Ok(Arc::new(ConstantSimilarity::new(score)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::TopDocs;
+    use crate::core::IndexSettings;
+    use crate::directory::RAMDirectory;
+    use crate::query::{
+        BM25Similarity, BooleanQuery, BooleanSimilarity, ConstantSimilarity, Query, QueryParser,
+        SimilarityDef, TfIdfSimilarity,
+    };
+    use crate::schema::{Schema, TEXT};
+    use crate::tests::assert_nearly_equals;
+    use crate::{DocAddress, Index, Score, Term};
+
+    fn create_schema() -> Schema {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("body", TEXT);
+        schema_builder.build()
+    }
+
+    fn create_index() -> Index {
+        create_index_with_settings(RAMDirectory::create(), IndexSettings::default())
+    }
+
+    fn create_index_with_settings(directory: RAMDirectory, settings: IndexSettings) -> Index {
+        let index = Index::create_with_settings(directory, create_schema(), settings).unwrap();
+        let title = index.schema().get_field("title").unwrap();
+        let body = index.schema().get_field("body").unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(title => "a", body => "a b c d"));
+        index_writer.add_document(doc!(title => "a a", body => "b"));
+        index_writer.add_document(doc!(title => "a b a b", body => "c"));
+        index_writer.add_document(doc!(title => "c", body => "a a a"));
+        index_writer.commit().unwrap();
+        index
+    }
+
+    fn scores(index: &Index, query: &dyn Query) -> Vec<(u32, Score)> {
+        let searcher = index.reader().unwrap().searcher();
+        let mut scores: Vec<(u32, Score)> = searcher
+            .search(query, &TopDocs::with_limit(10))
+            .unwrap()
+            .into_iter()
+            .map(|(score, doc_address)| (doc_address.doc(), score))
+            .collect();
+        scores.sort_by_key(|&(doc, _)| doc);
+        for &(doc, score) in &scores {
+            let explanation = query.explain(&searcher, DocAddress(0, doc)).unwrap();
+            assert_nearly_equals(explanation.value(), score);
+        }
+        scores
+    }
+
+    #[test]
+    fn test_bm25_similarity_parameters() {
+        let index = create_index();
+        let title = index.schema().get_field("title").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let query = query_parser.parse_query("a").unwrap();
+        let default_scores = scores(&index, &query);
+        // The length of the field outweighs the frequency of the term.
+        assert!(default_scores[0].1 > default_scores[2].1);
+
+        // Without length normalization, only the term frequency matters.
+        index.set_similarity(title, BM25Similarity::new(1.2, 0.0));
+        let no_norm_scores = scores(&index, &query);
+        assert!(no_norm_scores[0].1 < no_norm_scores[1].1);
+        assert_nearly_equals(no_norm_scores[1].1, no_norm_scores[2].1);
+
+        // With `k1 = 0`, the term frequency does not matter either.
+        index.set_similarity(title, BM25Similarity::new(0.0, 0.75));
+        let idf_scores = scores(&index, &query);
+        assert_nearly_equals(idf_scores[0].1, idf_scores[2].1);
+
+        let phrase_query = query_parser.parse_query("\"a b\"").unwrap();
+        assert_eq!(scores(&index, &phrase_query).len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bm25_similarity_invalid_b() {
+        BM25Similarity::new(1.2, 1.5);
+    }
+
+    #[test]
+    fn test_tf_idf_similarity() {
+        let index = create_index();
+        let title = index.schema().get_field("title").unwrap();
+        index.set_similarity(title, TfIdfSimilarity);
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let query = query_parser.parse_query("a").unwrap();
+        // 3 documents out of 4 contain `a`.
+        let idf = 1.0 + (5f32 / 4.0).ln();
+        let expected_scores = vec![
+            (0, idf * idf),
+            (1, idf * idf),
+            (2, idf * idf * 0.5f32.sqrt()),
+        ];
+        let tf_idf_scores = scores(&index, &query);
+        assert_eq!(tf_idf_scores.len(), expected_scores.len());
+        for (&(doc, score), &(expected_doc, expected_score)) in
+            tf_idf_scores.iter().zip(&expected_scores)
+        {
+            assert_eq!(doc, expected_doc);
+            assert_nearly_equals(score, expected_score);
+        }
+
+        // The top documents of a disjunction are pruned with the upper bounds of the scores.
+        let terms = vec![
+            Term::from_field_text(title, "a"),
+            Term::from_field_text(title, "b"),
+            Term::from_field_text(title, "c"),
+        ];
+        let disjunction = BooleanQuery::new_multiterms_query(terms);
+        let all_scores = scores(&index, &disjunction);
+        let searcher = index.reader().unwrap().searcher();
+        let top_docs = searcher
+            .search(&disjunction, &TopDocs::with_limit(1))
+            .unwrap();
+        let best_score = all_scores
+            .iter()
+            .map(|&(_, score)| score)
+            .fold(0.0, f32::max);
+        assert_nearly_equals(top_docs[0].0, best_score);
+    }
+
+    #[test]
+    fn test_boolean_similarity_per_field() {
+        let index = create_index();
+        let title = index.schema().get_field("title").unwrap();
+        let body = index.schema().get_field("body").unwrap();
+        index.set_similarity(title, BooleanSimilarity);
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let query = query_parser.parse_query("a b c").unwrap();
+        assert_eq!(
+            scores(&index, &query),
+            vec![(0, 1.0), (1, 1.0), (2, 2.0), (3, 1.0)]
+        );
+
+        // The other fields are still scored with BM25.
+        let query_parser = QueryParser::for_index(&index, vec![body]);
+        let query = query_parser.parse_query("a").unwrap();
+        let body_scores = scores(&index, &query);
+        assert!(body_scores[1].1 > body_scores[0].1);
+    }
+
+    #[test]
+    fn test_constant_similarity() {
+        let index = create_index();
+        let title = index.schema().get_field("title").unwrap();
+        let body = index.schema().get_field("body").unwrap();
+        index.set_similarity(title, ConstantSimilarity::new(3.0));
+        index.set_similarity(body, ConstantSimilarity::new(0.5));
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let query = query_parser.parse_query("a").unwrap();
+        assert_eq!(
+            scores(&index, &query),
+            vec![(0, 3.5), (1, 3.0), (2, 3.0), (3, 0.5)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_constant_similarity_negative_score() {
+        ConstantSimilarity::new(-1.0);
+    }
+
+    #[test]
+    fn test_similarity_settings_are_persisted() {
+        let directory = RAMDirectory::create();
+        let settings = IndexSettings::default()
+            .similarity("title", SimilarityDef::Constant { score: 2.0 })
+            .similarity("body", SimilarityDef::Boolean);
+        create_index_with_settings(directory.clone(), settings.clone());
+        let index = Index::open(directory).unwrap();
+        assert_eq!(index.settings(), &settings);
+        let title = index.schema().get_field("title").unwrap();
+        let body = index.schema().get_field("body").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let query = query_parser.parse_query("a").unwrap();
+        assert_eq!(
+            scores(&index, &query),
+            vec![(0, 3.0), (1, 2.0), (2, 2.0), (3, 1.0)]
+        );
+
+        // An explicit similarity overrides the one of the settings.
+        index.set_similarity(title, BooleanSimilarity);
+        let query = query_parser.parse_query("title:a").unwrap();
+        assert_eq!(scores(&index, &query), vec![(0, 1.0), (1, 1.0), (2, 1.0)]);
+    }
+
+    #[test]
+    fn test_similarity_settings_bm25_parameters() {
+        let settings =
+            IndexSettings::default().similarity("title", SimilarityDef::Bm25 { k1: 0.0, b: 0.75 });
+        let index = create_index_with_settings(RAMDirectory::create(), settings);
+        let title = index.schema().get_field("title").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let query = query_parser.parse_query("a").unwrap();
+        let idf_scores = scores(&index, &query);
+        assert_nearly_equals(idf_scores[0].1, idf_scores[2].1);
+    }
+
+    #[test]
+    fn test_invalid_similarity_settings() {
+        let invalid_settings = vec![
+            IndexSettings::default().similarity("missing", SimilarityDef::TfIdf),
+            IndexSettings::default().similarity("title", SimilarityDef::Bm25 { k1: 1.2, b: 1.5 }),
+            IndexSettings::default().similarity("title", SimilarityDef::Constant { score: -1.0 }),
+        ];
+        for settings in invalid_settings {
+            assert!(
+                Index::create_with_settings(RAMDirectory::create(), create_schema(), settings)
+                    .is_err()
+            );
+        }
+    }
+}
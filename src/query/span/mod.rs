@@ -0,0 +1,350 @@
+/*!
+Span queries match spans of positions within a field, rather than
+whole documents.
+
+Each span query exposes the positions of its matches to its parent
+span query, through a [`SpanScorer`](./trait.SpanScorer.html). This makes
+it possible to compose proximity constraints:
+- [`SpanTermQuery`](./struct.SpanTermQuery.html) matches the positions of a term.
+- [`SpanNearQuery`](./struct.SpanNearQuery.html) matches its clauses close
+  to one another, in order or not.
+- [`SpanOrQuery`](./struct.SpanOrQuery.html) matches the spans of any of its clauses.
+- [`SpanNotQuery`](./struct.SpanNotQuery.html) removes the spans close to the
+  spans of another query.
+- [`SpanFirstQuery`](./struct.SpanFirstQuery.html) restricts the spans to the
+  beginning of the field.
+- [`PayloadScoreQuery`](./struct.PayloadScoreQuery.html) scores the spans of a
+  query with the payloads of its terms.
+
+A span query is also a regular [`Query`](../trait.Query.html). Its documents
+are scored like a sloppy phrase: each span contributes `1 / (1 + slop)` to
+the frequency given to the similarity of the field.
+*/
+
+mod payload_score_query;
+mod span_first_query;
+mod span_near_query;
+mod span_not_query;
+mod span_or_query;
+mod span_term_query;
+mod span_weight;
+
+pub use self::payload_score_query::{PayloadFunction, PayloadScoreQuery};
+pub use self::span_first_query::SpanFirstQuery;
+pub use self::span_near_query::SpanNearQuery;
+pub use self::span_not_query::SpanNotQuery;
+pub use self::span_or_query::SpanOrQuery;
+pub use self::span_term_query::SpanTermQuery;
+pub use self::span_weight::SpanScoringWeight;
+
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::query::Query;
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+
+/// A range of positions `[start, end)` of a field, matched by a span query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span {
+    /// Position of the first token of the span.
+    pub start: u32,
+    /// Position following the last token of the span.
+    pub end: u32,
+    /// Number of positions within the span that are not matched by
+    /// a term, e.g. `1` for `a b` in `a x b`.
+    pub slop: u32,
+}
+
+impl Span {
+    /// Returns the number of positions covered by the span.
+    pub fn width(&self) -> u32 {
+        self.end - self.start
+    }
+}
+
+/// A `DocSet` exposing the spans matched within each of its documents.
+///
+/// It is the span counterpart of the [`Scorer`](../trait.Scorer.html).
+pub trait SpanScorer: DocSet + 'static {
+    /// Returns the spans matched in the current document, sorted by
+    /// their start and then by their end.
+    ///
+    /// A span scorer only emits the documents having at least one span.
+    fn spans(&mut self) -> &[Span];
+}
+
+impl SpanScorer for Box<dyn SpanScorer> {
+    fn spans(&mut self) -> &[Span] {
+        self.as_mut().spans()
+    }
+}
+
+/// The specialization of a span query for a given set of segments.
+pub trait SpanWeight: Send + Sync + 'static {
+    /// Returns the span scorer for the given segment.
+    fn span_scorer(&self, reader: &SegmentReader) -> Result<Box<dyn SpanScorer>>;
+}
+
+/// A `Query` whose matches are spans of positions of a single field.
+pub trait SpanQuery: Query + SpanQueryClone {
+    /// Returns the field of the spans.
+    fn field(&self) -> Field;
+
+    /// Creates the span weight associated to the query.
+    fn span_weight(&self, searcher: &Searcher) -> Result<Box<dyn SpanWeight>>;
+}
+
+/// Clones a `SpanQuery` into a boxed trait object.
+pub trait SpanQueryClone {
+    /// Returns a boxed clone of the span query.
+    fn box_clone_span(&self) -> Box<dyn SpanQuery>;
+}
+
+impl<T> SpanQueryClone for T
+where
+    T: 'static + SpanQuery + Clone,
+{
+    fn box_clone_span(&self) -> Box<dyn SpanQuery> {
+        Box::new(self.clone())
+    }
+}
+
+fn assert_same_field(field: Field, clauses: &[Box<dyn SpanQuery>]) {
+    for clause in clauses {
+        assert_eq!(
+            clause.field(),
+            field,
+            "All span clauses must belong to the same field."
+        );
+    }
+}
+
+/// A span scorer computing its spans from an underlying docset, and
+/// skipping the documents for which no span is left.
+trait SpanFilter: DocSet + 'static {
+    /// Computes the spans of the current document into `spans`.
+    fn compute_spans(&mut self, spans: &mut Vec<Span>);
+}
+
+struct FilteredSpanScorer<TSpanFilter: SpanFilter> {
+    span_filter: TSpanFilter,
+    spans: Vec<Span>,
+}
+
+impl<TSpanFilter: SpanFilter> FilteredSpanScorer<TSpanFilter> {
+    fn new(span_filter: TSpanFilter) -> FilteredSpanScorer<TSpanFilter> {
+        FilteredSpanScorer {
+            span_filter,
+            spans: Vec::new(),
+        }
+    }
+
+    fn has_spans(&mut self) -> bool {
+        self.spans.clear();
+        self.span_filter.compute_spans(&mut self.spans);
+        !self.spans.is_empty()
+    }
+}
+
+impl<TSpanFilter: SpanFilter> DocSet for FilteredSpanScorer<TSpanFilter> {
+    fn advance(&mut self) -> bool {
+        while self.span_filter.advance() {
+            if self.has_spans() {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        match self.span_filter.skip_next(target) {
+            SkipResult::End => SkipResult::End,
+            SkipResult::Reached if self.has_spans() => SkipResult::Reached,
+            SkipResult::OverStep if self.has_spans() => SkipResult::OverStep,
+            _ => {
+                if self.advance() {
+                    SkipResult::OverStep
+                } else {
+                    SkipResult::End
+                }
+            }
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.span_filter.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.span_filter.size_hint()
+    }
+}
+
+impl<TSpanFilter: SpanFilter> SpanScorer for FilteredSpanScorer<TSpanFilter> {
+    fn spans(&mut self) -> &[Span] {
+        &self.spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{PhraseQuery, Query};
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::tests::assert_nearly_equals;
+    use crate::{DocAddress, Index, IndexReader, Term};
+
+    fn build_index(texts: &[&str]) -> (IndexReader, Field) {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for &doc_text in texts {
+            index_writer.add_document(doc!(text => doc_text));
+        }
+        index_writer.commit().unwrap();
+        (index.reader().unwrap(), text)
+    }
+
+    fn span_term(field: Field, text: &str) -> Box<dyn SpanQuery> {
+        Box::new(SpanTermQuery::new(Term::from_field_text(field, text)))
+    }
+
+    fn matching_docs(reader: &IndexReader, query: &dyn Query) -> Vec<u32> {
+        let searcher = reader.searcher();
+        let mut docs: Vec<u32> = searcher
+            .search(query, &TopDocs::with_limit(100))
+            .unwrap()
+            .into_iter()
+            .map(|(score, doc_address)| {
+                let explanation = query.explain(&searcher, doc_address).unwrap();
+                assert_nearly_equals(explanation.value(), score);
+                doc_address.doc()
+            })
+            .collect();
+        docs.sort();
+        docs
+    }
+
+    fn spans(reader: &IndexReader, query: &dyn SpanQuery, doc: DocId) -> Vec<(u32, u32)> {
+        let searcher = reader.searcher();
+        let span_weight = query.span_weight(&searcher).unwrap();
+        let mut span_scorer = span_weight.span_scorer(searcher.segment_reader(0)).unwrap();
+        if span_scorer.skip_next(doc) != SkipResult::Reached {
+            return Vec::new();
+        }
+        span_scorer
+            .spans()
+            .iter()
+            .map(|span| (span.start, span.end))
+            .collect()
+    }
+
+    #[test]
+    fn test_span_term_query() {
+        let (reader, text) = build_index(&["a b a", "b c", "c"]);
+        let query = SpanTermQuery::new(Term::from_field_text(text, "a"));
+        assert_eq!(matching_docs(&reader, &query), vec![0]);
+        assert_eq!(spans(&reader, &query, 0), vec![(0, 1), (2, 3)]);
+        let query = SpanTermQuery::new(Term::from_field_text(text, "b"));
+        assert_eq!(matching_docs(&reader, &query), vec![0, 1]);
+        let query = SpanTermQuery::new(Term::from_field_text(text, "z"));
+        assert_eq!(matching_docs(&reader, &query), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_span_near_query() {
+        let (reader, text) = build_index(&["a b c", "a x b", "b a", "a x x b", "c"]);
+        let near = |slop, in_order| {
+            SpanNearQuery::new(
+                vec![span_term(text, "a"), span_term(text, "b")],
+                slop,
+                in_order,
+            )
+        };
+        assert_eq!(matching_docs(&reader, &near(0, true)), vec![0]);
+        assert_eq!(matching_docs(&reader, &near(1, true)), vec![0, 1]);
+        assert_eq!(matching_docs(&reader, &near(0, false)), vec![0, 2]);
+        assert_eq!(matching_docs(&reader, &near(2, false)), vec![0, 1, 2, 3]);
+        assert_eq!(spans(&reader, &near(1, true), 1), vec![(0, 3)]);
+        assert_eq!(spans(&reader, &near(0, false), 2), vec![(0, 2)]);
+
+        // Span near queries can be nested.
+        let nested =
+            SpanNearQuery::new(vec![Box::new(near(1, true)), span_term(text, "c")], 0, true);
+        assert_eq!(matching_docs(&reader, &nested), vec![0]);
+        assert_eq!(spans(&reader, &nested, 0), vec![(0, 3)]);
+
+        // Without slop, an ordered span near query matches like a phrase query.
+        let searcher = reader.searcher();
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text, "a"),
+            Term::from_field_text(text, "b"),
+        ]);
+        let phrase_score = searcher
+            .search(&phrase_query, &TopDocs::with_limit(1))
+            .unwrap()[0]
+            .0;
+        let top_docs = searcher
+            .search(&near(0, true), &TopDocs::with_limit(1))
+            .unwrap();
+        assert_eq!(top_docs[0].1, DocAddress(0, 0));
+        assert_nearly_equals(top_docs[0].0, phrase_score);
+    }
+
+    #[test]
+    fn test_span_or_query() {
+        let (reader, text) = build_index(&["a b", "c d", "b x c", "e"]);
+        let or_query = SpanOrQuery::new(vec![span_term(text, "b"), span_term(text, "c")]);
+        assert_eq!(matching_docs(&reader, &or_query), vec![0, 1, 2]);
+        assert_eq!(spans(&reader, &or_query, 2), vec![(0, 1), (2, 3)]);
+        let near = SpanNearQuery::new(vec![span_term(text, "a"), Box::new(or_query)], 0, true);
+        assert_eq!(matching_docs(&reader, &near), vec![0]);
+    }
+
+    #[test]
+    fn test_span_not_query() {
+        let (reader, text) = build_index(&["a b", "x a b", "a b y x", "a c b"]);
+        let a_b = || SpanNearQuery::new(vec![span_term(text, "a"), span_term(text, "b")], 1, true);
+        let not_query = SpanNotQuery::new(Box::new(a_b()), span_term(text, "x"), 0, 0);
+        assert_eq!(matching_docs(&reader, &not_query), vec![0, 1, 2, 3]);
+        let not_query = SpanNotQuery::new(Box::new(a_b()), span_term(text, "x"), 1, 1);
+        assert_eq!(matching_docs(&reader, &not_query), vec![0, 2, 3]);
+        let not_query = SpanNotQuery::new(Box::new(a_b()), span_term(text, "x"), 1, 2);
+        assert_eq!(matching_docs(&reader, &not_query), vec![0, 3]);
+        let not_query = SpanNotQuery::new(Box::new(a_b()), span_term(text, "c"), 0, 0);
+        assert_eq!(matching_docs(&reader, &not_query), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_span_first_query() {
+        let (reader, text) = build_index(&["a b", "x a", "x x a"]);
+        let first = |end| SpanFirstQuery::new(span_term(text, "a"), end);
+        assert_eq!(matching_docs(&reader, &first(1)), vec![0]);
+        assert_eq!(matching_docs(&reader, &first(2)), vec![0, 1]);
+        let searcher = reader.searcher();
+        assert_eq!(searcher.search(&first(10), &Count).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_span_query_requires_positions() {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader().unwrap().searcher();
+        let query = SpanTermQuery::new(Term::from_field_text(id, "a"));
+        assert!(query.weight(&searcher, true).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "All span clauses must belong to the same field.")]
+    fn test_span_near_query_different_fields() {
+        SpanNearQuery::new(
+            vec![span_term(Field(0), "a"), span_term(Field(1), "b")],
+            0,
+            true,
+        );
+    }
+}
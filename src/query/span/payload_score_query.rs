@@ -0,0 +1,243 @@
+use super::span_weight::span_query_weight_with_payloads;
+use super::{SpanQuery, SpanWeight};
+use crate::core::Searcher;
+use crate::query::{Query, Weight};
+use crate::schema::Field;
+use crate::Result;
+use crate::Score;
+use crate::Term;
+use std::collections::BTreeSet;
+
+/// Function combining the payloads of the terms matched in a document
+/// into the factor applied to its score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadFunction {
+    /// Sum of the payloads.
+    Sum,
+    /// Greatest payload.
+    Max,
+    /// Mean of the payloads.
+    Average,
+}
+
+impl PayloadFunction {
+    /// Combines the weights decoded from the payloads, `1.0` being
+    /// returned if there are none.
+    pub(crate) fn combine(self, weights: &[f32]) -> Score {
+        if weights.is_empty() {
+            return 1.0;
+        }
+        match self {
+            PayloadFunction::Sum => weights.iter().sum(),
+            PayloadFunction::Max => weights.iter().cloned().fold(f32::MIN, f32::max),
+            PayloadFunction::Average => weights.iter().sum::<f32>() / weights.len() as f32,
+        }
+    }
+}
+
+/// `PayloadScoreQuery` matches the documents of a span query, and multiplies
+/// their score by the payloads of the query terms found within the spans.
+///
+/// The payloads are read as the bits of a `f32` weight, as given by
+/// [`f32::to_bits`](https://doc.rust-lang.org/std/primitive.f32.html#method.to_bits),
+/// the positions without payload being ignored. The field must be indexed with
+/// [payloads](../schema/struct.TextFieldIndexing.html#method.set_payloads).
+///
+/// ```rust
+/// use tantivy::query::{PayloadFunction, PayloadScoreQuery, SpanTermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+/// use tantivy::Term;
+///
+/// let mut schema_builder = Schema::builder();
+/// let indexing = TextFieldIndexing::default()
+///     .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+///     .set_payloads(true);
+/// let body = schema_builder.add_text_field("body", TextOptions::default().set_indexing_options(indexing));
+/// let query = PayloadScoreQuery::new(
+///     Box::new(SpanTermQuery::new(Term::from_field_text(body, "rust"))),
+///     PayloadFunction::Max,
+/// );
+/// ```
+#[derive(Debug)]
+pub struct PayloadScoreQuery {
+    query: Box<dyn SpanQuery>,
+    payload_function: PayloadFunction,
+}
+
+impl PayloadScoreQuery {
+    /// Creates a query scoring the documents of `query` with the payloads
+    /// of its terms, combined by `payload_function`.
+    pub fn new(query: Box<dyn SpanQuery>, payload_function: PayloadFunction) -> PayloadScoreQuery {
+        PayloadScoreQuery {
+            query,
+            payload_function,
+        }
+    }
+
+    /// Returns the span query whose documents are matched.
+    pub fn query(&self) -> &dyn SpanQuery {
+        self.query.as_ref()
+    }
+
+    /// Returns the function combining the payloads.
+    pub fn payload_function(&self) -> PayloadFunction {
+        self.payload_function
+    }
+}
+
+impl Clone for PayloadScoreQuery {
+    fn clone(&self) -> Self {
+        PayloadScoreQuery {
+            query: self.query.box_clone_span(),
+            payload_function: self.payload_function,
+        }
+    }
+}
+
+impl Query for PayloadScoreQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        span_query_weight_with_payloads(
+            self,
+            searcher,
+            scoring_enabled,
+            Some(self.payload_function),
+        )
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+}
+
+impl SpanQuery for PayloadScoreQuery {
+    fn field(&self) -> Field {
+        self.query.field()
+    }
+
+    fn span_weight(&self, searcher: &Searcher) -> Result<Box<dyn SpanWeight>> {
+        self.query.span_weight(searcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PayloadFunction, PayloadScoreQuery};
+    use crate::collector::TopDocs;
+    use crate::query::{Query, SpanNearQuery, SpanQuery, SpanTermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, TEXT};
+    use crate::tests::assert_nearly_equals;
+    use crate::tokenizer::{SimpleTokenizer, Token, TokenFilter, TokenStream, Tokenizer};
+    use crate::{Index, TantivyError, Term};
+
+    /// Gives a weight of 3 to the tokens starting with an uppercase letter.
+    #[derive(Clone)]
+    struct CapitalizedPayloadFilter;
+
+    impl<TailTokenStream: TokenStream> TokenFilter<TailTokenStream> for CapitalizedPayloadFilter {
+        type ResultTokenStream = CapitalizedPayloadStream<TailTokenStream>;
+
+        fn transform(&self, token_stream: TailTokenStream) -> Self::ResultTokenStream {
+            CapitalizedPayloadStream { tail: token_stream }
+        }
+    }
+
+    struct CapitalizedPayloadStream<TailTokenStream> {
+        tail: TailTokenStream,
+    }
+
+    impl<TailTokenStream: TokenStream> TokenStream for CapitalizedPayloadStream<TailTokenStream> {
+        fn advance(&mut self) -> bool {
+            if !self.tail.advance() {
+                return false;
+            }
+            let token = self.tail.token_mut();
+            if token.text.starts_with(char::is_uppercase) {
+                token.payload = 3f32.to_bits();
+                token.text = token.text.to_lowercase();
+            }
+            true
+        }
+
+        fn token(&self) -> &Token {
+            self.tail.token()
+        }
+
+        fn token_mut(&mut self) -> &mut Token {
+            self.tail.token_mut()
+        }
+    }
+
+    #[test]
+    fn test_payload_score_query() {
+        let mut schema_builder = Schema::builder();
+        let indexing = TextFieldIndexing::default()
+            .set_tokenizer("capitalized")
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+            .set_payloads(true);
+        let text = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(indexing),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "capitalized",
+            SimpleTokenizer.filter(CapitalizedPayloadFilter),
+        );
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text => "rust guide"));
+        index_writer.add_document(doc!(text => "Rust guide"));
+        index_writer.add_document(doc!(text => "Rust Guide"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let span_term = |word: &str| -> Box<dyn SpanQuery> {
+            Box::new(SpanTermQuery::new(Term::from_field_text(text, word)))
+        };
+        let scores = |query: &dyn Query| -> Vec<f32> {
+            let mut top_docs = searcher.search(query, &TopDocs::with_limit(10)).unwrap();
+            top_docs.sort_by_key(|(_, doc_address)| doc_address.doc());
+            for &(score, doc_address) in &top_docs {
+                let explanation = query.explain(&searcher, doc_address).unwrap();
+                assert_nearly_equals(explanation.value(), score);
+            }
+            top_docs.into_iter().map(|(score, _)| score).collect()
+        };
+        let rust = scores(&PayloadScoreQuery::new(
+            span_term("rust"),
+            PayloadFunction::Max,
+        ));
+        assert_eq!(rust.len(), 3);
+        assert_nearly_equals(rust[1], rust[0] * 3.0);
+        assert_nearly_equals(rust[2], rust[0] * 3.0);
+        let near = || -> Box<dyn SpanQuery> {
+            Box::new(SpanNearQuery::new(
+                vec![span_term("rust"), span_term("guide")],
+                0,
+                true,
+            ))
+        };
+        let base = scores(near().as_ref());
+        let sum = scores(&PayloadScoreQuery::new(near(), PayloadFunction::Sum));
+        assert_nearly_equals(sum[0], base[0]);
+        assert_nearly_equals(sum[1], base[1] * 3.0);
+        assert_nearly_equals(sum[2], base[2] * 6.0);
+        let average = scores(&PayloadScoreQuery::new(near(), PayloadFunction::Average));
+        assert_nearly_equals(average[0], base[0]);
+        assert_nearly_equals(average[2], base[2] * 3.0);
+    }
+
+    #[test]
+    fn test_payload_score_query_requires_payloads() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader().unwrap().searcher();
+        let query = PayloadScoreQuery::new(
+            Box::new(SpanTermQuery::new(Term::from_field_text(text, "rust"))),
+            PayloadFunction::Sum,
+        );
+        assert!(matches!(
+            query.weight(&searcher, true),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+use super::span_weight::span_query_weight;
+use super::{FilteredSpanScorer, Span, SpanFilter, SpanQuery, SpanScorer, SpanWeight};
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::query::{Query, Weight};
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+use crate::Term;
+use std::collections::BTreeSet;
+
+/// `SpanFirstQuery` matches the spans of a query ending within
+/// the first `end` positions of the field.
+#[derive(Debug)]
+pub struct SpanFirstQuery {
+    query: Box<dyn SpanQuery>,
+    end: u32,
+}
+
+impl SpanFirstQuery {
+    /// Creates a query matching the spans of `query` ending at or before the position `end`.
+    pub fn new(query: Box<dyn SpanQuery>, end: u32) -> SpanFirstQuery {
+        SpanFirstQuery { query, end }
+    }
+
+    /// Returns the query whose spans are matched.
+    pub fn query(&self) -> &dyn SpanQuery {
+        self.query.as_ref()
+    }
+
+    /// Returns the position at which the spans must end at the latest.
+    pub fn end(&self) -> u32 {
+        self.end
+    }
+}
+
+impl Clone for SpanFirstQuery {
+    fn clone(&self) -> Self {
+        SpanFirstQuery {
+            query: self.query.box_clone_span(),
+            end: self.end,
+        }
+    }
+}
+
+impl Query for SpanFirstQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        span_query_weight(self, searcher, scoring_enabled)
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+}
+
+impl SpanQuery for SpanFirstQuery {
+    fn field(&self) -> Field {
+        self.query.field()
+    }
+
+    fn span_weight(&self, searcher: &Searcher) -> Result<Box<dyn SpanWeight>> {
+        Ok(Box::new(SpanFirstWeight {
+            span_weight: self.query.span_weight(searcher)?,
+            end: self.end,
+        }))
+    }
+}
+
+struct SpanFirstWeight {
+    span_weight: Box<dyn SpanWeight>,
+    end: u32,
+}
+
+impl SpanWeight for SpanFirstWeight {
+    fn span_scorer(&self, reader: &SegmentReader) -> Result<Box<dyn SpanScorer>> {
+        Ok(Box::new(FilteredSpanScorer::new(FirstSpanFilter {
+            span_scorer: self.span_weight.span_scorer(reader)?,
+            end: self.end,
+        })))
+    }
+}
+
+struct FirstSpanFilter {
+    span_scorer: Box<dyn SpanScorer>,
+    end: u32,
+}
+
+impl DocSet for FirstSpanFilter {
+    fn advance(&mut self) -> bool {
+        self.span_scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.span_scorer.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.span_scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.span_scorer.size_hint()
+    }
+}
+
+impl SpanFilter for FirstSpanFilter {
+    fn compute_spans(&mut self, spans: &mut Vec<Span>) {
+        let end = self.end;
+        spans.extend(
+            self.span_scorer
+                .spans()
+                .iter()
+                .filter(|span| span.end <= end),
+        );
+    }
+}
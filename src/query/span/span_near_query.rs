@@ -0,0 +1,278 @@
+use super::span_weight::span_query_weight;
+use super::{assert_same_field, FilteredSpanScorer, Span, SpanFilter};
+use super::{SpanQuery, SpanScorer, SpanWeight};
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::query::{Intersection, Query, Weight};
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+use crate::Term;
+use std::collections::BTreeSet;
+
+/// `SpanNearQuery` matches the spans of its clauses, close to one another.
+///
+/// The `slop` is the maximum number of positions between the spans of the
+/// clauses. If `in_order` is true, the spans of the clauses must also appear
+/// in the order of the clauses, without overlapping.
+///
+/// A `SpanNearQuery` with a slop of `0`, in order, made of `SpanTermQuery`s
+/// is equivalent to a [`PhraseQuery`](../struct.PhraseQuery.html).
+///
+/// # Panics
+///
+/// Creating a `SpanNearQuery` panics if there is no clause, or if the
+/// clauses do not belong to the same field.
+#[derive(Debug)]
+pub struct SpanNearQuery {
+    clauses: Vec<Box<dyn SpanQuery>>,
+    slop: u32,
+    in_order: bool,
+}
+
+impl SpanNearQuery {
+    /// Creates a query matching the spans of `clauses` distant of at most `slop` positions.
+    pub fn new(clauses: Vec<Box<dyn SpanQuery>>, slop: u32, in_order: bool) -> SpanNearQuery {
+        assert!(
+            !clauses.is_empty(),
+            "A span near query requires at least one clause."
+        );
+        assert_same_field(clauses[0].field(), &clauses);
+        SpanNearQuery {
+            clauses,
+            slop,
+            in_order,
+        }
+    }
+
+    /// Returns the clauses of the query.
+    pub fn clauses(&self) -> &[Box<dyn SpanQuery>] {
+        &self.clauses
+    }
+
+    /// Returns the maximum number of positions between the spans of the clauses.
+    pub fn slop(&self) -> u32 {
+        self.slop
+    }
+
+    /// Returns true if the spans of the clauses must appear in order.
+    pub fn in_order(&self) -> bool {
+        self.in_order
+    }
+}
+
+impl Clone for SpanNearQuery {
+    fn clone(&self) -> Self {
+        SpanNearQuery {
+            clauses: self
+                .clauses
+                .iter()
+                .map(|clause| clause.box_clone_span())
+                .collect(),
+            slop: self.slop,
+            in_order: self.in_order,
+        }
+    }
+}
+
+impl Query for SpanNearQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        span_query_weight(self, searcher, scoring_enabled)
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        for clause in &self.clauses {
+            clause.query_terms(term_set);
+        }
+    }
+}
+
+impl SpanQuery for SpanNearQuery {
+    fn field(&self) -> Field {
+        self.clauses[0].field()
+    }
+
+    fn span_weight(&self, searcher: &Searcher) -> Result<Box<dyn SpanWeight>> {
+        let span_weights = self
+            .clauses
+            .iter()
+            .map(|clause| clause.span_weight(searcher))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Box::new(SpanNearWeight {
+            span_weights,
+            slop: self.slop,
+            in_order: self.in_order,
+        }))
+    }
+}
+
+struct SpanNearWeight {
+    span_weights: Vec<Box<dyn SpanWeight>>,
+    slop: u32,
+    in_order: bool,
+}
+
+impl SpanWeight for SpanNearWeight {
+    fn span_scorer(&self, reader: &SegmentReader) -> Result<Box<dyn SpanScorer>> {
+        if self.span_weights.len() == 1 {
+            return self.span_weights[0].span_scorer(reader);
+        }
+        let span_scorers = self
+            .span_weights
+            .iter()
+            .enumerate()
+            .map(|(ord, span_weight)| {
+                Ok(OrdSpanScorer {
+                    ord,
+                    span_scorer: span_weight.span_scorer(reader)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let num_clauses = span_scorers.len();
+        Ok(Box::new(FilteredSpanScorer::new(NearSpanFilter {
+            intersection: Intersection::new(span_scorers),
+            clause_spans: vec![Vec::new(); num_clauses],
+            slop: self.slop,
+            in_order: self.in_order,
+        })))
+    }
+}
+
+/// A span scorer remembering the position of its clause.
+///
+/// The intersection reorders its docsets by size.
+struct OrdSpanScorer {
+    ord: usize,
+    span_scorer: Box<dyn SpanScorer>,
+}
+
+impl DocSet for OrdSpanScorer {
+    fn advance(&mut self) -> bool {
+        self.span_scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.span_scorer.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.span_scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.span_scorer.size_hint()
+    }
+}
+
+struct NearSpanFilter {
+    intersection: Intersection<OrdSpanScorer, OrdSpanScorer>,
+    clause_spans: Vec<Vec<Span>>,
+    slop: u32,
+    in_order: bool,
+}
+
+impl NearSpanFilter {
+    fn compute_ordered_spans(&self, spans: &mut Vec<Span>) {
+        let (first_spans, other_clause_spans) = self.clause_spans.split_first().unwrap();
+        'first_spans: for first_span in first_spans {
+            let mut end = first_span.end;
+            let mut width = first_span.width();
+            let mut slop = first_span.slop;
+            for clause_spans in other_clause_spans {
+                match clause_spans.iter().find(|span| span.start >= end) {
+                    Some(span) => {
+                        end = span.end;
+                        width += span.width();
+                        slop += span.slop;
+                    }
+                    None => continue 'first_spans,
+                }
+            }
+            let gaps = end - first_span.start - width;
+            if gaps <= self.slop {
+                spans.push(Span {
+                    start: first_span.start,
+                    end,
+                    slop: slop + gaps,
+                });
+            }
+        }
+    }
+
+    /// Slides a window over the spans of the clauses, by moving forward
+    /// the clause whose current span starts first.
+    fn compute_unordered_spans(&self, spans: &mut Vec<Span>) {
+        let mut cursors = vec![0; self.clause_spans.len()];
+        let mut window: Vec<Span> = Vec::with_capacity(cursors.len());
+        loop {
+            window.clear();
+            window.extend(
+                cursors
+                    .iter()
+                    .zip(self.clause_spans.iter())
+                    .map(|(&cursor, clause_spans)| clause_spans[cursor]),
+            );
+            let (first_ord, first_span) = window
+                .iter()
+                .enumerate()
+                .min_by_key(|&(_, span)| span.start)
+                .map(|(ord, &span)| (ord, span))
+                .unwrap();
+            window.sort();
+            let overlaps = window.windows(2).any(|pair| pair[1].start < pair[0].end);
+            if !overlaps {
+                let end = window.iter().map(|span| span.end).max().unwrap();
+                let width: u32 = window.iter().map(Span::width).sum();
+                let slop: u32 = window.iter().map(|span| span.slop).sum();
+                let gaps = end - first_span.start - width;
+                if gaps <= self.slop {
+                    spans.push(Span {
+                        start: first_span.start,
+                        end,
+                        slop: slop + gaps,
+                    });
+                }
+            }
+            cursors[first_ord] += 1;
+            if cursors[first_ord] == self.clause_spans[first_ord].len() {
+                break;
+            }
+        }
+    }
+}
+
+impl DocSet for NearSpanFilter {
+    fn advance(&mut self) -> bool {
+        self.intersection.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.intersection.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.intersection.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.intersection.size_hint()
+    }
+}
+
+impl SpanFilter for NearSpanFilter {
+    fn compute_spans(&mut self, spans: &mut Vec<Span>) {
+        for i in 0..self.clause_spans.len() {
+            let ord_span_scorer = self.intersection.docset_mut_specialized(i);
+            let clause_spans = &mut self.clause_spans[ord_span_scorer.ord];
+            clause_spans.clear();
+            clause_spans.extend_from_slice(ord_span_scorer.span_scorer.spans());
+        }
+        if self.in_order {
+            self.compute_ordered_spans(spans);
+        } else {
+            self.compute_unordered_spans(spans);
+        }
+        spans.sort();
+        spans.dedup();
+    }
+}
@@ -0,0 +1,189 @@
+use super::span_weight::span_query_weight;
+use super::{FilteredSpanScorer, Span, SpanFilter, SpanQuery, SpanScorer, SpanWeight};
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::query::{Query, Weight};
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+use crate::Term;
+use std::collections::BTreeSet;
+
+/// `SpanNotQuery` matches the spans of a query that are not close to
+/// the spans of another query.
+///
+/// A span of `include` is removed if a span of `exclude` overlaps it,
+/// or ends less than `pre` positions before it, or starts less than
+/// `post` positions after it.
+///
+/// # Panics
+///
+/// Creating a `SpanNotQuery` panics if both queries do not belong to the same field.
+#[derive(Debug)]
+pub struct SpanNotQuery {
+    include: Box<dyn SpanQuery>,
+    exclude: Box<dyn SpanQuery>,
+    pre: u32,
+    post: u32,
+}
+
+impl SpanNotQuery {
+    /// Creates a query matching the spans of `include` that are not
+    /// within the `pre` positions before or the `post` positions after
+    /// a span of `exclude`.
+    pub fn new(
+        include: Box<dyn SpanQuery>,
+        exclude: Box<dyn SpanQuery>,
+        pre: u32,
+        post: u32,
+    ) -> SpanNotQuery {
+        assert_eq!(
+            include.field(),
+            exclude.field(),
+            "All span clauses must belong to the same field."
+        );
+        SpanNotQuery {
+            include,
+            exclude,
+            pre,
+            post,
+        }
+    }
+
+    /// Returns the query whose spans are matched.
+    pub fn include(&self) -> &dyn SpanQuery {
+        self.include.as_ref()
+    }
+
+    /// Returns the query whose spans are excluded.
+    pub fn exclude(&self) -> &dyn SpanQuery {
+        self.exclude.as_ref()
+    }
+}
+
+impl Clone for SpanNotQuery {
+    fn clone(&self) -> Self {
+        SpanNotQuery {
+            include: self.include.box_clone_span(),
+            exclude: self.exclude.box_clone_span(),
+            pre: self.pre,
+            post: self.post,
+        }
+    }
+}
+
+impl Query for SpanNotQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        span_query_weight(self, searcher, scoring_enabled)
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.include.query_terms(term_set);
+    }
+}
+
+impl SpanQuery for SpanNotQuery {
+    fn field(&self) -> Field {
+        self.include.field()
+    }
+
+    fn span_weight(&self, searcher: &Searcher) -> Result<Box<dyn SpanWeight>> {
+        Ok(Box::new(SpanNotWeight {
+            include: self.include.span_weight(searcher)?,
+            exclude: self.exclude.span_weight(searcher)?,
+            pre: self.pre,
+            post: self.post,
+        }))
+    }
+}
+
+struct SpanNotWeight {
+    include: Box<dyn SpanWeight>,
+    exclude: Box<dyn SpanWeight>,
+    pre: u32,
+    post: u32,
+}
+
+impl SpanWeight for SpanNotWeight {
+    fn span_scorer(&self, reader: &SegmentReader) -> Result<Box<dyn SpanScorer>> {
+        Ok(Box::new(FilteredSpanScorer::new(NotSpanFilter {
+            include: self.include.span_scorer(reader)?,
+            exclude: self.exclude.span_scorer(reader)?,
+            exclude_state: ExcludeState::NotStarted,
+            pre: self.pre,
+            post: self.post,
+        })))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ExcludeState {
+    NotStarted,
+    Started,
+    Ended,
+}
+
+struct NotSpanFilter {
+    include: Box<dyn SpanScorer>,
+    exclude: Box<dyn SpanScorer>,
+    exclude_state: ExcludeState,
+    pre: u32,
+    post: u32,
+}
+
+impl NotSpanFilter {
+    /// Moves the exclude scorer to the first document greater or equal
+    /// to `doc`, and returns true if it contains `doc`.
+    fn exclude_contains(&mut self, doc: DocId) -> bool {
+        if self.exclude_state == ExcludeState::NotStarted {
+            self.exclude_state = if self.exclude.advance() {
+                ExcludeState::Started
+            } else {
+                ExcludeState::Ended
+            };
+        }
+        if self.exclude_state == ExcludeState::Ended {
+            return false;
+        }
+        if self.exclude.doc() < doc && self.exclude.skip_next(doc) == SkipResult::End {
+            self.exclude_state = ExcludeState::Ended;
+            return false;
+        }
+        self.exclude.doc() == doc
+    }
+}
+
+impl DocSet for NotSpanFilter {
+    fn advance(&mut self) -> bool {
+        self.include.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.include.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.include.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.include.size_hint()
+    }
+}
+
+impl SpanFilter for NotSpanFilter {
+    fn compute_spans(&mut self, spans: &mut Vec<Span>) {
+        let doc = self.include.doc();
+        if !self.exclude_contains(doc) {
+            spans.extend_from_slice(self.include.spans());
+            return;
+        }
+        let (pre, post) = (self.pre, self.post);
+        let exclude_spans = self.exclude.spans();
+        spans.extend(self.include.spans().iter().cloned().filter(|span| {
+            !exclude_spans.iter().any(|exclude_span| {
+                exclude_span.end + pre > span.start && exclude_span.start < span.end + post
+            })
+        }));
+    }
+}
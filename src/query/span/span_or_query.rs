@@ -0,0 +1,198 @@
+use super::span_weight::span_query_weight;
+use super::{assert_same_field, Span, SpanQuery, SpanScorer, SpanWeight};
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::query::{Query, Weight};
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+use crate::Term;
+use std::collections::BTreeSet;
+
+/// `SpanOrQuery` matches the spans of any of its clauses.
+///
+/// # Panics
+///
+/// Creating a `SpanOrQuery` panics if there is no clause, or if the
+/// clauses do not belong to the same field.
+#[derive(Debug)]
+pub struct SpanOrQuery {
+    clauses: Vec<Box<dyn SpanQuery>>,
+}
+
+impl SpanOrQuery {
+    /// Creates a query matching the spans of any of the `clauses`.
+    pub fn new(clauses: Vec<Box<dyn SpanQuery>>) -> SpanOrQuery {
+        assert!(
+            !clauses.is_empty(),
+            "A span or query requires at least one clause."
+        );
+        assert_same_field(clauses[0].field(), &clauses);
+        SpanOrQuery { clauses }
+    }
+
+    /// Returns the clauses of the query.
+    pub fn clauses(&self) -> &[Box<dyn SpanQuery>] {
+        &self.clauses
+    }
+}
+
+impl Clone for SpanOrQuery {
+    fn clone(&self) -> Self {
+        SpanOrQuery {
+            clauses: self
+                .clauses
+                .iter()
+                .map(|clause| clause.box_clone_span())
+                .collect(),
+        }
+    }
+}
+
+impl Query for SpanOrQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        span_query_weight(self, searcher, scoring_enabled)
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        for clause in &self.clauses {
+            clause.query_terms(term_set);
+        }
+    }
+}
+
+impl SpanQuery for SpanOrQuery {
+    fn field(&self) -> Field {
+        self.clauses[0].field()
+    }
+
+    fn span_weight(&self, searcher: &Searcher) -> Result<Box<dyn SpanWeight>> {
+        let span_weights = self
+            .clauses
+            .iter()
+            .map(|clause| clause.span_weight(searcher))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Box::new(SpanOrWeight { span_weights }))
+    }
+}
+
+struct SpanOrWeight {
+    span_weights: Vec<Box<dyn SpanWeight>>,
+}
+
+impl SpanWeight for SpanOrWeight {
+    fn span_scorer(&self, reader: &SegmentReader) -> Result<Box<dyn SpanScorer>> {
+        if self.span_weights.len() == 1 {
+            return self.span_weights[0].span_scorer(reader);
+        }
+        let span_scorers = self
+            .span_weights
+            .iter()
+            .map(|span_weight| span_weight.span_scorer(reader))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Box::new(SpanOrScorer {
+            span_scorers,
+            started: false,
+            doc: 0,
+            spans: Vec::new(),
+        }))
+    }
+}
+
+/// Disjunction of span scorers, merging the spans of the scorers
+/// positioned on the current document.
+///
+/// The exhausted scorers are removed.
+struct SpanOrScorer {
+    span_scorers: Vec<Box<dyn SpanScorer>>,
+    started: bool,
+    doc: DocId,
+    spans: Vec<Span>,
+}
+
+impl SpanOrScorer {
+    fn update_doc(&mut self) -> bool {
+        let doc = match self.span_scorers.iter().map(|scorer| scorer.doc()).min() {
+            Some(doc) => doc,
+            None => return false,
+        };
+        self.doc = doc;
+        self.spans.clear();
+        for span_scorer in &mut self.span_scorers {
+            if span_scorer.doc() == doc {
+                self.spans.extend_from_slice(span_scorer.spans());
+            }
+        }
+        self.spans.sort();
+        self.spans.dedup();
+        true
+    }
+}
+
+impl DocSet for SpanOrScorer {
+    fn advance(&mut self) -> bool {
+        let mut i = 0;
+        while i < self.span_scorers.len() {
+            let span_scorer = &mut self.span_scorers[i];
+            if (self.started && span_scorer.doc() != self.doc) || span_scorer.advance() {
+                i += 1;
+            } else {
+                self.span_scorers.swap_remove(i);
+            }
+        }
+        self.started = true;
+        self.update_doc()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        if self.started && self.doc >= target {
+            return if self.advance() {
+                SkipResult::OverStep
+            } else {
+                SkipResult::End
+            };
+        }
+        let mut i = 0;
+        while i < self.span_scorers.len() {
+            let span_scorer = &mut self.span_scorers[i];
+            let exhausted = if !self.started && !span_scorer.advance() {
+                true
+            } else if span_scorer.doc() < target {
+                span_scorer.skip_next(target) == SkipResult::End
+            } else {
+                false
+            };
+            if exhausted {
+                self.span_scorers.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        self.started = true;
+        if !self.update_doc() {
+            SkipResult::End
+        } else if self.doc == target {
+            SkipResult::Reached
+        } else {
+            SkipResult::OverStep
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.span_scorers
+            .iter()
+            .map(|span_scorer| span_scorer.size_hint())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl SpanScorer for SpanOrScorer {
+    fn spans(&mut self) -> &[Span] {
+        &self.spans
+    }
+}
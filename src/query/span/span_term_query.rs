@@ -0,0 +1,120 @@
+use super::span_weight::span_query_weight;
+use super::{Span, SpanQuery, SpanScorer, SpanWeight};
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::postings::{Postings, SegmentPostings};
+use crate::query::{Query, Weight};
+use crate::schema::{Field, IndexRecordOption};
+use crate::DocId;
+use crate::Result;
+use crate::Term;
+use std::collections::BTreeSet;
+
+/// `SpanTermQuery` matches the positions of a term.
+///
+/// It is the building block of the other span queries.
+/// The field of the term must have its positions indexed.
+#[derive(Clone, Debug)]
+pub struct SpanTermQuery {
+    term: Term,
+}
+
+impl SpanTermQuery {
+    /// Creates a span query matching the positions of `term`.
+    pub fn new(term: Term) -> SpanTermQuery {
+        SpanTermQuery { term }
+    }
+
+    /// Returns the term of the query.
+    pub fn term(&self) -> &Term {
+        &self.term
+    }
+}
+
+impl Query for SpanTermQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        span_query_weight(self, searcher, scoring_enabled)
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        term_set.insert(self.term.clone());
+    }
+}
+
+impl SpanQuery for SpanTermQuery {
+    fn field(&self) -> Field {
+        self.term.field()
+    }
+
+    fn span_weight(&self, _searcher: &Searcher) -> Result<Box<dyn SpanWeight>> {
+        Ok(Box::new(SpanTermWeight {
+            term: self.term.clone(),
+        }))
+    }
+}
+
+struct SpanTermWeight {
+    term: Term,
+}
+
+impl SpanWeight for SpanTermWeight {
+    fn span_scorer(&self, reader: &SegmentReader) -> Result<Box<dyn SpanScorer>> {
+        let postings = reader
+            .inverted_index(self.term.field())
+            .read_postings(&self.term, IndexRecordOption::WithFreqsAndPositions)
+            .unwrap_or_else(SegmentPostings::empty);
+        Ok(Box::new(SpanTermScorer {
+            postings,
+            positions: Vec::new(),
+            spans: Vec::new(),
+            spans_loaded: false,
+        }))
+    }
+}
+
+/// Span scorer emitting the positions of a term.
+///
+/// Positions are only decoded when the spans are requested.
+struct SpanTermScorer {
+    postings: SegmentPostings,
+    positions: Vec<u32>,
+    spans: Vec<Span>,
+    spans_loaded: bool,
+}
+
+impl DocSet for SpanTermScorer {
+    fn advance(&mut self) -> bool {
+        self.spans_loaded = false;
+        self.postings.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.spans_loaded = false;
+        self.postings.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.postings.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.postings.size_hint()
+    }
+}
+
+impl SpanScorer for SpanTermScorer {
+    fn spans(&mut self) -> &[Span] {
+        if !self.spans_loaded {
+            self.postings.positions(&mut self.positions);
+            self.spans.clear();
+            self.spans
+                .extend(self.positions.iter().map(|&position| Span {
+                    start: position,
+                    end: position + 1,
+                    slop: 0,
+                }));
+            self.spans_loaded = true;
+        }
+        &self.spans
+    }
+}
@@ -0,0 +1,280 @@
+use super::{PayloadFunction, Span, SpanQuery, SpanScorer, SpanWeight};
+use crate::core::{Searcher, SegmentReader};
+use crate::docset::{DocSet, SkipResult};
+use crate::fastfield::FastFieldReader;
+use crate::fieldnorm::FieldNormReader;
+use crate::postings::{Postings, SegmentPostings};
+use crate::query::explanation::does_not_match;
+use crate::query::similarity::similarity_weight;
+use crate::query::{Explanation, Scorer, SimilarityWeight, Weight};
+use crate::schema::{Field, IndexRecordOption};
+use crate::DocId;
+use crate::Result;
+use crate::Score;
+use crate::TantivyError;
+use crate::Term;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Creates the `Weight` of a span query, scoring its documents
+/// with the similarity of its field.
+pub(crate) fn span_query_weight(
+    query: &dyn SpanQuery,
+    searcher: &Searcher,
+    scoring_enabled: bool,
+) -> Result<Box<dyn Weight>> {
+    span_query_weight_with_payloads(query, searcher, scoring_enabled, None)
+}
+
+/// Same as `span_query_weight`, the score of the documents being also multiplied
+/// by the payloads of the terms within their spans, combined by `payload_function`.
+pub(crate) fn span_query_weight_with_payloads(
+    query: &dyn SpanQuery,
+    searcher: &Searcher,
+    scoring_enabled: bool,
+    payload_function: Option<PayloadFunction>,
+) -> Result<Box<dyn Weight>> {
+    let field = query.field();
+    let field_entry = searcher.schema().get_field_entry(field);
+    let has_positions = field_entry
+        .field_type()
+        .get_index_record_option()
+        .map(IndexRecordOption::has_positions)
+        .unwrap_or(false);
+    if !has_positions {
+        return Err(TantivyError::SchemaError(format!(
+            "Applied span query on field {:?}, which does not have positions indexed",
+            field_entry.name()
+        )));
+    }
+    if payload_function.is_some() && !field_entry.field_type().has_payloads() {
+        return Err(TantivyError::SchemaError(format!(
+            "Applied payload score query on field {:?}, which does not have payloads indexed",
+            field_entry.name()
+        )));
+    }
+    let mut term_set = BTreeSet::new();
+    query.query_terms(&mut term_set);
+    let terms: Vec<Term> = term_set.into_iter().collect();
+    let similarity_weight = if scoring_enabled && !terms.is_empty() {
+        Some(similarity_weight(searcher, &terms))
+    } else {
+        None
+    };
+    let payload_function = if scoring_enabled {
+        payload_function
+    } else {
+        None
+    };
+    Ok(Box::new(SpanScoringWeight {
+        field,
+        span_weight: query.span_weight(searcher)?,
+        similarity_weight,
+        payload_function,
+        terms,
+    }))
+}
+
+/// `Weight` scoring the documents matched by a span query.
+///
+/// Each span contributes `1 / (1 + slop)` to the frequency given to the
+/// similarity of the field, like the matches of a sloppy phrase query.
+pub struct SpanScoringWeight {
+    field: Field,
+    span_weight: Box<dyn SpanWeight>,
+    similarity_weight: Option<Arc<dyn SimilarityWeight>>,
+    payload_function: Option<PayloadFunction>,
+    terms: Vec<Term>,
+}
+
+impl SpanScoringWeight {
+    fn span_scoring_scorer(&self, reader: &SegmentReader) -> Result<SpanScoringScorer> {
+        let payload_scorer = self.payload_function.map(|payload_function| {
+            let inverted_index = reader.inverted_index(self.field);
+            let term_payloads = self
+                .terms
+                .iter()
+                .flat_map(|term| {
+                    inverted_index.read_postings(term, IndexRecordOption::WithFreqsAndPositions)
+                })
+                .map(TermPayloads::new)
+                .collect();
+            PayloadScorer {
+                payload_function,
+                term_payloads,
+                positions: Vec::new(),
+                payloads: Vec::new(),
+                weights: Vec::new(),
+            }
+        });
+        Ok(SpanScoringScorer {
+            span_scorer: self.span_weight.span_scorer(reader)?,
+            fieldnorm_reader: reader.get_fieldnorms_reader(self.field),
+            similarity_weight: self.similarity_weight.clone(),
+            boost_reader: reader.boost_reader().cloned(),
+            payload_scorer,
+        })
+    }
+}
+
+impl Weight for SpanScoringWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        Ok(Box::new(self.span_scoring_scorer(reader)?))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.span_scoring_scorer(reader)?;
+        if scorer.skip_next(doc) != SkipResult::Reached {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = Explanation::new("Span Scorer", scorer.score());
+        if let Some(boost) = scorer.boost() {
+            explanation.add_const("Static boost", boost);
+        }
+        explanation.add_const("Number of spans", scorer.span_scorer.spans().len() as f32);
+        if let Some(payload_factor) = scorer.payload_factor() {
+            explanation.add_const("Payload factor", payload_factor);
+        }
+        if let Some(similarity_weight) = self.similarity_weight.as_ref() {
+            let span_freq = scorer.span_freq();
+            explanation.add_detail(similarity_weight.explain(
+                &scorer.fieldnorm_reader,
+                doc,
+                span_freq,
+            ));
+        }
+        Ok(explanation)
+    }
+}
+
+/// Postings of a term, read to get the payloads of its positions.
+struct TermPayloads {
+    postings: SegmentPostings,
+    is_started: bool,
+    is_terminated: bool,
+}
+
+impl TermPayloads {
+    fn new(postings: SegmentPostings) -> TermPayloads {
+        TermPayloads {
+            postings,
+            is_started: false,
+            is_terminated: false,
+        }
+    }
+
+    /// Reads the positions and the payloads of the term in `doc`,
+    /// returning false if the term is not in `doc`.
+    ///
+    /// The documents must be given in increasing order.
+    fn read(&mut self, doc: DocId, positions: &mut Vec<u32>, payloads: &mut Vec<u32>) -> bool {
+        if self.is_terminated {
+            return false;
+        }
+        if !self.is_started || self.postings.doc() < doc {
+            self.is_started = true;
+            if self.postings.skip_next(doc) == SkipResult::End {
+                self.is_terminated = true;
+                return false;
+            }
+        }
+        if self.postings.doc() != doc {
+            return false;
+        }
+        self.postings.positions(positions);
+        self.postings.payloads(payloads);
+        true
+    }
+}
+
+/// Computes the factor applied to the score of a document,
+/// from the payloads of the terms within its spans.
+struct PayloadScorer {
+    payload_function: PayloadFunction,
+    term_payloads: Vec<TermPayloads>,
+    positions: Vec<u32>,
+    payloads: Vec<u32>,
+    weights: Vec<f32>,
+}
+
+impl PayloadScorer {
+    fn payload_factor(&mut self, doc: DocId, spans: &[Span]) -> f32 {
+        self.weights.clear();
+        for term_payloads in &mut self.term_payloads {
+            if !term_payloads.read(doc, &mut self.positions, &mut self.payloads) {
+                continue;
+            }
+            for (&position, &payload) in self.positions.iter().zip(self.payloads.iter()) {
+                let is_in_span = spans
+                    .iter()
+                    .any(|span| span.start <= position && position < span.end);
+                if payload != 0 && is_in_span {
+                    self.weights.push(f32::from_bits(payload));
+                }
+            }
+        }
+        self.payload_function.combine(&self.weights)
+    }
+}
+
+struct SpanScoringScorer {
+    span_scorer: Box<dyn SpanScorer>,
+    fieldnorm_reader: FieldNormReader,
+    similarity_weight: Option<Arc<dyn SimilarityWeight>>,
+    boost_reader: Option<FastFieldReader<f64>>,
+    payload_scorer: Option<PayloadScorer>,
+}
+
+impl SpanScoringScorer {
+    fn span_freq(&mut self) -> f32 {
+        self.span_scorer
+            .spans()
+            .iter()
+            .map(|span| 1.0 / (1.0 + span.slop as f32))
+            .sum()
+    }
+
+    fn payload_factor(&mut self) -> Option<Score> {
+        let doc = self.span_scorer.doc();
+        let spans = self.span_scorer.spans();
+        self.payload_scorer
+            .as_mut()
+            .map(|payload_scorer| payload_scorer.payload_factor(doc, spans))
+    }
+
+    fn boost(&self) -> Option<Score> {
+        self.boost_reader
+            .as_ref()
+            .map(|boost_reader| boost_reader.get(self.doc()) as Score)
+    }
+}
+
+impl DocSet for SpanScoringScorer {
+    fn advance(&mut self) -> bool {
+        self.span_scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.span_scorer.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.span_scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.span_scorer.size_hint()
+    }
+}
+
+impl Scorer for SpanScoringScorer {
+    fn score(&mut self) -> Score {
+        let similarity_weight = match self.similarity_weight.clone() {
+            Some(similarity_weight) => similarity_weight,
+            None => return 1.0,
+        };
+        let span_freq = self.span_freq();
+        let score = similarity_weight.score(&self.fieldnorm_reader, self.doc(), span_freq);
+        score * self.payload_factor().unwrap_or(1.0) * self.boost().unwrap_or(1.0)
+    }
+}
@@ -10,11 +10,14 @@ pub use self::term_weight::TermWeight;
 mod tests {
 
     use crate::collector::TopDocs;
+    use crate::directory::RAMDirectory;
     use crate::docset::DocSet;
     use crate::query::{Query, QueryParser, Scorer, TermQuery};
-    use crate::schema::{Field, IndexRecordOption, Schema, STRING, TEXT};
+    use crate::schema::{Field, IndexRecordOption, Schema, FAST, STRING, TEXT};
     use crate::tests::assert_nearly_equals;
+    use crate::DocId;
     use crate::Index;
+    use crate::IndexSettings;
     use crate::Term;
 
     #[test]
@@ -98,6 +101,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_term_query_static_boost() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let boost_field = schema_builder.add_f64_field("boost", FAST);
+        let settings = IndexSettings {
+            boost_field: Some("boost".to_string()),
+            ..IndexSettings::default()
+        };
+        let index =
+            Index::create_with_settings(RAMDirectory::create(), schema_builder.build(), settings)
+                .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a b", boost_field => 3.0f64));
+        index_writer.add_document(doc!(text_field => "a b"));
+        index_writer.add_document(doc!(text_field => "a b", boost_field => 0.5f64));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        for query_str in &["a", "a b", "\"a b\""] {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(3)).unwrap();
+            let docs: Vec<DocId> = top_docs.iter().map(|(_, doc)| doc.doc()).collect();
+            assert_eq!(docs, vec![0, 1, 2]);
+            let (unboosted_score, _) = top_docs[1];
+            assert_nearly_equals(3.0 * unboosted_score, top_docs[0].0);
+            assert_nearly_equals(0.5 * unboosted_score, top_docs[2].0);
+            let explanation = query.explain(&searcher, top_docs[0].1).unwrap();
+            assert_nearly_equals(top_docs[0].0, explanation.value());
+        }
+    }
+
     #[test]
     fn test_term_query_count_when_there_are_deletes() {
         let mut schema_builder = Schema::builder();
@@ -1,5 +1,5 @@
 use super::term_weight::TermWeight;
-use crate::query::bm25::BM25Weight;
+use crate::query::similarity::similarity_weight;
 use crate::query::Query;
 use crate::query::Weight;
 use crate::schema::IndexRecordOption;
@@ -93,13 +93,13 @@ impl TermQuery {
     /// This is useful for optimization purpose.
     pub fn specialized_weight(&self, searcher: &Searcher, scoring_enabled: bool) -> TermWeight {
         let term = self.term.clone();
-        let bm25_weight = BM25Weight::for_terms(searcher, &[term]);
+        let similarity_weight = similarity_weight(searcher, &[term]);
         let index_record_option = if scoring_enabled {
             self.index_record_option
         } else {
             IndexRecordOption::Basic
         };
-        TermWeight::new(self.term.clone(), index_record_option, bm25_weight)
+        TermWeight::new(self.term.clone(), index_record_option, similarity_weight)
     }
 }
 
@@ -110,4 +110,11 @@ impl Query for TermQuery {
     fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
         term_set.insert(self.term.clone());
     }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "TermQuery({:?}, {:?})",
+            self.term, self.index_record_option
+        ))
+    }
 }
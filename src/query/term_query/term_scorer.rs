@@ -3,27 +3,32 @@ use crate::query::{Explanation, Scorer};
 use crate::DocId;
 use crate::Score;
 
+use crate::fastfield::FastFieldReader;
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::Postings;
 use crate::postings::SegmentPostings;
-use crate::query::bm25::BM25Weight;
+use crate::query::SimilarityWeight;
+use std::sync::Arc;
 
 pub struct TermScorer {
     postings: SegmentPostings,
     fieldnorm_reader: FieldNormReader,
-    similarity_weight: BM25Weight,
+    similarity_weight: Arc<dyn SimilarityWeight>,
+    boost_reader: Option<FastFieldReader<f64>>,
 }
 
 impl TermScorer {
     pub fn new(
         postings: SegmentPostings,
         fieldnorm_reader: FieldNormReader,
-        similarity_weight: BM25Weight,
+        similarity_weight: Arc<dyn SimilarityWeight>,
+        boost_reader: Option<FastFieldReader<f64>>,
     ) -> TermScorer {
         TermScorer {
             postings,
             fieldnorm_reader,
             similarity_weight,
+            boost_reader,
         }
     }
 }
@@ -33,14 +38,51 @@ impl TermScorer {
         self.postings.term_freq()
     }
 
-    pub fn fieldnorm_id(&self) -> u8 {
-        self.fieldnorm_reader.fieldnorm_id(self.doc())
+    /// Returns true iff the scores are multiplied by a static boost.
+    ///
+    /// The upper bounds of the score do not account for the static boost.
+    pub fn has_static_boost(&self) -> bool {
+        self.boost_reader.is_some()
+    }
+
+    /// Returns an upper bound of the score of the documents.
+    pub fn max_score(&self) -> Score {
+        self.similarity_weight.max_score()
+    }
+
+    /// Returns an upper bound of the score of the documents of the block
+    /// containing the current document.
+    pub fn block_max_score(&self) -> Score {
+        match self.postings.block_max_term_freq() {
+            Some(block_max_term_freq) => self
+                .similarity_weight
+                .max_score_for_term_freq(block_max_term_freq),
+            None => self.max_score(),
+        }
+    }
+
+    /// Returns the last document of the block containing the current document.
+    pub fn block_last_doc(&self) -> DocId {
+        self.postings.block_last_doc()
     }
 
     pub fn explain(&self) -> Explanation {
-        let fieldnorm_id = self.fieldnorm_id();
-        let term_freq = self.term_freq();
-        self.similarity_weight.explain(fieldnorm_id, term_freq)
+        let term_freq = self.term_freq() as f32;
+        let similarity_explanation =
+            self.similarity_weight
+                .explain(&self.fieldnorm_reader, self.doc(), term_freq);
+        if let Some(boost_reader) = self.boost_reader.as_ref() {
+            let boost = boost_reader.get(self.doc()) as Score;
+            let mut explanation = Explanation::new(
+                "Static boost * similarity",
+                boost * similarity_explanation.value(),
+            );
+            explanation.add_const("Static boost", boost);
+            explanation.add_detail(similarity_explanation);
+            explanation
+        } else {
+            similarity_explanation
+        }
     }
 }
 
@@ -64,8 +106,14 @@ impl DocSet for TermScorer {
 
 impl Scorer for TermScorer {
     fn score(&mut self) -> Score {
-        let fieldnorm_id = self.fieldnorm_id();
-        let term_freq = self.term_freq();
-        self.similarity_weight.score(fieldnorm_id, term_freq)
+        let term_freq = self.term_freq() as f32;
+        let score = self
+            .similarity_weight
+            .score(&self.fieldnorm_reader, self.doc(), term_freq);
+        if let Some(boost_reader) = self.boost_reader.as_ref() {
+            score * boost_reader.get(self.doc()) as Score
+        } else {
+            score
+        }
     }
 }
@@ -2,19 +2,20 @@ use super::term_scorer::TermScorer;
 use crate::core::SegmentReader;
 use crate::docset::DocSet;
 use crate::postings::SegmentPostings;
-use crate::query::bm25::BM25Weight;
 use crate::query::explanation::does_not_match;
+use crate::query::SimilarityWeight;
 use crate::query::Weight;
 use crate::query::{Explanation, Scorer};
 use crate::schema::IndexRecordOption;
 use crate::DocId;
 use crate::Term;
 use crate::{Result, SkipResult};
+use std::sync::Arc;
 
 pub struct TermWeight {
     term: Term,
     index_record_option: IndexRecordOption,
-    similarity_weight: BM25Weight,
+    similarity_weight: Arc<dyn SimilarityWeight>,
 }
 
 impl Weight for TermWeight {
@@ -49,7 +50,7 @@ impl TermWeight {
     pub fn new(
         term: Term,
         index_record_option: IndexRecordOption,
-        similarity_weight: BM25Weight,
+        similarity_weight: Arc<dyn SimilarityWeight>,
     ) -> TermWeight {
         TermWeight {
             term,
@@ -63,6 +64,7 @@ impl TermWeight {
         let inverted_index = reader.inverted_index(field);
         let fieldnorm_reader = reader.get_fieldnorms_reader(field);
         let similarity_weight = self.similarity_weight.clone();
+        let boost_reader = reader.boost_reader().cloned();
         let postings_opt: Option<SegmentPostings> =
             inverted_index.read_postings(&self.term, self.index_record_option);
         if let Some(segment_postings) = postings_opt {
@@ -70,12 +72,14 @@ impl TermWeight {
                 segment_postings,
                 fieldnorm_reader,
                 similarity_weight,
+                boost_reader,
             ))
         } else {
             Ok(TermScorer::new(
                 SegmentPostings::empty(),
                 fieldnorm_reader,
                 similarity_weight,
+                boost_reader,
             ))
         }
     }
@@ -0,0 +1,262 @@
+use crate::common::BitSet;
+use crate::core::Searcher;
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, SkipResult};
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    AutomatonWeight, BitSetDocSet, ConstScorer, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::Field;
+use crate::DocId;
+use crate::Result;
+use crate::Term;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+use tantivy_fst::raw::{Builder, CompiledAddr, Fst};
+use tantivy_fst::Automaton;
+
+/// An automaton accepting a sorted set of terms, compiled into an FST.
+///
+/// Searching the term dictionary with it intersects the two FSTs:
+/// the term dictionary is walked once, along the prefixes shared with
+/// the set of terms.
+struct TermSetAutomaton {
+    fst: Fst,
+}
+
+impl TermSetAutomaton {
+    fn new(sorted_terms: &[&[u8]]) -> TermSetAutomaton {
+        let mut builder = Builder::memory();
+        for term in sorted_terms {
+            builder
+                .add(term)
+                .expect("Terms are sorted and deduplicated");
+        }
+        let fst_bytes = builder.into_inner().expect("Building an FST in memory");
+        TermSetAutomaton {
+            fst: Fst::new(fst_bytes).expect("The FST was just built"),
+        }
+    }
+}
+
+impl Automaton for TermSetAutomaton {
+    type State = Option<CompiledAddr>;
+
+    fn start(&self) -> Self::State {
+        Some(self.fst.root().addr())
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        match *state {
+            Some(addr) => self.fst.node(addr).is_final(),
+            None => false,
+        }
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let node = self.fst.node((*state)?);
+        node.find_input(byte)
+            .map(|transition| node.transition_addr(transition))
+    }
+}
+
+/// `TermSetQuery` matches the documents containing any of the given terms.
+///
+/// It is equivalent to a [`BooleanQuery`](./struct.BooleanQuery.html) of
+/// `Should` [`TermQuery`](./struct.TermQuery.html) clauses, but it can handle
+/// thousands of terms efficiently: the terms of each field are compiled
+/// into an FST, which is intersected with the term dictionary of each segment.
+/// This makes it the right tool to filter documents by a list of ids.
+///
+/// Like the [`RangeQuery`](./struct.RangeQuery.html), it does not score
+/// the documents: all of them get a score of `1`.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::TermSetQuery;
+/// use tantivy::schema::{Schema, STRING};
+/// use tantivy::{doc, Index, Result, Term};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = Schema::builder();
+///     let owner = schema_builder.add_text_field("owner", STRING);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer(3_000_000)?;
+///         for user_id in 0..100 {
+///             index_writer.add_document(doc!(owner => format!("user{}", user_id)));
+///         }
+///         index_writer.commit()?;
+///     }
+///     let searcher = index.reader()?.searcher();
+///
+///     let allowed_users = (0..1_000).step_by(10).map(|user_id| format!("user{}", user_id));
+///     let query = TermSetQuery::new(
+///         allowed_users.map(|user| Term::from_field_text(owner, &user)),
+///     );
+///     assert_eq!(searcher.search(&query, &Count)?, 10);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TermSetQuery {
+    terms: Arc<BTreeSet<Term>>,
+}
+
+impl TermSetQuery {
+    /// Creates a query matching the documents containing any of `terms`.
+    ///
+    /// The terms may belong to different fields.
+    pub fn new<TTerms: IntoIterator<Item = Term>>(terms: TTerms) -> TermSetQuery {
+        TermSetQuery {
+            terms: Arc::new(terms.into_iter().collect()),
+        }
+    }
+
+    /// Returns the number of distinct terms of the query.
+    pub fn num_terms(&self) -> usize {
+        self.terms.len()
+    }
+
+    fn field_terms(&self) -> Vec<(Field, Vec<&[u8]>)> {
+        // Terms are sorted by field, and then by value.
+        let mut field_terms: Vec<(Field, Vec<&[u8]>)> = Vec::new();
+        for term in self.terms.iter() {
+            match field_terms.last_mut() {
+                Some((field, values)) if *field == term.field() => {
+                    values.push(term.value_bytes());
+                }
+                _ => {
+                    field_terms.push((term.field(), vec![term.value_bytes()]));
+                }
+            }
+        }
+        field_terms
+    }
+}
+
+impl fmt::Debug for TermSetQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TermSetQuery(num_terms={})", self.terms.len())
+    }
+}
+
+impl Query for TermSetQuery {
+    fn weight(&self, _searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<dyn Weight>> {
+        let field_weights = self
+            .field_terms()
+            .into_iter()
+            .map(|(field, values)| AutomatonWeight::new(field, TermSetAutomaton::new(&values)))
+            .collect();
+        Ok(Box::new(TermSetWeight { field_weights }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        term_set.extend(self.terms.iter().cloned());
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!("TermSetQuery({:?})", self.terms))
+    }
+}
+
+struct TermSetWeight {
+    field_weights: Vec<AutomatonWeight<TermSetAutomaton>>,
+}
+
+impl Weight for TermSetWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<dyn Scorer>> {
+        if self.field_weights.len() == 1 {
+            return self.field_weights[0].scorer(reader);
+        }
+        let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+        for field_weight in &self.field_weights {
+            field_weight
+                .scorer(reader)?
+                .append_to_bitset(&mut doc_bitset);
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation> {
+        let mut scorer = self.scorer(reader)?;
+        if scorer.skip_next(doc) != SkipResult::Reached {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("TermSetQuery", 1.0f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TermSetQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{BooleanQuery, Query};
+    use crate::schema::{Schema, STRING};
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_term_set_query() {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        for i in 0..1_000 {
+            index_writer.add_document(doc!(id => format!("{}", i), tag => format!("tag{}", i % 7)));
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        // Many of the terms are absent from the index, and some are prefixes of one another.
+        let terms: Vec<Term> = (0..10_000)
+            .step_by(3)
+            .map(|i| Term::from_field_text(id, &format!("{}", i)))
+            .collect();
+        let query = TermSetQuery::new(terms.clone());
+        assert_eq!(query.num_terms(), terms.len());
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 334);
+        let small_terms = terms[..50].to_vec();
+        let matching_docs = |query: &dyn Query| {
+            let mut docs: Vec<DocAddress> = searcher
+                .search(query, &TopDocs::with_limit(1_000))
+                .unwrap()
+                .into_iter()
+                .map(|(_, doc_address)| doc_address)
+                .collect();
+            docs.sort();
+            docs
+        };
+        assert_eq!(
+            matching_docs(&TermSetQuery::new(small_terms.clone())),
+            matching_docs(&BooleanQuery::new_multiterms_query(small_terms))
+        );
+
+        let explanation = query.explain(&searcher, DocAddress(0, 3)).unwrap();
+        assert_eq!(explanation.value(), 1.0);
+        assert!(query.explain(&searcher, DocAddress(0, 4)).is_err());
+
+        // Terms of several fields are combined.
+        let query = TermSetQuery::new(vec![
+            Term::from_field_text(id, "1"),
+            Term::from_field_text(id, "1"),
+            Term::from_field_text(tag, "tag0"),
+        ]);
+        assert_eq!(query.num_terms(), 2);
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 144);
+
+        assert_eq!(
+            searcher
+                .search(&TermSetQuery::new(Vec::new()), &Count)
+                .unwrap(),
+            0
+        );
+    }
+}
@@ -14,7 +14,7 @@ const HORIZON: u32 = 64u32 * HORIZON_NUM_TINYBITSETS as u32;
 // it does not keep the original vector ordering.
 //
 // Also, it does not "yield" any elements.
-fn unordered_drain_filter<T, P>(v: &mut Vec<T>, mut predicate: P)
+pub(crate) fn unordered_drain_filter<T, P>(v: &mut Vec<T>, mut predicate: P)
 where
     P: FnMut(&mut T) -> bool,
 {
@@ -1,7 +1,23 @@
 use super::Scorer;
 use crate::core::SegmentReader;
 use crate::query::Explanation;
-use crate::{DocId, Result};
+use crate::{DocId, Result, Score};
+
+/// Calls `callback` with the documents of `scorer` whose score is greater
+/// than the threshold. `callback` returns the new threshold.
+///
+/// Every document of `scorer` is scored.
+pub(crate) fn for_each_pruning_scorer(
+    scorer: &mut dyn Scorer,
+    mut threshold: Score,
+    callback: &mut dyn FnMut(DocId, Score) -> Score,
+) {
+    scorer.for_each(&mut |doc, score| {
+        if score > threshold {
+            threshold = callback(doc, score);
+        }
+    });
+}
 
 /// A Weight is the specialization of a Query
 /// for a given set of segments.
@@ -15,6 +31,25 @@ pub trait Weight: Send + Sync + 'static {
     /// Returns an `Explanation` for the given document.
     fn explain(&self, reader: &SegmentReader, doc: DocId) -> Result<Explanation>;
 
+    /// Calls `callback` with the documents of the segment matching the query
+    /// whose score is greater than `threshold`, along with their score.
+    ///
+    /// `callback` returns the new threshold, which should never decrease.
+    /// This makes it possible to skip the documents that cannot exceed it,
+    /// when collecting the top documents of a segment.
+    ///
+    /// Deleted documents are not filtered out.
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> Result<()> {
+        let mut scorer = self.scorer(reader)?;
+        for_each_pruning_scorer(scorer.as_mut(), threshold, callback);
+        Ok(())
+    }
+
     /// Returns the number documents within the given `SegmentReader`.
     fn count(&self, reader: &SegmentReader) -> Result<u32> {
         let mut scorer = self.scorer(reader)?;
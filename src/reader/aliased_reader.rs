@@ -0,0 +1,171 @@
+use super::{IndexReader, IndexReaderBuilder, LeasedItem};
+use crate::Index;
+use crate::IndexAliases;
+use crate::Result;
+use crate::Searcher;
+use std::sync::RwLock;
+
+type ReaderBuilderFn = Box<dyn Fn(&Index) -> IndexReaderBuilder + Sync + Send>;
+
+struct AliasTarget {
+    target: String,
+    reader: IndexReader,
+}
+
+/// Reader following an alias of an [`IndexAliases`](./struct.IndexAliases.html) registry.
+///
+/// It searches the index the alias pointed to when it was last reloaded.
+/// Reloading it picks the index the alias currently points to: the searchers
+/// of the previous index remain valid until they are dropped, so that the
+/// swap is invisible to the queries in flight.
+///
+/// Within a same index, new commits are loaded as defined by the reload policy
+/// of the reader of the index.
+pub struct AliasedIndexReader {
+    aliases: IndexAliases,
+    alias: String,
+    reader_builder: ReaderBuilderFn,
+    current: RwLock<AliasTarget>,
+}
+
+impl AliasedIndexReader {
+    pub(crate) fn new(
+        aliases: IndexAliases,
+        alias: String,
+        reader_builder: ReaderBuilderFn,
+    ) -> Result<AliasedIndexReader> {
+        let target = aliases.resolve_existing(&alias)?;
+        let reader = open_reader(&aliases, &target, &reader_builder)?;
+        Ok(AliasedIndexReader {
+            aliases,
+            alias,
+            reader_builder,
+            current: RwLock::new(AliasTarget { target, reader }),
+        })
+    }
+
+    /// Returns the alias followed by the reader.
+    pub fn alias(&self) -> &str {
+        &self.alias
+    }
+
+    /// Returns the name of the index currently searched.
+    pub fn target(&self) -> String {
+        self.read().target.clone()
+    }
+
+    /// Returns the reader of the index currently searched.
+    pub fn index_reader(&self) -> IndexReader {
+        self.read().reader.clone()
+    }
+
+    /// Resolves the alias again, and switches to the index it points to
+    /// if it changed. Otherwise, reloads the reader of the current index.
+    pub fn reload(&self) -> Result<()> {
+        let target = self.aliases.resolve_existing(&self.alias)?;
+        if target == self.read().target {
+            return self.index_reader().reload();
+        }
+        // The index is opened before taking the lock so that
+        // searchers can still be acquired in the meantime.
+        let reader = open_reader(&self.aliases, &target, &self.reader_builder)?;
+        *self.current.write().expect("Aliased reader lock poisoned") =
+            AliasTarget { target, reader };
+        Ok(())
+    }
+
+    /// Returns a searcher over the index currently searched.
+    ///
+    /// See [`IndexReader::searcher`](./struct.IndexReader.html#method.searcher).
+    pub fn searcher(&self) -> LeasedItem<Searcher> {
+        // The reader is cloned so that the lock is not held while
+        // waiting for a searcher.
+        self.index_reader().searcher()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, AliasTarget> {
+        self.current.read().expect("Aliased reader lock poisoned")
+    }
+}
+
+fn open_reader(
+    aliases: &IndexAliases,
+    target: &str,
+    reader_builder: &ReaderBuilderFn,
+) -> Result<IndexReader> {
+    let index = aliases.open_target(target)?;
+    reader_builder(&index).try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::directory::RAMDirectory;
+    use crate::schema::{Schema, TEXT};
+    use crate::Index;
+    use crate::IndexAliases;
+    use crate::ReloadPolicy;
+    use crate::TantivyError;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_aliased_index_reader() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let mut indexes = HashMap::new();
+        for (target, num_docs) in &[("products_1", 1), ("products_2", 3)] {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            for _ in 0..*num_docs {
+                index_writer.add_document(doc!(text_field => "product"));
+            }
+            index_writer.commit().unwrap();
+            indexes.insert(target.to_string(), index);
+        }
+        let mut aliases = IndexAliases::new(RAMDirectory::create(), move |target: &str| {
+            indexes
+                .get(target)
+                .cloned()
+                .ok_or_else(|| TantivyError::InvalidArgument(target.to_string()))
+        });
+        assert!(aliases.reader("products_current").is_err());
+        assert!(aliases.set_alias("../products", "products_1").is_err());
+        aliases.set_alias("products_current", "products_1").unwrap();
+        assert_eq!(
+            aliases.resolve("products_current").unwrap(),
+            Some("products_1".to_string())
+        );
+        assert_eq!(aliases.resolve("products_next").unwrap(), None);
+
+        let reader = aliases
+            .reader_with_builder("products_current", |index: &Index| {
+                index
+                    .reader_builder()
+                    .reload_policy(ReloadPolicy::Manual)
+                    .num_searchers(1)
+            })
+            .unwrap();
+        assert_eq!(reader.alias(), "products_current");
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+
+        aliases.set_alias("products_current", "products_2").unwrap();
+        // Not reloaded yet.
+        assert_eq!(reader.target(), "products_1");
+        reader.reload().unwrap();
+        assert_eq!(reader.target(), "products_2");
+        assert_eq!(reader.searcher().num_docs(), 3);
+        // The searchers of the previous index remain valid.
+        assert_eq!(searcher.num_docs(), 1);
+        assert_eq!(
+            aliases
+                .open_index("products_current")
+                .unwrap()
+                .load_metas()
+                .unwrap()
+                .segments
+                .len(),
+            1
+        );
+    }
+}
@@ -1,11 +1,17 @@
+mod aliased_reader;
 mod pool;
 
+pub use self::aliased_reader::AliasedIndexReader;
 pub use self::pool::LeasedItem;
 use self::pool::Pool;
+use crate::core::Executor;
+use crate::core::FilterCache;
+use crate::core::QueryCache;
 use crate::core::Segment;
 use crate::directory::Directory;
 use crate::directory::WatchHandle;
 use crate::directory::META_LOCK;
+use crate::indexer::segment_updater::SegmentUpdater;
 use crate::Index;
 use crate::Result;
 use crate::Searcher;
@@ -29,6 +35,12 @@ pub enum ReloadPolicy {
     OnCommit, // TODO add NEAR_REAL_TIME(target_ms)
 }
 
+/// Callback run on the `Searcher` of every newly loaded version of the index,
+/// before it is made available.
+///
+/// See [`IndexReaderBuilder::warmer`](./struct.IndexReaderBuilder.html#method.warmer).
+pub type Warmer = Arc<dyn Fn(&Searcher) + Sync + Send>;
+
 /// `IndexReader` builder
 ///
 /// It makes it possible to set the following values.
@@ -40,10 +52,27 @@ pub enum ReloadPolicy {
 /// - `reload_policy` (by default `ReloadPolicy::OnCommit`):
 ///
 ///   See [`ReloadPolicy`](./enum.ReloadPolicy.html) for more details.
+/// - `search_executor` (by default, the search executor of the index):
+///
+///   See [`.search_executor(...)`](#method.search_executor) for more details.
+/// - `query_cache_size_in_bytes` (by default, no query cache):
+///
+///   See [`.query_cache_size_in_bytes(...)`](#method.query_cache_size_in_bytes) for more details.
+/// - `filter_cache_size_in_bytes` (by default, no filter cache):
+///
+///   See [`.filter_cache_size_in_bytes(...)`](#method.filter_cache_size_in_bytes) for more details.
+/// - `warmers` (by default, none):
+///
+///   See [`.warmer(...)`](#method.warmer) for more details.
 #[derive(Clone)]
 pub struct IndexReaderBuilder {
     num_searchers: usize,
     reload_policy: ReloadPolicy,
+    search_executor: Option<Arc<Executor>>,
+    query_cache_size_in_bytes: Option<usize>,
+    filter_cache_size_in_bytes: Option<usize>,
+    warmers: Vec<Warmer>,
+    nrt_segment_updater: Option<SegmentUpdater>,
     index: Index,
 }
 
@@ -52,6 +81,11 @@ impl IndexReaderBuilder {
         IndexReaderBuilder {
             num_searchers: num_cpus::get(),
             reload_policy: ReloadPolicy::OnCommit,
+            search_executor: None,
+            query_cache_size_in_bytes: None,
+            filter_cache_size_in_bytes: None,
+            warmers: Vec::new(),
+            nrt_segment_updater: None,
             index,
         }
     }
@@ -63,9 +97,20 @@ impl IndexReaderBuilder {
     /// of time and it may return an error.
     /// TODO(pmasurel) Use the `TryInto` trait once it is available in stable.
     pub fn try_into(self) -> Result<IndexReader> {
+        let index = self.index;
+        let search_executor = self
+            .search_executor
+            .unwrap_or_else(|| index.search_executor_arc());
         let inner_reader = InnerIndexReader {
-            index: self.index,
+            search_executor,
+            query_cache_size_in_bytes: self.query_cache_size_in_bytes,
+            filter_cache: self
+                .filter_cache_size_in_bytes
+                .map(|num_bytes| Arc::new(FilterCache::with_max_num_bytes(num_bytes))),
+            index,
             num_searchers: self.num_searchers,
+            warmers: self.warmers,
+            nrt_segment_updater: self.nrt_segment_updater,
             searcher_pool: Pool::new(),
         };
         inner_reader.reload()?;
@@ -107,15 +152,84 @@ impl IndexReaderBuilder {
         self
     }
 
+    /// Makes the reader search the segments of an `IndexWriter`, committed or not,
+    /// rather than the segments of the last commit.
+    ///
+    /// See `IndexWriter::nrt_reader`.
+    pub(crate) fn nrt_segment_updater(
+        mut self,
+        segment_updater: SegmentUpdater,
+    ) -> IndexReaderBuilder {
+        self.nrt_segment_updater = Some(segment_updater);
+        self
+    }
+
     /// Sets the number of `Searcher` in the searcher pool.
     pub fn num_searchers(mut self, num_searchers: usize) -> IndexReaderBuilder {
         self.num_searchers = num_searchers;
         self
     }
+
+    /// Sets the executor used by the searchers to search the segments.
+    ///
+    /// The same executor can be shared by the readers of several indexes
+    /// so that they do not use more than a given number of threads overall,
+    /// or dedicated to a reader. See [`Executor`](./struct.Executor.html).
+    pub fn search_executor(mut self, search_executor: Arc<Executor>) -> IndexReaderBuilder {
+        self.search_executor = Some(search_executor);
+        self
+    }
+
+    /// Enables the cache of the results of `Searcher::search_cached`, whose results
+    /// take at most approximately `num_bytes` (see `collector::FruitSize`).
+    ///
+    /// Every version of the index loaded by the reader has its own cache, shared by its
+    /// searchers, so that cached results are dropped when the reader is reloaded.
+    /// When the cache is full, the least recently used results are evicted.
+    pub fn query_cache_size_in_bytes(mut self, num_bytes: usize) -> IndexReaderBuilder {
+        self.query_cache_size_in_bytes = Some(num_bytes);
+        self
+    }
+
+    /// Enables the cache of the documents matching the queries used as filters
+    /// through a [`QueryFilter`](../query/struct.QueryFilter.html), whose segment
+    /// filters take at most approximately `num_bytes`.
+    ///
+    /// The cache is shared by all of the searchers of the reader. As segments are
+    /// immutable, the filters of a segment are kept across reloads, until the
+    /// segment is no longer searched, e.g. after being merged.
+    /// When the cache is full, the least recently used segment filters are evicted.
+    pub fn filter_cache_size_in_bytes(mut self, num_bytes: usize) -> IndexReaderBuilder {
+        self.filter_cache_size_in_bytes = Some(num_bytes);
+        self
+    }
+
+    /// Registers a warmer.
+    ///
+    /// Warmers are called, in the order they were registered, every time
+    /// a new version of the index is loaded (including when the reader is built),
+    /// with a `Searcher` on that version. The new searchers are only
+    /// made available once all warmers have returned.
+    ///
+    /// Warmers typically touch the fast fields or build the caches that queries
+    /// will need, so that the first queries after a commit do not have to
+    /// pay for it.
+    pub fn warmer<F>(mut self, warmer: F) -> IndexReaderBuilder
+    where
+        F: Fn(&Searcher) + Sync + Send + 'static,
+    {
+        self.warmers.push(Arc::new(warmer));
+        self
+    }
 }
 
 struct InnerIndexReader {
     num_searchers: usize,
+    search_executor: Arc<Executor>,
+    query_cache_size_in_bytes: Option<usize>,
+    filter_cache: Option<Arc<FilterCache>>,
+    warmers: Vec<Warmer>,
+    nrt_segment_updater: Option<SegmentUpdater>,
     searcher_pool: Pool<Searcher>,
     index: Index,
 }
@@ -131,21 +245,81 @@ impl InnerIndexReader {
                 .collect::<Result<_>>()?
         };
         let schema = self.index.schema();
-        let searchers = (0..self.num_searchers)
-            .map(|_| Searcher::new(schema.clone(), self.index.clone(), segment_readers.clone()))
+        let query_cache_opt = self.new_query_cache();
+        if let Some(ref filter_cache) = self.filter_cache {
+            let segment_ids: Vec<_> = segment_readers
+                .iter()
+                .map(SegmentReader::segment_id)
+                .collect();
+            filter_cache.retain_segments(&segment_ids);
+        }
+        let searchers: Vec<Searcher> = (0..self.num_searchers)
+            .map(|_| {
+                let searcher = Searcher::new(
+                    schema.clone(),
+                    self.index.clone(),
+                    segment_readers.clone(),
+                    self.search_executor.clone(),
+                );
+                self.with_caches(searcher, &query_cache_opt)
+            })
             .collect();
+        // All the searchers share the same segment readers,
+        // so warming one of them is enough.
+        if let Some(searcher) = searchers.first() {
+            for warmer in &self.warmers {
+                warmer(searcher);
+            }
+        }
         self.searcher_pool.publish_new_generation(searchers);
         Ok(())
     }
 
+    fn with_caches(
+        &self,
+        mut searcher: Searcher,
+        query_cache_opt: &Option<Arc<QueryCache>>,
+    ) -> Searcher {
+        if let Some(query_cache) = query_cache_opt {
+            searcher = searcher.with_query_cache(query_cache.clone());
+        }
+        if let Some(ref filter_cache) = self.filter_cache {
+            searcher = searcher.with_filter_cache(filter_cache.clone());
+        }
+        searcher
+    }
+
+    fn new_query_cache(&self) -> Option<Arc<QueryCache>> {
+        self.query_cache_size_in_bytes
+            .map(|num_bytes| Arc::new(QueryCache::with_max_num_bytes(num_bytes)))
+    }
+
     /// Returns the list of segments that are searchable
     fn searchable_segments(&self) -> Result<Vec<Segment>> {
-        self.index.searchable_segments()
+        match self.nrt_segment_updater {
+            Some(ref segment_updater) => Ok(segment_updater
+                .segment_metas()
+                .into_iter()
+                .map(|segment_meta| self.index.segment(segment_meta))
+                .collect()),
+            None => self.index.searchable_segments(),
+        }
     }
 
     fn searcher(&self) -> LeasedItem<Searcher> {
         self.searcher_pool.acquire()
     }
+
+    fn searcher_at_generation(&self, generation: u64) -> Result<Searcher> {
+        let searcher = self
+            .index
+            .pinned_searcher(generation, self.search_executor.clone())?;
+        let searcher = self.with_caches(searcher, &self.new_query_cache());
+        for warmer in &self.warmers {
+            warmer(&searcher);
+        }
+        Ok(searcher)
+    }
 }
 
 /// `IndexReader` is your entry point to read and search the index.
@@ -188,4 +362,24 @@ impl IndexReader {
     pub fn searcher(&self) -> LeasedItem<Searcher> {
         self.inner.searcher()
     }
+
+    /// Returns a searcher pinned to a given generation of the meta file,
+    /// that is to say to the state of the index after a given commit.
+    ///
+    /// Unlike the searchers of the pool, the pinned searcher is not affected
+    /// by reloads: as long as it lives, the generation and
+    /// the files of its segments are protected from garbage collection, and the same
+    /// generation can be pinned again. This makes it possible, for instance, to paginate
+    /// over results or to export documents from a stable view of the index.
+    ///
+    /// The available generations are listed by
+    /// [`Index::list_meta_generations`](./struct.Index.html#method.list_meta_generations).
+    /// Only the generations kept according to `Index::set_num_meta_generations_kept`,
+    /// or pinned by a living searcher, are guaranteed to be available.
+    ///
+    /// Garbage collection only takes the pins of the same `Index` object (or of its clones)
+    /// into account.
+    pub fn searcher_at_generation(&self, generation: u64) -> Result<Searcher> {
+        self.inner.searcher_at_generation(generation)
+    }
 }
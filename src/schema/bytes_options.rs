@@ -0,0 +1,116 @@
+use crate::schema::flags::{FastFlag, IndexedFlag, SchemaFlagList};
+use crate::schema::Cardinality;
+use std::ops::BitOr;
+
+/// Define how a bytes field should be handled by tantivy.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BytesOptions {
+    indexed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fast: Option<Cardinality>,
+}
+
+impl BytesOptions {
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self) -> bool {
+        self.indexed
+    }
+
+    /// Returns true iff the value is a fast field.
+    pub fn is_fast(&self) -> bool {
+        self.fast.is_some()
+    }
+
+    /// Set the bytes options as indexed.
+    ///
+    /// Setting a bytes field as indexed will generate a posting list
+    /// for each value taken by the field, so that the documents can
+    /// be searched for an exact binary value, e.g. a hash or a UUID.
+    pub fn set_indexed(mut self) -> BytesOptions {
+        self.indexed = true;
+        self
+    }
+
+    /// Set the bytes options as a fast field.
+    ///
+    /// Fast fields are designed for random access.
+    /// If more than one value is associated to a single-valued fast field,
+    /// the values are concatenated.
+    /// A multi-valued fast field keeps each value apart instead.
+    pub fn set_fast(mut self, cardinality: Cardinality) -> BytesOptions {
+        self.fast = Some(cardinality);
+        self
+    }
+
+    /// Returns the cardinality of the fastfield.
+    ///
+    /// If the field has not been declared as a fastfield, then
+    /// the method returns None.
+    pub fn get_fastfield_cardinality(&self) -> Option<Cardinality> {
+        self.fast
+    }
+}
+
+impl From<()> for BytesOptions {
+    fn from(_: ()) -> BytesOptions {
+        BytesOptions::default()
+    }
+}
+
+impl From<FastFlag> for BytesOptions {
+    fn from(_: FastFlag) -> Self {
+        BytesOptions {
+            indexed: false,
+            fast: Some(Cardinality::SingleValue),
+        }
+    }
+}
+
+impl From<IndexedFlag> for BytesOptions {
+    fn from(_: IndexedFlag) -> Self {
+        BytesOptions {
+            indexed: true,
+            fast: None,
+        }
+    }
+}
+
+impl<T: Into<BytesOptions>> BitOr<T> for BytesOptions {
+    type Output = BytesOptions;
+
+    fn bitor(self, other: T) -> BytesOptions {
+        let other = other.into();
+        BytesOptions {
+            indexed: self.indexed | other.indexed,
+            fast: self.fast.or(other.fast),
+        }
+    }
+}
+
+impl<Head, Tail> From<SchemaFlagList<Head, Tail>> for BytesOptions
+where
+    Head: Clone,
+    Tail: Clone,
+    Self: BitOr<Output = Self> + From<Head> + From<Tail>,
+{
+    fn from(head_tail: SchemaFlagList<Head, Tail>) -> Self {
+        Self::from(head_tail.head) | Self::from(head_tail.tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BytesOptions;
+    use crate::schema::{FAST, INDEXED};
+
+    #[test]
+    fn test_bytes_options_from_flags() {
+        let options: BytesOptions = FAST.into();
+        assert!(options.is_fast());
+        assert!(!options.is_indexed());
+        let options: BytesOptions = (INDEXED | FAST).into();
+        assert!(options.is_fast());
+        assert!(options.is_indexed());
+        assert_eq!(BytesOptions::default().set_indexed(), INDEXED.into());
+    }
+}
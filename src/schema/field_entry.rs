@@ -1,3 +1,5 @@
+use crate::schema::BytesOptions;
+use crate::schema::Cardinality;
 use crate::schema::IntOptions;
 use crate::schema::TextOptions;
 
@@ -66,6 +68,24 @@ impl FieldEntry {
         }
     }
 
+    /// Creates a new geo point field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_geo_point(field_name: String, field_type: IntOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::GeoPoint(field_type),
+        }
+    }
+
+    /// Creates a new json object field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_json(field_name: String, text_options: TextOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::Json(text_options),
+        }
+    }
+
     /// Creates a field entry for a facet.
     pub fn new_facet(field_name: String) -> FieldEntry {
         FieldEntry {
@@ -74,11 +94,12 @@ impl FieldEntry {
         }
     }
 
-    /// Creates a field entry for a bytes field
-    pub fn new_bytes(field_name: String) -> FieldEntry {
+    /// Creates a field entry for a bytes field, given
+    /// a name, and some options.
+    pub fn new_bytes(field_name: String, bytes_options: BytesOptions) -> FieldEntry {
         FieldEntry {
             name: field_name,
-            field_type: FieldType::Bytes,
+            field_type: FieldType::Bytes(bytes_options),
         }
     }
 
@@ -95,13 +116,16 @@ impl FieldEntry {
     /// Returns true iff the field is indexed
     pub fn is_indexed(&self) -> bool {
         match self.field_type {
-            FieldType::Str(ref options) => options.get_indexing_options().is_some(),
+            FieldType::Str(ref options) | FieldType::Json(ref options) => {
+                options.get_indexing_options().is_some()
+            }
             FieldType::U64(ref options)
             | FieldType::I64(ref options)
             | FieldType::F64(ref options)
-            | FieldType::Date(ref options) => options.is_indexed(),
+            | FieldType::Date(ref options)
+            | FieldType::GeoPoint(ref options) => options.is_indexed(),
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(ref options) => options.is_indexed(),
         }
     }
 
@@ -121,11 +145,12 @@ impl FieldEntry {
             FieldType::U64(ref options)
             | FieldType::I64(ref options)
             | FieldType::F64(ref options)
-            | FieldType::Date(ref options) => options.is_stored(),
-            FieldType::Str(ref options) => options.is_stored(),
+            | FieldType::Date(ref options)
+            | FieldType::GeoPoint(ref options) => options.is_stored(),
+            FieldType::Str(ref options) | FieldType::Json(ref options) => options.is_stored(),
             // TODO make stored hierarchical facet optional
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(_) => false,
         }
     }
 }
@@ -159,11 +184,20 @@ impl Serialize for FieldEntry {
                 s.serialize_field("type", "date")?;
                 s.serialize_field("options", options)?;
             }
+            FieldType::GeoPoint(ref options) => {
+                s.serialize_field("type", "geo_point")?;
+                s.serialize_field("options", options)?;
+            }
             FieldType::HierarchicalFacet => {
                 s.serialize_field("type", "hierarchical_facet")?;
             }
-            FieldType::Bytes => {
+            FieldType::Bytes(ref options) => {
                 s.serialize_field("type", "bytes")?;
+                s.serialize_field("options", options)?;
+            }
+            FieldType::Json(ref options) => {
+                s.serialize_field("type", "json_object")?;
+                s.serialize_field("options", options)?;
             }
         }
 
@@ -220,9 +254,14 @@ impl<'de> Deserialize<'de> for FieldEntry {
                                     field_type = Some(FieldType::HierarchicalFacet);
                                 }
                                 "bytes" => {
-                                    field_type = Some(FieldType::Bytes);
+                                    // Bytes fields used to be fast fields without options.
+                                    // Their options, if any, come next.
+                                    let options =
+                                        BytesOptions::default().set_fast(Cardinality::SingleValue);
+                                    field_type = Some(FieldType::Bytes(options));
                                 }
-                                "text" | "u64" | "i64" | "f64" | "date" => {
+                                "text" | "u64" | "i64" | "f64" | "date" | "geo_point"
+                                | "json_object" => {
                                     // These types require additional options to create a field_type
                                 }
                                 _ => panic!("unhandled type"),
@@ -241,6 +280,13 @@ impl<'de> Deserialize<'de> for FieldEntry {
                                 "i64" => field_type = Some(FieldType::I64(map.next_value()?)),
                                 "f64" => field_type = Some(FieldType::F64(map.next_value()?)),
                                 "date" => field_type = Some(FieldType::Date(map.next_value()?)),
+                                "geo_point" => {
+                                    field_type = Some(FieldType::GeoPoint(map.next_value()?))
+                                }
+                                "bytes" => field_type = Some(FieldType::Bytes(map.next_value()?)),
+                                "json_object" => {
+                                    field_type = Some(FieldType::Json(map.next_value()?))
+                                }
                                 _ => {
                                     let msg = format!("Unrecognised type {}", ty);
                                     return Err(de::Error::custom(msg));
@@ -265,7 +311,7 @@ impl<'de> Deserialize<'de> for FieldEntry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schema::TEXT;
+    use crate::schema::{INDEXED, TEXT};
     use serde_json;
 
     #[test]
@@ -296,4 +342,28 @@ mod tests {
             _ => panic!("expected FieldType::Str"),
         }
     }
+
+    #[test]
+    fn test_bytes_json_serialization() {
+        let field_value = FieldEntry::new_bytes(String::from("hash"), INDEXED.into());
+        let expected = r#"{
+  "name": "hash",
+  "type": "bytes",
+  "options": {
+    "indexed": true
+  }
+}"#;
+        let field_value_json = serde_json::to_string_pretty(&field_value).unwrap();
+        assert_eq!(expected, &field_value_json);
+        let field_value: FieldEntry = serde_json::from_str(expected).unwrap();
+        assert!(field_value.is_indexed());
+
+        // Bytes fields without options are fast fields.
+        let legacy: FieldEntry =
+            serde_json::from_str(r#"{"name": "hash", "type": "bytes"}"#).unwrap();
+        assert_eq!(
+            legacy.field_type(),
+            &FieldType::Bytes(BytesOptions::default().set_fast(Cardinality::SingleValue))
+        );
+    }
 }
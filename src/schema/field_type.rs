@@ -1,8 +1,9 @@
 use base64::decode;
 
-use crate::schema::{IntOptions, TextOptions};
+use crate::schema::{BytesOptions, IntOptions, TextOptions};
 
 use crate::schema::Facet;
+use crate::schema::GeoPoint;
 use crate::schema::IndexRecordOption;
 use crate::schema::TextFieldIndexing;
 use crate::schema::Value;
@@ -21,6 +22,9 @@ pub enum ValueParsingError {
     /// The json node is a string but contains json that is
     /// not valid base64.
     InvalidBase64(String),
+    /// The json node is neither a `"lat,lon"` string nor a `{"lat": .., "lon": ..}`
+    /// object, or its coordinates are out of range.
+    InvalidGeoPoint(String),
 }
 
 /// Type of the value that a field can take.
@@ -43,6 +47,10 @@ pub enum Type {
     HierarchicalFacet,
     /// `Vec<u8>`
     Bytes,
+    /// `tantivy::schema::GeoPoint`
+    GeoPoint,
+    /// `serde_json::Map<String, serde_json::Value>`
+    Json,
 }
 
 /// A `FieldType` describes the type (text, u64) of a field as well as
@@ -61,8 +69,17 @@ pub enum FieldType {
     Date(IntOptions),
     /// Hierachical Facet
     HierarchicalFacet,
-    /// Bytes (one per document)
-    Bytes,
+    /// Bytes field type configuration
+    Bytes(BytesOptions),
+    /// Geo point field type configuration
+    GeoPoint(IntOptions),
+    /// Json object field type configuration
+    ///
+    /// The text values of the json objects are indexed according
+    /// to the indexing options, while their numbers are indexed
+    /// as integers or floats.
+    /// Json objects cannot be fast fields.
+    Json(TextOptions),
 }
 
 impl FieldType {
@@ -75,20 +92,25 @@ impl FieldType {
             FieldType::F64(_) => Type::F64,
             FieldType::Date(_) => Type::Date,
             FieldType::HierarchicalFacet => Type::HierarchicalFacet,
-            FieldType::Bytes => Type::Bytes,
+            FieldType::Bytes(_) => Type::Bytes,
+            FieldType::GeoPoint(_) => Type::GeoPoint,
+            FieldType::Json(_) => Type::Json,
         }
     }
 
     /// returns true iff the field is indexed.
     pub fn is_indexed(&self) -> bool {
         match *self {
-            FieldType::Str(ref text_options) => text_options.get_indexing_options().is_some(),
+            FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => {
+                text_options.get_indexing_options().is_some()
+            }
             FieldType::U64(ref int_options)
             | FieldType::I64(ref int_options)
-            | FieldType::F64(ref int_options) => int_options.is_indexed(),
+            | FieldType::F64(ref int_options)
+            | FieldType::GeoPoint(ref int_options) => int_options.is_indexed(),
             FieldType::Date(ref date_options) => date_options.is_indexed(),
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(ref bytes_options) => bytes_options.is_indexed(),
         }
     }
 
@@ -98,13 +120,14 @@ impl FieldType {
     /// If the field is not indexed, then returns `None`.
     pub fn get_index_record_option(&self) -> Option<IndexRecordOption> {
         match *self {
-            FieldType::Str(ref text_options) => text_options
+            FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => text_options
                 .get_indexing_options()
                 .map(TextFieldIndexing::index_option),
             FieldType::U64(ref int_options)
             | FieldType::I64(ref int_options)
             | FieldType::F64(ref int_options)
-            | FieldType::Date(ref int_options) => {
+            | FieldType::Date(ref int_options)
+            | FieldType::GeoPoint(ref int_options) => {
                 if int_options.is_indexed() {
                     Some(IndexRecordOption::Basic)
                 } else {
@@ -112,7 +135,50 @@ impl FieldType {
                 }
             }
             FieldType::HierarchicalFacet => Some(IndexRecordOption::Basic),
-            FieldType::Bytes => None,
+            FieldType::Bytes(ref bytes_options) => {
+                if bytes_options.is_indexed() {
+                    Some(IndexRecordOption::Basic)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Returns true iff the byte offsets of the tokens are stored
+    /// along with their positions.
+    pub fn has_offsets(&self) -> bool {
+        match *self {
+            FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => text_options
+                .get_indexing_options()
+                .map(TextFieldIndexing::has_offsets)
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Returns true iff the payloads of the tokens are stored
+    /// along with their positions.
+    pub fn has_payloads(&self) -> bool {
+        match *self {
+            FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => text_options
+                .get_indexing_options()
+                .map(TextFieldIndexing::has_payloads)
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Returns the document frequency above which the postings
+    /// of a term are not indexed, if any.
+    ///
+    /// See [`TextFieldIndexing::set_max_doc_freq`](./struct.TextFieldIndexing.html#method.set_max_doc_freq).
+    pub fn max_doc_freq(&self) -> Option<u32> {
+        match *self {
+            FieldType::Str(ref text_options) | FieldType::Json(ref text_options) => text_options
+                .get_indexing_options()
+                .and_then(TextFieldIndexing::max_doc_freq),
+            _ => None,
         }
     }
 
@@ -132,12 +198,17 @@ impl FieldType {
                     )))
                 }
                 FieldType::HierarchicalFacet => Ok(Value::Facet(Facet::from(field_text))),
-                FieldType::Bytes => decode(field_text).map(Value::Bytes).map_err(|_| {
+                FieldType::Bytes(_) => decode(field_text).map(Value::Bytes).map_err(|_| {
                     ValueParsingError::InvalidBase64(format!(
                         "Expected base64 string, got {:?}",
                         field_text
                     ))
                 }),
+                FieldType::GeoPoint(_) => geo_point_from_str(field_text),
+                FieldType::Json(_) => {
+                    let msg = format!("Expected a json object, got {:?}", json);
+                    Err(ValueParsingError::TypeError(msg))
+                }
             },
             JsonValue::Number(ref field_val_num) => match *self {
                 FieldType::I64(_) | FieldType::Date(_) => {
@@ -164,11 +235,31 @@ impl FieldType {
                         Err(ValueParsingError::OverflowError(msg))
                     }
                 }
-                FieldType::Str(_) | FieldType::HierarchicalFacet | FieldType::Bytes => {
+                FieldType::Str(_) | FieldType::HierarchicalFacet | FieldType::Bytes(_) => {
                     let msg = format!("Expected a string, got {:?}", json);
                     Err(ValueParsingError::TypeError(msg))
                 }
+                FieldType::GeoPoint(_) => {
+                    let msg = format!("Expected a geo point, got {:?}", json);
+                    Err(ValueParsingError::TypeError(msg))
+                }
+                FieldType::Json(_) => {
+                    let msg = format!("Expected a json object, got {:?}", json);
+                    Err(ValueParsingError::TypeError(msg))
+                }
             },
+            JsonValue::Object(ref json_object) if self.value_type() == Type::Json => {
+                Ok(Value::JsonObject(json_object.clone()))
+            }
+            JsonValue::Object(_) if self.value_type() == Type::GeoPoint => {
+                serde_json::from_value(json.clone())
+                    .ok()
+                    .and_then(valid_geo_point)
+                    .ok_or_else(|| {
+                        let msg = format!("Expected a geo point, got {:?}", json);
+                        ValueParsingError::InvalidGeoPoint(msg)
+                    })
+            }
             _ => {
                 let msg = format!(
                     "Json value not supported error {:?}. Expected {:?}",
@@ -180,29 +271,77 @@ impl FieldType {
     }
 }
 
+/// Parses a geo point given as `"lat,lon"`.
+fn geo_point_from_str(text: &str) -> Result<Value, ValueParsingError> {
+    let mut coordinates = text
+        .splitn(2, ',')
+        .map(|coordinate| coordinate.trim().parse());
+    match (coordinates.next(), coordinates.next()) {
+        (Some(Ok(lat)), Some(Ok(lon))) => valid_geo_point(GeoPoint { lat, lon }),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        let msg = format!("Expected a \"lat,lon\" geo point, got {:?}", text);
+        ValueParsingError::InvalidGeoPoint(msg)
+    })
+}
+
+fn valid_geo_point(geo_point: GeoPoint) -> Option<Value> {
+    if geo_point.is_valid() {
+        Some(Value::GeoPoint(geo_point))
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::FieldType;
     use crate::schema::field_type::ValueParsingError;
-    use crate::schema::Value;
+    use crate::schema::{BytesOptions, GeoPoint, IntOptions, Value};
 
     #[test]
     fn test_bytes_value_from_json() {
-        let result = FieldType::Bytes
+        let bytes_type = FieldType::Bytes(BytesOptions::default());
+        let result = bytes_type
             .value_from_json(&json!("dGhpcyBpcyBhIHRlc3Q="))
             .unwrap();
         assert_eq!(result, Value::Bytes("this is a test".as_bytes().to_vec()));
 
-        let result = FieldType::Bytes.value_from_json(&json!(521));
+        let result = bytes_type.value_from_json(&json!(521));
         match result {
             Err(ValueParsingError::TypeError(_)) => {}
             _ => panic!("Expected parse failure for wrong type"),
         }
 
-        let result = FieldType::Bytes.value_from_json(&json!("-"));
+        let result = bytes_type.value_from_json(&json!("-"));
         match result {
             Err(ValueParsingError::InvalidBase64(_)) => {}
             _ => panic!("Expected parse failure for invalid base64"),
         }
     }
+
+    #[test]
+    fn test_geo_point_value_from_json() {
+        let field_type = FieldType::GeoPoint(IntOptions::default());
+        let expected = Value::GeoPoint(GeoPoint::new(48.8566, 2.3522));
+        assert_eq!(
+            field_type.value_from_json(&json!("48.8566, 2.3522")),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            field_type.value_from_json(&json!({"lat": 48.8566, "lon": 2.3522})),
+            Ok(expected)
+        );
+        for invalid in &[json!("48.8566"), json!("91,0"), json!({"lat": 0.0})] {
+            match field_type.value_from_json(invalid) {
+                Err(ValueParsingError::InvalidGeoPoint(_)) => {}
+                _ => panic!("Expected parse failure for {:?}", invalid),
+            }
+        }
+        match field_type.value_from_json(&json!(3)) {
+            Err(ValueParsingError::TypeError(_)) => {}
+            _ => panic!("Expected parse failure for wrong type"),
+        }
+    }
 }
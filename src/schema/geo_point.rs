@@ -0,0 +1,180 @@
+use std::fmt;
+
+/// Mean radius of the earth, in meters.
+pub(crate) const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Number of bits of the morton code refined by each level of cells.
+///
+/// Each level splits the cells of the previous level in 4 along the
+/// latitude, and in 4 along the longitude.
+pub(crate) const CELL_LEVEL_BITS: u32 = 4;
+
+/// Number of levels of cells indexed for a geo point.
+///
+/// The cells of the last level are about 60cm wide.
+pub(crate) const NUM_CELL_LEVELS: u8 = 13;
+
+/// A point on the earth, given by its latitude and its longitude in degrees.
+///
+/// Tantivy encodes a geo point as a `u64` called its morton code, by
+/// interleaving the bits of its latitude and of its longitude,
+/// each quantized on 32 bits. The precision of this encoding is of the order
+/// of the centimeter.
+///
+/// Indexed geo points are indexed as the cells of a grid containing them,
+/// at different levels of precision.
+/// The first bits of the morton code of a point identify these cells,
+/// just like the prefixes of a geohash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct GeoPoint {
+    /// Latitude, between `-90` and `90`.
+    pub lat: f64,
+    /// Longitude, between `-180` and `180`.
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Creates a geo point given its latitude and its longitude.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the latitude is not within `[-90, 90]`, or if the longitude
+    /// is not within `[-180, 180]`.
+    pub fn new(lat: f64, lon: f64) -> GeoPoint {
+        let geo_point = GeoPoint { lat, lon };
+        assert!(
+            geo_point.is_valid(),
+            "Invalid geo point latitude {} or longitude {}",
+            lat,
+            lon
+        );
+        geo_point
+    }
+
+    /// Returns true iff the latitude is within `[-90, 90]`, and
+    /// the longitude within `[-180, 180]`.
+    pub fn is_valid(&self) -> bool {
+        (-90f64..=90f64).contains(&self.lat) && (-180f64..=180f64).contains(&self.lon)
+    }
+
+    /// Returns the great-circle distance to another point, in meters.
+    pub fn distance(&self, other: &GeoPoint) -> f64 {
+        let lat = self.lat.to_radians();
+        let other_lat = other.lat.to_radians();
+        let half_delta_lat = (other_lat - lat) / 2f64;
+        let half_delta_lon = (other.lon - self.lon).to_radians() / 2f64;
+        let haversine = half_delta_lat.sin().powi(2)
+            + lat.cos() * other_lat.cos() * half_delta_lon.sin().powi(2);
+        2f64 * EARTH_RADIUS_METERS * haversine.sqrt().min(1f64).asin()
+    }
+
+    /// Returns the morton code of the point.
+    ///
+    /// Coordinates out of their range are clamped.
+    pub fn to_u64(&self) -> u64 {
+        let lat_bits = quantize(self.lat, -90f64, 180f64);
+        let lon_bits = quantize(self.lon, -180f64, 360f64);
+        (spread_bits(lon_bits) << 1) | spread_bits(lat_bits)
+    }
+
+    /// Decodes a morton code, as returned by `.to_u64()`.
+    pub fn from_u64(morton_code: u64) -> GeoPoint {
+        GeoPoint {
+            lat: dequantize(compact_bits(morton_code), -90f64, 180f64),
+            lon: dequantize(compact_bits(morton_code >> 1), -180f64, 360f64),
+        }
+    }
+}
+
+impl fmt::Display for GeoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.lat, self.lon)
+    }
+}
+
+/// Returns the code of the cell of level `level` containing the
+/// point of morton code `morton_code`.
+///
+/// The code is made of the `CELL_LEVEL_BITS * level` first bits of the
+/// morton code, followed by a bit set to 1 marking the level of the cell.
+pub(crate) fn cell_code(morton_code: u64, level: u8) -> u64 {
+    let level_bit = 1u64 << (63 - CELL_LEVEL_BITS * u32::from(level));
+    (morton_code & !(2 * level_bit - 1)) | level_bit
+}
+
+fn quantize(val: f64, min: f64, amplitude: f64) -> u32 {
+    let quantized = ((val - min) / amplitude * 4_294_967_296f64) as u64;
+    quantized.min(u64::from(u32::MAX)) as u32
+}
+
+/// Returns the coordinate at the center of the quantization interval.
+fn dequantize(quantized: u32, min: f64, amplitude: f64) -> f64 {
+    min + (f64::from(quantized) + 0.5f64) / 4_294_967_296f64 * amplitude
+}
+
+/// Moves the 32 bits of `val` to the even bits of a `u64`.
+fn spread_bits(val: u32) -> u64 {
+    let mut spread = u64::from(val);
+    spread = (spread | (spread << 16)) & 0x0000_FFFF_0000_FFFF;
+    spread = (spread | (spread << 8)) & 0x00FF_00FF_00FF_00FF;
+    spread = (spread | (spread << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    spread = (spread | (spread << 2)) & 0x3333_3333_3333_3333;
+    (spread | (spread << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Gathers the even bits of `val`, inverse of `spread_bits`.
+pub(crate) fn compact_bits(val: u64) -> u32 {
+    let mut compact = val & 0x5555_5555_5555_5555;
+    compact = (compact | (compact >> 1)) & 0x3333_3333_3333_3333;
+    compact = (compact | (compact >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    compact = (compact | (compact >> 4)) & 0x00FF_00FF_00FF_00FF;
+    compact = (compact | (compact >> 8)) & 0x0000_FFFF_0000_FFFF;
+    ((compact | (compact >> 16)) & 0x0000_0000_FFFF_FFFF) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cell_code, GeoPoint};
+
+    #[test]
+    fn test_geo_point_morton_code() {
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let decoded = GeoPoint::from_u64(paris.to_u64());
+        assert!((decoded.lat - paris.lat).abs() < 1e-7);
+        assert!((decoded.lon - paris.lon).abs() < 1e-7);
+        assert_eq!(decoded.to_u64(), paris.to_u64());
+        assert_eq!(GeoPoint::new(-90.0, -180.0).to_u64(), 0u64);
+        assert_eq!(GeoPoint::new(90.0, 180.0).to_u64(), u64::MAX);
+        // the first bit is the one of the longitude.
+        assert_eq!(GeoPoint::new(-90.0, 0.0).to_u64(), 1u64 << 63);
+        assert_eq!(GeoPoint::new(0.0, -180.0).to_u64(), 1u64 << 62);
+    }
+
+    #[test]
+    fn test_geo_point_distance() {
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let london = GeoPoint::new(51.5074, -0.1278);
+        let distance = paris.distance(&london);
+        assert!((distance - 343_560f64).abs() < 1_000f64, "{}", distance);
+        assert_eq!(paris.distance(&paris), 0f64);
+        let antipode = GeoPoint::new(-48.8566, -177.6478);
+        let half_circumference = std::f64::consts::PI * super::EARTH_RADIUS_METERS;
+        assert!((paris.distance(&antipode) - half_circumference).abs() < 1f64);
+    }
+
+    #[test]
+    fn test_geo_point_cell_code() {
+        let morton_code = GeoPoint::new(48.8566, 2.3522).to_u64();
+        let level_1 = cell_code(morton_code, 1);
+        assert_eq!(level_1 >> 59, (morton_code >> 60) << 1 | 1);
+        assert_eq!(level_1.trailing_zeros(), 59);
+        assert_eq!(cell_code(morton_code, 13).trailing_zeros(), 11);
+        assert_ne!(cell_code(morton_code, 2), cell_code(morton_code, 3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_geo_point_invalid() {
+        GeoPoint::new(91.0, 0.0);
+    }
+}
@@ -26,6 +26,14 @@ pub enum IndexRecordOption {
     /// Positions are required to run [PhraseQueries](../query/struct.PhraseQuery.html).
     #[serde(rename = "position")]
     WithFreqsAndPositions,
+    /// records the document id, the term frequency, the positions of
+    /// the occurences in the document, and the byte offsets of their tokens.
+    /// Offsets make it possible to highlight the occurences of a term
+    /// without analyzing the text again
+    /// (See [`SnippetGenerator::snippet_from_doc_address`](
+    ///     ../struct.SnippetGenerator.html#method.snippet_from_doc_address)).
+    #[serde(rename = "offsets")]
+    WithFreqsAndPositionsAndOffsets,
 }
 
 impl IndexRecordOption {
@@ -34,7 +42,9 @@ impl IndexRecordOption {
     pub fn has_freq(self) -> bool {
         match self {
             IndexRecordOption::Basic => false,
-            IndexRecordOption::WithFreqs | IndexRecordOption::WithFreqsAndPositions => true,
+            IndexRecordOption::WithFreqs
+            | IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
         }
     }
 
@@ -43,7 +53,19 @@ impl IndexRecordOption {
     pub fn has_positions(self) -> bool {
         match self {
             IndexRecordOption::Basic | IndexRecordOption::WithFreqs => false,
-            IndexRecordOption::WithFreqsAndPositions => true,
+            IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
+        }
+    }
+
+    /// Returns true iff this option include encoding
+    /// the byte offsets of the tokens.
+    pub fn has_offsets(self) -> bool {
+        match self {
+            IndexRecordOption::Basic
+            | IndexRecordOption::WithFreqs
+            | IndexRecordOption::WithFreqsAndPositions => false,
+            IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
         }
     }
 }
@@ -20,6 +20,12 @@ pub struct IntOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     fast: Option<Cardinality>,
     stored: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    range_indexed: bool,
+}
+
+fn is_false(val: &bool) -> bool {
+    !*val
 }
 
 impl IntOptions {
@@ -67,6 +73,24 @@ impl IntOptions {
         self
     }
 
+    /// Returns true iff the values of the fast field are range indexed.
+    pub fn is_range_indexed(&self) -> bool {
+        self.range_indexed
+    }
+
+    /// Set the fast field as range indexed.
+    ///
+    /// Each segment then stores the `DocId`s of its documents sorted by
+    /// their value, so that a `RangeQuery` can find the documents within
+    /// a range by binary search, without going through the term dictionary.
+    ///
+    /// Range indexing only applies to single-valued fast fields. Documents
+    /// without a value are indexed with the default value of the fast field.
+    pub fn set_range_indexed(mut self) -> IntOptions {
+        self.range_indexed = true;
+        self
+    }
+
     /// Returns the cardinality of the fastfield.
     ///
     /// If the field has not been declared as a fastfield, then
@@ -82,6 +106,7 @@ impl Default for IntOptions {
             indexed: false,
             stored: false,
             fast: None,
+            range_indexed: false,
         }
     }
 }
@@ -98,6 +123,7 @@ impl From<FastFlag> for IntOptions {
             indexed: false,
             stored: false,
             fast: Some(Cardinality::SingleValue),
+            range_indexed: false,
         }
     }
 }
@@ -108,6 +134,7 @@ impl From<StoredFlag> for IntOptions {
             indexed: false,
             stored: true,
             fast: None,
+            range_indexed: false,
         }
     }
 }
@@ -118,6 +145,7 @@ impl From<IndexedFlag> for IntOptions {
             indexed: true,
             stored: false,
             fast: None,
+            range_indexed: false,
         }
     }
 }
@@ -131,6 +159,7 @@ impl<T: Into<IntOptions>> BitOr<T> for IntOptions {
         res.indexed = self.indexed | other.indexed;
         res.stored = self.stored | other.stored;
         res.fast = self.fast.or(other.fast);
+        res.range_indexed = self.range_indexed | other.range_indexed;
         res
     }
 }
@@ -104,9 +104,11 @@ let schema = schema_builder.build();
 
 mod document;
 mod facet;
+mod geo_point;
 mod schema;
 mod term;
 
+mod bytes_options;
 mod field_entry;
 mod field_type;
 mod field_value;
@@ -128,9 +130,15 @@ pub use self::value::Value;
 pub use self::facet::Facet;
 pub(crate) use self::facet::FACET_SEP_BYTE;
 
+pub use self::geo_point::GeoPoint;
+pub(crate) use self::geo_point::{cell_code, compact_bits, CELL_LEVEL_BITS, NUM_CELL_LEVELS};
+
 pub use self::document::Document;
 pub use self::field::Field;
 pub use self::term::Term;
+pub(crate) use self::term::{
+    JSON_F64_CODE, JSON_I64_CODE, JSON_PATH_SEGMENT_SEP, JSON_TEXT_CODE, JSON_U64_CODE,
+};
 
 pub use self::field_entry::FieldEntry;
 pub use self::field_type::{FieldType, Type};
@@ -142,6 +150,7 @@ pub use self::text_options::TextOptions;
 pub use self::text_options::STRING;
 pub use self::text_options::TEXT;
 
+pub use self::bytes_options::BytesOptions;
 pub use self::flags::{FAST, INDEXED, STORED};
 pub use self::int_options::Cardinality;
 pub use self::int_options::IntOptions;
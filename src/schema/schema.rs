@@ -124,6 +124,32 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a new geo point field.
+    /// Returns the associated field handle
+    ///
+    /// Indexed geo points can be searched with a
+    /// [`GeoDistanceQuery`](../query/struct.GeoDistanceQuery.html),
+    /// which also requires them to be fast fields.
+    /// Fast geo points are stored as their morton code
+    /// (see [`GeoPoint::to_u64`](./struct.GeoPoint.html#method.to_u64)).
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name
+    /// will result in the shadowing of the first
+    /// by the second one.
+    /// The first field will get a field id
+    /// but only the second one will be indexed
+    pub fn add_geo_point_field<T: Into<IntOptions>>(
+        &mut self,
+        field_name_str: &str,
+        field_options: T,
+    ) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_geo_point(field_name, field_options.into());
+        self.add_field(field_entry)
+    }
+
     /// Adds a new text field.
     /// Returns the associated field handle
     ///
@@ -144,24 +170,53 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a new json object field.
+    /// Returns the associated field handle
+    ///
+    /// The json objects are flattened: each value is indexed together
+    /// with its path within the object, e.g. `attrs.color`, so that
+    /// a schema is not required for the content of the objects.
+    /// The text values are tokenized according to `field_options`,
+    /// while the numbers are indexed as `i64`, `u64` or `f64` values.
+    /// Booleans are indexed as the texts `true` and `false`.
+    ///
+    /// See `Term::from_field_json_text`, or use the query parser
+    /// with the path syntax, e.g. `attrs.color:red`.
+    pub fn add_json_field<T: Into<TextOptions>>(
+        &mut self,
+        field_name_str: &str,
+        field_options: T,
+    ) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_json(field_name, field_options.into());
+        self.add_field(field_entry)
+    }
+
     /// Adds a facet field to the schema.
     pub fn add_facet_field(&mut self, field_name: &str) -> Field {
         let field_entry = FieldEntry::new_facet(field_name.to_string());
         self.add_field(field_entry)
     }
 
-    /// Adds a fast bytes field to the schema.
+    /// Adds a bytes field to the schema.
     ///
-    /// Bytes field are not searchable and are only used
-    /// as fast field, to associate any kind of payload
+    /// As a fast field, a bytes field associates any kind of payload
     /// to a document.
-    ///
     /// For instance, learning-to-rank often requires to access
     /// some document features at scoring time.
     /// These can be serializing and stored as a bytes field to
     /// get access rapidly when scoring each document.
-    pub fn add_bytes_field(&mut self, field_name: &str) -> Field {
-        let field_entry = FieldEntry::new_bytes(field_name.to_string());
+    /// A multi-valued bytes fast field keeps several payloads per document.
+    ///
+    /// As an indexed field, a bytes field can be searched for
+    /// an exact binary value, e.g. a hash or a UUID, using
+    /// `Term::from_field_bytes`.
+    pub fn add_bytes_field<T: Into<BytesOptions>>(
+        &mut self,
+        field_name: &str,
+        field_options: T,
+    ) -> Field {
+        let field_entry = FieldEntry::new_bytes(field_name.to_string(), field_options.into());
         self.add_field(field_entry)
     }
 
@@ -633,7 +688,10 @@ mod tests {
             );
             assert_matches!(
                 json_err,
-                Err(DocParsingError::ValueError(_, ValueParsingError::TypeError(_)))
+                Err(DocParsingError::ValueError(
+                    _,
+                    ValueParsingError::TypeError(_)
+                ))
             );
         }
         {
@@ -648,7 +706,10 @@ mod tests {
             );
             assert_matches!(
                 json_err,
-                Err(DocParsingError::ValueError(_, ValueParsingError::OverflowError(_)))
+                Err(DocParsingError::ValueError(
+                    _,
+                    ValueParsingError::OverflowError(_)
+                ))
             );
         }
         {
@@ -663,7 +724,10 @@ mod tests {
             );
             assert!(!matches!(
                 json_err,
-                Err(DocParsingError::ValueError(_, ValueParsingError::OverflowError(_)))
+                Err(DocParsingError::ValueError(
+                    _,
+                    ValueParsingError::OverflowError(_)
+                ))
             ));
         }
         {
@@ -678,7 +742,10 @@ mod tests {
             );
             assert_matches!(
                 json_err,
-                Err(DocParsingError::ValueError(_, ValueParsingError::OverflowError(_)))
+                Err(DocParsingError::ValueError(
+                    _,
+                    ValueParsingError::OverflowError(_)
+                ))
             );
         }
         {
@@ -10,6 +10,31 @@ use std::str;
 /// Size (in bytes) of the buffer of a int field.
 const INT_TERM_LEN: usize = 4 + 8;
 
+/// Separates the segments of the path of a json value, within a term.
+pub(crate) const JSON_PATH_SEGMENT_SEP: u8 = 1u8;
+/// Ends the path of a json value, within a term.
+pub(crate) const JSON_END_OF_PATH: u8 = 0u8;
+
+/// Codes of the type of a json value, written right after its path.
+pub(crate) const JSON_TEXT_CODE: u8 = b's';
+pub(crate) const JSON_I64_CODE: u8 = b'i';
+pub(crate) const JSON_U64_CODE: u8 = b'u';
+pub(crate) const JSON_F64_CODE: u8 = b'f';
+
+/// Converts a path with dot separated segments, e.g. `attrs.color`,
+/// into its representation within a term.
+fn json_path_bytes(path: &str) -> Vec<u8> {
+    path.bytes()
+        .map(|byte| {
+            if byte == b'.' {
+                JSON_PATH_SEGMENT_SEP
+            } else {
+                byte
+            }
+        })
+        .collect()
+}
+
 /// Term represents the value that the token can take.
 ///
 /// It actually wraps a `Vec<u8>`.
@@ -93,6 +118,63 @@ impl Term {
         term
     }
 
+    /// Builds a term given a json field, the path of a value within the json
+    /// objects, and a text value.
+    ///
+    /// The segments of the path are separated by dots, e.g. `attrs.color`.
+    /// As for `from_field_text`, the text is not tokenized.
+    pub fn from_field_json_text(field: Field, path: &str, text: &str) -> Term {
+        let mut term = Term::for_json_path(field, &json_path_bytes(path), JSON_TEXT_CODE);
+        term.append_bytes(text.as_bytes());
+        term
+    }
+
+    /// Builds a term given a json field, the path of a value within the json
+    /// objects, and a i64-value.
+    ///
+    /// The integers of the json objects are indexed as i64-values,
+    /// unless they are too large.
+    pub fn from_field_json_i64(field: Field, path: &str, val: i64) -> Term {
+        Term::from_field_json_u64_code(field, path, JSON_I64_CODE, common::i64_to_u64(val))
+    }
+
+    /// Builds a term given a json field, the path of a value within the json
+    /// objects, and a u64-value.
+    ///
+    /// Only the integers of the json objects exceeding `i64::MAX`
+    /// are indexed as u64-values.
+    pub fn from_field_json_u64(field: Field, path: &str, val: u64) -> Term {
+        Term::from_field_json_u64_code(field, path, JSON_U64_CODE, val)
+    }
+
+    /// Builds a term given a json field, the path of a value within the json
+    /// objects, and a f64-value.
+    ///
+    /// Only the numbers of the json objects that are not integers
+    /// are indexed as f64-values.
+    pub fn from_field_json_f64(field: Field, path: &str, val: f64) -> Term {
+        Term::from_field_json_u64_code(field, path, JSON_F64_CODE, common::f64_to_u64(val))
+    }
+
+    fn from_field_json_u64_code(field: Field, path: &str, type_code: u8, val: u64) -> Term {
+        let mut term = Term::for_json_path(field, &json_path_bytes(path), type_code);
+        let mut val_bytes = [0u8; 8];
+        BigEndian::write_u64(&mut val_bytes, val);
+        term.append_bytes(&val_bytes);
+        term
+    }
+
+    /// Creates the prefix of the terms of the values of type `type_code`
+    /// found at `path` in a json field.
+    ///
+    /// The segments of `path` are separated by `JSON_PATH_SEGMENT_SEP`.
+    pub(crate) fn for_json_path(field: Field, path: &[u8], type_code: u8) -> Term {
+        let mut term = Term::for_field(field);
+        term.append_bytes(path);
+        term.append_bytes(&[JSON_END_OF_PATH, type_code]);
+        term
+    }
+
     /// Creates a new Term for a given field.
     pub(crate) fn for_field(field: Field) -> Term {
         let mut term = Term(Vec::with_capacity(100));
@@ -134,7 +216,23 @@ impl Term {
         self.0.extend(bytes);
     }
 
-    pub(crate) fn from_field_bytes(field: Field, bytes: &[u8]) -> Term {
+    /// Appends some bytes to the value of the term.
+    pub(crate) fn append_bytes(&mut self, bytes: &[u8]) {
+        self.0.extend(bytes);
+    }
+
+    /// Truncates the term to its first `len` bytes, including the field.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    /// Builds a term given a field, and a bytes value
+    ///
+    /// Assuming the term has a field id of 2, and a bytes value of `[7, 8]`,
+    /// the Term will have 6 bytes.
+    /// The first four bytes are encoding the field, and the two following
+    /// bytes are the value itself.
+    pub fn from_field_bytes(field: Field, bytes: &[u8]) -> Term {
         let mut term = Term::for_field(field);
         term.set_bytes(bytes);
         term
@@ -1,5 +1,5 @@
 use crate::schema::flags::SchemaFlagList;
-use crate::schema::flags::StoredFlag;
+use crate::schema::flags::{FastFlag, StoredFlag};
 use crate::schema::IndexRecordOption;
 use std::borrow::Cow;
 use std::ops::BitOr;
@@ -9,6 +9,12 @@ use std::ops::BitOr;
 pub struct TextOptions {
     indexing: Option<TextFieldIndexing>,
     stored: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    fast: bool,
+}
+
+fn is_false(val: &bool) -> bool {
+    !*val
 }
 
 impl TextOptions {
@@ -22,12 +28,34 @@ impl TextOptions {
         self.stored
     }
 
+    /// Returns true iff the term ordinals of the field are
+    /// stored as a fast field.
+    pub fn is_fast(&self) -> bool {
+        self.fast
+    }
+
     /// Sets the field as stored
     pub fn set_stored(mut self) -> TextOptions {
         self.stored = true;
         self
     }
 
+    /// Sets the field as a fast field.
+    ///
+    /// For each document, the ordinals of its terms in the
+    /// segment's term dictionary are stored in a multivalued
+    /// fast field. This makes it possible to sort or group
+    /// documents on a string field cheaply.
+    /// See [`StrFastFieldReader`](../fastfield/struct.StrFastFieldReader.html).
+    ///
+    /// The field needs to be indexed for its terms to be recorded.
+    /// Typically, it is used with the `STRING` options, so that
+    /// each value is a single term.
+    pub fn set_fast(mut self) -> TextOptions {
+        self.fast = true;
+        self
+    }
+
     /// Sets the field as indexed, with the specific indexing options.
     pub fn set_indexing_options(mut self, indexing: TextFieldIndexing) -> TextOptions {
         self.indexing = Some(indexing);
@@ -40,6 +68,7 @@ impl Default for TextOptions {
         TextOptions {
             indexing: None,
             stored: false,
+            fast: false,
         }
     }
 }
@@ -54,6 +83,14 @@ impl Default for TextOptions {
 pub struct TextFieldIndexing {
     record: IndexRecordOption,
     tokenizer: Cow<'static, str>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    exact_fieldnorms: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    offsets: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    payloads: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_doc_freq: Option<u32>,
 }
 
 impl Default for TextFieldIndexing {
@@ -61,6 +98,10 @@ impl Default for TextFieldIndexing {
         TextFieldIndexing {
             tokenizer: Cow::Borrowed("default"),
             record: IndexRecordOption::Basic,
+            exact_fieldnorms: false,
+            offsets: false,
+            payloads: false,
+            max_doc_freq: None,
         }
     }
 }
@@ -91,6 +132,82 @@ impl TextFieldIndexing {
     pub fn index_option(&self) -> IndexRecordOption {
         self.record
     }
+
+    /// Sets whether the exact number of tokens of the field
+    /// should be stored for each document.
+    ///
+    /// By default, the fieldnorm of a document is quantized on
+    /// one byte, which makes the BM25 length normalization coarse.
+    /// Exact fieldnorms are stored in an additional `u32` column,
+    /// used by the BM25 scorer instead of the quantized value.
+    pub fn set_exact_fieldnorms(mut self, exact_fieldnorms: bool) -> TextFieldIndexing {
+        self.exact_fieldnorms = exact_fieldnorms;
+        self
+    }
+
+    /// Returns true iff the exact fieldnorms are stored for this field.
+    pub fn exact_fieldnorms(&self) -> bool {
+        self.exact_fieldnorms
+    }
+
+    /// Sets whether the byte offsets of the tokens should be stored
+    /// along with their positions.
+    ///
+    /// Offsets map each occurrence of a term back to the original text,
+    /// even if the token was altered by the tokenizer (stemming, ngrams...),
+    /// which makes exact highlighting possible.
+    /// They are only stored if the positions are indexed
+    /// (See [`IndexRecordOption::WithFreqsAndPositions`](./enum.IndexRecordOption.html)),
+    /// and are always stored with `IndexRecordOption::WithFreqsAndPositionsAndOffsets`.
+    pub fn set_offsets(mut self, offsets: bool) -> TextFieldIndexing {
+        self.offsets = offsets;
+        self
+    }
+
+    /// Returns true iff the byte offsets of the tokens are stored for this field.
+    pub fn has_offsets(&self) -> bool {
+        self.record.has_offsets() || (self.offsets && self.record.has_positions())
+    }
+
+    /// Sets whether the payloads of the tokens should be stored
+    /// along with their positions.
+    ///
+    /// A payload is a small value attached to a token by the tokenizer
+    /// (See [`Token::payload`](../tokenizer/struct.Token.html#structfield.payload)),
+    /// e.g. the weight of the section of the document containing the token.
+    /// Payloads can then influence the score of the documents
+    /// (See [`PayloadScoreQuery`](../query/struct.PayloadScoreQuery.html)).
+    /// They are only stored if the positions are indexed.
+    pub fn set_payloads(mut self, payloads: bool) -> TextFieldIndexing {
+        self.payloads = payloads;
+        self
+    }
+
+    /// Returns true iff the payloads of the tokens are stored for this field.
+    pub fn has_payloads(&self) -> bool {
+        self.payloads && self.record.has_positions()
+    }
+
+    /// Stops indexing the postings of the terms that appear in more
+    /// than `max_doc_freq` documents of a segment.
+    ///
+    /// Such terms behave as stopwords: they do not match any document,
+    /// but they are still accounted for in the fieldnorms, and their
+    /// document frequency is kept in the term dictionary, so that they
+    /// still weigh in the term statistics.
+    ///
+    /// This keeps the index of stopword-heavy corpora small, without
+    /// requiring a fixed list of stopwords.
+    pub fn set_max_doc_freq(mut self, max_doc_freq: u32) -> TextFieldIndexing {
+        self.max_doc_freq = Some(max_doc_freq);
+        self
+    }
+
+    /// Returns the document frequency above which the postings of a term
+    /// are not indexed, if any.
+    pub fn max_doc_freq(&self) -> Option<u32> {
+        self.max_doc_freq
+    }
 }
 
 /// The field will be untokenized and indexed
@@ -98,8 +215,13 @@ pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: Cow::Borrowed("raw"),
         record: IndexRecordOption::Basic,
+        exact_fieldnorms: false,
+        offsets: false,
+        payloads: false,
+        max_doc_freq: None,
     }),
     stored: false,
+    fast: false,
 };
 
 /// The field will be tokenized and indexed
@@ -107,8 +229,13 @@ pub const TEXT: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: Cow::Borrowed("default"),
         record: IndexRecordOption::WithFreqsAndPositions,
+        exact_fieldnorms: false,
+        offsets: false,
+        payloads: false,
+        max_doc_freq: None,
     }),
     stored: false,
+    fast: false,
 };
 
 impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
@@ -119,6 +246,7 @@ impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
         let mut res = TextOptions::default();
         res.indexing = self.indexing.or(other.indexing);
         res.stored = self.stored | other.stored;
+        res.fast = self.fast | other.fast;
         res
     }
 }
@@ -134,6 +262,17 @@ impl From<StoredFlag> for TextOptions {
         TextOptions {
             indexing: None,
             stored: true,
+            fast: false,
+        }
+    }
+}
+
+impl From<FastFlag> for TextOptions {
+    fn from(_: FastFlag) -> TextOptions {
+        TextOptions {
+            indexing: None,
+            stored: false,
+            fast: true,
         }
     }
 }
@@ -181,9 +320,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fast_flag() {
+        let field_options = STRING | FAST;
+        assert!(field_options.is_fast());
+        assert!(field_options.get_indexing_options().is_some());
+        assert!(!STRING.is_fast());
+        assert!(!(STRING | STORED).is_fast());
+        let json = serde_json::to_string(&field_options).unwrap();
+        let deserialized: TextOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, field_options);
+        assert!(!serde_json::to_string(&STRING).unwrap().contains("fast"));
+    }
+
     #[test]
     fn test_cmp_index_record_option() {
+        assert!(
+            IndexRecordOption::WithFreqsAndPositionsAndOffsets
+                > IndexRecordOption::WithFreqsAndPositions
+        );
         assert!(IndexRecordOption::WithFreqsAndPositions > IndexRecordOption::WithFreqs);
         assert!(IndexRecordOption::WithFreqs > IndexRecordOption::Basic);
     }
+
+    #[test]
+    fn test_offsets_index_record_option() {
+        let indexing_options = TextFieldIndexing::default()
+            .set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets);
+        assert!(indexing_options.has_offsets());
+        assert!(!TextFieldIndexing::default()
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+            .has_offsets());
+        let json = serde_json::to_string(&indexing_options).unwrap();
+        assert!(json.contains("\"record\":\"offsets\""));
+    }
 }
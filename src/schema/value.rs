@@ -1,12 +1,15 @@
 use crate::schema::Facet;
+use crate::schema::GeoPoint;
 use crate::DateTime;
-use serde::de::Visitor;
+use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Map as JsonObject;
+use serde_json::Value as JsonValue;
 use std::{cmp::Ordering, fmt};
 
 /// Value represents the value of a any field.
 /// It is an enum over all over all of the possible field type.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     /// The str type is used for any text information.
     Str(String),
@@ -22,9 +25,20 @@ pub enum Value {
     Facet(Facet),
     /// Arbitrarily sized byte array
     Bytes(Vec<u8>),
+    /// Geo point
+    GeoPoint(GeoPoint),
+    /// Json object
+    JsonObject(JsonObject<String, JsonValue>),
 }
 
 impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Ord for Value {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
@@ -34,6 +48,13 @@ impl Ord for Value {
             (Value::Date(l), Value::Date(r)) => l.cmp(r),
             (Value::Facet(l), Value::Facet(r)) => l.cmp(r),
             (Value::Bytes(l), Value::Bytes(r)) => l.cmp(r),
+            (Value::GeoPoint(l), Value::GeoPoint(r)) => l.partial_cmp(r).unwrap_or(Ordering::Equal),
+            (Value::JsonObject(l), Value::JsonObject(r)) => {
+                // Json objects have no natural order, their text form is used instead.
+                let l = serde_json::to_string(l).unwrap_or_default();
+                let r = serde_json::to_string(r).unwrap_or_default();
+                l.cmp(&r)
+            }
             (Value::F64(l), Value::F64(r)) => {
                 match (l.is_nan(), r.is_nan()) {
                     (false, false) => l.partial_cmp(r).unwrap(), // only fail on NaN
@@ -54,6 +75,10 @@ impl Ord for Value {
             (_, Value::Date(_)) => Ordering::Greater,
             (Value::Facet(_), _) => Ordering::Less,
             (_, Value::Facet(_)) => Ordering::Greater,
+            (Value::Bytes(_), _) => Ordering::Less,
+            (_, Value::Bytes(_)) => Ordering::Greater,
+            (Value::GeoPoint(_), _) => Ordering::Less,
+            (_, Value::GeoPoint(_)) => Ordering::Greater,
         }
     }
 }
@@ -71,6 +96,8 @@ impl Serialize for Value {
             Value::Date(ref date) => serializer.serialize_i64(date.timestamp()),
             Value::Facet(ref facet) => facet.serialize(serializer),
             Value::Bytes(ref bytes) => serializer.serialize_bytes(bytes),
+            Value::GeoPoint(ref geo_point) => geo_point.serialize(serializer),
+            Value::JsonObject(ref json_object) => json_object.serialize(serializer),
         }
     }
 }
@@ -108,6 +135,15 @@ impl<'de> Deserialize<'de> for Value {
             fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
                 Ok(Value::Str(v))
             }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let json_object =
+                    JsonObject::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+                Ok(Value::JsonObject(json_object))
+            }
         }
 
         deserializer.deserialize_any(ValueVisitor)
@@ -167,6 +203,26 @@ impl Value {
             _ => panic!("This is not a date field."),
         }
     }
+
+    /// Returns the geo point, provided the value is of the `GeoPoint` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `GeoPoint`
+    pub fn geo_point_value(&self) -> GeoPoint {
+        match *self {
+            Value::GeoPoint(value) => value,
+            _ => panic!("This is not a geo point field."),
+        }
+    }
+
+    /// Returns the json object, provided the value is of the `JsonObject` type.
+    /// (Returns None if the value is not of the `JsonObject` type).
+    pub fn json_object(&self) -> Option<&JsonObject<String, JsonValue>> {
+        match *self {
+            Value::JsonObject(ref json_object) => Some(json_object),
+            _ => None,
+        }
+    }
 }
 
 impl From<String> for Value {
@@ -217,10 +273,22 @@ impl From<Vec<u8>> for Value {
     }
 }
 
+impl From<GeoPoint> for Value {
+    fn from(geo_point: GeoPoint) -> Value {
+        Value::GeoPoint(geo_point)
+    }
+}
+
+impl From<JsonObject<String, JsonValue>> for Value {
+    fn from(json_object: JsonObject<String, JsonValue>) -> Value {
+        Value::JsonObject(json_object)
+    }
+}
+
 mod binary_serialize {
     use super::Value;
     use crate::common::{f64_to_u64, u64_to_f64, BinarySerializable};
-    use crate::schema::Facet;
+    use crate::schema::{Facet, GeoPoint};
     use chrono::{TimeZone, Utc};
     use std::io::{self, Read, Write};
 
@@ -231,6 +299,8 @@ mod binary_serialize {
     const BYTES_CODE: u8 = 4;
     const DATE_CODE: u8 = 5;
     const F64_CODE: u8 = 6;
+    const GEO_POINT_CODE: u8 = 7;
+    const JSON_OBJECT_CODE: u8 = 8;
 
     impl BinarySerializable for Value {
         fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
@@ -263,6 +333,15 @@ mod binary_serialize {
                     BYTES_CODE.serialize(writer)?;
                     bytes.serialize(writer)
                 }
+                Value::GeoPoint(ref geo_point) => {
+                    GEO_POINT_CODE.serialize(writer)?;
+                    f64_to_u64(geo_point.lat).serialize(writer)?;
+                    f64_to_u64(geo_point.lon).serialize(writer)
+                }
+                Value::JsonObject(ref json_object) => {
+                    JSON_OBJECT_CODE.serialize(writer)?;
+                    serde_json::to_string(json_object)?.serialize(writer)
+                }
             }
         }
         fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
@@ -290,6 +369,16 @@ mod binary_serialize {
                 }
                 HIERARCHICAL_FACET_CODE => Ok(Value::Facet(Facet::deserialize(reader)?)),
                 BYTES_CODE => Ok(Value::Bytes(Vec::<u8>::deserialize(reader)?)),
+                GEO_POINT_CODE => {
+                    let lat = u64_to_f64(u64::deserialize(reader)?);
+                    let lon = u64_to_f64(u64::deserialize(reader)?);
+                    Ok(Value::GeoPoint(GeoPoint { lat, lon }))
+                }
+                JSON_OBJECT_CODE => {
+                    let text = String::deserialize(reader)?;
+                    let json_object = serde_json::from_str(&text)?;
+                    Ok(Value::JsonObject(json_object))
+                }
                 _ => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     format!("No field type is associated with code {:?}", type_code),
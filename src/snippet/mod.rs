@@ -1,8 +1,10 @@
+use crate::docset::{DocSet, SkipResult};
 use crate::query::Query;
-use crate::schema::Field;
 use crate::schema::Value;
+use crate::schema::{Field, IndexRecordOption, Term};
 use crate::tokenizer::BoxedTokenizer;
 use crate::tokenizer::{Token, TokenStream};
+use crate::DocAddress;
 use crate::Document;
 use crate::Result;
 use crate::Searcher;
@@ -77,6 +79,8 @@ impl FragmentCandidate {
 pub struct Snippet {
     fragments: String,
     highlighted: Vec<HighlightSection>,
+    highlight_prefix: String,
+    highlight_postfix: String,
 }
 
 const HIGHLIGHTEN_PREFIX: &str = "<b>";
@@ -85,12 +89,26 @@ const HIGHLIGHTEN_POSTFIX: &str = "</b>";
 impl Snippet {
     /// Create a new, empty, `Snippet`
     pub fn empty() -> Snippet {
+        Snippet::new(String::new(), Vec::new())
+    }
+
+    fn new(fragments: String, highlighted: Vec<HighlightSection>) -> Snippet {
         Snippet {
-            fragments: String::new(),
-            highlighted: Vec::new(),
+            fragments,
+            highlighted,
+            highlight_prefix: HIGHLIGHTEN_PREFIX.to_string(),
+            highlight_postfix: HIGHLIGHTEN_POSTFIX.to_string(),
         }
     }
 
+    /// Sets the tags surrounding the highlighted parts in `to_html`.
+    ///
+    /// They default to `<b>` and `</b>`, and are not escaped.
+    pub fn set_highlight_tags(&mut self, prefix: &str, postfix: &str) {
+        self.highlight_prefix = prefix.to_string();
+        self.highlight_postfix = postfix.to_string();
+    }
+
     /// Returns a hignlightned html from the `Snippet`.
     pub fn to_html(&self) -> String {
         let mut html = String::new();
@@ -98,9 +116,9 @@ impl Snippet {
 
         for item in self.highlighted.iter() {
             html.push_str(&encode_minimal(&self.fragments[start_from..item.start]));
-            html.push_str(HIGHLIGHTEN_PREFIX);
+            html.push_str(&self.highlight_prefix);
             html.push_str(&encode_minimal(&self.fragments[item.start..item.stop]));
-            html.push_str(HIGHLIGHTEN_POSTFIX);
+            html.push_str(&self.highlight_postfix);
             start_from = item.stop;
         }
         html.push_str(&encode_minimal(
@@ -166,48 +184,127 @@ fn search_fragments<'a>(
     fragments
 }
 
+/// Same as `search_fragments`, but the target terms are located by
+/// the byte offsets `(offset_from, offset_to, score)` of their occurences,
+/// sorted and non-overlapping.
+///
+/// Each fragment is extended past its last occurence, up to `max_num_chars` bytes
+/// and without cutting a word, so that the fragments have some context as
+/// the ones of `search_fragments`.
+fn search_fragments_from_offsets(
+    text: &str,
+    occurences: &[(usize, usize, f32)],
+    max_num_chars: usize,
+) -> Vec<FragmentCandidate> {
+    let mut fragment = FragmentCandidate::new(0);
+    let mut fragments: Vec<FragmentCandidate> = vec![];
+    for &(offset_from, offset_to, score) in occurences {
+        if (offset_to - fragment.start_offset) > max_num_chars && fragment.score > 0.0 {
+            fragments.push(fragment);
+            fragment = FragmentCandidate::new(offset_from);
+        }
+        fragment.stop_offset = offset_to;
+        fragment.score += score;
+        fragment
+            .highlighted
+            .push(HighlightSection::new(offset_from, offset_to));
+    }
+    if fragment.score > 0.0 {
+        fragments.push(fragment)
+    }
+    let fragment_limits: Vec<usize> = fragments
+        .iter()
+        .skip(1)
+        .map(|fragment| fragment.start_offset)
+        .chain(Some(text.len()))
+        .collect();
+    for (fragment, &fragment_limit) in fragments.iter_mut().zip(fragment_limits.iter()) {
+        let mut limit = fragment_limit.min(fragment.start_offset + max_num_chars);
+        if limit <= fragment.stop_offset {
+            continue;
+        }
+        while !text.is_char_boundary(limit) {
+            limit -= 1;
+        }
+        if limit < text.len() {
+            // the fragment ends at the last whitespace, as it would on a token.
+            limit = text[fragment.stop_offset..limit]
+                .rfind(char::is_whitespace)
+                .map(|pos| fragment.stop_offset + pos)
+                .unwrap_or(fragment.stop_offset);
+        }
+        fragment.stop_offset = limit;
+    }
+    fragments
+}
+
+// Orders fragments by score. Among fragments with the same score, the
+// earliest one in the text is considered the best.
+fn cmp_fragments(left: &FragmentCandidate, right: &FragmentCandidate) -> Ordering {
+    let cmp_score = left
+        .score
+        .partial_cmp(&right.score)
+        .unwrap_or(Ordering::Equal);
+    if cmp_score == Ordering::Equal {
+        (right.start_offset, right.stop_offset).cmp(&(left.start_offset, left.stop_offset))
+    } else {
+        cmp_score
+    }
+}
+
+/// Creates the `Snippet` of a fragment, with highlighted sections
+/// relative to the beginning of the fragment.
+fn snippet_from_fragment(fragment: &FragmentCandidate, text: &str) -> Snippet {
+    let fragment_text = &text[fragment.start_offset..fragment.stop_offset];
+    let highlighted = fragment
+        .highlighted
+        .iter()
+        .map(|item| {
+            HighlightSection::new(
+                item.start - fragment.start_offset,
+                item.stop - fragment.start_offset,
+            )
+        })
+        .collect();
+    Snippet::new(fragment_text.to_string(), highlighted)
+}
+
 /// Returns a Snippet
 ///
 /// Takes a vector of `FragmentCandidate`s and the text.
 /// Figures out the best fragment from it and creates a snippet.
 fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str) -> Snippet {
-    let best_fragment_opt = fragments.iter().max_by(|left, right| {
-        let cmp_score = left
-            .score
-            .partial_cmp(&right.score)
-            .unwrap_or(Ordering::Equal);
-        if cmp_score == Ordering::Equal {
-            (right.start_offset, right.stop_offset).cmp(&(left.start_offset, left.stop_offset))
-        } else {
-            cmp_score
-        }
-    });
+    let best_fragment_opt = fragments
+        .iter()
+        .max_by(|left, right| cmp_fragments(left, right));
     if let Some(fragment) = best_fragment_opt {
-        let fragment_text = &text[fragment.start_offset..fragment.stop_offset];
-        let highlighted = fragment
-            .highlighted
-            .iter()
-            .map(|item| {
-                HighlightSection::new(
-                    item.start - fragment.start_offset,
-                    item.stop - fragment.start_offset,
-                )
-            })
-            .collect();
-        Snippet {
-            fragments: fragment_text.to_string(),
-            highlighted,
-        }
+        snippet_from_fragment(fragment, text)
     } else {
         // when there no fragments to chose from,
         // for now create a empty snippet
-        Snippet {
-            fragments: String::new(),
-            highlighted: vec![],
-        }
+        Snippet::empty()
     }
 }
 
+/// Returns the `Snippet`s of the `max_num_fragments` best fragments,
+/// by decreasing score.
+///
+/// The fragments emitted by `search_fragments` do not overlap,
+/// so neither do the returned snippets.
+fn select_best_fragments(
+    fragments: &[FragmentCandidate],
+    text: &str,
+    max_num_fragments: usize,
+) -> Vec<Snippet> {
+    let mut sorted_fragments: Vec<&FragmentCandidate> = fragments.iter().collect();
+    sorted_fragments.sort_by(|left, right| cmp_fragments(right, left));
+    sorted_fragments
+        .into_iter()
+        .take(max_num_fragments)
+        .map(|fragment| snippet_from_fragment(fragment, text))
+        .collect()
+}
+
 /// `SnippetGenerator`
 ///
 /// # Example
@@ -246,6 +343,10 @@ fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str)
 /// let snippet = snippet_generator.snippet_from_doc(&doc);
 /// let snippet_html: String = snippet.to_html();
 /// assert_eq!(snippet_html, "Comme je descendais des Fleuves impassibles,\n  Je ne me sentis plus guidé par les <b>haleurs</b> :\n Des");
+///
+/// snippet_generator.set_highlight_tags("<em class=\"hit\">", "</em>");
+/// let snippet = snippet_generator.snippet_from_doc(&doc);
+/// assert!(snippet.to_html().contains("<em class=\"hit\">haleurs</em>"));
 /// #    Ok(())
 /// # }
 /// ```
@@ -254,6 +355,7 @@ pub struct SnippetGenerator {
     tokenizer: BoxedTokenizer,
     field: Field,
     max_num_chars: usize,
+    highlight_tags: Option<(String, String)>,
 }
 
 impl SnippetGenerator {
@@ -284,6 +386,7 @@ impl SnippetGenerator {
             tokenizer,
             field,
             max_num_chars: DEFAULT_MAX_NUM_CHARS,
+            highlight_tags: None,
         })
     }
 
@@ -292,6 +395,20 @@ impl SnippetGenerator {
         self.max_num_chars = max_num_chars;
     }
 
+    /// Sets the tags surrounding the highlighted parts of the generated
+    /// snippets, e.g. `<em>` and `</em>`.
+    ///
+    /// See [`Snippet::set_highlight_tags`](./struct.Snippet.html#method.set_highlight_tags).
+    pub fn set_highlight_tags(&mut self, prefix: &str, postfix: &str) {
+        self.highlight_tags = Some((prefix.to_string(), postfix.to_string()));
+    }
+
+    fn apply_highlight_tags(&self, snippet: &mut Snippet) {
+        if let Some((ref prefix, ref postfix)) = self.highlight_tags {
+            snippet.set_highlight_tags(prefix, postfix);
+        }
+    }
+
     #[cfg(test)]
     pub fn terms_text(&self) -> &BTreeMap<String, f32> {
         &self.terms_text
@@ -314,19 +431,131 @@ impl SnippetGenerator {
     /// Generates a snippet for the given text.
     pub fn snippet(&self, text: &str) -> Snippet {
         let fragment_candidates =
-            search_fragments(&self.tokenizer, &text, &self.terms_text, self.max_num_chars);
-        select_best_fragment_combination(&fragment_candidates[..], &text)
+            search_fragments(&self.tokenizer, text, &self.terms_text, self.max_num_chars);
+        let mut snippet = select_best_fragment_combination(&fragment_candidates[..], text);
+        self.apply_highlight_tags(&mut snippet);
+        snippet
+    }
+
+    /// Generates a snippet for the document at `doc_address`.
+    ///
+    /// The terms are located using the byte offsets stored in the index,
+    /// instead of analyzing the text of the document again: they are highlighted
+    /// exactly, even if the tokenizer of the field changed (stemming, synonyms...).
+    ///
+    /// The field must be stored, and its offsets indexed
+    /// (See [`IndexRecordOption::WithFreqsAndPositionsAndOffsets`](
+    ///     ./schema/enum.IndexRecordOption.html)).
+    /// Otherwise, the snippet is computed as in `snippet_from_doc`.
+    pub fn snippet_from_doc_address(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+    ) -> Result<Snippet> {
+        let doc = searcher.doc(doc_address)?;
+        let DocAddress(segment_ord, doc_id) = doc_address;
+        let segment_reader = searcher.segment_reader(segment_ord);
+        let has_offsets = segment_reader
+            .schema()
+            .get_field_entry(self.field)
+            .field_type()
+            .has_offsets();
+        if !has_offsets {
+            return Ok(self.snippet_from_doc(&doc));
+        }
+        let texts: Vec<&str> = doc
+            .get_all(self.field)
+            .into_iter()
+            .flat_map(Value::text)
+            .collect();
+        // The offsets are expressed in the concatenation of the values of the field,
+        // while the values are joined by a space in the text of the snippet.
+        let mut value_starts = Vec::with_capacity(texts.len());
+        let mut value_start = 0;
+        for text in &texts {
+            value_starts.push(value_start);
+            value_start += text.len();
+        }
+        let text = texts.join(" ");
+        let inverted_index = segment_reader.inverted_index(self.field);
+        let mut occurences: Vec<(usize, usize, f32)> = vec![];
+        let mut offsets = vec![];
+        for (term_text, &score) in &self.terms_text {
+            let term = Term::from_field_text(self.field, term_text);
+            let mut postings = match inverted_index
+                .read_postings(&term, IndexRecordOption::WithFreqsAndPositionsAndOffsets)
+            {
+                Some(postings) => postings,
+                None => continue,
+            };
+            if postings.skip_next(doc_id) != SkipResult::Reached {
+                continue;
+            }
+            postings.offsets(&mut offsets);
+            for &(offset_from, offset_to) in &offsets {
+                let (offset_from, offset_to) = (offset_from as usize, offset_to as usize);
+                let value_ord = value_starts
+                    .iter()
+                    .take_while(|&&value_start| value_start <= offset_from)
+                    .count()
+                    .saturating_sub(1);
+                occurences.push((offset_from + value_ord, offset_to + value_ord, score));
+            }
+        }
+        occurences.sort_by_key(|&(offset_from, offset_to, _)| (offset_from, offset_to));
+        // Stacked tokens may overlap.
+        let mut last_offset_to = 0;
+        occurences.retain(|&(offset_from, offset_to, _)| {
+            if offset_from < last_offset_to || offset_to > text.len() {
+                return false;
+            }
+            last_offset_to = offset_to;
+            true
+        });
+        let fragment_candidates =
+            search_fragments_from_offsets(&text, &occurences, self.max_num_chars);
+        let mut snippet = select_best_fragment_combination(&fragment_candidates[..], &text);
+        self.apply_highlight_tags(&mut snippet);
+        Ok(snippet)
+    }
+
+    /// Generates up to `max_num_fragments` snippets for the given `Document`,
+    /// as `snippet_from_doc` would.
+    pub fn snippets_from_doc(&self, doc: &Document, max_num_fragments: usize) -> Vec<Snippet> {
+        let text: String = doc
+            .get_all(self.field)
+            .into_iter()
+            .flat_map(Value::text)
+            .collect::<Vec<&str>>()
+            .join(" ");
+        self.snippets(&text, max_num_fragments)
+    }
+
+    /// Generates up to `max_num_fragments` snippets for the given text.
+    ///
+    /// Snippets do not overlap, and are sorted by decreasing score:
+    /// the first one is the snippet returned by `.snippet(text)`.
+    /// If the text does not contain any of the query terms,
+    /// the returned `Vec` is empty.
+    pub fn snippets(&self, text: &str, max_num_fragments: usize) -> Vec<Snippet> {
+        let fragment_candidates =
+            search_fragments(&self.tokenizer, text, &self.terms_text, self.max_num_chars);
+        let mut snippets = select_best_fragments(&fragment_candidates[..], text, max_num_fragments);
+        for snippet in &mut snippets {
+            self.apply_highlight_tags(snippet);
+        }
+        snippets
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{search_fragments, select_best_fragment_combination};
+    use super::{search_fragments, select_best_fragment_combination, select_best_fragments};
     use crate::query::QueryParser;
     use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, TEXT};
     use crate::tokenizer::SimpleTokenizer;
-    use crate::Index;
     use crate::SnippetGenerator;
+    use crate::{DocAddress, Index};
     use maplit::btreemap;
     use std::collections::BTreeMap;
     use std::iter::Iterator;
@@ -600,4 +829,102 @@ Survey in 2016, 2017, and 2018."#;
             assert_eq!(snippet.to_html(), "<b>Rust</b> is syntactically similar to C++[according to whom?],\nbut its <b>designers</b> intend it to");
         }
     }
+
+    #[test]
+    fn test_select_best_fragments() {
+        let boxed_tokenizer = SimpleTokenizer.into();
+        let text = "a b c d e f g h";
+        let terms = btreemap! {
+            String::from("a") => 0.5,
+            String::from("e") => 1.0,
+            String::from("g") => 0.5,
+        };
+        let fragments = search_fragments(&boxed_tokenizer, text, &terms, 3);
+        assert_eq!(fragments.len(), 3);
+        let snippets = select_best_fragments(&fragments[..], text, 2);
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].to_html(), "<b>e</b> f");
+        assert_eq!(snippets[1].to_html(), "<b>a</b> b");
+        assert_eq!(snippets[1].highlighted()[0].bounds(), (0, 1));
+        assert_eq!(select_best_fragments(&fragments[..], text, 10).len(), 3);
+        assert!(select_best_fragments(&[], text, 2).is_empty());
+    }
+
+    #[test]
+    fn test_snippet_generator_snippets() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let doc = doc!(text_field => TEST_TEXT);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc.clone());
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.reader().unwrap().searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("rust").unwrap();
+        let mut snippet_generator =
+            SnippetGenerator::create(&searcher, &*query, text_field).unwrap();
+        snippet_generator.set_max_num_chars(50);
+        let snippets = snippet_generator.snippets_from_doc(&doc, 3);
+        assert_eq!(snippets.len(), 3);
+        assert_eq!(
+            snippets[0].to_html(),
+            snippet_generator.snippet(TEST_TEXT).to_html()
+        );
+        for snippet in &snippets {
+            assert!(snippet.to_html().contains("<b>Rust</b>"));
+        }
+        let query = query_parser.parse_query("javascript").unwrap();
+        let snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field).unwrap();
+        assert!(snippet_generator.snippets(TEST_TEXT, 3).is_empty());
+    }
+
+    #[test]
+    fn test_snippet_highlight_tags() {
+        let boxed_tokenizer = SimpleTokenizer.into();
+        let text = "a <b> c";
+        let terms = btreemap! { String::from("b") => 1.0 };
+        let fragments = search_fragments(&boxed_tokenizer, text, &terms, 10);
+        let mut snippet = select_best_fragment_combination(&fragments[..], text);
+        assert_eq!(snippet.to_html(), "a &lt;<b>b</b>&gt; c");
+        snippet.set_highlight_tags("<mark>", "</mark>");
+        assert_eq!(snippet.to_html(), "a &lt;<mark>b</mark>&gt; c");
+    }
+
+    #[test]
+    fn test_snippet_from_doc_address() {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("en_stem")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets),
+            )
+            .set_stored();
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => TEST_TEXT));
+        index_writer.add_document(doc!(text_field => "A designer", text_field => "of Rust"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("rust design").unwrap();
+        // The text is not analyzed again.
+        index.tokenizers().register("en_stem", SimpleTokenizer);
+        let mut snippet_generator =
+            SnippetGenerator::create(&searcher, &*query, text_field).unwrap();
+        snippet_generator.set_max_num_chars(90);
+        let snippet = snippet_generator
+            .snippet_from_doc_address(&searcher, DocAddress(0, 0))
+            .unwrap();
+        assert_eq!(snippet.to_html(), "<b>Rust</b> is syntactically similar to C++[according to whom?],\nbut its <b>designers</b> intend it to");
+        let snippet = snippet_generator
+            .snippet_from_doc_address(&searcher, DocAddress(0, 1))
+            .unwrap();
+        assert_eq!(snippet.to_html(), "A <b>designer</b> of <b>Rust</b>");
+    }
 }
@@ -11,6 +11,7 @@ under-count actual resultant space usage by up to 4095 bytes per file.
 
 use crate::schema::Field;
 use crate::SegmentComponent;
+use crate::SegmentId;
 use std::collections::HashMap;
 
 /// Indicates space usage in bytes
@@ -53,6 +54,19 @@ impl SearcherSpaceUsage {
         &self.segments[..]
     }
 
+    /// Space usage of each field, summed over all segments.
+    ///
+    /// See [`SegmentSpaceUsage::per_field`](./struct.SegmentSpaceUsage.html#method.per_field).
+    pub fn per_field(&self) -> HashMap<Field, FieldSpaceUsage> {
+        let mut per_field: HashMap<Field, FieldSpaceUsage> = HashMap::new();
+        for segment in &self.segments {
+            for (field, field_space_usage) in segment.per_field() {
+                per_field.entry(field).or_default().add(&field_space_usage);
+            }
+        }
+        per_field
+    }
+
     /// Returns total byte usage of this searcher, including all large subcomponents.
     /// Does not account for smaller things like `meta.json`.
     pub fn total(&self) -> ByteCount {
@@ -63,6 +77,7 @@ impl SearcherSpaceUsage {
 /// Represents combined space usage for all of the large components comprising a segment.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SegmentSpaceUsage {
+    segment_id: SegmentId,
     num_docs: u32,
 
     termdict: PerFieldSpaceUsage,
@@ -82,6 +97,7 @@ pub struct SegmentSpaceUsage {
 impl SegmentSpaceUsage {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
+        segment_id: SegmentId,
         num_docs: u32,
         termdict: PerFieldSpaceUsage,
         postings: PerFieldSpaceUsage,
@@ -95,11 +111,13 @@ impl SegmentSpaceUsage {
         let total = termdict.total()
             + postings.total()
             + positions.total()
+            + positions_idx.total()
             + fast_fields.total()
             + fieldnorms.total()
             + store.total()
             + deletes;
         SegmentSpaceUsage {
+            segment_id,
             num_docs,
             termdict,
             postings,
@@ -132,11 +150,43 @@ impl SegmentSpaceUsage {
         }
     }
 
+    /// Id of the segment
+    pub fn segment_id(&self) -> SegmentId {
+        self.segment_id
+    }
+
     /// Num docs in segment
     pub fn num_docs(&self) -> u32 {
         self.num_docs
     }
 
+    /// Space usage of each field of the segment, broken down by component.
+    ///
+    /// The store and the delete bitset are not attributed to any field,
+    /// so the sum of the fields' totals is lower than `self.total()`.
+    pub fn per_field(&self) -> HashMap<Field, FieldSpaceUsage> {
+        let mut per_field: HashMap<Field, FieldSpaceUsage> = HashMap::new();
+        add_field_usages(&mut per_field, &self.termdict, |usage, num_bytes| {
+            usage.termdict += num_bytes
+        });
+        add_field_usages(&mut per_field, &self.postings, |usage, num_bytes| {
+            usage.postings += num_bytes
+        });
+        add_field_usages(&mut per_field, &self.positions, |usage, num_bytes| {
+            usage.positions += num_bytes
+        });
+        add_field_usages(&mut per_field, &self.positions_idx, |usage, num_bytes| {
+            usage.positions_idx += num_bytes
+        });
+        add_field_usages(&mut per_field, &self.fast_fields, |usage, num_bytes| {
+            usage.fast_fields += num_bytes
+        });
+        add_field_usages(&mut per_field, &self.fieldnorms, |usage, num_bytes| {
+            usage.fieldnorms += num_bytes
+        });
+        per_field
+    }
+
     /// Space usage for term dictionary
     pub fn termdict(&self) -> &PerFieldSpaceUsage {
         &self.termdict
@@ -183,6 +233,79 @@ impl SegmentSpaceUsage {
     }
 }
 
+fn add_field_usages(
+    per_field: &mut HashMap<Field, FieldSpaceUsage>,
+    component: &PerFieldSpaceUsage,
+    add: fn(&mut FieldSpaceUsage, ByteCount),
+) {
+    for (field, field_usage) in component.fields() {
+        let field_space_usage = per_field.entry(*field).or_default();
+        add(field_space_usage, field_usage.total());
+    }
+}
+
+/// Represents the space usage of a field, broken down by component.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FieldSpaceUsage {
+    termdict: ByteCount,
+    postings: ByteCount,
+    positions: ByteCount,
+    positions_idx: ByteCount,
+    fast_fields: ByteCount,
+    fieldnorms: ByteCount,
+}
+
+impl FieldSpaceUsage {
+    fn add(&mut self, other: &FieldSpaceUsage) {
+        self.termdict += other.termdict;
+        self.postings += other.postings;
+        self.positions += other.positions;
+        self.positions_idx += other.positions_idx;
+        self.fast_fields += other.fast_fields;
+        self.fieldnorms += other.fieldnorms;
+    }
+
+    /// Space usage of the field in the term dictionary
+    pub fn termdict(&self) -> ByteCount {
+        self.termdict
+    }
+
+    /// Space usage of the field in the postings
+    pub fn postings(&self) -> ByteCount {
+        self.postings
+    }
+
+    /// Space usage of the field in the positions
+    pub fn positions(&self) -> ByteCount {
+        self.positions
+    }
+
+    /// Space usage of the field in the positions skip idx
+    pub fn positions_skip_idx(&self) -> ByteCount {
+        self.positions_idx
+    }
+
+    /// Space usage of the field in the fast fields
+    pub fn fast_fields(&self) -> ByteCount {
+        self.fast_fields
+    }
+
+    /// Space usage of the field in the field norms
+    pub fn fieldnorms(&self) -> ByteCount {
+        self.fieldnorms
+    }
+
+    /// Total space usage in bytes for this field
+    pub fn total(&self) -> ByteCount {
+        self.termdict
+            + self.postings
+            + self.positions
+            + self.positions_idx
+            + self.fast_fields
+            + self.fieldnorms
+    }
+}
+
 /// Represents space usage for the Store for this segment.
 ///
 /// This is composed of two parts.
@@ -290,6 +413,120 @@ impl FieldUsage {
     }
 }
 
+/// Represents the estimated memory footprint of an open searcher and of its segments.
+///
+/// Most of the data of a searcher is read from the files of the index, which are
+/// memory mapped (or held in anonymous memory by a `RAMDirectory`). The operating system
+/// only loads the pages that are actually accessed, so that the size of the data
+/// of a component is an upper bound of the resident memory it uses.
+///
+/// Inverted indexes (term dictionary, postings and positions) are only opened for the fields
+/// that were searched, and only these are accounted for.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearcherMemoryUsage {
+    segments: Vec<SegmentMemoryUsage>,
+    total: ByteCount,
+}
+
+impl SearcherMemoryUsage {
+    pub(crate) fn new() -> SearcherMemoryUsage {
+        SearcherMemoryUsage {
+            segments: Vec::new(),
+            total: 0,
+        }
+    }
+
+    /// Add a segment, to `self`.
+    pub(crate) fn add_segment(&mut self, segment: SegmentMemoryUsage) {
+        self.total += segment.total();
+        self.segments.push(segment);
+    }
+
+    /// Per segment memory usage
+    pub fn segments(&self) -> &[SegmentMemoryUsage] {
+        &self.segments[..]
+    }
+
+    /// Returns the overall estimated memory usage of this searcher.
+    pub fn total(&self) -> ByteCount {
+        self.total
+    }
+}
+
+/// Represents the estimated memory footprint of the components of an open segment.
+///
+/// See [`SearcherMemoryUsage`](./struct.SearcherMemoryUsage.html).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SegmentMemoryUsage {
+    pub(crate) num_open_inverted_indexes: usize,
+    pub(crate) termdict: ByteCount,
+    pub(crate) postings: ByteCount,
+    pub(crate) fast_fields: ByteCount,
+    pub(crate) fieldnorms: ByteCount,
+    pub(crate) store: ByteCount,
+    pub(crate) store_cache: ByteCount,
+    pub(crate) deletes: ByteCount,
+}
+
+impl SegmentMemoryUsage {
+    /// Number of fields whose inverted index has been opened.
+    pub fn num_open_inverted_indexes(&self) -> usize {
+        self.num_open_inverted_indexes
+    }
+
+    /// Term dictionary blocks of the opened inverted indexes.
+    pub fn termdict(&self) -> ByteCount {
+        self.termdict
+    }
+
+    /// Postings and positions of the opened inverted indexes.
+    pub fn postings(&self) -> ByteCount {
+        self.postings
+    }
+
+    /// Data of the fast fields.
+    pub fn fast_fields(&self) -> ByteCount {
+        self.fast_fields
+    }
+
+    /// Data of the field norms.
+    pub fn fieldnorms(&self) -> ByteCount {
+        self.fieldnorms
+    }
+
+    /// Compressed data of the doc store.
+    pub fn store(&self) -> ByteCount {
+        self.store
+    }
+
+    /// Decompressed doc store block cached by the searcher.
+    ///
+    /// Unlike the other components, this is allocated on the heap.
+    pub fn store_cache(&self) -> ByteCount {
+        self.store_cache
+    }
+
+    /// Bitset of the deleted documents.
+    pub fn deletes(&self) -> ByteCount {
+        self.deletes
+    }
+
+    /// Estimated memory usage of the data read from the files of the index.
+    pub fn mapped(&self) -> ByteCount {
+        self.termdict
+            + self.postings
+            + self.fast_fields
+            + self.fieldnorms
+            + self.store
+            + self.deletes
+    }
+
+    /// Overall estimated memory usage of the segment.
+    pub fn total(&self) -> ByteCount {
+        self.mapped() + self.store_cache
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::core::Index;
@@ -297,7 +534,9 @@ mod test {
     use crate::schema::Schema;
     use crate::schema::{FAST, INDEXED, STORED, TEXT};
     use crate::space_usage::ByteCount;
+    use crate::space_usage::FieldSpaceUsage;
     use crate::space_usage::PerFieldSpaceUsage;
+    use crate::DocAddress;
     use crate::Term;
 
     #[test]
@@ -444,6 +683,57 @@ mod test {
         assert_eq!(0, segment.deletes());
     }
 
+    #[test]
+    fn test_per_field() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let num = schema_builder.add_u64_field("num", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text => "hi", num => 1u64));
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text => "hello hi goodbye", num => 2u64));
+            index_writer.commit().unwrap();
+        }
+
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let searcher_space_usage = searcher.space_usage();
+        assert_eq!(2, searcher_space_usage.segments().len());
+        let segment = &searcher_space_usage.segments()[0];
+        assert!(searcher
+            .segment_readers()
+            .iter()
+            .any(|segment_reader| segment_reader.segment_id() == segment.segment_id()));
+
+        let per_field = searcher_space_usage.per_field();
+        assert_eq!(per_field.len(), 2);
+        let text_usage = &per_field[&text];
+        assert!(text_usage.postings() > 0);
+        assert!(text_usage.positions() > 0);
+        assert_eq!(text_usage.fast_fields(), 0);
+        let num_usage = &per_field[&num];
+        assert!(num_usage.fast_fields() > 0);
+        assert_eq!(num_usage.postings(), 0);
+
+        let segments_text_total: ByteCount = searcher_space_usage
+            .segments()
+            .iter()
+            .map(|segment| segment.per_field()[&text].total())
+            .sum();
+        assert_eq!(text_usage.total(), segments_text_total);
+        let fields_total: ByteCount = per_field.values().map(FieldSpaceUsage::total).sum();
+        let stores_total: ByteCount = searcher_space_usage
+            .segments()
+            .iter()
+            .map(|segment| segment.store().total())
+            .sum();
+        assert_eq!(fields_total + stores_total, searcher_space_usage.total());
+    }
+
     #[test]
     fn test_deletes() {
         let mut schema_builder = Schema::builder();
@@ -490,4 +780,44 @@ mod test {
         //        assert_eq!(0, segment.store().total());
         assert!(segment.deletes() > 0);
     }
+
+    #[test]
+    fn test_memory_usage() {
+        let mut schema_builder = Schema::builder();
+        let name = schema_builder.add_text_field("name", TEXT | STORED);
+        let age = schema_builder.add_u64_field("age", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(name => "hi", age => 1u64));
+            index_writer.add_document(doc!(name => "hello hi goodbye", age => 2u64));
+            index_writer.commit().unwrap();
+        }
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let memory_usage = searcher.memory_usage();
+        assert_eq!(1, memory_usage.segments().len());
+        let segment = &memory_usage.segments()[0];
+        assert_eq!(0, segment.num_open_inverted_indexes());
+        assert_eq!(0, segment.termdict());
+        assert_eq!(0, segment.postings());
+        assert!(segment.fast_fields() > 0);
+        assert!(segment.fieldnorms() > 0);
+        assert!(segment.store() > 0);
+        assert_eq!(0, segment.store_cache());
+        assert_eq!(0, segment.deletes());
+
+        searcher.segment_reader(0).inverted_index(name);
+        searcher.doc(DocAddress(0, 0)).unwrap();
+        let memory_usage = searcher.memory_usage();
+        let segment = &memory_usage.segments()[0];
+        assert_eq!(1, segment.num_open_inverted_indexes());
+        assert!(segment.termdict() > 0);
+        assert!(segment.postings() > 0);
+        assert!(segment.store_cache() > 0);
+        assert_eq!(segment.total(), segment.mapped() + segment.store_cache());
+        assert_eq!(memory_usage.total(), segment.total());
+    }
 }
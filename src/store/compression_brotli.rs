@@ -0,0 +1,23 @@
+extern crate brotli;
+
+use std::io::{self, Read, Write};
+
+const BUFFER_SIZE: usize = 4_096;
+const QUALITY: u32 = 11;
+const LG_WINDOW_SIZE: u32 = 22;
+
+pub fn compress(uncompressed: &[u8], compressed: &mut Vec<u8>) -> io::Result<()> {
+    compressed.clear();
+    let mut encoder =
+        brotli::CompressorWriter::new(compressed, BUFFER_SIZE, QUALITY, LG_WINDOW_SIZE);
+    encoder.write_all(&uncompressed)?;
+    // finishes the brotli stream.
+    encoder.into_inner();
+    Ok(())
+}
+
+pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<()> {
+    decompressed.clear();
+    brotli::Decompressor::new(compressed, BUFFER_SIZE).read_to_end(decompressed)?;
+    Ok(())
+}
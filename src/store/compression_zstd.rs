@@ -0,0 +1,18 @@
+extern crate zstd;
+
+use std::io::{self, Read, Write};
+
+pub fn compress(uncompressed: &[u8], compressed: &mut Vec<u8>, level: i32) -> io::Result<()> {
+    compressed.clear();
+    let mut encoder = zstd::Encoder::new(compressed, level)?;
+    encoder.write_all(&uncompressed)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<()> {
+    decompressed.clear();
+    let mut decoder = zstd::Decoder::new(compressed)?;
+    decoder.read_to_end(decompressed)?;
+    Ok(())
+}
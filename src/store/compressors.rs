@@ -0,0 +1,189 @@
+use std::io;
+
+const NONE_ID: u8 = 0;
+const LZ4_ID: u8 = 1;
+const SNAPPY_ID: u8 = 2;
+const ZSTD_ID: u8 = 3;
+const BROTLI_ID: u8 = 4;
+
+/// Compressor applied to the blocks of the doc store.
+///
+/// It is part of the `IndexSettings` and is persisted in the `meta.json` file.
+///
+/// Every block records the compressor it was written with,
+/// so that changing the compressor does not prevent the
+/// reading or merging of existing segments.
+///
+/// `Lz4`, `Zstd` and `Brotli` respectively require the
+/// `lz4-compression`, `zstd-compression` and `brotli-compression`
+/// features.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compressor {
+    /// Blocks are stored uncompressed.
+    None,
+    /// Blocks are compressed using LZ4.
+    Lz4,
+    /// Blocks are compressed using Snappy.
+    Snappy,
+    /// Blocks are compressed using Zstd.
+    Zstd {
+        /// Compression level. Higher levels are slower but more compact.
+        level: i32,
+    },
+    /// Blocks are compressed using Brotli.
+    ///
+    /// Brotli is slow but very compact.
+    Brotli,
+}
+
+impl Default for Compressor {
+    /// Defaults to `Lz4` if the `lz4-compression` feature is enabled,
+    /// and to `Snappy` otherwise.
+    fn default() -> Compressor {
+        if cfg!(feature = "lz4") {
+            Compressor::Lz4
+        } else {
+            Compressor::Snappy
+        }
+    }
+}
+
+fn feature_missing(feature: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("This compressor requires the `{}` feature.", feature),
+    )
+}
+
+impl Compressor {
+    fn id(self) -> u8 {
+        match self {
+            Compressor::None => NONE_ID,
+            Compressor::Lz4 => LZ4_ID,
+            Compressor::Snappy => SNAPPY_ID,
+            Compressor::Zstd { .. } => ZSTD_ID,
+            Compressor::Brotli => BROTLI_ID,
+        }
+    }
+
+    /// Returns an error if the support for this compressor
+    /// has not been compiled in.
+    pub(crate) fn check_available(self) -> io::Result<()> {
+        match self {
+            Compressor::None | Compressor::Snappy => Ok(()),
+            Compressor::Lz4 if !cfg!(feature = "lz4") => Err(feature_missing("lz4-compression")),
+            Compressor::Zstd { .. } if !cfg!(feature = "zstd") => {
+                Err(feature_missing("zstd-compression"))
+            }
+            Compressor::Brotli if !cfg!(feature = "brotli") => {
+                Err(feature_missing("brotli-compression"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Compresses a block.
+    ///
+    /// The first byte of the compressed block identifies
+    /// the compressor.
+    pub(crate) fn compress(self, uncompressed: &[u8], compressed: &mut Vec<u8>) -> io::Result<()> {
+        self.check_available()?;
+        match self {
+            Compressor::None => {
+                compressed.clear();
+                compressed.extend_from_slice(uncompressed);
+            }
+            Compressor::Snappy => super::compression_snap::compress(uncompressed, compressed)?,
+            #[cfg(feature = "lz4")]
+            Compressor::Lz4 => super::compression_lz4::compress(uncompressed, compressed)?,
+            #[cfg(feature = "zstd")]
+            Compressor::Zstd { level } => {
+                super::compression_zstd::compress(uncompressed, compressed, level)?
+            }
+            #[cfg(feature = "brotli")]
+            Compressor::Brotli => super::compression_brotli::compress(uncompressed, compressed)?,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        }
+        compressed.insert(0, self.id());
+        Ok(())
+    }
+}
+
+/// Decompresses a block written by `Compressor::compress`.
+pub(crate) fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<()> {
+    let (&compressor_id, payload) = compressed
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Empty doc store block."))?;
+    match compressor_id {
+        NONE_ID => {
+            decompressed.clear();
+            decompressed.extend_from_slice(payload);
+            Ok(())
+        }
+        SNAPPY_ID => super::compression_snap::decompress(payload, decompressed),
+        #[cfg(feature = "lz4")]
+        LZ4_ID => super::compression_lz4::decompress(payload, decompressed),
+        #[cfg(not(feature = "lz4"))]
+        LZ4_ID => Err(feature_missing("lz4-compression")),
+        #[cfg(feature = "zstd")]
+        ZSTD_ID => super::compression_zstd::decompress(payload, decompressed),
+        #[cfg(not(feature = "zstd"))]
+        ZSTD_ID => Err(feature_missing("zstd-compression")),
+        #[cfg(feature = "brotli")]
+        BROTLI_ID => super::compression_brotli::decompress(payload, decompressed),
+        #[cfg(not(feature = "brotli"))]
+        BROTLI_ID => Err(feature_missing("brotli-compression")),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unknown doc store compressor id {}.", compressor_id),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decompress, Compressor};
+
+    fn test_compressor(compressor: Compressor) {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 7 + i / 100) as u8).collect();
+        let mut compressed = Vec::new();
+        compressor.compress(&data, &mut compressed).unwrap();
+        let mut decompressed = Vec::new();
+        decompress(&compressed, &mut decompressed).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn test_compressors_roundtrip() {
+        test_compressor(Compressor::None);
+        test_compressor(Compressor::Snappy);
+        test_compressor(Compressor::default());
+        if cfg!(feature = "zstd") {
+            test_compressor(Compressor::Zstd { level: 3 });
+        }
+        if cfg!(feature = "brotli") {
+            test_compressor(Compressor::Brotli);
+        }
+    }
+
+    #[test]
+    fn test_compressor_unavailable() {
+        let mut compressed = Vec::new();
+        assert_eq!(
+            Compressor::Zstd { level: 3 }
+                .compress(b"abc", &mut compressed)
+                .is_ok(),
+            cfg!(feature = "zstd")
+        );
+    }
+
+    #[test]
+    fn test_compressor_serde() {
+        let json = serde_json::to_string(&Compressor::Zstd { level: 5 }).unwrap();
+        assert_eq!(json, r#"{"zstd":{"level":5}}"#);
+        let compressor: Compressor = serde_json::from_str(r#""brotli""#).unwrap();
+        assert_eq!(compressor, Compressor::Brotli);
+    }
+}
@@ -5,8 +5,13 @@ A field needs to be marked as stored in the schema in
 order to be handled in the `Store`.
 
 Internally, documents (or rather their stored fields) are serialized to a buffer.
-When the buffer exceeds 16K, the buffer is compressed using `LZ4`
-and the resulting block is written to disk.
+When the buffer exceeds 16K, the buffer is compressed using the
+[`Compressor`](./enum.Compressor.html) configured in the index settings
+(`LZ4` or `Snappy` by default) and the resulting block is written to disk.
+
+Documents larger than 1MB get a block of their own, and are serialized
+and compressed on the fly in chunks of 1MB written right after it,
+so that storing them does not require buffering a copy of their bytes.
 
 One can then request for a specific `DocId`.
 A skip list helps navigating to the right block,
@@ -33,34 +38,42 @@ and should rely on either
 
 !*/
 
+mod compressors;
 mod reader;
 mod skiplist;
 mod writer;
-pub use self::reader::StoreReader;
+pub use self::compressors::Compressor;
+pub use self::reader::{StoreDocIter, StoreReader};
 pub use self::writer::StoreWriter;
 
+#[cfg(feature = "brotli")]
+mod compression_brotli;
 #[cfg(feature = "lz4")]
 mod compression_lz4;
-#[cfg(feature = "lz4")]
-use self::compression_lz4::*;
-
-#[cfg(not(feature = "lz4"))]
 mod compression_snap;
-#[cfg(not(feature = "lz4"))]
-use self::compression_snap::*;
+#[cfg(feature = "zstd")]
+mod compression_zstd;
 
 #[cfg(test)]
 pub mod tests {
 
     use super::*;
-    use crate::directory::{Directory, RAMDirectory, WritePtr};
+    use crate::directory::{Directory, RAMDirectory, ReadOnlySource, WritePtr};
+    use crate::fastfield::DeleteBitSet;
     use crate::schema::Document;
     use crate::schema::FieldValue;
     use crate::schema::Schema;
     use crate::schema::TextOptions;
+    use crate::DocId;
     use std::path::Path;
 
-    pub fn write_lorem_ipsum_store(writer: WritePtr, num_docs: usize) -> Schema {
+    /// Writes a store of `num_docs` lorem ipsum documents, compressed
+    /// with `compressor`, and returns their schema.
+    pub fn write_lorem_ipsum_store(
+        writer: WritePtr,
+        num_docs: usize,
+        compressor: Compressor,
+    ) -> Schema {
         let mut schema_builder = Schema::builder();
         let field_body = schema_builder.add_text_field("body", TextOptions::default().set_stored());
         let field_title =
@@ -77,7 +90,7 @@ pub mod tests {
              laborum.",
         );
         {
-            let mut store_writer = StoreWriter::new(writer);
+            let mut store_writer = StoreWriter::new(writer, compressor);
             for i in 0..num_docs {
                 let mut fields: Vec<FieldValue> = Vec::new();
                 {
@@ -98,12 +111,11 @@ pub mod tests {
         schema
     }
 
-    #[test]
-    fn test_store() {
+    fn test_store_with_compressor(compressor: Compressor) {
         let path = Path::new("store");
         let mut directory = RAMDirectory::create();
         let store_file = directory.open_write(path).unwrap();
-        let schema = write_lorem_ipsum_store(store_file, 1_000);
+        let schema = write_lorem_ipsum_store(store_file, 1_000, compressor);
         let field_title = schema.get_field("title").unwrap();
         let store_source = directory.open_read(path).unwrap();
         let store = StoreReader::from_source(store_source);
@@ -120,6 +132,169 @@ pub mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_store() {
+        test_store_with_compressor(Compressor::default());
+    }
+
+    #[test]
+    fn test_store_iter() {
+        let path = Path::new("store");
+        let mut directory = RAMDirectory::create();
+        let store_file = directory.open_write(path).unwrap();
+        let schema = write_lorem_ipsum_store(store_file, 1_000, Compressor::default());
+        let field_title = schema.get_field("title").unwrap();
+        let store_source = directory.open_read(path).unwrap();
+        let store = StoreReader::from_source(store_source);
+        let title = |doc: &Document| {
+            doc.get_first(field_title)
+                .unwrap()
+                .text()
+                .unwrap()
+                .to_string()
+        };
+
+        let docs: Vec<(DocId, Document)> = store.iter(None).map(Result::unwrap).collect();
+        assert_eq!(docs.len(), 1_000);
+        for (i, (doc_id, doc)) in docs.iter().enumerate() {
+            assert_eq!(*doc_id, i as DocId);
+            assert_eq!(title(doc), format!("Doc {}", i));
+        }
+
+        // deletes every third document.
+        let delete_bytes: Vec<u8> = (0..125u32)
+            .map(|byte_ord| {
+                (0..8u32)
+                    .filter(|shift| (byte_ord * 8 + shift) % 3 == 0)
+                    .fold(0u8, |byte, shift| byte | (1u8 << shift))
+            })
+            .collect();
+        let delete_bitset = DeleteBitSet::open(ReadOnlySource::from(delete_bytes));
+        let alive_doc_ids: Vec<DocId> = store
+            .iter(Some(&delete_bitset))
+            .map(|doc_res| {
+                let (doc_id, doc) = doc_res.unwrap();
+                assert_eq!(title(&doc), format!("Doc {}", doc_id));
+                doc_id
+            })
+            .collect();
+        let expected_doc_ids: Vec<DocId> = (0..1_000).filter(|doc| doc % 3 != 0).collect();
+        assert_eq!(alive_doc_ids, expected_doc_ids);
+    }
+
+    #[test]
+    fn test_store_large_docs() {
+        let mut schema_builder = Schema::builder();
+        let field_body = schema_builder.add_text_field("body", TextOptions::default().set_stored());
+        let field_title =
+            schema_builder.add_text_field("title", TextOptions::default().set_stored());
+        let mut directory = RAMDirectory::create();
+        let body = |i: usize| -> String {
+            let num_bytes = if i % 5 < 2 { 3_000_000 } else { 10 };
+            (0..num_bytes)
+                .map(|pos| (b'a' + ((pos * 7 + i) % 26) as u8) as char)
+                .collect()
+        };
+        let doc = |i: usize| {
+            let mut doc = Document::default();
+            doc.add_text(field_body, &body(i));
+            doc.add_text(field_title, &format!("Doc {}", i));
+            doc
+        };
+        {
+            let mut store_writer = StoreWriter::new(
+                directory.open_write(Path::new("store")).unwrap(),
+                Compressor::default(),
+            );
+            for i in 0..10 {
+                store_writer.store(&doc(i)).unwrap();
+            }
+            store_writer.close().unwrap();
+        }
+        let store = StoreReader::from_source(directory.open_read(Path::new("store")).unwrap());
+        assert!(store.space_usage().total() < 2 * 3_000_000);
+        for &i in &[9, 0, 3, 1, 2, 5, 6] {
+            assert_eq!(store.get(i as DocId).unwrap(), doc(i));
+        }
+        for (i, doc_res) in store.iter(None).enumerate() {
+            assert_eq!(doc_res.unwrap(), (i as DocId, doc(i)));
+        }
+        {
+            let mut store_writer = StoreWriter::new(
+                directory.open_write(Path::new("stacked")).unwrap(),
+                Compressor::default(),
+            );
+            store_writer.store(&doc(2)).unwrap();
+            store_writer.stack(&store).unwrap();
+            store_writer.close().unwrap();
+        }
+        let stacked = StoreReader::from_source(directory.open_read(Path::new("stacked")).unwrap());
+        assert_eq!(stacked.get(6).unwrap(), doc(5));
+        assert_eq!(stacked.get(7).unwrap(), doc(6));
+        assert_eq!(stacked.iter(None).filter(Result::is_ok).count(), 11);
+    }
+
+    #[test]
+    fn test_store_no_compression() {
+        test_store_with_compressor(Compressor::None);
+    }
+
+    #[test]
+    fn test_store_snappy() {
+        test_store_with_compressor(Compressor::Snappy);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_store_zstd() {
+        test_store_with_compressor(Compressor::Zstd { level: 3 });
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn test_store_brotli() {
+        test_store_with_compressor(Compressor::Brotli);
+    }
+
+    #[test]
+    fn test_store_stack_mixed_compressors() {
+        let mut directory = RAMDirectory::create();
+        let schema = write_lorem_ipsum_store(
+            directory.open_write(Path::new("store1")).unwrap(),
+            100,
+            Compressor::None,
+        );
+        write_lorem_ipsum_store(
+            directory.open_write(Path::new("store2")).unwrap(),
+            100,
+            Compressor::Snappy,
+        );
+        let field_title = schema.get_field("title").unwrap();
+        let store1 = StoreReader::from_source(directory.open_read(Path::new("store1")).unwrap());
+        let store2 = StoreReader::from_source(directory.open_read(Path::new("store2")).unwrap());
+        {
+            let merged_write = directory.open_write(Path::new("merged")).unwrap();
+            let mut store_writer = StoreWriter::new(merged_write, Compressor::default());
+            store_writer.stack(&store1).unwrap();
+            store_writer.stack(&store2).unwrap();
+            store_writer.close().unwrap();
+        }
+        let merged = StoreReader::from_source(directory.open_read(Path::new("merged")).unwrap());
+        for i in 0..200 {
+            assert_eq!(
+                *merged
+                    .get(i)
+                    .unwrap()
+                    .get_first(field_title)
+                    .unwrap()
+                    .text()
+                    .unwrap(),
+                format!("Doc {}", i % 100)
+            );
+        }
+        assert_eq!(merged.iter(None).filter(Result::is_ok).count(), 200);
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
@@ -128,7 +303,7 @@ mod bench {
     use super::tests::write_lorem_ipsum_store;
     use crate::directory::Directory;
     use crate::directory::RAMDirectory;
-    use crate::store::StoreReader;
+    use crate::store::{Compressor, StoreReader};
     use std::path::Path;
     use test::Bencher;
 
@@ -138,7 +313,11 @@ mod bench {
         let mut directory = RAMDirectory::create();
         let path = Path::new("store");
         b.iter(|| {
-            write_lorem_ipsum_store(directory.open_write(path).unwrap(), 1_000);
+            write_lorem_ipsum_store(
+                directory.open_write(path).unwrap(),
+                1_000,
+                Compressor::default(),
+            );
             directory.delete(path).unwrap();
         });
     }
@@ -147,7 +326,11 @@ mod bench {
     fn bench_store_decode(b: &mut Bencher) {
         let mut directory = RAMDirectory::create();
         let path = Path::new("store");
-        write_lorem_ipsum_store(directory.open_write(path).unwrap(), 1_000);
+        write_lorem_ipsum_store(
+            directory.open_write(path).unwrap(),
+            1_000,
+            Compressor::default(),
+        );
         let store_source = directory.open_read(path).unwrap();
         let store = StoreReader::from_source(store_source);
         b.iter(|| {
@@ -1,10 +1,11 @@
 use crate::Result;
 
-use super::decompress;
+use super::compressors::decompress;
 use super::skiplist::SkipList;
 use crate::common::BinarySerializable;
 use crate::common::VInt;
 use crate::directory::ReadOnlySource;
+use crate::fastfield::DeleteBitSet;
 use crate::schema::Document;
 use crate::space_usage::StoreSpaceUsage;
 use crate::DocId;
@@ -57,6 +58,24 @@ impl StoreReader {
         &buffer[..block_len]
     }
 
+    /// Reads a document too large to be stored within a block. Its block
+    /// only contains an empty length, and is followed by the chunks of
+    /// the serialized document, terminated by an empty chunk.
+    fn read_chunked_doc(&self, block_offset: usize, output: &mut Vec<u8>) -> io::Result<()> {
+        output.clear();
+        let mut chunk = Vec::new();
+        let mut chunk_offset = block_offset + 4 + self.compressed_block(block_offset).len();
+        loop {
+            let compressed_chunk = self.compressed_block(chunk_offset);
+            if compressed_chunk.is_empty() {
+                return Ok(());
+            }
+            decompress(compressed_chunk, &mut chunk)?;
+            output.extend_from_slice(&chunk);
+            chunk_offset += 4 + compressed_chunk.len();
+        }
+    }
+
     fn read_block(&self, block_offset: usize) -> io::Result<()> {
         if block_offset != *self.current_block_offset.borrow() {
             let mut current_block_mut = self.current_block.borrow_mut();
@@ -71,7 +90,7 @@ impl StoreReader {
     /// Reads a given document.
     ///
     /// Calling `.get(doc)` is relatively costly as it requires
-    /// decompressing a LZ4-compressed block.
+    /// decompressing a compressed block.
     ///
     /// It should not be called to score documents
     /// for instance.
@@ -85,14 +104,159 @@ impl StoreReader {
             cursor = &cursor[doc_length..];
         }
         let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
+        if doc_length == 0 {
+            let mut doc_bytes = Vec::new();
+            self.read_chunked_doc(block_offset as usize, &mut doc_bytes)?;
+            return Ok(Document::deserialize(&mut &doc_bytes[..])?);
+        }
         cursor = &cursor[..doc_length];
         Ok(Document::deserialize(&mut cursor)?)
     }
 
+    /// Iterates over all of the documents of the store, in the
+    /// order of their `DocId`.
+    ///
+    /// Contrary to calling `.get(doc)` for every document, each
+    /// block is decompressed exactly once, which makes it the
+    /// preferred way to export or reindex a segment.
+    ///
+    /// Documents marked as deleted in `delete_bitset` are skipped.
+    pub fn iter<'a>(&'a self, delete_bitset: Option<&'a DeleteBitSet>) -> StoreDocIter<'a> {
+        let mut blocks = Vec::new();
+        let mut block_start = (0u32, 0usize);
+        for (block_end_doc, block_end_offset) in self.block_index() {
+            let (start_doc, start_offset) = block_start;
+            let end_doc = block_end_doc as DocId;
+            // `stack` may record the same block boundary twice.
+            if end_doc > start_doc {
+                blocks.push((end_doc, start_offset));
+            }
+            block_start = (end_doc, block_end_offset as usize);
+        }
+        StoreDocIter {
+            store_reader: self,
+            delete_bitset,
+            blocks: blocks.into_iter(),
+            block: Vec::new(),
+            block_offset: 0,
+            chunked_doc: Vec::new(),
+            cursor: 0,
+            block_end_doc: 0,
+            doc: 0,
+            failed: false,
+        }
+    }
+
     /// Summarize total space usage of this store reader.
     pub fn space_usage(&self) -> StoreSpaceUsage {
         StoreSpaceUsage::new(self.data.len(), self.offset_index_source.len())
     }
+
+    /// Returns the number of bytes allocated for the decompressed block cache.
+    pub(crate) fn cache_num_bytes(&self) -> usize {
+        self.current_block.borrow().capacity()
+    }
+}
+
+/// Iterator over the documents of a `StoreReader`.
+///
+/// See [`StoreReader::iter`](./struct.StoreReader.html#method.iter).
+pub struct StoreDocIter<'a> {
+    store_reader: &'a StoreReader,
+    delete_bitset: Option<&'a DeleteBitSet>,
+    // (first doc of the next block, offset of the block)
+    blocks: ::std::vec::IntoIter<(DocId, usize)>,
+    block: Vec<u8>,
+    block_offset: usize,
+    // Buffer for the documents stored in chunks.
+    chunked_doc: Vec<u8>,
+    cursor: usize,
+    block_end_doc: DocId,
+    doc: DocId,
+    failed: bool,
+}
+
+impl<'a> StoreDocIter<'a> {
+    /// Decompresses the next block, if any.
+    fn load_next_block(&mut self) -> io::Result<bool> {
+        if let Some((block_end_doc, block_offset)) = self.blocks.next() {
+            let compressed_block = self.store_reader.compressed_block(block_offset);
+            decompress(compressed_block, &mut self.block)?;
+            self.block_offset = block_offset;
+            self.cursor = 0;
+            self.block_end_doc = block_end_doc;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns the serialized bytes of the current document,
+    /// and advances to the next one.
+    fn next_doc_bytes(&mut self) -> io::Result<&[u8]> {
+        let mut cursor = &self.block[self.cursor..];
+        let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
+        let doc_start = self.block.len() - cursor.len();
+        if doc_length == 0 {
+            self.store_reader
+                .read_chunked_doc(self.block_offset, &mut self.chunked_doc)?;
+            self.cursor = doc_start;
+            self.doc += 1;
+            return Ok(&self.chunked_doc[..]);
+        }
+        let doc_end = doc_start + doc_length;
+        if doc_end > self.block.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Document exceeds the store block.",
+            ));
+        }
+        self.cursor = doc_end;
+        self.doc += 1;
+        Ok(&self.block[doc_start..doc_end])
+    }
+}
+
+impl<'a> Iterator for StoreDocIter<'a> {
+    type Item = Result<(DocId, Document)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        loop {
+            if self.doc >= self.block_end_doc {
+                match self.load_next_block() {
+                    Ok(true) => {}
+                    Ok(false) => return None,
+                    Err(err) => {
+                        self.failed = true;
+                        return Some(Err(err.into()));
+                    }
+                }
+            }
+            let doc_id = self.doc;
+            let is_deleted = self
+                .delete_bitset
+                .map(|delete_bitset| delete_bitset.is_deleted(doc_id))
+                .unwrap_or(false);
+            let doc_res = self.next_doc_bytes().and_then(|mut doc_bytes| {
+                if is_deleted {
+                    Ok(None)
+                } else {
+                    Document::deserialize(&mut doc_bytes).map(Some)
+                }
+            });
+            match doc_res {
+                Ok(Some(doc)) => return Some(Ok((doc_id, doc))),
+                Ok(None) => {}
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err.into()));
+                }
+            }
+        }
+    }
 }
 
 fn split_source(data: ReadOnlySource) -> (ReadOnlySource, ReadOnlySource, DocId) {
@@ -1,16 +1,85 @@
-use super::compress;
 use super::skiplist::SkipListBuilder;
+use super::Compressor;
 use super::StoreReader;
 use crate::common::CountingWriter;
 use crate::common::{BinarySerializable, VInt};
 use crate::directory::TerminatingWrite;
 use crate::directory::WritePtr;
 use crate::schema::Document;
+use crate::schema::Value;
 use crate::DocId;
 use std::io::{self, Write};
 
 const BLOCK_SIZE: usize = 16_384;
 
+/// Size of the chunks in which large documents are compressed.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Documents whose stored values exceed this number of bytes are streamed
+/// to the store as a sequence of chunks, instead of being copied to
+/// the current block as a whole.
+const LARGE_DOC_NUM_BYTES: usize = CHUNK_SIZE;
+
+/// Returns a lower bound of the number of bytes of the serialized document.
+fn stored_num_bytes_lower_bound(doc: &Document) -> usize {
+    doc.field_values()
+        .iter()
+        .map(|field_value| match *field_value.value() {
+            Value::Str(ref text) => text.len(),
+            Value::Facet(ref facet) => facet.encoded_str().len(),
+            Value::Bytes(ref bytes) => bytes.len(),
+            Value::U64(_) | Value::I64(_) | Value::F64(_) | Value::Date(_) => 8,
+            Value::GeoPoint(_) => 16,
+            // Each entry takes at least one byte.
+            Value::JsonObject(ref json_object) => json_object.len(),
+        })
+        .sum()
+}
+
+/// Compresses the bytes written to it in chunks of `CHUNK_SIZE`,
+/// each of them written with the same layout as a block.
+///
+/// The sequence of chunks is terminated by an empty chunk.
+struct ChunkWriter<'a> {
+    compressor: Compressor,
+    writer: &'a mut CountingWriter<WritePtr>,
+    chunk: Vec<u8>,
+    compressed_chunk: Vec<u8>,
+}
+
+impl<'a> ChunkWriter<'a> {
+    fn write_chunk(&mut self) -> io::Result<()> {
+        self.compressor
+            .compress(&self.chunk[..], &mut self.compressed_chunk)?;
+        (self.compressed_chunk.len() as u32).serialize(self.writer)?;
+        self.writer.write_all(&self.compressed_chunk)?;
+        self.chunk.clear();
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if !self.chunk.is_empty() {
+            self.write_chunk()?;
+        }
+        0u32.serialize(self.writer)
+    }
+}
+
+impl<'a> Write for ChunkWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = buf.len().min(CHUNK_SIZE - self.chunk.len());
+        self.chunk.extend_from_slice(&buf[..num_bytes]);
+        if self.chunk.len() == CHUNK_SIZE {
+            self.write_chunk()?;
+        }
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Write tantivy's [`Store`](./index.html)
 ///
 /// Contrary to the other components of `tantivy`,
@@ -19,8 +88,12 @@ const BLOCK_SIZE: usize = 16_384;
 ///
 /// The skip list index on the other hand, is built in memory.
 ///
+/// Large documents are serialized directly in chunks following
+/// their block, so that the writer never holds a copy of them.
+///
 pub struct StoreWriter {
     doc: DocId,
+    compressor: Compressor,
     offset_index_writer: SkipListBuilder<u64>,
     writer: CountingWriter<WritePtr>,
     intermediary_buffer: Vec<u8>,
@@ -31,10 +104,11 @@ impl StoreWriter {
     /// Create a store writer.
     ///
     /// The store writer will writes blocks on disc as
-    /// document are added.
-    pub fn new(writer: WritePtr) -> StoreWriter {
+    /// document are added, compressing them with the given `compressor`.
+    pub fn new(writer: WritePtr, compressor: Compressor) -> StoreWriter {
         StoreWriter {
             doc: 0,
+            compressor,
             offset_index_writer: SkipListBuilder::new(4),
             writer: CountingWriter::wrap(writer),
             intermediary_buffer: Vec::new(),
@@ -48,6 +122,9 @@ impl StoreWriter {
     /// this method has been called.
     ///
     pub fn store(&mut self, stored_document: &Document) -> io::Result<()> {
+        if stored_num_bytes_lower_bound(stored_document) > LARGE_DOC_NUM_BYTES {
+            return self.store_chunked(stored_document);
+        }
         self.intermediary_buffer.clear();
         stored_document.serialize(&mut self.intermediary_buffer)?;
         let doc_num_bytes = self.intermediary_buffer.len();
@@ -65,6 +142,8 @@ impl StoreWriter {
     /// This method is an optimization compared to iterating over the documents
     /// in the store and adding them one by one, as the store's data will
     /// not be decompressed and then recompressed.
+    ///
+    /// The stacked blocks keep the compressor they were written with.
     pub fn stack(&mut self, store_reader: &StoreReader) -> io::Result<()> {
         if !self.current_block.is_empty() {
             self.write_and_compress_block()?;
@@ -87,14 +166,44 @@ impl StoreWriter {
         Ok(())
     }
 
-    fn write_and_compress_block(&mut self) -> io::Result<()> {
+    /// Stores a large document in its own block.
+    ///
+    /// The block only contains an empty document length, telling that
+    /// the document is serialized in the chunks following the block.
+    fn store_chunked(&mut self, stored_document: &Document) -> io::Result<()> {
+        if !self.current_block.is_empty() {
+            self.write_and_compress_block()?;
+        }
+        VInt(0u64).serialize(&mut self.current_block)?;
+        self.compress_block()?;
+        let mut chunk_writer = ChunkWriter {
+            compressor: self.compressor,
+            writer: &mut self.writer,
+            chunk: Vec::with_capacity(CHUNK_SIZE),
+            compressed_chunk: Vec::new(),
+        };
+        stored_document.serialize(&mut chunk_writer)?;
+        chunk_writer.finish()?;
+        self.doc += 1;
+        self.offset_index_writer
+            .insert(u64::from(self.doc), &self.writer.written_bytes())?;
+        Ok(())
+    }
+
+    fn compress_block(&mut self) -> io::Result<()> {
         self.intermediary_buffer.clear();
-        compress(&self.current_block[..], &mut self.intermediary_buffer)?;
+        self.compressor
+            .compress(&self.current_block[..], &mut self.intermediary_buffer)?;
         (self.intermediary_buffer.len() as u32).serialize(&mut self.writer)?;
         self.writer.write_all(&self.intermediary_buffer)?;
+        self.current_block.clear();
+        Ok(())
+    }
+
+    fn write_and_compress_block(&mut self) -> io::Result<()> {
+        self.compress_block()?;
         self.offset_index_writer
             .insert(u64::from(self.doc), &(self.writer.written_bytes() as u64))?;
-        self.current_block.clear();
         Ok(())
     }
 
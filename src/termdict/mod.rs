@@ -31,14 +31,17 @@ mod termdict;
 pub use self::merger::TermMerger;
 pub use self::streamer::{TermStreamer, TermStreamerBuilder};
 pub use self::termdict::{TermDictionary, TermDictionaryBuilder};
+pub use tantivy_fst::automaton::AlwaysMatch;
+pub use tantivy_fst::Automaton;
 
 #[cfg(test)]
 mod tests {
-    use super::{TermDictionary, TermDictionaryBuilder, TermStreamer};
+    use super::{Automaton, TermDictionary, TermDictionaryBuilder, TermStreamer};
     use crate::core::Index;
     use crate::directory::{Directory, RAMDirectory, ReadOnlySource};
     use crate::postings::TermInfo;
     use crate::schema::{Document, FieldType, Schema, TEXT};
+    use std::collections::Bound;
     use std::path::PathBuf;
     use std::str;
 
@@ -432,4 +435,92 @@ mod tests {
         assert_eq!("Spain".as_bytes(), range.key());
         assert!(!range.advance());
     }
+
+    /// Matches the terms starting with a given prefix.
+    struct Prefix(&'static [u8]);
+
+    impl Automaton for Prefix {
+        // Number of bytes of the prefix matched so far, or `None`
+        // if the term does not start with the prefix.
+        type State = Option<usize>;
+
+        fn start(&self) -> Option<usize> {
+            Some(0)
+        }
+
+        fn is_match(&self, state: &Option<usize>) -> bool {
+            *state == Some(self.0.len())
+        }
+
+        fn can_match(&self, state: &Option<usize>) -> bool {
+            state.is_some()
+        }
+
+        fn accept(&self, state: &Option<usize>, byte: u8) -> Option<usize> {
+            match *state {
+                Some(len) if len == self.0.len() => Some(len),
+                Some(len) if self.0[len] == byte => Some(len + 1),
+                _ => None,
+            }
+        }
+    }
+
+    fn collect_terms<A: Automaton>(mut stream: TermStreamer<'_, A>) -> Vec<(String, u32)> {
+        let mut terms = Vec::new();
+        while let Some((term, term_info)) = stream.next() {
+            terms.push((
+                str::from_utf8(term).unwrap().to_string(),
+                term_info.doc_freq,
+            ));
+        }
+        terms
+    }
+
+    #[test]
+    fn test_stream_range_and_custom_automaton() {
+        let mut directory = RAMDirectory::create();
+        let path = PathBuf::from("TermDictionary");
+        {
+            let write = directory.open_write(&path).unwrap();
+            let field_type = FieldType::Str(TEXT);
+            let mut term_dictionary_builder =
+                TermDictionaryBuilder::create(write, &field_type).unwrap();
+            for (val, term) in ["Serbia", "Slovakia", "Slovenia", "Spain"]
+                .iter()
+                .enumerate()
+            {
+                term_dictionary_builder
+                    .insert(term.as_bytes(), &make_term_info(val as u64))
+                    .unwrap();
+            }
+            term_dictionary_builder.finish().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        let term_dict: TermDictionary = TermDictionary::from_source(&source);
+        assert_eq!(
+            collect_terms(
+                term_dict.stream_range(Bound::Excluded("Serbia"), Bound::Included("Slovenia"))
+            ),
+            vec![("Slovakia".to_string(), 1), ("Slovenia".to_string(), 2)]
+        );
+        assert_eq!(
+            collect_terms(
+                term_dict.stream_range::<&str>(Bound::Unbounded, Bound::Excluded("Slovakia"))
+            ),
+            vec![("Serbia".to_string(), 0)]
+        );
+        assert_eq!(
+            collect_terms(term_dict.search(Prefix(b"Slov")).into_stream()),
+            vec![("Slovakia".to_string(), 1), ("Slovenia".to_string(), 2)]
+        );
+        assert_eq!(
+            collect_terms(
+                term_dict
+                    .search(Prefix(b"Slov"))
+                    .gt("Slovakia")
+                    .into_stream()
+            ),
+            vec![("Slovenia".to_string(), 2)]
+        );
+    }
 }
@@ -6,6 +6,7 @@ use crate::directory::ReadOnlySource;
 use crate::postings::TermInfo;
 use crate::schema::FieldType;
 use crate::termdict::TermOrdinal;
+use std::collections::Bound;
 use std::io::{self, Write};
 use tantivy_fst;
 use tantivy_fst::raw::Fst;
@@ -190,13 +191,62 @@ impl TermDictionary {
         TermStreamerBuilder::new(self, self.fst_index.range())
     }
 
+    /// A stream of the sorted terms within the bounds, along with their `TermInfo`.
+    ///
+    /// ```rust
+    /// use std::collections::Bound;
+    /// # use tantivy::schema::{Schema, TEXT};
+    /// # use tantivy::{doc, Index};
+    /// # fn main() -> tantivy::Result<()> {
+    /// # let mut schema_builder = Schema::builder();
+    /// # let body = schema_builder.add_text_field("body", TEXT);
+    /// # let index = Index::create_in_ram(schema_builder.build());
+    /// # let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+    /// # index_writer.add_document(doc!(body => "apple banana cherry"));
+    /// # index_writer.commit()?;
+    /// # let searcher = index.reader()?.searcher();
+    /// let inverted_index = searcher.segment_reader(0).inverted_index(body);
+    /// let mut stream = inverted_index
+    ///     .terms()
+    ///     .stream_range(Bound::Excluded("apple"), Bound::Unbounded);
+    /// let mut terms = Vec::new();
+    /// while let Some((term, term_info)) = stream.next() {
+    ///     terms.push((String::from_utf8(term.to_vec()).unwrap(), term_info.doc_freq));
+    /// }
+    /// assert_eq!(terms, vec![("banana".to_string(), 1), ("cherry".to_string(), 1)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream_range<K: AsRef<[u8]>>(
+        &self,
+        lower: Bound<K>,
+        upper: Bound<K>,
+    ) -> TermStreamer<'_> {
+        let mut builder = self.range();
+        builder = match lower {
+            Bound::Included(bound) => builder.ge(bound),
+            Bound::Excluded(bound) => builder.gt(bound),
+            Bound::Unbounded => builder,
+        };
+        builder = match upper {
+            Bound::Included(bound) => builder.le(bound),
+            Bound::Excluded(bound) => builder.lt(bound),
+            Bound::Unbounded => builder,
+        };
+        builder.into_stream()
+    }
+
     /// A stream of all the sorted terms. [See also `.stream_field()`](#method.stream_field)
     pub fn stream(&self) -> TermStreamer<'_> {
         self.range().into_stream()
     }
 
     /// Returns a search builder, to stream all of the terms
-    /// within the Automaton
+    /// within the Automaton, along with their `TermInfo`.
+    ///
+    /// Any implementation of [`Automaton`](./trait.Automaton.html) can be used, for instance
+    /// to find the terms within an edit distance of a misspelled word.
+    /// The stream can be further restricted to a range of terms.
     pub fn search<'a, A: Automaton + 'a>(&'a self, automaton: A) -> TermStreamerBuilder<'a, A> {
         let stream_builder = self.fst_index.search(automaton);
         TermStreamerBuilder::<A>::new(self, stream_builder)
@@ -0,0 +1,220 @@
+use super::{Token, TokenStream, Tokenizer};
+use std::ops::Range;
+use std::vec;
+
+/// `CharFilter`s transform the text before its tokenization,
+/// e.g. to strip the markup of an HTML document.
+///
+/// The offsets of the tokens are expressed in the original text,
+/// so that they can be used for highlighting.
+///
+/// A char filter is added to a tokenizer with
+/// [`Tokenizer::char_filter`](./trait.Tokenizer.html#method.char_filter).
+pub trait CharFilter: Clone + Send + Sync + 'static {
+    /// Pushes the pieces of the filtered version of `text` to `filtered_text`,
+    /// in order.
+    fn filter(&self, text: &str, filtered_text: &mut FilteredText);
+}
+
+#[derive(Clone)]
+struct Piece {
+    filtered: Range<usize>,
+    original: Range<usize>,
+    is_verbatim: bool,
+}
+
+/// Text produced by a `CharFilter`, keeping track of the range
+/// of the original text each piece of the filtered text comes from.
+#[derive(Default)]
+pub struct FilteredText {
+    text: String,
+    pieces: Vec<Piece>,
+}
+
+impl FilteredText {
+    /// Returns the filtered text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Pushes a piece of the original text, found at `original_from`, as is.
+    pub fn push_original(&mut self, text: &str, original_from: usize) {
+        if text.is_empty() {
+            return;
+        }
+        let filtered_from = self.text.len();
+        self.text.push_str(text);
+        if let Some(last_piece) = self.pieces.last_mut() {
+            if last_piece.is_verbatim && last_piece.original.end == original_from {
+                last_piece.filtered.end = self.text.len();
+                last_piece.original.end += text.len();
+                return;
+            }
+        }
+        self.pieces.push(Piece {
+            filtered: filtered_from..self.text.len(),
+            original: original_from..original_from + text.len(),
+            is_verbatim: true,
+        });
+    }
+
+    /// Pushes `replacement` in place of the `original` range of the original text.
+    ///
+    /// The replacement may be empty, in order to remove a piece of the original text.
+    pub fn push_replacement(&mut self, replacement: &str, original: Range<usize>) {
+        let filtered_from = self.text.len();
+        self.text.push_str(replacement);
+        self.pieces.push(Piece {
+            filtered: filtered_from..self.text.len(),
+            original,
+            is_verbatim: false,
+        });
+    }
+
+    /// Returns the offset in the original text of a token starting
+    /// at `offset` in the filtered text.
+    fn original_offset_from(&self, offset: usize) -> usize {
+        let piece_ord = self
+            .pieces
+            .binary_search_by(|piece| {
+                if piece.filtered.end <= offset {
+                    std::cmp::Ordering::Less
+                } else if piece.filtered.start > offset {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .unwrap_or_else(|piece_ord| piece_ord);
+        match self.pieces.get(piece_ord) {
+            Some(piece) if piece.is_verbatim => {
+                piece.original.start + offset - piece.filtered.start
+            }
+            Some(piece) => piece.original.start,
+            None => self
+                .pieces
+                .last()
+                .map(|piece| piece.original.end)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Returns the offset in the original text of a token ending
+    /// at `offset` in the filtered text.
+    fn original_offset_to(&self, offset: usize) -> usize {
+        let piece_ord = self
+            .pieces
+            .binary_search_by(|piece| {
+                if piece.filtered.end < offset {
+                    std::cmp::Ordering::Less
+                } else if piece.filtered.start >= offset {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .unwrap_or_else(|piece_ord| piece_ord);
+        match self.pieces.get(piece_ord) {
+            Some(piece) if piece.is_verbatim => {
+                piece.original.start + offset - piece.filtered.start
+            }
+            Some(piece) => piece.original.end,
+            None => 0,
+        }
+    }
+}
+
+/// `Tokenizer` applying a `CharFilter` to the text, before tokenizing it with
+/// another `Tokenizer`.
+///
+/// It is created with [`Tokenizer::char_filter`](./trait.Tokenizer.html#method.char_filter).
+#[derive(Clone)]
+pub struct CharFilterTokenizer<F, T> {
+    char_filter: F,
+    tokenizer: T,
+}
+
+impl<F, T> CharFilterTokenizer<F, T> {
+    pub(crate) fn new(char_filter: F, tokenizer: T) -> CharFilterTokenizer<F, T> {
+        CharFilterTokenizer {
+            char_filter,
+            tokenizer,
+        }
+    }
+}
+
+impl<'a, F, T> Tokenizer<'a> for CharFilterTokenizer<F, T>
+where
+    F: CharFilter,
+    T: for<'b> Tokenizer<'b>,
+{
+    type TokenStreamImpl = CharFilterTokenStream;
+
+    fn token_stream(&self, text: &'a str) -> Self::TokenStreamImpl {
+        let mut filtered_text = FilteredText::default();
+        self.char_filter.filter(text, &mut filtered_text);
+        // The tokens borrow the filtered text, so they are all computed beforehand.
+        let mut tokens = Vec::new();
+        self.tokenizer
+            .token_stream(filtered_text.text())
+            .process(&mut |token: &Token| {
+                let mut token = token.clone();
+                token.offset_from = filtered_text.original_offset_from(token.offset_from);
+                token.offset_to = filtered_text.original_offset_to(token.offset_to);
+                tokens.push(token);
+            });
+        CharFilterTokenStream {
+            tokens: tokens.into_iter(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct CharFilterTokenStream {
+    tokens: vec::IntoIter<Token>,
+    token: Token,
+}
+
+impl TokenStream for CharFilterTokenStream {
+    fn advance(&mut self) -> bool {
+        match self.tokens.next() {
+            Some(token) => {
+                self.token = token;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilteredText;
+
+    #[test]
+    fn test_filtered_text_offsets() {
+        // original: "<b>x</b> &amp; yz"
+        let mut filtered_text = FilteredText::default();
+        filtered_text.push_replacement("", 0..3);
+        filtered_text.push_original("x", 3);
+        filtered_text.push_replacement("", 4..8);
+        filtered_text.push_original(" ", 8);
+        filtered_text.push_replacement("&", 9..14);
+        filtered_text.push_original(" yz", 14);
+        assert_eq!(filtered_text.text(), "x & yz");
+        assert_eq!(filtered_text.original_offset_from(0), 3);
+        assert_eq!(filtered_text.original_offset_to(1), 4);
+        assert_eq!(filtered_text.original_offset_from(2), 9);
+        assert_eq!(filtered_text.original_offset_to(3), 14);
+        assert_eq!(filtered_text.original_offset_from(4), 15);
+        assert_eq!(filtered_text.original_offset_to(6), 17);
+    }
+}
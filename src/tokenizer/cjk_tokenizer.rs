@@ -0,0 +1,296 @@
+use super::{Token, TokenStream, Tokenizer};
+use std::collections::VecDeque;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::CharIndices;
+
+/// Returns true if the character belongs to one of the scripts
+/// of the Chinese, Japanese and Korean languages.
+fn is_cjk(c: char) -> bool {
+    match c {
+        // Hangul Jamo
+        '\u{1100}'..='\u{11FF}' => true,
+        // CJK radicals, punctuation, Hiragana, Katakana, Bopomofo, Hangul compatibility Jamo...
+        '\u{2E80}'..='\u{33FF}' => true,
+        // CJK unified ideographs, extension A
+        '\u{3400}'..='\u{4DBF}' => true,
+        // CJK unified ideographs
+        '\u{4E00}'..='\u{9FFF}' => true,
+        // Hangul syllables
+        '\u{AC00}'..='\u{D7AF}' => true,
+        // CJK compatibility ideographs
+        '\u{F900}'..='\u{FAFF}' => true,
+        // Halfwidth Katakana
+        '\u{FF66}'..='\u{FF9F}' => true,
+        // CJK unified ideographs, extensions B to F
+        '\u{20000}'..='\u{2FA1F}' => true,
+        _ => false,
+    }
+}
+
+/// Splits a run of CJK characters into words.
+///
+/// This is the integration point of the dictionary-based segmentation
+/// of the Chinese, Japanese or Korean texts, used by the
+/// [`CjkTokenizer`](./struct.CjkTokenizer.html).
+pub trait CjkSegmenter: Clone + Send + Sync + 'static {
+    /// Appends to `words` the byte ranges of the words of `text`,
+    /// which only contains CJK alphanumeric characters, in increasing order.
+    fn segment(&self, text: &str, words: &mut Vec<Range<usize>>);
+}
+
+/// `CjkSegmenter` emitting the overlapping bigrams of the characters of the text,
+/// or the text itself if it is a single character.
+#[derive(Clone, Default)]
+pub struct BigramSegmenter;
+
+impl CjkSegmenter for BigramSegmenter {
+    fn segment(&self, text: &str, words: &mut Vec<Range<usize>>) {
+        let mut char_offsets: Vec<usize> = text.char_indices().map(|(offset, _)| offset).collect();
+        char_offsets.push(text.len());
+        if char_offsets.len() == 2 {
+            words.push(0..text.len());
+            return;
+        }
+        for window in char_offsets.windows(3) {
+            words.push(window[0]..window[2]);
+        }
+    }
+}
+
+/// Tokenize the text by splitting on whitespaces and punctuation like the
+/// [`SimpleTokenizer`](./struct.SimpleTokenizer.html), the runs of Chinese,
+/// Japanese or Korean characters being split into words by a `CjkSegmenter`.
+///
+/// The `SimpleTokenizer` emits each of these runs as a single token, so that
+/// words can hardly be searched in texts which do not separate them with spaces.
+#[derive(Clone)]
+pub struct CjkTokenizer<S> {
+    segmenter: S,
+}
+
+impl<S: CjkSegmenter> CjkTokenizer<S> {
+    /// Creates a `CjkTokenizer` splitting the runs of CJK characters with `segmenter`.
+    pub fn new(segmenter: S) -> CjkTokenizer<S> {
+        CjkTokenizer { segmenter }
+    }
+}
+
+impl<'a, S: CjkSegmenter> Tokenizer<'a> for CjkTokenizer<S> {
+    type TokenStreamImpl = CjkTokenStream<'a, S>;
+
+    fn token_stream(&self, text: &'a str) -> Self::TokenStreamImpl {
+        CjkTokenStream::new(text, self.segmenter.clone())
+    }
+}
+
+/// Tokenize the text like the [`SimpleTokenizer`](./struct.SimpleTokenizer.html),
+/// the runs of Chinese, Japanese or Korean characters being split into overlapping bigrams.
+///
+/// Indexing the bigrams makes it possible to search CJK texts without a dictionary:
+/// a word of several characters is searched as a phrase of bigrams.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// # fn main() {
+/// let tokenizer = CjkBigramTokenizer;
+/// let mut stream = tokenizer.token_stream("東京都 tokyo");
+/// assert_eq!(stream.next().unwrap().text, "東京");
+/// assert_eq!(stream.next().unwrap().text, "京都");
+/// assert_eq!(stream.next().unwrap().text, "tokyo");
+/// assert!(stream.next().is_none());
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CjkBigramTokenizer;
+
+impl<'a> Tokenizer<'a> for CjkBigramTokenizer {
+    type TokenStreamImpl = CjkTokenStream<'a, BigramSegmenter>;
+
+    fn token_stream(&self, text: &'a str) -> Self::TokenStreamImpl {
+        CjkTokenStream::new(text, BigramSegmenter)
+    }
+}
+
+pub struct CjkTokenStream<'a, S> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    segmenter: S,
+    // byte ranges of the words of the current CJK run.
+    words: VecDeque<Range<usize>>,
+    segment_buffer: Vec<Range<usize>>,
+    token: Token,
+}
+
+impl<'a, S: CjkSegmenter> CjkTokenStream<'a, S> {
+    fn new(text: &'a str, segmenter: S) -> CjkTokenStream<'a, S> {
+        CjkTokenStream {
+            text,
+            chars: text.char_indices().peekable(),
+            segmenter,
+            words: VecDeque::new(),
+            segment_buffer: Vec::new(),
+            token: Token::default(),
+        }
+    }
+
+    // search for the end of the current run of CJK or non-CJK characters.
+    fn search_run_end(&mut self, is_cjk_run: bool) -> usize {
+        while let Some(&(offset, c)) = self.chars.peek() {
+            if !c.is_alphanumeric() || is_cjk(c) != is_cjk_run {
+                return offset;
+            }
+            self.chars.next();
+        }
+        self.text.len()
+    }
+}
+
+impl<'a, S: CjkSegmenter> TokenStream for CjkTokenStream<'a, S> {
+    fn advance(&mut self) -> bool {
+        loop {
+            if let Some(word) = self.words.pop_front() {
+                self.token.text.clear();
+                self.token.text.push_str(&self.text[word.clone()]);
+                self.token.offset_from = word.start;
+                self.token.offset_to = word.end;
+                self.token.position = self.token.position.wrapping_add(1);
+                self.token.payload = 0;
+                return true;
+            }
+            let (run_start, c) = loop {
+                match self.chars.next() {
+                    Some((offset, c)) if c.is_alphanumeric() => break (offset, c),
+                    Some(_) => {}
+                    None => return false,
+                }
+            };
+            let is_cjk_run = is_cjk(c);
+            let run_end = self.search_run_end(is_cjk_run);
+            if is_cjk_run {
+                self.segment_buffer.clear();
+                self.segmenter
+                    .segment(&self.text[run_start..run_end], &mut self.segment_buffer);
+                self.words.extend(
+                    self.segment_buffer
+                        .iter()
+                        .map(|word| (run_start + word.start)..(run_start + word.end)),
+                );
+            } else {
+                self.words.push_back(run_start..run_end);
+            }
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CjkBigramTokenizer, CjkSegmenter, CjkTokenizer};
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{Token, TokenStream, Tokenizer};
+    use crate::Index;
+    use std::ops::Range;
+
+    fn tokenize<'a, T: Tokenizer<'a>>(tokenizer: T, text: &'a str) -> Vec<Token> {
+        let mut tokens = vec![];
+        tokenizer
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_cjk_bigram_tokenizer() {
+        let tokens = tokenize(CjkBigramTokenizer, "我爱北京, tiān'ānmén 天 한국어");
+        assert_eq!(tokens.len(), 8);
+        assert_token(&tokens[0], 0, "我爱", 0, 6);
+        assert_token(&tokens[1], 1, "爱北", 3, 9);
+        assert_token(&tokens[2], 2, "北京", 6, 12);
+        assert_token(&tokens[3], 3, "tiān", 14, 19);
+        assert_token(&tokens[4], 4, "ānmén", 20, 27);
+        assert_token(&tokens[5], 5, "天", 28, 31);
+        assert_token(&tokens[6], 6, "한국", 32, 38);
+        assert_token(&tokens[7], 7, "국어", 35, 41);
+        // Mixed runs are split.
+        let tokens = tokenize(CjkBigramTokenizer, "iPhone用");
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "iPhone", 0, 6);
+        assert_token(&tokens[1], 1, "用", 6, 9);
+    }
+
+    /// Segments the text with the longest words of a dictionary,
+    /// the unknown characters being single words.
+    #[derive(Clone)]
+    struct DictionarySegmenter(Vec<&'static str>);
+
+    impl CjkSegmenter for DictionarySegmenter {
+        fn segment(&self, text: &str, words: &mut Vec<Range<usize>>) {
+            let mut start = 0;
+            while start < text.len() {
+                let rest = &text[start..];
+                let word_len = self
+                    .0
+                    .iter()
+                    .filter(|word| rest.starts_with(*word))
+                    .map(|word| word.len())
+                    .max()
+                    .unwrap_or_else(|| rest.chars().next().unwrap().len_utf8());
+                words.push(start..start + word_len);
+                start += word_len;
+            }
+        }
+    }
+
+    #[test]
+    fn test_cjk_tokenizer_with_dictionary() {
+        let tokenizer = CjkTokenizer::new(DictionarySegmenter(vec!["北京", "北京大学", "我"]));
+        let tokens = tokenize(tokenizer, "我在北京大学");
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "我", 0, 3);
+        assert_token(&tokens[1], 1, "在", 3, 6);
+        assert_token(&tokens[2], 2, "北京大学", 6, 18);
+    }
+
+    #[test]
+    fn test_cjk_bigram_search() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("cjk")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "東京都に住んでいます"));
+        index_writer.add_document(doc!(text_field => "京都に行きました"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("京都"), 2);
+        assert_eq!(count("東京"), 1);
+        assert_eq!(count("\"京都に\""), 2);
+        assert_eq!(count("\"住んで\""), 1);
+        assert_eq!(count("\"大阪\""), 0);
+    }
+}
@@ -0,0 +1,220 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! # fn main() {
+//! let tokenizer = SimpleTokenizer
+//!   .filter(LowerCaser)
+//!   .filter(EdgeNgramFilter::new(2, 3, false));
+//!
+//! let mut stream = tokenizer.token_stream("Hello world");
+//! assert_eq!(stream.next().unwrap().text, "he");
+//! assert_eq!(stream.next().unwrap().text, "hel");
+//! assert_eq!(stream.next().unwrap().text, "wo");
+//! assert_eq!(stream.next().unwrap().text, "wor");
+//! assert!(stream.next().is_none());
+//! # }
+//! ```
+use super::{Token, TokenFilter, TokenStream};
+
+/// `TokenFilter` replacing each token by its prefixes of `min_gram` to `max_gram`
+/// characters, typically used to implement autocomplete at index time.
+///
+/// The prefixes keep the position and the offsets of their token.
+/// Tokens shorter than `min_gram` characters do not emit any prefix.
+/// With `preserve_original`, the tokens whose length is not within `min_gram`
+/// and `max_gram` are also emitted as is, after their prefixes.
+#[derive(Clone)]
+pub struct EdgeNgramFilter {
+    min_gram: usize,
+    max_gram: usize,
+    preserve_original: bool,
+}
+
+impl EdgeNgramFilter {
+    /// Creates an `EdgeNgramFilter` emitting the prefixes of
+    /// `min_gram` to `max_gram` characters of the tokens.
+    pub fn new(min_gram: usize, max_gram: usize, preserve_original: bool) -> EdgeNgramFilter {
+        assert!(min_gram > 0, "min_gram must be greater than 0");
+        assert!(
+            min_gram <= max_gram,
+            "min_gram must not be greater than max_gram"
+        );
+        EdgeNgramFilter {
+            min_gram,
+            max_gram,
+            preserve_original,
+        }
+    }
+}
+
+impl<TailTokenStream> TokenFilter<TailTokenStream> for EdgeNgramFilter
+where
+    TailTokenStream: TokenStream,
+{
+    type ResultTokenStream = EdgeNgramFilterStream<TailTokenStream>;
+
+    fn transform(&self, token_stream: TailTokenStream) -> Self::ResultTokenStream {
+        EdgeNgramFilterStream {
+            filter: self.clone(),
+            tail: token_stream,
+            token: Token::default(),
+            gram_ends: Vec::new(),
+            cursor: 0,
+            emit_original: false,
+        }
+    }
+}
+
+pub struct EdgeNgramFilterStream<TailTokenStream> {
+    filter: EdgeNgramFilter,
+    tail: TailTokenStream,
+    token: Token,
+    // byte offsets of the ends of the prefixes of the current tail token.
+    gram_ends: Vec<usize>,
+    cursor: usize,
+    emit_original: bool,
+}
+
+impl<TailTokenStream> EdgeNgramFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn emit(&mut self, text_end: Option<usize>) {
+        let tail_token = self.tail.token();
+        let text = match text_end {
+            Some(end) => &tail_token.text[..end],
+            None => &tail_token.text[..],
+        };
+        self.token.text.clear();
+        self.token.text.push_str(text);
+        self.token.offset_from = tail_token.offset_from;
+        self.token.offset_to = tail_token.offset_to;
+        self.token.position = tail_token.position;
+        self.token.position_length = tail_token.position_length;
+        self.token.payload = tail_token.payload;
+    }
+}
+
+impl<TailTokenStream> TokenStream for EdgeNgramFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn advance(&mut self) -> bool {
+        loop {
+            if self.cursor < self.gram_ends.len() {
+                let gram_end = self.gram_ends[self.cursor];
+                self.cursor += 1;
+                self.emit(Some(gram_end));
+                return true;
+            }
+            if self.emit_original {
+                self.emit_original = false;
+                self.emit(None);
+                return true;
+            }
+            if !self.tail.advance() {
+                return false;
+            }
+            self.gram_ends.clear();
+            self.cursor = 0;
+            let mut num_chars = 0;
+            for (offset, c) in self.tail.token().text.char_indices() {
+                num_chars += 1;
+                if num_chars > self.filter.max_gram {
+                    break;
+                }
+                if num_chars >= self.filter.min_gram {
+                    self.gram_ends.push(offset + c.len_utf8());
+                }
+            }
+            self.emit_original = self.filter.preserve_original
+                && (num_chars < self.filter.min_gram || num_chars > self.filter.max_gram);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EdgeNgramFilter;
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, Token, TokenStream, Tokenizer};
+    use crate::Index;
+
+    fn edge_ngram_helper(text: &str, filter: EdgeNgramFilter) -> Vec<Token> {
+        let mut tokens = vec![];
+        SimpleTokenizer
+            .filter(filter)
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_edge_ngram_filter() {
+        let tokens = edge_ngram_helper("a hεllo", EdgeNgramFilter::new(2, 3, false));
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 1, "hε", 2, 8);
+        assert_token(&tokens[1], 1, "hεl", 2, 8);
+    }
+
+    #[test]
+    fn test_edge_ngram_filter_preserve_original() {
+        let tokens = edge_ngram_helper("a hi hello", EdgeNgramFilter::new(2, 3, true));
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, "a", 0, 1);
+        assert_token(&tokens[1], 1, "hi", 2, 4);
+        assert_token(&tokens[2], 2, "he", 5, 10);
+        assert_token(&tokens[3], 2, "hel", 5, 10);
+        assert_token(&tokens[4], 2, "hello", 5, 10);
+    }
+
+    #[test]
+    fn test_edge_ngram_filter_autocomplete() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(
+            "title",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("autocomplete")
+                    .set_index_option(IndexRecordOption::WithFreqs),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "autocomplete",
+            SimpleTokenizer
+                .filter(LowerCaser)
+                .filter(EdgeNgramFilter::new(1, 10, true)),
+        );
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(title => "Autocomplete everything"));
+        index_writer.add_document(doc!(title => "Automatic"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        // The query is not split into prefixes.
+        index
+            .tokenizers()
+            .register("autocomplete", SimpleTokenizer.filter(LowerCaser));
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("auto"), 2);
+        assert_eq!(count("autoc"), 1);
+        assert_eq!(count("everything"), 1);
+        assert_eq!(count("utomatic"), 0);
+    }
+}
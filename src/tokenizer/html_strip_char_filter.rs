@@ -0,0 +1,167 @@
+use super::{CharFilter, FilteredText};
+use htmlescape::decode_html;
+
+/// Tags which do not separate words, e.g. `<b>Bold</b>face`.
+const INLINE_TAGS: [&str; 18] = [
+    "a", "abbr", "b", "bdi", "bdo", "cite", "code", "em", "font", "i", "kbd", "mark", "q", "s",
+    "small", "span", "strong", "u",
+];
+
+/// Tags whose content is not text.
+const RAW_TEXT_TAGS: [&str; 2] = ["script", "style"];
+
+/// The longest entity name is `&CounterClockwiseContourIntegral;`.
+const MAX_ENTITY_LEN: usize = 33;
+
+/// `CharFilter` removing the markup of an HTML document.
+///
+/// The tags and the comments are removed, as well as the content of
+/// the `<script>` and `<style>` elements, and the character entities are decoded.
+/// The tags are replaced by a line break, except for the comments and the
+/// inline tags like `<b>` or `<span>`, which do not separate words.
+///
+/// A `<` which does not start a tag is kept as is.
+///
+/// See [`Tokenizer::char_filter`](./trait.Tokenizer.html#method.char_filter)
+/// for an example.
+#[derive(Clone, Default)]
+pub struct HtmlStripCharFilter;
+
+/// Returns the name of the tag starting at the beginning of `text`,
+/// which is a `<` followed by an optional `/`.
+fn tag_name(text: &str) -> &str {
+    let name_start = if text[1..].starts_with('/') { 2 } else { 1 };
+    let name_len = text[name_start..]
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(text.len() - name_start);
+    &text[name_start..name_start + name_len]
+}
+
+/// Returns the length of the element starting at the beginning of `text`,
+/// if it is a markup element.
+fn markup_len(text: &str) -> Option<usize> {
+    if text.starts_with("<!--") {
+        return Some(text.find("-->").map(|end| end + 3).unwrap_or(text.len()));
+    }
+    let is_tag_start = text[1..]
+        .chars()
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?')
+        .unwrap_or(false);
+    if !is_tag_start {
+        return None;
+    }
+    let tag_len = text.find('>')? + 1;
+    let name = tag_name(text).to_ascii_lowercase();
+    if text[1..].starts_with('/') || !RAW_TEXT_TAGS.contains(&name.as_str()) {
+        return Some(tag_len);
+    }
+    // The content of a raw text element is removed along with its tags.
+    let closing_tag = format!("</{}", name);
+    let content_len = text[tag_len..]
+        .to_ascii_lowercase()
+        .find(&closing_tag)
+        .map(|closing_tag_start| {
+            let closing_tag_start = tag_len + closing_tag_start;
+            text[closing_tag_start..]
+                .find('>')
+                .map(|closing_tag_len| closing_tag_start + closing_tag_len + 1)
+                .unwrap_or(text.len())
+        })
+        .unwrap_or(text.len());
+    Some(content_len)
+}
+
+/// Returns the decoded value of the character entity starting at the beginning
+/// of `text`, along with its length.
+fn decode_entity(text: &str) -> Option<(String, usize)> {
+    let entity_len = text
+        .char_indices()
+        .take(MAX_ENTITY_LEN)
+        .find(|&(_, c)| c == ';')
+        .map(|(offset, _)| offset + 1)?;
+    let decoded = decode_html(&text[..entity_len]).ok()?;
+    Some((decoded, entity_len))
+}
+
+impl CharFilter for HtmlStripCharFilter {
+    fn filter(&self, text: &str, filtered_text: &mut FilteredText) {
+        let mut offset = 0;
+        while offset < text.len() {
+            let rest = &text[offset..];
+            if rest.starts_with('<') {
+                if let Some(markup_len) = markup_len(rest) {
+                    let name = tag_name(rest).to_ascii_lowercase();
+                    let is_comment = rest.starts_with("<!--");
+                    let replacement = if is_comment || INLINE_TAGS.contains(&name.as_str()) {
+                        ""
+                    } else {
+                        "\n"
+                    };
+                    filtered_text.push_replacement(replacement, offset..offset + markup_len);
+                    offset += markup_len;
+                    continue;
+                }
+            } else if rest.starts_with('&') {
+                if let Some((decoded, entity_len)) = decode_entity(rest) {
+                    filtered_text.push_replacement(&decoded, offset..offset + entity_len);
+                    offset += entity_len;
+                    continue;
+                }
+            }
+            // The first character is kept, even if it is a `<` or a `&`.
+            let text_len = rest[1..]
+                .find(&['<', '&'][..])
+                .map(|text_len| text_len + 1)
+                .unwrap_or(rest.len());
+            filtered_text.push_original(&rest[..text_len], offset);
+            offset += text_len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HtmlStripCharFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        CharFilter, FilteredText, SimpleTokenizer, Token, TokenStream, Tokenizer,
+    };
+
+    fn strip(html: &str) -> String {
+        let mut filtered_text = FilteredText::default();
+        HtmlStripCharFilter.filter(html, &mut filtered_text);
+        filtered_text.text().to_string()
+    }
+
+    #[test]
+    fn test_html_strip_char_filter() {
+        assert_eq!(
+            strip("<p class=\"x\">Bold<b>face</b></p><div>end</div>"),
+            "\nBoldface\n\nend\n"
+        );
+        assert_eq!(strip("a <!-- comment --> b"), "a  b");
+        assert_eq!(
+            strip("<style>p { color: red; }</style>x<SCRIPT>if (a < b) {}</script>"),
+            "\nx\n"
+        );
+        assert_eq!(strip("caf&eacute; &lt;3 &#233;"), "café <3 é");
+        assert_eq!(strip("1 < 2 & 3 &unknown; <"), "1 < 2 & 3 &unknown; <");
+    }
+
+    #[test]
+    fn test_html_strip_char_filter_offsets() {
+        let html = "<h1>Caf&eacute;</h1><p>Fish &amp; <i>chips</i></p>";
+        let mut tokens = vec![];
+        SimpleTokenizer
+            .char_filter(HtmlStripCharFilter)
+            .token_stream(html)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "Café", 4, 15);
+        assert_token(&tokens[1], 1, "Fish", 23, 27);
+        assert_token(&tokens[2], 2, "chips", 37, 42);
+        assert_eq!(&html[4..15], "Caf&eacute;");
+        assert_eq!(&html[37..42], "chips");
+    }
+}
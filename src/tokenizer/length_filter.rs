@@ -0,0 +1,109 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! # fn main() {
+//! let tokenizer = SimpleTokenizer
+//!   .filter(LengthFilter::new(2, 5));
+//!
+//! let mut stream = tokenizer.token_stream("a toolong nice é");
+//! // `a` and `é` are shorter than 2 characters,
+//! // and `toolong` is longer than 5 characters.
+//! assert_eq!(stream.next().unwrap().text, "nice");
+//! assert!(stream.next().is_none());
+//! # }
+//! ```
+use super::{Token, TokenFilter, TokenStream};
+
+/// `LengthFilter` removes the tokens whose length, in characters,
+/// is not within `min_len` and `max_len` (inclusive).
+///
+/// Unlike the `RemoveLongFilter`, the length of the tokens is measured
+/// in characters rather than in bytes.
+#[derive(Clone)]
+pub struct LengthFilter {
+    min_len: usize,
+    max_len: usize,
+}
+
+impl LengthFilter {
+    /// Creates a `LengthFilter` keeping the tokens of `min_len` to `max_len` characters.
+    pub fn new(min_len: usize, max_len: usize) -> LengthFilter {
+        assert!(
+            min_len <= max_len,
+            "min_len must not be greater than max_len"
+        );
+        LengthFilter { min_len, max_len }
+    }
+}
+
+impl<TailTokenStream> TokenFilter<TailTokenStream> for LengthFilter
+where
+    TailTokenStream: TokenStream,
+{
+    type ResultTokenStream = LengthFilterStream<TailTokenStream>;
+
+    fn transform(&self, token_stream: TailTokenStream) -> Self::ResultTokenStream {
+        LengthFilterStream {
+            filter: self.clone(),
+            tail: token_stream,
+        }
+    }
+}
+
+pub struct LengthFilterStream<TailTokenStream> {
+    filter: LengthFilter,
+    tail: TailTokenStream,
+}
+
+impl<TailTokenStream> LengthFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn predicate(&self, token: &Token) -> bool {
+        // The number of characters is bounded by the number of bytes.
+        if token.text.len() < self.filter.min_len {
+            return false;
+        }
+        let len = token.text.chars().count();
+        self.filter.min_len <= len && len <= self.filter.max_len
+    }
+}
+
+impl<TailTokenStream> TokenStream for LengthFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+
+    fn advance(&mut self) -> bool {
+        while self.tail.advance() {
+            if self.predicate(self.tail.token()) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LengthFilter;
+    use crate::tokenizer::{SimpleTokenizer, Token, TokenStream, Tokenizer};
+
+    #[test]
+    fn test_length_filter_counts_chars() {
+        let mut tokens = vec![];
+        SimpleTokenizer
+            .filter(LengthFilter::new(3, 4))
+            .token_stream("ab abc été ûûûûû abcd abcde")
+            .process(&mut |token: &Token| tokens.push(token.text.clone()));
+        assert_eq!(tokens, vec!["abc", "été", "abcd"]);
+    }
+}
@@ -54,6 +54,22 @@
 //! remove their inflection. This tokenizer is slower than the default one,
 //! but is recommended to improve recall.
 //!
+//! ## `cjk`
+//!
+//! Like `default`, except that the runs of Chinese, Japanese and Korean characters
+//! are split into overlapping bigrams by the [`CjkBigramTokenizer`](./struct.CjkBigramTokenizer.html).
+//! A dictionary-based segmentation can be plugged into the [`CjkTokenizer`](./struct.CjkTokenizer.html)
+//! by implementing the [`CjkSegmenter`](./trait.CjkSegmenter.html) trait.
+//!
+//! ## `fr_stem`, `de_stem`, ...
+//!
+//! A stemming tokenizer is registered for each of the [`Language`s](./enum.Language.html)
+//! supported by the [`Stemmer`](./struct.Stemmer.html), named after the ISO 639-1 code
+//! of the language: `ar_stem`, `da_stem`, `nl_stem`, `fi_stem`, `fr_stem`, `de_stem`,
+//! `el_stem`, `hu_stem`, `it_stem`, `pt_stem`, `ro_stem`, `ru_stem`, `es_stem`,
+//! `sv_stem`, `ta_stem` and `tr_stem`. In a multilingual corpus, each language can be
+//! indexed in its own field, with its own stemming tokenizer.
+//!
 //!
 //! # Custom tokenizers
 //!
@@ -61,6 +77,10 @@
 //! or you can extend an existing [`Tokenizer`](./trait.Tokenizer.html) by chaining it several
 //! [`TokenFilter`s](./trait.TokenFilter.html).
 //!
+//! The text can also be transformed before its tokenization by a
+//! [`CharFilter`](./trait.CharFilter.html), e.g. the [`HtmlStripCharFilter`](./struct.HtmlStripCharFilter.html)
+//! removing the markup of HTML documents. The offsets of the tokens still refer to the original text.
+//!
 //! For instance, the `en_stem` is defined as follows.
 //!
 //! ```rust
@@ -127,33 +147,55 @@
 //!
 mod alphanum_only;
 mod ascii_folding_filter;
+mod char_filter;
+mod cjk_tokenizer;
+mod edge_ngram_filter;
 mod facet_tokenizer;
+mod html_strip_char_filter;
+mod length_filter;
 mod lower_caser;
 mod ngram_tokenizer;
+mod pattern_replace_char_filter;
+mod phonetic_filter;
 mod raw_tokenizer;
 mod remove_long;
 mod simple_tokenizer;
 mod stemmer;
 mod stop_word_filter;
+mod synonym_filter;
+mod text_analyzer_def;
 mod token_stream_chain;
 mod tokenizer;
 mod tokenizer_manager;
+mod unicode_normalizer;
+mod word_delimiter_filter;
 
 pub use self::alphanum_only::AlphaNumOnlyFilter;
 pub use self::ascii_folding_filter::AsciiFoldingFilter;
+pub use self::char_filter::{CharFilter, CharFilterTokenizer, FilteredText};
+pub use self::cjk_tokenizer::{BigramSegmenter, CjkBigramTokenizer, CjkSegmenter, CjkTokenizer};
+pub use self::edge_ngram_filter::EdgeNgramFilter;
 pub use self::facet_tokenizer::FacetTokenizer;
+pub use self::html_strip_char_filter::HtmlStripCharFilter;
+pub use self::length_filter::LengthFilter;
 pub use self::lower_caser::LowerCaser;
 pub use self::ngram_tokenizer::NgramTokenizer;
+pub use self::pattern_replace_char_filter::PatternReplaceCharFilter;
+pub use self::phonetic_filter::{PhoneticAlgorithm, PhoneticFilter};
 pub use self::raw_tokenizer::RawTokenizer;
 pub use self::remove_long::RemoveLongFilter;
 pub use self::simple_tokenizer::SimpleTokenizer;
 pub use self::stemmer::{Language, Stemmer};
 pub use self::stop_word_filter::StopWordFilter;
+pub use self::synonym_filter::SynonymFilter;
+pub use self::text_analyzer_def::{CharFilterDef, TextAnalyzerDef, TokenFilterDef, TokenizerDef};
 pub(crate) use self::token_stream_chain::TokenStreamChain;
 pub use self::tokenizer::BoxedTokenizer;
 
 pub use self::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
 pub use self::tokenizer_manager::TokenizerManager;
+pub use self::unicode_normalizer::{NormalizationForm, UnicodeNormalizer};
+pub use self::word_delimiter_filter::WordDelimiterFilter;
 
 /// Maximum authorized len (in bytes) for a token.
 ///
@@ -256,6 +298,24 @@ pub mod tests {
         assert_token(&tokens[2], 2, "φορολογουμεν", 37, 63);
     }
 
+    #[test]
+    fn test_language_tokenizers() {
+        let tokenizer_manager = TokenizerManager::default();
+        let stem = |tokenizer_name: &str, text: &str| -> Vec<String> {
+            let mut tokens: Vec<String> = vec![];
+            tokenizer_manager
+                .get(tokenizer_name)
+                .unwrap()
+                .token_stream(text)
+                .process(&mut |token: &Token| tokens.push(token.text.clone()));
+            tokens
+        };
+        assert_eq!(stem("fr_stem", "Les Chevaux"), vec!["le", "cheval"]);
+        assert_eq!(stem("de_stem", "Häuser"), vec!["haus"]);
+        assert_eq!(stem("es_stem", "corriendo"), vec!["corr"]);
+        assert_eq!(stem("ru_stem", "книги"), vec!["книг"]);
+    }
+
     #[test]
     fn test_tokenizer_empty() {
         let tokenizer_manager = TokenizerManager::default();
@@ -158,6 +158,7 @@ impl<'a> TokenStream for NgramTokenStream<'a> {
             self.token.offset_to = offset_to;
             self.token.text.clear();
             self.token.text.push_str(&self.text[offset_from..offset_to]);
+            self.token.payload = 0;
             true
         } else {
             false
@@ -0,0 +1,98 @@
+use super::{CharFilter, FilteredText};
+use crate::TantivyError;
+use regex::Regex;
+
+/// `CharFilter` replacing the matches of a regular expression.
+///
+/// The replacement may refer to the capture groups of the match,
+/// using the syntax of [`Regex::replace`](https://docs.rs/regex/1/regex/struct.Regex.html#method.replace),
+/// e.g. `$1` or `${name}`.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// # fn main() {
+/// // Phone numbers are indexed as a single token.
+/// let tokenizer = SimpleTokenizer
+///     .char_filter(PatternReplaceCharFilter::new("([0-9]+)-([0-9]+)", "$1$2").unwrap());
+///
+/// let mut stream = tokenizer.token_stream("call 555-0123");
+/// assert_eq!(stream.next().unwrap().text, "call");
+/// let token = stream.next().unwrap();
+/// assert_eq!((token.text.as_str(), token.offset_from, token.offset_to), ("5550123", 5, 13));
+/// assert!(stream.next().is_none());
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PatternReplaceCharFilter {
+    regex: Regex,
+    replacement: String,
+}
+
+impl PatternReplaceCharFilter {
+    /// Creates a `PatternReplaceCharFilter` replacing the matches of `pattern`
+    /// by `replacement`.
+    ///
+    /// Returns an `InvalidArgument` error if the pattern cannot be compiled.
+    pub fn new(pattern: &str, replacement: &str) -> crate::Result<PatternReplaceCharFilter> {
+        let regex = Regex::new(pattern).map_err(|err| {
+            TantivyError::InvalidArgument(format!("Invalid regex {:?}: {}", pattern, err))
+        })?;
+        Ok(PatternReplaceCharFilter {
+            regex,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+impl CharFilter for PatternReplaceCharFilter {
+    fn filter(&self, text: &str, filtered_text: &mut FilteredText) {
+        let mut offset = 0;
+        let mut replacement = String::new();
+        for captures in self.regex.captures_iter(text) {
+            let matched = captures.get(0).expect("the group 0 is the whole match");
+            filtered_text.push_original(&text[offset..matched.start()], offset);
+            replacement.clear();
+            captures.expand(&self.replacement, &mut replacement);
+            filtered_text.push_replacement(&replacement, matched.start()..matched.end());
+            offset = matched.end();
+        }
+        filtered_text.push_original(&text[offset..], offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PatternReplaceCharFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        HtmlStripCharFilter, LowerCaser, SimpleTokenizer, Token, TokenStream, Tokenizer,
+    };
+    use crate::TantivyError;
+
+    #[test]
+    fn test_pattern_replace_char_filter() {
+        let text = "<b>Ph.D.</b> in A.I.";
+        let mut tokens = vec![];
+        SimpleTokenizer
+            .filter(LowerCaser)
+            .char_filter(PatternReplaceCharFilter::new(r"([A-Za-z])\.", "$1").unwrap())
+            .char_filter(HtmlStripCharFilter)
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "phd", 3, 8);
+        assert_token(&tokens[1], 1, "in", 13, 15);
+        assert_token(&tokens[2], 2, "ai", 16, 20);
+    }
+
+    #[test]
+    fn test_pattern_replace_char_filter_invalid_pattern() {
+        match PatternReplaceCharFilter::new("jap[ao", "") {
+            Err(TantivyError::InvalidArgument(msg)) => assert!(msg.contains("jap[ao")),
+            _ => panic!("Expected an invalid argument error"),
+        }
+    }
+}
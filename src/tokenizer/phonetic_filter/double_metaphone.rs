@@ -0,0 +1,711 @@
+//! Port of the Double Metaphone algorithm by Lawrence Philips,
+//! following the implementation of Apache Commons Codec.
+
+const MAX_CODE_LEN: usize = 4;
+
+const SILENT_START: [&str; 5] = ["GN", "KN", "PN", "WR", "PS"];
+const L_R_N_M_B_H_F_V_W_SPACE: [&str; 10] = ["L", "R", "N", "M", "B", "H", "F", "V", "W", " "];
+const ES_EP_EB_EL_EY_IB_IL_IN_IE_EI_ER: [&str; 11] = [
+    "ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER",
+];
+const L_T_K_S_N_M_B_Z: [&str; 8] = ["L", "T", "K", "S", "N", "M", "B", "Z"];
+
+/// Primary and alternate codes being built.
+struct Codes {
+    primary: String,
+    alternate: String,
+}
+
+impl Codes {
+    fn append_primary(&mut self, code: &str) {
+        let len = code
+            .len()
+            .min(MAX_CODE_LEN.saturating_sub(self.primary.len()));
+        self.primary.push_str(&code[..len]);
+    }
+
+    fn append_alternate(&mut self, code: &str) {
+        let len = code
+            .len()
+            .min(MAX_CODE_LEN.saturating_sub(self.alternate.len()));
+        self.alternate.push_str(&code[..len]);
+    }
+
+    fn append(&mut self, code: &str) {
+        self.append_primary(code);
+        self.append_alternate(code);
+    }
+
+    fn append_both(&mut self, primary: &str, alternate: &str) {
+        self.append_primary(primary);
+        self.append_alternate(alternate);
+    }
+
+    fn is_complete(&self) -> bool {
+        self.primary.len() >= MAX_CODE_LEN && self.alternate.len() >= MAX_CODE_LEN
+    }
+}
+
+/// Uppercased word being encoded.
+struct Word {
+    chars: Vec<char>,
+    is_slavo_germanic: bool,
+}
+
+impl Word {
+    fn new(text: &str) -> Word {
+        let chars: Vec<char> = text.trim().chars().flat_map(char::to_uppercase).collect();
+        let text: String = chars.iter().collect();
+        let is_slavo_germanic = text.contains('W') || text.contains('K') || text.contains("CZ");
+        Word {
+            chars,
+            is_slavo_germanic,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Returns the character at `index`, or `'\0'` if `index` is out of bounds.
+    fn char_at(&self, index: isize) -> char {
+        if index < 0 {
+            return '\0';
+        }
+        self.chars.get(index as usize).cloned().unwrap_or('\0')
+    }
+
+    fn is_vowel_at(&self, index: isize) -> bool {
+        is_vowel(self.char_at(index))
+    }
+
+    /// Returns true if the `len` characters starting at `start` are one of `candidates`.
+    fn contains(&self, start: isize, len: usize, candidates: &[&str]) -> bool {
+        if start < 0 || start as usize + len > self.chars.len() {
+            return false;
+        }
+        let start = start as usize;
+        let chars = &self.chars[start..start + len];
+        candidates
+            .iter()
+            .any(|candidate| candidate.chars().eq(chars.iter().cloned()))
+    }
+
+    fn is_last(&self, index: isize) -> bool {
+        index == self.len() as isize - 1
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    "AEIOUY".contains(c)
+}
+
+/// Returns the primary and alternate Double Metaphone codes of `text`,
+/// which may be empty, e.g. if `text` does not contain any letter.
+pub(crate) fn double_metaphone(text: &str) -> (String, String) {
+    let word = Word::new(text);
+    let mut codes = Codes {
+        primary: String::with_capacity(MAX_CODE_LEN),
+        alternate: String::with_capacity(MAX_CODE_LEN),
+    };
+    if word.len() == 0 {
+        return (codes.primary, codes.alternate);
+    }
+    let mut index: isize = if word.contains(0, 2, &SILENT_START) {
+        1
+    } else {
+        0
+    };
+    while !codes.is_complete() && index < word.len() as isize {
+        index = match word.char_at(index) {
+            'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                if index == 0 {
+                    codes.append("A");
+                }
+                index + 1
+            }
+            'B' => {
+                codes.append("P");
+                skip_double(&word, index, 'B')
+            }
+            'Ç' => {
+                codes.append("S");
+                index + 1
+            }
+            'C' => handle_c(&word, &mut codes, index),
+            'D' => handle_d(&word, &mut codes, index),
+            'F' => {
+                codes.append("F");
+                skip_double(&word, index, 'F')
+            }
+            'G' => handle_g(&word, &mut codes, index),
+            'H' => handle_h(&word, &mut codes, index),
+            'J' => handle_j(&word, &mut codes, index),
+            'K' => {
+                codes.append("K");
+                skip_double(&word, index, 'K')
+            }
+            'L' => handle_l(&word, &mut codes, index),
+            'M' => {
+                codes.append("M");
+                if condition_m0(&word, index) {
+                    index + 2
+                } else {
+                    index + 1
+                }
+            }
+            'N' => {
+                codes.append("N");
+                skip_double(&word, index, 'N')
+            }
+            'Ñ' => {
+                codes.append("N");
+                index + 1
+            }
+            'P' => handle_p(&word, &mut codes, index),
+            'Q' => {
+                codes.append("K");
+                skip_double(&word, index, 'Q')
+            }
+            'R' => handle_r(&word, &mut codes, index),
+            'S' => handle_s(&word, &mut codes, index),
+            'T' => handle_t(&word, &mut codes, index),
+            'V' => {
+                codes.append("F");
+                skip_double(&word, index, 'V')
+            }
+            'W' => handle_w(&word, &mut codes, index),
+            'X' => handle_x(&word, &mut codes, index),
+            'Z' => handle_z(&word, &mut codes, index),
+            _ => index + 1,
+        };
+    }
+    (codes.primary, codes.alternate)
+}
+
+/// Returns the index following the character at `index`, and its duplicate if any.
+fn skip_double(word: &Word, index: isize, c: char) -> isize {
+    if word.char_at(index + 1) == c {
+        index + 2
+    } else {
+        index + 1
+    }
+}
+
+fn handle_c(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if condition_c0(word, index) {
+        codes.append("K");
+        index + 2
+    } else if index == 0 && word.contains(index, 6, &["CAESAR"]) {
+        codes.append("S");
+        index + 2
+    } else if word.contains(index, 2, &["CH"]) {
+        handle_ch(word, codes, index)
+    } else if word.contains(index, 2, &["CZ"]) && !word.contains(index - 2, 4, &["WICZ"]) {
+        // "Czerny"
+        codes.append_both("S", "X");
+        index + 2
+    } else if word.contains(index + 1, 3, &["CIA"]) {
+        // "focaccia"
+        codes.append("X");
+        index + 3
+    } else if word.contains(index, 2, &["CC"]) && !(index == 1 && word.char_at(0) == 'M') {
+        // double "cc", but not "McClelland"
+        handle_cc(word, codes, index)
+    } else if word.contains(index, 2, &["CK", "CG", "CQ"]) {
+        codes.append("K");
+        index + 2
+    } else if word.contains(index, 2, &["CI", "CE", "CY"]) {
+        // Italian vs. English
+        if word.contains(index, 3, &["CIO", "CIE", "CIA"]) {
+            codes.append_both("S", "X");
+        } else {
+            codes.append("S");
+        }
+        index + 2
+    } else {
+        codes.append("K");
+        if word.contains(index + 1, 2, &[" C", " Q", " G"]) {
+            // "Mac Caffrey", "Mac Gregor"
+            index + 3
+        } else if word.contains(index + 1, 1, &["C", "K", "Q"])
+            && !word.contains(index + 1, 2, &["CE", "CI"])
+        {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+}
+
+fn handle_cc(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.contains(index + 2, 1, &["I", "E", "H"]) && !word.contains(index + 2, 2, &["HU"]) {
+        // "bellocchio", but not "bacchus"
+        if (index == 1 && word.char_at(index - 1) == 'A')
+            || word.contains(index - 1, 5, &["UCCEE", "UCCES"])
+        {
+            // "accident", "accede", "succeed"
+            codes.append("KS");
+        } else {
+            // "bacci", "bertucci", other Italian
+            codes.append("X");
+        }
+        index + 3
+    } else {
+        // Pierce's rule
+        codes.append("K");
+        index + 2
+    }
+}
+
+fn handle_ch(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if index > 0 && word.contains(index, 4, &["CHAE"]) {
+        // "Michael"
+        codes.append_both("K", "X");
+    } else if condition_ch0(word, index) || condition_ch1(word, index) {
+        // Greek roots, e.g. "chemistry", "chorus", or Germanic
+        codes.append("K");
+    } else if index > 0 {
+        if word.contains(0, 2, &["MC"]) {
+            codes.append("K");
+        } else {
+            codes.append_both("X", "K");
+        }
+    } else {
+        codes.append("X");
+    }
+    index + 2
+}
+
+fn handle_d(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.contains(index, 2, &["DG"]) {
+        if word.contains(index + 2, 1, &["I", "E", "Y"]) {
+            // "Edge"
+            codes.append("J");
+            index + 3
+        } else {
+            // "Edgar"
+            codes.append("TK");
+            index + 2
+        }
+    } else if word.contains(index, 2, &["DT", "DD"]) {
+        codes.append("T");
+        index + 2
+    } else {
+        codes.append("T");
+        index + 1
+    }
+}
+
+fn handle_g(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.char_at(index + 1) == 'H' {
+        handle_gh(word, codes, index)
+    } else if word.char_at(index + 1) == 'N' {
+        if index == 1 && word.is_vowel_at(0) && !word.is_slavo_germanic {
+            codes.append_both("KN", "N");
+        } else if !word.contains(index + 2, 2, &["EY"])
+            && word.char_at(index + 1) != 'Y'
+            && !word.is_slavo_germanic
+        {
+            codes.append_both("N", "KN");
+        } else {
+            codes.append("KN");
+        }
+        index + 2
+    } else if word.contains(index + 1, 2, &["LI"]) && !word.is_slavo_germanic {
+        codes.append_both("KL", "L");
+        index + 2
+    } else if index == 0
+        && (word.char_at(index + 1) == 'Y'
+            || word.contains(index + 1, 2, &ES_EP_EB_EL_EY_IB_IL_IN_IE_EI_ER))
+    {
+        // -ges-, -gep-, -gel-, -gie- at the beginning
+        codes.append_both("K", "J");
+        index + 2
+    } else if (word.contains(index + 1, 2, &["ER"]) || word.char_at(index + 1) == 'Y')
+        && !word.contains(0, 6, &["DANGER", "RANGER", "MANGER"])
+        && !word.contains(index - 1, 1, &["E", "I"])
+        && !word.contains(index - 1, 3, &["RGY", "OGY"])
+    {
+        // -ger-, -gy-
+        codes.append_both("K", "J");
+        index + 2
+    } else if word.contains(index + 1, 1, &["E", "I", "Y"])
+        || word.contains(index - 1, 4, &["AGGI", "OGGI"])
+    {
+        // Italian "biaggi"
+        if word.contains(0, 4, &["VAN ", "VON "])
+            || word.contains(0, 3, &["SCH"])
+            || word.contains(index + 1, 2, &["ET"])
+        {
+            // obvious Germanic
+            codes.append("K");
+        } else if word.contains(index + 1, 3, &["IER"]) {
+            codes.append("J");
+        } else {
+            codes.append_both("J", "K");
+        }
+        index + 2
+    } else if word.char_at(index + 1) == 'G' {
+        codes.append("K");
+        index + 2
+    } else {
+        codes.append("K");
+        index + 1
+    }
+}
+
+fn handle_gh(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if index > 0 && !word.is_vowel_at(index - 1) {
+        codes.append("K");
+    } else if index == 0 {
+        if word.char_at(index + 2) == 'I' {
+            codes.append("J");
+        } else {
+            codes.append("K");
+        }
+    } else if (index > 1 && word.contains(index - 2, 1, &["B", "H", "D"]))
+        || (index > 2 && word.contains(index - 3, 1, &["B", "H", "D"]))
+        || (index > 3 && word.contains(index - 4, 1, &["B", "H"]))
+    {
+        // Parker's rule, e.g. "hugh"
+    } else if index > 2
+        && word.char_at(index - 1) == 'U'
+        && word.contains(index - 3, 1, &["C", "G", "L", "R", "T"])
+    {
+        // "laugh", "McLaughlin", "cough", "gough", "rough", "tough"
+        codes.append("F");
+    } else if index > 0 && word.char_at(index - 1) != 'I' {
+        codes.append("K");
+    }
+    index + 2
+}
+
+fn handle_h(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    // only kept if first and before a vowel, or between two vowels
+    if (index == 0 || word.is_vowel_at(index - 1)) && word.is_vowel_at(index + 1) {
+        codes.append("H");
+        index + 2
+    } else {
+        index + 1
+    }
+}
+
+fn handle_j(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.contains(index, 4, &["JOSE"]) || word.contains(0, 4, &["SAN "]) {
+        // obvious Spanish, "Jose", "San Jacinto"
+        if (index == 0 && word.char_at(index + 4) == ' ')
+            || word.len() == 4
+            || word.contains(0, 4, &["SAN "])
+        {
+            codes.append("H");
+        } else {
+            codes.append_both("J", "H");
+        }
+        return index + 1;
+    }
+    if index == 0 {
+        codes.append_both("J", "A");
+    } else if word.is_vowel_at(index - 1)
+        && !word.is_slavo_germanic
+        && (word.char_at(index + 1) == 'A' || word.char_at(index + 1) == 'O')
+    {
+        codes.append_both("J", "H");
+    } else if word.is_last(index) {
+        codes.append_both("J", " ");
+    } else if !word.contains(index + 1, 1, &L_T_K_S_N_M_B_Z)
+        && !word.contains(index - 1, 1, &["S", "K", "L"])
+    {
+        codes.append("J");
+    }
+    skip_double(word, index, 'J')
+}
+
+fn handle_l(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.char_at(index + 1) == 'L' {
+        if condition_l0(word, index) {
+            codes.append_primary("L");
+        } else {
+            codes.append("L");
+        }
+        index + 2
+    } else {
+        codes.append("L");
+        index + 1
+    }
+}
+
+fn handle_p(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.char_at(index + 1) == 'H' {
+        codes.append("F");
+        index + 2
+    } else {
+        codes.append("P");
+        if word.contains(index + 1, 1, &["P", "B"]) {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+}
+
+fn handle_r(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.is_last(index)
+        && !word.is_slavo_germanic
+        && word.contains(index - 2, 2, &["IE"])
+        && !word.contains(index - 4, 2, &["ME", "MA"])
+    {
+        // French, e.g. "rogier"
+        codes.append_alternate("R");
+    } else {
+        codes.append("R");
+    }
+    skip_double(word, index, 'R')
+}
+
+fn handle_s(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.contains(index - 1, 3, &["ISL", "YSL"]) {
+        // "island", "isle", "carlisle", "carlysle"
+        index + 1
+    } else if index == 0 && word.contains(index, 5, &["SUGAR"]) {
+        codes.append_both("X", "S");
+        index + 1
+    } else if word.contains(index, 2, &["SH"]) {
+        if word.contains(index + 1, 4, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+            // Germanic
+            codes.append("S");
+        } else {
+            codes.append("X");
+        }
+        index + 2
+    } else if word.contains(index, 3, &["SIO", "SIA"]) || word.contains(index, 4, &["SIAN"]) {
+        // Italian and Armenian
+        if word.is_slavo_germanic {
+            codes.append("S");
+        } else {
+            codes.append_both("S", "X");
+        }
+        index + 3
+    } else if (index == 0 && word.contains(index + 1, 1, &["M", "N", "L", "W"]))
+        || word.contains(index + 1, 1, &["Z"])
+    {
+        // German and anglicisations, e.g. "smith" matches "schmidt",
+        // and -sz- in Slavic languages
+        codes.append_both("S", "X");
+        if word.contains(index + 1, 1, &["Z"]) {
+            index + 2
+        } else {
+            index + 1
+        }
+    } else if word.contains(index, 2, &["SC"]) {
+        handle_sc(word, codes, index)
+    } else {
+        if word.is_last(index) && word.contains(index - 2, 2, &["AI", "OI"]) {
+            // French, e.g. "resnais", "artois"
+            codes.append_alternate("S");
+        } else {
+            codes.append("S");
+        }
+        if word.contains(index + 1, 1, &["S", "Z"]) {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+}
+
+fn handle_sc(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.char_at(index + 2) == 'H' {
+        // Schlesinger's rule
+        if word.contains(index + 3, 2, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+            // Dutch origin, e.g. "school", "schooner"
+            if word.contains(index + 3, 2, &["ER", "EN"]) {
+                // "schermerhorn", "schenker"
+                codes.append_both("X", "SK");
+            } else {
+                codes.append("SK");
+            }
+        } else if index == 0 && !word.is_vowel_at(3) && word.char_at(3) != 'W' {
+            codes.append_both("X", "S");
+        } else {
+            codes.append("X");
+        }
+    } else if word.contains(index + 2, 1, &["I", "E", "Y"]) {
+        codes.append("S");
+    } else {
+        codes.append("SK");
+    }
+    index + 3
+}
+
+fn handle_t(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.contains(index, 4, &["TION"]) || word.contains(index, 3, &["TIA", "TCH"]) {
+        codes.append("X");
+        index + 3
+    } else if word.contains(index, 2, &["TH"]) || word.contains(index, 3, &["TTH"]) {
+        if word.contains(index + 2, 2, &["OM", "AM"])
+            || word.contains(0, 4, &["VAN ", "VON "])
+            || word.contains(0, 3, &["SCH"])
+        {
+            // "thomas", "thames", or Germanic
+            codes.append("T");
+        } else {
+            codes.append_both("0", "T");
+        }
+        index + 2
+    } else {
+        codes.append("T");
+        if word.contains(index + 1, 1, &["T", "D"]) {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+}
+
+fn handle_w(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.contains(index, 2, &["WR"]) {
+        // can also be in the middle of a word
+        codes.append("R");
+        index + 2
+    } else if index == 0 && (word.is_vowel_at(index + 1) || word.contains(index, 2, &["WH"])) {
+        if word.is_vowel_at(index + 1) {
+            // "Wasserman" matches "Vasserman"
+            codes.append_both("A", "F");
+        } else {
+            // "Uomo" matches "Womo"
+            codes.append("A");
+        }
+        index + 1
+    } else if (word.is_last(index) && word.is_vowel_at(index - 1))
+        || word.contains(index - 1, 5, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+        || word.contains(0, 3, &["SCH"])
+    {
+        // "Arnow" matches "Arnoff"
+        codes.append_alternate("F");
+        index + 1
+    } else if word.contains(index, 4, &["WICZ", "WITZ"]) {
+        // Polish, e.g. "filipowicz"
+        codes.append_both("TS", "FX");
+        index + 4
+    } else {
+        index + 1
+    }
+}
+
+fn handle_x(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if index == 0 {
+        codes.append("S");
+        return index + 1;
+    }
+    let is_french_ending = word.is_last(index)
+        && (word.contains(index - 3, 3, &["IAU", "EAU"])
+            || word.contains(index - 2, 2, &["AU", "OU"]));
+    if !is_french_ending {
+        // not French, e.g. "breaux"
+        codes.append("KS");
+    }
+    if word.contains(index + 1, 1, &["C", "X"]) {
+        index + 2
+    } else {
+        index + 1
+    }
+}
+
+fn handle_z(word: &Word, codes: &mut Codes, index: isize) -> isize {
+    if word.char_at(index + 1) == 'H' {
+        // Chinese pinyin, e.g. "zhao"
+        codes.append("J");
+        return index + 2;
+    }
+    if word.contains(index + 1, 2, &["ZO", "ZI", "ZA"])
+        || (word.is_slavo_germanic && index > 0 && word.char_at(index - 1) != 'T')
+    {
+        codes.append_both("S", "TS");
+    } else {
+        codes.append("S");
+    }
+    skip_double(word, index, 'Z')
+}
+
+fn condition_c0(word: &Word, index: isize) -> bool {
+    if word.contains(index, 4, &["CHIA"]) {
+        true
+    } else if index <= 1 || word.is_vowel_at(index - 2) || !word.contains(index - 1, 3, &["ACH"]) {
+        false
+    } else {
+        let c = word.char_at(index + 2);
+        (c != 'I' && c != 'E') || word.contains(index - 2, 6, &["BACHER", "MACHER"])
+    }
+}
+
+fn condition_ch0(word: &Word, index: isize) -> bool {
+    index == 0
+        && (word.contains(index + 1, 5, &["HARAC", "HARIS"])
+            || word.contains(index + 1, 3, &["HOR", "HYM", "HIA", "HEM"]))
+        && !word.contains(0, 5, &["CHORE"])
+}
+
+fn condition_ch1(word: &Word, index: isize) -> bool {
+    word.contains(0, 4, &["VAN ", "VON "])
+        || word.contains(0, 3, &["SCH"])
+        || word.contains(index - 2, 6, &["ORCHES", "ARCHIT", "ORCHID"])
+        || word.contains(index + 2, 1, &["T", "S"])
+        || ((word.contains(index - 1, 1, &["A", "O", "U", "E"]) || index == 0)
+            && (word.contains(index + 2, 1, &L_R_N_M_B_H_F_V_W_SPACE)
+                || index + 1 == word.len() as isize - 1))
+}
+
+fn condition_l0(word: &Word, index: isize) -> bool {
+    let len = word.len() as isize;
+    if index == len - 3 && word.contains(index - 1, 4, &["ILLO", "ILLA", "ALLE"]) {
+        return true;
+    }
+    (word.contains(len - 2, 2, &["AS", "OS"]) || word.contains(len - 1, 1, &["A", "O"]))
+        && word.contains(index - 1, 4, &["ALLE"])
+}
+
+fn condition_m0(word: &Word, index: isize) -> bool {
+    if word.char_at(index + 1) == 'M' {
+        return true;
+    }
+    word.contains(index - 1, 3, &["UMB"])
+        && (index + 1 == word.len() as isize - 1 || word.contains(index + 2, 2, &["ER"]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::double_metaphone;
+
+    fn assert_codes(word: &str, primary: &str, alternate: &str) {
+        assert_eq!(
+            double_metaphone(word),
+            (primary.to_string(), alternate.to_string()),
+            "codes of {:?}",
+            word
+        );
+    }
+
+    #[test]
+    fn test_double_metaphone() {
+        assert_codes("Smith", "SM0", "XMT");
+        assert_codes("Smyth", "SM0", "XMT");
+        assert_codes("Schmidt", "XMT", "SMT");
+        assert_codes("John", "JN", "AN");
+        assert_codes("Jon", "JN", "AN");
+        assert_codes("testing", "TSTN", "TSTN");
+        assert_codes("jumped", "JMPT", "AMPT");
+        assert_codes("Raj", "RJ", "R ");
+        assert_codes("Catherine", "K0RN", "KTRN");
+        assert_codes("Kathryn", "K0RN", "KTRN");
+        assert_codes("Michael", "MKL", "MXL");
+        assert_codes("Jose", "HS", "HS");
+        assert_codes("Knight", "NT", "NT");
+        assert_codes("laugh", "LF", "LF");
+        assert_codes("Wasserman", "ASRM", "FSRM");
+        assert_codes("Filipowicz", "FLPT", "FLPF");
+        assert_codes("Xavier", "SF", "SFR");
+        assert_codes("", "", "");
+        assert_codes("42", "", "");
+    }
+}
@@ -0,0 +1,196 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! # fn main() {
+//! let tokenizer = SimpleTokenizer
+//!   .filter(LowerCaser)
+//!   .filter(PhoneticFilter::new(PhoneticAlgorithm::DoubleMetaphone, true));
+//!
+//! let mut stream = tokenizer.token_stream("Jon Smyth");
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("jon".to_string(), 0)));
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("JN".to_string(), 0)));
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("AN".to_string(), 0)));
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("smyth".to_string(), 1)));
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("SM0".to_string(), 1)));
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("XMT".to_string(), 1)));
+//! assert!(stream.next().is_none());
+//! # }
+//! ```
+mod double_metaphone;
+mod soundex;
+
+use self::double_metaphone::double_metaphone;
+use self::soundex::soundex;
+use crate::tokenizer::{Token, TokenFilter, TokenStream};
+use std::collections::VecDeque;
+
+/// Phonetic encodings of the `PhoneticFilter`.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Copy, Clone)]
+pub enum PhoneticAlgorithm {
+    /// American Soundex, encoding a word as its first letter followed by three digits.
+    /// It is designed for English surnames.
+    Soundex,
+    /// Double Metaphone, encoding a word as a primary code of up to four letters,
+    /// and an alternate code accounting for the words of foreign origin.
+    DoubleMetaphone,
+}
+
+/// `TokenFilter` adding the phonetic codes of the tokens to the token stream,
+/// so that the words that sound alike match each other, e.g. `Jon` and `John`.
+///
+/// The codes are emitted at the same position and with the same offsets as their token.
+/// With the `DoubleMetaphone`, the alternate code is emitted as well if it differs
+/// from the primary code. The codes are uppercase, so that they do not collide
+/// with the lowercased tokens.
+///
+/// If `keep_original` is false, the tokens are replaced by their codes, except for
+/// the tokens which cannot be encoded, like numbers.
+#[derive(Clone)]
+pub struct PhoneticFilter {
+    algorithm: PhoneticAlgorithm,
+    keep_original: bool,
+}
+
+impl PhoneticFilter {
+    /// Creates a `PhoneticFilter` encoding the tokens with `algorithm`.
+    ///
+    /// If `keep_original` is true, the codes are added to the tokens.
+    /// Otherwise, they replace them.
+    pub fn new(algorithm: PhoneticAlgorithm, keep_original: bool) -> PhoneticFilter {
+        PhoneticFilter {
+            algorithm,
+            keep_original,
+        }
+    }
+
+    /// Appends the codes of `text` to `codes`.
+    fn encode(&self, text: &str, codes: &mut Vec<String>) {
+        match self.algorithm {
+            PhoneticAlgorithm::Soundex => codes.extend(soundex(text)),
+            PhoneticAlgorithm::DoubleMetaphone => {
+                let (primary, alternate) = double_metaphone(text);
+                // The alternate code of a final `J` is a space.
+                let alternate = alternate.trim_end().to_string();
+                if !primary.is_empty() {
+                    codes.push(primary);
+                }
+                if !alternate.is_empty() && !codes.contains(&alternate) {
+                    codes.push(alternate);
+                }
+            }
+        }
+    }
+}
+
+impl<TailTokenStream> TokenFilter<TailTokenStream> for PhoneticFilter
+where
+    TailTokenStream: TokenStream,
+{
+    type ResultTokenStream = PhoneticFilterStream<TailTokenStream>;
+
+    fn transform(&self, token_stream: TailTokenStream) -> Self::ResultTokenStream {
+        PhoneticFilterStream {
+            filter: self.clone(),
+            tail: token_stream,
+            codes: Vec::new(),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct PhoneticFilterStream<TailTokenStream> {
+    filter: PhoneticFilter,
+    tail: TailTokenStream,
+    codes: Vec<String>,
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<TailTokenStream> TokenStream for PhoneticFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn advance(&mut self) -> bool {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                self.token = token;
+                return true;
+            }
+            if !self.tail.advance() {
+                return false;
+            }
+            let token = self.tail.token();
+            self.codes.clear();
+            self.filter.encode(&token.text, &mut self.codes);
+            if self.filter.keep_original || self.codes.is_empty() {
+                self.pending.push_back(token.clone());
+            }
+            for code in &self.codes {
+                if *code != token.text {
+                    self.pending.push_back(Token {
+                        text: code.clone(),
+                        ..token.clone()
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PhoneticAlgorithm, PhoneticFilter};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, Token, TokenStream, Tokenizer};
+
+    fn phonetic_helper(text: &str, filter: PhoneticFilter) -> Vec<Token> {
+        let mut tokens = vec![];
+        SimpleTokenizer
+            .filter(LowerCaser)
+            .filter(filter)
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_phonetic_filter_replace() {
+        let filter = PhoneticFilter::new(PhoneticAlgorithm::Soundex, false);
+        let tokens = phonetic_helper("Robert Rupert 42", filter);
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "R163", 0, 6);
+        assert_token(&tokens[1], 1, "R163", 7, 13);
+        assert_token(&tokens[2], 2, "42", 14, 16);
+
+        let filter = PhoneticFilter::new(PhoneticAlgorithm::DoubleMetaphone, false);
+        let tokens = phonetic_helper("Thomas", filter);
+        assert_eq!(tokens.len(), 1);
+        assert_token(&tokens[0], 0, "TMS", 0, 6);
+    }
+
+    #[test]
+    fn test_phonetic_filter_names_match() {
+        let codes = |name: &str| -> Vec<String> {
+            phonetic_helper(
+                name,
+                PhoneticFilter::new(PhoneticAlgorithm::DoubleMetaphone, false),
+            )
+            .into_iter()
+            .map(|token| token.text)
+            .collect()
+        };
+        assert_eq!(codes("Jon"), codes("John"));
+        assert_eq!(codes("Smyth"), codes("Smith"));
+        assert_eq!(codes("Catherine"), codes("Kathryn"));
+    }
+}
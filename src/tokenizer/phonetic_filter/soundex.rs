@@ -0,0 +1,65 @@
+//! American Soundex, as described by the U.S. National Archives.
+
+const CODE_LEN: usize = 4;
+
+/// Returns the digit of a letter, `'0'` for the vowels.
+fn soundex_digit(c: char) -> char {
+    match c {
+        'B' | 'F' | 'P' | 'V' => '1',
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => '2',
+        'D' | 'T' => '3',
+        'L' => '4',
+        'M' | 'N' => '5',
+        'R' => '6',
+        _ => '0',
+    }
+}
+
+/// Returns the Soundex code of `text`, the ASCII letters of the text being encoded
+/// and the other characters ignored, or `None` if `text` does not contain any ASCII letter.
+pub(crate) fn soundex(text: &str) -> Option<String> {
+    let mut letters = text
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase());
+    let first_letter = letters.next()?;
+    let mut code = String::with_capacity(CODE_LEN);
+    code.push(first_letter);
+    let mut previous_digit = soundex_digit(first_letter);
+    for letter in letters {
+        if code.len() == CODE_LEN {
+            break;
+        }
+        // H and W do not separate the letters having the same digit, unlike the vowels.
+        if letter == 'H' || letter == 'W' {
+            continue;
+        }
+        let digit = soundex_digit(letter);
+        if digit != '0' && digit != previous_digit {
+            code.push(digit);
+        }
+        previous_digit = digit;
+    }
+    while code.len() < CODE_LEN {
+        code.push('0');
+    }
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::soundex;
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!(soundex("Robert").unwrap(), "R163");
+        assert_eq!(soundex("Rupert").unwrap(), "R163");
+        assert_eq!(soundex("Ashcraft").unwrap(), "A261");
+        assert_eq!(soundex("Tymczak").unwrap(), "T522");
+        assert_eq!(soundex("Pfister").unwrap(), "P236");
+        assert_eq!(soundex("Honeyman").unwrap(), "H555");
+        assert_eq!(soundex("Lee").unwrap(), "L000");
+        assert_eq!(soundex("O'Hara").unwrap(), "O600");
+        assert_eq!(soundex("1984"), None);
+    }
+}
@@ -19,6 +19,7 @@ impl<'a> Tokenizer<'a> for RawTokenizer {
             position: 0,
             text: text.to_string(),
             position_length: 1,
+            payload: 0,
         };
         RawTokenStream {
             token,
@@ -24,7 +24,55 @@ pub enum Language {
     Turkish,
 }
 
+/// All of the available stemmer languages.
+pub(crate) const LANGUAGES: [Language; 17] = [
+    Language::Arabic,
+    Language::Danish,
+    Language::Dutch,
+    Language::English,
+    Language::Finnish,
+    Language::French,
+    Language::German,
+    Language::Greek,
+    Language::Hungarian,
+    Language::Italian,
+    Language::Portuguese,
+    Language::Romanian,
+    Language::Russian,
+    Language::Spanish,
+    Language::Swedish,
+    Language::Tamil,
+    Language::Turkish,
+];
+
 impl Language {
+    /// Returns the ISO 639-1 code of the language, e.g. `fr` for French.
+    ///
+    /// The `TokenizerManager` registers by default a stemming
+    /// tokenizer named `<code>_stem` for each language.
+    pub fn iso_code(self) -> &'static str {
+        use self::Language::*;
+        match self {
+            Arabic => "ar",
+            Danish => "da",
+            Dutch => "nl",
+            English => "en",
+            Finnish => "fi",
+            French => "fr",
+            German => "de",
+            Greek => "el",
+            Hungarian => "hu",
+            Italian => "it",
+            Portuguese => "pt",
+            Romanian => "ro",
+            Russian => "ru",
+            Spanish => "es",
+            Swedish => "sv",
+            Tamil => "ta",
+            Turkish => "tr",
+        }
+    }
+
     fn algorithm(self) -> Algorithm {
         use self::Language::*;
         match self {
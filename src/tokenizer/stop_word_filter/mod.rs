@@ -12,7 +12,26 @@
 //! assert!(stream.next().is_none());
 //! # }
 //! ```
-use super::{Token, TokenFilter, TokenStream};
+//!
+//! The stop words of several languages are built in:
+//!
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! # fn main() {
+//! let tokenizer = SimpleTokenizer
+//!   .filter(LowerCaser)
+//!   .filter(StopWordFilter::new(Language::French).unwrap());
+//!
+//! let mut stream = tokenizer.token_stream("Le renard est rusé");
+//! assert_eq!(stream.next().unwrap().text, "renard");
+//! assert_eq!(stream.next().unwrap().text, "rusé");
+//! assert!(stream.next().is_none());
+//! # }
+//! ```
+mod stopwords;
+
+use crate::tokenizer::{Language, Token, TokenFilter, TokenStream};
 use fnv::FnvHasher;
 use std::collections::HashSet;
 use std::hash::BuildHasherDefault;
@@ -39,14 +58,34 @@ impl StopWordFilter {
         StopWordFilter { words: set }
     }
 
-    fn english() -> StopWordFilter {
-        let words: [&'static str; 33] = [
-            "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into",
-            "is", "it", "no", "not", "of", "on", "or", "such", "that", "the", "their", "then",
-            "there", "these", "they", "this", "to", "was", "will", "with",
-        ];
+    /// Creates a `StopWordFilter` removing the built-in stop words of a language.
+    ///
+    /// The stop words are lowercased, so the filter should be applied after
+    /// the `LowerCaser`. Returns `None` if there are no built-in stop words
+    /// for the language.
+    pub fn new(language: Language) -> Option<StopWordFilter> {
+        let words = match language {
+            Language::Danish => stopwords::DANISH,
+            Language::Dutch => stopwords::DUTCH,
+            Language::English => stopwords::ENGLISH,
+            Language::Finnish => stopwords::FINNISH,
+            Language::French => stopwords::FRENCH,
+            Language::German => stopwords::GERMAN,
+            Language::Hungarian => stopwords::HUNGARIAN,
+            Language::Italian => stopwords::ITALIAN,
+            Language::Portuguese => stopwords::PORTUGUESE,
+            Language::Russian => stopwords::RUSSIAN,
+            Language::Spanish => stopwords::SPANISH,
+            Language::Swedish => stopwords::SWEDISH,
+            _ => return None,
+        };
+        Some(StopWordFilter::remove(
+            words.iter().map(|&word| word.to_string()).collect(),
+        ))
+    }
 
-        StopWordFilter::remove(words.iter().map(|s| s.to_string()).collect())
+    fn english() -> StopWordFilter {
+        StopWordFilter::new(Language::English).expect("English stop words are built in")
     }
 }
 
@@ -112,3 +151,23 @@ impl Default for StopWordFilter {
         StopWordFilter::english()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::StopWordFilter;
+    use crate::tokenizer::stemmer::LANGUAGES;
+    use crate::tokenizer::Language;
+
+    #[test]
+    fn test_stop_words_are_lowercased() {
+        for &language in &LANGUAGES {
+            if let Some(filter) = StopWordFilter::new(language) {
+                assert!(!filter.words.is_empty());
+                for word in &filter.words {
+                    assert_eq!(word, &word.to_lowercase(), "{:?}", language);
+                }
+            }
+        }
+        assert!(StopWordFilter::new(Language::Greek).is_none());
+    }
+}
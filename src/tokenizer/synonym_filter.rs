@@ -0,0 +1,311 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! # fn main() {
+//! let tokenizer = SimpleTokenizer
+//!   .filter(LowerCaser)
+//!   .filter(SynonymFilter::new(true).add_equivalents(&["nyc", "new york"]));
+//!
+//! let mut stream = tokenizer.token_stream("NYC hotels");
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("nyc".to_string(), 0)));
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("new".to_string(), 0)));
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("york".to_string(), 0)));
+//! assert_eq!(stream.next().map(|token| (token.text.clone(), token.position)), Some(("hotels".to_string(), 1)));
+//! assert!(stream.next().is_none());
+//! # }
+//! ```
+use super::{Token, TokenFilter, TokenStream};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// `TokenFilter` replacing phrases by their synonyms, or adding their synonyms
+/// to the token stream.
+///
+/// A phrase is a sequence of one or several words separated by whitespaces,
+/// which is matched against consecutive tokens. The longest phrase is matched first.
+///
+/// The synonyms are emitted at the same position as the tokens they replace:
+/// the word `i` of a synonym is stacked on the token `i` of the matched phrase,
+/// and the words in excess are stacked on the last token of the phrase.
+/// As a result, a phrase query matches a multi-word synonym only if the phrase
+/// it was stacked on has at least as many words.
+///
+/// The words are compared with the text of the tokens as they reach the filter,
+/// so the filter is usually applied after the `LowerCaser`, with lowercased phrases.
+///
+/// Because the tokens of a phrase query are expected to be at distinct positions,
+/// the synonyms are typically expanded at index time only, the queries being
+/// tokenized without the `SynonymFilter`.
+#[derive(Clone)]
+pub struct SynonymFilter {
+    // phrase (words joined by a space) -> synonyms (as lists of words)
+    synonyms: Arc<HashMap<String, Vec<Vec<String>>>>,
+    max_phrase_len: usize,
+    keep_original: bool,
+}
+
+impl SynonymFilter {
+    /// Creates a `SynonymFilter` without any synonyms.
+    ///
+    /// If `keep_original` is true, the synonyms are added to the matched tokens.
+    /// Otherwise, they replace them.
+    pub fn new(keep_original: bool) -> SynonymFilter {
+        SynonymFilter {
+            synonyms: Arc::default(),
+            max_phrase_len: 0,
+            keep_original,
+        }
+    }
+
+    /// Declares `phrases` as equivalent: each of them is a synonym of all of the others.
+    pub fn add_equivalents(self, phrases: &[&str]) -> SynonymFilter {
+        phrases.iter().fold(self, |filter, &phrase| {
+            let synonyms: Vec<&str> = phrases
+                .iter()
+                .cloned()
+                .filter(|&synonym| synonym != phrase)
+                .collect();
+            filter.add_synonyms(phrase, &synonyms)
+        })
+    }
+
+    /// Declares `synonyms` as synonyms of `phrase`, but not the other way around.
+    pub fn add_synonyms(mut self, phrase: &str, synonyms: &[&str]) -> SynonymFilter {
+        let words: Vec<&str> = phrase.split_whitespace().collect();
+        if words.is_empty() {
+            return self;
+        }
+        self.max_phrase_len = self.max_phrase_len.max(words.len());
+        let synonym_words = synonyms.iter().map(|synonym| {
+            synonym
+                .split_whitespace()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+        });
+        Arc::make_mut(&mut self.synonyms)
+            .entry(words.join(" "))
+            .or_default()
+            .extend(synonym_words.filter(|words| !words.is_empty()));
+        self
+    }
+}
+
+impl<TailTokenStream> TokenFilter<TailTokenStream> for SynonymFilter
+where
+    TailTokenStream: TokenStream,
+{
+    type ResultTokenStream = SynonymFilterStream<TailTokenStream>;
+
+    fn transform(&self, token_stream: TailTokenStream) -> Self::ResultTokenStream {
+        SynonymFilterStream {
+            filter: self.clone(),
+            tail: token_stream,
+            lookahead: VecDeque::new(),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct SynonymFilterStream<TailTokenStream> {
+    filter: SynonymFilter,
+    tail: TailTokenStream,
+    // tokens of the tail that have not been matched yet.
+    lookahead: VecDeque<Token>,
+    // tokens ready to be emitted, sorted by position.
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<TailTokenStream> SynonymFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    /// Returns the length of the longest phrase of the lookahead
+    /// having synonyms, along with its synonyms.
+    fn longest_match(&self) -> Option<(usize, &[Vec<String>])> {
+        let mut phrase = String::new();
+        let mut longest_match = None;
+        for (phrase_len, token) in self.lookahead.iter().enumerate() {
+            if phrase_len > 0 {
+                phrase.push(' ');
+            }
+            phrase.push_str(&token.text);
+            if let Some(synonyms) = self.filter.synonyms.get(&phrase) {
+                longest_match = Some((phrase_len + 1, &synonyms[..]));
+            }
+        }
+        longest_match
+    }
+
+    fn expand(&mut self) {
+        let (phrase_len, synonyms) = match self.longest_match() {
+            Some((phrase_len, synonyms)) => (phrase_len, synonyms.to_vec()),
+            None => {
+                let token = self.lookahead.pop_front().expect("lookahead is not empty");
+                self.pending.push_back(token);
+                return;
+            }
+        };
+        let phrase_tokens: Vec<Token> = self.lookahead.drain(..phrase_len).collect();
+        let offset_from = phrase_tokens[0].offset_from;
+        let offset_to = phrase_tokens[phrase_len - 1].offset_to;
+        let mut tokens: Vec<Token> = Vec::new();
+        if self.filter.keep_original {
+            tokens.extend(phrase_tokens.iter().cloned());
+        }
+        for synonym in &synonyms {
+            for (word_ord, word) in synonym.iter().enumerate() {
+                let phrase_token = &phrase_tokens[word_ord.min(phrase_len - 1)];
+                let is_last_word = word_ord + 1 == synonym.len();
+                let position_length = if is_last_word && word_ord < phrase_len {
+                    phrase_len - word_ord
+                } else {
+                    1
+                };
+                let is_duplicate = tokens
+                    .iter()
+                    .any(|token| token.position == phrase_token.position && &token.text == word);
+                if !is_duplicate {
+                    tokens.push(Token {
+                        offset_from,
+                        offset_to,
+                        position: phrase_token.position,
+                        text: word.clone(),
+                        position_length,
+                        payload: phrase_token.payload,
+                    });
+                }
+            }
+        }
+        // The sort is stable: the original tokens come first.
+        tokens.sort_by_key(|token| token.position);
+        self.pending.extend(tokens);
+    }
+}
+
+impl<TailTokenStream> TokenStream for SynonymFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn advance(&mut self) -> bool {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                self.token = token;
+                return true;
+            }
+            while self.lookahead.len() < self.filter.max_phrase_len.max(1) && self.tail.advance() {
+                self.lookahead.push_back(self.tail.token().clone());
+            }
+            if self.lookahead.is_empty() {
+                return false;
+            }
+            self.expand();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SynonymFilter;
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, Token, TokenStream, Tokenizer};
+    use crate::Index;
+
+    fn synonym_helper(text: &str, filter: SynonymFilter) -> Vec<Token> {
+        let mut tokens = vec![];
+        SimpleTokenizer
+            .filter(LowerCaser)
+            .filter(filter)
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_synonym_filter_multi_word() {
+        let filter = SynonymFilter::new(true).add_equivalents(&["nyc", "new york city"]);
+        let tokens = synonym_helper("New York City is big", filter.clone());
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "new", 0, 3);
+        assert_token(&tokens[1], 0, "nyc", 0, 13);
+        assert_eq!(tokens[1].position_length, 3);
+        assert_token(&tokens[2], 1, "york", 4, 8);
+        assert_token(&tokens[3], 2, "city", 9, 13);
+        assert_token(&tokens[4], 3, "is", 14, 16);
+        assert_token(&tokens[5], 4, "big", 17, 20);
+
+        let tokens = synonym_helper("in NYC", filter);
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, "in", 0, 2);
+        assert_token(&tokens[1], 1, "nyc", 3, 6);
+        assert_token(&tokens[2], 1, "new", 3, 6);
+        assert_token(&tokens[3], 1, "york", 3, 6);
+        assert_token(&tokens[4], 1, "city", 3, 6);
+    }
+
+    #[test]
+    fn test_synonym_filter_normalize() {
+        let filter = SynonymFilter::new(false)
+            .add_synonyms("colour", &["color"])
+            .add_synonyms("grey", &["gray"]);
+        let tokens = synonym_helper("Grey colour", filter);
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "gray", 0, 4);
+        assert_token(&tokens[1], 1, "color", 5, 11);
+    }
+
+    #[test]
+    fn test_synonym_filter_index_time_expansion() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("synonyms")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "synonyms",
+            SimpleTokenizer
+                .filter(LowerCaser)
+                .filter(SynonymFilter::new(true).add_equivalents(&["nyc", "new york"])),
+        );
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "Hotels in NYC"));
+        index_writer.add_document(doc!(text_field => "New York hotels"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        // The stacked tokens do not count in the fieldnorms.
+        let fieldnorm_reader = searcher.segment_reader(0).get_fieldnorms_reader(text_field);
+        assert_eq!(fieldnorm_reader.fieldnorm(0), 3);
+        assert_eq!(fieldnorm_reader.fieldnorm(1), 3);
+
+        index
+            .tokenizers()
+            .register("synonyms", SimpleTokenizer.filter(LowerCaser));
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("nyc"), 2);
+        assert_eq!(count("york"), 2);
+        assert_eq!(count("\"in nyc\""), 1);
+        assert_eq!(count("\"new york hotels\""), 1);
+    }
+}
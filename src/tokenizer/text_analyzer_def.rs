@@ -0,0 +1,434 @@
+use crate::tokenizer::{
+    AlphaNumOnlyFilter, AsciiFoldingFilter, BoxedTokenizer, CharFilter, CjkBigramTokenizer,
+    EdgeNgramFilter, HtmlStripCharFilter, Language, LengthFilter, LowerCaser, NgramTokenizer,
+    NormalizationForm, PatternReplaceCharFilter, PhoneticAlgorithm, PhoneticFilter, RawTokenizer,
+    RemoveLongFilter, SimpleTokenizer, Stemmer, StopWordFilter, SynonymFilter, TokenFilter,
+    TokenStream, Tokenizer, UnicodeNormalizer, WordDelimiterFilter,
+};
+use crate::Result;
+use crate::TantivyError;
+
+/// Definition of a char filter of a `TextAnalyzerDef`, applied to the text
+/// before its tokenization.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CharFilterDef {
+    /// The `HtmlStripCharFilter`, removing the markup of HTML documents.
+    HtmlStrip,
+    /// The `PatternReplaceCharFilter`, replacing the matches of a regular expression.
+    PatternReplace {
+        /// Regular expression to replace.
+        pattern: String,
+        /// Replacement of the matches, which may refer to the capture groups.
+        replacement: String,
+    },
+}
+
+/// Definition of the tokenizer at the head of a `TextAnalyzerDef`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TokenizerDef {
+    /// The `RawTokenizer`, emitting the whole text as a single token.
+    Raw,
+    /// The `SimpleTokenizer`, splitting the text on whitespaces and punctuation.
+    Simple,
+    /// The `CjkBigramTokenizer`, splitting the text like the `SimpleTokenizer`
+    /// and the runs of CJK characters into bigrams.
+    CjkBigram,
+    /// The `NgramTokenizer`, emitting the n-grams of the text.
+    Ngram {
+        /// Minimum size of the n-grams.
+        min_gram: usize,
+        /// Maximum size of the n-grams.
+        max_gram: usize,
+        /// If true, only the n-grams starting at the beginning of the text are emitted.
+        #[serde(default)]
+        prefix_only: bool,
+    },
+}
+
+/// Definition of a token filter of a `TextAnalyzerDef`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TokenFilterDef {
+    /// The `LowerCaser` filter.
+    LowerCaser,
+    /// The `RemoveLongFilter`, removing the tokens longer than `length_limit` bytes.
+    RemoveLong {
+        /// Maximum length of the tokens, in bytes.
+        length_limit: usize,
+    },
+    /// The `LengthFilter`, removing the tokens whose length is not within
+    /// `min_len` and `max_len` characters.
+    Length {
+        /// Minimum length of the tokens, in characters.
+        min_len: usize,
+        /// Maximum length of the tokens, in characters.
+        max_len: usize,
+    },
+    /// The `Stemmer` filter, for the given language.
+    Stemmer {
+        /// Language of the stemmer.
+        language: Language,
+    },
+    /// The `StopWordFilter`, removing the given words.
+    StopWords {
+        /// Words removed by the filter.
+        words: Vec<String>,
+    },
+    /// The `StopWordFilter`, removing the built-in stop words of the given language.
+    LanguageStopWords {
+        /// Language of the stop words.
+        language: Language,
+    },
+    /// The `SynonymFilter`, adding the synonyms of the matched phrases to the tokens.
+    Synonyms {
+        /// Groups of equivalent phrases.
+        equivalents: Vec<Vec<String>>,
+    },
+    /// The `AsciiFoldingFilter`.
+    AsciiFolding,
+    /// The `AlphaNumOnlyFilter`.
+    AlphaNumOnly,
+    /// The `UnicodeNormalizer`, for the given normalization form.
+    UnicodeNormalization {
+        /// Normalization form of the tokens.
+        form: NormalizationForm,
+    },
+    /// The `EdgeNgramFilter`, replacing the tokens by their prefixes.
+    EdgeNgram {
+        /// Minimum size of the prefixes, in characters.
+        min_gram: usize,
+        /// Maximum size of the prefixes, in characters.
+        max_gram: usize,
+        /// If true, the tokens whose size is not within `min_gram` and `max_gram`
+        /// are emitted as well.
+        #[serde(default)]
+        preserve_original: bool,
+    },
+    /// The `PhoneticFilter`, adding the phonetic codes of the tokens.
+    Phonetic {
+        /// Phonetic encoding of the tokens.
+        algorithm: PhoneticAlgorithm,
+        /// If true, the codes are added to the tokens. Otherwise, they replace them.
+        #[serde(default)]
+        keep_original: bool,
+    },
+    /// The `WordDelimiterFilter`, splitting the tokens into their subwords.
+    WordDelimiter {
+        /// If true, the tokens that are split are emitted as well.
+        #[serde(default)]
+        preserve_original: bool,
+    },
+}
+
+/// Serializable definition of a tokenizer: a chain of char filters, followed by
+/// a tokenizer and a chain of token filters, along with their parameters.
+///
+/// The definitions given in the `tokenizers` of the
+/// [`IndexSettings`](../struct.IndexSettings.html) are persisted
+/// in the `meta.json` file, and registered in the `TokenizerManager`
+/// whenever the index is opened. Indexing and searching then always
+/// use the same analysis, without having to register the tokenizers
+/// in the code opening the index.
+///
+/// ```rust
+/// use tantivy::tokenizer::{Language, TextAnalyzerDef, TokenFilterDef, TokenizerDef};
+///
+/// let fr_stem = TextAnalyzerDef::new(TokenizerDef::Simple)
+///     .filter(TokenFilterDef::RemoveLong { length_limit: 40 })
+///     .filter(TokenFilterDef::LowerCaser)
+///     .filter(TokenFilterDef::Stemmer { language: Language::French });
+/// let tokenizer = fr_stem.build().unwrap();
+/// let mut token_stream = tokenizer.token_stream("Les Chevaux");
+/// assert_eq!(token_stream.next().unwrap().text, "le");
+/// assert_eq!(token_stream.next().unwrap().text, "cheval");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextAnalyzerDef {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    char_filters: Vec<CharFilterDef>,
+    tokenizer: TokenizerDef,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    filters: Vec<TokenFilterDef>,
+}
+
+impl TextAnalyzerDef {
+    /// Creates a definition, given the definition of its tokenizer.
+    pub fn new(tokenizer: TokenizerDef) -> TextAnalyzerDef {
+        TextAnalyzerDef {
+            char_filters: Vec::new(),
+            tokenizer,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Appends a token filter to the definition.
+    pub fn filter(mut self, filter: TokenFilterDef) -> TextAnalyzerDef {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Appends a char filter to the definition.
+    ///
+    /// The char filters are applied to the text in order, before the tokenizer.
+    pub fn char_filter(mut self, char_filter: CharFilterDef) -> TextAnalyzerDef {
+        self.char_filters.push(char_filter);
+        self
+    }
+
+    /// Returns the definitions of the char filters, in order.
+    pub fn char_filters(&self) -> &[CharFilterDef] {
+        &self.char_filters
+    }
+
+    /// Returns the definition of the tokenizer.
+    pub fn tokenizer(&self) -> &TokenizerDef {
+        &self.tokenizer
+    }
+
+    /// Returns the definitions of the token filters, in order.
+    pub fn filters(&self) -> &[TokenFilterDef] {
+        &self.filters
+    }
+
+    /// Builds the tokenizer described by the definition.
+    ///
+    /// Returns an `InvalidArgument` error if the parameters are invalid,
+    /// e.g. an n-gram tokenizer or filter with a `min_gram` greater than its `max_gram`,
+    /// stop words of a language without built-in stop words, or an invalid regular
+    /// expression.
+    pub fn build(&self) -> Result<BoxedTokenizer> {
+        let mut tokenizer: BoxedTokenizer = match self.tokenizer {
+            TokenizerDef::Raw => RawTokenizer.into(),
+            TokenizerDef::Simple => SimpleTokenizer.into(),
+            TokenizerDef::CjkBigram => CjkBigramTokenizer.into(),
+            TokenizerDef::Ngram {
+                min_gram,
+                max_gram,
+                prefix_only,
+            } => {
+                check_gram_sizes(min_gram, max_gram)?;
+                NgramTokenizer::new(min_gram, max_gram, prefix_only).into()
+            }
+        };
+        for filter in &self.filters {
+            tokenizer = match *filter {
+                TokenFilterDef::LowerCaser => append_filter(tokenizer, LowerCaser),
+                TokenFilterDef::RemoveLong { length_limit } => {
+                    append_filter(tokenizer, RemoveLongFilter::limit(length_limit))
+                }
+                TokenFilterDef::Length { min_len, max_len } => {
+                    if min_len > max_len {
+                        return Err(TantivyError::InvalidArgument(format!(
+                            "Invalid token lengths: min_len={}, max_len={}",
+                            min_len, max_len
+                        )));
+                    }
+                    append_filter(tokenizer, LengthFilter::new(min_len, max_len))
+                }
+                TokenFilterDef::Stemmer { language } => {
+                    append_filter(tokenizer, Stemmer::new(language))
+                }
+                TokenFilterDef::StopWords { ref words } => {
+                    append_filter(tokenizer, StopWordFilter::remove(words.clone()))
+                }
+                TokenFilterDef::LanguageStopWords { language } => {
+                    let stop_word_filter = StopWordFilter::new(language).ok_or_else(|| {
+                        TantivyError::InvalidArgument(format!(
+                            "No built-in stop words for {:?}",
+                            language
+                        ))
+                    })?;
+                    append_filter(tokenizer, stop_word_filter)
+                }
+                TokenFilterDef::Synonyms { ref equivalents } => {
+                    let synonym_filter = equivalents.iter().fold(
+                        SynonymFilter::new(true),
+                        |synonym_filter, phrases| {
+                            let phrases: Vec<&str> = phrases.iter().map(String::as_str).collect();
+                            synonym_filter.add_equivalents(&phrases)
+                        },
+                    );
+                    append_filter(tokenizer, synonym_filter)
+                }
+                TokenFilterDef::AsciiFolding => append_filter(tokenizer, AsciiFoldingFilter),
+                TokenFilterDef::AlphaNumOnly => append_filter(tokenizer, AlphaNumOnlyFilter),
+                TokenFilterDef::UnicodeNormalization { form } => {
+                    append_filter(tokenizer, UnicodeNormalizer::new(form))
+                }
+                TokenFilterDef::EdgeNgram {
+                    min_gram,
+                    max_gram,
+                    preserve_original,
+                } => {
+                    check_gram_sizes(min_gram, max_gram)?;
+                    append_filter(
+                        tokenizer,
+                        EdgeNgramFilter::new(min_gram, max_gram, preserve_original),
+                    )
+                }
+                TokenFilterDef::Phonetic {
+                    algorithm,
+                    keep_original,
+                } => append_filter(tokenizer, PhoneticFilter::new(algorithm, keep_original)),
+                TokenFilterDef::WordDelimiter { preserve_original } => {
+                    append_filter(tokenizer, WordDelimiterFilter::new(preserve_original))
+                }
+            };
+        }
+        // The last char filter appended to a tokenizer is the first one applied.
+        for char_filter in self.char_filters.iter().rev() {
+            tokenizer = match *char_filter {
+                CharFilterDef::HtmlStrip => append_char_filter(tokenizer, HtmlStripCharFilter),
+                CharFilterDef::PatternReplace {
+                    ref pattern,
+                    ref replacement,
+                } => append_char_filter(
+                    tokenizer,
+                    PatternReplaceCharFilter::new(pattern, replacement)?,
+                ),
+            };
+        }
+        Ok(tokenizer)
+    }
+}
+
+fn check_gram_sizes(min_gram: usize, max_gram: usize) -> Result<()> {
+    if min_gram == 0 || min_gram > max_gram {
+        return Err(TantivyError::InvalidArgument(format!(
+            "Invalid n-gram sizes: min_gram={}, max_gram={}",
+            min_gram, max_gram
+        )));
+    }
+    Ok(())
+}
+
+/// Makes it possible to append a filter to a `BoxedTokenizer`.
+#[derive(Clone)]
+struct BoxedTokenizerHead(BoxedTokenizer);
+
+impl<'a> Tokenizer<'a> for BoxedTokenizerHead {
+    type TokenStreamImpl = Box<dyn TokenStream + 'a>;
+
+    fn token_stream(&self, text: &'a str) -> Self::TokenStreamImpl {
+        self.0.token_stream(text)
+    }
+}
+
+fn append_filter<F>(tokenizer: BoxedTokenizer, filter: F) -> BoxedTokenizer
+where
+    F: 'static + Send + Sync + for<'a> TokenFilter<Box<dyn TokenStream + 'a>>,
+{
+    BoxedTokenizerHead(tokenizer).filter(filter).into()
+}
+
+fn append_char_filter<F: CharFilter>(tokenizer: BoxedTokenizer, char_filter: F) -> BoxedTokenizer {
+    BoxedTokenizerHead(tokenizer)
+        .char_filter(char_filter)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CharFilterDef, TextAnalyzerDef, TokenFilterDef, TokenizerDef};
+    use crate::tokenizer::{Language, Token, TokenStream};
+
+    #[test]
+    fn test_text_analyzer_def_serialization() {
+        let analyzer_def = TextAnalyzerDef::new(TokenizerDef::Simple)
+            .filter(TokenFilterDef::LowerCaser)
+            .filter(TokenFilterDef::StopWords {
+                words: vec!["the".to_string()],
+            })
+            .filter(TokenFilterDef::Stemmer {
+                language: Language::English,
+            });
+        let json = serde_json::to_string(&analyzer_def).unwrap();
+        assert_eq!(
+            json,
+            r#"{"tokenizer":{"type":"simple"},"filters":[{"type":"lower_caser"},{"type":"stop_words","words":["the"]},{"type":"stemmer","language":"English"}]}"#
+        );
+        let deserialized: TextAnalyzerDef = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, analyzer_def);
+
+        let tokenizer = analyzer_def.build().unwrap();
+        let mut tokens: Vec<String> = Vec::new();
+        tokenizer
+            .token_stream("The Running dogs")
+            .process(&mut |token: &Token| tokens.push(token.text.clone()));
+        assert_eq!(tokens, vec!["run", "dog"]);
+    }
+
+    #[test]
+    fn test_text_analyzer_def_invalid_ngram() {
+        let analyzer_def = TextAnalyzerDef::new(TokenizerDef::Ngram {
+            min_gram: 3,
+            max_gram: 2,
+            prefix_only: false,
+        });
+        assert!(analyzer_def.build().is_err());
+        let analyzer_def =
+            TextAnalyzerDef::new(TokenizerDef::Simple).filter(TokenFilterDef::EdgeNgram {
+                min_gram: 0,
+                max_gram: 2,
+                preserve_original: false,
+            });
+        assert!(analyzer_def.build().is_err());
+        let analyzer_def =
+            TextAnalyzerDef::new(TokenizerDef::Simple).filter(TokenFilterDef::LanguageStopWords {
+                language: Language::Tamil,
+            });
+        assert!(analyzer_def.build().is_err());
+        let analyzer_def: TextAnalyzerDef = serde_json::from_str(
+            r#"{"tokenizer": {"type": "ngram", "min_gram": 1, "max_gram": 2}}"#,
+        )
+        .unwrap();
+        let mut tokens: Vec<String> = Vec::new();
+        analyzer_def
+            .build()
+            .unwrap()
+            .token_stream("ab")
+            .process(&mut |token: &Token| tokens.push(token.text.clone()));
+        assert_eq!(tokens, vec!["a", "ab", "b"]);
+    }
+
+    #[test]
+    fn test_text_analyzer_def_char_filters() {
+        let analyzer_def: TextAnalyzerDef = serde_json::from_str(
+            r#"{
+                "char_filters": [
+                    {"type": "html_strip"},
+                    {"type": "pattern_replace", "pattern": "([0-9])-([0-9])", "replacement": "$1$2"}
+                ],
+                "tokenizer": {"type": "simple"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            analyzer_def,
+            TextAnalyzerDef::new(TokenizerDef::Simple)
+                .char_filter(CharFilterDef::HtmlStrip)
+                .char_filter(CharFilterDef::PatternReplace {
+                    pattern: "([0-9])-([0-9])".to_string(),
+                    replacement: "$1$2".to_string(),
+                })
+        );
+        let mut tokens: Vec<(String, usize, usize)> = Vec::new();
+        analyzer_def
+            .build()
+            .unwrap()
+            .token_stream("<b>555</b>-0123")
+            .process(&mut |token: &Token| {
+                tokens.push((token.text.clone(), token.offset_from, token.offset_to))
+            });
+        assert_eq!(tokens, vec![("5550123".to_string(), 3, 15)]);
+
+        let analyzer_def =
+            TextAnalyzerDef::new(TokenizerDef::Simple).char_filter(CharFilterDef::PatternReplace {
+                pattern: "[".to_string(),
+                replacement: "".to_string(),
+            });
+        assert!(analyzer_def.build().is_err());
+    }
+}
@@ -43,6 +43,7 @@ where
                 self.token.position = token.position + self.position_shift;
                 self.token.text.clear();
                 self.token.text.push_str(token.text.as_str());
+                self.token.payload = token.payload;
                 return true;
             } else {
                 self.stream_idx += 1;
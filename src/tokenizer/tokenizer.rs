@@ -1,4 +1,4 @@
-use crate::tokenizer::TokenStreamChain;
+use crate::tokenizer::{CharFilter, CharFilterTokenizer, TokenStreamChain};
 /// The tokenizer module contains all of the tools used to process
 /// text in `tantivy`.
 use std::borrow::{Borrow, BorrowMut};
@@ -19,6 +19,11 @@ pub struct Token {
     pub text: String,
     /// Is the length expressed in term of number of original tokens.
     pub position_length: usize,
+    /// Small value attached to the position of the token, `0` meaning no payload.
+    ///
+    /// It is only indexed if the field stores payloads
+    /// (See [`TextFieldIndexing::set_payloads`](../schema/struct.TextFieldIndexing.html#method.set_payloads)).
+    pub payload: u32,
 }
 
 impl Default for Token {
@@ -29,6 +34,7 @@ impl Default for Token {
             position: usize::max_value(),
             text: String::with_capacity(200),
             position_length: 1,
+            payload: 0,
         }
     }
 }
@@ -75,6 +81,40 @@ pub trait Tokenizer<'a>: Sized + Clone {
             tail: self,
         }
     }
+
+    /// Applies a char filter to the text, before it is processed by the
+    /// current tokenizer.
+    ///
+    /// The offsets of the resulting tokens are expressed in the original text.
+    /// If several char filters are added, the last one is applied first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::tokenizer::*;
+    ///
+    /// # fn main() {
+    /// let html_tokenizer = SimpleTokenizer
+    ///     .filter(LowerCaser)
+    ///     .char_filter(HtmlStripCharFilter);
+    ///
+    /// let mut stream = html_tokenizer.token_stream("<p>Fish &amp; <b>Chips</b></p>");
+    /// let token = stream.next().unwrap();
+    /// assert_eq!((token.text.as_str(), token.offset_from, token.offset_to), ("fish", 3, 7));
+    /// let token = stream.next().unwrap();
+    /// assert_eq!((token.text.as_str(), token.offset_from, token.offset_to), ("chips", 17, 22));
+    /// assert!(stream.next().is_none());
+    /// # }
+    /// ```
+    fn char_filter<NewCharFilter>(
+        self,
+        char_filter: NewCharFilter,
+    ) -> CharFilterTokenizer<NewCharFilter, Self>
+    where
+        NewCharFilter: CharFilter,
+    {
+        CharFilterTokenizer::new(char_filter, self)
+    }
 }
 
 /// A boxed tokenizer
@@ -300,6 +340,7 @@ mod test {
             offset_to: 3,
             text: "abc".to_string(),
             position_length: 1,
+            payload: 0,
         };
         let t2 = t1.clone();
 
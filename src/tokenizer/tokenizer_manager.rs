@@ -1,5 +1,6 @@
-use crate::tokenizer::stemmer::Language;
+use crate::tokenizer::stemmer::LANGUAGES;
 use crate::tokenizer::BoxedTokenizer;
+use crate::tokenizer::CjkBigramTokenizer;
 use crate::tokenizer::LowerCaser;
 use crate::tokenizer::RawTokenizer;
 use crate::tokenizer::RemoveLongFilter;
@@ -21,6 +22,11 @@ use std::sync::{Arc, RwLock};
 ///  * `en_stem` : Like `default`, but also applies stemming on the
 ///  resulting tokens. Stemming can improve the recall of your
 ///  search engine.
+///  * `cjk` : Like `default`, but the runs of Chinese, Japanese and Korean
+///    characters are split into overlapping bigrams.
+///  * `fr_stem`, `de_stem`, ... : Like `en_stem`, for each of the
+///    other stemmer languages, named after the
+///    [ISO 639-1 code](./enum.Language.html#method.iso_code) of the language.
 #[derive(Clone)]
 pub struct TokenizerManager {
     tokenizers: Arc<RwLock<HashMap<String, BoxedTokenizer>>>,
@@ -52,9 +58,10 @@ impl TokenizerManager {
 impl Default for TokenizerManager {
     /// Creates an `TokenizerManager` prepopulated with
     /// the default pre-configured tokenizers of `tantivy`.
-    /// - simple
-    /// - en_stem
-    /// - ja
+    /// - raw
+    /// - default
+    /// - cjk
+    /// - en_stem, fr_stem, de_stem, ...
     fn default() -> TokenizerManager {
         let manager = TokenizerManager {
             tokenizers: Arc::new(RwLock::new(HashMap::new())),
@@ -67,12 +74,20 @@ impl Default for TokenizerManager {
                 .filter(LowerCaser),
         );
         manager.register(
-            "en_stem",
-            SimpleTokenizer
+            "cjk",
+            CjkBigramTokenizer
                 .filter(RemoveLongFilter::limit(40))
-                .filter(LowerCaser)
-                .filter(Stemmer::new(Language::English)),
+                .filter(LowerCaser),
         );
+        for &language in &LANGUAGES {
+            manager.register(
+                &format!("{}_stem", language.iso_code()),
+                SimpleTokenizer
+                    .filter(RemoveLongFilter::limit(40))
+                    .filter(LowerCaser)
+                    .filter(Stemmer::new(language)),
+            );
+        }
         manager
     }
 }
@@ -0,0 +1,154 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! # fn main() {
+//! let tokenizer = RawTokenizer
+//!   .filter(UnicodeNormalizer::new(NormalizationForm::Nfkc));
+//!
+//! // The "ﬁ" ligature is decomposed, and the combining acute accent is composed.
+//! let mut stream = tokenizer.token_stream("ﬁance\u{301}");
+//! assert_eq!(stream.next().unwrap().text, "fiancé");
+//! assert!(stream.next().is_none());
+//! # }
+//! ```
+use super::{Token, TokenFilter, TokenStream};
+use std::mem;
+use unicode_normalization::UnicodeNormalization;
+
+/// Unicode normalization forms, see [Unicode Normalization Forms](https://unicode.org/reports/tr15/).
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Copy, Clone)]
+pub enum NormalizationForm {
+    /// Canonical decomposition, followed by canonical composition.
+    Nfc,
+    /// Canonical decomposition.
+    Nfd,
+    /// Compatibility decomposition, followed by canonical composition.
+    Nfkc,
+    /// Compatibility decomposition.
+    Nfkd,
+}
+
+/// Token filter normalizing the text of the tokens to a Unicode normalization form.
+///
+/// The same text can be encoded with different sequences of code points,
+/// e.g. `é` can either be a single code point or an `e` followed by a combining
+/// accent. Normalizing the tokens makes these sequences match each other.
+/// The `Nfkc` and `Nfkd` forms also replace the compatibility characters,
+/// like ligatures or full-width letters, by their usual equivalent.
+///
+/// Note that the `SimpleTokenizer` splits the text on the combining characters,
+/// which are not alphanumeric: texts which are not in a composed form should
+/// rather be normalized before the tokenization.
+#[derive(Clone)]
+pub struct UnicodeNormalizer {
+    form: NormalizationForm,
+}
+
+impl UnicodeNormalizer {
+    /// Creates a `UnicodeNormalizer` for the given normalization form.
+    pub fn new(form: NormalizationForm) -> UnicodeNormalizer {
+        UnicodeNormalizer { form }
+    }
+}
+
+impl<TailTokenStream> TokenFilter<TailTokenStream> for UnicodeNormalizer
+where
+    TailTokenStream: TokenStream,
+{
+    type ResultTokenStream = UnicodeNormalizerTokenStream<TailTokenStream>;
+
+    fn transform(&self, token_stream: TailTokenStream) -> Self::ResultTokenStream {
+        UnicodeNormalizerTokenStream {
+            form: self.form,
+            tail: token_stream,
+            buffer: String::with_capacity(100),
+        }
+    }
+}
+
+pub struct UnicodeNormalizerTokenStream<TailTokenStream> {
+    form: NormalizationForm,
+    tail: TailTokenStream,
+    buffer: String,
+}
+
+impl<TailTokenStream> TokenStream for UnicodeNormalizerTokenStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let text = &self.tail.token().text;
+        // ASCII text is left unchanged by all of the normalization forms.
+        if text.is_ascii() {
+            return true;
+        }
+        self.buffer.clear();
+        match self.form {
+            NormalizationForm::Nfc => self.buffer.extend(text.nfc()),
+            NormalizationForm::Nfd => self.buffer.extend(text.nfd()),
+            NormalizationForm::Nfkc => self.buffer.extend(text.nfkc()),
+            NormalizationForm::Nfkd => self.buffer.extend(text.nfkd()),
+        }
+        mem::swap(&mut self.tail.token_mut().text, &mut self.buffer);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NormalizationForm, UnicodeNormalizer};
+    use crate::tokenizer::{
+        AsciiFoldingFilter, LowerCaser, RawTokenizer, Token, TokenStream, Tokenizer,
+    };
+
+    fn normalize_helper(text: &str, form: NormalizationForm) -> Vec<String> {
+        let mut tokens = vec![];
+        RawTokenizer
+            .filter(UnicodeNormalizer::new(form))
+            .filter(LowerCaser)
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.text.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_unicode_normalizer() {
+        // "Crème" with a combining grave accent, and a full-width "Ｂ".
+        let text = "Cre\u{300}me Ｂrûlée";
+        assert_eq!(
+            normalize_helper(text, NormalizationForm::Nfc),
+            vec!["crème ｂrûlée"]
+        );
+        assert_eq!(
+            normalize_helper(text, NormalizationForm::Nfkc),
+            vec!["crème brûlée"]
+        );
+        assert_eq!(
+            normalize_helper("é", NormalizationForm::Nfd),
+            vec!["e\u{301}"]
+        );
+    }
+
+    #[test]
+    fn test_unicode_normalizer_before_ascii_folding() {
+        let mut tokens = vec![];
+        RawTokenizer
+            .filter(UnicodeNormalizer::new(NormalizationForm::Nfc))
+            .filter(AsciiFoldingFilter)
+            .token_stream("Cre\u{300}me")
+            .process(&mut |token: &Token| tokens.push(token.text.clone()));
+        assert_eq!(tokens, vec!["Creme"]);
+    }
+}
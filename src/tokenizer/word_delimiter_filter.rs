@@ -0,0 +1,316 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! # fn main() {
+//! let tokenizer = RawTokenizer
+//!   .filter(WordDelimiterFilter::new(false))
+//!   .filter(LowerCaser);
+//!
+//! let mut stream = tokenizer.token_stream("WiFiRouter2000");
+//! assert_eq!(stream.next().unwrap().text, "wi");
+//! assert_eq!(stream.next().unwrap().text, "fi");
+//! assert_eq!(stream.next().unwrap().text, "router");
+//! assert_eq!(stream.next().unwrap().text, "2000");
+//! assert!(stream.next().is_none());
+//! # }
+//! ```
+use super::{Token, TokenFilter, TokenStream};
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// `TokenFilter` splitting the tokens into their subwords, typically used
+/// to search code identifiers or product references.
+///
+/// The tokens are split:
+/// - on their non-alphanumeric characters, which are removed (`wi-fi` → `wi`, `fi`),
+/// - on the case transitions (`WiFi` → `Wi`, `Fi` and `HTMLParser` → `HTML`, `Parser`),
+/// - between their letters and digits (`router2000` → `router`, `2000`).
+///
+/// Each subword is emitted at its own position, so that the subwords can be
+/// searched with a phrase query. With `preserve_original`, the tokens that are split
+/// are also emitted as is, at the position of their first subword.
+///
+/// The filter is meant to be applied before the `LowerCaser`. The offsets of the subwords
+/// are within the offsets of their token, unless the text of the token was modified
+/// by a previous filter.
+#[derive(Clone)]
+pub struct WordDelimiterFilter {
+    preserve_original: bool,
+}
+
+impl WordDelimiterFilter {
+    /// Creates a `WordDelimiterFilter`.
+    ///
+    /// If `preserve_original` is true, the tokens that are split are emitted
+    /// along with their subwords.
+    pub fn new(preserve_original: bool) -> WordDelimiterFilter {
+        WordDelimiterFilter { preserve_original }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    // letters without case, e.g. in CJK scripts.
+    OtherLetter,
+    Delimiter,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_lowercase() {
+        CharClass::Lower
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_numeric() {
+        CharClass::Digit
+    } else if c.is_alphanumeric() {
+        CharClass::OtherLetter
+    } else {
+        CharClass::Delimiter
+    }
+}
+
+/// Returns true if a subword ends between `previous` and `current`,
+/// `next` being the class of the character following `current`.
+fn is_subword_boundary(previous: CharClass, current: CharClass, next: Option<CharClass>) -> bool {
+    match (previous, current) {
+        (CharClass::Digit, CharClass::Digit) => false,
+        (CharClass::Digit, _) | (_, CharClass::Digit) => true,
+        (CharClass::Lower, CharClass::Upper) | (CharClass::OtherLetter, CharClass::Upper) => true,
+        (CharClass::Upper, CharClass::Upper) => next == Some(CharClass::Lower),
+        _ => false,
+    }
+}
+
+/// Appends to `subwords` the byte ranges of the subwords of `text`.
+fn split_subwords(text: &str, subwords: &mut Vec<Range<usize>>) {
+    let chars: Vec<(usize, CharClass)> = text
+        .char_indices()
+        .map(|(offset, c)| (offset, char_class(c)))
+        .collect();
+    let mut subword_start: Option<usize> = None;
+    for (char_ord, &(offset, class)) in chars.iter().enumerate() {
+        if class == CharClass::Delimiter {
+            if let Some(start) = subword_start.take() {
+                subwords.push(start..offset);
+            }
+            continue;
+        }
+        if let Some(start) = subword_start {
+            let previous = chars[char_ord - 1].1;
+            let next = chars.get(char_ord + 1).map(|&(_, class)| class);
+            if is_subword_boundary(previous, class, next) {
+                subwords.push(start..offset);
+                subword_start = Some(offset);
+            }
+        } else {
+            subword_start = Some(offset);
+        }
+    }
+    if let Some(start) = subword_start {
+        subwords.push(start..text.len());
+    }
+}
+
+impl<TailTokenStream> TokenFilter<TailTokenStream> for WordDelimiterFilter
+where
+    TailTokenStream: TokenStream,
+{
+    type ResultTokenStream = WordDelimiterFilterStream<TailTokenStream>;
+
+    fn transform(&self, token_stream: TailTokenStream) -> Self::ResultTokenStream {
+        WordDelimiterFilterStream {
+            preserve_original: self.preserve_original,
+            tail: token_stream,
+            subwords: Vec::new(),
+            pending: VecDeque::new(),
+            position_shift: 0,
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct WordDelimiterFilterStream<TailTokenStream> {
+    preserve_original: bool,
+    tail: TailTokenStream,
+    subwords: Vec<Range<usize>>,
+    pending: VecDeque<Token>,
+    // number of positions added by the subwords of the previous tokens.
+    position_shift: usize,
+    token: Token,
+}
+
+impl<TailTokenStream> WordDelimiterFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn split(&mut self) {
+        let token = self.tail.token();
+        let position = token.position.wrapping_add(self.position_shift);
+        self.subwords.clear();
+        split_subwords(&token.text, &mut self.subwords);
+        let is_unchanged = match self.subwords.as_slice() {
+            [subword] => subword.len() == token.text.len(),
+            _ => false,
+        };
+        if is_unchanged || (self.subwords.is_empty() && self.preserve_original) {
+            let mut token = token.clone();
+            token.position = position;
+            self.pending.push_back(token);
+            return;
+        }
+        if self.preserve_original {
+            let mut token = token.clone();
+            token.position = position;
+            token.position_length = self.subwords.len();
+            self.pending.push_back(token);
+        }
+        // The offsets of the subwords can only be computed if the text of the token
+        // is the text it was generated from.
+        let has_original_text = token.offset_to - token.offset_from == token.text.len();
+        for (subword_ord, subword) in self.subwords.iter().enumerate() {
+            let (offset_from, offset_to) = if has_original_text {
+                (
+                    token.offset_from + subword.start,
+                    token.offset_from + subword.end,
+                )
+            } else {
+                (token.offset_from, token.offset_to)
+            };
+            self.pending.push_back(Token {
+                offset_from,
+                offset_to,
+                position: position.wrapping_add(subword_ord),
+                text: token.text[subword.clone()].to_string(),
+                position_length: 1,
+                payload: token.payload,
+            });
+        }
+        self.position_shift += self.subwords.len().saturating_sub(1);
+    }
+}
+
+impl<TailTokenStream> TokenStream for WordDelimiterFilterStream<TailTokenStream>
+where
+    TailTokenStream: TokenStream,
+{
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn advance(&mut self) -> bool {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                self.token = token;
+                return true;
+            }
+            if !self.tail.advance() {
+                return false;
+            }
+            self.split();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WordDelimiterFilter;
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        LowerCaser, RawTokenizer, SimpleTokenizer, Token, TokenStream, Tokenizer,
+    };
+    use crate::Index;
+
+    fn word_delimiter_helper(text: &str, preserve_original: bool) -> Vec<Token> {
+        let mut tokens = vec![];
+        SimpleTokenizer
+            .filter(WordDelimiterFilter::new(preserve_original))
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_word_delimiter_filter() {
+        let tokens = word_delimiter_helper("the HTMLParser of iPhone11 works", false);
+        assert_eq!(tokens.len(), 8);
+        assert_token(&tokens[0], 0, "the", 0, 3);
+        assert_token(&tokens[1], 1, "HTML", 4, 8);
+        assert_token(&tokens[2], 2, "Parser", 8, 14);
+        assert_token(&tokens[3], 3, "of", 15, 17);
+        assert_token(&tokens[4], 4, "i", 18, 19);
+        assert_token(&tokens[5], 5, "Phone", 19, 24);
+        assert_token(&tokens[6], 6, "11", 24, 26);
+        assert_token(&tokens[7], 7, "works", 27, 32);
+    }
+
+    #[test]
+    fn test_word_delimiter_filter_preserve_original() {
+        let tokens = word_delimiter_helper("WiFi router", true);
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "WiFi", 0, 4);
+        assert_eq!(tokens[0].position_length, 2);
+        assert_token(&tokens[1], 0, "Wi", 0, 2);
+        assert_token(&tokens[2], 1, "Fi", 2, 4);
+        assert_token(&tokens[3], 2, "router", 5, 11);
+
+        let mut tokens = vec![];
+        RawTokenizer
+            .filter(WordDelimiterFilter::new(false))
+            .token_stream("--")
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        assert!(tokens.is_empty());
+    }
+
+    #[test]
+    fn test_word_delimiter_filter_sku_search() {
+        let mut schema_builder = Schema::builder();
+        let sku_field = schema_builder.add_text_field(
+            "sku",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("sku")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "sku",
+            RawTokenizer
+                .filter(WordDelimiterFilter::new(true))
+                .filter(LowerCaser),
+        );
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(sku_field => "WiFiRouter2000"));
+        index_writer.add_document(doc!(sku_field => "wifi-router-3000"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        // The original tokens would be stacked on the queried phrases.
+        index.tokenizers().register(
+            "sku",
+            RawTokenizer
+                .filter(WordDelimiterFilter::new(false))
+                .filter(LowerCaser),
+        );
+        let query_parser = QueryParser::for_index(&index, vec![sku_field]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("router"), 2);
+        assert_eq!(count("\"wi-fi router 2000\""), 1);
+        assert_eq!(count("\"router 3000\""), 1);
+        assert_eq!(count("WiFiRouter2000"), 1);
+    }
+}